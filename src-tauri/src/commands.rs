@@ -1,7 +1,8 @@
 use crate::session::SessionManager;
 use crate::types::SessionConfig;
 use crate::utils::{validate_port, is_common_port};
-use crate::parser::{ProtocolParser, get_parser_registry, Parser, ProtocolRepository, ProtocolMetadata, ProtocolImportRequest, ProtocolExportOptions, FactorTranslator, FactorDefinition, ParsedFactor, FactorSummary};
+use crate::parser::{ProtocolParser, get_parser_registry, Parser, ProtocolRepository, ProtocolMetadata, ProtocolImportRequest, ProtocolExportOptions, FactorTranslator, FactorDefinition, ParsedFactor, FactorSummary, EnvelopeContext, ExportFormat, ParseResult, RuleLinter, ValidationReport, ProtocolRevision, RuleDiff, BundleImportAction, BundleImportResult, ConflictStrategy, SessionParserBinding, Hj212Packet};
+use crate::simulator::{Hj212Device, Hj212DeviceConfig, Hj212PlatformConfig};
 use tauri::{State, AppHandle, Theme, Manager};
 use tauri::window::Color;
 use serde::{Deserialize, Serialize};
@@ -128,14 +129,21 @@ pub async fn connect_session(
     config: SessionConfig,
     session_manager: State<'_, SessionManager>,
 ) -> Result<bool, String> {
-    // Validate port before creating session
-    if let Err(port_error) = validate_port(config.port) {
-        return Err(format!("Invalid port configuration: {}", port_error));
-    }
+    // Validate port before creating session; serial, Modbus RTU and serial Modbus ASCII sessions have no network port
+    let has_port = match config.protocol.to_lowercase().as_str() {
+        "serial" | "modbus-rtu" => false,
+        "modbus-ascii" => config.serial.is_none(),
+        _ => true,
+    };
+    if has_port {
+        if let Err(port_error) = validate_port(config.port) {
+            return Err(format!("Invalid port configuration: {}", port_error));
+        }
 
-    // Warn about common ports
-    if let Some(service) = is_common_port(config.port) {
-        eprintln!("Warning: Port {} is commonly used by {} service", config.port, service);
+        // Warn about common ports
+        if let Some(service) = is_common_port(config.port) {
+            eprintln!("Warning: Port {} is commonly used by {} service", config.port, service);
+        }
     }
 
     // Create session if it doesn't exist, or update existing session with new config
@@ -204,6 +212,98 @@ pub async fn disconnect_session(
     }
 }
 
+/// Bind protocol parsers to a session's received data
+#[tauri::command]
+pub fn set_session_parser_binding(
+    session_id: String,
+    binding: SessionParserBinding,
+    session_manager: State<'_, SessionManager>,
+) -> Result<(), String> {
+    session_manager.set_parser_binding(&session_id, binding)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the protocol parsers bound to a session
+#[tauri::command]
+pub fn get_session_parser_binding(
+    session_id: String,
+    session_manager: State<'_, SessionManager>,
+) -> Result<SessionParserBinding, String> {
+    session_manager.get_parser_binding(&session_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Start an HJ212 platform simulator on a TCP server session
+#[tauri::command]
+pub async fn start_hj212_platform(
+    session_id: String,
+    config: Hj212PlatformConfig,
+    app_handle: AppHandle,
+    session_manager: State<'_, SessionManager>,
+) -> Result<(), String> {
+    crate::simulator::start_hj212_platform(app_handle, &session_manager, &session_id, config)
+        .map_err(|e| e.to_string())
+}
+
+/// Stop the HJ212 platform simulator of a session
+#[tauri::command]
+pub fn stop_hj212_platform(session_id: String) -> Result<bool, String> {
+    Ok(crate::simulator::stop_hj212_platform(&session_id))
+}
+
+/// Get the devices connected to a session's HJ212 platform simulator
+#[tauri::command]
+pub fn get_hj212_platform_devices(session_id: String) -> Result<Vec<Hj212Device>, String> {
+    crate::simulator::hj212_platform_devices(&session_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Start an HJ212 station simulator on a TCP client session
+#[tauri::command]
+pub async fn start_hj212_device(
+    session_id: String,
+    config: Hj212DeviceConfig,
+    app_handle: AppHandle,
+    session_manager: State<'_, SessionManager>,
+) -> Result<(), String> {
+    crate::simulator::start_hj212_device(app_handle, &session_manager, &session_id, config)
+        .map_err(|e| e.to_string())
+}
+
+/// Stop the HJ212 station simulator of a session
+#[tauri::command]
+pub fn stop_hj212_device(session_id: String) -> Result<bool, String> {
+    Ok(crate::simulator::stop_hj212_device(&session_id))
+}
+
+/// Get the factor alarm rules by factor code
+#[tauri::command]
+pub fn get_factor_alarm_rules() -> Result<std::collections::HashMap<String, AlarmRule>, String> {
+    Ok(get_alarm_engine().lock().unwrap().rules().clone())
+}
+
+/// Set the alarm rule of a factor code
+#[tauri::command]
+pub fn set_factor_alarm_rule(factor_code: String, rule: AlarmRule) -> Result<(), String> {
+    get_alarm_engine().lock().unwrap()
+        .set_rule(&factor_code, rule)
+        .map_err(|e| e.to_string())
+}
+
+/// Remove the alarm rule of a factor code, clearing its raised alarms
+#[tauri::command]
+pub fn remove_factor_alarm_rule(factor_code: String, app_handle: AppHandle) -> Result<(), String> {
+    let events = get_alarm_engine().lock().unwrap().remove_rule(&factor_code, chrono::Utc::now());
+    crate::network::notify_factor_alarms(&app_handle, events);
+    Ok(())
+}
+
+/// Get the factor alarms currently raised
+#[tauri::command]
+pub fn get_active_factor_alarms() -> Result<Vec<ActiveAlarm>, String> {
+    Ok(get_alarm_engine().lock().unwrap().active_alarms())
+}
+
 /// Pause auto-reconnect for a session
 #[tauri::command]
 pub async fn pause_auto_reconnect(
@@ -357,6 +457,7 @@ pub async fn publish_mqtt_message(
 
 /// Modbus request/response data structure
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ModbusRequest {
     pub function_code: u8,
     pub address: u16,
@@ -366,7 +467,8 @@ pub struct ModbusRequest {
     pub coil_values: Option<Vec<bool>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ModbusResponse {
     pub success: bool,
     pub data: Option<Vec<u16>>,
@@ -374,101 +476,174 @@ pub struct ModbusResponse {
     pub error: Option<String>,
 }
 
+impl ModbusResponse {
+    fn done() -> Self {
+        Self { success: true, ..Default::default() }
+    }
+
+    fn registers(data: Vec<u16>) -> Self {
+        Self { success: true, data: Some(data), ..Default::default() }
+    }
+
+    fn read(data: crate::network::modbus_poll::AreaData) -> Self {
+        match data {
+            crate::network::modbus_poll::AreaData::Bits(bits) => {
+                Self { success: true, coil_data: Some(bits), ..Default::default() }
+            }
+            crate::network::modbus_poll::AreaData::Registers(registers) => Self::registers(registers),
+        }
+    }
+}
+
+async fn modbus_read(
+    session_id: &str,
+    area: crate::network::modbus_slave::ModbusArea,
+    address: u16,
+    quantity: u16,
+) -> Result<ModbusResponse, String> {
+    crate::network::modbus::read(session_id, area, address, quantity)
+        .await
+        .map(ModbusResponse::read)
+        .map_err(|e| e.to_string())
+}
+
 /// Read Modbus coils (function code 0x01)
-/// TODO: This command requires architectural changes to access connections directly
-/// For now, use the standard send/receive pattern with encoded Modbus frames
 #[tauri::command]
-pub async fn modbus_read_coils(
-    _session_id: String,
-    _address: u16,
-    _quantity: u16,
-    _session_manager: State<'_, SessionManager>,
-) -> Result<ModbusResponse, String> {
-    Err("Modbus commands not yet implemented - use standard send/receive".to_string())
+pub async fn modbus_read_coils(session_id: String, address: u16, quantity: u16) -> Result<ModbusResponse, String> {
+    modbus_read(&session_id, crate::network::modbus_slave::ModbusArea::Coils, address, quantity).await
 }
 
 /// Read Modbus discrete inputs (function code 0x02)
-/// TODO: This command requires architectural changes to access connections directly
 #[tauri::command]
-pub async fn modbus_read_discrete_inputs(
-    _session_id: String,
-    _address: u16,
-    _quantity: u16,
-    _session_manager: State<'_, SessionManager>,
-) -> Result<ModbusResponse, String> {
-    Err("Modbus commands not yet implemented - use standard send/receive".to_string())
+pub async fn modbus_read_discrete_inputs(session_id: String, address: u16, quantity: u16) -> Result<ModbusResponse, String> {
+    modbus_read(&session_id, crate::network::modbus_slave::ModbusArea::DiscreteInputs, address, quantity).await
 }
 
 /// Read Modbus holding registers (function code 0x03)
-/// TODO: This command requires architectural changes to access connections directly
 #[tauri::command]
-pub async fn modbus_read_holding_registers(
-    _session_id: String,
-    _address: u16,
-    _quantity: u16,
-    _session_manager: State<'_, SessionManager>,
-) -> Result<ModbusResponse, String> {
-    Err("Modbus commands not yet implemented - use standard send/receive".to_string())
+pub async fn modbus_read_holding_registers(session_id: String, address: u16, quantity: u16) -> Result<ModbusResponse, String> {
+    modbus_read(&session_id, crate::network::modbus_slave::ModbusArea::HoldingRegisters, address, quantity).await
 }
 
 /// Read Modbus input registers (function code 0x04)
-/// TODO: This command requires architectural changes to access connections directly
 #[tauri::command]
-pub async fn modbus_read_input_registers(
-    _session_id: String,
-    _address: u16,
-    _quantity: u16,
-    _session_manager: State<'_, SessionManager>,
-) -> Result<ModbusResponse, String> {
-    Err("Modbus commands not yet implemented - use standard send/receive".to_string())
+pub async fn modbus_read_input_registers(session_id: String, address: u16, quantity: u16) -> Result<ModbusResponse, String> {
+    modbus_read(&session_id, crate::network::modbus_slave::ModbusArea::InputRegisters, address, quantity).await
 }
 
 /// Write Modbus single coil (function code 0x05)
-/// TODO: This command requires architectural changes to access connections directly
 #[tauri::command]
-pub async fn modbus_write_single_coil(
-    _session_id: String,
-    _address: u16,
-    _value: bool,
-    _session_manager: State<'_, SessionManager>,
-) -> Result<ModbusResponse, String> {
-    Err("Modbus commands not yet implemented - use standard send/receive".to_string())
+pub async fn modbus_write_single_coil(session_id: String, address: u16, value: bool) -> Result<ModbusResponse, String> {
+    crate::network::modbus::write_single_coil(&session_id, address, value)
+        .await
+        .map(|_| ModbusResponse::done())
+        .map_err(|e| e.to_string())
 }
 
 /// Write Modbus single register (function code 0x06)
-/// TODO: This command requires architectural changes to access connections directly
 #[tauri::command]
-pub async fn modbus_write_single_register(
-    _session_id: String,
-    _address: u16,
-    _value: u16,
-    _session_manager: State<'_, SessionManager>,
-) -> Result<ModbusResponse, String> {
-    Err("Modbus commands not yet implemented - use standard send/receive".to_string())
+pub async fn modbus_write_single_register(session_id: String, address: u16, value: u16) -> Result<ModbusResponse, String> {
+    crate::network::modbus::write_single_register(&session_id, address, value)
+        .await
+        .map(|_| ModbusResponse::done())
+        .map_err(|e| e.to_string())
 }
 
 /// Write Modbus multiple coils (function code 0x0F)
-/// TODO: This command requires architectural changes to access connections directly
 #[tauri::command]
-pub async fn modbus_write_multiple_coils(
-    _session_id: String,
-    _address: u16,
-    _values: Vec<bool>,
-    _session_manager: State<'_, SessionManager>,
-) -> Result<ModbusResponse, String> {
-    Err("Modbus commands not yet implemented - use standard send/receive".to_string())
+pub async fn modbus_write_multiple_coils(session_id: String, address: u16, values: Vec<bool>) -> Result<ModbusResponse, String> {
+    crate::network::modbus::write_multiple_coils(&session_id, address, &values)
+        .await
+        .map(|_| ModbusResponse::done())
+        .map_err(|e| e.to_string())
 }
 
 /// Write Modbus multiple registers (function code 0x10)
-/// TODO: This command requires architectural changes to access connections directly
 #[tauri::command]
-pub async fn modbus_write_multiple_registers(
-    _session_id: String,
-    _address: u16,
-    _values: Vec<u16>,
-    _session_manager: State<'_, SessionManager>,
+pub async fn modbus_write_multiple_registers(session_id: String, address: u16, values: Vec<u16>) -> Result<ModbusResponse, String> {
+    crate::network::modbus::write_multiple_registers(&session_id, address, &values)
+        .await
+        .map(|_| ModbusResponse::done())
+        .map_err(|e| e.to_string())
+}
+
+/// Mask write a Modbus register (function code 0x16)
+#[tauri::command]
+pub async fn modbus_mask_write_register(
+    session_id: String,
+    address: u16,
+    and_mask: u16,
+    or_mask: u16,
+) -> Result<ModbusResponse, String> {
+    crate::network::modbus::mask_write_register(&session_id, address, and_mask, or_mask)
+        .await
+        .map(|_| ModbusResponse::done())
+        .map_err(|e| e.to_string())
+}
+
+/// Write then read Modbus holding registers in one request (function code 0x17)
+#[tauri::command]
+pub async fn modbus_read_write_multiple_registers(
+    session_id: String,
+    read_address: u16,
+    read_quantity: u16,
+    write_address: u16,
+    values: Vec<u16>,
 ) -> Result<ModbusResponse, String> {
-    Err("Modbus commands not yet implemented - use standard send/receive".to_string())
+    crate::network::modbus::read_write_multiple_registers(&session_id, read_address, read_quantity, write_address, &values)
+        .await
+        .map(ModbusResponse::registers)
+        .map_err(|e| e.to_string())
+}
+
+/// Read a Modbus FIFO queue (function code 0x18)
+#[tauri::command]
+pub async fn modbus_read_fifo_queue(session_id: String, address: u16) -> Result<ModbusResponse, String> {
+    crate::network::modbus::read_fifo_queue(&session_id, address)
+        .await
+        .map(ModbusResponse::registers)
+        .map_err(|e| e.to_string())
+}
+
+/// Read Modbus exception status (function code 0x07)
+#[tauri::command]
+pub async fn modbus_read_exception_status(session_id: String) -> Result<u8, String> {
+    crate::network::modbus::read_exception_status(&session_id).await.map_err(|e| e.to_string())
+}
+
+/// Run a Modbus diagnostics sub-function (function code 0x08)
+#[tauri::command]
+pub async fn modbus_diagnostics(
+    session_id: String,
+    sub_function: u16,
+    data: Option<Vec<u16>>,
+) -> Result<crate::network::modbus_functions::DiagnosticsResponse, String> {
+    crate::network::modbus::diagnostics(&session_id, sub_function, data.as_deref().unwrap_or(&[0]))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Read Modbus device identification (function code 0x2B / MEI 0x0E)
+#[tauri::command]
+pub async fn modbus_read_device_identification(
+    session_id: String,
+    read_code: Option<u8>,
+    object_id: Option<u8>,
+) -> Result<crate::network::modbus_functions::DeviceIdentification, String> {
+    crate::network::modbus::read_device_identification(&session_id, read_code.unwrap_or(1), object_id.unwrap_or(0))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Send a Modbus request with any function code and hex request data,
+/// returning the hex response data that follows the function code
+#[tauri::command]
+pub async fn modbus_custom_request(session_id: String, function_code: u8, data: String) -> Result<String, String> {
+    let data = hex::decode(data.replace([' ', ':'], "")).map_err(|e| format!("Invalid hex data: {}", e))?;
+    crate::network::modbus::custom_request(&session_id, function_code, &data)
+        .await
+        .map(hex::encode)
+        .map_err(|e| e.to_string())
 }
 
 // ============================================================================
@@ -615,6 +790,39 @@ pub async fn parse_data_auto(data: Vec<u8>) -> Result<String, String> {
     }
 }
 
+/// Parse data with its transport envelope (topic, peer, session)
+#[tauri::command]
+pub async fn parse_data_with_envelope(
+    data: Vec<u8>,
+    envelope: EnvelopeContext,
+    parser_id: Option<String>,
+) -> Result<String, String> {
+    let registry = get_parser_registry();
+    let result = {
+        let guard = registry.read().unwrap();
+        guard.parse_with_envelope(parser_id.as_deref(), &data, &envelope)
+    };
+
+    match result {
+        Ok(result) => {
+            Ok(serde_json::to_string(&result).unwrap_or_default())
+        }
+        Err(e) => Err(format!("Failed to parse data: {}", e)),
+    }
+}
+
+/// Export parse results as Influx line protocol, flat JSON or CSV
+#[tauri::command]
+pub async fn export_parse_results(
+    results: Vec<ParseResult>,
+    format: ExportFormat,
+) -> Result<String, String> {
+    let registry = get_parser_registry();
+    let guard = registry.read().unwrap();
+    guard.export_results(&results, format)
+        .map_err(|e| format!("Failed to export parse results: {}", e))
+}
+
 /// Validate parsed data
 #[tauri::command]
 pub async fn validate_parsed_data(
@@ -674,6 +882,8 @@ pub async fn import_protocol(
     custom_category: Option<String>,
     tags: Vec<String>,
     enabled: bool,
+    author: Option<String>,
+    note: Option<String>,
 ) -> Result<String, String> {
     let registry = get_parser_registry();
     let mut registry_guard = registry.write().unwrap();
@@ -685,6 +895,8 @@ pub async fn import_protocol(
             custom_category,
             tags,
             enabled,
+            author,
+            note,
         };
 
         match repository.import_protocol(request) {
@@ -744,6 +956,27 @@ pub async fn list_protocols() -> Result<Vec<ProtocolMetadata>, String> {
     }
 }
 
+/// Lint protocol rule content and return fix suggestions
+#[tauri::command]
+pub async fn lint_protocol_rule(rule_content: String) -> Result<ValidationReport, String> {
+    RuleLinter::lint_str(&rule_content)
+        .map_err(|e| format!("Failed to lint protocol rule: {}", e))
+}
+
+/// Lint a stored protocol
+#[tauri::command]
+pub async fn lint_protocol(protocol_id: String) -> Result<ValidationReport, String> {
+    let registry = get_parser_registry();
+    let mut registry_guard = registry.write().unwrap();
+
+    if let Some(repository) = registry_guard.repository_mut() {
+        repository.lint_protocol(&protocol_id)
+            .map_err(|e| format!("Failed to lint protocol: {}", e))
+    } else {
+        Err("Protocol repository not available".to_string())
+    }
+}
+
 /// Get protocol content by ID
 #[tauri::command]
 pub async fn get_protocol_content(protocol_id: String) -> Result<String, String> {
@@ -779,6 +1012,106 @@ pub async fn list_enabled_protocols() -> Result<Vec<ProtocolMetadata>, String> {
     }
 }
 
+/// Update a protocol's rule content, recording a new revision
+#[tauri::command]
+pub async fn update_protocol(
+    protocol_id: String,
+    content: String,
+    author: Option<String>,
+    note: Option<String>,
+) -> Result<ProtocolRevision, String> {
+    let registry = get_parser_registry();
+    let mut registry_guard = registry.write().unwrap();
+
+    let repository = registry_guard.repository_mut()
+        .ok_or_else(|| "Protocol repository not available".to_string())?;
+    let revision = repository.update_protocol(&protocol_id, &content, author.as_deref(), note.as_deref().unwrap_or(""))
+        .map_err(|e| format!("Failed to update protocol: {}", e))?;
+    let enabled = repository.get_protocol_metadata(&protocol_id)
+        .map(|metadata| metadata.enabled)
+        .unwrap_or(false);
+
+    if enabled {
+        registry_guard.reload_protocol(&protocol_id)
+            .map_err(|e| format!("Protocol updated but failed to reload parser: {}", e))?;
+    }
+
+    Ok(revision)
+}
+
+/// List revisions of a protocol
+#[tauri::command]
+pub async fn list_protocol_revisions(protocol_id: String) -> Result<Vec<ProtocolRevision>, String> {
+    let registry = get_parser_registry();
+    let registry_guard = registry.read().unwrap();
+
+    if let Some(repository) = registry_guard.repository() {
+        repository.list_revisions(&protocol_id)
+            .map_err(|e| format!("Failed to list revisions: {}", e))
+    } else {
+        Err("Protocol repository not available".to_string())
+    }
+}
+
+/// Get the rule content of a protocol revision
+#[tauri::command]
+pub async fn get_protocol_revision_content(protocol_id: String, revision: u32) -> Result<String, String> {
+    let registry = get_parser_registry();
+    let registry_guard = registry.read().unwrap();
+
+    if let Some(repository) = registry_guard.repository() {
+        repository.get_revision_content(&protocol_id, revision)
+            .map_err(|e| format!("Failed to read revision: {}", e))
+    } else {
+        Err("Protocol repository not available".to_string())
+    }
+}
+
+/// Compare two protocol revisions field by field
+#[tauri::command]
+pub async fn diff_protocol_revisions(
+    protocol_id: String,
+    from_revision: u32,
+    to_revision: u32,
+) -> Result<RuleDiff, String> {
+    let registry = get_parser_registry();
+    let mut registry_guard = registry.write().unwrap();
+
+    if let Some(repository) = registry_guard.repository_mut() {
+        repository.diff_revisions(&protocol_id, from_revision, to_revision)
+            .map_err(|e| format!("Failed to diff revisions: {}", e))
+    } else {
+        Err("Protocol repository not available".to_string())
+    }
+}
+
+/// Roll a protocol back to an earlier revision and reload its parser
+#[tauri::command]
+pub async fn rollback_protocol(
+    protocol_id: String,
+    revision: u32,
+    author: Option<String>,
+    note: Option<String>,
+) -> Result<ProtocolRevision, String> {
+    let registry = get_parser_registry();
+    let mut registry_guard = registry.write().unwrap();
+
+    let repository = registry_guard.repository_mut()
+        .ok_or_else(|| "Protocol repository not available".to_string())?;
+    let new_revision = repository.rollback_protocol(&protocol_id, revision, author.as_deref(), note.as_deref())
+        .map_err(|e| format!("Failed to roll back protocol: {}", e))?;
+    let enabled = repository.get_protocol_metadata(&protocol_id)
+        .map(|metadata| metadata.enabled)
+        .unwrap_or(false);
+
+    if enabled {
+        registry_guard.reload_protocol(&protocol_id)
+            .map_err(|e| format!("Protocol rolled back but failed to reload parser: {}", e))?;
+    }
+
+    Ok(new_revision)
+}
+
 /// Get protocol metadata by ID
 #[tauri::command]
 pub async fn get_protocol_metadata(protocol_id: String) -> Result<ProtocolMetadata, String> {
@@ -845,6 +1178,118 @@ pub async fn set_protocol_enabled(
     }
 }
 
+/// Import a lookup catalog (CSV) into the repository
+#[tauri::command]
+pub async fn import_protocol_catalog(
+    filename: String,
+    content: String,
+) -> Result<String, String> {
+    let registry = get_parser_registry();
+    let mut registry_guard = registry.write().unwrap();
+
+    let (path, enabled_ids) = if let Some(repository) = registry_guard.repository_mut() {
+        let path = repository.import_catalog(&filename, &content)
+            .map_err(|e| format!("Failed to import catalog: {}", e))?;
+        let enabled_ids: Vec<String> = repository.list_enabled_protocols()
+            .into_iter()
+            .map(|metadata| metadata.id.clone())
+            .collect();
+        (path, enabled_ids)
+    } else {
+        return Err("Protocol repository not available".to_string());
+    };
+
+    // Reload enabled parsers so rules referencing the catalog pick it up
+    for protocol_id in enabled_ids {
+        if let Err(e) = registry_guard.reload_protocol(&protocol_id) {
+            log::warn!("Failed to reload protocol {} after catalog import: {}", protocol_id, e);
+        }
+    }
+
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// List lookup catalogs stored in the repository
+#[tauri::command]
+pub async fn list_protocol_catalogs() -> Result<Vec<String>, String> {
+    let registry = get_parser_registry();
+    let registry_guard = registry.read().unwrap();
+
+    if let Some(repository) = registry_guard.repository() {
+        repository.list_catalogs()
+            .map_err(|e| format!("Failed to list catalogs: {}", e))
+    } else {
+        Err("Protocol repository not available".to_string())
+    }
+}
+
+/// Export a protocol with its catalogs, schemas and samples as a bundle
+#[tauri::command]
+pub async fn export_protocol_bundle(
+    protocol_id: String,
+    export_path: String,
+) -> Result<String, String> {
+    let registry = get_parser_registry();
+    let mut registry_guard = registry.write().unwrap();
+
+    if let Some(repository) = registry_guard.repository_mut() {
+        repository.export_bundle(&protocol_id, &PathBuf::from(export_path))
+            .map(|path| path.to_string_lossy().to_string())
+            .map_err(|e| format!("Failed to export protocol bundle: {}", e))
+    } else {
+        Err("Protocol repository not available".to_string())
+    }
+}
+
+/// Import a protocol bundle and load its parser when enabled
+#[tauri::command]
+pub async fn import_protocol_bundle(
+    bundle_path: String,
+    on_conflict: ConflictStrategy,
+) -> Result<BundleImportResult, String> {
+    let data = std::fs::read(&bundle_path)
+        .map_err(|e| format!("Failed to read bundle file: {}", e))?;
+
+    let registry = get_parser_registry();
+    let mut registry_guard = registry.write().unwrap();
+
+    let repository = registry_guard.repository_mut()
+        .ok_or_else(|| "Protocol repository not available".to_string())?;
+    let result = repository.import_bundle(&data, on_conflict)
+        .map_err(|e| format!("Failed to import protocol bundle: {}", e))?;
+
+    match result.action {
+        BundleImportAction::Skipped => {}
+        _ if result.enabled => {
+            registry_guard.reload_protocol(&result.protocol_id)
+                .map_err(|e| format!("Bundle imported but failed to load parser: {}", e))?;
+        }
+        // A replaced protocol that is now disabled must not keep its old parser
+        _ => registry_guard.remove_parser(&result.protocol_id),
+    }
+
+    Ok(result)
+}
+
+/// Attach a sample capture to a protocol
+#[tauri::command]
+pub async fn add_protocol_sample(
+    protocol_id: String,
+    name: String,
+    data: Vec<u8>,
+) -> Result<String, String> {
+    let registry = get_parser_registry();
+    let registry_guard = registry.read().unwrap();
+
+    if let Some(repository) = registry_guard.repository() {
+        repository.add_sample(&protocol_id, &name, &data)
+            .map(|path| path.to_string_lossy().to_string())
+            .map_err(|e| format!("Failed to add protocol sample: {}", e))
+    } else {
+        Err("Protocol repository not available".to_string())
+    }
+}
+
 /// Set the application theme for window chrome and system menu integration
 #[tauri::command]
 pub async fn set_window_theme(
@@ -998,7 +1443,7 @@ pub async fn init_log_manager(app_handle: tauri::AppHandle) -> Result<(), String
 }
 
 /// Get the log manager instance
-async fn get_log_manager() -> Result<Arc<RwLock<LogManager>>, String> {
+pub(crate) async fn get_log_manager() -> Result<Arc<RwLock<LogManager>>, String> {
     LOG_MANAGER.get()
         .ok_or_else(|| "Log manager not initialized".to_string())
         .map(|manager| manager.clone())
@@ -1297,22 +1742,9 @@ pub async fn parse_factor_codes(
     let rule = repository.load_protocol_rule(&protocol_id)
         .map_err(|e| format!("Failed to load protocol rule: {}", e))?;
 
-    // Extract factor definitions from the rule
-    let mut factor_translator = FactorTranslator::new();
-
-    if let Some(factor_codes) = rule.factor_codes {
-        let mut factor_definitions = std::collections::HashMap::new();
-
-        for (code, definition_value) in factor_codes {
-            // Convert serde_yaml::Value to FactorDefinition
-            let definition: FactorDefinition = serde_yaml::from_value(definition_value)
-                .map_err(|e| format!("Failed to parse factor definition for {}: {}", code, e))?;
-
-            factor_definitions.insert(code, definition);
-        }
-
-        factor_translator.load_factor_definitions(factor_definitions);
-    }
+    // Load factor definitions from the rule
+    let factor_translator = FactorTranslator::from_rule(&rule)
+        .map_err(|e| e.to_string())?;
 
     // Parse the factor string
     factor_translator.parse_factor_string(&factor_string)
@@ -1374,49 +1806,76 @@ pub async fn parse_hj212_message(
     protocol_id: String,
     message_data: String,
 ) -> Result<HJ212ParseResult, String> {
-    // Parse the message using the protocol
-    let parse_result = {
-        let registry = get_parser_registry();
-        let mut registry = registry.write().map_err(|e| format!("Failed to acquire registry lock: {}", e))?;
-
-        // Get the protocol repository
-        let repository = registry.repository_mut()
-            .ok_or_else(|| "Protocol repository not initialized".to_string())?;
-
-        // Create protocol parser
-        let parser = repository.create_protocol_parser(&protocol_id)
-            .map_err(|e| format!("Failed to create protocol parser: {}", e))?;
-
-        // Parse the message
-        parser.parse(message_data.as_bytes())
-            .map_err(|e| format!("Failed to parse message: {}", e))?
+    // Decode with the built-in engine, falling back to the protocol rule
+    let packet = Hj212Packet::decode(message_data.as_bytes()).ok();
+    let parse_result = match &packet {
+        Some(packet) => packet.to_parse_result(message_data.as_bytes()),
+        None => {
+            let registry = get_parser_registry();
+            let mut registry = registry.write().map_err(|e| format!("Failed to acquire registry lock: {}", e))?;
+
+            // Get the protocol repository
+            let repository = registry.repository_mut()
+                .ok_or_else(|| "Protocol repository not initialized".to_string())?;
+
+            // Create protocol parser
+            let parser = repository.create_protocol_parser(&protocol_id)
+                .map_err(|e| format!("Failed to create protocol parser: {}", e))?;
+
+            // Parse the message
+            parser.parse(message_data.as_bytes())
+                .map_err(|e| format!("Failed to parse message: {}", e))?
+        }
     };
 
-    // Extract PolId field if present
+    // Translate the decoded factor records, or the PolId list when the
+    // packet carries no values
     let mut parsed_factors = Vec::new();
-    let mut factor_summary = None;
-
-    if let Some(cp_field) = parse_result.fields.fields.get("cp") {
-        let cp_value = cp_field.value.as_string();
-        // Look for PolId in CP field
-        if let Some(polid_start) = cp_value.find("PolId=") {
-            let polid_part = &cp_value[polid_start + 6..];
-            let polid_end = polid_part.find(';').unwrap_or(polid_part.len());
-            let polid_value = &polid_part[..polid_end];
-
-            // Parse factors
-            parsed_factors = parse_factor_codes(protocol_id.clone(), polid_value.to_string()).await?;
-
-            // Get summary
-            let translator = FactorTranslator::new();
-            factor_summary = Some(translator.get_factor_summary(&parsed_factors));
+    match &packet {
+        Some(packet) if !packet.cp.factors.is_empty() => {
+            let rule = {
+                let registry = get_parser_registry();
+                let mut registry = registry.write().map_err(|e| format!("Failed to acquire registry lock: {}", e))?;
+                let repository = registry.repository_mut()
+                    .ok_or_else(|| "Protocol repository not initialized".to_string())?;
+                repository.load_protocol_rule(&protocol_id)
+                    .map_err(|e| format!("Failed to load protocol rule: {}", e))?
+            };
+            let translator = FactorTranslator::from_rule(&rule).map_err(|e| e.to_string())?;
+
+            parsed_factors = packet.cp.factors
+                .iter()
+                .map(|factor| {
+                    let value = factor.primary_value().map(|value| value.to_string());
+                    translator.translate_factor(&factor.code, value.as_deref(), factor.flag.as_deref())
+                })
+                .collect();
+        }
+        _ => {
+            let polid = match &packet {
+                Some(packet) => packet.cp.params.get("PolId").cloned(),
+                None => parse_result.fields.fields.get("cp").and_then(|cp_field| {
+                    let cp_value = cp_field.value.as_string();
+                    let polid_part = &cp_value[cp_value.find("PolId=")? + 6..];
+                    let polid_end = polid_part.find(';').unwrap_or(polid_part.len());
+                    Some(polid_part[..polid_end].to_string())
+                }),
+            };
+
+            if let Some(polid) = polid {
+                parsed_factors = parse_factor_codes(protocol_id.clone(), polid).await?;
+            }
         }
     }
 
+    let factor_summary = (!parsed_factors.is_empty())
+        .then(|| FactorTranslator::new().get_factor_summary(&parsed_factors));
+
     Ok(HJ212ParseResult {
         parse_result,
         parsed_factors,
         factor_summary,
+        packet,
     })
 }
 
@@ -1426,6 +1885,174 @@ pub struct HJ212ParseResult {
     pub parse_result: crate::parser::ParseResult,
     pub parsed_factors: Vec<ParsedFactor>,
     pub factor_summary: Option<FactorSummary>,
+    pub packet: Option<Hj212Packet>,
+}
+
+/// Build the platform general response (0x8001) acknowledging a JT/T 808 message
+#[tauri::command]
+pub fn build_jt808_platform_response(
+    data: Vec<u8>,
+    serial: u16,
+    result: Option<u8>,
+) -> Result<Vec<u8>, String> {
+    let request = Jt808Packet::decode(&data).map_err(|e| e.to_string())?;
+    Jt808Packet::platform_response(&request.header, serial, result.unwrap_or(crate::parser::jt808::RESULT_SUCCESS))
+        .and_then(|response| response.encode())
+        .map_err(|e| e.to_string())
+}
+
+/// Read one data identifier from a DL/T 645 meter over a session
+#[tauri::command]
+pub async fn dlt645_read(
+    session_id: String,
+    address: String,
+    di: String,
+    timeout_ms: Option<u64>,
+    session_manager: State<'_, SessionManager>,
+) -> Result<Dlt645Frame, String> {
+    let di = crate::parser::dlt645::parse_di(&di).map_err(|e| e.to_string())?;
+    let timeout = timeout_ms.map(std::time::Duration::from_millis).unwrap_or(crate::master::dlt645::DEFAULT_TIMEOUT);
+    crate::master::dlt645::read(&session_manager, &session_id, &address, di, timeout)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Read the address of the only DL/T 645 meter on a session
+#[tauri::command]
+pub async fn dlt645_read_address(
+    session_id: String,
+    timeout_ms: Option<u64>,
+    session_manager: State<'_, SessionManager>,
+) -> Result<Dlt645Frame, String> {
+    let timeout = timeout_ms.map(std::time::Duration::from_millis).unwrap_or(crate::master::dlt645::DEFAULT_TIMEOUT);
+    crate::master::dlt645::read_address(&session_manager, &session_id, timeout)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Points of a running IEC 104 server session
+#[tauri::command]
+pub async fn iec104_get_points(session_id: String) -> Result<Vec<crate::network::iec104::Iec104Point>, String> {
+    crate::network::iec104::points(&session_id).map_err(|e| e.to_string())
+}
+
+/// Change a point of a running IEC 104 server session, reporting it spontaneously
+#[tauri::command]
+pub async fn iec104_set_point(session_id: String, address: u32, value: f64) -> Result<(), String> {
+    crate::network::iec104::set_point(&session_id, address, value).map_err(|e| e.to_string())
+}
+
+/// Points of a running DNP3 outstation session
+#[tauri::command]
+pub async fn dnp3_get_points(session_id: String) -> Result<Vec<crate::network::dnp3::Dnp3Point>, String> {
+    crate::network::dnp3::points(&session_id).map_err(|e| e.to_string())
+}
+
+/// Change a point of a running DNP3 outstation session, buffering an event for its class
+#[tauri::command]
+pub async fn dnp3_set_point(
+    session_id: String,
+    kind: crate::network::dnp3::Dnp3PointKind,
+    index: u16,
+    value: f64,
+) -> Result<(), String> {
+    crate::network::dnp3::set_point(&session_id, kind, index, value).map_err(|e| e.to_string())
+}
+
+/// Send an integrity poll from a connected DNP3 master session
+#[tauri::command]
+pub async fn dnp3_integrity_poll(session_id: String) -> Result<(), String> {
+    crate::network::dnp3::integrity_poll(&session_id).await.map_err(|e| e.to_string())
+}
+
+/// Send an event class poll from a connected DNP3 master session
+#[tauri::command]
+pub async fn dnp3_class_poll(session_id: String, classes: Vec<u8>) -> Result<(), String> {
+    crate::network::dnp3::class_poll(&session_id, &classes).await.map_err(|e| e.to_string())
+}
+
+/// Read coils or discrete inputs of a running Modbus slave session
+#[tauri::command]
+pub async fn modbus_slave_read_bits(
+    session_id: String,
+    area: crate::network::modbus_slave::ModbusArea,
+    address: u16,
+    quantity: u16,
+) -> Result<Vec<bool>, String> {
+    crate::network::modbus_slave::read_bits(&session_id, area, address, quantity).map_err(|e| e.to_string())
+}
+
+/// Read holding or input registers of a running Modbus slave session
+#[tauri::command]
+pub async fn modbus_slave_read_registers(
+    session_id: String,
+    area: crate::network::modbus_slave::ModbusArea,
+    address: u16,
+    quantity: u16,
+) -> Result<Vec<u16>, String> {
+    crate::network::modbus_slave::read_registers(&session_id, area, address, quantity).map_err(|e| e.to_string())
+}
+
+/// Write coils or discrete inputs of a running Modbus slave session
+#[tauri::command]
+pub async fn modbus_slave_write_bits(
+    session_id: String,
+    area: crate::network::modbus_slave::ModbusArea,
+    address: u16,
+    values: Vec<bool>,
+) -> Result<(), String> {
+    crate::network::modbus_slave::write_bits(&session_id, area, address, &values).map_err(|e| e.to_string())
+}
+
+/// Write holding or input registers of a running Modbus slave session
+#[tauri::command]
+pub async fn modbus_slave_write_registers(
+    session_id: String,
+    area: crate::network::modbus_slave::ModbusArea,
+    address: u16,
+    values: Vec<u16>,
+) -> Result<(), String> {
+    crate::network::modbus_slave::write_registers(&session_id, area, address, &values).map_err(|e| e.to_string())
+}
+
+/// Set or clear the fault injected by a running Modbus RTU slave session
+#[tauri::command]
+pub async fn modbus_rtu_slave_set_fault(
+    session_id: String,
+    fault: Option<crate::network::modbus_rtu_slave::RtuFault>,
+) -> Result<(), String> {
+    crate::network::modbus_rtu_slave::set_session_fault(&session_id, fault).map_err(|e| e.to_string())
+}
+
+/// Start polling the tags of a Modbus client session, replacing its current polling
+#[tauri::command]
+pub async fn modbus_start_polling(
+    session_id: String,
+    config: crate::network::modbus_poll::ModbusPollConfig,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    crate::network::modbus_poll::start_polling(&session_id, &config, Some(app_handle)).map_err(|e| e.to_string())
+}
+
+/// Stop polling a Modbus client session
+#[tauri::command]
+pub async fn modbus_stop_polling(session_id: String) -> Result<(), String> {
+    crate::network::modbus_poll::stop_polling(&session_id);
+    Ok(())
+}
+
+/// Read a Modbus tag map from CSV content
+#[tauri::command]
+pub async fn modbus_import_tags_csv(content: String) -> Result<Vec<crate::network::modbus_poll::ModbusTag>, String> {
+    crate::network::modbus_poll::import_tags_csv(&content).map_err(|e| e.to_string())
+}
+
+/// Set the DTR and/or RTS lines of an open serial session
+#[tauri::command]
+pub async fn serial_set_control_lines(session_id: String, dtr: Option<bool>, rts: Option<bool>) -> Result<(), String> {
+    crate::network::serial::set_session_control_lines(&session_id, dtr, rts)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 /// File filter for save dialog
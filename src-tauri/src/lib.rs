@@ -1,10 +1,12 @@
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
 // Modules
 mod logging;
 mod network;
 mod session;
 mod parser;
+mod simulator;
+mod master;
 mod commands;
 mod storage;
 mod types;
@@ -36,6 +38,17 @@ pub fn run() {
             send_to_client,
             broadcast_message,
             disconnect_client,
+            set_session_parser_binding,
+            get_session_parser_binding,
+            start_hj212_platform,
+            stop_hj212_platform,
+            get_hj212_platform_devices,
+            start_hj212_device,
+            stop_hj212_device,
+            get_factor_alarm_rules,
+            set_factor_alarm_rule,
+            remove_factor_alarm_rule,
+            get_active_factor_alarms,
             pause_auto_reconnect,
             resume_auto_reconnect,
             send_udp_message,
@@ -52,8 +65,24 @@ pub fn run() {
             modbus_write_single_register,
             modbus_write_multiple_coils,
             modbus_write_multiple_registers,
+            modbus_mask_write_register,
+            modbus_read_write_multiple_registers,
+            modbus_read_fifo_queue,
+            modbus_read_exception_status,
+            modbus_diagnostics,
+            modbus_read_device_identification,
+            modbus_custom_request,
+            modbus_slave_read_bits,
+            modbus_slave_read_registers,
+            modbus_slave_write_bits,
+            modbus_slave_write_registers,
+            modbus_rtu_slave_set_fault,
+            modbus_start_polling,
+            modbus_stop_polling,
+            modbus_import_tags_csv,
             // Serial port commands
             list_serial_ports,
+            serial_set_control_lines,
             // Parsing commands
             load_protocol_rule,
             // Menu state commands
@@ -65,6 +94,8 @@ pub fn run() {
             load_protocol_rule_from_string,
             parse_data_with_rule,
             parse_data_auto,
+            parse_data_with_envelope,
+            export_parse_results,
             validate_parsed_data,
             get_available_parsers,
             register_parser,
@@ -73,10 +104,22 @@ pub fn run() {
             export_protocol,
             list_protocols,
             list_enabled_protocols,
+            update_protocol,
+            list_protocol_revisions,
+            get_protocol_revision_content,
+            diff_protocol_revisions,
+            rollback_protocol,
             get_protocol_metadata,
+            lint_protocol_rule,
+            lint_protocol,
             get_protocol_content,
             delete_protocol,
             set_protocol_enabled,
+            import_protocol_catalog,
+            list_protocol_catalogs,
+            export_protocol_bundle,
+            import_protocol_bundle,
+            add_protocol_sample,
             // Theme commands
             set_window_theme,
             // Logging commands
@@ -91,6 +134,15 @@ pub fn run() {
             get_factor_summary,
             get_protocol_factor_definitions,
             parse_hj212_message,
+            build_jt808_platform_response,
+            dlt645_read,
+            dlt645_read_address,
+            iec104_get_points,
+            iec104_set_point,
+            dnp3_get_points,
+            dnp3_set_point,
+            dnp3_integrity_poll,
+            dnp3_class_poll,
             // File dialog commands
             save_file_dialog,
             // Shell executor commands
@@ -204,8 +256,50 @@ pub fn run() {
 
             if let Err(e) = parser::initialize_parser_system_with_repository(Some(protocols_dir)) {
                 log::error!("Failed to initialize parser system: {}", e);
+            } else {
+                // Alarm limits come from the factor definitions
+                parser::load_factor_alarm_rules(
+                    &mut parser::get_alarm_engine().lock().unwrap(),
+                    &mut parser::get_parser_registry().write().unwrap(),
+                );
+
+                // Hot-reload rule files edited outside the app
+                let app_handle_watcher = app.handle().clone();
+                match parser::ProtocolWatcher::start(parser::get_parser_registry(), move |change| {
+                    if let Err(e) = app_handle_watcher.emit(parser::PROTOCOLS_CHANGED_EVENT, &change) {
+                        log::error!("Failed to emit protocol change event: {}", e);
+                    }
+                }) {
+                    Ok(watcher) => {
+                        app.manage(std::sync::Mutex::new(watcher));
+                    }
+                    Err(e) => {
+                        log::error!("Failed to start protocol watcher: {}", e);
+                    }
+                }
             }
 
+            // Report multi-packet messages whose remaining fragments never arrived
+            let app_handle_fragments = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut ticker = tokio::time::interval(std::time::Duration::from_secs(1));
+                loop {
+                    ticker.tick().await;
+                    network::emit_expired_fragments(&app_handle_fragments);
+                }
+            });
+
+            // Raise stale-data alarms for factors that stopped reporting
+            let app_handle_alarms = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let mut ticker = tokio::time::interval(std::time::Duration::from_secs(5));
+                loop {
+                    ticker.tick().await;
+                    let events = parser::get_alarm_engine().lock().unwrap().check_stale(chrono::Utc::now());
+                    network::notify_factor_alarms(&app_handle_alarms, events);
+                }
+            });
+
             // Initialize logging system
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
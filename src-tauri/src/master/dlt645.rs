@@ -0,0 +1,79 @@
+//! DL/T 645 master
+//!
+//! Reads data identifiers from meters behind a TCP gateway or on a serial
+//! line, using the frame codec of `parser::dlt645`.
+
+use crate::parser::dlt645::{self, Dlt645Frame, FN_READ_ADDRESS, FN_READ_DATA};
+use crate::session::SessionManager;
+use crate::types::{NetworkError, NetworkResult};
+use std::time::Duration;
+
+/// Response timeout used when the caller gives none
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Extractor for the answer of one meter to one function
+///
+/// Frames from other meters or for other functions are skipped, so a
+/// shared RS-485 line or a chatty gateway does not end the wait.
+fn answer_from(address: String, function: u8) -> impl FnMut(&mut Vec<u8>) -> Option<Vec<u8>> + Send + 'static {
+    move |buffer: &mut Vec<u8>| {
+        while let Some((start, end)) = dlt645::find_frame(buffer) {
+            let frame: Vec<u8> = buffer.drain(..end).skip(start).collect();
+            if Dlt645Frame::decode(&frame).is_ok_and(|decoded| decoded.answers(&address, function)) {
+                return Some(frame);
+            }
+        }
+        None
+    }
+}
+
+/// Send a request and decode the answer
+async fn exchange(
+    session_manager: &SessionManager,
+    session_id: &str,
+    request: Vec<u8>,
+    address: &str,
+    function: u8,
+    timeout: Duration,
+) -> NetworkResult<Dlt645Frame> {
+    let response = super::request(
+        session_manager,
+        session_id,
+        &request,
+        answer_from(address.to_string(), function),
+        timeout,
+    ).await?;
+
+    let frame = Dlt645Frame::decode(&response)?;
+    if !frame.checksum_valid() {
+        return Err(NetworkError::ParseError(format!(
+            "DL/T 645 answer from {} has check sum {:02X}, expected {:02X}",
+            frame.address, frame.checksum, frame.calculated_checksum
+        )));
+    }
+    Ok(frame)
+}
+
+/// Read one DI from the meter at an address
+///
+/// Abnormal answers are returned as frames; their body carries the error.
+pub async fn read(
+    session_manager: &SessionManager,
+    session_id: &str,
+    address: &str,
+    di: u32,
+    timeout: Duration,
+) -> NetworkResult<Dlt645Frame> {
+    let request = dlt645::read_request(address, di)?;
+    exchange(session_manager, session_id, request, address, FN_READ_DATA, timeout).await
+}
+
+/// Read the address of the only meter on the line
+pub async fn read_address(
+    session_manager: &SessionManager,
+    session_id: &str,
+    timeout: Duration,
+) -> NetworkResult<Dlt645Frame> {
+    let request = dlt645::read_address_request()?;
+    exchange(session_manager, session_id, request, dlt645::WILDCARD_ADDRESS, FN_READ_ADDRESS, timeout).await
+}
@@ -0,0 +1,100 @@
+//! Protocol masters polling devices over live sessions
+//!
+//! A master sends a request through the `SessionManager` and waits for the
+//! matching answer. The network layer hands received data over through
+//! `handle_inbound`; the waiting request buffers it until its extractor
+//! recognises a complete response. One request is outstanding per session.
+
+pub mod dlt645;
+
+use crate::session::SessionManager;
+use crate::types::{NetworkError, NetworkResult};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Received bytes kept while waiting for a response
+const MAX_BUFFERED: usize = 8192;
+
+/// Pulls a complete response out of the received bytes, dropping what it consumed
+type Extractor = Box<dyn FnMut(&mut Vec<u8>) -> Option<Vec<u8>> + Send>;
+
+/// Request waiting for its response
+struct PendingRequest {
+    buffer: Vec<u8>,
+    extract: Extractor,
+    reply: Option<oneshot::Sender<Vec<u8>>>,
+}
+
+/// Outstanding requests by session
+static PENDING_REQUESTS: OnceLock<Mutex<HashMap<String, PendingRequest>>> = OnceLock::new();
+
+fn pending_requests() -> &'static Mutex<HashMap<String, PendingRequest>> {
+    PENDING_REQUESTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Forgets the pending request of a session however the request ends
+struct PendingGuard<'a>(&'a str);
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        pending_requests().lock().unwrap().remove(self.0);
+    }
+}
+
+/// Send a request and wait for the response the extractor recognises
+pub async fn request<F>(
+    session_manager: &SessionManager,
+    session_id: &str,
+    frame: &[u8],
+    extract: F,
+    timeout: Duration,
+) -> NetworkResult<Vec<u8>>
+where
+    F: FnMut(&mut Vec<u8>) -> Option<Vec<u8>> + Send + 'static,
+{
+    let (reply, response) = oneshot::channel();
+    {
+        let mut pending = pending_requests().lock().unwrap();
+        if pending.contains_key(session_id) {
+            return Err(NetworkError::ProtocolError(format!(
+                "A request is already waiting for a response on session {}", session_id
+            )));
+        }
+        pending.insert(session_id.to_string(), PendingRequest {
+            buffer: Vec::new(),
+            extract: Box::new(extract),
+            reply: Some(reply),
+        });
+    }
+    let _guard = PendingGuard(session_id);
+
+    session_manager.send_message(session_id, frame).await?;
+
+    match tokio::time::timeout(timeout, response).await {
+        Ok(Ok(response)) => Ok(response),
+        Ok(Err(_)) => Err(NetworkError::ReceiveFailed(format!("Request on session {} was abandoned", session_id))),
+        Err(_) => Err(NetworkError::ReceiveFailed(format!(
+            "No response on session {} within {} ms", session_id, timeout.as_millis()
+        ))),
+    }
+}
+
+/// Hand data received on a session to the request waiting on it
+pub fn handle_inbound(session_id: &str, data: &[u8]) {
+    let mut pending = pending_requests().lock().unwrap();
+    let Some(request) = pending.get_mut(session_id) else {
+        return;
+    };
+
+    request.buffer.extend_from_slice(data);
+    if let Some(response) = (request.extract)(&mut request.buffer) {
+        if let Some(reply) = request.reply.take() {
+            let _ = reply.send(response);
+        }
+    } else if request.buffer.len() > MAX_BUFFERED {
+        let excess = request.buffer.len() - MAX_BUFFERED;
+        request.buffer.drain(..excess);
+    }
+}
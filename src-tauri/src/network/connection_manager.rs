@@ -2,6 +2,9 @@ use crate::network::{Connection, ServerConnection};
 use crate::network::tcp::TcpServer;
 use crate::network::websocket::WebSocketServer;
 use crate::network::udp::{UdpClient, UdpServer};
+use crate::network::iec104::Iec104Server;
+use crate::network::dnp3::Dnp3Server;
+use crate::network::modbus_slave::ModbusTcpServer;
 use crate::types::{NetworkResult, NetworkError, ConnectionStatus};
 use std::sync::Arc;
 use std::time::Duration;
@@ -338,6 +341,12 @@ impl ConnectionManager {
                 server_connection.disconnect_client(client_id).await?;
             } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<WebSocketServer>() {
                 server_connection.disconnect_client(client_id).await?;
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<Iec104Server>() {
+                server_connection.disconnect_client(client_id).await?;
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<Dnp3Server>() {
+                server_connection.disconnect_client(client_id).await?;
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<ModbusTcpServer>() {
+                server_connection.disconnect_client(client_id).await?;
             } else {
                 return Err(crate::types::NetworkError::ConnectionFailed("Connection does not support client disconnection".to_string()));
             }
@@ -370,6 +379,15 @@ impl ConnectionManager {
             } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<UdpServer>() {
                 eprintln!("ConnectionManager: Successfully downcast to UdpServer for session {}", self.session_id);
                 server_connection.send_to_client(client_id, data).await
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<Iec104Server>() {
+                eprintln!("ConnectionManager: Successfully downcast to Iec104Server for session {}", self.session_id);
+                server_connection.send_to_client(client_id, data).await
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<Dnp3Server>() {
+                eprintln!("ConnectionManager: Successfully downcast to Dnp3Server for session {}", self.session_id);
+                server_connection.send_to_client(client_id, data).await
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<ModbusTcpServer>() {
+                eprintln!("ConnectionManager: Successfully downcast to ModbusTcpServer for session {}", self.session_id);
+                server_connection.send_to_client(client_id, data).await
             } else {
                 let error_msg = "Connection does not support server operations".to_string();
                 eprintln!("ConnectionManager: {}", error_msg);
@@ -392,6 +410,12 @@ impl ConnectionManager {
                 server_connection.broadcast(data).await
             } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<UdpServer>() {
                 server_connection.broadcast(data).await
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<Iec104Server>() {
+                server_connection.broadcast(data).await
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<Dnp3Server>() {
+                server_connection.broadcast(data).await
+            } else if let Some(server_connection) = connection.as_any_mut().downcast_mut::<ModbusTcpServer>() {
+                server_connection.broadcast(data).await
             } else {
                 Err(crate::types::NetworkError::ConnectionFailed("Connection does not support server operations".to_string()))
             }
@@ -428,7 +452,9 @@ impl ConnectionManager {
                 eprintln!("🏠 ConnectionManager: Successfully downcast to UdpServer for session {}", self.session_id);
 
                 // For UDP server, we need to use send_to_client method with the target address as client_id
-                let client_id = format!("{}:{}", target_host, target_port);
+                let client_id = crate::utils::parse_socket_addr(target_host, target_port)
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_else(|_| format!("{}:{}", target_host, target_port));
                 eprintln!("🎯 ConnectionManager: UdpServer sending to client_id: {}", client_id);
 
                 match udp_server.send_to_client(&client_id, data).await {
@@ -525,7 +551,7 @@ impl ConnectionManager {
             // Add WebSocket server support
             else if let Some(_ws_server) = connection.as_any_mut().downcast_mut::<WebSocketServer>() {
                 eprintln!("ConnectionManager: Setting app handle on WebSocket server for session {}", self.session_id);
-                // WebSocket server doesn't have set_app_handle method yet, but we can add it later
+                // The WebSocket server receives its app handle from ConnectionFactory
             }
         } else {
             eprintln!("ConnectionManager: Warning - No connection available to set app handle for session {}", self.session_id);
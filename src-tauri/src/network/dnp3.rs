@@ -0,0 +1,1242 @@
+//! DNP3 over TCP master and outstation sessions
+//!
+//! `Dnp3Client` is a master: it connects to an outstation, runs an integrity
+//! poll on connect and, when configured, periodic integrity and class polls,
+//! confirming responses that ask for it. `Dnp3Server` simulates an outstation
+//! that answers reads from a point database and buffers change events by
+//! class until the master confirms them. Both sides run the link layer
+//! (link status, reset and confirmed user data are answered), the transport
+//! layer through `Dnp3Link`, and emit the binary, analog and counter objects
+//! of every response as `dnp3-objects` events. Data written to either session
+//! may be a complete link frame, which is sent unchanged, or a bare
+//! application fragment, which is segmented and framed by the link.
+
+use async_trait::async_trait;
+use crate::network::{Connection, ServerConnection};
+use crate::parser::dnp3::{
+    self, AppFragment, DataObject, DataValue, LinkFrame, LinkHeader, ObjectHeader, ObjectRange,
+    TransportReassembler, FC_CONFIRM, FC_READ, FC_UNSOLICITED_RESPONSE, FLAG_ONLINE, IIN_CLASS1_EVENTS,
+    IIN_CLASS2_EVENTS, IIN_CLASS3_EVENTS, IIN_EVENT_BUFFER_OVERFLOW, IIN_NO_FUNC_CODE_SUPPORT, IIN_OBJECT_UNKNOWN,
+    LINK_ACK, LINK_CONFIRMED_USER_DATA, LINK_NOT_SUPPORTED, LINK_REQUEST_LINK_STATUS, LINK_RESET_LINK_STATES,
+    LINK_STATUS, LINK_TEST_LINK_STATES, LINK_UNCONFIRMED_USER_DATA, APP_SEQUENCE_MODULO,
+};
+use crate::types::{NetworkError, NetworkEvent, NetworkResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Event carrying the measurement objects of a received response
+pub const DNP3_OBJECTS_EVENT: &str = "dnp3-objects";
+
+/// Requests queued per link task
+const COMMAND_QUEUE: usize = 64;
+
+/// Events an outstation buffers before it drops the oldest
+const MAX_EVENTS: usize = 256;
+
+/// Addresses from 0xFFF0 up are reserved for broadcast and self-address
+const MAX_ADDRESS: u16 = 0xFFEF;
+
+/// Addresses, polling and the simulated point database, read from `dnp3` in the session config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Dnp3Settings {
+    /// Link address of the master
+    pub master_address: u16,
+    /// Link address of the outstation
+    pub outstation_address: u16,
+    /// Seconds between integrity polls; 0 polls once after connecting
+    pub integrity_interval: u64,
+    /// Seconds between event class polls; 0 disables them
+    pub class_interval: u64,
+    /// Event classes read by class polls
+    pub classes: Vec<u8>,
+    /// Points served in server mode
+    pub points: Vec<Dnp3Point>,
+}
+
+impl Default for Dnp3Settings {
+    fn default() -> Self {
+        Self {
+            master_address: 1,
+            outstation_address: 10,
+            integrity_interval: 0,
+            class_interval: 0,
+            classes: vec![1, 2, 3],
+            points: Vec::new(),
+        }
+    }
+}
+
+impl Dnp3Settings {
+    /// Read the settings from a session config, falling back to the defaults
+    pub fn from_config(config: &serde_json::Value) -> NetworkResult<Self> {
+        let settings: Self = match config.get("dnp3") {
+            Some(value) if !value.is_null() => serde_json::from_value(value.clone())
+                .map_err(|e| NetworkError::InvalidConfig(format!("Invalid DNP3 settings: {}", e)))?,
+            _ => Self::default(),
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Check addresses, classes and point indexes
+    pub fn validate(&self) -> NetworkResult<()> {
+        if self.master_address > MAX_ADDRESS || self.outstation_address > MAX_ADDRESS {
+            return Err(NetworkError::InvalidConfig(format!("Link addresses must be 0..={}", MAX_ADDRESS)));
+        }
+        if self.master_address == self.outstation_address {
+            return Err(NetworkError::InvalidConfig("Master and outstation need different link addresses".to_string()));
+        }
+        if let Some(class) = self.classes.iter().find(|class| !(1..=3).contains(*class)) {
+            return Err(NetworkError::InvalidConfig(format!("Event classes are 1..=3, got {}", class)));
+        }
+        let mut seen = std::collections::HashSet::new();
+        for point in &self.points {
+            if point.class > 3 {
+                return Err(NetworkError::InvalidConfig(format!("Point {:?} {} has class {}", point.kind, point.index, point.class)));
+            }
+            if !seen.insert((point.kind, point.index)) {
+                return Err(NetworkError::InvalidConfig(format!("Duplicate point {:?} {}", point.kind, point.index)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Kind of a simulated point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dnp3PointKind {
+    /// Binary input (g1v2, events g2v2)
+    Binary,
+    /// Counter (g20v1, events g22v5)
+    Counter,
+    /// Analog input (g30v5, events g32v7)
+    Analog,
+}
+
+impl Dnp3PointKind {
+    /// Static and event object group and variation
+    fn objects(self) -> ((u8, u8), (u8, u8)) {
+        match self {
+            Self::Binary => ((1, 2), (2, 2)),
+            Self::Counter => ((20, 1), (22, 5)),
+            Self::Analog => ((30, 5), (32, 7)),
+        }
+    }
+
+    fn from_group(group: u8) -> Option<Self> {
+        match group {
+            1 => Some(Self::Binary),
+            20 => Some(Self::Counter),
+            30 => Some(Self::Analog),
+            _ => None,
+        }
+    }
+}
+
+/// Simulated point
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dnp3Point {
+    pub kind: Dnp3PointKind,
+    pub index: u16,
+    #[serde(default)]
+    pub value: f64,
+    /// Event class of changes; 0 reports no events
+    #[serde(default)]
+    pub class: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Dnp3Point {
+    fn object(&self) -> DataObject {
+        let value = match self.kind {
+            Dnp3PointKind::Binary => DataValue::Binary { state: self.value != 0.0, flags: FLAG_ONLINE },
+            Dnp3PointKind::Counter => DataValue::Counter { value: self.value as u32, flags: FLAG_ONLINE },
+            Dnp3PointKind::Analog => DataValue::Analog { value: self.value, flags: FLAG_ONLINE },
+        };
+        DataObject::new(self.index, value)
+    }
+}
+
+/// Buffered change event
+#[derive(Debug, Clone)]
+struct Dnp3Event {
+    id: u64,
+    class: u8,
+    kind: Dnp3PointKind,
+    object: DataObject,
+}
+
+/// Point database and event buffer of a simulated outstation
+#[derive(Debug, Clone)]
+pub struct PointDatabase {
+    points: BTreeMap<(Dnp3PointKind, u16), Dnp3Point>,
+    events: Vec<Dnp3Event>,
+    next_event: u64,
+    overflow: bool,
+    /// Application sequence and events of the last response awaiting confirmation
+    unconfirmed: Option<(u8, Vec<u64>)>,
+}
+
+impl PointDatabase {
+    pub fn new(points: Vec<Dnp3Point>) -> Self {
+        Self {
+            points: points.into_iter().map(|point| ((point.kind, point.index), point)).collect(),
+            events: Vec::new(),
+            next_event: 0,
+            overflow: false,
+            unconfirmed: None,
+        }
+    }
+
+    /// Points ordered by kind and index
+    pub fn points(&self) -> Vec<Dnp3Point> {
+        self.points.values().cloned().collect()
+    }
+
+    /// Buffered events not yet confirmed by the master
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Change a point, buffering an event when its value changes and it has a class
+    pub fn set(&mut self, kind: Dnp3PointKind, index: u16, value: f64) -> NetworkResult<()> {
+        let point = self.points.get_mut(&(kind, index)).ok_or_else(|| {
+            NetworkError::InvalidConfig(format!("Point {:?} {} is not in the database", kind, index))
+        })?;
+        let changed = point.value != value;
+        point.value = value;
+        if !changed || point.class == 0 {
+            return Ok(());
+        }
+
+        let time = chrono::Utc::now().timestamp_millis().max(0) as u64;
+        let event = Dnp3Event { id: self.next_event, class: point.class, kind, object: point.object().with_time(time) };
+        self.next_event += 1;
+        if self.events.len() >= MAX_EVENTS {
+            self.events.remove(0);
+            self.overflow = true;
+        }
+        self.events.push(event);
+        Ok(())
+    }
+
+    /// Answer an application request; confirms produce no response
+    pub fn handle(&mut self, request: &AppFragment) -> Option<AppFragment> {
+        let seq = request.control.seq;
+        match request.function {
+            FC_CONFIRM => {
+                if let Some((pending, ids)) = self.unconfirmed.take() {
+                    if pending == seq {
+                        self.events.retain(|event| !ids.contains(&event.id));
+                        self.overflow = false;
+                    } else {
+                        self.unconfirmed = Some((pending, ids));
+                    }
+                }
+                None
+            }
+            FC_READ => {
+                let mut iin = 0;
+                let mut headers = Vec::new();
+                let mut sent = Vec::new();
+                for header in &request.headers {
+                    match header.polled_class() {
+                        Some(0) => headers.extend(self.static_headers(None)),
+                        Some(class) => {
+                            let (events, ids) = self.event_headers(class);
+                            headers.extend(events);
+                            sent.extend(ids);
+                        }
+                        None => match Dnp3PointKind::from_group(header.group) {
+                            Some(kind) if header.range == ObjectRange::All => headers.extend(self.static_headers(Some(kind))),
+                            _ => iin |= IIN_OBJECT_UNKNOWN,
+                        },
+                    }
+                }
+
+                let mut response = AppFragment::response(seq, iin | self.iin(&sent), headers);
+                if !sent.is_empty() {
+                    response.control.con = true;
+                    self.unconfirmed = Some((seq, sent));
+                }
+                Some(response)
+            }
+            _ => Some(AppFragment::response(seq, self.iin(&[]) | IIN_NO_FUNC_CODE_SUPPORT, Vec::new())),
+        }
+    }
+
+    /// Internal indications for the events not in `sent`
+    fn iin(&self, sent: &[u64]) -> u16 {
+        let mut iin = if self.overflow { IIN_EVENT_BUFFER_OVERFLOW } else { 0 };
+        for event in self.events.iter().filter(|event| !sent.contains(&event.id)) {
+            iin |= match event.class {
+                1 => IIN_CLASS1_EVENTS,
+                2 => IIN_CLASS2_EVENTS,
+                _ => IIN_CLASS3_EVENTS,
+            };
+        }
+        iin
+    }
+
+    /// Static data, one header per run of consecutive indexes
+    fn static_headers(&self, only: Option<Dnp3PointKind>) -> Vec<ObjectHeader> {
+        let mut headers: Vec<ObjectHeader> = Vec::new();
+        for point in self.points.values().filter(|point| only.is_none() || only == Some(point.kind)) {
+            let ((group, variation), _) = point.kind.objects();
+            let object = point.object();
+            match headers.last_mut() {
+                Some(header) if header.group == group && matches!(
+                    header.range,
+                    ObjectRange::Range { stop, .. } if stop.checked_add(1) == Some(point.index)
+                ) => {
+                    header.objects.push(object);
+                    *header = ObjectHeader::range(group, variation, std::mem::take(&mut header.objects));
+                }
+                _ => headers.push(ObjectHeader::range(group, variation, vec![object])),
+            }
+        }
+        headers
+    }
+
+    /// Events of a class by kind, and their ids
+    fn event_headers(&self, class: u8) -> (Vec<ObjectHeader>, Vec<u64>) {
+        let mut by_kind: BTreeMap<Dnp3PointKind, Vec<DataObject>> = BTreeMap::new();
+        let mut ids = Vec::new();
+        for event in self.events.iter().filter(|event| event.class == class) {
+            by_kind.entry(event.kind).or_default().push(event.object);
+            ids.push(event.id);
+        }
+        let headers = by_kind
+            .into_iter()
+            .map(|(kind, objects)| {
+                let (_, (group, variation)) = kind.objects();
+                ObjectHeader::indexed(group, variation, objects)
+            })
+            .collect();
+        (headers, ids)
+    }
+}
+
+/// Link and transport state of one DNP3 connection
+#[derive(Debug)]
+pub struct Dnp3Link {
+    master: bool,
+    local: u16,
+    remote: u16,
+    app_seq: u8,
+    transport_seq: u8,
+    reassembler: TransportReassembler,
+}
+
+impl Dnp3Link {
+    pub fn new(settings: &Dnp3Settings, master: bool) -> Self {
+        let (local, remote) = if master {
+            (settings.master_address, settings.outstation_address)
+        } else {
+            (settings.outstation_address, settings.master_address)
+        };
+        Self { master, local, remote, app_seq: 0, transport_seq: 0, reassembler: TransportReassembler::new() }
+    }
+
+    /// Frame a master request, assigning its application sequence number
+    pub fn request(&mut self, mut fragment: AppFragment) -> NetworkResult<Vec<Vec<u8>>> {
+        fragment.control.seq = self.app_seq;
+        self.app_seq = (self.app_seq + 1) % APP_SEQUENCE_MODULO;
+        self.send(&fragment)
+    }
+
+    /// Segment and frame an application fragment as unconfirmed user data
+    pub fn send(&mut self, fragment: &AppFragment) -> NetworkResult<Vec<Vec<u8>>> {
+        let (segments, next) = dnp3::segment(&fragment.encode()?, self.transport_seq);
+        self.transport_seq = next;
+        segments
+            .into_iter()
+            .map(|segment| LinkFrame::new(self.primary(LINK_UNCONFIRMED_USER_DATA), segment).encode())
+            .collect()
+    }
+
+    /// Process a received link frame, returning link replies and a completed fragment
+    pub fn receive(&mut self, frame: LinkFrame) -> NetworkResult<(Vec<Vec<u8>>, Option<AppFragment>)> {
+        let header = frame.header;
+        // 0xFFFD..=0xFFFF are broadcast addresses
+        if header.destination != self.local && header.destination < 0xFFFD {
+            return Ok((Vec::new(), None));
+        }
+        if !header.prm {
+            return Ok((Vec::new(), None));
+        }
+
+        let (reply, user_data) = match header.function {
+            LINK_REQUEST_LINK_STATUS => (Some(LINK_STATUS), false),
+            LINK_RESET_LINK_STATES | LINK_TEST_LINK_STATES => (Some(LINK_ACK), false),
+            LINK_CONFIRMED_USER_DATA => (Some(LINK_ACK), true),
+            LINK_UNCONFIRMED_USER_DATA => (None, true),
+            _ => (Some(LINK_NOT_SUPPORTED), false),
+        };
+        let replies = match reply {
+            Some(function) if header.destination == self.local => {
+                vec![LinkFrame::new(LinkHeader::secondary(function, self.master, header.source, self.local), Vec::new()).encode()?]
+            }
+            _ => Vec::new(),
+        };
+        let fragment = if user_data {
+            self.reassembler.push(&frame.user_data).map(|data| AppFragment::decode(&data)).transpose()?
+        } else {
+            None
+        };
+        Ok((replies, fragment))
+    }
+
+    fn primary(&self, function: u8) -> LinkHeader {
+        LinkHeader::primary(function, self.master, self.remote, self.local)
+    }
+}
+
+/// When a master's polls are due
+#[derive(Debug)]
+struct PollSchedule {
+    integrity: Option<Instant>,
+    integrity_interval: Option<Duration>,
+    class: Option<Instant>,
+    class_interval: Option<Duration>,
+    classes: Vec<u8>,
+}
+
+impl PollSchedule {
+    /// Integrity poll right away, then at the configured intervals
+    fn new(settings: &Dnp3Settings, now: Instant) -> Self {
+        let interval = |seconds: u64| (seconds > 0).then(|| Duration::from_secs(seconds));
+        let class_interval = interval(settings.class_interval).filter(|_| !settings.classes.is_empty());
+        Self {
+            integrity: Some(now),
+            integrity_interval: interval(settings.integrity_interval),
+            class: class_interval.map(|interval| now + interval),
+            class_interval,
+            classes: settings.classes.clone(),
+        }
+    }
+
+    /// Polls due at `now`
+    fn due(&mut self, now: Instant) -> Vec<AppFragment> {
+        let mut polls = Vec::new();
+        if self.integrity.is_some_and(|due| now >= due) {
+            polls.push(AppFragment::integrity_poll(0));
+            self.integrity = self.integrity_interval.map(|interval| now + interval);
+            // An integrity poll reads the events too
+            self.class = self.class_interval.map(|interval| now + interval);
+        }
+        if self.class.is_some_and(|due| now >= due) {
+            polls.push(AppFragment::class_poll(0, &self.classes));
+            self.class = self.class_interval.map(|interval| now + interval);
+        }
+        polls
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        [self.integrity, self.class].into_iter().flatten().min()
+    }
+}
+
+/// Request handed to a running link task
+#[derive(Debug, Clone)]
+enum LinkCommand {
+    /// Complete link frame sent unchanged
+    Frame(Vec<u8>),
+    /// Application fragment framed by the link
+    Fragment(AppFragment),
+    /// Master request numbered by the link
+    Request(AppFragment),
+}
+
+impl LinkCommand {
+    /// Interpret data written to a session: a complete link frame or a bare application fragment
+    fn from_bytes(data: &[u8]) -> NetworkResult<Self> {
+        match LinkFrame::decode(data) {
+            Ok(_) => Ok(Self::Frame(data.to_vec())),
+            Err(_) => AppFragment::decode(data)
+                .map(Self::Fragment)
+                .map_err(|e| NetworkError::SendFailed(format!("Not a DNP3 link frame or application fragment: {}", e))),
+        }
+    }
+}
+
+/// Simulated outstation shared by the server's links
+#[derive(Debug)]
+struct Outstation {
+    database: Mutex<PointDatabase>,
+    clients: Mutex<HashMap<String, mpsc::Sender<LinkCommand>>>,
+}
+
+impl Outstation {
+    fn broadcast(&self, command: LinkCommand) -> usize {
+        let clients = self.clients.lock().unwrap();
+        clients.values().filter(|sender| sender.try_send(command.clone()).is_ok()).count()
+    }
+}
+
+/// Outstations of running server sessions
+static OUTSTATIONS: OnceLock<Mutex<HashMap<String, Arc<Outstation>>>> = OnceLock::new();
+
+/// Command channels of connected master sessions
+static MASTERS: OnceLock<Mutex<HashMap<String, mpsc::Sender<LinkCommand>>>> = OnceLock::new();
+
+fn outstations() -> &'static Mutex<HashMap<String, Arc<Outstation>>> {
+    OUTSTATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn masters() -> &'static Mutex<HashMap<String, mpsc::Sender<LinkCommand>>> {
+    MASTERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn outstation(session_id: &str) -> NetworkResult<Arc<Outstation>> {
+    outstations()
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| NetworkError::SessionNotFound(format!("{} is not a running DNP3 outstation", session_id)))
+}
+
+/// Points of a running server session
+pub fn points(session_id: &str) -> NetworkResult<Vec<Dnp3Point>> {
+    Ok(outstation(session_id)?.database.lock().unwrap().points())
+}
+
+/// Change a point of a running server session, buffering an event for its class
+pub fn set_point(session_id: &str, kind: Dnp3PointKind, index: u16, value: f64) -> NetworkResult<()> {
+    outstation(session_id)?.database.lock().unwrap().set(kind, index, value)
+}
+
+/// Send an integrity poll from a connected master session
+pub async fn integrity_poll(session_id: &str) -> NetworkResult<()> {
+    master_request(session_id, AppFragment::integrity_poll(0)).await
+}
+
+/// Send an event class poll from a connected master session
+pub async fn class_poll(session_id: &str, classes: &[u8]) -> NetworkResult<()> {
+    if classes.is_empty() || classes.iter().any(|class| !(1..=3).contains(class)) {
+        return Err(NetworkError::InvalidConfig(format!("Event classes are 1..=3, got {:?}", classes)));
+    }
+    master_request(session_id, AppFragment::class_poll(0, classes)).await
+}
+
+async fn master_request(session_id: &str, fragment: AppFragment) -> NetworkResult<()> {
+    let commands = masters()
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| NetworkError::SessionNotFound(format!("{} is not a connected DNP3 master", session_id)))?;
+    commands.send(LinkCommand::Request(fragment)).await.map_err(|_| NetworkError::NotConnected)
+}
+
+/// Where a link task reports its traffic
+#[derive(Debug, Clone)]
+struct LinkContext {
+    session_id: String,
+    client_id: Option<String>,
+    peer: Option<SocketAddr>,
+    settings: Dnp3Settings,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+    outstation: Option<Arc<Outstation>>,
+}
+
+impl LinkContext {
+    async fn send_event(&self, event_type: &str, data: Option<Vec<u8>>, error: Option<String>) {
+        if let Some(tx) = &self.event_tx {
+            let event = NetworkEvent {
+                session_id: self.session_id.clone(),
+                event_type: event_type.to_string(),
+                data,
+                error,
+                client_id: self.client_id.clone(),
+                mqtt_topic: None,
+                mqtt_qos: None,
+                mqtt_retain: None,
+                sse_event: None,
+            };
+            let _ = tx.send(event).await;
+        }
+    }
+
+    async fn frame(&self, frame: &[u8], direction: &str) {
+        if direction == "in" {
+            self.send_event("data_received", Some(frame.to_vec()), None).await;
+        }
+        if let Some(app_handle) = &self.app_handle {
+            let payload = serde_json::json!({
+                "sessionId": self.session_id,
+                "data": frame.to_vec(),
+                "direction": direction,
+                "clientId": self.client_id,
+            });
+            if let Err(e) = app_handle.emit("message-received", payload) {
+                eprintln!("DNP3: Failed to emit message-received event: {}", e);
+            }
+            if direction == "in" {
+                crate::network::emit_parsed_messages(app_handle, "dnp3", &self.session_id, self.client_id.as_deref(), self.peer, None, frame);
+            }
+        }
+    }
+
+    /// Emit the measurement objects of a response
+    fn objects(&self, source: u16, fragment: &AppFragment) {
+        let Some(app_handle) = &self.app_handle else { return };
+        if !fragment.is_response() || fragment.objects().next().is_none() {
+            return;
+        }
+
+        let objects: Vec<serde_json::Value> = fragment
+            .objects()
+            .filter_map(|(header, object)| {
+                let mut value = serde_json::to_value(object).ok()?;
+                value["group"] = header.group.into();
+                value["variation"] = header.variation.into();
+                Some(value)
+            })
+            .collect();
+        let payload = serde_json::json!({
+            "sessionId": self.session_id,
+            "clientId": self.client_id,
+            "source": source,
+            "function": dnp3::function_name(fragment.function),
+            "iin": fragment.iin,
+            "objects": objects,
+        });
+        if let Err(e) = app_handle.emit(DNP3_OBJECTS_EVENT, payload) {
+            eprintln!("DNP3: Failed to emit {} event: {}", DNP3_OBJECTS_EVENT, e);
+        }
+    }
+
+    async fn write(&self, writer: &mut OwnedWriteHalf, frames: Vec<Vec<u8>>) -> NetworkResult<()> {
+        for frame in frames {
+            writer.write_all(&frame).await.map_err(|e| NetworkError::SendFailed(e.to_string()))?;
+            self.frame(&frame, "out").await;
+        }
+        Ok(())
+    }
+}
+
+/// Drive one connection until it closes, the command channel closes or writing fails
+async fn run_link(
+    stream: TcpStream,
+    context: LinkContext,
+    mut commands: mpsc::Receiver<LinkCommand>,
+) -> NetworkResult<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    let master = context.outstation.is_none();
+    let mut link = Dnp3Link::new(&context.settings, master);
+    let mut schedule = master.then(|| PollSchedule::new(&context.settings, Instant::now()));
+    let mut framer = dnp3::frame_detector();
+    let mut buffer = [0u8; 1024];
+
+    loop {
+        if let Some(schedule) = &mut schedule {
+            for poll in schedule.due(Instant::now()) {
+                let frames = link.request(poll)?;
+                context.write(&mut writer, frames).await?;
+            }
+        }
+
+        let deadline = schedule
+            .as_ref()
+            .and_then(PollSchedule::next_deadline)
+            .unwrap_or_else(|| Instant::now() + Duration::from_secs(3600));
+        tokio::select! {
+            read = reader.read(&mut buffer) => {
+                let n = read.map_err(|e| NetworkError::ReceiveFailed(e.to_string()))?;
+                if n == 0 {
+                    return Ok(());
+                }
+                for frame in framer.detect_frames(&buffer[..n])? {
+                    context.frame(&frame.data, "in").await;
+                    let received = LinkFrame::decode(&frame.data).and_then(|decoded| {
+                        let source = decoded.header.source;
+                        link.receive(decoded).map(|(replies, fragment)| (source, replies, fragment))
+                    });
+                    let (source, replies, fragment) = match received {
+                        Ok(received) => received,
+                        Err(e) => {
+                            // A corrupted frame is dropped, as the link layer would
+                            eprintln!("DNP3: Session {} dropped a frame: {}", context.session_id, e);
+                            continue;
+                        }
+                    };
+                    context.write(&mut writer, replies).await?;
+                    let Some(fragment) = fragment else { continue };
+
+                    context.objects(source, &fragment);
+                    if let Some(outstation) = &context.outstation {
+                        let response = outstation.database.lock().unwrap().handle(&fragment);
+                        if let Some(response) = response {
+                            let frames = link.send(&response)?;
+                            context.write(&mut writer, frames).await?;
+                        }
+                    } else if fragment.is_response() && fragment.control.con {
+                        let unsolicited = fragment.function == FC_UNSOLICITED_RESPONSE;
+                        let frames = link.send(&AppFragment::confirm(fragment.control.seq, unsolicited))?;
+                        context.write(&mut writer, frames).await?;
+                    }
+                }
+            }
+            command = commands.recv() => {
+                let frames = match command {
+                    Some(LinkCommand::Frame(frame)) => vec![frame],
+                    Some(LinkCommand::Fragment(fragment)) => link.send(&fragment)?,
+                    Some(LinkCommand::Request(fragment)) => link.request(fragment)?,
+                    None => return Ok(()),
+                };
+                context.write(&mut writer, frames).await?;
+            },
+            _ = tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)) => {}
+        }
+    }
+}
+
+/// DNP3 master (client)
+#[derive(Debug)]
+pub struct Dnp3Client {
+    session_id: String,
+    host: String,
+    port: u16,
+    timeout: Duration,
+    settings: Dnp3Settings,
+    connected: Arc<AtomicBool>,
+    commands: Option<mpsc::Sender<LinkCommand>>,
+    link_task: Option<tokio::task::JoinHandle<()>>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+}
+
+impl Dnp3Client {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
+        let host = config.get("host")
+            .and_then(|v| v.as_str())
+            .unwrap_or("127.0.0.1")
+            .to_string();
+
+        let port = config.get("port")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(20000) as u16;
+
+        let timeout = config.get("timeout")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5000);
+
+        Ok(Self {
+            session_id,
+            host,
+            port,
+            timeout: Duration::from_millis(timeout),
+            settings: Dnp3Settings::from_config(&config)?,
+            connected: Arc::new(AtomicBool::new(false)),
+            commands: None,
+            link_task: None,
+            app_handle,
+            event_tx: None,
+        })
+    }
+
+    /// Read the static data and all events
+    pub async fn integrity_poll(&self) -> NetworkResult<()> {
+        self.command(LinkCommand::Request(AppFragment::integrity_poll(0))).await
+    }
+
+    /// Read the events of the given classes
+    pub async fn class_poll(&self, classes: &[u8]) -> NetworkResult<()> {
+        self.command(LinkCommand::Request(AppFragment::class_poll(0, classes))).await
+    }
+
+    async fn command(&self, command: LinkCommand) -> NetworkResult<()> {
+        let commands = self.commands.as_ref().ok_or(NetworkError::NotConnected)?;
+        commands.send(command).await.map_err(|_| NetworkError::NotConnected)
+    }
+}
+
+#[async_trait]
+impl Connection for Dnp3Client {
+    async fn connect(&mut self) -> NetworkResult<()> {
+        if self.connected.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let address = format!("{}:{}", self.host, self.port);
+        let stream = tokio::time::timeout(self.timeout, TcpStream::connect(&address))
+            .await
+            .map_err(|_| NetworkError::ConnectionFailed(format!("Connection to {} timed out", address)))?
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to connect to {}: {}", address, e)))?;
+
+        let (commands, command_rx) = mpsc::channel(COMMAND_QUEUE);
+        let context = LinkContext {
+            session_id: self.session_id.clone(),
+            client_id: None,
+            peer: stream.peer_addr().ok(),
+            settings: self.settings.clone(),
+            app_handle: self.app_handle.clone(),
+            event_tx: self.event_tx.clone(),
+            outstation: None,
+        };
+        let connected = self.connected.clone();
+        connected.store(true, Ordering::SeqCst);
+
+        self.link_task = Some(tokio::spawn(async move {
+            let result = run_link(stream, context.clone(), command_rx).await;
+            let was_connected = connected.swap(false, Ordering::SeqCst);
+            masters().lock().unwrap().remove(&context.session_id);
+            let error = result.err().map(|e| e.to_string());
+            eprintln!("DNP3Client: Session {} link closed: {}", context.session_id, error.as_deref().unwrap_or("by peer"));
+
+            if was_connected {
+                context.send_event("disconnected", None, error.clone()).await;
+                if let Some(app_handle) = &context.app_handle {
+                    let payload = serde_json::json!({
+                        "sessionId": context.session_id,
+                        "status": "disconnected",
+                        "error": error.unwrap_or_else(|| "Connection closed by outstation".to_string()),
+                    });
+                    if let Err(e) = app_handle.emit("connection-status", payload) {
+                        eprintln!("DNP3Client: Failed to emit connection-status event: {}", e);
+                    }
+                }
+            }
+        }));
+        masters().lock().unwrap().insert(self.session_id.clone(), commands.clone());
+        self.commands = Some(commands);
+
+        eprintln!("DNP3Client: Session {} connected to {}", self.session_id, address);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> NetworkResult<()> {
+        let was_connected = self.connected.swap(false, Ordering::SeqCst);
+        self.commands = None;
+        masters().lock().unwrap().remove(&self.session_id);
+        if let Some(task) = self.link_task.take() {
+            task.abort();
+        }
+        if was_connected {
+            if let Some(tx) = &self.event_tx {
+                let _ = tx.send(NetworkEvent {
+                    session_id: self.session_id.clone(),
+                    event_type: "disconnected".to_string(),
+                    data: None,
+                    error: None,
+                    client_id: None,
+                    mqtt_topic: None,
+                    mqtt_qos: None,
+                    mqtt_retain: None,
+                    sse_event: None,
+                }).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        if !self.connected.load(Ordering::SeqCst) {
+            return Err(NetworkError::NotConnected);
+        }
+        self.command(LinkCommand::from_bytes(data)?).await?;
+        Ok(data.len())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    fn status(&self) -> String {
+        if self.is_connected() {
+            format!(
+                "Master {} connected to outstation {} at {}:{}",
+                self.settings.master_address, self.settings.outstation_address, self.host, self.port
+            )
+        } else {
+            "Disconnected".to_string()
+        }
+    }
+
+    async fn start_receiving(&mut self) -> NetworkResult<mpsc::Receiver<NetworkEvent>> {
+        let (tx, rx) = mpsc::channel(100);
+        self.event_tx = Some(tx);
+        Ok(rx)
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// DNP3 outstation simulator (server)
+#[derive(Debug)]
+pub struct Dnp3Server {
+    session_id: String,
+    host: String,
+    port: u16,
+    actual_port: Option<u16>,
+    settings: Dnp3Settings,
+    outstation: Arc<Outstation>,
+    accept_task: Option<tokio::task::JoinHandle<()>>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+}
+
+impl Dnp3Server {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
+        let host = config.get("host")
+            .and_then(|v| v.as_str())
+            .unwrap_or("0.0.0.0")
+            .to_string();
+
+        let port = config.get("port")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(20000) as u16;
+
+        let settings = Dnp3Settings::from_config(&config)?;
+        let outstation = Arc::new(Outstation {
+            database: Mutex::new(PointDatabase::new(settings.points.clone())),
+            clients: Mutex::new(HashMap::new()),
+        });
+
+        Ok(Self {
+            session_id,
+            host,
+            port,
+            actual_port: None,
+            settings,
+            outstation,
+            accept_task: None,
+            app_handle,
+            event_tx: None,
+        })
+    }
+
+    /// Current point database
+    pub fn points(&self) -> Vec<Dnp3Point> {
+        self.outstation.database.lock().unwrap().points()
+    }
+
+    /// Change a point, buffering an event for its class
+    pub fn set_point(&self, kind: Dnp3PointKind, index: u16, value: f64) -> NetworkResult<()> {
+        self.outstation.database.lock().unwrap().set(kind, index, value)
+    }
+
+    fn client(&self, client_id: &str) -> NetworkResult<mpsc::Sender<LinkCommand>> {
+        self.outstation.clients.lock().unwrap().get(client_id).cloned().ok_or_else(|| {
+            NetworkError::SendFailed(format!("Client {} is not connected", client_id))
+        })
+    }
+}
+
+#[async_trait]
+impl Connection for Dnp3Server {
+    async fn connect(&mut self) -> NetworkResult<()> {
+        if self.accept_task.is_some() {
+            return Ok(());
+        }
+
+        let address = format!("{}:{}", self.host, self.port);
+        let listener = TcpListener::bind(&address)
+            .await
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to bind {}: {}", address, e)))?;
+        self.actual_port = listener.local_addr().ok().map(|addr| addr.port());
+
+        let context = LinkContext {
+            session_id: self.session_id.clone(),
+            client_id: None,
+            peer: None,
+            settings: self.settings.clone(),
+            app_handle: self.app_handle.clone(),
+            event_tx: self.event_tx.clone(),
+            outstation: Some(self.outstation.clone()),
+        };
+        let outstation = self.outstation.clone();
+
+        self.accept_task = Some(tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        eprintln!("DNP3Server: Accept failed for session {}: {}", context.session_id, e);
+                        continue;
+                    }
+                };
+                let client_id = peer.to_string();
+                let (commands, command_rx) = mpsc::channel(COMMAND_QUEUE);
+                outstation.clients.lock().unwrap().insert(client_id.clone(), commands);
+
+                let client = LinkContext { client_id: Some(client_id.clone()), peer: Some(peer), ..context.clone() };
+                client.send_event("client_connected", None, None).await;
+                if let Some(app_handle) = &client.app_handle {
+                    let payload = serde_json::json!({
+                        "sessionId": client.session_id,
+                        "clientId": client_id,
+                        "remoteAddress": peer.ip().to_string(),
+                        "remotePort": peer.port(),
+                    });
+                    if let Err(e) = app_handle.emit("client-connected", payload) {
+                        eprintln!("DNP3Server: Failed to emit client-connected event: {}", e);
+                    }
+                }
+
+                let outstation = outstation.clone();
+                tokio::spawn(async move {
+                    let result = run_link(stream, client.clone(), command_rx).await;
+                    outstation.clients.lock().unwrap().remove(&client_id);
+                    crate::parser::get_session_parsers().lock().unwrap().reset_stream(&client.session_id, Some(&client_id));
+
+                    let error = result.err().map(|e| e.to_string());
+                    eprintln!("DNP3Server: Client {} closed: {}", client_id, error.as_deref().unwrap_or("by peer"));
+                    client.send_event("client_disconnected", None, error).await;
+                    if let Some(app_handle) = &client.app_handle {
+                        let payload = serde_json::json!({
+                            "sessionId": client.session_id,
+                            "clientId": client_id,
+                        });
+                        if let Err(e) = app_handle.emit("client-disconnected", payload) {
+                            eprintln!("DNP3Server: Failed to emit client-disconnected event: {}", e);
+                        }
+                    }
+                });
+            }
+        }));
+        outstations().lock().unwrap().insert(self.session_id.clone(), self.outstation.clone());
+
+        eprintln!("DNP3Server: Session {} listening on {}:{}", self.session_id, self.host, self.actual_port.unwrap_or(self.port));
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> NetworkResult<()> {
+        if let Some(task) = self.accept_task.take() {
+            task.abort();
+        }
+        // Dropping the command senders ends every link task
+        self.outstation.clients.lock().unwrap().clear();
+        outstations().lock().unwrap().remove(&self.session_id);
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        self.broadcast(data).await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.accept_task.is_some()
+    }
+
+    fn status(&self) -> String {
+        if self.is_connected() {
+            format!(
+                "Outstation {} on {}:{} ({} clients)",
+                self.settings.outstation_address,
+                self.host,
+                self.actual_port.unwrap_or(self.port),
+                self.get_clients().len()
+            )
+        } else {
+            "Server stopped".to_string()
+        }
+    }
+
+    async fn start_receiving(&mut self) -> NetworkResult<mpsc::Receiver<NetworkEvent>> {
+        let (tx, rx) = mpsc::channel(100);
+        self.event_tx = Some(tx);
+        Ok(rx)
+    }
+
+    fn get_actual_port(&self) -> Option<u16> {
+        self.actual_port
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[async_trait]
+impl ServerConnection for Dnp3Server {
+    async fn send_to_client(&mut self, client_id: &str, data: &[u8]) -> NetworkResult<usize> {
+        let command = LinkCommand::from_bytes(data)?;
+        self.client(client_id)?
+            .send(command)
+            .await
+            .map_err(|_| NetworkError::SendFailed(format!("Client {} is not connected", client_id)))?;
+        Ok(data.len())
+    }
+
+    async fn broadcast(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        let command = LinkCommand::from_bytes(data)?;
+        if self.outstation.broadcast(command) == 0 {
+            return Err(NetworkError::SendFailed("No DNP3 masters connected".to_string()));
+        }
+        Ok(data.len())
+    }
+
+    fn get_clients(&self) -> Vec<String> {
+        self.outstation.clients.lock().unwrap().keys().cloned().collect()
+    }
+
+    async fn disconnect_client(&mut self, client_id: &str) -> NetworkResult<()> {
+        self.outstation
+            .clients
+            .lock()
+            .unwrap()
+            .remove(client_id)
+            .map(|_| ())
+            .ok_or_else(|| NetworkError::SendFailed(format!("Client {} is not connected", client_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> Dnp3Settings {
+        let point = |kind, index, value, class| Dnp3Point { kind, index, value, class, name: None };
+        Dnp3Settings {
+            points: vec![
+                point(Dnp3PointKind::Binary, 0, 1.0, 1),
+                point(Dnp3PointKind::Binary, 1, 0.0, 0),
+                point(Dnp3PointKind::Binary, 5, 0.0, 0),
+                point(Dnp3PointKind::Analog, 0, 230.5, 2),
+                point(Dnp3PointKind::Counter, 0, 1000.0, 3),
+            ],
+            ..Dnp3Settings::default()
+        }
+    }
+
+    #[test]
+    fn test_point_database_polls() {
+        let mut database = PointDatabase::new(settings().points);
+
+        let response = database.handle(&AppFragment::integrity_poll(2)).unwrap();
+        assert_eq!(response.control.seq, 2);
+        assert!(!response.control.con);
+        let names: Vec<String> = response.headers.iter().map(ObjectHeader::name).collect();
+        assert_eq!(names, vec!["g1v2", "g1v2", "g20v1", "g30v5"]);
+        assert_eq!(response.headers[0].range, ObjectRange::Range { start: 0, stop: 1 });
+        assert_eq!(response.objects().count(), 5);
+
+        database.set(Dnp3PointKind::Analog, 0, 231.0).unwrap();
+        database.set(Dnp3PointKind::Binary, 1, 1.0).unwrap();
+        database.set(Dnp3PointKind::Binary, 0, 0.0).unwrap();
+        assert_eq!(database.event_count(), 2);
+        assert!(database.set(Dnp3PointKind::Analog, 9, 0.0).is_err());
+
+        // Class 1 poll returns the binary event and still flags the class 2 event
+        let response = database.handle(&AppFragment::class_poll(3, &[1])).unwrap();
+        assert!(response.control.con);
+        assert_eq!(response.iin, Some(IIN_CLASS2_EVENTS));
+        assert_eq!(response.headers[0].name(), "g2v2");
+        assert_eq!(response.headers[0].objects[0].value, DataValue::Binary { state: false, flags: FLAG_ONLINE });
+        assert!(response.headers[0].objects[0].time.is_some());
+
+        // Events stay until confirmed with the matching sequence
+        assert!(database.handle(&AppFragment::confirm(4, false)).is_none());
+        assert_eq!(database.event_count(), 2);
+        database.handle(&AppFragment::confirm(3, false));
+        assert_eq!(database.event_count(), 1);
+
+        let response = database.handle(&AppFragment::request(FC_READ, 5, vec![ObjectHeader::all(40, 1)])).unwrap();
+        assert_eq!(response.iin, Some(IIN_CLASS2_EVENTS | IIN_OBJECT_UNKNOWN));
+    }
+
+    #[test]
+    fn test_link_layer() {
+        let settings = settings();
+        let mut master = Dnp3Link::new(&settings, true);
+        let mut outstation = Dnp3Link::new(&settings, false);
+
+        let status = LinkFrame::new(LinkHeader::primary(LINK_REQUEST_LINK_STATUS, true, 10, 1), Vec::new());
+        let (replies, fragment) = outstation.receive(status).unwrap();
+        assert!(fragment.is_none());
+        let reply = LinkFrame::decode(&replies[0]).unwrap();
+        assert_eq!((reply.header.function, reply.header.prm, reply.header.destination), (LINK_STATUS, false, 1));
+
+        // Requests are numbered by the master link
+        for expected in 0..2 {
+            let frames = master.request(AppFragment::integrity_poll(9)).unwrap();
+            let (replies, fragment) = outstation.receive(LinkFrame::decode(&frames[0]).unwrap()).unwrap();
+            assert!(replies.is_empty());
+            assert_eq!(fragment.unwrap().control.seq, expected);
+        }
+
+        // Frames for another outstation are ignored
+        let other = LinkFrame::new(LinkHeader::primary(LINK_CONFIRMED_USER_DATA, true, 11, 1), vec![0xC0, 0xC0, 0x01]);
+        assert_eq!(outstation.receive(other).unwrap(), (Vec::new(), None));
+
+        let schedule_settings = Dnp3Settings { integrity_interval: 60, class_interval: 5, ..settings };
+        let now = Instant::now();
+        let mut schedule = PollSchedule::new(&schedule_settings, now);
+        assert_eq!(schedule.due(now).len(), 1);
+        assert_eq!(schedule.next_deadline(), Some(now + Duration::from_secs(5)));
+        let polls = schedule.due(now + Duration::from_secs(5));
+        assert_eq!(polls[0].headers.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_loopback_integrity_poll() {
+        let config = serde_json::json!({
+            "host": "127.0.0.1",
+            "port": 0,
+            "dnp3": serde_json::to_value(settings()).unwrap(),
+        });
+        let mut server = Dnp3Server::new("dnp3-server".to_string(), config.clone(), None).unwrap();
+        server.connect().await.unwrap();
+
+        let mut client_config = config;
+        client_config["port"] = server.get_actual_port().unwrap().into();
+        let mut client = Dnp3Client::new("dnp3-client".to_string(), client_config, None).unwrap();
+        let mut events = client.start_receiving().await.unwrap();
+        client.connect().await.unwrap();
+
+        let mut reassembler = TransportReassembler::new();
+        let response = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = events.recv().await {
+                let frame = LinkFrame::decode(&event.data.unwrap()).unwrap();
+                if let Some(data) = reassembler.push(&frame.user_data) {
+                    return AppFragment::decode(&data).unwrap();
+                }
+            }
+            panic!("link closed before the response");
+        })
+        .await
+        .unwrap();
+
+        assert!(response.is_response());
+        let analog = response.objects().find(|(header, _)| header.group == 30).unwrap().1;
+        assert_eq!(analog.value, DataValue::Analog { value: 230.5, flags: FLAG_ONLINE });
+
+        // Events reach the master through a class poll
+        server.set_point(Dnp3PointKind::Counter, 0, 1001.0).unwrap();
+        client.class_poll(&[3]).await.unwrap();
+        let response = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = events.recv().await {
+                let frame = LinkFrame::decode(&event.data.unwrap()).unwrap();
+                if let Some(data) = reassembler.push(&frame.user_data) {
+                    return AppFragment::decode(&data).unwrap();
+                }
+            }
+            panic!("link closed before the response");
+        })
+        .await
+        .unwrap();
+        assert_eq!(response.headers[0].name(), "g22v5");
+        assert_eq!(response.headers[0].objects[0].value, DataValue::Counter { value: 1001, flags: FLAG_ONLINE });
+
+        client.disconnect().await.unwrap();
+        server.disconnect().await.unwrap();
+    }
+}
@@ -0,0 +1,295 @@
+//! Idle-gap (silent interval) framing of received data
+//!
+//! Modbus RTU and many serial protocols end a frame with a pause on the line
+//! rather than a delimiter or length. When a session has an idle gap set,
+//! received chunks are handed to a per-stream task that joins them until the
+//! line has been silent for the gap, so `message-received` and parsed
+//! messages carry the device's frames instead of arbitrary read chunks.
+
+use crate::types::{NetworkError, NetworkResult, SessionConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+
+/// Bits per character when not configured: start, 8 data, parity and stop
+const DEFAULT_BITS_PER_CHARACTER: u32 = 11;
+
+/// Shortest character-time gap above 19200 baud, as Modbus RTU recommends
+const MIN_CHARACTER_GAP: Duration = Duration::from_micros(1750);
+
+/// Data buffered without a gap before it is emitted anyway
+const MAX_FRAME_LENGTH: usize = 64 * 1024;
+
+/// Idle gap that ends a frame, read from `idleGap` in the session config
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdleGapSettings {
+    /// Fixed gap in milliseconds; takes precedence over character times
+    #[serde(default)]
+    pub gap_ms: Option<f64>,
+
+    /// Gap in character times, e.g. 3.5 for Modbus RTU
+    #[serde(default)]
+    pub characters: Option<f64>,
+
+    /// Line baud rate for character times; serial sessions default to their own
+    #[serde(default)]
+    pub baud_rate: Option<u32>,
+
+    /// Bits per character including start, parity and stop bits (default 11)
+    #[serde(default)]
+    pub bits_per_character: Option<u32>,
+}
+
+impl IdleGapSettings {
+    /// Resolve the gap, using `line_baud` when no baud rate is configured
+    pub fn gap(&self, line_baud: Option<u32>) -> NetworkResult<Duration> {
+        if let Some(gap_ms) = self.gap_ms {
+            if !gap_ms.is_finite() || gap_ms <= 0.0 {
+                return Err(NetworkError::InvalidConfig(format!("Idle gap must be positive, got {} ms", gap_ms)));
+            }
+            return Ok(Duration::from_secs_f64(gap_ms / 1000.0));
+        }
+
+        let characters = self.characters.ok_or_else(|| {
+            NetworkError::InvalidConfig("Idle gap needs gapMs or characters".to_string())
+        })?;
+        if !characters.is_finite() || characters <= 0.0 {
+            return Err(NetworkError::InvalidConfig(format!("Idle gap must be positive, got {} characters", characters)));
+        }
+        let baud_rate = self.baud_rate.or(line_baud).filter(|baud| *baud > 0).ok_or_else(|| {
+            NetworkError::InvalidConfig("Idle gap in characters needs a baud rate".to_string())
+        })?;
+        let bits = self.bits_per_character.unwrap_or(DEFAULT_BITS_PER_CHARACTER);
+        if bits == 0 {
+            return Err(NetworkError::InvalidConfig("Bits per character must be positive".to_string()));
+        }
+
+        let gap = Duration::from_secs_f64(characters * bits as f64 / baud_rate as f64);
+        if baud_rate > 19200 {
+            Ok(gap.max(MIN_CHARACTER_GAP))
+        } else {
+            Ok(gap)
+        }
+    }
+}
+
+/// Joins received chunks into frames separated by idle gaps
+#[derive(Debug, Clone)]
+pub struct IdleGapFramer {
+    gap: Duration,
+    buffer: Vec<u8>,
+    last_received: Option<Instant>,
+}
+
+impl IdleGapFramer {
+    pub fn new(gap: Duration) -> Self {
+        Self { gap, buffer: Vec::new(), last_received: None }
+    }
+
+    /// Add a chunk received at `at`, returning the frames it completes
+    pub fn push(&mut self, data: &[u8], at: Instant) -> Vec<Vec<u8>> {
+        let mut frames: Vec<Vec<u8>> = self.flush_idle(at).into_iter().collect();
+        self.buffer.extend_from_slice(data);
+        self.last_received = Some(at);
+        if self.buffer.len() >= MAX_FRAME_LENGTH {
+            frames.extend(self.flush());
+        }
+        frames
+    }
+
+    /// Take the buffered frame if the line has been idle for the gap at `now`
+    pub fn flush_idle(&mut self, now: Instant) -> Option<Vec<u8>> {
+        let idle = self.last_received.is_some_and(|last| now.saturating_duration_since(last) >= self.gap);
+        if idle {
+            self.flush()
+        } else {
+            None
+        }
+    }
+
+    /// Take the buffered frame regardless of timing
+    pub fn flush(&mut self) -> Option<Vec<u8>> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        Some(std::mem::take(&mut self.buffer))
+    }
+
+    /// When the buffered frame completes if nothing else arrives
+    pub fn deadline(&self) -> Option<Instant> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+        self.last_received.map(|last| last + self.gap)
+    }
+}
+
+type StreamKey = (String, Option<String>);
+
+/// Sender of chunks with their arrival time to a framing task
+type ChunkSender = mpsc::UnboundedSender<(Instant, Vec<u8>)>;
+
+/// Idle gaps of sessions with idle-gap framing
+static GAPS: OnceLock<Mutex<HashMap<String, Duration>>> = OnceLock::new();
+
+/// Chunk channels of running framing tasks
+static STREAMS: OnceLock<Mutex<HashMap<StreamKey, ChunkSender>>> = OnceLock::new();
+
+fn gaps() -> &'static Mutex<HashMap<String, Duration>> {
+    GAPS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn streams() -> &'static Mutex<HashMap<StreamKey, ChunkSender>> {
+    STREAMS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Apply a session config's idle gap, flushing the session's pending frames
+pub fn configure_session(session_id: &str, config: &SessionConfig) -> NetworkResult<()> {
+    let gap = match &config.idle_gap {
+        Some(settings) => Some(settings.gap(config.serial.as_ref().map(|serial| serial.baud_rate))?),
+        None => None,
+    };
+    set_session_gap(session_id, gap);
+    Ok(())
+}
+
+/// Set or clear a session's idle gap, flushing its pending frames
+pub fn set_session_gap(session_id: &str, gap: Option<Duration>) {
+    remove_session(session_id);
+    if let Some(gap) = gap {
+        gaps().lock().unwrap().insert(session_id.to_string(), gap);
+    }
+}
+
+/// Forget a session's idle gap, flushing its pending frames
+pub fn remove_session(session_id: &str) {
+    gaps().lock().unwrap().remove(session_id);
+    // Dropping the senders makes the tasks emit what they hold and exit
+    streams().lock().unwrap().retain(|(session, _), _| session != session_id);
+}
+
+/// Flush and stop the framing task of one client
+pub fn close_stream(session_id: &str, client_id: Option<&str>) {
+    streams().lock().unwrap().remove(&(session_id.to_string(), client_id.map(str::to_string)));
+}
+
+/// Hand a received chunk to the session's framing task; returns it back when
+/// the session has no idle gap
+pub(crate) fn deliver<'a>(
+    app_handle: &AppHandle,
+    transport: &str,
+    session_id: &str,
+    client_id: Option<&str>,
+    peer: Option<SocketAddr>,
+    data: &'a [u8],
+) -> Option<&'a [u8]> {
+    let Some(gap) = gaps().lock().unwrap().get(session_id).copied() else {
+        return Some(data);
+    };
+
+    let received = Instant::now();
+    let key = (session_id.to_string(), client_id.map(str::to_string));
+    let mut streams = streams().lock().unwrap();
+    if let Some(sender) = streams.get(&key) {
+        if sender.send((received, data.to_vec())).is_ok() {
+            return None;
+        }
+    }
+
+    let (sender, chunks) = mpsc::unbounded_channel();
+    let _ = sender.send((received, data.to_vec()));
+    streams.insert(key, sender);
+    tokio::spawn(run_stream(
+        chunks,
+        IdleGapFramer::new(gap),
+        app_handle.clone(),
+        transport.to_string(),
+        session_id.to_string(),
+        client_id.map(str::to_string),
+        peer,
+    ));
+    None
+}
+
+async fn run_stream(
+    mut chunks: mpsc::UnboundedReceiver<(Instant, Vec<u8>)>,
+    mut framer: IdleGapFramer,
+    app_handle: AppHandle,
+    transport: String,
+    session_id: String,
+    client_id: Option<String>,
+    peer: Option<SocketAddr>,
+) {
+    let emit = |frame: Vec<u8>| {
+        crate::network::emit_received_frame(&app_handle, &transport, &session_id, client_id.as_deref(), peer, None, &frame);
+    };
+
+    loop {
+        let chunk = match framer.deadline() {
+            Some(deadline) => {
+                match tokio::time::timeout_at(tokio::time::Instant::from_std(deadline), chunks.recv()).await {
+                    Ok(chunk) => chunk,
+                    Err(_) => {
+                        framer.flush_idle(Instant::now()).into_iter().for_each(emit);
+                        continue;
+                    }
+                }
+            }
+            None => chunks.recv().await,
+        };
+
+        match chunk {
+            Some((received, data)) => framer.push(&data, received).into_iter().for_each(emit),
+            None => {
+                framer.flush().into_iter().for_each(emit);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gap_settings() {
+        let modbus = IdleGapSettings { characters: Some(3.5), ..Default::default() };
+        // 3.5 characters of 11 bits at 9600 baud
+        assert_eq!(modbus.gap(Some(9600)).unwrap().as_micros(), 4010);
+        assert_eq!(modbus.gap(Some(115200)).unwrap(), MIN_CHARACTER_GAP);
+        assert!(modbus.gap(None).is_err());
+
+        let fixed = IdleGapSettings { gap_ms: Some(20.0), characters: Some(3.5), ..Default::default() };
+        assert_eq!(fixed.gap(None).unwrap(), Duration::from_millis(20));
+        assert!(IdleGapSettings::default().gap(Some(9600)).is_err());
+        assert!(IdleGapSettings { gap_ms: Some(0.0), ..Default::default() }.gap(None).is_err());
+    }
+
+    #[test]
+    fn test_framer_splits_on_gaps() {
+        let gap = Duration::from_millis(5);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut framer = IdleGapFramer::new(gap);
+
+        assert!(framer.push(&[0x01, 0x03], at(0)).is_empty());
+        assert!(framer.push(&[0x02, 0x00], at(2)).is_empty());
+        assert_eq!(framer.deadline(), Some(at(7)));
+        assert!(framer.flush_idle(at(6)).is_none());
+
+        // A chunk after the gap completes the previous frame
+        assert_eq!(framer.push(&[0x11], at(20)), vec![vec![0x01, 0x03, 0x02, 0x00]]);
+        assert_eq!(framer.flush_idle(at(25)), Some(vec![0x11]));
+        assert_eq!(framer.deadline(), None);
+        assert!(framer.flush().is_none());
+
+        let frames = framer.push(&vec![0u8; MAX_FRAME_LENGTH], at(30));
+        assert_eq!(frames.len(), 1);
+    }
+}
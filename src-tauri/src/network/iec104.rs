@@ -0,0 +1,1174 @@
+//! IEC 60870-5-104 client and server sessions
+//!
+//! `Iec104Client` connects to a controlled station, opens data transfer with
+//! STARTDT and exchanges ASDUs; `Iec104Server` simulates a controlled station
+//! that answers station interrogations and single commands from a point
+//! table. Both run the link layer through `Iec104Link`: send and receive
+//! sequence numbers, the k window of unacknowledged I-frames, an S-frame
+//! after w received I-frames, and the t1 (acknowledgement), t2 (supervisory
+//! acknowledgement) and t3 (idle test frame) timers. Data written to either
+//! session may be a complete APDU or a bare ASDU; sequence numbers are always
+//! assigned by the link.
+
+use async_trait::async_trait;
+use crate::network::{Connection, ServerConnection};
+use crate::parser::iec104::{
+    self, Apci, Apdu, Asdu, Cp56Time2a, InformationObject, ObjectValue, UFunction, BROADCAST_ADDRESS,
+    COT_ACTIVATION, COT_ACTIVATION_CON, COT_ACTIVATION_TERM, COT_DEACTIVATION, COT_DEACTIVATION_CON,
+    COT_INTERROGATED, COT_REMOTE_COMMAND, COT_SPONTANEOUS, COT_UNKNOWN_CAUSE, COT_UNKNOWN_COMMON_ADDRESS,
+    COT_UNKNOWN_OBJECT_ADDRESS, COT_UNKNOWN_TYPE, C_IC_NA_1, C_SC_NA_1, C_SC_TA_1, M_IT_NA_1, M_IT_TB_1,
+    M_ME_NC_1, M_ME_TF_1, M_SP_NA_1, M_SP_TB_1, SEQUENCE_MODULO,
+};
+use crate::types::{NetworkError, NetworkEvent, NetworkResult};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::OwnedWriteHalf;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Requests queued per link task
+const COMMAND_QUEUE: usize = 64;
+
+/// Link parameters and the simulated point table, read from `iec104` in the session config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Iec104Settings {
+    /// Common address of the station
+    pub common_address: u16,
+    /// Most unacknowledged I-frames sent
+    pub k: u16,
+    /// Received I-frames acknowledged at the latest
+    pub w: u16,
+    /// Seconds to wait for an acknowledgement or U-frame confirmation
+    pub t1: u64,
+    /// Seconds before received I-frames are acknowledged with an S-frame
+    pub t2: u64,
+    /// Idle seconds before a TESTFR is sent
+    pub t3: u64,
+    /// Points served in server mode
+    pub points: Vec<Iec104Point>,
+}
+
+impl Default for Iec104Settings {
+    fn default() -> Self {
+        Self {
+            common_address: 1,
+            k: 12,
+            w: 8,
+            t1: 15,
+            t2: 10,
+            t3: 20,
+            points: Vec::new(),
+        }
+    }
+}
+
+impl Iec104Settings {
+    /// Read the settings from a session config, falling back to the standard defaults
+    pub fn from_config(config: &serde_json::Value) -> NetworkResult<Self> {
+        let settings: Self = match config.get("iec104") {
+            Some(value) if !value.is_null() => serde_json::from_value(value.clone())
+                .map_err(|e| NetworkError::InvalidConfig(format!("Invalid IEC 104 settings: {}", e)))?,
+            _ => Self::default(),
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Check the parameters against the ranges IEC 60870-5-104 allows
+    pub fn validate(&self) -> NetworkResult<()> {
+        if self.k == 0 || self.k >= SEQUENCE_MODULO {
+            return Err(NetworkError::InvalidConfig(format!("k must be 1..32767, got {}", self.k)));
+        }
+        if self.w == 0 || self.w > self.k {
+            return Err(NetworkError::InvalidConfig(format!("w must be 1..=k ({}), got {}", self.k, self.w)));
+        }
+        if self.t1 == 0 || self.t3 == 0 {
+            return Err(NetworkError::InvalidConfig("t1 and t3 must be at least one second".to_string()));
+        }
+        if self.t2 >= self.t1 {
+            return Err(NetworkError::InvalidConfig(format!("t2 ({}s) must be shorter than t1 ({}s)", self.t2, self.t1)));
+        }
+        Ok(())
+    }
+}
+
+/// Kind of a simulated point
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Iec104PointKind {
+    /// Single-point information, switchable by single commands
+    Single,
+    /// Short floating point measured value
+    Float,
+    /// Integrated total
+    Counter,
+}
+
+/// Simulated information object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Iec104Point {
+    pub address: u32,
+    pub kind: Iec104PointKind,
+    #[serde(default)]
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Iec104Point {
+    fn object(&self, time: Option<Cp56Time2a>) -> InformationObject {
+        let value = match self.kind {
+            Iec104PointKind::Single => ObjectValue::SinglePoint { value: self.value != 0.0, quality: 0 },
+            Iec104PointKind::Float => ObjectValue::ShortFloat { value: self.value as f32, quality: 0 },
+            Iec104PointKind::Counter => ObjectValue::IntegratedTotal {
+                value: self.value as i32,
+                sequence: 0,
+                carry: false,
+                adjusted: false,
+                invalid: false,
+            },
+        };
+        InformationObject { address: self.address, value, time }
+    }
+
+    /// Type identification without and with time tag
+    fn type_ids(&self) -> (u8, u8) {
+        match self.kind {
+            Iec104PointKind::Single => (M_SP_NA_1, M_SP_TB_1),
+            Iec104PointKind::Float => (M_ME_NC_1, M_ME_TF_1),
+            Iec104PointKind::Counter => (M_IT_NA_1, M_IT_TB_1),
+        }
+    }
+}
+
+/// Answers of the simulated station to one received ASDU
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StationResponse {
+    /// ASDUs for the requesting client
+    pub replies: Vec<Asdu>,
+    /// ASDUs for every connected client
+    pub spontaneous: Vec<Asdu>,
+}
+
+/// Point table of a simulated controlled station
+#[derive(Debug, Clone)]
+pub struct PointTable {
+    common_address: u16,
+    points: BTreeMap<u32, Iec104Point>,
+}
+
+impl PointTable {
+    pub fn new(common_address: u16, points: Vec<Iec104Point>) -> Self {
+        Self {
+            common_address,
+            points: points.into_iter().map(|point| (point.address, point)).collect(),
+        }
+    }
+
+    /// Points ordered by address
+    pub fn points(&self) -> Vec<Iec104Point> {
+        self.points.values().cloned().collect()
+    }
+
+    /// Change a point and build its time-tagged spontaneous report
+    pub fn set(&mut self, address: u32, value: f64) -> NetworkResult<Asdu> {
+        let point = self.points.get_mut(&address).ok_or_else(|| {
+            NetworkError::InvalidConfig(format!("Information object {} is not in the point table", address))
+        })?;
+        point.value = value;
+        Ok(self.spontaneous(address, COT_SPONTANEOUS))
+    }
+
+    /// Answer an ASDU received from a controlling station
+    pub fn handle(&mut self, asdu: &Asdu) -> StationResponse {
+        let mut response = StationResponse::default();
+        if asdu.common_address != self.common_address && asdu.common_address != BROADCAST_ADDRESS {
+            response.replies.push(asdu.mirror(COT_UNKNOWN_COMMON_ADDRESS, true));
+            return response;
+        }
+
+        match (asdu.type_id, asdu.cause) {
+            (C_IC_NA_1, COT_ACTIVATION) => {
+                response.replies.push(asdu.mirror(COT_ACTIVATION_CON, false));
+                response.replies.extend(self.interrogation());
+                response.replies.push(asdu.mirror(COT_ACTIVATION_TERM, false));
+            }
+            (C_IC_NA_1, COT_DEACTIVATION) | (C_SC_NA_1 | C_SC_TA_1, COT_DEACTIVATION) => {
+                response.replies.push(asdu.mirror(COT_DEACTIVATION_CON, false));
+            }
+            (C_SC_NA_1 | C_SC_TA_1, COT_ACTIVATION) => {
+                let command = asdu.objects.first().and_then(|object| match object.value {
+                    ObjectValue::SingleCommand { state, select, .. } => Some((object.address, state, select)),
+                    _ => None,
+                });
+                let switchable = command.filter(|(address, _, _)| {
+                    self.points.get(address).is_some_and(|point| point.kind == Iec104PointKind::Single)
+                });
+                match switchable {
+                    Some((_, _, true)) => response.replies.push(asdu.mirror(COT_ACTIVATION_CON, false)),
+                    Some((address, state, false)) => {
+                        if let Some(point) = self.points.get_mut(&address) {
+                            point.value = if state { 1.0 } else { 0.0 };
+                        }
+                        response.replies.push(asdu.mirror(COT_ACTIVATION_CON, false));
+                        response.spontaneous.push(self.spontaneous(address, COT_REMOTE_COMMAND));
+                        response.replies.push(asdu.mirror(COT_ACTIVATION_TERM, false));
+                    }
+                    None => response.replies.push(asdu.mirror(COT_UNKNOWN_OBJECT_ADDRESS, true)),
+                }
+            }
+            (C_IC_NA_1 | C_SC_NA_1 | C_SC_TA_1, _) => response.replies.push(asdu.mirror(COT_UNKNOWN_CAUSE, true)),
+            _ => response.replies.push(asdu.mirror(COT_UNKNOWN_TYPE, true)),
+        }
+        response
+    }
+
+    /// Every point without time tag, grouped by type and split to fit the APDU size
+    fn interrogation(&self) -> Vec<Asdu> {
+        let mut groups: BTreeMap<u8, Vec<InformationObject>> = BTreeMap::new();
+        for point in self.points.values() {
+            groups.entry(point.type_ids().0).or_default().push(point.object(None));
+        }
+
+        groups
+            .into_iter()
+            .flat_map(|(type_id, objects)| {
+                objects
+                    .chunks(iec104::max_objects(type_id))
+                    .map(|chunk| Asdu::new(type_id, COT_INTERROGATED, self.common_address, chunk.to_vec()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn spontaneous(&self, address: u32, cause: u8) -> Asdu {
+        let point = &self.points[&address];
+        let time = Cp56Time2a::from_datetime(&chrono::Local::now().naive_local());
+        Asdu::new(point.type_ids().1, cause, self.common_address, vec![point.object(Some(time))])
+    }
+}
+
+/// Link layer state of one IEC 104 connection
+///
+/// Frames to write are collected and handed out by `poll`, which also runs
+/// the timers; `next_deadline` tells when `poll` has to run again.
+#[derive(Debug)]
+pub struct Iec104Link {
+    k: u16,
+    w: u16,
+    t1: Duration,
+    t2: Duration,
+    t3: Duration,
+    active: bool,
+    send_seq: u16,
+    recv_seq: u16,
+    acked_seq: u16,
+    unacked_received: u16,
+    ack_deadline: Option<Instant>,
+    pending_control: Option<(UFunction, Instant)>,
+    t2_deadline: Option<Instant>,
+    t3_deadline: Instant,
+    queue: VecDeque<Asdu>,
+    outbox: Vec<Apdu>,
+}
+
+impl Iec104Link {
+    pub fn new(settings: &Iec104Settings, now: Instant) -> Self {
+        let t3 = Duration::from_secs(settings.t3);
+        Self {
+            k: settings.k,
+            w: settings.w,
+            t1: Duration::from_secs(settings.t1),
+            t2: Duration::from_secs(settings.t2),
+            t3,
+            active: false,
+            send_seq: 0,
+            recv_seq: 0,
+            acked_seq: 0,
+            unacked_received: 0,
+            ack_deadline: None,
+            pending_control: None,
+            t2_deadline: None,
+            t3_deadline: now + t3,
+            queue: VecDeque::new(),
+            outbox: Vec::new(),
+        }
+    }
+
+    /// Whether data transfer has been started with STARTDT
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Sent I-frames the peer has not acknowledged yet
+    pub fn outstanding(&self) -> u16 {
+        self.send_seq.wrapping_sub(self.acked_seq) % SEQUENCE_MODULO
+    }
+
+    /// ASDUs waiting for STARTDT or for the k window to open
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Queue an ASDU for sending
+    pub fn queue(&mut self, asdu: Asdu) {
+        self.queue.push_back(asdu);
+    }
+
+    /// Send a U-frame; activations wait t1 for their confirmation
+    pub fn control(&mut self, function: UFunction, now: Instant) {
+        if function.confirmation().is_some() {
+            self.pending_control = Some((function, now + self.t1));
+        }
+        self.outbox.push(Apdu::u_frame(function));
+    }
+
+    /// Process a received APDU, returning the ASDU it carries
+    pub fn receive(&mut self, apdu: Apdu, now: Instant) -> NetworkResult<Option<Asdu>> {
+        self.t3_deadline = now + self.t3;
+
+        match apdu.apci {
+            Apci::I { send_seq, recv_seq } => {
+                if !self.active {
+                    return Err(NetworkError::ProtocolError("I-format APDU received before STARTDT".to_string()));
+                }
+                if send_seq != self.recv_seq {
+                    return Err(NetworkError::ProtocolError(format!(
+                        "Expected N(S) {} but received {}",
+                        self.recv_seq, send_seq
+                    )));
+                }
+                self.acknowledge(recv_seq, now)?;
+                self.recv_seq = (self.recv_seq + 1) % SEQUENCE_MODULO;
+                self.unacked_received += 1;
+                if self.unacked_received >= self.w {
+                    self.send_ack();
+                } else if self.t2_deadline.is_none() {
+                    self.t2_deadline = Some(now + self.t2);
+                }
+                Ok(apdu.asdu)
+            }
+            Apci::S { recv_seq } => {
+                self.acknowledge(recv_seq, now)?;
+                Ok(None)
+            }
+            Apci::U { function } => {
+                if let Some(confirmation) = function.confirmation() {
+                    match function {
+                        UFunction::StartDtAct => self.active = true,
+                        UFunction::StopDtAct => {
+                            self.active = false;
+                            if self.unacked_received > 0 {
+                                self.send_ack();
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.outbox.push(Apdu::u_frame(confirmation));
+                } else {
+                    if self.pending_control.and_then(|(pending, _)| pending.confirmation()) == Some(function) {
+                        self.pending_control = None;
+                    }
+                    match function {
+                        UFunction::StartDtCon => self.active = true,
+                        UFunction::StopDtCon => self.active = false,
+                        _ => {}
+                    }
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Run the timers and release queued ASDUs, returning the frames to write
+    ///
+    /// Fails when t1 expires; the connection has to be closed then.
+    pub fn poll(&mut self, now: Instant) -> NetworkResult<Vec<Apdu>> {
+        if self.ack_deadline.is_some_and(|deadline| now >= deadline) {
+            return Err(NetworkError::ProtocolError(format!(
+                "t1 expired with {} unacknowledged I-frames",
+                self.outstanding()
+            )));
+        }
+        if let Some((function, deadline)) = self.pending_control {
+            if now >= deadline {
+                return Err(NetworkError::ProtocolError(format!("t1 expired waiting for {} confirmation", function.name())));
+            }
+        }
+        if self.t2_deadline.is_some_and(|deadline| now >= deadline) {
+            self.send_ack();
+        }
+        if now >= self.t3_deadline {
+            if self.pending_control.is_none() {
+                self.control(UFunction::TestFrAct, now);
+            }
+            self.t3_deadline = now + self.t3;
+        }
+
+        while self.active && self.outstanding() < self.k {
+            let Some(asdu) = self.queue.pop_front() else { break };
+            self.outbox.push(Apdu::i_frame(self.send_seq, self.recv_seq, asdu));
+            self.send_seq = (self.send_seq + 1) % SEQUENCE_MODULO;
+            self.ack_deadline.get_or_insert(now + self.t1);
+            // The I-frame carries N(R), acknowledging everything received
+            self.unacked_received = 0;
+            self.t2_deadline = None;
+        }
+
+        Ok(std::mem::take(&mut self.outbox))
+    }
+
+    /// Earliest time `poll` has work to do
+    pub fn next_deadline(&self) -> Instant {
+        [
+            self.ack_deadline,
+            self.pending_control.map(|(_, deadline)| deadline),
+            self.t2_deadline,
+        ]
+        .into_iter()
+        .flatten()
+        .fold(self.t3_deadline, Instant::min)
+    }
+
+    fn send_ack(&mut self) {
+        self.outbox.push(Apdu::s_frame(self.recv_seq));
+        self.unacked_received = 0;
+        self.t2_deadline = None;
+    }
+
+    fn acknowledge(&mut self, recv_seq: u16, now: Instant) -> NetworkResult<()> {
+        let acked = recv_seq.wrapping_sub(self.acked_seq) % SEQUENCE_MODULO;
+        if acked > self.outstanding() {
+            return Err(NetworkError::ProtocolError(format!(
+                "N(R) {} acknowledges I-frames that were not sent (next N(S) {})",
+                recv_seq, self.send_seq
+            )));
+        }
+        self.acked_seq = recv_seq;
+        self.ack_deadline = if self.outstanding() == 0 {
+            None
+        } else if acked > 0 {
+            Some(now + self.t1)
+        } else {
+            self.ack_deadline
+        };
+        Ok(())
+    }
+}
+
+/// Request handed to a running link task
+#[derive(Debug, Clone)]
+enum LinkCommand {
+    Send(Asdu),
+    Control(UFunction),
+}
+
+impl LinkCommand {
+    /// Interpret data written to a session: a complete APDU or a bare ASDU
+    fn from_bytes(data: &[u8]) -> NetworkResult<Self> {
+        match Apdu::decode(data) {
+            Ok(Apdu { asdu: Some(asdu), .. }) => Ok(Self::Send(asdu)),
+            Ok(Apdu { apci: Apci::U { function }, .. }) => Ok(Self::Control(function)),
+            Ok(_) => Err(NetworkError::SendFailed(
+                "S-format APDUs are sent by the link layer".to_string(),
+            )),
+            Err(_) => Asdu::decode(data)
+                .map(Self::Send)
+                .map_err(|e| NetworkError::SendFailed(format!("Not an IEC 104 APDU or ASDU: {}", e))),
+        }
+    }
+}
+
+/// Simulated controlled station shared by the server's links
+#[derive(Debug)]
+struct Station {
+    table: Mutex<PointTable>,
+    clients: Mutex<HashMap<String, mpsc::Sender<LinkCommand>>>,
+}
+
+impl Station {
+    fn broadcast(&self, command: LinkCommand) -> usize {
+        let clients = self.clients.lock().unwrap();
+        clients.values().filter(|sender| sender.try_send(command.clone()).is_ok()).count()
+    }
+}
+
+/// Stations of running server sessions
+static STATIONS: OnceLock<Mutex<HashMap<String, Arc<Station>>>> = OnceLock::new();
+
+fn stations() -> &'static Mutex<HashMap<String, Arc<Station>>> {
+    STATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn station(session_id: &str) -> NetworkResult<Arc<Station>> {
+    stations()
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| NetworkError::SessionNotFound(format!("{} is not a running IEC 104 server", session_id)))
+}
+
+/// Points of a running server session
+pub fn points(session_id: &str) -> NetworkResult<Vec<Iec104Point>> {
+    Ok(station(session_id)?.table.lock().unwrap().points())
+}
+
+/// Change a point of a running server session and report it spontaneously to its clients
+pub fn set_point(session_id: &str, address: u32, value: f64) -> NetworkResult<()> {
+    let station = station(session_id)?;
+    let report = station.table.lock().unwrap().set(address, value)?;
+    station.broadcast(LinkCommand::Send(report));
+    Ok(())
+}
+
+/// Where a link task reports its traffic
+#[derive(Debug, Clone)]
+struct LinkContext {
+    session_id: String,
+    client_id: Option<String>,
+    peer: Option<SocketAddr>,
+    settings: Iec104Settings,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+    station: Option<Arc<Station>>,
+}
+
+impl LinkContext {
+    async fn send_event(&self, event_type: &str, data: Option<Vec<u8>>, error: Option<String>) {
+        if let Some(tx) = &self.event_tx {
+            let event = NetworkEvent {
+                session_id: self.session_id.clone(),
+                event_type: event_type.to_string(),
+                data,
+                error,
+                client_id: self.client_id.clone(),
+                mqtt_topic: None,
+                mqtt_qos: None,
+                mqtt_retain: None,
+                sse_event: None,
+            };
+            let _ = tx.send(event).await;
+        }
+    }
+
+    async fn frame(&self, frame: &[u8], direction: &str) {
+        if direction == "in" {
+            self.send_event("data_received", Some(frame.to_vec()), None).await;
+        }
+        if let Some(app_handle) = &self.app_handle {
+            let payload = serde_json::json!({
+                "sessionId": self.session_id,
+                "data": frame.to_vec(),
+                "direction": direction,
+                "clientId": self.client_id,
+            });
+            if let Err(e) = app_handle.emit("message-received", payload) {
+                eprintln!("IEC104: Failed to emit message-received event: {}", e);
+            }
+            if direction == "in" {
+                crate::network::emit_parsed_messages(app_handle, "iec104", &self.session_id, self.client_id.as_deref(), self.peer, None, frame);
+            }
+        }
+    }
+
+    async fn write(&self, writer: &mut OwnedWriteHalf, apdu: &Apdu) -> NetworkResult<()> {
+        let frame = apdu.encode()?;
+        writer.write_all(&frame).await.map_err(|e| NetworkError::SendFailed(e.to_string()))?;
+        self.frame(&frame, "out").await;
+        Ok(())
+    }
+}
+
+/// Drive one connection until it closes, the command channel closes or the link fails
+async fn run_link(
+    stream: TcpStream,
+    context: LinkContext,
+    mut commands: mpsc::Receiver<LinkCommand>,
+) -> NetworkResult<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    let mut link = Iec104Link::new(&context.settings, Instant::now());
+    let mut framer = iec104::frame_detector();
+    let mut buffer = [0u8; 1024];
+
+    // The controlling station opens data transfer
+    if context.station.is_none() {
+        link.control(UFunction::StartDtAct, Instant::now());
+    }
+
+    loop {
+        for apdu in link.poll(Instant::now())? {
+            context.write(&mut writer, &apdu).await?;
+        }
+
+        let deadline = tokio::time::Instant::from_std(link.next_deadline());
+        tokio::select! {
+            read = reader.read(&mut buffer) => {
+                let n = read.map_err(|e| NetworkError::ReceiveFailed(e.to_string()))?;
+                if n == 0 {
+                    return Ok(());
+                }
+                for frame in framer.detect_frames(&buffer[..n])? {
+                    context.frame(&frame.data, "in").await;
+                    let apdu = Apdu::decode(&frame.data)?;
+                    let Some(asdu) = link.receive(apdu, Instant::now())? else { continue };
+                    if let Some(station) = &context.station {
+                        let response = station.table.lock().unwrap().handle(&asdu);
+                        response.replies.into_iter().for_each(|reply| link.queue(reply));
+                        for report in response.spontaneous {
+                            station.broadcast(LinkCommand::Send(report));
+                        }
+                    }
+                }
+            }
+            command = commands.recv() => match command {
+                Some(LinkCommand::Send(asdu)) => link.queue(asdu),
+                Some(LinkCommand::Control(function)) => link.control(function, Instant::now()),
+                None => return Ok(()),
+            },
+            _ = tokio::time::sleep_until(deadline) => {}
+        }
+    }
+}
+
+/// IEC 104 controlling station (client)
+#[derive(Debug)]
+pub struct Iec104Client {
+    session_id: String,
+    host: String,
+    port: u16,
+    timeout: Duration,
+    settings: Iec104Settings,
+    connected: Arc<AtomicBool>,
+    commands: Option<mpsc::Sender<LinkCommand>>,
+    link_task: Option<tokio::task::JoinHandle<()>>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+}
+
+impl Iec104Client {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
+        let host = config.get("host")
+            .and_then(|v| v.as_str())
+            .unwrap_or("127.0.0.1")
+            .to_string();
+
+        let port = config.get("port")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(2404) as u16;
+
+        let timeout = config.get("timeout")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(5000);
+
+        Ok(Self {
+            session_id,
+            host,
+            port,
+            timeout: Duration::from_millis(timeout),
+            settings: Iec104Settings::from_config(&config)?,
+            connected: Arc::new(AtomicBool::new(false)),
+            commands: None,
+            link_task: None,
+            app_handle,
+            event_tx: None,
+        })
+    }
+
+    /// Send a station interrogation to the configured common address
+    pub async fn interrogate(&self) -> NetworkResult<()> {
+        self.command(LinkCommand::Send(Asdu::interrogation(self.settings.common_address))).await
+    }
+
+    /// Send a single command to the configured common address
+    pub async fn single_command(&self, address: u32, state: bool, select: bool) -> NetworkResult<()> {
+        let asdu = Asdu::single_command(self.settings.common_address, address, state, select);
+        self.command(LinkCommand::Send(asdu)).await
+    }
+
+    /// Send STARTDT, STOPDT or TESTFR
+    pub async fn control(&self, function: UFunction) -> NetworkResult<()> {
+        self.command(LinkCommand::Control(function)).await
+    }
+
+    async fn command(&self, command: LinkCommand) -> NetworkResult<()> {
+        let commands = self.commands.as_ref().ok_or(NetworkError::NotConnected)?;
+        commands.send(command).await.map_err(|_| NetworkError::NotConnected)
+    }
+}
+
+#[async_trait]
+impl Connection for Iec104Client {
+    async fn connect(&mut self) -> NetworkResult<()> {
+        if self.connected.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let address = format!("{}:{}", self.host, self.port);
+        let stream = tokio::time::timeout(self.timeout, TcpStream::connect(&address))
+            .await
+            .map_err(|_| NetworkError::ConnectionFailed(format!("Connection to {} timed out", address)))?
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to connect to {}: {}", address, e)))?;
+
+        let (commands, command_rx) = mpsc::channel(COMMAND_QUEUE);
+        let context = LinkContext {
+            session_id: self.session_id.clone(),
+            client_id: None,
+            peer: stream.peer_addr().ok(),
+            settings: self.settings.clone(),
+            app_handle: self.app_handle.clone(),
+            event_tx: self.event_tx.clone(),
+            station: None,
+        };
+        let connected = self.connected.clone();
+        connected.store(true, Ordering::SeqCst);
+
+        self.link_task = Some(tokio::spawn(async move {
+            let result = run_link(stream, context.clone(), command_rx).await;
+            let was_connected = connected.swap(false, Ordering::SeqCst);
+            let error = result.err().map(|e| e.to_string());
+            eprintln!("IEC104Client: Session {} link closed: {}", context.session_id, error.as_deref().unwrap_or("by peer"));
+
+            if was_connected {
+                context.send_event("disconnected", None, error.clone()).await;
+                if let Some(app_handle) = &context.app_handle {
+                    let payload = serde_json::json!({
+                        "sessionId": context.session_id,
+                        "status": "disconnected",
+                        "error": error.unwrap_or_else(|| "Connection closed by server".to_string()),
+                    });
+                    if let Err(e) = app_handle.emit("connection-status", payload) {
+                        eprintln!("IEC104Client: Failed to emit connection-status event: {}", e);
+                    }
+                }
+            }
+        }));
+        self.commands = Some(commands);
+
+        eprintln!("IEC104Client: Session {} connected to {}", self.session_id, address);
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> NetworkResult<()> {
+        let was_connected = self.connected.swap(false, Ordering::SeqCst);
+        self.commands = None;
+        if let Some(task) = self.link_task.take() {
+            task.abort();
+        }
+        if was_connected {
+            if let Some(tx) = &self.event_tx {
+                let _ = tx.send(NetworkEvent {
+                    session_id: self.session_id.clone(),
+                    event_type: "disconnected".to_string(),
+                    data: None,
+                    error: None,
+                    client_id: None,
+                    mqtt_topic: None,
+                    mqtt_qos: None,
+                    mqtt_retain: None,
+                    sse_event: None,
+                }).await;
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        if !self.connected.load(Ordering::SeqCst) {
+            return Err(NetworkError::NotConnected);
+        }
+        self.command(LinkCommand::from_bytes(data)?).await?;
+        Ok(data.len())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    fn status(&self) -> String {
+        if self.is_connected() {
+            format!("Connected to {}:{} (CA {})", self.host, self.port, self.settings.common_address)
+        } else {
+            "Disconnected".to_string()
+        }
+    }
+
+    async fn start_receiving(&mut self) -> NetworkResult<mpsc::Receiver<NetworkEvent>> {
+        let (tx, rx) = mpsc::channel(100);
+        self.event_tx = Some(tx);
+        Ok(rx)
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+/// IEC 104 controlled station simulator (server)
+#[derive(Debug)]
+pub struct Iec104Server {
+    session_id: String,
+    host: String,
+    port: u16,
+    actual_port: Option<u16>,
+    settings: Iec104Settings,
+    station: Arc<Station>,
+    accept_task: Option<tokio::task::JoinHandle<()>>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+}
+
+impl Iec104Server {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
+        let host = config.get("host")
+            .and_then(|v| v.as_str())
+            .unwrap_or("0.0.0.0")
+            .to_string();
+
+        let port = config.get("port")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(2404) as u16;
+
+        let settings = Iec104Settings::from_config(&config)?;
+        let station = Arc::new(Station {
+            table: Mutex::new(PointTable::new(settings.common_address, settings.points.clone())),
+            clients: Mutex::new(HashMap::new()),
+        });
+
+        Ok(Self {
+            session_id,
+            host,
+            port,
+            actual_port: None,
+            settings,
+            station,
+            accept_task: None,
+            app_handle,
+            event_tx: None,
+        })
+    }
+
+    /// Current point table
+    pub fn points(&self) -> Vec<Iec104Point> {
+        self.station.table.lock().unwrap().points()
+    }
+
+    /// Change a point and report it spontaneously to every client
+    pub fn set_point(&self, address: u32, value: f64) -> NetworkResult<()> {
+        let report = self.station.table.lock().unwrap().set(address, value)?;
+        self.station.broadcast(LinkCommand::Send(report));
+        Ok(())
+    }
+
+    fn client(&self, client_id: &str) -> NetworkResult<mpsc::Sender<LinkCommand>> {
+        self.station.clients.lock().unwrap().get(client_id).cloned().ok_or_else(|| {
+            NetworkError::SendFailed(format!("Client {} is not connected", client_id))
+        })
+    }
+}
+
+#[async_trait]
+impl Connection for Iec104Server {
+    async fn connect(&mut self) -> NetworkResult<()> {
+        if self.accept_task.is_some() {
+            return Ok(());
+        }
+
+        let address = format!("{}:{}", self.host, self.port);
+        let listener = TcpListener::bind(&address)
+            .await
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to bind {}: {}", address, e)))?;
+        self.actual_port = listener.local_addr().ok().map(|addr| addr.port());
+
+        let context = LinkContext {
+            session_id: self.session_id.clone(),
+            client_id: None,
+            peer: None,
+            settings: self.settings.clone(),
+            app_handle: self.app_handle.clone(),
+            event_tx: self.event_tx.clone(),
+            station: Some(self.station.clone()),
+        };
+        let station = self.station.clone();
+
+        self.accept_task = Some(tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        eprintln!("IEC104Server: Accept failed for session {}: {}", context.session_id, e);
+                        continue;
+                    }
+                };
+                let client_id = peer.to_string();
+                let (commands, command_rx) = mpsc::channel(COMMAND_QUEUE);
+                station.clients.lock().unwrap().insert(client_id.clone(), commands);
+
+                let client = LinkContext { client_id: Some(client_id.clone()), peer: Some(peer), ..context.clone() };
+                client.send_event("client_connected", None, None).await;
+                if let Some(app_handle) = &client.app_handle {
+                    let payload = serde_json::json!({
+                        "sessionId": client.session_id,
+                        "clientId": client_id,
+                        "remoteAddress": peer.ip().to_string(),
+                        "remotePort": peer.port(),
+                    });
+                    if let Err(e) = app_handle.emit("client-connected", payload) {
+                        eprintln!("IEC104Server: Failed to emit client-connected event: {}", e);
+                    }
+                }
+
+                let station = station.clone();
+                tokio::spawn(async move {
+                    let result = run_link(stream, client.clone(), command_rx).await;
+                    station.clients.lock().unwrap().remove(&client_id);
+                    crate::parser::get_session_parsers().lock().unwrap().reset_stream(&client.session_id, Some(&client_id));
+
+                    let error = result.err().map(|e| e.to_string());
+                    eprintln!("IEC104Server: Client {} closed: {}", client_id, error.as_deref().unwrap_or("by peer"));
+                    client.send_event("client_disconnected", None, error).await;
+                    if let Some(app_handle) = &client.app_handle {
+                        let payload = serde_json::json!({
+                            "sessionId": client.session_id,
+                            "clientId": client_id,
+                        });
+                        if let Err(e) = app_handle.emit("client-disconnected", payload) {
+                            eprintln!("IEC104Server: Failed to emit client-disconnected event: {}", e);
+                        }
+                    }
+                });
+            }
+        }));
+        stations().lock().unwrap().insert(self.session_id.clone(), self.station.clone());
+
+        eprintln!("IEC104Server: Session {} listening on {}:{}", self.session_id, self.host, self.actual_port.unwrap_or(self.port));
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> NetworkResult<()> {
+        if let Some(task) = self.accept_task.take() {
+            task.abort();
+        }
+        // Dropping the command senders ends every link task
+        self.station.clients.lock().unwrap().clear();
+        stations().lock().unwrap().remove(&self.session_id);
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        self.broadcast(data).await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.accept_task.is_some()
+    }
+
+    fn status(&self) -> String {
+        if self.is_connected() {
+            format!(
+                "Station CA {} on {}:{} ({} clients)",
+                self.settings.common_address,
+                self.host,
+                self.actual_port.unwrap_or(self.port),
+                self.get_clients().len()
+            )
+        } else {
+            "Server stopped".to_string()
+        }
+    }
+
+    async fn start_receiving(&mut self) -> NetworkResult<mpsc::Receiver<NetworkEvent>> {
+        let (tx, rx) = mpsc::channel(100);
+        self.event_tx = Some(tx);
+        Ok(rx)
+    }
+
+    fn get_actual_port(&self) -> Option<u16> {
+        self.actual_port
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[async_trait]
+impl ServerConnection for Iec104Server {
+    async fn send_to_client(&mut self, client_id: &str, data: &[u8]) -> NetworkResult<usize> {
+        let command = LinkCommand::from_bytes(data)?;
+        self.client(client_id)?
+            .send(command)
+            .await
+            .map_err(|_| NetworkError::SendFailed(format!("Client {} is not connected", client_id)))?;
+        Ok(data.len())
+    }
+
+    async fn broadcast(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        let command = LinkCommand::from_bytes(data)?;
+        if self.station.broadcast(command) == 0 {
+            return Err(NetworkError::SendFailed("No IEC 104 clients connected".to_string()));
+        }
+        Ok(data.len())
+    }
+
+    fn get_clients(&self) -> Vec<String> {
+        self.station.clients.lock().unwrap().keys().cloned().collect()
+    }
+
+    async fn disconnect_client(&mut self, client_id: &str) -> NetworkResult<()> {
+        self.station
+            .clients
+            .lock()
+            .unwrap()
+            .remove(client_id)
+            .map(|_| ())
+            .ok_or_else(|| NetworkError::SendFailed(format!("Client {} is not connected", client_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(k: u16, w: u16) -> Iec104Settings {
+        Iec104Settings {
+            k,
+            w,
+            points: vec![
+                Iec104Point { address: 1, kind: Iec104PointKind::Single, value: 0.0, name: None },
+                Iec104Point { address: 2, kind: Iec104PointKind::Single, value: 1.0, name: None },
+                Iec104Point { address: 16385, kind: Iec104PointKind::Float, value: 220.5, name: None },
+                Iec104Point { address: 25601, kind: Iec104PointKind::Counter, value: 12345.0, name: None },
+            ],
+            ..Iec104Settings::default()
+        }
+    }
+
+    /// Deliver every frame one link writes to the other, returning the ASDUs received
+    fn deliver(from: &mut Iec104Link, to: &mut Iec104Link, now: Instant) -> Vec<Asdu> {
+        from.poll(now)
+            .unwrap()
+            .into_iter()
+            .filter_map(|apdu| {
+                let frame = apdu.encode().unwrap();
+                to.receive(Apdu::decode(&frame).unwrap(), now).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_link_windows_and_timers() {
+        let now = Instant::now();
+        let mut client = Iec104Link::new(&settings(12, 2), now);
+        let mut server = Iec104Link::new(&settings(3, 2), now);
+
+        client.control(UFunction::StartDtAct, now);
+        deliver(&mut client, &mut server, now);
+        assert!(server.is_active());
+        deliver(&mut server, &mut client, now);
+        assert!(client.is_active());
+
+        // k = 3 stops the server after three unacknowledged I-frames
+        for _ in 0..5 {
+            server.queue(Asdu::interrogation(1));
+        }
+        assert_eq!(deliver(&mut server, &mut client, now).len(), 3);
+        assert_eq!((server.outstanding(), server.queued()), (3, 2));
+
+        // w = 2 made the client acknowledge after the second frame; the third waits for t2
+        deliver(&mut client, &mut server, now);
+        assert_eq!(server.outstanding(), 1);
+        assert_eq!(client.next_deadline(), now + Duration::from_secs(10));
+        let later = now + Duration::from_secs(10);
+        assert_eq!(client.poll(later).unwrap(), vec![Apdu::s_frame(3)]);
+
+        // Nothing acknowledges the server's remaining frames: t1 expires
+        assert_eq!(deliver(&mut server, &mut client, now).len(), 2);
+        assert!(server.poll(now + Duration::from_secs(15)).is_err());
+
+        assert_eq!(client.poll(now).unwrap(), vec![Apdu::s_frame(5)]);
+
+        // Idle for t3 the client tests the link
+        let idle = now + Duration::from_secs(40);
+        assert_eq!(client.poll(idle).unwrap(), vec![Apdu::u_frame(UFunction::TestFrAct)]);
+
+        // Out of sequence I-frames are protocol errors
+        let wrong = Apdu::i_frame(7, 0, Asdu::interrogation(1));
+        assert!(client.receive(wrong, idle).is_err());
+    }
+
+    #[test]
+    fn test_point_table_commands() {
+        let mut table = PointTable::new(1, settings(12, 8).points);
+
+        let replies = table.handle(&Asdu::interrogation(1)).replies;
+        let causes: Vec<u8> = replies.iter().map(|asdu| asdu.cause).collect();
+        assert_eq!(causes, vec![COT_ACTIVATION_CON, COT_INTERROGATED, COT_INTERROGATED, COT_INTERROGATED, COT_ACTIVATION_TERM]);
+        assert_eq!(replies[1].type_id, M_SP_NA_1);
+        assert_eq!(replies[1].objects.len(), 2);
+        assert_eq!(replies[2].objects[0].value, ObjectValue::ShortFloat { value: 220.5, quality: 0 });
+
+        let response = table.handle(&Asdu::single_command(1, 1, true, false));
+        assert_eq!(response.replies.iter().map(|asdu| asdu.cause).collect::<Vec<_>>(), vec![COT_ACTIVATION_CON, COT_ACTIVATION_TERM]);
+        assert_eq!(response.spontaneous[0].type_id, M_SP_TB_1);
+        assert_eq!(response.spontaneous[0].objects[0].value, ObjectValue::SinglePoint { value: true, quality: 0 });
+        assert_eq!(table.points()[0].value, 1.0);
+
+        // Measured values cannot be switched
+        let rejected = table.handle(&Asdu::single_command(1, 16385, true, false)).replies;
+        assert_eq!((rejected[0].cause, rejected[0].negative), (COT_UNKNOWN_OBJECT_ADDRESS, true));
+        let rejected = table.handle(&Asdu::interrogation(9)).replies;
+        assert_eq!(rejected[0].cause, COT_UNKNOWN_COMMON_ADDRESS);
+    }
+
+    #[tokio::test]
+    async fn test_loopback_interrogation() {
+        let config = serde_json::json!({
+            "host": "127.0.0.1",
+            "port": 0,
+            "iec104": serde_json::to_value(settings(12, 8)).unwrap(),
+        });
+        let mut server = Iec104Server::new("iec104-server".to_string(), config.clone(), None).unwrap();
+        server.connect().await.unwrap();
+
+        let mut client_config = config;
+        client_config["port"] = server.get_actual_port().unwrap().into();
+        let mut client = Iec104Client::new("iec104-client".to_string(), client_config, None).unwrap();
+        let mut events = client.start_receiving().await.unwrap();
+        client.connect().await.unwrap();
+        client.interrogate().await.unwrap();
+
+        let mut received = Vec::new();
+        let finished = tokio::time::timeout(Duration::from_secs(5), async {
+            while let Some(event) = events.recv().await {
+                let apdu = Apdu::decode(&event.data.unwrap()).unwrap();
+                if let Some(asdu) = apdu.asdu {
+                    let done = asdu.cause == COT_ACTIVATION_TERM;
+                    received.push(asdu);
+                    if done {
+                        break;
+                    }
+                }
+            }
+        })
+        .await;
+        assert!(finished.is_ok(), "interrogation did not terminate");
+        assert_eq!(received.len(), 5);
+        assert_eq!(received[3].objects[0].address, 25601);
+
+        server.set_point(16385, 231.0).unwrap();
+        let report = tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap();
+        let asdu = Apdu::decode(&report.data.unwrap()).unwrap().asdu.unwrap();
+        assert_eq!((asdu.type_id, asdu.cause), (M_ME_TF_1, COT_SPONTANEOUS));
+
+        client.disconnect().await.unwrap();
+        server.disconnect().await.unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use crate::types::{NetworkResult, NetworkEvent};
 use tokio::sync::mpsc;
+use std::net::SocketAddr;
 
 pub use connection_manager::ConnectionManager;
 
@@ -10,6 +11,15 @@ pub mod websocket;
 pub mod mqtt;
 pub mod sse;
 pub mod modbus;
+pub mod modbus_slave;
+pub mod modbus_rtu_slave;
+pub mod modbus_poll;
+pub mod modbus_functions;
+pub mod modbus_transport;
+pub mod iec104;
+pub mod dnp3;
+pub mod serial;
+pub mod idle_gap;
 pub mod connection_manager;
 
 /// Core connection trait that all network protocols must implement
@@ -123,26 +133,50 @@ impl ConnectionFactory {
             }
             "websocket" | "ws" => {
                 if connection_type == "server" {
-                    Ok(Box::new(websocket::WebSocketServer::new(session_id, config)?))
+                    Ok(Box::new(websocket::WebSocketServer::new(session_id, config, app_handle)?))
                 } else {
-                    Ok(Box::new(websocket::WebSocketClient::new(session_id, config)?))
+                    Ok(Box::new(websocket::WebSocketClient::new(session_id, config, app_handle)?))
                 }
             }
             "mqtt" => {
-                Ok(Box::new(mqtt::MqttClient::new(session_id, config)?))
+                Ok(Box::new(mqtt::MqttClient::new(session_id, config, app_handle)?))
             }
             "sse" => {
-                Ok(Box::new(sse::SseClient::new(session_id, config)?))
+                Ok(Box::new(sse::SseClient::new(session_id, config, app_handle)?))
             }
             "modbus" | "modbus-tcp" => {
                 if connection_type == "server" {
-                    Ok(Box::new(modbus::ModbusTcpServer::new(session_id, config)?))
+                    Ok(Box::new(modbus::ModbusTcpServer::new(session_id, config, app_handle)?))
                 } else {
                     Ok(Box::new(modbus::ModbusTcpClient::new(session_id, config)?))
                 }
             }
+            "serial" => {
+                Ok(Box::new(serial::SerialConnection::new(session_id, config, app_handle)?))
+            }
             "modbus-rtu" => {
-                Ok(Box::new(modbus::ModbusRtuClient::new(session_id, config)?))
+                if connection_type == "server" {
+                    Ok(Box::new(modbus_rtu_slave::ModbusRtuSlave::new(session_id, config, app_handle)?))
+                } else {
+                    Ok(Box::new(modbus::ModbusRtuClient::new(session_id, config)?))
+                }
+            }
+            "modbus-rtu-over-tcp" | "modbus-rtu-over-udp" | "modbus-ascii" => {
+                Ok(Box::new(modbus::ModbusTransportClient::new(session_id, config)?))
+            }
+            "iec104" => {
+                if connection_type == "server" {
+                    Ok(Box::new(iec104::Iec104Server::new(session_id, config, app_handle)?))
+                } else {
+                    Ok(Box::new(iec104::Iec104Client::new(session_id, config, app_handle)?))
+                }
+            }
+            "dnp3" => {
+                if connection_type == "server" {
+                    Ok(Box::new(dnp3::Dnp3Server::new(session_id, config, app_handle)?))
+                } else {
+                    Ok(Box::new(dnp3::Dnp3Client::new(session_id, config, app_handle)?))
+                }
             }
             _ => Err(crate::types::NetworkError::InvalidConfig(
                 format!("Unsupported protocol: {}", protocol)
@@ -150,3 +184,164 @@ impl ConnectionFactory {
         }
     }
 }
+
+/// Emit received data as `message-received` and parsed messages, joined into
+/// frames at idle gaps when the session has idle-gap framing
+///
+/// Messages published on an MQTT topic are already framed and skip idle-gap
+/// framing.
+pub fn emit_received(
+    app_handle: &tauri::AppHandle,
+    transport: &str,
+    session_id: &str,
+    client_id: Option<&str>,
+    peer: Option<SocketAddr>,
+    mqtt_topic: Option<&str>,
+    data: &[u8],
+) {
+    if mqtt_topic.is_some() {
+        emit_received_frame(app_handle, transport, session_id, client_id, peer, mqtt_topic, data);
+    } else if let Some(data) = idle_gap::deliver(app_handle, transport, session_id, client_id, peer, data) {
+        emit_received_frame(app_handle, transport, session_id, client_id, peer, None, data);
+    }
+}
+
+/// Emit one received frame as `message-received` and parsed messages
+pub(crate) fn emit_received_frame(
+    app_handle: &tauri::AppHandle,
+    transport: &str,
+    session_id: &str,
+    client_id: Option<&str>,
+    peer: Option<SocketAddr>,
+    mqtt_topic: Option<&str>,
+    data: &[u8],
+) {
+    use tauri::Emitter;
+
+    let mut payload = serde_json::json!({
+        "sessionId": session_id,
+        "data": data.to_vec(),
+        "direction": "in"
+    });
+    if let Some(client_id) = client_id {
+        payload["clientId"] = client_id.into();
+    }
+    if let Some(topic) = mqtt_topic {
+        payload["topic"] = topic.into();
+    }
+    if let Err(e) = app_handle.emit("message-received", payload) {
+        eprintln!("Failed to emit message-received event for session {}: {}", session_id, e);
+    }
+
+    emit_parsed_messages(app_handle, transport, session_id, client_id, peer, mqtt_topic, data);
+}
+
+/// Emit a fragments-expired event for every multi-packet message that timed
+/// out before all of its fragments arrived
+pub fn emit_expired_fragments(app_handle: &tauri::AppHandle) {
+    use tauri::Emitter;
+
+    let expired = {
+        let registry = crate::parser::get_parser_registry();
+        let registry = registry.read().unwrap();
+        registry.expire_fragments(std::time::Instant::now())
+    };
+
+    for fragments in expired {
+        log::warn!("{} (parser {})", fragments.warning.message, fragments.parser_id);
+        if let Err(e) = app_handle.emit(crate::parser::FRAGMENTS_EXPIRED_EVENT, &fragments) {
+            eprintln!("Failed to emit fragments-expired event: {}", e);
+        }
+    }
+}
+
+/// Run received data through the parsers bound to its session and emit the results
+pub fn emit_parsed_messages(
+    app_handle: &tauri::AppHandle,
+    transport: &str,
+    session_id: &str,
+    client_id: Option<&str>,
+    peer: Option<SocketAddr>,
+    mqtt_topic: Option<&str>,
+    data: &[u8],
+) {
+    use tauri::Emitter;
+
+    let session_parsers = crate::parser::get_session_parsers();
+    let messages = {
+        let mut session_parsers = session_parsers.lock().unwrap();
+        if !session_parsers.is_bound(session_id) {
+            return;
+        }
+
+        let mut envelope = crate::parser::EnvelopeContext::for_session(transport, session_id, client_id, peer);
+        envelope.mqtt_topic = mqtt_topic.map(str::to_string);
+        let registry = crate::parser::get_parser_registry();
+        let registry = registry.read().unwrap();
+        session_parsers.process(&registry, session_id, client_id, data, &envelope)
+    };
+
+    let alarms = {
+        let engine = crate::parser::get_alarm_engine();
+        let mut engine = engine.lock().unwrap();
+        let now = chrono::Utc::now();
+        messages.iter()
+            .flat_map(|message| engine.observe_message(message, now))
+            .collect::<Vec<_>>()
+    };
+
+    for message in messages {
+        if let Err(e) = app_handle.emit(crate::parser::MESSAGE_PARSED_EVENT, &message) {
+            eprintln!("Failed to emit message-parsed event for session {}: {}", session_id, e);
+        }
+    }
+
+    notify_factor_alarms(app_handle, alarms);
+}
+
+/// Emit factor alarm transitions and record them in the log database
+pub fn notify_factor_alarms(app_handle: &tauri::AppHandle, events: Vec<crate::parser::FactorAlarmEvent>) {
+    use crate::logging::{LogCategory, LogEntry, LogLevel};
+    use crate::parser::AlarmTransition;
+    use tauri::Emitter;
+
+    if events.is_empty() {
+        return;
+    }
+
+    for event in &events {
+        match event.transition {
+            AlarmTransition::Raised => log::warn!("{} (session {})", event.message(), event.session_id),
+            AlarmTransition::Cleared => log::info!("{} (session {})", event.message(), event.session_id),
+        }
+        if let Err(e) = app_handle.emit(crate::parser::FACTOR_ALARM_EVENT, event) {
+            eprintln!("Failed to emit factor-alarm event for session {}: {}", event.session_id, e);
+        }
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let manager = match crate::commands::get_log_manager().await {
+            Ok(manager) => manager,
+            Err(e) => {
+                log::warn!("Factor alarms not written to the log database: {}", e);
+                return;
+            }
+        };
+        let manager = manager.read().await;
+
+        for event in events {
+            let level = match event.transition {
+                AlarmTransition::Raised => LogLevel::Warning,
+                AlarmTransition::Cleared => LogLevel::Info,
+            };
+            let entry = LogEntry::new(level, "factor-alarm".to_string(), event.message())
+                .with_session(event.session_id.clone(), None)
+                .with_category(LogCategory::Protocol)
+                .with_protocol("HJ212".to_string())
+                .with_details(serde_json::to_value(&event).unwrap_or_default());
+            if let Err(e) = manager.add_log(entry).await {
+                eprintln!("Failed to log factor alarm for session {}: {}", event.session_id, e);
+            }
+        }
+    });
+}
@@ -6,7 +6,21 @@ use tokio_modbus::prelude::*;
 use tokio_modbus::client::{Context as ModbusContext};
 use std::net::SocketAddr;
 use std::time::Duration;
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use tokio_modbus::client::Client;
+use crate::network::idle_gap::IdleGapSettings;
+use crate::network::modbus_functions::{self, DeviceIdentification, DiagnosticsResponse};
+use crate::network::modbus_poll::{AreaData, PollError};
+use crate::network::modbus_rtu_slave::FRAME_GAP_CHARACTERS;
+use crate::network::modbus_slave::{MbapHeader, ModbusArea, ModbusException};
+use crate::network::modbus_transport::{self, AsciiStream, DatagramStream, ModbusTransport, SharedStream, NETWORK_FRAME_GAP};
+use crate::network::serial::SerialSettings;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+
+pub use crate::network::modbus_slave::ModbusTcpServer;
 
 /// Modbus function codes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,7 +33,12 @@ pub enum ModbusFunctionCode {
     WriteSingleRegister = 0x06,
     WriteMultipleCoils = 0x0F,
     WriteMultipleRegisters = 0x10,
+    ReadExceptionStatus = 0x07,
+    Diagnostics = 0x08,
+    MaskWriteRegister = 0x16,
     ReadWriteMultipleRegisters = 0x17,
+    ReadFifoQueue = 0x18,
+    EncapsulatedInterface = 0x2B,
 }
 
 impl ModbusFunctionCode {
@@ -33,12 +52,286 @@ impl ModbusFunctionCode {
             0x06 => Some(Self::WriteSingleRegister),
             0x0F => Some(Self::WriteMultipleCoils),
             0x10 => Some(Self::WriteMultipleRegisters),
+            0x07 => Some(Self::ReadExceptionStatus),
+            0x08 => Some(Self::Diagnostics),
+            0x16 => Some(Self::MaskWriteRegister),
             0x17 => Some(Self::ReadWriteMultipleRegisters),
+            0x18 => Some(Self::ReadFifoQueue),
+            0x2B => Some(Self::EncapsulatedInterface),
             _ => None,
         }
     }
 }
 
+/// Context of a connected client session, shared with polling and commands
+#[derive(Debug, Clone)]
+struct ClientHandle {
+    context: Arc<Mutex<ModbusContext>>,
+    unit_id: u8,
+    timeout: Duration,
+    /// Stream under the context, for requests framed directly
+    stream: SharedStream,
+    /// Silent interval ending a response on RTU-framed sessions; `None` for MBAP
+    rtu_frame_gap: Option<Duration>,
+}
+
+/// Connected Modbus TCP and RTU client sessions
+static CLIENTS: OnceLock<std::sync::Mutex<HashMap<String, ClientHandle>>> = OnceLock::new();
+
+fn clients() -> &'static std::sync::Mutex<HashMap<String, ClientHandle>> {
+    CLIENTS.get_or_init(|| std::sync::Mutex::new(HashMap::new()))
+}
+
+/// Attach a client context to a session's stream and register it for
+/// polling and commands; sessions with a frame gap speak RTU, others MBAP
+fn register_client(
+    session_id: &str,
+    stream: SharedStream,
+    unit_id: u8,
+    timeout: Duration,
+    rtu_frame_gap: Option<Duration>,
+) -> Arc<Mutex<ModbusContext>> {
+    let context = match rtu_frame_gap {
+        Some(_) => rtu::attach_slave(stream.clone(), Slave(unit_id)),
+        None => tcp::attach_slave(stream.clone(), Slave(unit_id)),
+    };
+    let context = Arc::new(Mutex::new(context));
+    clients().lock().unwrap().insert(session_id.to_string(), ClientHandle {
+        context: context.clone(),
+        unit_id,
+        timeout,
+        stream,
+        rtu_frame_gap,
+    });
+    context
+}
+
+fn client_handle(session_id: &str) -> NetworkResult<ClientHandle> {
+    clients()
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| NetworkError::SessionNotFound(format!("{} is not a connected Modbus client", session_id)))
+}
+
+/// Read an area of the device behind a client session
+///
+/// `unit_id` and `timeout` default to the session's own.
+pub async fn read_area(
+    session_id: &str,
+    unit_id: Option<u8>,
+    area: ModbusArea,
+    address: u16,
+    quantity: u16,
+    timeout: Option<Duration>,
+) -> Result<AreaData, PollError> {
+    let client = client_handle(session_id).map_err(|e| PollError::Failed(e.to_string()))?;
+    let mut ctx = client.context.lock().await;
+    ctx.set_slave(Slave(unit_id.unwrap_or(client.unit_id)));
+
+    let read = async {
+        match area {
+            ModbusArea::Coils => ctx.read_coils(address, quantity).await.map(|r| r.map(AreaData::Bits)),
+            ModbusArea::DiscreteInputs => ctx.read_discrete_inputs(address, quantity).await.map(|r| r.map(AreaData::Bits)),
+            ModbusArea::HoldingRegisters => ctx.read_holding_registers(address, quantity).await.map(|r| r.map(AreaData::Registers)),
+            ModbusArea::InputRegisters => ctx.read_input_registers(address, quantity).await.map(|r| r.map(AreaData::Registers)),
+        }
+    };
+    let result = tokio::time::timeout(timeout.unwrap_or(client.timeout), read).await;
+    ctx.set_slave(Slave(client.unit_id));
+
+    match result {
+        Err(_) => Err(PollError::Timeout),
+        Ok(Err(e)) => Err(PollError::Failed(e.to_string())),
+        Ok(Ok(Err(exception))) => Err(PollError::Exception(format!("{:?}", exception))),
+        Ok(Ok(Ok(data))) => Ok(data),
+    }
+}
+
+/// Flatten the timeout, transport and exception layers of a client call
+fn call_result<T, E: std::fmt::Debug, F: std::fmt::Display>(
+    result: Result<Result<Result<T, E>, F>, tokio::time::error::Elapsed>,
+    what: &str,
+    timeout: Duration,
+) -> NetworkResult<T> {
+    match result {
+        Err(_) => Err(NetworkError::ReceiveFailed(format!("{} timed out after {} ms", what, timeout.as_millis()))),
+        Ok(Err(e)) => Err(NetworkError::SendFailed(format!("{} failed: {}", what, e))),
+        Ok(Ok(Err(exception))) => Err(NetworkError::ProtocolError(format!("{} failed with Modbus exception {:?}", what, exception))),
+        Ok(Ok(Ok(value))) => Ok(value),
+    }
+}
+
+/// Read an area through a client session, with command-friendly errors
+pub async fn read(session_id: &str, area: ModbusArea, address: u16, quantity: u16) -> NetworkResult<AreaData> {
+    read_area(session_id, None, area, address, quantity, None).await.map_err(|e| match e {
+        PollError::Timeout => NetworkError::ReceiveFailed(format!("Read of {:?} timed out", area)),
+        PollError::Exception(exception) => {
+            NetworkError::ProtocolError(format!("Read of {:?} failed with Modbus exception {}", area, exception))
+        }
+        PollError::Failed(error) => NetworkError::SendFailed(error),
+    })
+}
+
+/// Write single coil (function code 0x05)
+pub async fn write_single_coil(session_id: &str, address: u16, value: bool) -> NetworkResult<()> {
+    let client = client_handle(session_id)?;
+    let mut ctx = client.context.lock().await;
+    let result = tokio::time::timeout(client.timeout, ctx.write_single_coil(address, value)).await;
+    call_result(result, "Write single coil", client.timeout)
+}
+
+/// Write single register (function code 0x06)
+pub async fn write_single_register(session_id: &str, address: u16, value: u16) -> NetworkResult<()> {
+    let client = client_handle(session_id)?;
+    let mut ctx = client.context.lock().await;
+    let result = tokio::time::timeout(client.timeout, ctx.write_single_register(address, value)).await;
+    call_result(result, "Write single register", client.timeout)
+}
+
+/// Write multiple coils (function code 0x0F)
+pub async fn write_multiple_coils(session_id: &str, address: u16, values: &[bool]) -> NetworkResult<()> {
+    let client = client_handle(session_id)?;
+    let mut ctx = client.context.lock().await;
+    let result = tokio::time::timeout(client.timeout, ctx.write_multiple_coils(address, values)).await;
+    call_result(result, "Write multiple coils", client.timeout)
+}
+
+/// Write multiple registers (function code 0x10)
+pub async fn write_multiple_registers(session_id: &str, address: u16, values: &[u16]) -> NetworkResult<()> {
+    let client = client_handle(session_id)?;
+    let mut ctx = client.context.lock().await;
+    let result = tokio::time::timeout(client.timeout, ctx.write_multiple_registers(address, values)).await;
+    call_result(result, "Write multiple registers", client.timeout)
+}
+
+/// Mask write register (function code 0x16): the register becomes
+/// `(value AND and_mask) OR (or_mask AND NOT and_mask)`
+pub async fn mask_write_register(session_id: &str, address: u16, and_mask: u16, or_mask: u16) -> NetworkResult<()> {
+    let client = client_handle(session_id)?;
+    let mut ctx = client.context.lock().await;
+    let result = tokio::time::timeout(client.timeout, ctx.masked_write_register(address, and_mask, or_mask)).await;
+    call_result(result, "Mask write register", client.timeout)
+}
+
+/// Read/write multiple registers (function code 0x17); the write happens first
+pub async fn read_write_multiple_registers(
+    session_id: &str,
+    read_address: u16,
+    read_quantity: u16,
+    write_address: u16,
+    write_values: &[u16],
+) -> NetworkResult<Vec<u16>> {
+    let client = client_handle(session_id)?;
+    let mut ctx = client.context.lock().await;
+    let call = ctx.read_write_multiple_registers(read_address, read_quantity, write_address, write_values);
+    let result = tokio::time::timeout(client.timeout, call).await;
+    call_result(result, "Read/write multiple registers", client.timeout)
+}
+
+/// Send a function code without a typed request with raw request data;
+/// returns the response data that follows the function code
+///
+/// The RTU client cannot tell where responses to these functions end, so
+/// RTU-framed sessions frame the request themselves and take the response
+/// up to the next silent interval.
+pub async fn custom_request(session_id: &str, function_code: u8, data: &[u8]) -> NetworkResult<Vec<u8>> {
+    modbus_functions::validate_function_code(function_code)?;
+    let client = client_handle(session_id)?;
+    let mut ctx = client.context.lock().await;
+    let what = format!("Function 0x{:02X}", function_code);
+
+    let Some(frame_gap) = client.rtu_frame_gap else {
+        let request = Request::Custom(function_code, Cow::Owned(data.to_vec()));
+        let result = tokio::time::timeout(client.timeout, ctx.call(request)).await;
+        return match call_result(result, &what, client.timeout)? {
+            Response::Custom(code, data) if code == function_code => Ok(data.to_vec()),
+            other => Err(NetworkError::ProtocolError(format!("{} got an unexpected response: {:?}", what, other))),
+        };
+    };
+
+    let pdu = [&[function_code][..], data].concat();
+    let mut stream = client.stream.clone();
+    let response = modbus_transport::rtu_exchange(&mut stream, client.unit_id, &pdu, frame_gap, client.timeout).await?;
+    match response.as_slice() {
+        [code, data @ ..] if *code == function_code => Ok(data.to_vec()),
+        [code, exception] if *code == function_code | 0x80 => {
+            let exception = ModbusException::from_code(*exception)
+                .map(|exception| exception.to_string())
+                .unwrap_or_else(|| format!("0x{:02X}", exception));
+            Err(NetworkError::ProtocolError(format!("{} failed with Modbus exception {}", what, exception)))
+        }
+        _ => Err(NetworkError::ProtocolError(format!("{} got an unexpected response: {}", what, hex::encode(&response)))),
+    }
+}
+
+/// Send a request the device never answers
+async fn send_unanswered(session_id: &str, pdu: &[u8]) -> NetworkResult<()> {
+    let client = client_handle(session_id)?;
+    let _ctx = client.context.lock().await;
+    let mut stream = client.stream.clone();
+    match client.rtu_frame_gap {
+        Some(_) => modbus_transport::rtu_send(&mut stream, client.unit_id, pdu).await,
+        None => {
+            stream.write_all(&MbapHeader::frame(0, client.unit_id, pdu)).await
+                .map_err(|e| NetworkError::SendFailed(format!("Failed to send request: {}", e)))?;
+            stream.flush().await
+                .map_err(|e| NetworkError::SendFailed(format!("Failed to send request: {}", e)))
+        }
+    }
+}
+
+/// Read exception status (function code 0x07)
+pub async fn read_exception_status(session_id: &str) -> NetworkResult<u8> {
+    let data = custom_request(session_id, modbus_functions::FC_READ_EXCEPTION_STATUS, &[]).await?;
+    modbus_functions::decode_exception_status(&data)
+}
+
+/// Run a diagnostics sub-function (function code 0x08)
+///
+/// Force listen only mode is not answered; it returns the request once sent.
+pub async fn diagnostics(session_id: &str, sub_function: u16, data: &[u16]) -> NetworkResult<DiagnosticsResponse> {
+    let request = modbus_functions::diagnostics_request(sub_function, data);
+    if sub_function == modbus_functions::diagnostics::FORCE_LISTEN_ONLY {
+        let pdu = [&[modbus_functions::FC_DIAGNOSTICS][..], &request].concat();
+        send_unanswered(session_id, &pdu).await?;
+        return Ok(DiagnosticsResponse { sub_function, data: data.to_vec() });
+    }
+    let response = custom_request(session_id, modbus_functions::FC_DIAGNOSTICS, &request).await?;
+    modbus_functions::decode_diagnostics(&response)
+}
+
+/// Read FIFO queue (function code 0x18)
+pub async fn read_fifo_queue(session_id: &str, address: u16) -> NetworkResult<Vec<u16>> {
+    let request = modbus_functions::fifo_request(address);
+    let response = custom_request(session_id, modbus_functions::FC_READ_FIFO_QUEUE, &request).await?;
+    modbus_functions::decode_fifo(&response)
+}
+
+/// Read device identification (function code 0x2B, MEI 0x0E), following
+/// the device's "more follows" responses of stream reads
+pub async fn read_device_identification(session_id: &str, read_code: u8, object_id: u8) -> NetworkResult<DeviceIdentification> {
+    let mut identification = DeviceIdentification::default();
+    let mut next = Some(object_id);
+    for _ in 0..modbus_functions::MAX_DEVICE_ID_PAGES {
+        let Some(object_id) = next else {
+            return Ok(identification);
+        };
+        let request = modbus_functions::device_id_request(read_code, object_id)?;
+        let response = custom_request(session_id, modbus_functions::FC_ENCAPSULATED_INTERFACE, &request).await?;
+        let page = modbus_functions::decode_device_id(&response)?;
+        identification.conformity_level = page.conformity_level;
+        identification.objects.extend(page.objects);
+        // Individual access (read code 4) never continues
+        next = page.next_object_id.filter(|_| read_code != 4);
+    }
+    Err(NetworkError::ProtocolError(format!(
+        "Device identification still continued after {} responses",
+        modbus_functions::MAX_DEVICE_ID_PAGES
+    )))
+}
+
 /// Modbus TCP Client
 #[derive(Debug, Clone)]
 pub struct ModbusTcpClient {
@@ -222,10 +515,11 @@ impl Connection for ModbusTcpClient {
             .map_err(|e| NetworkError::ConnectionFailed(format!("Invalid address: {}", e)))?;
 
         // Create Modbus TCP client context
-        let context = tcp::connect_slave(socket_addr, Slave(self.unit_id)).await
+        let stream = TcpStream::connect(socket_addr).await
             .map_err(|e| NetworkError::ConnectionFailed(format!("Modbus TCP connection failed: {}", e)))?;
 
-        self.context = Some(Arc::new(Mutex::new(context)));
+        let context = register_client(&self.session_id, SharedStream::new(stream), self.unit_id, self.timeout, None);
+        self.context = Some(context);
         self.connected = true;
 
         eprintln!("ModbusTcpClient: Connected successfully");
@@ -240,6 +534,7 @@ impl Connection for ModbusTcpClient {
         }
 
         self.context = None;
+        clients().lock().unwrap().remove(&self.session_id);
         self.connected = false;
 
         eprintln!("ModbusTcpClient: Disconnected");
@@ -476,9 +771,10 @@ impl Connection for ModbusRtuClient {
             .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to open serial port: {}", e)))?;
 
         // Create Modbus RTU client context
-        let context = rtu::attach_slave(port, Slave(self.unit_id));
-
-        self.context = Some(Arc::new(Mutex::new(context)));
+        let frame_gap = IdleGapSettings { characters: Some(FRAME_GAP_CHARACTERS), ..Default::default() }
+            .gap(Some(self.baud_rate))?;
+        let context = register_client(&self.session_id, SharedStream::new(port), self.unit_id, self.timeout, Some(frame_gap));
+        self.context = Some(context);
         self.connected = true;
 
         eprintln!("ModbusRtuClient: Connected successfully");
@@ -493,6 +789,7 @@ impl Connection for ModbusRtuClient {
         }
 
         self.context = None;
+        clients().lock().unwrap().remove(&self.session_id);
         self.connected = false;
 
         eprintln!("ModbusRtuClient: Disconnected");
@@ -531,77 +828,98 @@ impl Connection for ModbusRtuClient {
     }
 }
 
-/// Modbus TCP Server (Slave Simulator)
-#[derive(Debug)]
-pub struct ModbusTcpServer {
+/// Modbus client over RTU over TCP/UDP or Modbus ASCII (serial or TCP)
+#[derive(Debug, Clone)]
+pub struct ModbusTransportClient {
     session_id: String,
+    transport: ModbusTransport,
     host: String,
     port: u16,
+    serial: Option<SerialSettings>,
     unit_id: u8,
+    timeout: Duration,
     connected: bool,
+    context: Option<Arc<Mutex<ModbusContext>>>,
     event_tx: Option<mpsc::Sender<NetworkEvent>>,
-    // Simulated register storage
-    coils: std::sync::Arc<tokio::sync::RwLock<Vec<bool>>>,
-    discrete_inputs: std::sync::Arc<tokio::sync::RwLock<Vec<bool>>>,
-    holding_registers: std::sync::Arc<tokio::sync::RwLock<Vec<u16>>>,
-    input_registers: std::sync::Arc<tokio::sync::RwLock<Vec<u16>>>,
 }
 
-impl ModbusTcpServer {
+impl ModbusTransportClient {
     pub fn new(session_id: String, config: serde_json::Value) -> NetworkResult<Self> {
-        let host = config["host"]
-            .as_str()
-            .unwrap_or("0.0.0.0")
-            .to_string();
+        let protocol = config["protocol"].as_str().unwrap_or_default();
+        let has_serial = config.get("serial").is_some_and(|serial| !serial.is_null());
+        let transport = ModbusTransport::from_protocol(protocol, has_serial)?;
 
-        let port = config["port"]
-            .as_u64()
-            .ok_or_else(|| NetworkError::InvalidConfig("Missing port".to_string()))?
-            as u16;
+        let serial = match transport {
+            ModbusTransport::AsciiSerial => Some(SerialSettings::from_config(&config)?),
+            _ => None,
+        };
+
+        let (host, port) = if serial.is_some() {
+            (String::new(), 0)
+        } else {
+            let host = config["host"]
+                .as_str()
+                .ok_or_else(|| NetworkError::InvalidConfig("Missing host".to_string()))?
+                .to_string();
+            let port = config["port"]
+                .as_u64()
+                .ok_or_else(|| NetworkError::InvalidConfig("Missing port".to_string()))?
+                as u16;
+            (host, port)
+        };
 
         let unit_id = config["modbusUnitId"]
             .as_u64()
             .unwrap_or(1) as u8;
 
-        // Initialize register storage with default sizes
-        let coils = std::sync::Arc::new(tokio::sync::RwLock::new(vec![false; 10000]));
-        let discrete_inputs = std::sync::Arc::new(tokio::sync::RwLock::new(vec![false; 10000]));
-        let holding_registers = std::sync::Arc::new(tokio::sync::RwLock::new(vec![0u16; 10000]));
-        let input_registers = std::sync::Arc::new(tokio::sync::RwLock::new(vec![0u16; 10000]));
+        let timeout_ms = config["timeout"]
+            .as_u64()
+            .unwrap_or(5000);
 
         Ok(Self {
             session_id,
+            transport,
             host,
             port,
+            serial,
             unit_id,
+            timeout: Duration::from_millis(timeout_ms),
             connected: false,
+            context: None,
             event_tx: None,
-            coils,
-            discrete_inputs,
-            holding_registers,
-            input_registers,
         })
     }
 
-    /// Set holding register value (for testing/simulation)
-    pub async fn set_holding_register(&self, address: u16, value: u16) -> NetworkResult<()> {
-        let mut registers = self.holding_registers.write().await;
-        if (address as usize) < registers.len() {
-            registers[address as usize] = value;
-            Ok(())
-        } else {
-            Err(NetworkError::InvalidConfig(format!("Register address {} out of range", address)))
-        }
+    pub fn transport(&self) -> ModbusTransport {
+        self.transport
     }
 
-    /// Get holding register value
-    pub async fn get_holding_register(&self, address: u16) -> NetworkResult<u16> {
-        let registers = self.holding_registers.read().await;
-        if (address as usize) < registers.len() {
-            Ok(registers[address as usize])
+    async fn connect_tcp(&self) -> NetworkResult<TcpStream> {
+        tokio::time::timeout(self.timeout, TcpStream::connect((self.host.as_str(), self.port)))
+            .await
+            .map_err(|_| NetworkError::ConnectionFailed(format!("Connection to {}:{} timed out", self.host, self.port)))?
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to connect to {}:{}: {}", self.host, self.port, e)))
+    }
+
+    async fn connect_udp(&self) -> NetworkResult<UdpSocket> {
+        let address = tokio::net::lookup_host((self.host.as_str(), self.port))
+            .await
+            .ok()
+            .and_then(|mut addresses| addresses.next())
+            .ok_or_else(|| NetworkError::ConnectionFailed(format!("Cannot resolve {}:{}", self.host, self.port)))?;
+        let local: SocketAddr = if address.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
         } else {
-            Err(NetworkError::InvalidConfig(format!("Register address {} out of range", address)))
-        }
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(local)
+            .await
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to bind UDP socket: {}", e)))?;
+        socket
+            .connect(address)
+            .await
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to connect UDP socket to {}: {}", address, e)))?;
+        Ok(socket)
     }
 
     /// Send event to frontend
@@ -624,20 +942,29 @@ impl ModbusTcpServer {
 }
 
 #[async_trait]
-impl Connection for ModbusTcpServer {
+impl Connection for ModbusTransportClient {
     async fn connect(&mut self) -> NetworkResult<()> {
         if self.connected {
             return Ok(());
         }
 
-        eprintln!("ModbusTcpServer: Starting server on {}:{} (Unit ID: {})",
-                  self.host, self.port, self.unit_id);
+        eprintln!("ModbusTransportClient: Connecting over Modbus {} (Unit ID: {})", self.transport.name(), self.unit_id);
+
+        let stream = match (self.transport, &self.serial) {
+            (ModbusTransport::RtuOverTcp, _) => SharedStream::new(self.connect_tcp().await?),
+            (ModbusTransport::RtuOverUdp, _) => SharedStream::new(DatagramStream::new(self.connect_udp().await?)),
+            (ModbusTransport::AsciiTcp, _) => SharedStream::new(AsciiStream::new(self.connect_tcp().await?)),
+            (ModbusTransport::AsciiSerial, Some(serial)) => SharedStream::new(AsciiStream::new(serial.open()?)),
+            (ModbusTransport::AsciiSerial, None) => {
+                return Err(NetworkError::InvalidConfig("Missing serial settings".to_string()));
+            }
+        };
 
-        // Note: Full Modbus TCP server implementation would require tokio-modbus server support
-        // For now, we mark as connected and log that this is a simplified implementation
+        let context = register_client(&self.session_id, stream, self.unit_id, self.timeout, Some(NETWORK_FRAME_GAP));
+        self.context = Some(context);
         self.connected = true;
 
-        eprintln!("ModbusTcpServer: Server started (simplified slave simulator)");
+        eprintln!("ModbusTransportClient: Connected successfully");
         self.send_event("connected", None, None).await;
 
         Ok(())
@@ -648,9 +975,11 @@ impl Connection for ModbusTcpServer {
             return Ok(());
         }
 
+        self.context = None;
+        clients().lock().unwrap().remove(&self.session_id);
         self.connected = false;
 
-        eprintln!("ModbusTcpServer: Server stopped");
+        eprintln!("ModbusTransportClient: Disconnected");
         self.send_event("disconnected", None, None).await;
 
         Ok(())
@@ -658,7 +987,7 @@ impl Connection for ModbusTcpServer {
 
     async fn send(&mut self, _data: &[u8]) -> NetworkResult<usize> {
         Err(NetworkError::SendFailed(
-            "Modbus server does not support raw send".to_string()
+            "Use Modbus-specific function code methods instead of raw send".to_string()
         ))
     }
 
@@ -667,10 +996,14 @@ impl Connection for ModbusTcpServer {
     }
 
     fn status(&self) -> String {
-        if self.connected {
-            format!("Server running on {}:{} (Unit ID: {})", self.host, self.port, self.unit_id)
-        } else {
-            "Server stopped".to_string()
+        if !self.connected {
+            return "Disconnected".to_string();
+        }
+        match &self.serial {
+            Some(serial) => format!("Connected to {} at {} baud over Modbus {} (Unit ID: {})",
+                                    serial.port, serial.baud_rate, self.transport.name(), self.unit_id),
+            None => format!("Connected to {}:{} over Modbus {} (Unit ID: {})",
+                            self.host, self.port, self.transport.name(), self.unit_id),
         }
     }
 
@@ -685,3 +1018,54 @@ impl Connection for ModbusTcpServer {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::modbus_functions::diagnostics;
+    use crate::network::modbus_rtu_slave::rtu_frame;
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn test_rtu_session_frames_custom_functions() {
+        let (client, mut device) = tokio::io::duplex(256);
+        register_client("rtu-custom", SharedStream::new(client), 0x11, Duration::from_secs(2), Some(Duration::from_millis(20)));
+
+        let device_task = tokio::spawn(async move {
+            // Diagnostics return query data is echoed
+            let mut request = [0u8; 8];
+            device.read_exact(&mut request).await.unwrap();
+            assert_eq!(request.to_vec(), rtu_frame(0x11, &[0x08, 0x00, 0x00, 0xA5, 0x37]));
+            device.write_all(&request).await.unwrap();
+
+            // Force listen only is never answered
+            device.read_exact(&mut request).await.unwrap();
+            assert_eq!(request.to_vec(), rtu_frame(0x11, &[0x08, 0x00, 0x04, 0x00, 0x00]));
+
+            // Typed reads still go through the RTU client on the same stream
+            device.read_exact(&mut request).await.unwrap();
+            assert_eq!(request.to_vec(), rtu_frame(0x11, &[0x03, 0x00, 0x10, 0x00, 0x01]));
+            device.write_all(&rtu_frame(0x11, &[0x03, 0x02, 0x12, 0x34])).await.unwrap();
+
+            let mut request = [0u8; 6];
+            device.read_exact(&mut request).await.unwrap();
+            assert_eq!(request.to_vec(), rtu_frame(0x11, &[0x41, 0x01, 0x02]));
+            device.write_all(&rtu_frame(0x11, &[0xC1, 0x01])).await.unwrap();
+        });
+
+        let echo = diagnostics("rtu-custom", diagnostics::RETURN_QUERY_DATA, &[0xA537]).await.unwrap();
+        assert_eq!(echo.data, vec![0xA537]);
+        let listen_only = tokio::time::timeout(
+            Duration::from_millis(500),
+            diagnostics("rtu-custom", diagnostics::FORCE_LISTEN_ONLY, &[0]),
+        ).await;
+        assert_eq!(listen_only.unwrap().unwrap().sub_function, diagnostics::FORCE_LISTEN_ONLY);
+        let data = read("rtu-custom", ModbusArea::HoldingRegisters, 0x10, 1).await.unwrap();
+        assert_eq!(data, AreaData::Registers(vec![0x1234]));
+        let error = custom_request("rtu-custom", 0x41, &[0x01, 0x02]).await.unwrap_err();
+        assert!(error.to_string().contains("illegal function"), "{}", error);
+        assert!(custom_request("rtu-custom", 0x03, &[0x00, 0x10, 0x00, 0x01]).await.is_err());
+
+        device_task.await.unwrap();
+        clients().lock().unwrap().remove("rtu-custom");
+    }
+}
@@ -0,0 +1,253 @@
+//! Request and response data of the less common Modbus functions
+//!
+//! FC 07 read exception status, FC 08 diagnostics, FC 24 read FIFO queue and
+//! FC 43/14 read device identification travel as custom PDUs; these helpers
+//! build their request data and decode the response data that follows the
+//! function code. Custom requests leave the functions with typed requests
+//! (reads, writes, mask write and read/write registers) to their commands.
+
+use crate::types::{NetworkError, NetworkResult};
+use serde::{Deserialize, Serialize};
+
+pub const FC_READ_EXCEPTION_STATUS: u8 = 0x07;
+pub const FC_DIAGNOSTICS: u8 = 0x08;
+pub const FC_READ_FIFO_QUEUE: u8 = 0x18;
+pub const FC_ENCAPSULATED_INTERFACE: u8 = 0x2B;
+
+/// MEI type of Read Device Identification within FC 43
+pub const MEI_READ_DEVICE_ID: u8 = 0x0E;
+
+/// Most FIFO entries a response can carry
+const MAX_FIFO_COUNT: u16 = 31;
+
+/// Most continuation requests followed for one device identification read
+pub const MAX_DEVICE_ID_PAGES: usize = 32;
+
+/// Diagnostics sub-functions (FC 08)
+pub mod diagnostics {
+    pub const RETURN_QUERY_DATA: u16 = 0x00;
+    pub const RESTART_COMMUNICATIONS: u16 = 0x01;
+    pub const RETURN_DIAGNOSTIC_REGISTER: u16 = 0x02;
+    pub const CHANGE_ASCII_DELIMITER: u16 = 0x03;
+    /// The device stops answering; no response is expected
+    pub const FORCE_LISTEN_ONLY: u16 = 0x04;
+    pub const CLEAR_COUNTERS: u16 = 0x0A;
+    pub const BUS_MESSAGE_COUNT: u16 = 0x0B;
+    pub const BUS_COMMUNICATION_ERROR_COUNT: u16 = 0x0C;
+    pub const BUS_EXCEPTION_ERROR_COUNT: u16 = 0x0D;
+    pub const SERVER_MESSAGE_COUNT: u16 = 0x0E;
+    pub const SERVER_NO_RESPONSE_COUNT: u16 = 0x0F;
+    pub const SERVER_NAK_COUNT: u16 = 0x10;
+    pub const SERVER_BUSY_COUNT: u16 = 0x11;
+    pub const BUS_CHARACTER_OVERRUN_COUNT: u16 = 0x12;
+    pub const CLEAR_OVERRUN_COUNTER: u16 = 0x14;
+}
+
+fn short(function: &str, data: &[u8]) -> NetworkError {
+    NetworkError::ParseError(format!("{} response too short: {}", function, hex::encode(data)))
+}
+
+/// Name of a function with its own typed request and command
+fn typed_function(function_code: u8) -> Option<&'static str> {
+    match function_code {
+        0x01 => Some("read coils"),
+        0x02 => Some("read discrete inputs"),
+        0x03 => Some("read holding registers"),
+        0x04 => Some("read input registers"),
+        0x05 => Some("write single coil"),
+        0x06 => Some("write single register"),
+        0x0F => Some("write multiple coils"),
+        0x10 => Some("write multiple registers"),
+        0x16 => Some("mask write register"),
+        0x17 => Some("read/write multiple registers"),
+        _ => None,
+    }
+}
+
+/// Check a function code usable for a custom request
+pub fn validate_function_code(function_code: u8) -> NetworkResult<()> {
+    if function_code == 0 || function_code >= 0x80 {
+        return Err(NetworkError::InvalidConfig(format!(
+            "Function code must be 1..=127, got {}", function_code
+        )));
+    }
+    if let Some(name) = typed_function(function_code) {
+        return Err(NetworkError::InvalidConfig(format!(
+            "Function code 0x{:02X} ({}) cannot be sent as a custom request, use its own command",
+            function_code, name
+        )));
+    }
+    Ok(())
+}
+
+/// Decode the FC 07 response: the eight exception status outputs
+pub fn decode_exception_status(data: &[u8]) -> NetworkResult<u8> {
+    data.first().copied().ok_or_else(|| short("Read exception status", data))
+}
+
+/// Echoed sub-function and data of an FC 08 response
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiagnosticsResponse {
+    pub sub_function: u16,
+    pub data: Vec<u16>,
+}
+
+/// FC 08 request data: sub-function followed by its data words
+pub fn diagnostics_request(sub_function: u16, data: &[u16]) -> Vec<u8> {
+    std::iter::once(sub_function).chain(data.iter().copied()).flat_map(u16::to_be_bytes).collect()
+}
+
+pub fn decode_diagnostics(data: &[u8]) -> NetworkResult<DiagnosticsResponse> {
+    if data.len() < 2 || data.len() % 2 == 1 {
+        return Err(short("Diagnostics", data));
+    }
+    let words: Vec<u16> = data.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect();
+    Ok(DiagnosticsResponse { sub_function: words[0], data: words[1..].to_vec() })
+}
+
+/// FC 24 request data: the FIFO pointer address
+pub fn fifo_request(address: u16) -> Vec<u8> {
+    address.to_be_bytes().to_vec()
+}
+
+/// Decode the FC 24 response into the queued values
+pub fn decode_fifo(data: &[u8]) -> NetworkResult<Vec<u16>> {
+    if data.len() < 4 {
+        return Err(short("Read FIFO queue", data));
+    }
+    let byte_count = u16::from_be_bytes([data[0], data[1]]) as usize;
+    let count = u16::from_be_bytes([data[2], data[3]]);
+    if count > MAX_FIFO_COUNT || byte_count != 2 + count as usize * 2 || data.len() < 2 + byte_count {
+        return Err(NetworkError::ParseError(format!(
+            "Read FIFO queue response has byte count {} for {} values in {} bytes",
+            byte_count, count, data.len()
+        )));
+    }
+    Ok(data[4..2 + byte_count].chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect())
+}
+
+/// One object of a device identification
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceObject {
+    pub id: u8,
+    /// Standard name of objects 0x00..=0x06
+    pub name: Option<String>,
+    pub value: String,
+}
+
+/// Objects read with FC 43/14
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceIdentification {
+    pub conformity_level: u8,
+    pub objects: Vec<DeviceObject>,
+}
+
+/// One FC 43/14 response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceIdPage {
+    pub read_code: u8,
+    pub conformity_level: u8,
+    /// Object id to continue from when the device has more objects
+    pub next_object_id: Option<u8>,
+    pub objects: Vec<DeviceObject>,
+}
+
+fn object_name(id: u8) -> Option<&'static str> {
+    match id {
+        0x00 => Some("VendorName"),
+        0x01 => Some("ProductCode"),
+        0x02 => Some("MajorMinorRevision"),
+        0x03 => Some("VendorUrl"),
+        0x04 => Some("ProductName"),
+        0x05 => Some("ModelName"),
+        0x06 => Some("UserApplicationName"),
+        _ => None,
+    }
+}
+
+/// FC 43/14 request data. Read codes: 1 basic, 2 regular, 3 extended
+/// (streams from `object_id`), 4 one specific object.
+pub fn device_id_request(read_code: u8, object_id: u8) -> NetworkResult<Vec<u8>> {
+    if !(1..=4).contains(&read_code) {
+        return Err(NetworkError::InvalidConfig(format!("Read device id code must be 1..=4, got {}", read_code)));
+    }
+    Ok(vec![MEI_READ_DEVICE_ID, read_code, object_id])
+}
+
+pub fn decode_device_id(data: &[u8]) -> NetworkResult<DeviceIdPage> {
+    if data.len() < 6 {
+        return Err(short("Read device identification", data));
+    }
+    if data[0] != MEI_READ_DEVICE_ID {
+        return Err(NetworkError::ParseError(format!("Unexpected MEI type 0x{:02X}", data[0])));
+    }
+
+    let mut objects = Vec::with_capacity(data[5] as usize);
+    let mut rest = &data[6..];
+    for _ in 0..data[5] {
+        let [id, length, tail @ ..] = rest else {
+            return Err(short("Read device identification", data));
+        };
+        let value = tail.get(..*length as usize).ok_or_else(|| short("Read device identification", data))?;
+        objects.push(DeviceObject {
+            id: *id,
+            name: object_name(*id).map(str::to_string),
+            value: String::from_utf8_lossy(value).into_owned(),
+        });
+        rest = &tail[*length as usize..];
+    }
+
+    Ok(DeviceIdPage {
+        read_code: data[1],
+        conformity_level: data[2],
+        next_object_id: (data[3] == 0xFF).then_some(data[4]),
+        objects,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diagnostics_and_fifo() {
+        assert_eq!(diagnostics_request(diagnostics::RETURN_QUERY_DATA, &[0xA537]), vec![0x00, 0x00, 0xA5, 0x37]);
+        let echo = decode_diagnostics(&[0x00, 0x0B, 0x00, 0x2A]).unwrap();
+        assert_eq!(echo, DiagnosticsResponse { sub_function: diagnostics::BUS_MESSAGE_COUNT, data: vec![42] });
+        assert!(decode_diagnostics(&[0x00, 0x00, 0x01]).is_err());
+
+        // Spec example: two values queued at 0x04DE
+        assert_eq!(fifo_request(0x04DE), vec![0x04, 0xDE]);
+        assert_eq!(decode_fifo(&[0x00, 0x06, 0x00, 0x02, 0x01, 0xB8, 0x12, 0x84]).unwrap(), vec![0x01B8, 0x1284]);
+        assert!(decode_fifo(&[0x00, 0x06, 0x00, 0x03, 0x01, 0xB8, 0x12, 0x84]).is_err());
+        assert_eq!(decode_exception_status(&[0x6D]).unwrap(), 0x6D);
+        assert!(validate_function_code(0x81).is_err());
+        assert!(validate_function_code(0x03).unwrap_err().to_string().contains("read holding registers"));
+        assert!(validate_function_code(0x17).is_err());
+        assert!(validate_function_code(FC_DIAGNOSTICS).is_ok());
+        assert!(validate_function_code(0x41).is_ok());
+    }
+
+    #[test]
+    fn test_device_identification() {
+        assert_eq!(device_id_request(1, 0).unwrap(), vec![0x0E, 0x01, 0x00]);
+        assert!(device_id_request(5, 0).is_err());
+
+        let mut data = vec![0x0E, 0x01, 0x01, 0xFF, 0x02, 0x02];
+        data.extend([0x00, 0x04]);
+        data.extend(b"Acme");
+        data.extend([0x01, 0x05]);
+        data.extend(b"PM-10");
+        let page = decode_device_id(&data).unwrap();
+        assert_eq!(page.next_object_id, Some(0x02));
+        assert_eq!(page.conformity_level, 0x01);
+        assert_eq!(page.objects[0].name.as_deref(), Some("VendorName"));
+        assert_eq!(page.objects[1].value, "PM-10");
+
+        data.truncate(data.len() - 1);
+        assert!(decode_device_id(&data).is_err());
+    }
+}
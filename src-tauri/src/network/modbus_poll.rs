@@ -0,0 +1,648 @@
+//! Modbus polling with typed tag maps
+//!
+//! A tag names a value in a device: its area and address, its data type,
+//! byte order, scale and unit. Tags are polled in groups, each at its own
+//! interval; the tags of a group are merged into as few contiguous reads as
+//! the function code limits allow. Every poll emits a `modbus-poll` event with
+//! a typed reading, or the error or timeout, for each tag of the group.
+
+use crate::network::modbus_slave::ModbusArea;
+use crate::types::{NetworkError, NetworkResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted after each poll of a group
+pub const MODBUS_POLL_EVENT: &str = "modbus-poll";
+
+/// Shortest poll interval accepted
+const MIN_INTERVAL_MS: u64 = 10;
+
+/// Most bits one FC 1/2 request reads
+const MAX_READ_BITS: u16 = 2000;
+
+/// Most registers one FC 3/4 request reads
+const MAX_READ_REGISTERS: u16 = 125;
+
+/// Data type of a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagType {
+    Bool,
+    U16,
+    I16,
+    U32,
+    I32,
+    F32,
+    U64,
+    I64,
+    F64,
+    String,
+}
+
+impl TagType {
+    /// Parse a type name, accepting common aliases such as `uint16` or `float`
+    pub fn parse(name: &str) -> NetworkResult<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "bool" | "bit" | "boolean" => Ok(Self::Bool),
+            "u16" | "uint16" | "word" => Ok(Self::U16),
+            "i16" | "int16" | "short" => Ok(Self::I16),
+            "u32" | "uint32" | "dword" => Ok(Self::U32),
+            "i32" | "int32" | "int" => Ok(Self::I32),
+            "f32" | "float32" | "float" | "real" => Ok(Self::F32),
+            "u64" | "uint64" => Ok(Self::U64),
+            "i64" | "int64" | "long" => Ok(Self::I64),
+            "f64" | "float64" | "double" => Ok(Self::F64),
+            "string" | "str" | "ascii" => Ok(Self::String),
+            other => Err(NetworkError::ParseError(format!("Unknown tag type: {}", other))),
+        }
+    }
+
+    /// Registers a value of this type occupies; strings need their byte length
+    fn registers(self, length: Option<u16>) -> u16 {
+        match self {
+            Self::Bool | Self::U16 | Self::I16 => 1,
+            Self::U32 | Self::I32 | Self::F32 => 2,
+            Self::U64 | Self::I64 | Self::F64 => 4,
+            Self::String => length.unwrap_or(0).div_ceil(2),
+        }
+    }
+}
+
+/// Order of the bytes of a multi-register value, A being the most significant
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ByteOrder {
+    /// Big-endian words in big-endian order
+    #[default]
+    Abcd,
+    /// Word swapped
+    Cdab,
+    /// Byte swapped
+    Badc,
+    /// Little-endian
+    Dcba,
+}
+
+impl ByteOrder {
+    pub fn parse(name: &str) -> NetworkResult<Self> {
+        match name.trim().to_ascii_uppercase().as_str() {
+            "" | "ABCD" | "BIG" => Ok(Self::Abcd),
+            "CDAB" => Ok(Self::Cdab),
+            "BADC" => Ok(Self::Badc),
+            "DCBA" | "LITTLE" => Ok(Self::Dcba),
+            other => Err(NetworkError::ParseError(format!("Unknown byte order: {}", other))),
+        }
+    }
+
+    fn swaps_words(self) -> bool {
+        matches!(self, Self::Cdab | Self::Dcba)
+    }
+
+    fn swaps_bytes(self) -> bool {
+        matches!(self, Self::Badc | Self::Dcba)
+    }
+}
+
+/// Parse an area name: `coils`, `discreteInputs`, `holdingRegisters` or
+/// `inputRegisters`, their short forms or the Modicon prefixes `0x`..`4x`
+pub fn parse_area(name: &str) -> NetworkResult<ModbusArea> {
+    let name: String = name.chars().filter(|c| !matches!(c, ' ' | '_' | '-')).collect();
+    match name.to_ascii_lowercase().as_str() {
+        "coil" | "coils" | "co" | "0x" => Ok(ModbusArea::Coils),
+        "discreteinput" | "discreteinputs" | "di" | "1x" => Ok(ModbusArea::DiscreteInputs),
+        "inputregister" | "inputregisters" | "ir" | "3x" => Ok(ModbusArea::InputRegisters),
+        "holdingregister" | "holdingregisters" | "hr" | "4x" => Ok(ModbusArea::HoldingRegisters),
+        other => Err(NetworkError::ParseError(format!("Unknown Modbus area: {}", other))),
+    }
+}
+
+/// A value read from a device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusTag {
+    pub name: String,
+    pub area: ModbusArea,
+    /// Zero-based address
+    pub address: u16,
+    #[serde(rename = "type")]
+    pub data_type: TagType,
+    #[serde(default)]
+    pub byte_order: ByteOrder,
+    /// String length in bytes
+    #[serde(default)]
+    pub length: Option<u16>,
+    /// Factor applied to numeric values
+    #[serde(default)]
+    pub scale: Option<f64>,
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// Poll group; the first group when absent
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+impl ModbusTag {
+    /// Bits or registers the tag occupies
+    pub fn span(&self) -> u16 {
+        if self.area.is_bit() {
+            1
+        } else {
+            self.data_type.registers(self.length)
+        }
+    }
+
+    pub fn validate(&self) -> NetworkResult<()> {
+        let invalid = |reason: &str| Err(NetworkError::InvalidConfig(format!("Tag {}: {}", self.name, reason)));
+        if self.name.trim().is_empty() {
+            return Err(NetworkError::InvalidConfig("Tag name must not be empty".to_string()));
+        }
+        if self.area.is_bit() != (self.data_type == TagType::Bool) {
+            return invalid("bool tags must be coils or discrete inputs, and only bool tags can be");
+        }
+        if self.data_type == TagType::String && (self.span() == 0 || self.span() > MAX_READ_REGISTERS) {
+            return invalid("string length must be 1..=250 bytes");
+        }
+        if self.address as u32 + self.span() as u32 > 0x10000 {
+            return invalid("runs past address 65535");
+        }
+        if self.scale.is_some_and(|scale| !scale.is_finite()) {
+            return invalid("scale must be a finite number");
+        }
+        Ok(())
+    }
+
+    /// Decode the tag's value from its registers
+    pub fn decode(&self, registers: &[u16]) -> serde_json::Value {
+        let mut words = registers.to_vec();
+        if self.byte_order.swaps_words() && self.data_type != TagType::String {
+            words.reverse();
+        }
+        let bytes: Vec<u8> = words
+            .iter()
+            .flat_map(|word| {
+                let [high, low] = word.to_be_bytes();
+                if self.byte_order.swaps_bytes() { [low, high] } else { [high, low] }
+            })
+            .collect();
+        let array = |n: usize| -> [u8; 8] {
+            let mut value = [0u8; 8];
+            value[8 - n..].copy_from_slice(&bytes[..n]);
+            value
+        };
+
+        let number = match self.data_type {
+            TagType::Bool => return serde_json::json!(registers.first().is_some_and(|word| *word != 0)),
+            TagType::String => {
+                let length = (self.length.unwrap_or(0) as usize).min(bytes.len());
+                let text = String::from_utf8_lossy(&bytes[..length]);
+                return serde_json::json!(text.trim_end_matches(['\0', ' ']));
+            }
+            TagType::U16 => Number::Unsigned(u16::from_be_bytes([bytes[0], bytes[1]]) as u64),
+            TagType::I16 => Number::Signed(i16::from_be_bytes([bytes[0], bytes[1]]) as i64),
+            TagType::U32 => Number::Unsigned(u64::from_be_bytes(array(4))),
+            TagType::I32 => Number::Signed(i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as i64),
+            TagType::F32 => Number::Float(f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64),
+            TagType::U64 => Number::Unsigned(u64::from_be_bytes(array(8))),
+            TagType::I64 => Number::Signed(i64::from_be_bytes(array(8))),
+            TagType::F64 => Number::Float(f64::from_be_bytes(array(8))),
+        };
+        match (self.scale, number) {
+            (Some(scale), number) => serde_json::json!(number.as_f64() * scale),
+            (None, Number::Unsigned(value)) => serde_json::json!(value),
+            (None, Number::Signed(value)) => serde_json::json!(value),
+            (None, Number::Float(value)) => serde_json::json!(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Number {
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn as_f64(self) -> f64 {
+        match self {
+            Self::Unsigned(value) => value as f64,
+            Self::Signed(value) => value as f64,
+            Self::Float(value) => value,
+        }
+    }
+}
+
+/// Tags polled together at one interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PollGroup {
+    pub name: String,
+    pub interval_ms: u64,
+    /// Unit id for this group; the session's when absent
+    #[serde(default)]
+    pub unit_id: Option<u8>,
+    /// Timeout of each read; the session's when absent
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// Groups and tags polled on one session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusPollConfig {
+    pub groups: Vec<PollGroup>,
+    pub tags: Vec<ModbusTag>,
+}
+
+impl ModbusPollConfig {
+    /// Validate the config and split it into groups with their tags and reads
+    pub fn plan(&self) -> NetworkResult<Vec<GroupPlan>> {
+        if self.groups.is_empty() {
+            return Err(NetworkError::InvalidConfig("Polling needs at least one group".to_string()));
+        }
+        let mut plans: Vec<GroupPlan> = Vec::new();
+        for group in &self.groups {
+            if group.interval_ms < MIN_INTERVAL_MS {
+                return Err(NetworkError::InvalidConfig(format!(
+                    "Group {}: interval must be at least {} ms", group.name, MIN_INTERVAL_MS
+                )));
+            }
+            if plans.iter().any(|plan| plan.group.name == group.name) {
+                return Err(NetworkError::InvalidConfig(format!("Duplicate poll group {}", group.name)));
+            }
+            plans.push(GroupPlan { group: group.clone(), tags: Vec::new(), blocks: Vec::new() });
+        }
+
+        for tag in &self.tags {
+            tag.validate()?;
+            let plan = match &tag.group {
+                Some(name) => plans.iter_mut().find(|plan| &plan.group.name == name).ok_or_else(|| {
+                    NetworkError::InvalidConfig(format!("Tag {}: unknown poll group {}", tag.name, name))
+                })?,
+                None => &mut plans[0],
+            };
+            plan.tags.push(tag.clone());
+        }
+        for plan in &mut plans {
+            plan.blocks = merge_reads(&plan.tags);
+        }
+        Ok(plans)
+    }
+}
+
+/// One read covering one or more tags
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadBlock {
+    pub area: ModbusArea,
+    pub address: u16,
+    pub quantity: u16,
+    /// Indexes of the tags the read covers
+    pub tags: Vec<usize>,
+}
+
+/// A group with its tags and the reads that cover them
+#[derive(Debug, Clone)]
+pub struct GroupPlan {
+    pub group: PollGroup,
+    pub tags: Vec<ModbusTag>,
+    pub blocks: Vec<ReadBlock>,
+}
+
+/// Merge tags of the same area whose ranges touch or overlap into single reads
+pub fn merge_reads(tags: &[ModbusTag]) -> Vec<ReadBlock> {
+    let mut order: Vec<usize> = (0..tags.len()).collect();
+    order.sort_by_key(|&index| (tags[index].area as u8, tags[index].address));
+
+    let mut blocks: Vec<ReadBlock> = Vec::new();
+    for index in order {
+        let tag = &tags[index];
+        let end = tag.address as u32 + tag.span() as u32;
+        let limit = if tag.area.is_bit() { MAX_READ_BITS } else { MAX_READ_REGISTERS } as u32;
+        if let Some(block) = blocks.last_mut() {
+            let block_end = block.address as u32 + block.quantity as u32;
+            if block.area == tag.area && tag.address as u32 <= block_end && end.max(block_end) - block.address as u32 <= limit {
+                block.quantity = (end.max(block_end) - block.address as u32) as u16;
+                block.tags.push(index);
+                continue;
+            }
+        }
+        blocks.push(ReadBlock { area: tag.area, address: tag.address, quantity: tag.span(), tags: vec![index] });
+    }
+    blocks
+}
+
+/// Data returned by one read
+#[derive(Debug, Clone, PartialEq)]
+pub enum AreaData {
+    Bits(Vec<bool>),
+    Registers(Vec<u16>),
+}
+
+/// Why a read produced no data
+#[derive(Debug, Clone, PartialEq)]
+pub enum PollError {
+    Timeout,
+    /// Exception response from the device
+    Exception(String),
+    Failed(String),
+}
+
+/// Outcome of reading one tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ReadStatus {
+    Good,
+    Timeout,
+    Exception,
+    Error,
+}
+
+/// Value or error of one tag in a poll
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagReading {
+    pub name: String,
+    pub value: Option<serde_json::Value>,
+    pub unit: Option<String>,
+    pub status: ReadStatus,
+    pub error: Option<String>,
+}
+
+/// Result of one poll of a group
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusPollUpdate {
+    pub session_id: String,
+    pub group: String,
+    pub readings: Vec<TagReading>,
+    pub duration_ms: u64,
+    pub timestamp: String,
+}
+
+/// Readings of the tags a block covers
+pub fn block_readings(tags: &[ModbusTag], block: &ReadBlock, result: &Result<AreaData, PollError>) -> Vec<TagReading> {
+    block
+        .tags
+        .iter()
+        .map(|&index| {
+            let tag = &tags[index];
+            let offset = (tag.address - block.address) as usize;
+            let (value, status, error) = match result {
+                Ok(AreaData::Bits(bits)) => match bits.get(offset) {
+                    Some(bit) => (Some(serde_json::json!(bit)), ReadStatus::Good, None),
+                    None => (None, ReadStatus::Error, Some("Short response".to_string())),
+                },
+                Ok(AreaData::Registers(registers)) => match registers.get(offset..offset + tag.span() as usize) {
+                    Some(words) => (Some(tag.decode(words)), ReadStatus::Good, None),
+                    None => (None, ReadStatus::Error, Some("Short response".to_string())),
+                },
+                Err(PollError::Timeout) => (None, ReadStatus::Timeout, Some("Timed out".to_string())),
+                Err(PollError::Exception(exception)) => (None, ReadStatus::Exception, Some(exception.clone())),
+                Err(PollError::Failed(error)) => (None, ReadStatus::Error, Some(error.clone())),
+            };
+            TagReading { name: tag.name.clone(), value, unit: tag.unit.clone(), status, error }
+        })
+        .collect()
+}
+
+/// Read a tag map from CSV with a header row
+///
+/// Columns are matched by name, ignoring case, spaces and underscores:
+/// `name`, `area`, `address`, `type`, `byteOrder` (or `order`), `length`,
+/// `scale`, `unit` and `group`. Only the first four are required.
+pub fn import_tags_csv(content: &str) -> NetworkResult<Vec<ModbusTag>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| NetworkError::ParseError(format!("Failed to read CSV header: {}", e)))?
+        .iter()
+        .map(|header| header.chars().filter(|c| !matches!(c, ' ' | '_' | '-')).collect::<String>().to_ascii_lowercase())
+        .collect();
+    let column = |names: &[&str]| headers.iter().position(|header| names.contains(&header.as_str()));
+    let required = |name: &str| {
+        column(&[name]).ok_or_else(|| NetworkError::ParseError(format!("CSV has no {} column", name)))
+    };
+    let (name, area, address, data_type) = (required("name")?, required("area")?, required("address")?, required("type")?);
+    let (byte_order, length, scale) = (column(&["byteorder", "order"]), column(&["length"]), column(&["scale"]));
+    let (unit, group) = (column(&["unit"]), column(&["group"]));
+
+    let mut tags = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| NetworkError::ParseError(format!("Failed to read CSV record: {}", e)))?;
+        let line = record.position().map_or(0, |position| position.line());
+        let cell = |index: Option<usize>| index.and_then(|index| record.get(index)).filter(|cell| !cell.is_empty());
+        let number = |index: Option<usize>, what: &str| -> NetworkResult<Option<f64>> {
+            cell(index)
+                .map(|text| text.parse::<f64>().map_err(|_| NetworkError::ParseError(format!("Line {}: invalid {} {}", line, what, text))))
+                .transpose()
+        };
+
+        let tag = ModbusTag {
+            name: cell(Some(name)).unwrap_or_default().to_string(),
+            area: parse_area(cell(Some(area)).unwrap_or_default())
+                .map_err(|e| NetworkError::ParseError(format!("Line {}: {}", line, e)))?,
+            address: cell(Some(address))
+                .and_then(|text| text.parse().ok())
+                .ok_or_else(|| NetworkError::ParseError(format!("Line {}: invalid address", line)))?,
+            data_type: TagType::parse(cell(Some(data_type)).unwrap_or_default())
+                .map_err(|e| NetworkError::ParseError(format!("Line {}: {}", line, e)))?,
+            byte_order: ByteOrder::parse(cell(byte_order).unwrap_or_default())
+                .map_err(|e| NetworkError::ParseError(format!("Line {}: {}", line, e)))?,
+            length: number(length, "length")?.map(|length| length as u16),
+            scale: number(scale, "scale")?,
+            unit: cell(unit).map(str::to_string),
+            group: cell(group).map(str::to_string),
+        };
+        tag.validate().map_err(|e| NetworkError::ParseError(format!("Line {}: {}", line, e)))?;
+        tags.push(tag);
+    }
+    Ok(tags)
+}
+
+/// Poll tasks of sessions
+static POLLERS: OnceLock<Mutex<HashMap<String, Vec<tokio::task::JoinHandle<()>>>>> = OnceLock::new();
+
+fn pollers() -> &'static Mutex<HashMap<String, Vec<tokio::task::JoinHandle<()>>>> {
+    POLLERS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Start polling a Modbus client session, replacing any polling it had
+pub fn start_polling(session_id: &str, config: &ModbusPollConfig, app_handle: Option<AppHandle>) -> NetworkResult<()> {
+    let plans = config.plan()?;
+    stop_polling(session_id);
+
+    let tasks = plans
+        .into_iter()
+        .map(|plan| tokio::spawn(run_group(session_id.to_string(), plan, app_handle.clone())))
+        .collect();
+    pollers().lock().unwrap().insert(session_id.to_string(), tasks);
+    Ok(())
+}
+
+/// Stop polling a session
+pub fn stop_polling(session_id: &str) {
+    if let Some(tasks) = pollers().lock().unwrap().remove(session_id) {
+        tasks.iter().for_each(|task| task.abort());
+    }
+}
+
+/// Poll one group every interval until stopped
+async fn run_group(session_id: String, plan: GroupPlan, app_handle: Option<AppHandle>) {
+    let mut ticker = tokio::time::interval(Duration::from_millis(plan.group.interval_ms));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let timeout = plan.group.timeout_ms.map(Duration::from_millis);
+
+    loop {
+        ticker.tick().await;
+        let started = Instant::now();
+        let mut readings = Vec::with_capacity(plan.tags.len());
+        for block in &plan.blocks {
+            let result = crate::network::modbus::read_area(
+                &session_id, plan.group.unit_id, block.area, block.address, block.quantity, timeout,
+            ).await;
+            readings.extend(block_readings(&plan.tags, block, &result));
+        }
+
+        let update = ModbusPollUpdate {
+            session_id: session_id.clone(),
+            group: plan.group.name.clone(),
+            readings,
+            duration_ms: started.elapsed().as_millis() as u64,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Some(app_handle) = &app_handle {
+            if let Err(e) = app_handle.emit(MODBUS_POLL_EVENT, &update) {
+                eprintln!("ModbusPoll: Failed to emit {} event: {}", MODBUS_POLL_EVENT, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(name: &str, area: ModbusArea, address: u16, data_type: TagType) -> ModbusTag {
+        ModbusTag {
+            name: name.to_string(),
+            area,
+            address,
+            data_type,
+            byte_order: ByteOrder::Abcd,
+            length: None,
+            scale: None,
+            unit: None,
+            group: None,
+        }
+    }
+
+    #[test]
+    fn test_decode_byte_orders() {
+        // 123.456 as f32 is 0x42F6E979
+        let mut value = tag("t", ModbusArea::HoldingRegisters, 0, TagType::F32);
+        let decode = |tag: &ModbusTag, registers: &[u16]| tag.decode(registers).as_f64().unwrap();
+        assert!((decode(&value, &[0x42F6, 0xE979]) - 123.456).abs() < 1e-4);
+        value.byte_order = ByteOrder::Cdab;
+        assert!((decode(&value, &[0xE979, 0x42F6]) - 123.456).abs() < 1e-4);
+        value.byte_order = ByteOrder::Badc;
+        assert!((decode(&value, &[0xF642, 0x79E9]) - 123.456).abs() < 1e-4);
+        value.byte_order = ByteOrder::Dcba;
+        assert!((decode(&value, &[0x79E9, 0xF642]) - 123.456).abs() < 1e-4);
+
+        let mut signed = tag("i", ModbusArea::InputRegisters, 0, TagType::I32);
+        assert_eq!(signed.decode(&[0xFFFF, 0xFFFE]), serde_json::json!(-2));
+        signed.scale = Some(0.5);
+        assert_eq!(signed.decode(&[0x0000, 0x0003]), serde_json::json!(1.5));
+        assert_eq!(tag("u", ModbusArea::InputRegisters, 0, TagType::U16).decode(&[0xFFFF]), serde_json::json!(65535));
+        assert_eq!(tag("d", ModbusArea::InputRegisters, 0, TagType::F64).decode(&[0x4009, 0x21FB, 0x5444, 0x2D18]), serde_json::json!(std::f64::consts::PI));
+
+        let mut text = tag("s", ModbusArea::HoldingRegisters, 0, TagType::String);
+        text.length = Some(6);
+        assert_eq!(text.decode(&[0x4142, 0x4344, 0x0000]), serde_json::json!("ABCD"));
+        text.byte_order = ByteOrder::Badc;
+        assert_eq!(text.decode(&[0x4241, 0x4443, 0x0045]), serde_json::json!("ABCDE"));
+    }
+
+    #[test]
+    fn test_merge_and_readings() {
+        let mut text = tag("serial", ModbusArea::HoldingRegisters, 110, TagType::String);
+        text.length = Some(8);
+        let tags = vec![
+            tag("power", ModbusArea::HoldingRegisters, 102, TagType::F32),
+            tag("voltage", ModbusArea::HoldingRegisters, 100, TagType::U16),
+            tag("current", ModbusArea::HoldingRegisters, 101, TagType::U16),
+            tag("energy", ModbusArea::HoldingRegisters, 104, TagType::U32),
+            text,
+            tag("running", ModbusArea::Coils, 3, TagType::Bool),
+            tag("tripped", ModbusArea::Coils, 4, TagType::Bool),
+            tag("far", ModbusArea::HoldingRegisters, 300, TagType::U16),
+        ];
+        let blocks = merge_reads(&tags);
+        let spans: Vec<_> = blocks.iter().map(|block| (block.area, block.address, block.quantity)).collect();
+        assert_eq!(spans, vec![
+            (ModbusArea::Coils, 3, 2),
+            (ModbusArea::HoldingRegisters, 100, 6),
+            (ModbusArea::HoldingRegisters, 110, 4),
+            (ModbusArea::HoldingRegisters, 300, 1),
+        ]);
+        assert_eq!(blocks[1].tags, vec![1, 2, 0, 3]);
+
+        let registers = AreaData::Registers(vec![230, 5, 0x42F6, 0xE979, 0x0001, 0x0000]);
+        let readings = block_readings(&tags, &blocks[1], &Ok(registers));
+        assert_eq!(readings[0].value, Some(serde_json::json!(230)));
+        assert_eq!(readings[3].value, Some(serde_json::json!(65536)));
+        assert!(readings.iter().all(|reading| reading.status == ReadStatus::Good));
+
+        let timed_out = block_readings(&tags, &blocks[0], &Err(PollError::Timeout));
+        assert_eq!(timed_out.len(), 2);
+        assert!(timed_out.iter().all(|reading| reading.status == ReadStatus::Timeout && reading.value.is_none()));
+
+        let config = ModbusPollConfig {
+            groups: vec![PollGroup { name: "fast".to_string(), interval_ms: 5, unit_id: None, timeout_ms: None }],
+            tags: Vec::new(),
+        };
+        assert!(config.plan().is_err());
+        assert!(tag("bad", ModbusArea::Coils, 0, TagType::U16).validate().is_err());
+        assert!(tag("end", ModbusArea::HoldingRegisters, 65535, TagType::U32).validate().is_err());
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let csv = "Name,Area,Address,Type,Byte Order,Scale,Unit,Group\n\
+                   voltage,4x,100,uint16,,0.1,V,fast\n\
+                   # comment rows are skipped\n\
+                   power,holdingRegisters,101,float,CDAB,,kW,fast\n\
+                   \"alarm, main\",coils,7,bool,,,,slow\n";
+        let tags = import_tags_csv(csv).unwrap();
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags[0].scale, Some(0.1));
+        assert_eq!(tags[0].unit.as_deref(), Some("V"));
+        assert_eq!(tags[1].byte_order, ByteOrder::Cdab);
+        assert_eq!(tags[1].data_type, TagType::F32);
+        assert_eq!(tags[2].name, "alarm, main");
+        assert_eq!(tags[2].group.as_deref(), Some("slow"));
+
+        let config = ModbusPollConfig {
+            groups: vec![
+                PollGroup { name: "fast".to_string(), interval_ms: 100, unit_id: None, timeout_ms: None },
+                PollGroup { name: "slow".to_string(), interval_ms: 1000, unit_id: Some(2), timeout_ms: None },
+            ],
+            tags,
+        };
+        let plans = config.plan().unwrap();
+        assert_eq!(plans[0].blocks.len(), 1);
+        assert_eq!(plans[1].tags.len(), 1);
+
+        assert!(import_tags_csv("name,area,address\nx,hr,1\n").is_err());
+        let error = import_tags_csv("name,area,address,type\nx,hr,1,u16\ny,hr,abc,u16\n").unwrap_err();
+        assert!(error.to_string().contains("Line 3"));
+    }
+}
@@ -0,0 +1,669 @@
+//! Simulated Modbus RTU slave
+//!
+//! `ModbusRtuSlave` answers Modbus RTU requests on a serial port from the same
+//! `ModbusTables` as the TCP slave. Requests are framed by the 3.5 character
+//! silent interval (or the session's idle gap) and checked against their CRC.
+//! A response delay and fault injection (corrupted CRC, no reply or an
+//! exception response) help exercise master firmware without hardware. The
+//! port name `pty` opens a pseudo-terminal pair on Unix and reports the path
+//! the master should open.
+
+use async_trait::async_trait;
+use crate::network::idle_gap::{IdleGapFramer, IdleGapSettings};
+use crate::network::modbus_slave::{self, ModbusTables, ModbusTransaction};
+use crate::network::serial::SerialSettings;
+use crate::network::Connection;
+use crate::parser::crc_validator::CrcAlgorithm;
+use crate::types::{NetworkError, NetworkEvent, NetworkResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tokio_serial::{SerialPort, SerialStream};
+
+/// Silent interval between RTU frames, in character times
+pub const FRAME_GAP_CHARACTERS: f64 = 3.5;
+
+/// Port name that opens a pseudo-terminal pair instead of a device
+pub const PTY_PORT: &str = "pty";
+
+/// Raw writes queued for the port task
+const COMMAND_QUEUE: usize = 64;
+
+/// Modbus RTU CRC of data
+pub fn rtu_crc(data: &[u8]) -> u16 {
+    CrcAlgorithm::MODBUS.checksum(data) as u16
+}
+
+/// RTU frame: address, PDU and CRC (low byte first)
+pub fn rtu_frame(address: u8, pdu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(pdu.len() + 3);
+    frame.push(address);
+    frame.extend_from_slice(pdu);
+    frame.extend_from_slice(&rtu_crc(&frame).to_le_bytes());
+    frame
+}
+
+/// Whether a frame is long enough and ends with a valid CRC
+pub fn check_rtu_crc(frame: &[u8]) -> bool {
+    if frame.len() < 4 {
+        return false;
+    }
+    let (body, crc) = frame.split_at(frame.len() - 2);
+    rtu_crc(body) == u16::from_le_bytes([crc[0], crc[1]])
+}
+
+/// Fault injected into a slave's responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RtuFaultKind {
+    /// Answer with a corrupted CRC
+    WrongCrc,
+    /// Do not answer
+    NoReply,
+    /// Answer with an exception instead of processing the request
+    Exception,
+}
+
+/// When and how to inject a fault
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RtuFault {
+    pub kind: RtuFaultKind,
+    /// Exception code for `exception` faults (default 0x04, server device failure)
+    #[serde(default)]
+    pub exception_code: Option<u8>,
+    /// Inject on every Nth matching request (default 1, every request)
+    #[serde(default)]
+    pub every: Option<u32>,
+    /// Function codes the fault applies to; all when empty
+    #[serde(default)]
+    pub function_codes: Vec<u8>,
+}
+
+impl RtuFault {
+    pub fn validate(&self) -> NetworkResult<()> {
+        if self.every == Some(0) {
+            return Err(NetworkError::InvalidConfig("Fault interval must be at least 1".to_string()));
+        }
+        if matches!(self.exception_code, Some(0) | Some(0x80..)) {
+            return Err(NetworkError::InvalidConfig("Exception code must be 1..=127".to_string()));
+        }
+        Ok(())
+    }
+
+    fn applies_to(&self, function_code: u8) -> bool {
+        self.function_codes.is_empty() || self.function_codes.contains(&function_code)
+    }
+}
+
+/// RTU slave behaviour, read from `modbusRtuSlave` in the session config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModbusRtuSlaveSettings {
+    /// Delay before each response, in milliseconds
+    pub response_delay_ms: u64,
+    /// Discard requests with a bad CRC, as a real slave does
+    pub check_crc: bool,
+    pub fault: Option<RtuFault>,
+}
+
+impl Default for ModbusRtuSlaveSettings {
+    fn default() -> Self {
+        Self { response_delay_ms: 0, check_crc: true, fault: None }
+    }
+}
+
+impl ModbusRtuSlaveSettings {
+    /// Read the settings from a session config; absent settings are the defaults
+    pub fn from_config(config: &serde_json::Value) -> NetworkResult<Self> {
+        let settings: Self = match config.get("modbusRtuSlave") {
+            Some(value) if !value.is_null() => serde_json::from_value(value.clone())
+                .map_err(|e| NetworkError::InvalidConfig(format!("Invalid Modbus RTU slave settings: {}", e)))?,
+            _ => Self::default(),
+        };
+        if let Some(fault) = &settings.fault {
+            fault.validate()?;
+        }
+        Ok(settings)
+    }
+}
+
+/// Settings and fault counter shared between a slave and its commands
+#[derive(Debug)]
+struct SlaveState {
+    settings: ModbusRtuSlaveSettings,
+    /// Requests matching the fault since it was last injected
+    fault_matches: u32,
+}
+
+type SharedState = Arc<Mutex<SlaveState>>;
+
+/// State of running RTU slave sessions
+static RTU_SLAVES: OnceLock<Mutex<HashMap<String, SharedState>>> = OnceLock::new();
+
+fn rtu_slaves() -> &'static Mutex<HashMap<String, SharedState>> {
+    RTU_SLAVES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Set or clear the fault injected by a running RTU slave session
+pub fn set_session_fault(session_id: &str, fault: Option<RtuFault>) -> NetworkResult<()> {
+    if let Some(fault) = &fault {
+        fault.validate()?;
+    }
+    let state = rtu_slaves()
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| NetworkError::SessionNotFound(format!("{} is not a running Modbus RTU slave", session_id)))?;
+    let mut state = state.lock().unwrap();
+    state.settings.fault = fault;
+    state.fault_matches = 0;
+    Ok(())
+}
+
+/// Request handling of one slave, independent of the port
+#[derive(Debug, Clone)]
+struct RtuResponder {
+    session_id: String,
+    slave_id: u8,
+    tables: Arc<RwLock<ModbusTables>>,
+    state: SharedState,
+}
+
+impl RtuResponder {
+    /// Answer one received frame. Frames with a bad CRC yield no transaction;
+    /// broadcasts and requests for other slaves yield no response.
+    fn handle(&self, frame: &[u8]) -> Option<(ModbusTransaction, Option<Vec<u8>>)> {
+        let mut state = self.state.lock().unwrap();
+        if frame.len() < 4 || (state.settings.check_crc && !check_rtu_crc(frame)) {
+            eprintln!("ModbusRtuSlave: Session {} - Discarding frame with bad CRC: {}", self.session_id, hex::encode(frame));
+            return None;
+        }
+
+        let address = frame[0];
+        let pdu = &frame[1..frame.len() - 2];
+        let mut transaction = ModbusTransaction::new(&self.session_id, None, address, frame, pdu);
+        if address != self.slave_id && address != 0 {
+            return Some((transaction, None));
+        }
+
+        let function_code = transaction.function_code;
+        let fault = match &state.settings.fault {
+            Some(fault) if address != 0 && fault.applies_to(function_code) => {
+                let fault = fault.clone();
+                state.fault_matches += 1;
+                if state.fault_matches >= fault.every.unwrap_or(1) {
+                    state.fault_matches = 0;
+                    Some(fault)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+        drop(state);
+
+        let response_pdu = match &fault {
+            Some(fault) if fault.kind == RtuFaultKind::Exception => {
+                vec![function_code | 0x80, fault.exception_code.unwrap_or(0x04)]
+            }
+            _ => self.tables.write().unwrap().process(pdu),
+        };
+        transaction.fault = fault.as_ref().map(|fault| fault.kind);
+
+        // Broadcasts are processed but never answered
+        if address == 0 || transaction.fault == Some(RtuFaultKind::NoReply) {
+            return Some((transaction, None));
+        }
+        let mut response = rtu_frame(address, &response_pdu);
+        if transaction.fault == Some(RtuFaultKind::WrongCrc) {
+            let length = response.len();
+            response[length - 2] ^= 0xFF;
+            response[length - 1] ^= 0xFF;
+        }
+        transaction.respond(response.clone(), &response_pdu);
+        Some((transaction, Some(response)))
+    }
+
+    fn response_delay(&self) -> Duration {
+        Duration::from_millis(self.state.lock().unwrap().settings.response_delay_ms)
+    }
+}
+
+/// Simulated Modbus RTU slave on a serial port
+#[derive(Debug)]
+pub struct ModbusRtuSlave {
+    session_id: String,
+    serial: SerialSettings,
+    slave_id: u8,
+    frame_gap: Duration,
+    tables: Arc<RwLock<ModbusTables>>,
+    state: SharedState,
+    /// Path of the pseudo-terminal the master opens, for `pty` ports
+    pty_path: Option<String>,
+    /// Far end of the pseudo-terminal, kept open so the pair stays up
+    pty_peer: Option<SerialStream>,
+    writes: Option<mpsc::Sender<Vec<u8>>>,
+    port_task: Option<tokio::task::JoinHandle<()>>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+}
+
+impl ModbusRtuSlave {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
+        let serial = SerialSettings::from_config(&config)?;
+
+        let slave_id = config["modbusUnitId"]
+            .as_u64()
+            .unwrap_or(1);
+        if !(1..=247).contains(&slave_id) {
+            return Err(NetworkError::InvalidConfig(format!("Modbus RTU slave id must be 1..=247, got {}", slave_id)));
+        }
+
+        let frame_gap = match config.get("idleGap").filter(|value| !value.is_null()) {
+            Some(value) => serde_json::from_value::<IdleGapSettings>(value.clone())
+                .map_err(|e| NetworkError::InvalidConfig(format!("Invalid idle gap: {}", e)))?,
+            None => IdleGapSettings { characters: Some(FRAME_GAP_CHARACTERS), ..Default::default() },
+        }
+        .gap(Some(serial.baud_rate))?;
+
+        let state = SlaveState { settings: ModbusRtuSlaveSettings::from_config(&config)?, fault_matches: 0 };
+
+        Ok(Self {
+            session_id,
+            serial,
+            slave_id: slave_id as u8,
+            frame_gap,
+            tables: Arc::new(RwLock::new(ModbusTables::default())),
+            state: Arc::new(Mutex::new(state)),
+            pty_path: None,
+            pty_peer: None,
+            writes: None,
+            port_task: None,
+            app_handle,
+            event_tx: None,
+        })
+    }
+
+    /// Shared register tables
+    pub fn tables(&self) -> Arc<RwLock<ModbusTables>> {
+        self.tables.clone()
+    }
+
+    /// Path of the pseudo-terminal to open as the master, for `pty` ports
+    pub fn pty_path(&self) -> Option<&str> {
+        self.pty_path.as_deref()
+    }
+
+    #[cfg(unix)]
+    fn open_pty(&mut self) -> NetworkResult<SerialStream> {
+        let (port, peer) = SerialStream::pair()
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to open pseudo-terminal: {}", e)))?;
+        self.pty_path = peer.name();
+        self.pty_peer = Some(peer);
+        Ok(port)
+    }
+
+    #[cfg(not(unix))]
+    fn open_pty(&mut self) -> NetworkResult<SerialStream> {
+        Err(NetworkError::ConnectionFailed("Pseudo-terminals are only available on Unix".to_string()))
+    }
+
+    /// Start the port task on an opened port
+    fn attach<T: AsyncRead + AsyncWrite + Unpin + Send + 'static>(&mut self, port: T) {
+        let (writes, write_rx) = mpsc::channel(COMMAND_QUEUE);
+        let responder = RtuResponder {
+            session_id: self.session_id.clone(),
+            slave_id: self.slave_id,
+            tables: self.tables.clone(),
+            state: self.state.clone(),
+        };
+        self.port_task = Some(tokio::spawn(run_port(
+            port,
+            write_rx,
+            responder,
+            self.frame_gap,
+            self.app_handle.clone(),
+            self.event_tx.clone(),
+        )));
+        modbus_slave::register_slave(&self.session_id, self.tables.clone());
+        rtu_slaves().lock().unwrap().insert(self.session_id.clone(), self.state.clone());
+        self.writes = Some(writes);
+    }
+
+    fn detach(&mut self) {
+        self.writes = None;
+        self.pty_peer = None;
+        modbus_slave::unregister_slave(&self.session_id);
+        rtu_slaves().lock().unwrap().remove(&self.session_id);
+    }
+}
+
+async fn send_event(event_tx: &Option<mpsc::Sender<NetworkEvent>>, session_id: &str, event_type: &str, data: Option<Vec<u8>>, error: Option<String>) {
+    if let Some(tx) = event_tx {
+        let event = NetworkEvent {
+            session_id: session_id.to_string(),
+            event_type: event_type.to_string(),
+            data,
+            error,
+            client_id: None,
+            mqtt_topic: None,
+            mqtt_qos: None,
+            mqtt_retain: None,
+            sse_event: None,
+        };
+        let _ = tx.send(event).await;
+    }
+}
+
+fn emit_sent(app_handle: &Option<AppHandle>, session_id: &str, data: &[u8]) {
+    if let Some(app_handle) = app_handle {
+        let payload = serde_json::json!({
+            "sessionId": session_id,
+            "data": data.to_vec(),
+            "direction": "out"
+        });
+        if let Err(e) = app_handle.emit("message-received", payload) {
+            eprintln!("ModbusRtuSlave: Failed to emit message-received event: {}", e);
+        }
+    }
+}
+
+/// Write one frame and flush it to the line
+async fn write_frame<T: AsyncWrite + Unpin>(port: &mut T, frame: &[u8]) -> std::io::Result<()> {
+    port.write_all(frame).await?;
+    port.flush().await
+}
+
+/// Frame requests, answer them and serve raw writes until the port closes or the session disconnects
+async fn run_port<T: AsyncRead + AsyncWrite + Unpin>(
+    mut port: T,
+    mut writes: mpsc::Receiver<Vec<u8>>,
+    responder: RtuResponder,
+    frame_gap: Duration,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+) {
+    let session_id = responder.session_id.clone();
+    let mut framer = IdleGapFramer::new(frame_gap);
+    let mut buffer = [0u8; 1024];
+
+    let error = loop {
+        let deadline = framer.deadline();
+        let frames = tokio::select! {
+            read = port.read(&mut buffer) => match read {
+                Ok(0) => break None,
+                Ok(n) => framer.push(&buffer[..n], Instant::now()),
+                Err(e) => break Some(format!("Serial read error: {}", e)),
+            },
+            _ = tokio::time::sleep_until(tokio::time::Instant::from_std(deadline.unwrap_or_else(Instant::now))), if deadline.is_some() => {
+                framer.flush_idle(Instant::now()).into_iter().collect()
+            }
+            write = writes.recv() => match write {
+                Some(data) => {
+                    if let Err(e) = write_frame(&mut port, &data).await {
+                        break Some(format!("Serial write error: {}", e));
+                    }
+                    emit_sent(&app_handle, &session_id, &data);
+                    continue;
+                }
+                // The session dropped its sender: manual disconnect
+                None => return,
+            },
+        };
+
+        for frame in frames {
+            send_event(&event_tx, &session_id, "data_received", Some(frame.clone()), None).await;
+            if let Some(app_handle) = &app_handle {
+                crate::network::emit_received(app_handle, "modbus-rtu", &session_id, None, None, None, &frame);
+            }
+
+            let Some((transaction, response)) = responder.handle(&frame) else {
+                continue;
+            };
+            if let Some(response) = response {
+                let delay = responder.response_delay();
+                if !delay.is_zero() {
+                    tokio::time::sleep(delay).await;
+                }
+                if let Err(e) = write_frame(&mut port, &response).await {
+                    transaction.emit(&app_handle);
+                    eprintln!("ModbusRtuSlave: Session {} - Failed to send response: {}", session_id, e);
+                    continue;
+                }
+                emit_sent(&app_handle, &session_id, &response);
+            }
+            transaction.emit(&app_handle);
+        }
+    };
+
+    modbus_slave::unregister_slave(&session_id);
+    rtu_slaves().lock().unwrap().remove(&session_id);
+    eprintln!("ModbusRtuSlave: Session {} - Port closed: {}", session_id, error.as_deref().unwrap_or("end of stream"));
+    send_event(&event_tx, &session_id, "disconnected", None, error.clone()).await;
+    if let Some(app_handle) = &app_handle {
+        let payload = serde_json::json!({
+            "sessionId": session_id,
+            "status": "disconnected",
+            "error": error.unwrap_or_else(|| "Serial port closed".to_string())
+        });
+        if let Err(e) = app_handle.emit("connection-status", payload) {
+            eprintln!("ModbusRtuSlave: Failed to emit connection-status event: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl Connection for ModbusRtuSlave {
+    async fn connect(&mut self) -> NetworkResult<()> {
+        if self.is_connected() {
+            return Ok(());
+        }
+
+        let port = if self.serial.port == PTY_PORT {
+            self.open_pty()?
+        } else {
+            self.serial.open()?
+        };
+        self.attach(port);
+        eprintln!("ModbusRtuSlave: Slave {} listening on {} at {}",
+                  self.slave_id, self.pty_path.as_deref().unwrap_or(&self.serial.port), self.serial.describe());
+        send_event(&self.event_tx, &self.session_id, "connected", None, None).await;
+
+        if let Some(app_handle) = &self.app_handle {
+            let payload = serde_json::json!({
+                "sessionId": self.session_id,
+                "status": "connected",
+                "ptyPath": self.pty_path
+            });
+            if let Err(e) = app_handle.emit("connection-status", payload) {
+                eprintln!("ModbusRtuSlave: Failed to emit connection-status event: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> NetworkResult<()> {
+        let was_connected = self.is_connected();
+        // Dropping the sender ends the port task, which closes the port
+        self.detach();
+        if let Some(task) = self.port_task.take() {
+            let _ = task.await;
+        }
+        self.pty_path = None;
+
+        if was_connected {
+            eprintln!("ModbusRtuSlave: Session {} - Closed {}", self.session_id, self.serial.port);
+            send_event(&self.event_tx, &self.session_id, "disconnected", None, None).await;
+            if let Some(app_handle) = &self.app_handle {
+                let payload = serde_json::json!({
+                    "sessionId": self.session_id,
+                    "status": "disconnected",
+                    "error": null
+                });
+                if let Err(e) = app_handle.emit("connection-status", payload) {
+                    eprintln!("ModbusRtuSlave: Failed to emit connection-status event: {}", e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        let writes = self.writes.as_ref().ok_or(NetworkError::NotConnected)?;
+        writes.send(data.to_vec()).await.map_err(|_| NetworkError::NotConnected)?;
+        Ok(data.len())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.port_task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+
+    fn status(&self) -> String {
+        if self.is_connected() {
+            format!("Slave {} on {} at {}",
+                    self.slave_id, self.pty_path.as_deref().unwrap_or(&self.serial.port), self.serial.describe())
+        } else {
+            "Slave stopped".to_string()
+        }
+    }
+
+    async fn start_receiving(&mut self) -> NetworkResult<mpsc::Receiver<NetworkEvent>> {
+        let (tx, rx) = mpsc::channel(100);
+        self.event_tx = Some(tx);
+        Ok(rx)
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::modbus_slave::ModbusArea;
+
+    fn responder(settings: ModbusRtuSlaveSettings) -> RtuResponder {
+        RtuResponder {
+            session_id: "rtu-test".to_string(),
+            slave_id: 17,
+            tables: Arc::new(RwLock::new(ModbusTables::new(200))),
+            state: Arc::new(Mutex::new(SlaveState { settings, fault_matches: 0 })),
+        }
+    }
+
+    #[test]
+    fn test_rtu_requests() {
+        // Read holding registers 0x006B..=0x006D from slave 0x11, the classic spec example
+        let request = rtu_frame(0x11, &[0x03, 0x00, 0x6B, 0x00, 0x03]);
+        assert_eq!(&request[6..], &[0x76, 0x87]);
+        assert!(check_rtu_crc(&request));
+
+        let slave = responder(ModbusRtuSlaveSettings::default());
+        slave.tables.write().unwrap().write_registers(ModbusArea::HoldingRegisters, 0x6B, &[0x022B, 0x0000, 0x0064]).unwrap();
+        let (transaction, response) = slave.handle(&request).unwrap();
+        assert_eq!(response.unwrap(), rtu_frame(0x11, &[0x03, 0x06, 0x02, 0x2B, 0x00, 0x00, 0x00, 0x64]));
+        assert_eq!(transaction.exception, None);
+
+        // Bad CRC is discarded, other slaves are ignored and broadcasts are processed silently
+        let mut corrupted = request.clone();
+        corrupted[6] ^= 0x01;
+        assert!(slave.handle(&corrupted).is_none());
+        assert!(slave.handle(&rtu_frame(0x12, &[0x03, 0x00, 0x6B, 0x00, 0x01])).unwrap().1.is_none());
+        assert!(slave.handle(&rtu_frame(0x00, &[0x06, 0x00, 0x01, 0x00, 0x07])).unwrap().1.is_none());
+        assert_eq!(slave.tables.read().unwrap().read_registers(ModbusArea::HoldingRegisters, 1, 1).unwrap(), vec![7]);
+
+        let (transaction, response) = slave.handle(&rtu_frame(0x11, &[0x03, 0x00, 0xC8, 0x00, 0x01])).unwrap();
+        assert_eq!(response.unwrap(), rtu_frame(0x11, &[0x83, 0x02]));
+        assert_eq!(transaction.exception, Some(modbus_slave::ModbusException::IllegalDataAddress));
+    }
+
+    #[test]
+    fn test_fault_injection() {
+        let fault = RtuFault { kind: RtuFaultKind::Exception, exception_code: Some(0x06), every: Some(2), function_codes: vec![0x03] };
+        let slave = responder(ModbusRtuSlaveSettings { fault: Some(fault), ..Default::default() });
+        let read = rtu_frame(0x11, &[0x03, 0x00, 0x00, 0x00, 0x01]);
+
+        assert_eq!(slave.handle(&read).unwrap().1.unwrap(), rtu_frame(0x11, &[0x03, 0x02, 0x00, 0x00]));
+        let (transaction, response) = slave.handle(&read).unwrap();
+        assert_eq!(response.unwrap(), rtu_frame(0x11, &[0x83, 0x06]));
+        assert_eq!(transaction.fault, Some(RtuFaultKind::Exception));
+        // Other function codes are not counted
+        assert!(slave.handle(&rtu_frame(0x11, &[0x06, 0x00, 0x00, 0x00, 0x01])).unwrap().1.is_some());
+
+        slave.state.lock().unwrap().settings.fault =
+            Some(RtuFault { kind: RtuFaultKind::WrongCrc, exception_code: None, every: None, function_codes: Vec::new() });
+        let response = slave.handle(&read).unwrap().1.unwrap();
+        assert_eq!(&response[..5], &[0x11, 0x03, 0x02, 0x00, 0x01]);
+        assert!(!check_rtu_crc(&response));
+
+        slave.state.lock().unwrap().settings.fault =
+            Some(RtuFault { kind: RtuFaultKind::NoReply, exception_code: None, every: None, function_codes: Vec::new() });
+        assert!(slave.handle(&read).unwrap().1.is_none());
+
+        assert!(ModbusRtuSlaveSettings::from_config(&serde_json::json!({
+            "modbusRtuSlave": { "fault": { "kind": "noReply", "every": 0 } }
+        }))
+        .is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pty_slave() {
+        let (port, mut master) = SerialStream::pair().unwrap();
+        let config = serde_json::json!({
+            "serial": { "port": "pty", "baudRate": 19200 },
+            "modbusUnitId": 5,
+            "modbusRtuSlave": { "responseDelayMs": 5 }
+        });
+        let mut slave = ModbusRtuSlave::new("rtu-pty".to_string(), config, None).unwrap();
+        slave.attach(port);
+        assert!(slave.is_connected());
+        modbus_slave::write_bits("rtu-pty", ModbusArea::Coils, 2, &[true]).unwrap();
+
+        master.write_all(&rtu_frame(5, &[0x01, 0x00, 0x00, 0x00, 0x04])).await.unwrap();
+        let mut response = [0u8; 6];
+        tokio::time::timeout(Duration::from_secs(5), master.read_exact(&mut response)).await.unwrap().unwrap();
+        assert_eq!(response.to_vec(), rtu_frame(5, &[0x01, 0x01, 0x04]));
+
+        slave.disconnect().await.unwrap();
+        assert!(!slave.is_connected());
+        assert!(modbus_slave::read_bits("rtu-pty", ModbusArea::Coils, 0, 1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ascii_client_against_slave() {
+        use crate::network::modbus_transport::AsciiStream;
+        use tokio_modbus::prelude::*;
+
+        let (client, device) = tokio::io::duplex(1024);
+        let config = serde_json::json!({
+            "serial": { "port": "pty", "baudRate": 19200 },
+            "modbusUnitId": 7
+        });
+        let mut slave = ModbusRtuSlave::new("rtu-ascii".to_string(), config, None).unwrap();
+        // The slave end turns the ASCII frames back into the RTU frames it answers
+        slave.attach(AsciiStream::new(device));
+        slave.tables().write().unwrap().write_registers(ModbusArea::HoldingRegisters, 0x10, &[0x1234, 0x5678]).unwrap();
+
+        let mut context = rtu::attach_slave(AsciiStream::new(client), Slave(7));
+        let call = async {
+            let registers = context.read_holding_registers(0x10, 2).await.unwrap().unwrap();
+            context.write_single_coil(3, true).await.unwrap().unwrap();
+            let exception = context.read_holding_registers(0xFFF0, 1).await.unwrap().unwrap_err();
+            (registers, exception)
+        };
+        let (registers, exception) = tokio::time::timeout(Duration::from_secs(5), call).await.unwrap();
+        assert_eq!(registers, vec![0x1234, 0x5678]);
+        assert_eq!(exception, Exception::IllegalDataAddress);
+        assert_eq!(modbus_slave::read_bits("rtu-ascii", ModbusArea::Coils, 3, 1).unwrap(), vec![true]);
+
+        slave.disconnect().await.unwrap();
+    }
+}
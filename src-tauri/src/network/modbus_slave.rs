@@ -0,0 +1,948 @@
+//! Simulated Modbus slave
+//!
+//! `ModbusTables` holds the coils, discrete inputs, holding registers and
+//! input registers of a simulated device and answers request PDUs from them
+//! (FC 1/2/3/4/5/6/15/16/23), returning the standard exception codes for
+//! unsupported functions, out-of-range addresses and malformed requests.
+//! `ModbusTcpServer` serves the tables over Modbus TCP: it splits each client
+//! stream into MBAP frames, answers requests for its unit id (or 0xFF, the
+//! usual "this server" unit on TCP) and emits a `modbus-transaction` event per
+//! request. Requests for other units are not answered.
+
+use async_trait::async_trait;
+use crate::network::{Connection, ServerConnection};
+use crate::types::{NetworkError, NetworkEvent, NetworkResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+/// Event emitted for every request answered (or ignored) by a slave
+pub const MODBUS_TRANSACTION_EVENT: &str = "modbus-transaction";
+
+/// Entries per table of a simulated slave
+pub const DEFAULT_TABLE_SIZE: usize = 10000;
+
+/// MBAP header length: transaction id, protocol id, length and unit id
+pub const MBAP_HEADER_LENGTH: usize = 7;
+
+/// Largest PDU, so the largest MBAP length field is 1 + 253
+pub const MAX_PDU_LENGTH: usize = 253;
+
+/// Unit id addressing the server itself on Modbus TCP
+pub const TCP_SERVER_UNIT: u8 = 0xFF;
+
+/// Raw writes queued per client
+const COMMAND_QUEUE: usize = 64;
+
+/// Data area of a Modbus device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModbusArea {
+    Coils,
+    DiscreteInputs,
+    HoldingRegisters,
+    InputRegisters,
+}
+
+impl ModbusArea {
+    /// Whether the area holds single bits rather than 16-bit registers
+    pub fn is_bit(self) -> bool {
+        matches!(self, Self::Coils | Self::DiscreteInputs)
+    }
+}
+
+/// Modbus exception codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModbusException {
+    IllegalFunction = 0x01,
+    IllegalDataAddress = 0x02,
+    IllegalDataValue = 0x03,
+    ServerDeviceFailure = 0x04,
+    Acknowledge = 0x05,
+    ServerDeviceBusy = 0x06,
+    MemoryParityError = 0x08,
+    GatewayPathUnavailable = 0x0A,
+    GatewayTargetFailedToRespond = 0x0B,
+}
+
+impl ModbusException {
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x01 => Some(Self::IllegalFunction),
+            0x02 => Some(Self::IllegalDataAddress),
+            0x03 => Some(Self::IllegalDataValue),
+            0x04 => Some(Self::ServerDeviceFailure),
+            0x05 => Some(Self::Acknowledge),
+            0x06 => Some(Self::ServerDeviceBusy),
+            0x08 => Some(Self::MemoryParityError),
+            0x0A => Some(Self::GatewayPathUnavailable),
+            0x0B => Some(Self::GatewayTargetFailedToRespond),
+            _ => None,
+        }
+    }
+
+    /// Exception response PDU for a function code
+    pub fn pdu(self, function_code: u8) -> Vec<u8> {
+        vec![function_code | 0x80, self.code()]
+    }
+}
+
+impl std::fmt::Display for ModbusException {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::IllegalFunction => "illegal function",
+            Self::IllegalDataAddress => "illegal data address",
+            Self::IllegalDataValue => "illegal data value",
+            Self::ServerDeviceFailure => "server device failure",
+            Self::Acknowledge => "acknowledge",
+            Self::ServerDeviceBusy => "server device busy",
+            Self::MemoryParityError => "memory parity error",
+            Self::GatewayPathUnavailable => "gateway path unavailable",
+            Self::GatewayTargetFailedToRespond => "gateway target device failed to respond",
+        };
+        write!(f, "{} (0x{:02X})", text, self.code())
+    }
+}
+
+type ExceptionResult<T> = Result<T, ModbusException>;
+
+/// Big-endian u16 at `offset` of a request PDU
+fn word(pdu: &[u8], offset: usize) -> ExceptionResult<u16> {
+    pdu.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or(ModbusException::IllegalDataValue)
+}
+
+/// Pack bits LSB first, eight per byte
+pub fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; bits.len().div_ceil(8)];
+    for (i, _) in bits.iter().enumerate().filter(|(_, bit)| **bit) {
+        bytes[i / 8] |= 1 << (i % 8);
+    }
+    bytes
+}
+
+/// Unpack `quantity` bits packed LSB first
+pub fn unpack_bits(bytes: &[u8], quantity: usize) -> Vec<bool> {
+    (0..quantity).map(|i| bytes[i / 8] & (1 << (i % 8)) != 0).collect()
+}
+
+/// Register tables of a simulated slave
+#[derive(Debug, Clone)]
+pub struct ModbusTables {
+    coils: Vec<bool>,
+    discrete_inputs: Vec<bool>,
+    holding_registers: Vec<u16>,
+    input_registers: Vec<u16>,
+}
+
+impl Default for ModbusTables {
+    fn default() -> Self {
+        Self::new(DEFAULT_TABLE_SIZE)
+    }
+}
+
+impl ModbusTables {
+    /// Tables of `size` entries each, all zero
+    pub fn new(size: usize) -> Self {
+        Self {
+            coils: vec![false; size],
+            discrete_inputs: vec![false; size],
+            holding_registers: vec![0; size],
+            input_registers: vec![0; size],
+        }
+    }
+
+    fn bits(&self, area: ModbusArea) -> ExceptionResult<&Vec<bool>> {
+        match area {
+            ModbusArea::Coils => Ok(&self.coils),
+            ModbusArea::DiscreteInputs => Ok(&self.discrete_inputs),
+            _ => Err(ModbusException::IllegalFunction),
+        }
+    }
+
+    fn bits_mut(&mut self, area: ModbusArea) -> ExceptionResult<&mut Vec<bool>> {
+        match area {
+            ModbusArea::Coils => Ok(&mut self.coils),
+            ModbusArea::DiscreteInputs => Ok(&mut self.discrete_inputs),
+            _ => Err(ModbusException::IllegalFunction),
+        }
+    }
+
+    fn registers(&self, area: ModbusArea) -> ExceptionResult<&Vec<u16>> {
+        match area {
+            ModbusArea::HoldingRegisters => Ok(&self.holding_registers),
+            ModbusArea::InputRegisters => Ok(&self.input_registers),
+            _ => Err(ModbusException::IllegalFunction),
+        }
+    }
+
+    fn registers_mut(&mut self, area: ModbusArea) -> ExceptionResult<&mut Vec<u16>> {
+        match area {
+            ModbusArea::HoldingRegisters => Ok(&mut self.holding_registers),
+            ModbusArea::InputRegisters => Ok(&mut self.input_registers),
+            _ => Err(ModbusException::IllegalFunction),
+        }
+    }
+
+    /// Read bits from the coils or discrete inputs
+    pub fn read_bits(&self, area: ModbusArea, address: u16, quantity: u16) -> ExceptionResult<Vec<bool>> {
+        let table = self.bits(area)?;
+        let range = span(table.len(), address, quantity as usize)?;
+        Ok(table[range].to_vec())
+    }
+
+    /// Read holding or input registers
+    pub fn read_registers(&self, area: ModbusArea, address: u16, quantity: u16) -> ExceptionResult<Vec<u16>> {
+        let table = self.registers(area)?;
+        let range = span(table.len(), address, quantity as usize)?;
+        Ok(table[range].to_vec())
+    }
+
+    /// Write coils or discrete inputs
+    pub fn write_bits(&mut self, area: ModbusArea, address: u16, values: &[bool]) -> ExceptionResult<()> {
+        let table = self.bits_mut(area)?;
+        let range = span(table.len(), address, values.len())?;
+        table[range].copy_from_slice(values);
+        Ok(())
+    }
+
+    /// Write holding or input registers
+    pub fn write_registers(&mut self, area: ModbusArea, address: u16, values: &[u16]) -> ExceptionResult<()> {
+        let table = self.registers_mut(area)?;
+        let range = span(table.len(), address, values.len())?;
+        table[range].copy_from_slice(values);
+        Ok(())
+    }
+
+    /// Answer a request PDU with a response or exception PDU
+    pub fn process(&mut self, pdu: &[u8]) -> Vec<u8> {
+        let Some(&function_code) = pdu.first() else {
+            return ModbusException::IllegalFunction.pdu(0);
+        };
+        match self.respond(function_code, pdu) {
+            Ok(response) => response,
+            Err(exception) => exception.pdu(function_code),
+        }
+    }
+
+    fn respond(&mut self, function_code: u8, pdu: &[u8]) -> ExceptionResult<Vec<u8>> {
+        match function_code {
+            0x01 | 0x02 => {
+                let area = if function_code == 0x01 { ModbusArea::Coils } else { ModbusArea::DiscreteInputs };
+                let (address, quantity) = (word(pdu, 1)?, word(pdu, 3)?);
+                check_quantity(quantity, 2000)?;
+                let bytes = pack_bits(&self.read_bits(area, address, quantity)?);
+                Ok([vec![function_code, bytes.len() as u8], bytes].concat())
+            }
+            0x03 | 0x04 => {
+                let area = if function_code == 0x03 { ModbusArea::HoldingRegisters } else { ModbusArea::InputRegisters };
+                let (address, quantity) = (word(pdu, 1)?, word(pdu, 3)?);
+                check_quantity(quantity, 125)?;
+                let registers = self.read_registers(area, address, quantity)?;
+                Ok(register_response(function_code, &registers))
+            }
+            0x05 => {
+                let (address, value) = (word(pdu, 1)?, word(pdu, 3)?);
+                let state = match value {
+                    0xFF00 => true,
+                    0x0000 => false,
+                    _ => return Err(ModbusException::IllegalDataValue),
+                };
+                self.write_bits(ModbusArea::Coils, address, &[state])?;
+                Ok(pdu[..5].to_vec())
+            }
+            0x06 => {
+                let (address, value) = (word(pdu, 1)?, word(pdu, 3)?);
+                self.write_registers(ModbusArea::HoldingRegisters, address, &[value])?;
+                Ok(pdu[..5].to_vec())
+            }
+            0x0F => {
+                let (address, quantity) = (word(pdu, 1)?, word(pdu, 3)?);
+                check_quantity(quantity, 1968)?;
+                let data = byte_data(pdu, 5, (quantity as usize).div_ceil(8))?;
+                self.write_bits(ModbusArea::Coils, address, &unpack_bits(data, quantity as usize))?;
+                Ok(pdu[..5].to_vec())
+            }
+            0x10 => {
+                let (address, quantity) = (word(pdu, 1)?, word(pdu, 3)?);
+                check_quantity(quantity, 123)?;
+                let values = register_data(byte_data(pdu, 5, quantity as usize * 2)?);
+                self.write_registers(ModbusArea::HoldingRegisters, address, &values)?;
+                Ok(pdu[..5].to_vec())
+            }
+            0x17 => {
+                let (read_address, read_quantity) = (word(pdu, 1)?, word(pdu, 3)?);
+                let (write_address, write_quantity) = (word(pdu, 5)?, word(pdu, 7)?);
+                check_quantity(read_quantity, 125)?;
+                check_quantity(write_quantity, 121)?;
+                let values = register_data(byte_data(pdu, 9, write_quantity as usize * 2)?);
+                // Check the read range before writing so a failed request changes nothing
+                span(self.holding_registers.len(), read_address, read_quantity as usize)?;
+                self.write_registers(ModbusArea::HoldingRegisters, write_address, &values)?;
+                let registers = self.read_registers(ModbusArea::HoldingRegisters, read_address, read_quantity)?;
+                Ok(register_response(function_code, &registers))
+            }
+            _ => Err(ModbusException::IllegalFunction),
+        }
+    }
+}
+
+/// Table range of a request, or IllegalDataAddress when it runs off the table
+fn span(table_length: usize, address: u16, quantity: usize) -> ExceptionResult<std::ops::Range<usize>> {
+    let start = address as usize;
+    let end = start + quantity;
+    if end > table_length {
+        return Err(ModbusException::IllegalDataAddress);
+    }
+    Ok(start..end)
+}
+
+fn check_quantity(quantity: u16, max: u16) -> ExceptionResult<()> {
+    if quantity == 0 || quantity > max {
+        return Err(ModbusException::IllegalDataValue);
+    }
+    Ok(())
+}
+
+/// Data after the byte count at `offset`, checked against the expected count
+fn byte_data(pdu: &[u8], offset: usize, expected: usize) -> ExceptionResult<&[u8]> {
+    let count = *pdu.get(offset).ok_or(ModbusException::IllegalDataValue)? as usize;
+    if count != expected {
+        return Err(ModbusException::IllegalDataValue);
+    }
+    pdu.get(offset + 1..offset + 1 + count).ok_or(ModbusException::IllegalDataValue)
+}
+
+fn register_data(bytes: &[u8]) -> Vec<u16> {
+    bytes.chunks_exact(2).map(|pair| u16::from_be_bytes([pair[0], pair[1]])).collect()
+}
+
+fn register_response(function_code: u8, registers: &[u16]) -> Vec<u8> {
+    let mut response = vec![function_code, (registers.len() * 2) as u8];
+    response.extend(registers.iter().flat_map(|register| register.to_be_bytes()));
+    response
+}
+
+/// Modbus TCP application protocol header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MbapHeader {
+    pub transaction_id: u16,
+    pub protocol_id: u16,
+    /// Bytes following the length field: unit id and PDU
+    pub length: u16,
+    pub unit_id: u8,
+}
+
+impl MbapHeader {
+    /// Header for a PDU of `pdu_length` bytes
+    pub fn new(transaction_id: u16, unit_id: u8, pdu_length: usize) -> Self {
+        Self { transaction_id, protocol_id: 0, length: pdu_length as u16 + 1, unit_id }
+    }
+
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let header = data.get(..MBAP_HEADER_LENGTH)?;
+        Some(Self {
+            transaction_id: u16::from_be_bytes([header[0], header[1]]),
+            protocol_id: u16::from_be_bytes([header[2], header[3]]),
+            length: u16::from_be_bytes([header[4], header[5]]),
+            unit_id: header[6],
+        })
+    }
+
+    pub fn encode(&self) -> [u8; MBAP_HEADER_LENGTH] {
+        let [t0, t1] = self.transaction_id.to_be_bytes();
+        let [p0, p1] = self.protocol_id.to_be_bytes();
+        let [l0, l1] = self.length.to_be_bytes();
+        [t0, t1, p0, p1, l0, l1, self.unit_id]
+    }
+
+    /// Complete ADU with this header
+    pub fn frame(transaction_id: u16, unit_id: u8, pdu: &[u8]) -> Vec<u8> {
+        [&Self::new(transaction_id, unit_id, pdu.len()).encode()[..], pdu].concat()
+    }
+}
+
+/// Split one complete ADU off the front of a stream buffer
+pub fn take_mbap_frame(buffer: &mut Vec<u8>) -> NetworkResult<Option<Vec<u8>>> {
+    let Some(header) = MbapHeader::decode(buffer) else {
+        return Ok(None);
+    };
+    if header.protocol_id != 0 {
+        return Err(NetworkError::ProtocolError(format!("Unknown MBAP protocol id {}", header.protocol_id)));
+    }
+    if header.length < 2 || header.length as usize > MAX_PDU_LENGTH + 1 {
+        return Err(NetworkError::ProtocolError(format!("Invalid MBAP length {}", header.length)));
+    }
+
+    let total = MBAP_HEADER_LENGTH - 1 + header.length as usize;
+    if buffer.len() < total {
+        return Ok(None);
+    }
+    Ok(Some(buffer.drain(..total).collect()))
+}
+
+/// One request handled by a simulated slave
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusTransaction {
+    pub session_id: String,
+    pub client_id: Option<String>,
+    pub transaction_id: Option<u16>,
+    pub unit_id: u8,
+    pub function_code: u8,
+    /// Request ADU as received
+    pub request: Vec<u8>,
+    /// Response ADU sent, if any
+    pub response: Option<Vec<u8>>,
+    pub exception: Option<ModbusException>,
+    /// Fault injected into the response, if any
+    pub fault: Option<crate::network::modbus_rtu_slave::RtuFaultKind>,
+    pub timestamp: String,
+}
+
+impl ModbusTransaction {
+    pub fn new(session_id: &str, client_id: Option<&str>, unit_id: u8, request: &[u8], pdu: &[u8]) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            client_id: client_id.map(str::to_string),
+            transaction_id: None,
+            unit_id,
+            function_code: pdu.first().copied().unwrap_or(0),
+            request: request.to_vec(),
+            response: None,
+            exception: None,
+            fault: None,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Record the response ADU and its PDU's exception code
+    pub fn respond(&mut self, response: Vec<u8>, pdu: &[u8]) {
+        if pdu.first().is_some_and(|code| code & 0x80 != 0) {
+            self.exception = pdu.get(1).copied().and_then(ModbusException::from_code);
+        }
+        self.response = Some(response);
+    }
+
+    pub fn emit(&self, app_handle: &Option<AppHandle>) {
+        if let Some(app_handle) = app_handle {
+            if let Err(e) = app_handle.emit(MODBUS_TRANSACTION_EVENT, self) {
+                eprintln!("Modbus slave: Failed to emit {} event: {}", MODBUS_TRANSACTION_EVENT, e);
+            }
+        }
+    }
+}
+
+/// Tables of running slave sessions
+static SLAVES: OnceLock<Mutex<HashMap<String, Arc<RwLock<ModbusTables>>>>> = OnceLock::new();
+
+fn slaves() -> &'static Mutex<HashMap<String, Arc<RwLock<ModbusTables>>>> {
+    SLAVES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn slave_tables(session_id: &str) -> NetworkResult<Arc<RwLock<ModbusTables>>> {
+    slaves()
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| NetworkError::SessionNotFound(format!("{} is not a running Modbus slave", session_id)))
+}
+
+pub(crate) fn register_slave(session_id: &str, tables: Arc<RwLock<ModbusTables>>) {
+    slaves().lock().unwrap().insert(session_id.to_string(), tables);
+}
+
+pub(crate) fn unregister_slave(session_id: &str) {
+    slaves().lock().unwrap().remove(session_id);
+}
+
+fn table_error(area: ModbusArea, address: u16, exception: ModbusException) -> NetworkError {
+    match exception {
+        ModbusException::IllegalFunction => {
+            NetworkError::InvalidConfig(format!("{:?} does not hold this kind of value", area))
+        }
+        _ => NetworkError::InvalidConfig(format!("{:?} address {} is out of range", area, address)),
+    }
+}
+
+/// Read bits from a running slave session's coils or discrete inputs
+pub fn read_bits(session_id: &str, area: ModbusArea, address: u16, quantity: u16) -> NetworkResult<Vec<bool>> {
+    let tables = slave_tables(session_id)?;
+    let tables = tables.read().unwrap();
+    tables.read_bits(area, address, quantity).map_err(|e| table_error(area, address, e))
+}
+
+/// Read registers from a running slave session
+pub fn read_registers(session_id: &str, area: ModbusArea, address: u16, quantity: u16) -> NetworkResult<Vec<u16>> {
+    let tables = slave_tables(session_id)?;
+    let tables = tables.read().unwrap();
+    tables.read_registers(area, address, quantity).map_err(|e| table_error(area, address, e))
+}
+
+/// Write bits of a running slave session
+pub fn write_bits(session_id: &str, area: ModbusArea, address: u16, values: &[bool]) -> NetworkResult<()> {
+    let tables = slave_tables(session_id)?;
+    let mut tables = tables.write().unwrap();
+    tables.write_bits(area, address, values).map_err(|e| table_error(area, address, e))
+}
+
+/// Write registers of a running slave session
+pub fn write_registers(session_id: &str, area: ModbusArea, address: u16, values: &[u16]) -> NetworkResult<()> {
+    let tables = slave_tables(session_id)?;
+    let mut tables = tables.write().unwrap();
+    tables.write_registers(area, address, values).map_err(|e| table_error(area, address, e))
+}
+
+/// Where a client task reports its traffic
+#[derive(Debug, Clone)]
+struct SlaveContext {
+    session_id: String,
+    unit_id: u8,
+    tables: Arc<RwLock<ModbusTables>>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+}
+
+impl SlaveContext {
+    async fn send_event(&self, event_type: &str, client_id: &str, data: Option<Vec<u8>>, error: Option<String>) {
+        if let Some(tx) = &self.event_tx {
+            let event = NetworkEvent {
+                session_id: self.session_id.clone(),
+                event_type: event_type.to_string(),
+                data,
+                error,
+                client_id: Some(client_id.to_string()),
+                mqtt_topic: None,
+                mqtt_qos: None,
+                mqtt_retain: None,
+                sse_event: None,
+            };
+            let _ = tx.send(event).await;
+        }
+    }
+
+    fn emit_sent(&self, client_id: &str, data: &[u8]) {
+        if let Some(app_handle) = &self.app_handle {
+            let payload = serde_json::json!({
+                "sessionId": self.session_id,
+                "data": data.to_vec(),
+                "direction": "out",
+                "clientId": client_id
+            });
+            if let Err(e) = app_handle.emit("message-received", payload) {
+                eprintln!("ModbusTcpServer: Failed to emit message-received event: {}", e);
+            }
+        }
+    }
+
+    /// Answer one request ADU; requests for other units get no response
+    fn handle(&self, client_id: &str, request: &[u8]) -> (ModbusTransaction, Option<Vec<u8>>) {
+        let header = MbapHeader::decode(request).expect("framed ADU has a header");
+        let pdu = &request[MBAP_HEADER_LENGTH..];
+        let mut transaction = ModbusTransaction::new(&self.session_id, Some(client_id), header.unit_id, request, pdu);
+        transaction.transaction_id = Some(header.transaction_id);
+
+        if header.unit_id != self.unit_id && header.unit_id != TCP_SERVER_UNIT {
+            return (transaction, None);
+        }
+        let response_pdu = self.tables.write().unwrap().process(pdu);
+        let response = MbapHeader::frame(header.transaction_id, header.unit_id, &response_pdu);
+        transaction.respond(response.clone(), &response_pdu);
+        (transaction, Some(response))
+    }
+}
+
+/// Serve one client until it disconnects, sends a bad frame or is dropped by the server
+async fn serve_client(stream: TcpStream, context: SlaveContext, client_id: String, mut writes: mpsc::Receiver<Vec<u8>>) -> NetworkResult<()> {
+    let peer = stream.peer_addr().ok();
+    let (mut reader, mut writer) = stream.into_split();
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 1024];
+
+    loop {
+        tokio::select! {
+            read = reader.read(&mut chunk) => {
+                let n = read.map_err(|e| NetworkError::ReceiveFailed(e.to_string()))?;
+                if n == 0 {
+                    return Ok(());
+                }
+                buffer.extend_from_slice(&chunk[..n]);
+
+                while let Some(request) = take_mbap_frame(&mut buffer)? {
+                    context.send_event("data_received", &client_id, Some(request.clone()), None).await;
+                    if let Some(app_handle) = &context.app_handle {
+                        crate::network::emit_received(app_handle, "modbus", &context.session_id, Some(&client_id), peer, None, &request);
+                    }
+
+                    let (transaction, response) = context.handle(&client_id, &request);
+                    if let Some(response) = response {
+                        writer.write_all(&response).await.map_err(|e| NetworkError::SendFailed(e.to_string()))?;
+                        context.emit_sent(&client_id, &response);
+                    }
+                    transaction.emit(&context.app_handle);
+                }
+            }
+            write = writes.recv() => match write {
+                Some(data) => {
+                    writer.write_all(&data).await.map_err(|e| NetworkError::SendFailed(e.to_string()))?;
+                    context.emit_sent(&client_id, &data);
+                }
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+/// Modbus TCP Server (Slave Simulator)
+#[derive(Debug)]
+pub struct ModbusTcpServer {
+    session_id: String,
+    host: String,
+    port: u16,
+    actual_port: Option<u16>,
+    unit_id: u8,
+    tables: Arc<RwLock<ModbusTables>>,
+    clients: Arc<Mutex<HashMap<String, mpsc::Sender<Vec<u8>>>>>,
+    accept_task: Option<tokio::task::JoinHandle<()>>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+}
+
+impl ModbusTcpServer {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
+        let host = config["host"]
+            .as_str()
+            .unwrap_or("0.0.0.0")
+            .to_string();
+
+        let port = config["port"]
+            .as_u64()
+            .ok_or_else(|| NetworkError::InvalidConfig("Missing port".to_string()))?
+            as u16;
+
+        let unit_id = config["modbusUnitId"]
+            .as_u64()
+            .unwrap_or(1) as u8;
+
+        Ok(Self {
+            session_id,
+            host,
+            port,
+            actual_port: None,
+            unit_id,
+            tables: Arc::new(RwLock::new(ModbusTables::default())),
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            accept_task: None,
+            app_handle,
+            event_tx: None,
+        })
+    }
+
+    /// Shared register tables
+    pub fn tables(&self) -> Arc<RwLock<ModbusTables>> {
+        self.tables.clone()
+    }
+
+    /// Set holding register value (for testing/simulation)
+    pub async fn set_holding_register(&self, address: u16, value: u16) -> NetworkResult<()> {
+        self.tables
+            .write()
+            .unwrap()
+            .write_registers(ModbusArea::HoldingRegisters, address, &[value])
+            .map_err(|_| NetworkError::InvalidConfig(format!("Register address {} out of range", address)))
+    }
+
+    /// Get holding register value
+    pub async fn get_holding_register(&self, address: u16) -> NetworkResult<u16> {
+        self.tables
+            .read()
+            .unwrap()
+            .read_registers(ModbusArea::HoldingRegisters, address, 1)
+            .map(|values| values[0])
+            .map_err(|_| NetworkError::InvalidConfig(format!("Register address {} out of range", address)))
+    }
+
+    /// Send event to frontend
+    async fn send_event(&self, event_type: &str, data: Option<Vec<u8>>, error: Option<String>) {
+        if let Some(tx) = &self.event_tx {
+            let event = NetworkEvent {
+                session_id: self.session_id.clone(),
+                event_type: event_type.to_string(),
+                data,
+                error,
+                client_id: None,
+                mqtt_topic: None,
+                mqtt_qos: None,
+                mqtt_retain: None,
+                sse_event: None,
+            };
+            let _ = tx.send(event).await;
+        }
+    }
+
+    fn client(&self, client_id: &str) -> NetworkResult<mpsc::Sender<Vec<u8>>> {
+        self.clients.lock().unwrap().get(client_id).cloned().ok_or_else(|| {
+            NetworkError::SendFailed(format!("Client {} is not connected", client_id))
+        })
+    }
+}
+
+#[async_trait]
+impl Connection for ModbusTcpServer {
+    async fn connect(&mut self) -> NetworkResult<()> {
+        if self.accept_task.is_some() {
+            return Ok(());
+        }
+
+        eprintln!("ModbusTcpServer: Starting server on {}:{} (Unit ID: {})",
+                  self.host, self.port, self.unit_id);
+
+        let address = format!("{}:{}", self.host, self.port);
+        let listener = TcpListener::bind(&address)
+            .await
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to bind {}: {}", address, e)))?;
+        self.actual_port = listener.local_addr().ok().map(|addr| addr.port());
+
+        let context = SlaveContext {
+            session_id: self.session_id.clone(),
+            unit_id: self.unit_id,
+            tables: self.tables.clone(),
+            app_handle: self.app_handle.clone(),
+            event_tx: self.event_tx.clone(),
+        };
+        let clients = self.clients.clone();
+
+        self.accept_task = Some(tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        eprintln!("ModbusTcpServer: Accept failed for session {}: {}", context.session_id, e);
+                        continue;
+                    }
+                };
+                let client_id = peer.to_string();
+                let (writes, write_rx) = mpsc::channel(COMMAND_QUEUE);
+                clients.lock().unwrap().insert(client_id.clone(), writes);
+
+                context.send_event("client_connected", &client_id, None, None).await;
+                if let Some(app_handle) = &context.app_handle {
+                    let payload = serde_json::json!({
+                        "sessionId": context.session_id,
+                        "clientId": client_id,
+                        "remoteAddress": peer.ip().to_string(),
+                        "remotePort": peer.port(),
+                    });
+                    if let Err(e) = app_handle.emit("client-connected", payload) {
+                        eprintln!("ModbusTcpServer: Failed to emit client-connected event: {}", e);
+                    }
+                }
+
+                let context = context.clone();
+                let clients = clients.clone();
+                tokio::spawn(async move {
+                    let result = serve_client(stream, context.clone(), client_id.clone(), write_rx).await;
+                    clients.lock().unwrap().remove(&client_id);
+                    crate::parser::get_session_parsers().lock().unwrap().reset_stream(&context.session_id, Some(&client_id));
+                    crate::network::idle_gap::close_stream(&context.session_id, Some(&client_id));
+
+                    let error = result.err().map(|e| e.to_string());
+                    eprintln!("ModbusTcpServer: Client {} closed: {}", client_id, error.as_deref().unwrap_or("by peer"));
+                    context.send_event("client_disconnected", &client_id, None, error).await;
+                    if let Some(app_handle) = &context.app_handle {
+                        let payload = serde_json::json!({
+                            "sessionId": context.session_id,
+                            "clientId": client_id,
+                        });
+                        if let Err(e) = app_handle.emit("client-disconnected", payload) {
+                            eprintln!("ModbusTcpServer: Failed to emit client-disconnected event: {}", e);
+                        }
+                    }
+                });
+            }
+        }));
+        register_slave(&self.session_id, self.tables.clone());
+
+        eprintln!("ModbusTcpServer: Listening on {}:{}", self.host, self.actual_port.unwrap_or(self.port));
+        self.send_event("connected", None, None).await;
+
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> NetworkResult<()> {
+        let Some(task) = self.accept_task.take() else {
+            return Ok(());
+        };
+        task.abort();
+        // Dropping the write senders ends every client task
+        self.clients.lock().unwrap().clear();
+        unregister_slave(&self.session_id);
+
+        eprintln!("ModbusTcpServer: Server stopped");
+        self.send_event("disconnected", None, None).await;
+
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        self.broadcast(data).await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.accept_task.is_some()
+    }
+
+    fn status(&self) -> String {
+        if self.is_connected() {
+            format!("Server running on {}:{} (Unit ID: {}, {} clients)",
+                    self.host, self.actual_port.unwrap_or(self.port), self.unit_id, self.get_clients().len())
+        } else {
+            "Server stopped".to_string()
+        }
+    }
+
+    async fn start_receiving(&mut self) -> NetworkResult<mpsc::Receiver<NetworkEvent>> {
+        let (tx, rx) = mpsc::channel(100);
+        self.event_tx = Some(tx);
+        Ok(rx)
+    }
+
+    fn get_actual_port(&self) -> Option<u16> {
+        self.actual_port
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[async_trait]
+impl ServerConnection for ModbusTcpServer {
+    async fn send_to_client(&mut self, client_id: &str, data: &[u8]) -> NetworkResult<usize> {
+        self.client(client_id)?
+            .send(data.to_vec())
+            .await
+            .map_err(|_| NetworkError::SendFailed(format!("Client {} is not connected", client_id)))?;
+        Ok(data.len())
+    }
+
+    async fn broadcast(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        let clients: Vec<_> = self.clients.lock().unwrap().values().cloned().collect();
+        let mut sent = 0;
+        for client in clients {
+            if client.send(data.to_vec()).await.is_ok() {
+                sent += 1;
+            }
+        }
+        if sent == 0 {
+            return Err(NetworkError::SendFailed("No Modbus clients connected".to_string()));
+        }
+        Ok(data.len())
+    }
+
+    fn get_clients(&self) -> Vec<String> {
+        self.clients.lock().unwrap().keys().cloned().collect()
+    }
+
+    async fn disconnect_client(&mut self, client_id: &str) -> NetworkResult<()> {
+        self.clients
+            .lock()
+            .unwrap()
+            .remove(client_id)
+            .map(|_| ())
+            .ok_or_else(|| NetworkError::SendFailed(format!("Client {} is not connected", client_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_table_requests() {
+        let mut tables = ModbusTables::new(100);
+        tables.write_registers(ModbusArea::InputRegisters, 10, &[0x1234, 0xABCD]).unwrap();
+        tables.write_bits(ModbusArea::DiscreteInputs, 0, &[true, false, true]).unwrap();
+
+        assert_eq!(tables.process(&[0x04, 0x00, 0x0A, 0x00, 0x02]), vec![0x04, 0x04, 0x12, 0x34, 0xAB, 0xCD]);
+        assert_eq!(tables.process(&[0x02, 0x00, 0x00, 0x00, 0x03]), vec![0x02, 0x01, 0x05]);
+
+        // FC 15 writes ten coils, FC 1 reads them back
+        assert_eq!(tables.process(&[0x0F, 0x00, 0x13, 0x00, 0x0A, 0x02, 0xCD, 0x01]), vec![0x0F, 0x00, 0x13, 0x00, 0x0A]);
+        assert_eq!(tables.process(&[0x01, 0x00, 0x13, 0x00, 0x0A]), vec![0x01, 0x02, 0xCD, 0x01]);
+
+        assert_eq!(tables.process(&[0x06, 0x00, 0x01, 0x00, 0x03]), vec![0x06, 0x00, 0x01, 0x00, 0x03]);
+        assert_eq!(tables.process(&[0x10, 0x00, 0x02, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02]), vec![0x10, 0x00, 0x02, 0x00, 0x02]);
+        assert_eq!(tables.read_registers(ModbusArea::HoldingRegisters, 1, 3).unwrap(), vec![3, 10, 0x0102]);
+
+        // FC 23 writes before it reads
+        let response = tables.process(&[0x17, 0x00, 0x01, 0x00, 0x02, 0x00, 0x02, 0x00, 0x01, 0x02, 0x00, 0xFF]);
+        assert_eq!(response, vec![0x17, 0x04, 0x00, 0x03, 0x00, 0xFF]);
+
+        assert_eq!(tables.process(&[0x05, 0x00, 0x00, 0xFF, 0x00]), vec![0x05, 0x00, 0x00, 0xFF, 0x00]);
+        assert!(tables.read_bits(ModbusArea::Coils, 0, 1).unwrap()[0]);
+    }
+
+    #[test]
+    fn test_exceptions_and_framing() {
+        let mut tables = ModbusTables::new(100);
+        assert_eq!(tables.process(&[0x2B, 0x0E, 0x01, 0x00]), vec![0xAB, 0x01]);
+        assert_eq!(tables.process(&[0x03, 0x00, 0x63, 0x00, 0x02]), vec![0x83, 0x02]);
+        assert_eq!(tables.process(&[0x03, 0x00, 0x00, 0x00, 0x7E]), vec![0x83, 0x03]);
+        assert_eq!(tables.process(&[0x05, 0x00, 0x00, 0x12, 0x34]), vec![0x85, 0x03]);
+        assert_eq!(tables.process(&[0x10, 0x00, 0x00, 0x00, 0x02, 0x03, 0x00, 0x01, 0x00]), vec![0x90, 0x03]);
+        assert_eq!(tables.process(&[0x06, 0x00]), vec![0x86, 0x03]);
+        // A failed FC 23 leaves the tables untouched
+        assert_eq!(tables.process(&[0x17, 0x00, 0x63, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x02, 0x00, 0x01]), vec![0x97, 0x02]);
+        assert_eq!(tables.read_registers(ModbusArea::HoldingRegisters, 0, 1).unwrap(), vec![0]);
+        assert_eq!(tables.read_bits(ModbusArea::HoldingRegisters, 0, 1), Err(ModbusException::IllegalFunction));
+
+        let mut buffer = MbapHeader::frame(7, 1, &[0x03, 0x00, 0x00, 0x00, 0x01]);
+        buffer.extend_from_slice(&[0x00, 0x08, 0x00, 0x00]);
+        let frame = take_mbap_frame(&mut buffer).unwrap().unwrap();
+        assert_eq!(MbapHeader::decode(&frame).unwrap(), MbapHeader::new(7, 1, 5));
+        assert_eq!(take_mbap_frame(&mut buffer).unwrap(), None);
+        assert_eq!(buffer.len(), 4);
+
+        let mut bad = vec![0x00, 0x01, 0x00, 0x05, 0x00, 0x06, 0x01, 0x03];
+        assert!(take_mbap_frame(&mut bad).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_server_loopback() {
+        let config = serde_json::json!({ "host": "127.0.0.1", "port": 0, "modbusUnitId": 5 });
+        let mut server = ModbusTcpServer::new("modbus-slave".to_string(), config, None).unwrap();
+        server.connect().await.unwrap();
+        write_registers("modbus-slave", ModbusArea::HoldingRegisters, 100, &[0xBEEF]).unwrap();
+
+        let mut stream = TcpStream::connect(("127.0.0.1", server.get_actual_port().unwrap())).await.unwrap();
+        let mut response = [0u8; 11];
+
+        stream.write_all(&MbapHeader::frame(1, 5, &[0x03, 0x00, 0x64, 0x00, 0x01])).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), stream.read_exact(&mut response)).await.unwrap().unwrap();
+        assert_eq!(&response, &[0x00, 0x01, 0x00, 0x00, 0x00, 0x05, 0x05, 0x03, 0x02, 0xBE, 0xEF]);
+
+        // Requests for another unit are ignored; the next answer belongs to transaction 3
+        stream.write_all(&MbapHeader::frame(2, 9, &[0x06, 0x00, 0x00, 0x00, 0x01])).await.unwrap();
+        stream.write_all(&MbapHeader::frame(3, 0xFF, &[0x06, 0x00, 0x00, 0x00, 0x02])).await.unwrap();
+        let mut echo = [0u8; 12];
+        tokio::time::timeout(Duration::from_secs(5), stream.read_exact(&mut echo)).await.unwrap().unwrap();
+        assert_eq!(&echo[..2], &[0x00, 0x03]);
+        assert_eq!(read_registers("modbus-slave", ModbusArea::HoldingRegisters, 0, 1).unwrap(), vec![2]);
+
+        server.disconnect().await.unwrap();
+        assert!(read_registers("modbus-slave", ModbusArea::HoldingRegisters, 0, 1).is_err());
+    }
+}
@@ -0,0 +1,449 @@
+//! Modbus transports besides MBAP over TCP and RTU over serial lines
+//!
+//! RTU over TCP/UDP carries plain RTU frames (address, PDU, CRC) over an IP
+//! socket, the way serial gateways tunnel them. Modbus ASCII frames every
+//! message as `:`, hex address and PDU, hex LRC and CR LF, on serial ports or
+//! TCP. All of them run the tokio-modbus RTU client over a byte stream:
+//! `DatagramStream` turns a connected UDP socket into one, and `AsciiStream`
+//! rewrites the RTU frames to ASCII and back.
+//!
+//! The RTU client only knows the response length of the standard functions.
+//! Other function codes (diagnostics, device identification, vendor codes)
+//! are framed directly on the session's `SharedStream` with `rtu_exchange`,
+//! which ends the response at a silent interval and checks its CRC.
+
+use crate::network::idle_gap::IdleGapFramer;
+use crate::network::modbus_rtu_slave::{check_rtu_crc, rtu_frame};
+use crate::parser::checksum::lrc8;
+use crate::types::{NetworkError, NetworkResult};
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::UdpSocket;
+
+/// Silent interval that ends an RTU frame received over a socket or an
+/// ASCII stream, where character times do not apply
+pub const NETWORK_FRAME_GAP: Duration = Duration::from_millis(20);
+
+/// How a Modbus client session reaches its device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModbusTransport {
+    RtuOverTcp,
+    RtuOverUdp,
+    AsciiTcp,
+    AsciiSerial,
+}
+
+impl ModbusTransport {
+    /// Transport of a session protocol; `modbus-ascii` runs on a serial port
+    /// when the session has serial settings and over TCP otherwise
+    pub fn from_protocol(protocol: &str, has_serial: bool) -> NetworkResult<Self> {
+        match protocol.to_lowercase().as_str() {
+            "modbus-rtu-over-tcp" => Ok(Self::RtuOverTcp),
+            "modbus-rtu-over-udp" => Ok(Self::RtuOverUdp),
+            "modbus-ascii" if has_serial => Ok(Self::AsciiSerial),
+            "modbus-ascii" => Ok(Self::AsciiTcp),
+            other => Err(NetworkError::InvalidConfig(format!("Unknown Modbus transport: {}", other))),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RtuOverTcp => "RTU over TCP",
+            Self::RtuOverUdp => "RTU over UDP",
+            Self::AsciiTcp => "ASCII over TCP",
+            Self::AsciiSerial => "ASCII",
+        }
+    }
+}
+
+/// ASCII frame of an address and PDU
+pub fn ascii_encode(adu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(adu.len() * 2 + 5);
+    frame.push(b':');
+    frame.extend(hex::encode_upper(adu).bytes());
+    frame.extend(hex::encode_upper([lrc8(adu)]).bytes());
+    frame.extend(b"\r\n");
+    frame
+}
+
+/// Address and PDU of the hex content between `:` and CR LF, LRC checked
+pub fn ascii_decode(content: &[u8]) -> NetworkResult<Vec<u8>> {
+    let bytes = hex::decode(content).map_err(|e| {
+        NetworkError::ParseError(format!("Invalid Modbus ASCII frame {}: {}", String::from_utf8_lossy(content), e))
+    })?;
+    match bytes.split_last() {
+        Some((&check, adu)) if adu.len() >= 2 => {
+            if lrc8(adu) != check {
+                return Err(NetworkError::ParseError(format!(
+                    "Modbus ASCII LRC mismatch: expected {:02X}, got {:02X}", lrc8(adu), check
+                )));
+            }
+            Ok(adu.to_vec())
+        }
+        _ => Err(NetworkError::ParseError(format!(
+            "Modbus ASCII frame too short: {}", String::from_utf8_lossy(content)
+        ))),
+    }
+}
+
+/// Take the content of the next complete ASCII frame out of `buffer`
+///
+/// A `:` always starts a new frame, so anything before the last `:` ahead of
+/// the line end is noise or an aborted frame and is dropped.
+fn take_ascii_frame(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    loop {
+        let Some(end) = buffer.iter().position(|&b| b == b'\n') else {
+            match buffer.iter().rposition(|&b| b == b':') {
+                Some(start) => {
+                    buffer.drain(..start);
+                }
+                None => buffer.clear(),
+            }
+            return None;
+        };
+        let start = buffer[..end].iter().rposition(|&b| b == b':');
+        let line: Vec<u8> = buffer.drain(..=end).collect();
+        if let Some(start) = start {
+            let content = &line[start + 1..end];
+            return Some(content.strip_suffix(b"\r").unwrap_or(content).to_vec());
+        }
+    }
+}
+
+/// Byte stream speaking Modbus ASCII on `inner` while its user reads and
+/// writes RTU frames
+///
+/// Each flush sends the RTU frame written since the last one, with the CRC
+/// replaced by an LRC; each received ASCII frame reads back as an RTU frame
+/// with a fresh CRC.
+#[derive(Debug)]
+pub struct AsciiStream<T> {
+    inner: T,
+    /// RTU frame written by the client, converted on flush
+    request: Vec<u8>,
+    /// ASCII frame being written to `inner`
+    outgoing: Vec<u8>,
+    written: usize,
+    /// Bytes received on `inner` that do not form a frame yet
+    inbound: Vec<u8>,
+    /// RTU frame decoded from `inbound`, not read yet
+    decoded: Vec<u8>,
+}
+
+impl<T> AsciiStream<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            request: Vec::new(),
+            outgoing: Vec::new(),
+            written: 0,
+            inbound: Vec::new(),
+            decoded: Vec::new(),
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for AsciiStream<T> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if !this.decoded.is_empty() {
+                let count = this.decoded.len().min(buf.remaining());
+                buf.put_slice(&this.decoded[..count]);
+                this.decoded.drain(..count);
+                return Poll::Ready(Ok(()));
+            }
+            if let Some(content) = take_ascii_frame(&mut this.inbound) {
+                let adu = ascii_decode(&content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+                this.decoded = rtu_frame(adu[0], &adu[1..]);
+                continue;
+            }
+
+            let mut chunk = [0u8; 256];
+            let mut read = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut read))?;
+            if read.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            this.inbound.extend_from_slice(read.filled());
+        }
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for AsciiStream<T> {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, data: &[u8]) -> Poll<io::Result<usize>> {
+        self.get_mut().request.extend_from_slice(data);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.outgoing.is_empty() && this.request.len() > 2 {
+            // ASCII carries an LRC instead of the CRC the RTU client appended
+            this.outgoing = ascii_encode(&this.request[..this.request.len() - 2]);
+            this.request.clear();
+        }
+        while this.written < this.outgoing.len() {
+            let count = ready!(Pin::new(&mut this.inner).poll_write(cx, &this.outgoing[this.written..]))?;
+            if count == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            this.written += count;
+        }
+        this.outgoing.clear();
+        this.written = 0;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+/// Byte stream over a connected UDP socket: each flush sends one datagram
+/// and each datagram reads back whole
+#[derive(Debug)]
+pub struct DatagramStream {
+    socket: UdpSocket,
+    outgoing: Vec<u8>,
+}
+
+impl DatagramStream {
+    pub fn new(socket: UdpSocket) -> Self {
+        Self { socket, outgoing: Vec::new() }
+    }
+}
+
+impl AsyncRead for DatagramStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        self.socket.poll_recv(cx, buf)
+    }
+}
+
+impl AsyncWrite for DatagramStream {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut Context<'_>, data: &[u8]) -> Poll<io::Result<usize>> {
+        self.get_mut().outgoing.extend_from_slice(data);
+        Poll::Ready(Ok(data.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.outgoing.is_empty() {
+            ready!(this.socket.poll_send(cx, &this.outgoing))?;
+            this.outgoing.clear();
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+/// Byte stream a Modbus client session runs on
+pub trait ByteStream: AsyncRead + AsyncWrite + Unpin + Send + std::fmt::Debug {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + std::fmt::Debug> ByteStream for T {}
+
+/// Handle to a session's byte stream, shared by its tokio-modbus context and
+/// requests framed directly. Callers hold the session's context lock while
+/// using it, so the two never interleave.
+#[derive(Debug, Clone)]
+pub struct SharedStream(Arc<Mutex<Box<dyn ByteStream>>>);
+
+impl SharedStream {
+    pub fn new(stream: impl ByteStream + 'static) -> Self {
+        Self(Arc::new(Mutex::new(Box::new(stream))))
+    }
+}
+
+impl AsyncRead for SharedStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let mut stream = self.0.lock().unwrap();
+        Pin::new(&mut **stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for SharedStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, data: &[u8]) -> Poll<io::Result<usize>> {
+        let mut stream = self.0.lock().unwrap();
+        Pin::new(&mut **stream).poll_write(cx, data)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut stream = self.0.lock().unwrap();
+        Pin::new(&mut **stream).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut stream = self.0.lock().unwrap();
+        Pin::new(&mut **stream).poll_shutdown(cx)
+    }
+}
+
+/// Send a request PDU as an RTU frame without waiting for a response
+pub async fn rtu_send<T: AsyncWrite + Unpin>(stream: &mut T, address: u8, pdu: &[u8]) -> NetworkResult<()> {
+    stream.write_all(&rtu_frame(address, pdu)).await
+        .map_err(|e| NetworkError::SendFailed(format!("Failed to send RTU request: {}", e)))?;
+    stream.flush().await
+        .map_err(|e| NetworkError::SendFailed(format!("Failed to send RTU request: {}", e)))
+}
+
+/// Send a request PDU as an RTU frame and read the response frame, which
+/// ends at the first silent interval of `frame_gap`
+///
+/// Returns the response PDU once its CRC and address check out.
+pub async fn rtu_exchange<T: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut T,
+    address: u8,
+    pdu: &[u8],
+    frame_gap: Duration,
+    timeout: Duration,
+) -> NetworkResult<Vec<u8>> {
+    rtu_send(stream, address, pdu).await?;
+
+    let deadline = Instant::now() + timeout;
+    let mut framer = IdleGapFramer::new(frame_gap);
+    let mut buffer = [0u8; 512];
+    let frame = loop {
+        let wait_until = framer.deadline().map_or(deadline, |gap_end| gap_end.min(deadline));
+        match tokio::time::timeout_at(tokio::time::Instant::from_std(wait_until), stream.read(&mut buffer)).await {
+            Ok(Ok(0)) => match framer.flush() {
+                Some(frame) => break frame,
+                None => {
+                    return Err(NetworkError::ReceiveFailed("Connection closed before the RTU response arrived".to_string()));
+                }
+            },
+            Ok(Ok(count)) => {
+                if let Some(frame) = framer.push(&buffer[..count], Instant::now()).into_iter().next() {
+                    break frame;
+                }
+            }
+            Ok(Err(e)) => return Err(NetworkError::ReceiveFailed(format!("Failed to read RTU response: {}", e))),
+            Err(_) => {
+                if let Some(frame) = framer.flush_idle(Instant::now()) {
+                    break frame;
+                }
+                if Instant::now() >= deadline {
+                    return Err(NetworkError::ReceiveFailed(format!(
+                        "RTU response timed out after {} ms", timeout.as_millis()
+                    )));
+                }
+            }
+        }
+    };
+
+    if !check_rtu_crc(&frame) {
+        return Err(NetworkError::ProtocolError(format!("RTU response has a bad CRC: {}", hex::encode(&frame))));
+    }
+    if frame[0] != address {
+        return Err(NetworkError::ProtocolError(format!(
+            "RTU response came from unit {} instead of {}", frame[0], address
+        )));
+    }
+    Ok(frame[1..frame.len() - 2].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[test]
+    fn test_ascii_framing() {
+        // Spec example: read 3 holding registers at 0x006B of slave 0x11
+        let adu = [0x11, 0x03, 0x00, 0x6B, 0x00, 0x03];
+        assert_eq!(lrc8(&adu), 0x7E);
+        assert_eq!(ascii_encode(&adu), b":1103006B00037E\r\n".to_vec());
+        assert_eq!(ascii_decode(b"1103006B00037E").unwrap(), adu.to_vec());
+        assert!(ascii_decode(b"1103006B00037F").is_err());
+        assert!(ascii_decode(b"11EF").is_err());
+
+        let mut buffer = b"noise:11:1103006B00037E\r\n:0103".to_vec();
+        assert_eq!(take_ascii_frame(&mut buffer), Some(b"1103006B00037E".to_vec()));
+        assert_eq!(take_ascii_frame(&mut buffer), None);
+        assert_eq!(buffer, b":0103".to_vec());
+
+        assert_eq!(ModbusTransport::from_protocol("modbus-ascii", true).unwrap(), ModbusTransport::AsciiSerial);
+        assert_eq!(ModbusTransport::from_protocol("Modbus-RTU-over-UDP", false).unwrap(), ModbusTransport::RtuOverUdp);
+        assert!(ModbusTransport::from_protocol("modbus-rtu", false).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ascii_stream_rewrites_rtu_frames() {
+        let (client, mut device) = tokio::io::duplex(256);
+        let mut stream = AsciiStream::new(client);
+
+        stream.write_all(&rtu_frame(0x11, &[0x03, 0x00, 0x6B, 0x00, 0x03])).await.unwrap();
+        stream.flush().await.unwrap();
+        let mut sent = [0u8; 17];
+        device.read_exact(&mut sent).await.unwrap();
+        assert_eq!(&sent, b":1103006B00037E\r\n");
+
+        device.write_all(b"\r\n:1103020001E9\r\n").await.unwrap();
+        let expected = rtu_frame(0x11, &[0x03, 0x02, 0x00, 0x01]);
+        let mut received = vec![0u8; expected.len()];
+        stream.read_exact(&mut received).await.unwrap();
+        assert_eq!(received, expected);
+
+        device.write_all(b":1103020001E8\r\n").await.unwrap();
+        let error = stream.read(&mut received).await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[tokio::test]
+    async fn test_datagram_stream() {
+        let device = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        socket.connect(device.local_addr().unwrap()).await.unwrap();
+        let mut stream = DatagramStream::new(socket);
+
+        let request = rtu_frame(0x01, &[0x03, 0x00, 0x00, 0x00, 0x01]);
+        stream.write_all(&request).await.unwrap();
+        stream.flush().await.unwrap();
+        let mut datagram = [0u8; 64];
+        let (count, peer) = device.recv_from(&mut datagram).await.unwrap();
+        assert_eq!(&datagram[..count], &request[..]);
+
+        let response = rtu_frame(0x01, &[0x03, 0x02, 0x12, 0x34]);
+        device.send_to(&response, peer).await.unwrap();
+        let mut received = [0u8; 64];
+        let count = stream.read(&mut received).await.unwrap();
+        assert_eq!(&received[..count], &response[..]);
+    }
+
+    #[tokio::test]
+    async fn test_rtu_exchange_ends_at_silent_interval() {
+        let (client, mut device) = tokio::io::duplex(256);
+        let mut stream = SharedStream::new(client);
+        let gap = Duration::from_millis(20);
+        let timeout = Duration::from_secs(2);
+
+        // Diagnostics echo, sent in two chunks within the silent interval
+        let request = [0x08, 0x00, 0x00, 0xA5, 0x37];
+        let device_side = async {
+            let mut received = [0u8; 8];
+            device.read_exact(&mut received).await.unwrap();
+            assert_eq!(received.to_vec(), rtu_frame(0x11, &request));
+            let response = rtu_frame(0x11, &request);
+            device.write_all(&response[..3]).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            device.write_all(&response[3..]).await.unwrap();
+        };
+        let (pdu, ()) = tokio::join!(rtu_exchange(&mut stream, 0x11, &request, gap, timeout), device_side);
+        assert_eq!(pdu.unwrap(), request.to_vec());
+
+        let mut corrupted = rtu_frame(0x11, &[0x41, 0x01]);
+        corrupted[3] ^= 0xFF;
+        device.write_all(&corrupted).await.unwrap();
+        let error = rtu_exchange(&mut stream, 0x11, &[0x41], gap, timeout).await.unwrap_err();
+        assert!(error.to_string().contains("bad CRC"), "{}", error);
+
+        let error = rtu_exchange(&mut stream, 0x11, &[0x41], gap, Duration::from_millis(50)).await.unwrap_err();
+        assert!(error.to_string().contains("timed out"), "{}", error);
+    }
+}
@@ -3,7 +3,9 @@ use crate::network::{Connection, MqttConnection};
 use crate::types::{NetworkResult, NetworkError, NetworkEvent};
 use tokio::sync::mpsc;
 use std::collections::HashSet;
-use rumqttc::{AsyncClient, MqttOptions, Event, Packet, QoS};
+use rumqttc::{AsyncClient, EventLoop, MqttOptions, Event, Packet, QoS};
+use tauri::AppHandle;
+use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 /// MQTT Client implementation
@@ -17,10 +19,13 @@ pub struct MqttClient {
     subscriptions: HashSet<String>,
     connected: bool,
     client: Option<AsyncClient>,
+    app_handle: Option<AppHandle>,
+    events: Option<mpsc::Receiver<NetworkEvent>>,
+    receive_task: Option<JoinHandle<()>>,
 }
 
 impl MqttClient {
-    pub fn new(session_id: String, config: serde_json::Value) -> NetworkResult<Self> {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
         let host = config.get("host")
             .and_then(|v| v.as_str())
             .unwrap_or("127.0.0.1");
@@ -52,10 +57,63 @@ impl MqttClient {
             subscriptions: HashSet::new(),
             connected: false,
             client: None,
+            app_handle,
+            events: None,
+            receive_task: None,
         })
     }
 }
 
+/// Drive the MQTT event loop, delivering every publish to the session's
+/// parsers and to the event channel
+async fn run_event_loop(
+    mut eventloop: EventLoop,
+    session_id: String,
+    app_handle: Option<AppHandle>,
+    tx: mpsc::Sender<NetworkEvent>,
+) {
+    loop {
+        match eventloop.poll().await {
+            Ok(Event::Incoming(Packet::Publish(publish))) => {
+                if let Some(app_handle) = &app_handle {
+                    crate::network::emit_received(app_handle, "mqtt", &session_id, None, None, Some(&publish.topic), &publish.payload);
+                }
+
+                // Nobody may be reading the channel; never stall the event loop on it
+                let _ = tx.try_send(NetworkEvent {
+                    session_id: session_id.clone(),
+                    event_type: "message".to_string(),
+                    data: Some(publish.payload.to_vec()),
+                    error: None,
+                    client_id: None,
+                    mqtt_topic: Some(publish.topic.clone()),
+                    mqtt_qos: Some(publish.qos as u8),
+                    mqtt_retain: Some(publish.retain),
+                    sse_event: None,
+                });
+            }
+            Ok(_) => {
+                // Handle other MQTT events if needed
+            }
+            Err(e) => {
+                eprintln!("MqttClient: Event loop for session {} stopped: {}", session_id, e);
+                let _ = tx.try_send(NetworkEvent {
+                    session_id: session_id.clone(),
+                    event_type: "error".to_string(),
+                    data: None,
+                    error: Some(format!("MQTT error: {}", e)),
+                    client_id: None,
+                    mqtt_topic: None,
+                    mqtt_qos: None,
+                    mqtt_retain: None,
+                    sse_event: None,
+                });
+                break;
+            }
+        }
+    }
+}
+
 #[async_trait]
 impl Connection for MqttClient {
     async fn connect(&mut self) -> NetworkResult<()> {
@@ -86,22 +144,22 @@ impl Connection for MqttClient {
         let (client, mut eventloop) = AsyncClient::new(mqttoptions, 10);
 
         // Test connection by polling once
-        match eventloop.poll().await {
-            Ok(Event::Incoming(Packet::ConnAck(_))) => {
-                self.client = Some(client);
-                self.connected = true;
-                Ok(())
-            }
-            Ok(_) => {
-                // Continue polling until we get ConnAck or error
-                self.client = Some(client);
-                self.connected = true;
-                Ok(())
-            }
-            Err(e) => {
-                Err(NetworkError::ConnectionFailed(format!("MQTT connection failed: {}", e)))
-            }
+        if let Err(e) = eventloop.poll().await {
+            return Err(NetworkError::ConnectionFailed(format!("MQTT connection failed: {}", e)));
         }
+
+        // Keep polling the same event loop; subscriptions and publishes go through it
+        let (tx, rx) = mpsc::channel(1000);
+        self.receive_task = Some(tokio::spawn(run_event_loop(
+            eventloop,
+            self.session_id.clone(),
+            self.app_handle.clone(),
+            tx,
+        )));
+        self.events = Some(rx);
+        self.client = Some(client);
+        self.connected = true;
+        Ok(())
     }
 
     async fn disconnect(&mut self) -> NetworkResult<()> {
@@ -112,8 +170,12 @@ impl Connection for MqttClient {
         if let Some(client) = &self.client {
             let _ = client.disconnect().await; // Ignore disconnect errors
         }
+        if let Some(task) = self.receive_task.take() {
+            task.abort();
+        }
 
         self.client = None;
+        self.events = None;
         self.connected = false;
         self.subscriptions.clear();
         Ok(())
@@ -141,63 +203,9 @@ impl Connection for MqttClient {
             return Err(NetworkError::NotConnected);
         }
 
-        let (tx, rx) = mpsc::channel(1000);
-
-        if let Some(client) = &self.client {
-            // Clone client for background task
-            let _client_clone = client.clone();
-            let session_id = self.session_id.clone();
-
-            // Spawn background task to handle MQTT events
-            tokio::spawn(async move {
-                // Create new eventloop for receiving
-                let mqttoptions = MqttOptions::new("receiver", "localhost", 1883);
-                let (_client, mut eventloop) = AsyncClient::new(mqttoptions, 10);
-
-                loop {
-                    match eventloop.poll().await {
-                        Ok(Event::Incoming(Packet::Publish(publish))) => {
-                            let event = NetworkEvent {
-                                session_id: session_id.clone(),
-                                event_type: "message".to_string(),
-                                data: Some(publish.payload.to_vec()),
-                                error: None,
-                                client_id: None,
-                                mqtt_topic: Some(publish.topic.clone()),
-                                mqtt_qos: Some(publish.qos as u8),
-                                mqtt_retain: Some(publish.retain),
-                                sse_event: None,
-                            };
-
-                            if tx.send(event).await.is_err() {
-                                break; // Channel closed
-                            }
-                        }
-                        Ok(_) => {
-                            // Handle other MQTT events if needed
-                        }
-                        Err(e) => {
-                            let error_event = NetworkEvent {
-                                session_id: session_id.clone(),
-                                event_type: "error".to_string(),
-                                data: None,
-                                error: Some(format!("MQTT error: {}", e)),
-                                client_id: None,
-                                mqtt_topic: None,
-                                mqtt_qos: None,
-                                mqtt_retain: None,
-                                sse_event: None,
-                            };
-
-                            let _ = tx.send(error_event).await;
-                            break;
-                        }
-                    }
-                }
-            });
-        }
-
-        Ok(rx)
+        // The event loop started on connect feeds a single receiver
+        self.events.take()
+            .ok_or_else(|| NetworkError::ConnectionFailed("MQTT events are already being received".to_string()))
     }
 
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
@@ -0,0 +1,472 @@
+//! Raw serial port sessions
+//!
+//! `SerialConnection` opens an RS-232/485 port with the line settings from
+//! `serial` in the session config and exchanges raw bytes, emitting the same
+//! `message-received`, `connection-status` and parsed message events as a TCP
+//! client. One task owns the port; writes and DTR/RTS changes are handed to it
+//! over a channel so they never contend with the pending read.
+
+use async_trait::async_trait;
+use crate::network::Connection;
+use crate::types::{NetworkError, NetworkEvent, NetworkResult};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{mpsc, oneshot};
+use tokio_serial::{SerialPort, SerialPortBuilderExt, SerialStream};
+
+/// Requests queued for the port task
+const COMMAND_QUEUE: usize = 64;
+
+/// Port and line settings, read from `serial` in the session config
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SerialSettings {
+    /// Port name, e.g. `COM3` or `/dev/ttyUSB0`
+    pub port: String,
+    pub baud_rate: u32,
+    /// 5, 6, 7 or 8
+    pub data_bits: u8,
+    /// `none`, `even` or `odd`
+    pub parity: String,
+    /// 1 or 2
+    pub stop_bits: u8,
+    /// `none`, `software` (XON/XOFF) or `hardware` (RTS/CTS)
+    pub flow_control: String,
+    /// DTR level set after opening; left as the driver sets it when absent
+    pub dtr: Option<bool>,
+    /// RTS level set after opening; left as the driver sets it when absent
+    pub rts: Option<bool>,
+}
+
+impl Default for SerialSettings {
+    fn default() -> Self {
+        Self {
+            port: String::new(),
+            baud_rate: 9600,
+            data_bits: 8,
+            parity: "none".to_string(),
+            stop_bits: 1,
+            flow_control: "none".to_string(),
+            dtr: None,
+            rts: None,
+        }
+    }
+}
+
+impl SerialSettings {
+    /// Read the settings from a session config
+    pub fn from_config(config: &serde_json::Value) -> NetworkResult<Self> {
+        let settings: Self = match config.get("serial") {
+            Some(value) if !value.is_null() => serde_json::from_value(value.clone())
+                .map_err(|e| NetworkError::InvalidConfig(format!("Invalid serial settings: {}", e)))?,
+            _ => return Err(NetworkError::InvalidConfig("Missing serial settings".to_string())),
+        };
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Check the port name and line settings
+    pub fn validate(&self) -> NetworkResult<()> {
+        if self.port.trim().is_empty() {
+            return Err(NetworkError::InvalidConfig("Missing serial port name".to_string()));
+        }
+        if self.baud_rate == 0 {
+            return Err(NetworkError::InvalidConfig("Baud rate must be greater than 0".to_string()));
+        }
+        self.data_bits()?;
+        self.parity()?;
+        self.stop_bits()?;
+        self.flow_control()?;
+        Ok(())
+    }
+
+    fn data_bits(&self) -> NetworkResult<tokio_serial::DataBits> {
+        match self.data_bits {
+            5 => Ok(tokio_serial::DataBits::Five),
+            6 => Ok(tokio_serial::DataBits::Six),
+            7 => Ok(tokio_serial::DataBits::Seven),
+            8 => Ok(tokio_serial::DataBits::Eight),
+            other => Err(NetworkError::InvalidConfig(format!("Data bits must be 5..=8, got {}", other))),
+        }
+    }
+
+    fn parity(&self) -> NetworkResult<tokio_serial::Parity> {
+        match self.parity.to_ascii_lowercase().as_str() {
+            "none" => Ok(tokio_serial::Parity::None),
+            "even" => Ok(tokio_serial::Parity::Even),
+            "odd" => Ok(tokio_serial::Parity::Odd),
+            other => Err(NetworkError::InvalidConfig(format!("Unknown parity: {}", other))),
+        }
+    }
+
+    fn stop_bits(&self) -> NetworkResult<tokio_serial::StopBits> {
+        match self.stop_bits {
+            1 => Ok(tokio_serial::StopBits::One),
+            2 => Ok(tokio_serial::StopBits::Two),
+            other => Err(NetworkError::InvalidConfig(format!("Stop bits must be 1 or 2, got {}", other))),
+        }
+    }
+
+    fn flow_control(&self) -> NetworkResult<tokio_serial::FlowControl> {
+        match self.flow_control.to_ascii_lowercase().as_str() {
+            "none" => Ok(tokio_serial::FlowControl::None),
+            "software" | "xonxoff" => Ok(tokio_serial::FlowControl::Software),
+            "hardware" | "rtscts" => Ok(tokio_serial::FlowControl::Hardware),
+            other => Err(NetworkError::InvalidConfig(format!("Unknown flow control: {}", other))),
+        }
+    }
+
+    /// Line settings as text, e.g. `9600 8N1`
+    pub fn describe(&self) -> String {
+        let parity = self.parity.chars().next().unwrap_or('n').to_ascii_uppercase();
+        format!("{} {}{}{}", self.baud_rate, self.data_bits, parity, self.stop_bits)
+    }
+
+    /// Open the port and apply the configured DTR/RTS levels
+    pub(crate) fn open(&self) -> NetworkResult<SerialStream> {
+        let mut port = tokio_serial::new(&self.port, self.baud_rate)
+            .data_bits(self.data_bits()?)
+            .parity(self.parity()?)
+            .stop_bits(self.stop_bits()?)
+            .flow_control(self.flow_control()?)
+            .open_native_async()
+            .map_err(|e| NetworkError::ConnectionFailed(format!("Failed to open serial port {}: {}", self.port, e)))?;
+        set_control_lines(&mut port, self.dtr, self.rts)?;
+        Ok(port)
+    }
+}
+
+fn set_control_lines(port: &mut SerialStream, dtr: Option<bool>, rts: Option<bool>) -> NetworkResult<()> {
+    if let Some(level) = dtr {
+        port.write_data_terminal_ready(level)
+            .map_err(|e| NetworkError::SendFailed(format!("Failed to set DTR: {}", e)))?;
+    }
+    if let Some(level) = rts {
+        port.write_request_to_send(level)
+            .map_err(|e| NetworkError::SendFailed(format!("Failed to set RTS: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// Request handed to the port task
+#[derive(Debug)]
+enum PortCommand {
+    Write(Vec<u8>, oneshot::Sender<NetworkResult<usize>>),
+    ControlLines {
+        dtr: Option<bool>,
+        rts: Option<bool>,
+        reply: oneshot::Sender<NetworkResult<()>>,
+    },
+}
+
+/// Command channels of open serial sessions
+static PORTS: OnceLock<Mutex<HashMap<String, mpsc::Sender<PortCommand>>>> = OnceLock::new();
+
+fn ports() -> &'static Mutex<HashMap<String, mpsc::Sender<PortCommand>>> {
+    PORTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Set the DTR and/or RTS lines of an open serial session
+pub async fn set_session_control_lines(session_id: &str, dtr: Option<bool>, rts: Option<bool>) -> NetworkResult<()> {
+    let commands = ports()
+        .lock()
+        .unwrap()
+        .get(session_id)
+        .cloned()
+        .ok_or_else(|| NetworkError::SessionNotFound(format!("{} is not an open serial session", session_id)))?;
+    control_lines(&commands, dtr, rts).await
+}
+
+async fn control_lines(commands: &mpsc::Sender<PortCommand>, dtr: Option<bool>, rts: Option<bool>) -> NetworkResult<()> {
+    let (reply, result) = oneshot::channel();
+    commands
+        .send(PortCommand::ControlLines { dtr, rts, reply })
+        .await
+        .map_err(|_| NetworkError::NotConnected)?;
+    result.await.map_err(|_| NetworkError::NotConnected)?
+}
+
+/// Raw serial port connection
+#[derive(Debug)]
+pub struct SerialConnection {
+    session_id: String,
+    settings: SerialSettings,
+    connected: Arc<AtomicBool>,
+    commands: Option<mpsc::Sender<PortCommand>>,
+    port_task: Option<tokio::task::JoinHandle<()>>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+}
+
+impl SerialConnection {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
+        Ok(Self {
+            session_id,
+            settings: SerialSettings::from_config(&config)?,
+            connected: Arc::new(AtomicBool::new(false)),
+            commands: None,
+            port_task: None,
+            app_handle,
+            event_tx: None,
+        })
+    }
+
+    /// Set the DTR and/or RTS lines
+    pub async fn set_control_lines(&self, dtr: Option<bool>, rts: Option<bool>) -> NetworkResult<()> {
+        let commands = self.commands.as_ref().ok_or(NetworkError::NotConnected)?;
+        control_lines(commands, dtr, rts).await
+    }
+
+    /// Start the port task on an opened port
+    fn attach(&mut self, port: SerialStream) {
+        let (commands, command_rx) = mpsc::channel(COMMAND_QUEUE);
+        self.connected.store(true, Ordering::SeqCst);
+        self.port_task = Some(tokio::spawn(run_port(
+            port,
+            command_rx,
+            self.session_id.clone(),
+            self.connected.clone(),
+            self.app_handle.clone(),
+            self.event_tx.clone(),
+        )));
+        ports().lock().unwrap().insert(self.session_id.clone(), commands.clone());
+        self.commands = Some(commands);
+    }
+}
+
+async fn send_event(event_tx: &Option<mpsc::Sender<NetworkEvent>>, session_id: &str, event_type: &str, data: Option<Vec<u8>>, error: Option<String>) {
+    if let Some(tx) = event_tx {
+        let event = NetworkEvent {
+            session_id: session_id.to_string(),
+            event_type: event_type.to_string(),
+            data,
+            error,
+            client_id: None,
+            mqtt_topic: None,
+            mqtt_qos: None,
+            mqtt_retain: None,
+            sse_event: None,
+        };
+        let _ = tx.send(event).await;
+    }
+}
+
+/// Read from the port and serve commands until the port closes, fails or the session disconnects
+async fn run_port(
+    mut port: SerialStream,
+    mut commands: mpsc::Receiver<PortCommand>,
+    session_id: String,
+    connected: Arc<AtomicBool>,
+    app_handle: Option<AppHandle>,
+    event_tx: Option<mpsc::Sender<NetworkEvent>>,
+) {
+    let mut buffer = [0u8; 4096];
+    let error = loop {
+        tokio::select! {
+            read = port.read(&mut buffer) => {
+                let n = match read {
+                    Ok(0) => break None,
+                    Ok(n) => n,
+                    Err(e) => break Some(format!("Serial read error: {}", e)),
+                };
+                eprintln!("SerialConnection: Session {} - Received {} bytes", session_id, n);
+                crate::master::handle_inbound(&session_id, &buffer[..n]);
+                send_event(&event_tx, &session_id, "data_received", Some(buffer[..n].to_vec()), None).await;
+
+                if let Some(app_handle) = &app_handle {
+                    crate::network::emit_received(app_handle, "serial", &session_id, None, None, None, &buffer[..n]);
+                }
+            }
+            command = commands.recv() => match command {
+                Some(PortCommand::Write(data, reply)) => {
+                    let result = async {
+                        port.write_all(&data).await?;
+                        port.flush().await
+                    }
+                    .await
+                    .map(|_| data.len())
+                    .map_err(|e| NetworkError::SendFailed(e.to_string()));
+                    let failed = result.as_ref().err().map(|e| e.to_string());
+                    let _ = reply.send(result);
+                    if let Some(error) = failed {
+                        break Some(error);
+                    }
+                }
+                Some(PortCommand::ControlLines { dtr, rts, reply }) => {
+                    let _ = reply.send(set_control_lines(&mut port, dtr, rts));
+                }
+                // The session dropped its sender: manual disconnect
+                None => return,
+            },
+        }
+    };
+
+    connected.store(false, Ordering::SeqCst);
+    ports().lock().unwrap().remove(&session_id);
+    eprintln!("SerialConnection: Session {} - Port closed: {}", session_id, error.as_deref().unwrap_or("end of stream"));
+    send_event(&event_tx, &session_id, "disconnected", None, error.clone()).await;
+    if let Some(app_handle) = &app_handle {
+        let payload = serde_json::json!({
+            "sessionId": session_id,
+            "status": "disconnected",
+            "error": error.unwrap_or_else(|| "Serial port closed".to_string())
+        });
+        if let Err(e) = app_handle.emit("connection-status", payload) {
+            eprintln!("SerialConnection: Failed to emit connection-status event: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl Connection for SerialConnection {
+    async fn connect(&mut self) -> NetworkResult<()> {
+        if self.connected.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        eprintln!("SerialConnection: Opening {} at {}", self.settings.port, self.settings.describe());
+        let port = self.settings.open()?;
+        self.attach(port);
+        send_event(&self.event_tx, &self.session_id, "connected", None, None).await;
+
+        if let Some(app_handle) = &self.app_handle {
+            let payload = serde_json::json!({
+                "sessionId": self.session_id,
+                "status": "connected"
+            });
+            if let Err(e) = app_handle.emit("connection-status", payload) {
+                eprintln!("SerialConnection: Failed to emit connection-status event: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> NetworkResult<()> {
+        let was_connected = self.connected.swap(false, Ordering::SeqCst);
+        // Dropping the sender ends the port task, which closes the port
+        self.commands = None;
+        ports().lock().unwrap().remove(&self.session_id);
+        if let Some(task) = self.port_task.take() {
+            let _ = task.await;
+        }
+
+        if was_connected {
+            eprintln!("SerialConnection: Session {} - Closed {}", self.session_id, self.settings.port);
+            send_event(&self.event_tx, &self.session_id, "disconnected", None, None).await;
+            if let Some(app_handle) = &self.app_handle {
+                let payload = serde_json::json!({
+                    "sessionId": self.session_id,
+                    "status": "disconnected",
+                    "error": null
+                });
+                if let Err(e) = app_handle.emit("connection-status", payload) {
+                    eprintln!("SerialConnection: Failed to emit connection-status event: {}", e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn send(&mut self, data: &[u8]) -> NetworkResult<usize> {
+        let commands = self.commands.as_ref().ok_or(NetworkError::NotConnected)?;
+        let (reply, result) = oneshot::channel();
+        commands
+            .send(PortCommand::Write(data.to_vec(), reply))
+            .await
+            .map_err(|_| NetworkError::NotConnected)?;
+        let sent = result.await.map_err(|_| NetworkError::NotConnected)??;
+
+        if let Some(app_handle) = &self.app_handle {
+            let payload = serde_json::json!({
+                "sessionId": self.session_id,
+                "data": data.to_vec(),
+                "direction": "out"
+            });
+            if let Err(e) = app_handle.emit("message-received", payload) {
+                eprintln!("SerialConnection: Failed to emit message-received event: {}", e);
+            }
+        }
+        Ok(sent)
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected.load(Ordering::SeqCst)
+    }
+
+    fn status(&self) -> String {
+        if self.is_connected() {
+            format!("Open {} at {}", self.settings.port, self.settings.describe())
+        } else {
+            "Disconnected".to_string()
+        }
+    }
+
+    async fn start_receiving(&mut self) -> NetworkResult<mpsc::Receiver<NetworkEvent>> {
+        let (tx, rx) = mpsc::channel(100);
+        self.event_tx = Some(tx);
+        Ok(rx)
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_serial_settings() {
+        let config = serde_json::json!({
+            "serial": { "port": "/dev/ttyUSB0", "baudRate": 19200, "parity": "even", "flowControl": "hardware", "dtr": true }
+        });
+        let settings = SerialSettings::from_config(&config).unwrap();
+        assert_eq!(settings.describe(), "19200 8E1");
+        assert_eq!(settings.dtr, Some(true));
+        assert_eq!(settings.rts, None);
+        assert!(matches!(settings.flow_control(), Ok(tokio_serial::FlowControl::Hardware)));
+
+        assert!(SerialSettings::from_config(&serde_json::json!({})).is_err());
+        let invalid = |field: &str, value: serde_json::Value| {
+            let mut serial = serde_json::json!({ "port": "COM3" });
+            serial[field] = value;
+            SerialSettings::from_config(&serde_json::json!({ "serial": serial })).is_err()
+        };
+        assert!(invalid("dataBits", 9.into()));
+        assert!(invalid("stopBits", 3.into()));
+        assert!(invalid("parity", "mark".into()));
+        assert!(invalid("flowControl", "dsr".into()));
+        assert!(invalid("port", "".into()));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pty_loopback() {
+        let (port, mut device) = SerialStream::pair().unwrap();
+        let config = serde_json::json!({ "serial": { "port": "pty" } });
+        let mut connection = SerialConnection::new("serial-test".to_string(), config, None).unwrap();
+        let mut events = connection.start_receiving().await.unwrap();
+        connection.attach(port);
+        assert!(connection.is_connected());
+
+        assert_eq!(connection.send(b"*IDN?\r\n").await.unwrap(), 7);
+        let mut request = [0u8; 7];
+        tokio::time::timeout(Duration::from_secs(5), device.read_exact(&mut request)).await.unwrap().unwrap();
+        assert_eq!(&request, b"*IDN?\r\n");
+
+        device.write_all(&[0x02, 0x41, 0x03]).await.unwrap();
+        let event = tokio::time::timeout(Duration::from_secs(5), events.recv()).await.unwrap().unwrap();
+        assert_eq!(event.event_type, "data_received");
+        assert_eq!(event.data, Some(vec![0x02, 0x41, 0x03]));
+
+        connection.disconnect().await.unwrap();
+        assert!(!connection.is_connected());
+        assert!(connection.send(b"x").await.is_err());
+    }
+}
@@ -4,6 +4,8 @@ use crate::types::{NetworkResult, NetworkError, NetworkEvent, SseEvent};
 use tokio::sync::mpsc;
 use reqwest::{Client, StatusCode};
 use futures_util::StreamExt;
+use tauri::AppHandle;
+use tokio::task::JoinHandle;
 
 /// Format SSE connection error with helpful guidance
 fn format_sse_error(status: StatusCode, url: &str) -> (String, bool) {
@@ -57,10 +59,13 @@ pub struct SseClient {
     retry_interval: Option<u64>,
     connected: bool,
     client: Client,
+    app_handle: Option<AppHandle>,
+    events: Option<mpsc::Receiver<NetworkEvent>>,
+    receive_task: Option<JoinHandle<()>>,
 }
 
 impl SseClient {
-    pub fn new(session_id: String, config: serde_json::Value) -> NetworkResult<Self> {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
         let host = config.get("host")
             .and_then(|v| v.as_str())
             .unwrap_or("127.0.0.1");
@@ -90,10 +95,105 @@ impl SseClient {
             retry_interval,
             connected: false,
             client: Client::new(),
+            app_handle,
+            events: None,
+            receive_task: None,
         })
     }
 }
 
+/// Read the event stream, delivering each `data:` line to the session's
+/// parsers and to the event channel
+async fn run_receiver(
+    response: reqwest::Response,
+    session_id: String,
+    event_types: Option<Vec<String>>,
+    app_handle: Option<AppHandle>,
+    tx: mpsc::Sender<NetworkEvent>,
+) {
+    let mut stream = response.bytes_stream();
+    let mut buffer = String::new();
+
+    while let Some(chunk) = stream.next().await {
+        match chunk {
+            Ok(bytes) => {
+                if let Ok(text) = String::from_utf8(bytes.to_vec()) {
+                    buffer.push_str(&text);
+
+                    // Process complete lines
+                    while let Some(line_end) = buffer.find('\n') {
+                        let line = buffer[..line_end].trim_end_matches('\r').to_string();
+                        buffer = buffer[line_end + 1..].to_string();
+
+                        // Parse SSE format
+                        if let Some(data) = line.strip_prefix("data: ") {
+                            if let Some(app_handle) = &app_handle {
+                                crate::network::emit_received(app_handle, "sse", &session_id, None, None, None, data.as_bytes());
+                            }
+
+                            // Nobody may be reading the channel; never stall the stream on it
+                            let _ = tx.try_send(NetworkEvent {
+                                session_id: session_id.clone(),
+                                event_type: "message".to_string(),
+                                data: Some(data.as_bytes().to_vec()),
+                                error: None,
+                                client_id: None,
+                                mqtt_topic: None,
+                                mqtt_qos: None,
+                                mqtt_retain: None,
+                                sse_event: Some(SseEvent {
+                                    event_type: Some("message".to_string()),
+                                    data: data.to_string(),
+                                    id: None,
+                                    retry: None,
+                                }),
+                            });
+                        } else if let Some(event_type) = line.strip_prefix("event: ") {
+                            // Filter by event types if specified
+                            if let Some(ref types) = event_types {
+                                if !types.contains(&event_type.to_string()) {
+                                    continue;
+                                }
+                            }
+
+                            let _ = tx.try_send(NetworkEvent {
+                                session_id: session_id.clone(),
+                                event_type: "event".to_string(),
+                                data: None,
+                                error: None,
+                                client_id: None,
+                                mqtt_topic: None,
+                                mqtt_qos: None,
+                                mqtt_retain: None,
+                                sse_event: Some(SseEvent {
+                                    event_type: Some(event_type.to_string()),
+                                    data: String::new(),
+                                    id: None,
+                                    retry: None,
+                                }),
+                            });
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                let _ = tx.try_send(NetworkEvent {
+                    session_id: session_id.clone(),
+                    event_type: "error".to_string(),
+                    data: None,
+                    error: Some(format!("SSE stream error: {}", e)),
+                    client_id: None,
+                    mqtt_topic: None,
+                    mqtt_qos: None,
+                    mqtt_retain: None,
+                    sse_event: None,
+                });
+                break;
+            }
+        }
+    }
+}
+
 #[async_trait]
 impl Connection for SseClient {
     async fn connect(&mut self) -> NetworkResult<()> {
@@ -124,6 +224,17 @@ impl Connection for SseClient {
         let status = response.status();
         if status.is_success() {
             eprintln!("SSEClient: Successfully connected to {}", self.url);
+
+            // Keep reading the stream we just opened
+            let (tx, rx) = mpsc::channel(1000);
+            self.receive_task = Some(tokio::spawn(run_receiver(
+                response,
+                self.session_id.clone(),
+                self.event_types.clone(),
+                self.app_handle.clone(),
+                tx,
+            )));
+            self.events = Some(rx);
             self.connected = true;
             Ok(())
         } else {
@@ -140,6 +251,10 @@ impl Connection for SseClient {
     }
 
     async fn disconnect(&mut self) -> NetworkResult<()> {
+        if let Some(task) = self.receive_task.take() {
+            task.abort();
+        }
+        self.events = None;
         self.connected = false;
         Ok(())
     }
@@ -166,132 +281,9 @@ impl Connection for SseClient {
             return Err(NetworkError::NotConnected);
         }
 
-        let (tx, rx) = mpsc::channel(1000);
-
-        let client = self.client.clone();
-        let url = self.url.clone();
-        let session_id = self.session_id.clone();
-        let event_types = self.event_types.clone();
-
-        // Spawn background task to handle SSE stream
-        tokio::spawn(async move {
-            let response = match client
-                .get(&url)
-                .header("Accept", "text/event-stream")
-                .header("Cache-Control", "no-cache")
-                .send()
-                .await
-            {
-                Ok(response) => response,
-                Err(e) => {
-                    let error_event = NetworkEvent {
-                        session_id: session_id.clone(),
-                        event_type: "error".to_string(),
-                        data: None,
-                        error: Some(format!("SSE connection error: {}", e)),
-                        client_id: None,
-                        mqtt_topic: None,
-                        mqtt_qos: None,
-                        mqtt_retain: None,
-                        sse_event: None,
-                    };
-                    let _ = tx.send(error_event).await;
-                    return;
-                }
-            };
-
-            let mut stream = response.bytes_stream();
-            let mut buffer = String::new();
-
-            while let Some(chunk) = stream.next().await {
-                match chunk {
-                    Ok(bytes) => {
-                        if let Ok(text) = String::from_utf8(bytes.to_vec()) {
-                            buffer.push_str(&text);
-
-                            // Process complete lines
-                            while let Some(line_end) = buffer.find('\n') {
-                                let line = buffer[..line_end].trim_end_matches('\r').to_string();
-                                buffer = buffer[line_end + 1..].to_string();
-
-                                // Parse SSE format
-                                if line.starts_with("data: ") {
-                                    let data = &line[6..];
-
-                                    let event = NetworkEvent {
-                                        session_id: session_id.clone(),
-                                        event_type: "message".to_string(),
-                                        data: Some(data.as_bytes().to_vec()),
-                                        error: None,
-                                        client_id: None,
-                                        mqtt_topic: None,
-                                        mqtt_qos: None,
-                                        mqtt_retain: None,
-                                        sse_event: Some(SseEvent {
-                                            event_type: Some("message".to_string()),
-                                            data: data.to_string(),
-                                            id: None,
-                                            retry: None,
-                                        }),
-                                    };
-
-                                    if tx.send(event).await.is_err() {
-                                        return; // Channel closed
-                                    }
-                                } else if line.starts_with("event: ") {
-                                    let event_type = &line[7..];
-
-                                    // Filter by event types if specified
-                                    if let Some(ref types) = event_types {
-                                        if !types.contains(&event_type.to_string()) {
-                                            continue;
-                                        }
-                                    }
-
-                                    let event = NetworkEvent {
-                                        session_id: session_id.clone(),
-                                        event_type: "event".to_string(),
-                                        data: None,
-                                        error: None,
-                                        client_id: None,
-                                        mqtt_topic: None,
-                                        mqtt_qos: None,
-                                        mqtt_retain: None,
-                                        sse_event: Some(SseEvent {
-                                            event_type: Some(event_type.to_string()),
-                                            data: String::new(),
-                                            id: None,
-                                            retry: None,
-                                        }),
-                                    };
-
-                                    if tx.send(event).await.is_err() {
-                                        return; // Channel closed
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        let error_event = NetworkEvent {
-                            session_id: session_id.clone(),
-                            event_type: "error".to_string(),
-                            data: None,
-                            error: Some(format!("SSE stream error: {}", e)),
-                            client_id: None,
-                            mqtt_topic: None,
-                            mqtt_qos: None,
-                            mqtt_retain: None,
-                            sse_event: None,
-                        };
-                        let _ = tx.send(error_event).await;
-                        break;
-                    }
-                }
-            }
-        });
-
-        Ok(rx)
+        // The stream opened on connect feeds a single receiver
+        self.events.take()
+            .ok_or_else(|| NetworkError::ConnectionFailed("SSE events are already being received".to_string()))
     }
 
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
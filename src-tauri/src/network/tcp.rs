@@ -6,6 +6,7 @@ use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf};
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::RwLock;
@@ -73,6 +74,13 @@ impl TcpClient {
             mqtt_will: None,
             sse_event_types: None,
             sse_retry_interval: None,
+            modbus_unit_id: None,
+            modbus_rtu_slave: None,
+            iec104: None,
+            dnp3: None,
+            serial: None,
+            idle_gap: None,
+            parser_binding: None,
         };
 
         Ok(Self {
@@ -92,7 +100,7 @@ impl TcpClient {
         })
     }
 
-    async fn start_receiving_with_read_half(&mut self, read_half: ReadHalf<TcpStream>) -> NetworkResult<tokio::task::JoinHandle<()>> {
+    async fn start_receiving_with_read_half(&mut self, read_half: ReadHalf<TcpStream>, peer: Option<SocketAddr>) -> NetworkResult<tokio::task::JoinHandle<()>> {
         let session_id = self.session_id.clone();
         let app_handle = self.app_handle.clone();
         let connected = self.connected.clone(); // 克隆原子布尔值的引用
@@ -276,20 +284,11 @@ impl TcpClient {
                             Ok(n) => {
                                 // Data received from server
                                 eprintln!("TCPClient: Session {} - Received {} bytes from server", session_id, n);
+                                crate::master::handle_inbound(&session_id, &buffer[..n]);
 
                         // Emit message-received event for server-to-client data transmission
                         if let Some(app_handle) = &app_handle {
-                            let payload = serde_json::json!({
-                                "sessionId": session_id,
-                                "data": buffer[..n].to_vec(),
-                                "direction": "in"
-                            });
-
-                            if let Err(e) = app_handle.emit("message-received", payload) {
-                                eprintln!("TCPClient: Failed to emit message-received event for server-to-client transmission: {}", e);
-                            } else {
-                                eprintln!("TCPClient: Successfully emitted message-received event for {} bytes received from server", n);
-                            }
+                            crate::network::emit_received(app_handle, "tcp", &session_id, None, peer, None, &buffer[..n]);
                         }
                             }
                             Err(e) => {
@@ -425,13 +424,14 @@ impl Connection for TcpClient {
             self.session_id, self.host, self.port);
 
         // Split the stream into read and write halves to avoid conflicts
+        let peer = stream.peer_addr().ok();
         let (read_half, write_half) = tokio::io::split(stream);
         self.write_half = Some(Arc::new(RwLock::new(write_half)));
         self.connected.store(true, Ordering::SeqCst);
 
         // Start receiving data from server immediately after connection
         eprintln!("TCPClient: Session {} - Starting to receive data from server", self.session_id);
-        let read_task = self.start_receiving_with_read_half(read_half).await?;
+        let read_task = self.start_receiving_with_read_half(read_half, peer).await?;
         self.read_task = Some(read_task);
         eprintln!("TCPClient: Session {} - Successfully started receiving data from server", self.session_id);
 
@@ -610,14 +610,13 @@ impl TcpServer {
             let payload = match event_type {
                     "client-connected" => {
                         if let Some(client_id) = &client_id {
-                            // Parse client_id to get address and port
-                            let parts: Vec<&str> = client_id.split(':').collect();
-                            if parts.len() == 2 {
+                            // Client IDs are the client's socket address
+                            if let Ok(addr) = client_id.parse::<SocketAddr>() {
                                 serde_json::json!({
                                     "sessionId": self.session_id,
                                     "clientId": client_id,
-                                    "remoteAddress": parts[0],
-                                    "remotePort": parts[1].parse::<u16>().unwrap_or(0)
+                                    "remoteAddress": addr.ip().to_string(),
+                                    "remotePort": addr.port()
                                 })
                             } else {
                                 serde_json::json!({
@@ -820,7 +819,7 @@ impl TcpServer {
 
                 match listener.accept().await {
                     Ok((stream, addr)) => {
-                        let client_id = format!("{}:{}", addr.ip(), addr.port());
+                        let client_id = addr.to_string();
 
                         eprintln!("TCPServer: Client {} connected from {}:{}",
                             client_id, addr.ip(), addr.port());
@@ -870,6 +869,9 @@ impl TcpServer {
 
                                         // Use a flag to prevent double removal
                                         let removed = clients_clone.write().await.remove(&client_id_clone);
+                                        crate::parser::get_session_parsers().lock().unwrap().reset_stream(&session_id_clone, Some(&client_id_clone));
+                                        crate::network::idle_gap::close_stream(&session_id_clone, Some(&client_id_clone));
+                                        crate::simulator::client_disconnected(&session_id_clone, &client_id_clone);
                                         if removed.is_some() {
                                             eprintln!("TCPServer: [CLIENT HANDLER] Successfully removed client {}", client_id_clone);
 
@@ -940,16 +942,8 @@ impl TcpServer {
 
                                         // Also emit through app handle for backward compatibility
                                         if let Some(app_handle_ref) = app_handle_clone.read().await.as_ref() {
-                                            let payload = serde_json::json!({
-                                                "sessionId": session_id_clone,
-                                                "data": buffer[..n].to_vec(),
-                                                "direction": "in",
-                                                "clientId": client_id_clone
-                                            });
-
-                                            if let Err(e) = app_handle_ref.emit("message-received", payload) {
-                                                eprintln!("TCPServer: Failed to emit message-received event: {}", e);
-                                            }
+                                            crate::network::emit_received(app_handle_ref, "tcp", &session_id_clone, Some(&client_id_clone), Some(addr), None, &buffer[..n]);
+                                            crate::simulator::handle_inbound(app_handle_ref, &session_id_clone, Some(&client_id_clone), &buffer[..n]);
                                         }
                                     }
                                     Err(e) => {
@@ -958,6 +952,9 @@ impl TcpServer {
 
                                         // Use a flag to prevent double removal
                                         let removed = clients_clone.write().await.remove(&client_id_clone);
+                                        crate::parser::get_session_parsers().lock().unwrap().reset_stream(&session_id_clone, Some(&client_id_clone));
+                                        crate::network::idle_gap::close_stream(&session_id_clone, Some(&client_id_clone));
+                                        crate::simulator::client_disconnected(&session_id_clone, &client_id_clone);
                                         if removed.is_some() {
                                             eprintln!("TCPServer: [CLIENT HANDLER] Successfully removed client {} due to read error", client_id_clone);
 
@@ -999,23 +996,14 @@ impl TcpServer {
                         tokio::time::sleep(std::time::Duration::from_millis(10)).await;
 
                         // NOW emit client-connected event after everything is set up
-                        let parts: Vec<&str> = client_id.split(':').collect();
                         let client_connected_event = NetworkEvent {
                             session_id: session_id.clone(),
                             event_type: "client_connected".to_string(),
-                            data: Some(serde_json::to_vec(&if parts.len() == 2 {
-                                serde_json::json!({
-                                    "clientId": client_id,
-                                    "remoteAddress": parts[0],
-                                    "remotePort": parts[1].parse::<u16>().unwrap_or(0)
-                                })
-                            } else {
-                                serde_json::json!({
-                                    "clientId": client_id,
-                                    "remoteAddress": "unknown",
-                                    "remotePort": 0
-                                })
-                            }).unwrap_or_default()),
+                            data: Some(serde_json::to_vec(&serde_json::json!({
+                                "clientId": client_id,
+                                "remoteAddress": addr.ip().to_string(),
+                                "remotePort": addr.port()
+                            })).unwrap_or_default()),
                             error: None,
                             client_id: Some(client_id.clone()),
                             mqtt_topic: None,
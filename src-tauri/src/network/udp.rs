@@ -136,10 +136,16 @@ impl UdpClient {
         eprintln!("🔧 UdpClient: Session {} - Socket peer_addr: {:?}", session_id_for_log, socket_clone.peer_addr());
 
         // Check if socket is actually connected
-        match socket_clone.peer_addr() {
-            Ok(peer) => eprintln!("✅ UdpClient: Session {} - Socket is connected to peer: {}", session_id_for_log, peer),
-            Err(e) => eprintln!("❌ UdpClient: Session {} - Socket is NOT connected: {}", session_id_for_log, e),
-        }
+        let peer = match socket_clone.peer_addr() {
+            Ok(peer) => {
+                eprintln!("✅ UdpClient: Session {} - Socket is connected to peer: {}", session_id_for_log, peer);
+                Some(peer)
+            }
+            Err(e) => {
+                eprintln!("❌ UdpClient: Session {} - Socket is NOT connected: {}", session_id_for_log, e);
+                None
+            }
+        };
 
         tokio::spawn(async move {
             eprintln!("🔧 UdpClient: Starting background task to receive data from server for session {}", session_id);
@@ -170,6 +176,7 @@ impl UdpClient {
                     Ok(size) => {
                         eprintln!("✅ UdpClient: Session {} - Received {} bytes from server", session_id, size);
                         eprintln!("🔧 UdpClient: Session {} - Data received: {:?}", session_id, &buffer[..size]);
+                        crate::master::handle_inbound(&session_id, &buffer[..size]);
 
                         // Debug: Check app_handle status before event emission
                         eprintln!("🔍 UdpClient: Session {} - Checking app_handle availability in background task...", session_id);
@@ -179,18 +186,7 @@ impl UdpClient {
                         // Emit message-received event for server-to-client data
                         if let Some(app_handle_ref) = app_handle_guard.as_ref() {
                             eprintln!("✅ UdpClient: Session {} - App handle is available in background task, emitting event", session_id);
-                            let payload = serde_json::json!({
-                                "sessionId": session_id,
-                                "data": buffer[..size].to_vec(),
-                                "direction": "in"
-                            });
-
-                            eprintln!("🔧 UdpClient: Session {} - Emitting message-received event", session_id);
-                            if let Err(e) = app_handle_ref.emit("message-received", payload) {
-                                eprintln!("❌ UdpClient: Failed to emit message-received event for received data: {}", e);
-                            } else {
-                                eprintln!("✅ UdpClient: Successfully emitted message-received event for {} bytes received from server", size);
-                            }
+                            crate::network::emit_received(app_handle_ref, "udp", &session_id, None, peer, None, &buffer[..size]);
                         } else {
                             eprintln!("❌ UdpClient: Session {} - No app handle available for event emission in background task", session_id);
                             eprintln!("🔍 UdpClient: Session {} - App handle is None in background task", session_id);
@@ -465,18 +461,7 @@ impl UdpServer {
 
                         // Emit message-received event
                         if let Some(app_handle) = app_handle.read().await.as_ref() {
-                            let payload = serde_json::json!({
-                                "sessionId": session_id,
-                                "data": buffer[..size].to_vec(),
-                                "direction": "in",
-                                "clientId": client_id
-                            });
-
-                            if let Err(e) = app_handle.emit("message-received", payload) {
-                                eprintln!("UdpServer: Failed to emit message-received event: {}", e);
-                            } else {
-                                eprintln!("UdpServer: Successfully emitted message-received event for {} bytes from {}", size, client_id);
-                            }
+                            crate::network::emit_received(app_handle, "udp", &session_id, Some(&client_id), Some(addr), None, &buffer[..size]);
                         }
                     }
                     Err(e) => {
@@ -612,18 +597,7 @@ impl Connection for UdpServer {
 
                         // Emit message-received event
                         if let Some(app_handle_ref) = app_handle.read().await.as_ref() {
-                            let payload = serde_json::json!({
-                                "sessionId": session_id,
-                                "data": data.clone(),
-                                "direction": "in",
-                                "clientId": client_id
-                            });
-
-                            if let Err(e) = app_handle_ref.emit("message-received", payload) {
-                                eprintln!("UdpServer: Failed to emit message-received event: {}", e);
-                            } else {
-                                eprintln!("UdpServer: Successfully emitted message-received event for {} bytes from {}", size, client_id);
-                            }
+                            crate::network::emit_received(app_handle_ref, "udp", &session_id, Some(&client_id), Some(addr), None, &data);
                         }
                         if tx.send(NetworkEvent {
                             session_id: session_id.clone(),
@@ -7,10 +7,18 @@ use tokio_tungstenite::tungstenite::Message;
 use tokio::net::{TcpListener, TcpStream};
 use url::Url;
 use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::io::ErrorKind;
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::{SinkExt, StreamExt};
+use tauri::AppHandle;
+
+type ClientStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type ServerClientSink = SplitSink<WebSocketStream<TcpStream>, Message>;
+type ServerClients = Arc<RwLock<HashMap<String, Arc<RwLock<ServerClientSink>>>>>;
 
 /// Format WebSocket server binding error with Windows-specific guidance
 fn format_websocket_bind_error(error: &std::io::Error, host: &str, port: u16) -> String {
@@ -53,7 +61,10 @@ pub struct WebSocketClient {
     session_id: String,
     url: String,
     subprotocol: Option<String>,
-    ws_stream: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    ws_sink: Option<SplitSink<ClientStream, Message>>,
+    app_handle: Option<AppHandle>,
+    events: Option<mpsc::Receiver<NetworkEvent>>,
+    receive_task: Option<JoinHandle<()>>,
     connected: bool,
 }
 
@@ -69,7 +80,7 @@ impl std::fmt::Debug for WebSocketClient {
 }
 
 impl WebSocketClient {
-    pub fn new(session_id: String, config: serde_json::Value) -> NetworkResult<Self> {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
         let host = config.get("host")
             .and_then(|v| v.as_str())
             .unwrap_or("127.0.0.1");
@@ -88,12 +99,79 @@ impl WebSocketClient {
             session_id,
             url,
             subprotocol,
-            ws_stream: None,
+            ws_sink: None,
+            app_handle,
+            events: None,
+            receive_task: None,
             connected: false,
         })
     }
 }
 
+/// Read messages from the server, delivering each one to the session's
+/// parsers and to the event channel
+async fn run_client_receiver(
+    mut reader: SplitStream<ClientStream>,
+    session_id: String,
+    peer: Option<SocketAddr>,
+    app_handle: Option<AppHandle>,
+    tx: mpsc::Sender<NetworkEvent>,
+) {
+    while let Some(msg_result) = reader.next().await {
+        match msg_result {
+            Ok(message) => {
+                let data = match message {
+                    Message::Text(text) => text.into_bytes(),
+                    Message::Binary(bytes) => bytes,
+                    Message::Close(_) => {
+                        // Connection closed
+                        break;
+                    }
+                    Message::Ping(_) | Message::Pong(_) => {
+                        // Handle ping/pong automatically, don't send to application
+                        continue;
+                    }
+                    Message::Frame(_) => {
+                        // Raw frames are not typically handled at application level
+                        continue;
+                    }
+                };
+
+                if let Some(app_handle) = &app_handle {
+                    crate::network::emit_received(app_handle, "websocket", &session_id, None, peer, None, &data);
+                }
+
+                // Nobody may be reading the channel; never stall the reader on it
+                let _ = tx.try_send(NetworkEvent {
+                    session_id: session_id.clone(),
+                    event_type: "message".to_string(),
+                    data: Some(data),
+                    error: None,
+                    client_id: None,
+                    mqtt_topic: None,
+                    mqtt_qos: None,
+                    mqtt_retain: None,
+                    sse_event: None,
+                });
+            }
+            Err(e) => {
+                let _ = tx.try_send(NetworkEvent {
+                    session_id: session_id.clone(),
+                    event_type: "error".to_string(),
+                    data: None,
+                    error: Some(format!("WebSocket receive error: {}", e)),
+                    client_id: None,
+                    mqtt_topic: None,
+                    mqtt_qos: None,
+                    mqtt_retain: None,
+                    sse_event: None,
+                });
+                break;
+            }
+        }
+    }
+}
+
 #[async_trait]
 impl Connection for WebSocketClient {
     async fn connect(&mut self) -> NetworkResult<()> {
@@ -107,7 +185,23 @@ impl Connection for WebSocketClient {
         let (ws_stream, _response) = connect_async(url).await
             .map_err(|e| NetworkError::ConnectionFailed(format!("WebSocket connection failed: {}", e)))?;
 
-        self.ws_stream = Some(ws_stream);
+        let peer = match ws_stream.get_ref() {
+            MaybeTlsStream::Plain(stream) => stream.peer_addr().ok(),
+            _ => None,
+        };
+
+        // Read on a background task so incoming messages are parsed as they arrive
+        let (sink, reader) = ws_stream.split();
+        let (tx, rx) = mpsc::channel(1000);
+        self.receive_task = Some(tokio::spawn(run_client_receiver(
+            reader,
+            self.session_id.clone(),
+            peer,
+            self.app_handle.clone(),
+            tx,
+        )));
+        self.events = Some(rx);
+        self.ws_sink = Some(sink);
         self.connected = true;
         Ok(())
     }
@@ -117,10 +211,14 @@ impl Connection for WebSocketClient {
             return Ok(());
         }
 
-        if let Some(mut ws_stream) = self.ws_stream.take() {
-            let _ = ws_stream.close(None).await; // Ignore close errors
+        if let Some(mut ws_sink) = self.ws_sink.take() {
+            let _ = ws_sink.close().await; // Ignore close errors
+        }
+        if let Some(task) = self.receive_task.take() {
+            task.abort();
         }
 
+        self.events = None;
         self.connected = false;
         Ok(())
     }
@@ -130,7 +228,7 @@ impl Connection for WebSocketClient {
             return Err(NetworkError::NotConnected);
         }
 
-        let ws_stream = self.ws_stream.as_mut()
+        let ws_sink = self.ws_sink.as_mut()
             .ok_or(NetworkError::NotConnected)?;
 
         // Try to send as text first, fall back to binary if it's not valid UTF-8
@@ -140,7 +238,7 @@ impl Connection for WebSocketClient {
             Message::Binary(data.to_vec())
         };
 
-        ws_stream.send(message).await
+        ws_sink.send(message).await
             .map_err(|e| NetworkError::SendFailed(format!("WebSocket send failed: {}", e)))?;
 
         Ok(data.len())
@@ -163,69 +261,9 @@ impl Connection for WebSocketClient {
             return Err(NetworkError::NotConnected);
         }
 
-        let (tx, rx) = mpsc::channel(1000);
-
-        // Take the WebSocket stream for the background task
-        let mut ws_stream = self.ws_stream.take()
-            .ok_or(NetworkError::NotConnected)?;
-
-        let session_id = self.session_id.clone();
-
-        // Spawn background task for receiving messages
-        tokio::spawn(async move {
-            while let Some(msg_result) = ws_stream.next().await {
-                match msg_result {
-                    Ok(message) => {
-                        let data = match message {
-                            Message::Text(text) => text.into_bytes(),
-                            Message::Binary(bytes) => bytes,
-                            Message::Close(_) => {
-                                // Connection closed
-                                break;
-                            }
-                            Message::Ping(_) | Message::Pong(_) => {
-                                // Handle ping/pong automatically, don't send to application
-                                continue;
-                            }
-                            Message::Frame(_) => {
-                                // Raw frames are not typically handled at application level
-                                continue;
-                            }
-                        };
-
-                        if tx.send(NetworkEvent {
-                            session_id: session_id.clone(),
-                            event_type: "message".to_string(),
-                            data: Some(data),
-                            error: None,
-                            client_id: None,
-                            mqtt_topic: None,
-                            mqtt_qos: None,
-                            mqtt_retain: None,
-                            sse_event: None,
-                        }).await.is_err() {
-                            break; // Receiver dropped
-                        }
-                    }
-                    Err(e) => {
-                        let _ = tx.send(NetworkEvent {
-                            session_id: session_id.clone(),
-                            event_type: "error".to_string(),
-                            data: None,
-                            error: Some(format!("WebSocket receive error: {}", e)),
-                            client_id: None,
-                            mqtt_topic: None,
-                            mqtt_qos: None,
-                            mqtt_retain: None,
-                            sse_event: None,
-                        }).await;
-                        break;
-                    }
-                }
-            }
-        });
-
-        Ok(rx)
+        // The reader started on connect feeds a single receiver
+        self.events.take()
+            .ok_or_else(|| NetworkError::ConnectionFailed("WebSocket events are already being received".to_string()))
     }
 
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
@@ -238,8 +276,10 @@ pub struct WebSocketServer {
     session_id: String,
     host: String,
     port: u16,
-    listener: Option<TcpListener>,
-    clients: Arc<RwLock<HashMap<String, Arc<RwLock<WebSocketStream<TcpStream>>>>>>,
+    clients: ServerClients,
+    app_handle: Option<AppHandle>,
+    events: Option<mpsc::Receiver<NetworkEvent>>,
+    accept_task: Option<JoinHandle<()>>,
     connected: bool,
 }
 
@@ -255,7 +295,7 @@ impl std::fmt::Debug for WebSocketServer {
 }
 
 impl WebSocketServer {
-    pub fn new(session_id: String, config: serde_json::Value) -> NetworkResult<Self> {
+    pub fn new(session_id: String, config: serde_json::Value, app_handle: Option<AppHandle>) -> NetworkResult<Self> {
         let host = config.get("host")
             .and_then(|v| v.as_str())
             .unwrap_or("0.0.0.0")
@@ -269,13 +309,82 @@ impl WebSocketServer {
             session_id,
             host,
             port,
-            listener: None,
             clients: Arc::new(RwLock::new(HashMap::new())),
+            app_handle,
+            events: None,
+            accept_task: None,
             connected: false,
         })
     }
 }
 
+/// Accept clients, reading each one's messages on its own task
+async fn run_server_acceptor(
+    listener: TcpListener,
+    session_id: String,
+    clients: ServerClients,
+    app_handle: Option<AppHandle>,
+    tx: mpsc::Sender<NetworkEvent>,
+) {
+    while let Ok((stream, addr)) = listener.accept().await {
+        let client_id = addr.to_string();
+        let session_id = session_id.clone();
+        let clients = Arc::clone(&clients);
+        let app_handle = app_handle.clone();
+        let tx = tx.clone();
+
+        tokio::spawn(async move {
+            let ws_stream = match accept_async(stream).await {
+                Ok(ws_stream) => ws_stream,
+                Err(e) => {
+                    let _ = tx.try_send(NetworkEvent {
+                        session_id,
+                        event_type: "error".to_string(),
+                        data: None,
+                        error: Some(format!("WebSocket handshake failed for {}: {}", client_id, e)),
+                        client_id: Some(client_id),
+                        mqtt_topic: None,
+                        mqtt_qos: None,
+                        mqtt_retain: None,
+                        sse_event: None,
+                    });
+                    return;
+                }
+            };
+
+            let (sink, mut reader) = ws_stream.split();
+            clients.write().await.insert(client_id.clone(), Arc::new(RwLock::new(sink)));
+
+            while let Some(Ok(message)) = reader.next().await {
+                let data = match message {
+                    Message::Text(text) => text.into_bytes(),
+                    Message::Binary(bytes) => bytes,
+                    Message::Close(_) => break,
+                    Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+                };
+
+                if let Some(app_handle) = &app_handle {
+                    crate::network::emit_received(app_handle, "websocket", &session_id, Some(&client_id), Some(addr), None, &data);
+                }
+
+                let _ = tx.try_send(NetworkEvent {
+                    session_id: session_id.clone(),
+                    event_type: "message".to_string(),
+                    data: Some(data),
+                    error: None,
+                    client_id: Some(client_id.clone()),
+                    mqtt_topic: None,
+                    mqtt_qos: None,
+                    mqtt_retain: None,
+                    sse_event: None,
+                });
+            }
+
+            clients.write().await.remove(&client_id);
+        });
+    }
+}
+
 #[async_trait]
 impl Connection for WebSocketServer {
     async fn connect(&mut self) -> NetworkResult<()> {
@@ -294,7 +403,16 @@ impl Connection for WebSocketServer {
             })?;
 
         eprintln!("WebSocketServer: Successfully bound to {}", bind_addr);
-        self.listener = Some(listener);
+
+        let (tx, rx) = mpsc::channel(1000);
+        self.accept_task = Some(tokio::spawn(run_server_acceptor(
+            listener,
+            self.session_id.clone(),
+            Arc::clone(&self.clients),
+            self.app_handle.clone(),
+            tx,
+        )));
+        self.events = Some(rx);
         self.connected = true;
         Ok(())
     }
@@ -309,11 +427,14 @@ impl Connection for WebSocketServer {
             let mut clients = self.clients.write().await;
             for (_, client_stream) in clients.drain() {
                 let mut stream = client_stream.write().await;
-                let _ = stream.close(None).await; // Ignore close errors
+                let _ = stream.close().await; // Ignore close errors
             }
         }
+        if let Some(task) = self.accept_task.take() {
+            task.abort();
+        }
 
-        self.listener = None;
+        self.events = None;
         self.connected = false;
         Ok(())
     }
@@ -340,55 +461,9 @@ impl Connection for WebSocketServer {
             return Err(NetworkError::NotConnected);
         }
 
-        let (tx, rx) = mpsc::channel(1000);
-
-        // Take the listener for the background task
-        let listener = self.listener.take()
-            .ok_or(NetworkError::NotConnected)?;
-
-        let session_id = self.session_id.clone();
-        let clients = Arc::clone(&self.clients);
-
-        // Spawn background task for accepting connections
-        tokio::spawn(async move {
-            while let Ok((stream, addr)) = listener.accept().await {
-                let client_id = addr.to_string();
-                let tx_clone = tx.clone();
-                let session_id_clone = session_id.clone();
-                let clients_clone = Arc::clone(&clients);
-
-                // Spawn task for each client connection
-                tokio::spawn(async move {
-                    match accept_async(stream).await {
-                        Ok(ws_stream) => {
-                            // Add client to the clients map
-                            {
-                                let mut clients_guard = clients_clone.write().await;
-                                clients_guard.insert(client_id.clone(), Arc::new(RwLock::new(ws_stream)));
-                            }
-
-                            // Handle client messages (simplified for now)
-                            // In a full implementation, we'd need to handle the message loop here
-                        }
-                        Err(e) => {
-                            let _ = tx_clone.send(NetworkEvent {
-                                session_id: session_id_clone,
-                                event_type: "error".to_string(),
-                                data: None,
-                                error: Some(format!("WebSocket handshake failed for {}: {}", client_id, e)),
-                                client_id: Some(client_id),
-                                mqtt_topic: None,
-                                mqtt_qos: None,
-                                mqtt_retain: None,
-                                sse_event: None,
-                            }).await;
-                        }
-                    }
-                });
-            }
-        });
-
-        Ok(rx)
+        // The acceptor started on connect feeds a single receiver
+        self.events.take()
+            .ok_or_else(|| NetworkError::ConnectionFailed("WebSocket events are already being received".to_string()))
     }
 
     fn as_any_mut(&mut self) -> &mut dyn std::any::Any {
@@ -465,7 +540,7 @@ impl ServerConnection for WebSocketServer {
         let mut clients = self.clients.write().await;
         if let Some(client_stream) = clients.remove(client_id) {
             let mut stream = client_stream.write().await;
-            let _ = stream.close(None).await; // Ignore close errors
+            let _ = stream.close().await; // Ignore close errors
         }
         Ok(())
     }
@@ -0,0 +1,637 @@
+//! Stateful factor alarm engine
+//!
+//! `FactorTranslator` classifies one value against static thresholds. The
+//! engine keeps state per session, device and factor instead, so that alarms on live
+//! stations don't flap: a limit must be exceeded for `min_duration_secs`
+//! before it is raised, it clears only once the value is back inside the
+//! hysteresis band, and factors that stop reporting raise a stale alarm.
+//!
+//! Only real-time uploads (CN 2011) feed the engine. Interval and history
+//! uploads report averages for past periods, which would distort rates of
+//! change and hide devices that stopped sending live data.
+
+use crate::parser::binding::ParsedMessage;
+use crate::parser::factor_catalog::builtin_catalog;
+use crate::parser::factor_translator::{FactorDefinition, FactorTranslator};
+use crate::parser::hj212::Hj212Packet;
+use crate::parser::ParserRegistry;
+use crate::types::{NetworkError, NetworkResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Event emitted to the frontend for every raise and clear transition
+pub const FACTOR_ALARM_EVENT: &str = "factor-alarm";
+
+/// Command code of real-time data uploads
+const REALTIME_DATA_CN: &str = "2011";
+
+/// Alarm configuration of one factor code
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlarmRule {
+    /// Raise when the value goes above
+    #[serde(default)]
+    pub high: Option<f64>,
+
+    /// Raise when the value goes below
+    #[serde(default)]
+    pub low: Option<f64>,
+
+    /// How far back inside a limit the value must move before clearing
+    #[serde(default)]
+    pub hysteresis: f64,
+
+    /// How long a condition must hold before it is raised
+    #[serde(default)]
+    pub min_duration_secs: u64,
+
+    /// Largest allowed change per minute
+    #[serde(default)]
+    pub max_rate_per_minute: Option<f64>,
+
+    /// Seconds without an update before the factor is stale
+    #[serde(default)]
+    pub stale_after_secs: Option<u64>,
+}
+
+impl AlarmRule {
+    /// Limit rule from the static thresholds of a factor definition
+    pub fn from_definition(definition: &FactorDefinition) -> Option<Self> {
+        if definition.alarm_high.is_none() && definition.alarm_low.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            high: definition.alarm_high,
+            low: definition.alarm_low,
+            ..Self::default()
+        })
+    }
+
+    /// Check the rule is consistent
+    pub fn validate(&self) -> NetworkResult<()> {
+        if !self.hysteresis.is_finite() || self.hysteresis < 0.0 {
+            return Err(NetworkError::InvalidConfig("Alarm hysteresis must be a non-negative number".to_string()));
+        }
+        if let (Some(high), Some(low)) = (self.high, self.low) {
+            if low >= high {
+                return Err(NetworkError::InvalidConfig(format!(
+                    "Alarm low limit {} must be below high limit {}", low, high
+                )));
+            }
+        }
+        if matches!(self.max_rate_per_minute, Some(rate) if rate <= 0.0) {
+            return Err(NetworkError::InvalidConfig("Alarm rate limit must be positive".to_string()));
+        }
+        if self.stale_after_secs == Some(0) {
+            return Err(NetworkError::InvalidConfig("Alarm stale timeout must be positive".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Condition an alarm watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlarmKind {
+    High,
+    Low,
+    RateOfChange,
+    Stale,
+}
+
+impl AlarmKind {
+    fn label(self) -> &'static str {
+        match self {
+            Self::High => "high",
+            Self::Low => "low",
+            Self::RateOfChange => "rate of change",
+            Self::Stale => "stale data",
+        }
+    }
+}
+
+/// Alarm state change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlarmTransition {
+    Raised,
+    Cleared,
+}
+
+/// A raise or clear of one alarm
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactorAlarmEvent {
+    /// Session the device reports on
+    pub session_id: String,
+    /// Device identifier (HJ212 MN)
+    pub device_id: String,
+    pub factor_code: String,
+    pub kind: AlarmKind,
+    pub transition: AlarmTransition,
+    /// Value that caused the transition; for rate alarms, the rate per minute
+    pub value: Option<f64>,
+    /// Limit the value was compared against
+    pub threshold: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl FactorAlarmEvent {
+    /// One-line description for logs
+    pub fn message(&self) -> String {
+        let transition = match self.transition {
+            AlarmTransition::Raised => "raised",
+            AlarmTransition::Cleared => "cleared",
+        };
+        let mut message = format!(
+            "{} alarm {} for {} on device {}",
+            self.kind.label(), transition, self.factor_code, self.device_id
+        );
+        if let Some(value) = self.value {
+            message.push_str(&format!(", value {}", value));
+        }
+        if let Some(threshold) = self.threshold {
+            message.push_str(&format!(", limit {}", threshold));
+        }
+        message
+    }
+}
+
+/// An alarm currently raised
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActiveAlarm {
+    pub session_id: String,
+    pub device_id: String,
+    pub factor_code: String,
+    pub kind: AlarmKind,
+    pub value: Option<f64>,
+    pub threshold: Option<f64>,
+    pub since: DateTime<Utc>,
+}
+
+/// Raised alarm details kept in the factor state
+#[derive(Debug, Clone)]
+struct Raised {
+    value: Option<f64>,
+    threshold: Option<f64>,
+    since: DateTime<Utc>,
+}
+
+/// Tracking state of one device factor
+#[derive(Debug, Clone)]
+struct FactorState {
+    last_value: f64,
+    last_update: DateTime<Utc>,
+    /// When each not yet raised condition started to hold
+    pending: HashMap<AlarmKind, DateTime<Utc>>,
+    active: HashMap<AlarmKind, Raised>,
+}
+
+/// Result of checking one condition against a value
+struct Check {
+    kind: AlarmKind,
+    violated: bool,
+    cleared: bool,
+    value: f64,
+    threshold: f64,
+}
+
+/// Session, device and factor code a state belongs to
+type StateKey = (String, String, String);
+
+/// Alarm rules and per device factor state
+///
+/// The same MN reporting on two sessions is tracked as two devices.
+#[derive(Debug, Default)]
+pub struct AlarmEngine {
+    rules: HashMap<String, AlarmRule>,
+    states: HashMap<StateKey, FactorState>,
+}
+
+impl AlarmEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Configured rules by factor code
+    pub fn rules(&self) -> &HashMap<String, AlarmRule> {
+        &self.rules
+    }
+
+    /// Set the rule of a factor code
+    pub fn set_rule(&mut self, factor_code: &str, rule: AlarmRule) -> NetworkResult<()> {
+        rule.validate()?;
+        self.rules.insert(factor_code.to_string(), rule);
+        Ok(())
+    }
+
+    /// Add limit rules from factor definitions, keeping configured ones
+    pub fn load_definitions(&mut self, definitions: &HashMap<String, FactorDefinition>) {
+        for (code, definition) in definitions {
+            if self.rules.contains_key(code) {
+                continue;
+            }
+            if let Some(rule) = AlarmRule::from_definition(definition) {
+                if rule.validate().is_ok() {
+                    self.rules.insert(code.clone(), rule);
+                }
+            }
+        }
+    }
+
+    /// Remove the rule of a factor code, clearing its raised alarms
+    pub fn remove_rule(&mut self, factor_code: &str, at: DateTime<Utc>) -> Vec<FactorAlarmEvent> {
+        if self.rules.remove(factor_code).is_none() {
+            return Vec::new();
+        }
+
+        let mut events = Vec::new();
+        for ((session_id, device_id, code), state) in self.states.iter_mut() {
+            if code != factor_code {
+                continue;
+            }
+            state.pending.clear();
+            for (kind, raised) in state.active.drain() {
+                events.push(FactorAlarmEvent {
+                    session_id: session_id.clone(),
+                    device_id: device_id.clone(),
+                    factor_code: code.clone(),
+                    kind,
+                    transition: AlarmTransition::Cleared,
+                    value: raised.value,
+                    threshold: raised.threshold,
+                    timestamp: at,
+                });
+            }
+        }
+        events
+    }
+
+    /// Feed one factor value reported by a device
+    pub fn observe(
+        &mut self,
+        session_id: &str,
+        device_id: &str,
+        factor_code: &str,
+        value: f64,
+        at: DateTime<Utc>,
+    ) -> Vec<FactorAlarmEvent> {
+        let key = (session_id.to_string(), device_id.to_string(), factor_code.to_string());
+        let previous = self.states.get(&key).map(|state| (state.last_value, state.last_update));
+        let state = self.states.entry(key).or_insert_with(|| FactorState {
+            last_value: value,
+            last_update: at,
+            pending: HashMap::new(),
+            active: HashMap::new(),
+        });
+        state.last_value = value;
+        state.last_update = at;
+
+        let mut events = Vec::new();
+        if let Some(raised) = state.active.remove(&AlarmKind::Stale) {
+            events.push(event(session_id, device_id, factor_code, AlarmKind::Stale, AlarmTransition::Cleared, Some(value), raised.threshold, at));
+        }
+
+        let Some(rule) = self.rules.get(factor_code) else {
+            return events;
+        };
+
+        let mut checks = Vec::new();
+        if let Some(high) = rule.high {
+            checks.push(Check {
+                kind: AlarmKind::High,
+                violated: value > high,
+                cleared: value <= high - rule.hysteresis,
+                value,
+                threshold: high,
+            });
+        }
+        if let Some(low) = rule.low {
+            checks.push(Check {
+                kind: AlarmKind::Low,
+                violated: value < low,
+                cleared: value >= low + rule.hysteresis,
+                value,
+                threshold: low,
+            });
+        }
+        if let (Some(limit), Some((last_value, last_update))) = (rule.max_rate_per_minute, previous) {
+            let elapsed = (at - last_update).num_milliseconds() as f64 / 60_000.0;
+            if elapsed > 0.0 {
+                let rate = (value - last_value).abs() / elapsed;
+                checks.push(Check {
+                    kind: AlarmKind::RateOfChange,
+                    violated: rate > limit,
+                    cleared: rate <= limit,
+                    value: rate,
+                    threshold: limit,
+                });
+            }
+        }
+
+        let min_duration = chrono::Duration::seconds(rule.min_duration_secs as i64);
+        for check in checks {
+            if state.active.contains_key(&check.kind) {
+                if check.cleared {
+                    state.active.remove(&check.kind);
+                    events.push(event(session_id, device_id, factor_code, check.kind, AlarmTransition::Cleared, Some(check.value), Some(check.threshold), at));
+                }
+                continue;
+            }
+
+            if !check.violated {
+                state.pending.remove(&check.kind);
+                continue;
+            }
+
+            let since = *state.pending.entry(check.kind).or_insert(at);
+            if at - since >= min_duration {
+                state.pending.remove(&check.kind);
+                state.active.insert(check.kind, Raised {
+                    value: Some(check.value),
+                    threshold: Some(check.threshold),
+                    since: at,
+                });
+                events.push(event(session_id, device_id, factor_code, check.kind, AlarmTransition::Raised, Some(check.value), Some(check.threshold), at));
+            }
+        }
+
+        events
+    }
+
+    /// Feed the real-time values of a valid HJ212 real-time upload, keyed by its MN
+    ///
+    /// Other command codes and factors flagged anything but normal (`N`) are ignored.
+    pub fn observe_packet(&mut self, session_id: &str, packet: &Hj212Packet, at: DateTime<Utc>) -> Vec<FactorAlarmEvent> {
+        if packet.header.cn.as_deref() != Some(REALTIME_DATA_CN) {
+            return Vec::new();
+        }
+        let Some(device_id) = packet.header.mn.as_deref().filter(|_| packet.is_valid()) else {
+            return Vec::new();
+        };
+
+        let catalog = builtin_catalog();
+        let mut events = Vec::new();
+        for factor in &packet.cp.factors {
+            let normal = factor.flag.as_deref().map(|flag| flag.eq_ignore_ascii_case("N")).unwrap_or(true);
+            let Some(value) = factor.rtd.filter(|_| normal) else {
+                continue;
+            };
+            let code = catalog.standard_code(&factor.code).unwrap_or(&factor.code);
+            events.extend(self.observe(session_id, device_id, code, value, at));
+        }
+        events
+    }
+
+    /// Feed the HJ212 packet behind a message parsed on a live session
+    pub fn observe_message(&mut self, message: &ParsedMessage, at: DateTime<Utc>) -> Vec<FactorAlarmEvent> {
+        if !message.result.protocol.name.to_ascii_uppercase().contains("HJ212") {
+            return Vec::new();
+        }
+        match Hj212Packet::decode(&message.frame) {
+            Ok(packet) => self.observe_packet(&message.session_id, &packet, at),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Raise stale alarms for factors that stopped reporting
+    pub fn check_stale(&mut self, at: DateTime<Utc>) -> Vec<FactorAlarmEvent> {
+        let mut events = Vec::new();
+        for ((session_id, device_id, factor_code), state) in self.states.iter_mut() {
+            let Some(stale_after) = self.rules.get(factor_code).and_then(|rule| rule.stale_after_secs) else {
+                continue;
+            };
+            if state.active.contains_key(&AlarmKind::Stale)
+                || at - state.last_update < chrono::Duration::seconds(stale_after as i64)
+            {
+                continue;
+            }
+
+            let threshold = Some(stale_after as f64);
+            state.active.insert(AlarmKind::Stale, Raised { value: Some(state.last_value), threshold, since: at });
+            events.push(event(session_id, device_id, factor_code, AlarmKind::Stale, AlarmTransition::Raised, Some(state.last_value), threshold, at));
+        }
+        events
+    }
+
+    /// Alarms currently raised
+    pub fn active_alarms(&self) -> Vec<ActiveAlarm> {
+        let mut alarms: Vec<ActiveAlarm> = self.states.iter()
+            .flat_map(|((session_id, device_id, factor_code), state)| {
+                state.active.iter().map(move |(kind, raised)| ActiveAlarm {
+                    session_id: session_id.clone(),
+                    device_id: device_id.clone(),
+                    factor_code: factor_code.clone(),
+                    kind: *kind,
+                    value: raised.value,
+                    threshold: raised.threshold,
+                    since: raised.since,
+                })
+            })
+            .collect();
+        alarms.sort_by(|a, b| a.since.cmp(&b.since).then_with(|| a.device_id.cmp(&b.device_id)));
+        alarms
+    }
+
+    /// Forget the devices reporting on a session
+    pub fn remove_session(&mut self, session_id: &str) {
+        self.states.retain(|(session, _, _), _| session != session_id);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn event(
+    session_id: &str,
+    device_id: &str,
+    factor_code: &str,
+    kind: AlarmKind,
+    transition: AlarmTransition,
+    value: Option<f64>,
+    threshold: Option<f64>,
+    at: DateTime<Utc>,
+) -> FactorAlarmEvent {
+    FactorAlarmEvent {
+        session_id: session_id.to_string(),
+        device_id: device_id.to_string(),
+        factor_code: factor_code.to_string(),
+        kind,
+        transition,
+        value,
+        threshold,
+        timestamp: at,
+    }
+}
+
+/// Load limit rules from the factor definitions of enabled protocols, then
+/// from the built-in catalog, keeping rules already configured
+pub fn load_factor_alarm_rules(engine: &mut AlarmEngine, registry: &mut ParserRegistry) {
+    if let Some(repository) = registry.repository_mut() {
+        let protocol_ids: Vec<String> = repository.list_enabled_protocols()
+            .into_iter()
+            .map(|metadata| metadata.id.clone())
+            .collect();
+        for protocol_id in protocol_ids {
+            let translator = repository.load_protocol_rule(&protocol_id)
+                .and_then(|rule| FactorTranslator::from_rule(&rule));
+            match translator {
+                Ok(translator) => engine.load_definitions(translator.definitions()),
+                Err(e) => log::warn!("Skipping alarm limits of protocol {}: {}", protocol_id, e),
+            }
+        }
+    }
+    engine.load_definitions(builtin_catalog().definitions());
+}
+
+/// Global alarm engine instance
+static ALARM_ENGINE: OnceLock<Arc<Mutex<AlarmEngine>>> = OnceLock::new();
+
+/// Get the global alarm engine
+pub fn get_alarm_engine() -> Arc<Mutex<AlarmEngine>> {
+    ALARM_ENGINE.get_or_init(|| Arc::new(Mutex::new(AlarmEngine::new()))).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::seconds(secs)
+    }
+
+    fn transitions(events: &[FactorAlarmEvent]) -> Vec<(AlarmKind, AlarmTransition)> {
+        events.iter().map(|event| (event.kind, event.transition)).collect()
+    }
+
+    #[test]
+    fn test_duration_and_hysteresis() {
+        let mut engine = AlarmEngine::new();
+        engine.set_rule("w01018", AlarmRule {
+            high: Some(100.0),
+            hysteresis: 5.0,
+            min_duration_secs: 60,
+            ..AlarmRule::default()
+        }).unwrap();
+
+        // A short spike is not raised
+        assert!(engine.observe("s", "MN1", "w01018", 120.0, at(0)).is_empty());
+        assert!(engine.observe("s", "MN1", "w01018", 90.0, at(30)).is_empty());
+
+        // A sustained excursion is, once the duration has passed
+        assert!(engine.observe("s", "MN1", "w01018", 110.0, at(60)).is_empty());
+        let events = engine.observe("s", "MN1", "w01018", 105.0, at(120));
+        assert_eq!(transitions(&events), vec![(AlarmKind::High, AlarmTransition::Raised)]);
+        assert_eq!(engine.active_alarms().len(), 1);
+
+        // Inside the hysteresis band the alarm holds
+        assert!(engine.observe("s", "MN1", "w01018", 98.0, at(180)).is_empty());
+        let events = engine.observe("s", "MN1", "w01018", 94.0, at(240));
+        assert_eq!(transitions(&events), vec![(AlarmKind::High, AlarmTransition::Cleared)]);
+        assert!(engine.active_alarms().is_empty());
+
+        // State is per device and per session
+        assert!(engine.observe("s", "MN2", "w01018", 94.0, at(240)).is_empty());
+        engine.observe("s", "MN1", "w01018", 120.0, at(300));
+        let events = engine.observe("s", "MN1", "w01018", 120.0, at(360));
+        assert_eq!(transitions(&events), vec![(AlarmKind::High, AlarmTransition::Raised)]);
+        assert!(engine.observe("s2", "MN1", "w01018", 120.0, at(360)).is_empty());
+        assert!(engine.observe("s2", "MN1", "w01018", 90.0, at(420)).is_empty());
+        assert_eq!(engine.active_alarms()[0].session_id, "s");
+    }
+
+    #[test]
+    fn test_parsed_hj212_messages_are_observed() {
+        use crate::parser::hj212::{encode_frame, Hj212Parser};
+        use crate::parser::Parser;
+
+        let mut engine = AlarmEngine::new();
+        engine.set_rule("w01018", AlarmRule { high: Some(100.0), ..AlarmRule::default() }).unwrap();
+
+        let frame = encode_frame("QN=1;ST=32;CN=2011;PW=1;MN=MN1;Flag=5;CP=&&w01018-Rtd=150&&").unwrap().into_bytes();
+        let message = ParsedMessage {
+            session_id: "s".to_string(),
+            client_id: None,
+            parser_id: "hj212".to_string(),
+            data: frame.clone(),
+            result: Hj212Parser::new().parse(&frame).unwrap(),
+            frame,
+        };
+        let events = engine.observe_message(&message, at(0));
+        assert_eq!(transitions(&events), vec![(AlarmKind::High, AlarmTransition::Raised)]);
+        assert_eq!((events[0].session_id.as_str(), events[0].device_id.as_str()), ("s", "MN1"));
+    }
+
+    #[test]
+    fn test_only_normal_realtime_values_are_observed() {
+        use crate::parser::hj212::encode_frame;
+
+        let mut engine = AlarmEngine::new();
+        engine.set_rule("a34004", AlarmRule {
+            high: Some(100.0),
+            max_rate_per_minute: Some(10.0),
+            ..AlarmRule::default()
+        }).unwrap();
+        let packet = |cn: &str, cp: &str| {
+            let frame = encode_frame(&format!("QN=1;ST=22;CN={};PW=1;MN=MN1;Flag=5;CP=&&{}&&", cn, cp)).unwrap();
+            Hj212Packet::decode(frame.as_bytes()).unwrap()
+        };
+
+        assert!(engine.observe_packet("s", &packet("2011", "a34004-Rtd=30,a34004-Flag=N"), at(0)).is_empty());
+
+        // Minute and hourly averages arriving between real-time uploads are ignored
+        assert!(engine.observe_packet("s", &packet("2051", "DataTime=20240101000000;a34004-Avg=80,a34004-Flag=N"), at(20)).is_empty());
+        assert!(engine.observe_packet("s", &packet("2061", "DataTime=20231231230000;a34004-Avg=150,a34004-Flag=N"), at(40)).is_empty());
+        assert!(engine.observe_packet("s", &packet("2011", "a34004-Rtd=35,a34004-Flag=N"), at(60)).is_empty());
+
+        // Values flagged as maintenance or faulty are ignored
+        assert!(engine.observe_packet("s", &packet("2011", "a34004-Rtd=500,a34004-Flag=M"), at(120)).is_empty());
+
+        let events = engine.observe_packet("s", &packet("2011", "a34004-Rtd=150,a34004-Flag=N"), at(180));
+        assert_eq!(transitions(&events), vec![
+            (AlarmKind::High, AlarmTransition::Raised),
+            (AlarmKind::RateOfChange, AlarmTransition::Raised),
+        ]);
+        assert_eq!(events[1].value, Some(57.5));
+    }
+
+    #[test]
+    fn test_rate_of_change_and_stale() {
+        let mut engine = AlarmEngine::new();
+        engine.set_rule("a34004", AlarmRule {
+            max_rate_per_minute: Some(10.0),
+            stale_after_secs: Some(300),
+            ..AlarmRule::default()
+        }).unwrap();
+
+        assert!(engine.observe("s", "MN1", "a34004", 30.0, at(0)).is_empty());
+        let events = engine.observe("s", "MN1", "a34004", 60.0, at(60));
+        assert_eq!(transitions(&events), vec![(AlarmKind::RateOfChange, AlarmTransition::Raised)]);
+        assert_eq!(events[0].value, Some(30.0));
+        let events = engine.observe("s", "MN1", "a34004", 65.0, at(120));
+        assert_eq!(transitions(&events), vec![(AlarmKind::RateOfChange, AlarmTransition::Cleared)]);
+
+        assert!(engine.check_stale(at(300)).is_empty());
+        let events = engine.check_stale(at(420));
+        assert_eq!(transitions(&events), vec![(AlarmKind::Stale, AlarmTransition::Raised)]);
+        assert!(engine.check_stale(at(480)).is_empty());
+
+        let events = engine.observe("s", "MN1", "a34004", 65.0, at(500));
+        assert_eq!(transitions(&events), vec![(AlarmKind::Stale, AlarmTransition::Cleared)]);
+    }
+
+    #[test]
+    fn test_rule_validation_and_removal() {
+        let mut engine = AlarmEngine::new();
+        assert!(engine.set_rule("w01018", AlarmRule { high: Some(1.0), low: Some(2.0), ..AlarmRule::default() }).is_err());
+        assert!(engine.set_rule("w01018", AlarmRule { hysteresis: -1.0, ..AlarmRule::default() }).is_err());
+
+        engine.set_rule("w01018", AlarmRule { low: Some(5.0), ..AlarmRule::default() }).unwrap();
+        let events = engine.observe("s", "MN1", "w01018", 1.0, at(0));
+        assert_eq!(transitions(&events), vec![(AlarmKind::Low, AlarmTransition::Raised)]);
+
+        let events = engine.remove_rule("w01018", at(10));
+        assert_eq!(transitions(&events), vec![(AlarmKind::Low, AlarmTransition::Cleared)]);
+        assert!(engine.active_alarms().is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+//! Packed BCD helpers
+//!
+//! Two decimal digits per byte, most significant nibble first. Protocols
+//! that send BCD least significant byte first (DL/T 645) reverse the bytes
+//! before calling these.
+
+use crate::types::{NetworkError, NetworkResult};
+
+/// Nibbles as hex digits, without checking they are decimal
+pub fn to_digits(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+/// Every nibble is a decimal digit
+pub fn is_valid(bytes: &[u8]) -> bool {
+    bytes.iter().all(|byte| byte >> 4 <= 9 && byte & 0x0F <= 9)
+}
+
+/// Decode to an integer
+pub fn decode(bytes: &[u8]) -> NetworkResult<u64> {
+    if bytes.len() > 9 || !is_valid(bytes) {
+        return Err(NetworkError::ParseError(format!("Invalid BCD value {}", to_digits(bytes))));
+    }
+
+    Ok(bytes.iter().fold(0, |value, byte| value * 100 + (byte >> 4) as u64 * 10 + (byte & 0x0F) as u64))
+}
+
+/// Encode decimal digits into `length` bytes, left-padded with zeros
+pub fn encode(digits: &str, length: usize) -> NetworkResult<Vec<u8>> {
+    if digits.len() > length * 2 || !digits.bytes().all(|digit| digit.is_ascii_digit()) {
+        return Err(NetworkError::ParseError(format!("'{}' does not fit {} BCD bytes", digits, length)));
+    }
+
+    let padded = format!("{:0>width$}", digits, width = length * 2);
+    Ok(padded.as_bytes()
+        .chunks(2)
+        .map(|pair| ((pair[0] - b'0') << 4) | (pair[1] - b'0'))
+        .collect())
+}
+
+/// Encode an integer into `length` bytes
+pub fn encode_u64(value: u64, length: usize) -> NetworkResult<Vec<u8>> {
+    encode(&value.to_string(), length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bcd_round_trip() {
+        assert_eq!(encode("13800138000", 6).unwrap(), vec![0x01, 0x38, 0x00, 0x13, 0x80, 0x00]);
+        assert_eq!(decode(&[0x12, 0x34, 0x56]).unwrap(), 123456);
+        assert_eq!(encode_u64(2024, 2).unwrap(), vec![0x20, 0x24]);
+        assert_eq!(to_digits(&[0xAA, 0x01]), "AA01");
+        assert!(decode(&[0x1A]).is_err());
+        assert!(encode("12345", 2).is_err());
+    }
+}
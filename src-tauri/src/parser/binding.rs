@@ -0,0 +1,259 @@
+//! Live session parsing
+//!
+//! A session can bind one or more protocol parsers, or auto-detection, so
+//! every received chunk is parsed on the Rust side. Each session/client pair
+//! keeps its own frame detectors, so partial frames from one TCP client
+//! never mix with another client's data.
+
+use crate::parser::envelope::EnvelopeContext;
+use crate::parser::framing::FrameDetector;
+use crate::parser::result::ParseResult;
+use crate::parser::ParserRegistry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Event name emitted to the frontend for every parsed message
+pub const MESSAGE_PARSED_EVENT: &str = "message-parsed";
+
+/// Parsers applied to a session's received data
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionParserBinding {
+    /// Protocol IDs whose parsers run on every received chunk
+    #[serde(default)]
+    pub protocol_ids: Vec<String>,
+
+    /// Fall back to auto-detection when no bound parser produced a result
+    #[serde(default)]
+    pub auto_detect: bool,
+}
+
+impl SessionParserBinding {
+    /// Check if the binding parses anything at all
+    pub fn is_active(&self) -> bool {
+        !self.protocol_ids.is_empty() || self.auto_detect
+    }
+}
+
+/// A parse result together with the raw data it came from
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedMessage {
+    /// Session that received the data
+    pub session_id: String,
+
+    /// Client the data came from (server sessions)
+    pub client_id: Option<String>,
+
+    /// Parser that produced the result
+    pub parser_id: String,
+
+    /// Received chunk
+    pub data: Vec<u8>,
+
+    /// Frame the result was parsed from
+    pub frame: Vec<u8>,
+
+    /// Parse result
+    pub result: ParseResult,
+}
+
+/// Framing state of one session/client stream, per bound parser
+#[derive(Default)]
+struct StreamContext {
+    framers: HashMap<String, Option<FrameDetector>>,
+}
+
+/// Parser bindings and per-client framing state of all sessions
+#[derive(Default)]
+pub struct SessionParsers {
+    bindings: HashMap<String, SessionParserBinding>,
+    streams: HashMap<(String, Option<String>), StreamContext>,
+}
+
+impl SessionParsers {
+    /// Create an empty set of bindings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind parsers to a session, discarding its buffered partial frames
+    pub fn set_binding(&mut self, session_id: &str, binding: SessionParserBinding) {
+        self.reset_session(session_id);
+
+        if binding.is_active() {
+            self.bindings.insert(session_id.to_string(), binding);
+        } else {
+            self.bindings.remove(session_id);
+        }
+    }
+
+    /// Get a session's binding
+    pub fn binding(&self, session_id: &str) -> Option<&SessionParserBinding> {
+        self.bindings.get(session_id)
+    }
+
+    /// Check if a session has parsers bound
+    pub fn is_bound(&self, session_id: &str) -> bool {
+        self.bindings.contains_key(session_id)
+    }
+
+    /// Forget a session's binding and framing state
+    pub fn remove_session(&mut self, session_id: &str) {
+        self.reset_session(session_id);
+        self.bindings.remove(session_id);
+    }
+
+    /// Discard buffered partial frames of every client of a session
+    pub fn reset_session(&mut self, session_id: &str) {
+        self.streams.retain(|(session, _), _| session != session_id);
+    }
+
+    /// Discard buffered partial frames of one client
+    pub fn reset_stream(&mut self, session_id: &str, client_id: Option<&str>) {
+        self.streams.remove(&(session_id.to_string(), client_id.map(str::to_string)));
+    }
+
+    /// Run a received chunk through the session's bound parsers
+    pub fn process(
+        &mut self,
+        registry: &ParserRegistry,
+        session_id: &str,
+        client_id: Option<&str>,
+        data: &[u8],
+        envelope: &EnvelopeContext,
+    ) -> Vec<ParsedMessage> {
+        let Some(binding) = self.bindings.get(session_id) else {
+            return Vec::new();
+        };
+        let stream = self.streams
+            .entry((session_id.to_string(), client_id.map(str::to_string)))
+            .or_default();
+
+        let message = |parser_id: &str, frame: Vec<u8>, result: ParseResult| ParsedMessage {
+            session_id: session_id.to_string(),
+            client_id: client_id.map(str::to_string),
+            parser_id: parser_id.to_string(),
+            data: data.to_vec(),
+            frame,
+            result,
+        };
+
+        let mut messages = Vec::new();
+        for parser_id in &binding.protocol_ids {
+            let Some(parser) = registry.get_parser(parser_id) else {
+                continue;
+            };
+            if !parser.accepts_envelope(envelope) {
+                continue;
+            }
+
+            let framer = stream.framers
+                .entry(parser_id.clone())
+                .or_insert_with(|| parser.stream_framer());
+
+            match framer {
+                Some(framer) => {
+                    let frames = match framer.detect_frames(data) {
+                        Ok(frames) => frames,
+                        Err(e) => {
+                            log::warn!("Framing failed for parser {} on session {}: {}", parser_id, session_id, e);
+                            framer.reset();
+                            continue;
+                        }
+                    };
+
+                    for frame in frames.into_iter().filter(|frame| frame.complete) {
+                        match parser.parse_frame_with_envelope(&frame.data, envelope) {
+                            Ok(result) => messages.push(message(parser_id, frame.data, result)),
+                            Err(e) => log::warn!("Parser {} failed on session {}: {}", parser_id, session_id, e),
+                        }
+                    }
+                }
+                None => match parser.parse_with_envelope(data, envelope) {
+                    Ok(result) => messages.push(message(parser_id, data.to_vec(), result)),
+                    Err(e) => log::warn!("Parser {} failed on session {}: {}", parser_id, session_id, e),
+                },
+            }
+        }
+
+        if messages.is_empty() && binding.auto_detect {
+            if let Some(parser) = registry.auto_detect_with_envelope(data, envelope) {
+                match parser.parse_with_envelope(data, envelope) {
+                    Ok(result) if result.success => {
+                        messages.push(message(parser.get_id(), data.to_vec(), result));
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Auto-detected parser {} failed on session {}: {}", parser.get_id(), session_id, e),
+                }
+            }
+        }
+
+        messages
+    }
+}
+
+/// Global session parser bindings
+static SESSION_PARSERS: OnceLock<Arc<Mutex<SessionParsers>>> = OnceLock::new();
+
+/// Get the global session parser bindings
+pub fn get_session_parsers() -> Arc<Mutex<SessionParsers>> {
+    SESSION_PARSERS.get_or_init(|| {
+        Arc::new(Mutex::new(SessionParsers::new()))
+    }).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ProtocolParser;
+
+    fn registry_with_parser() -> ParserRegistry {
+        let rule = "meta:\n  name: Pair\n  version: \"1.0\"\n  author: A\nframing:\n  fixed_size: 2\nfields:\n  - name: a\n    type: uint8\n    offset: 0\n  - name: b\n    type: uint8\n    offset: 1\n";
+        let mut registry = ParserRegistry::new();
+        registry.register_parser(Box::new(ProtocolParser::from_rule_string("pair".to_string(), rule).unwrap()));
+        registry
+    }
+
+    #[test]
+    fn test_frames_split_across_chunks() {
+        let registry = registry_with_parser();
+        let mut parsers = SessionParsers::new();
+        parsers.set_binding("s1", SessionParserBinding {
+            protocol_ids: vec!["pair".to_string()],
+            auto_detect: false,
+        });
+        let envelope = EnvelopeContext::new();
+
+        assert!(parsers.process(&registry, "s1", Some("c1"), &[0x01], &envelope).is_empty());
+
+        // Another client's bytes must not complete c1's frame
+        assert!(parsers.process(&registry, "s1", Some("c2"), &[0x09], &envelope).is_empty());
+
+        let messages = parsers.process(&registry, "s1", Some("c1"), &[0x02, 0x03, 0x04], &envelope);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].frame, vec![0x01, 0x02]);
+        assert_eq!(messages[1].frame, vec![0x03, 0x04]);
+        assert!(messages[0].result.success);
+        assert_eq!(messages[0].client_id.as_deref(), Some("c1"));
+    }
+
+    #[test]
+    fn test_unbound_session_is_ignored() {
+        let registry = registry_with_parser();
+        let mut parsers = SessionParsers::new();
+        let envelope = EnvelopeContext::new();
+
+        assert!(parsers.process(&registry, "s1", None, &[0x01, 0x02], &envelope).is_empty());
+
+        parsers.set_binding("s1", SessionParserBinding {
+            protocol_ids: vec!["pair".to_string()],
+            auto_detect: false,
+        });
+        assert!(parsers.is_bound("s1"));
+
+        parsers.set_binding("s1", SessionParserBinding::default());
+        assert!(!parsers.is_bound("s1"));
+    }
+}
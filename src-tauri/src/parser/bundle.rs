@@ -0,0 +1,473 @@
+//! Protocol bundles
+//!
+//! A bundle is a single zip archive carrying everything needed to use a
+//! protocol on another machine: the rule file, its metadata (including the
+//! enabled state), referenced catalog tables, `.proto` schemas and sample
+//! captures. Catalogs keep the path the rule references them by, relative to
+//! the rule directory. `bundle.json` lists every file with its SHA-256 so a bundle can
+//! be validated before anything is written to the repository.
+
+use crate::parser::repository::ProtocolMetadata;
+use crate::parser::revisions::content_hash_bytes;
+use crate::types::{NetworkResult, NetworkError};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Seek, Write};
+use std::path::{Component, Path};
+use std::sync::OnceLock;
+
+/// Bundle format version written by this build
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// File extension of protocol bundles
+pub const BUNDLE_EXTENSION: &str = "kkpbundle";
+
+/// Manifest file name inside the archive
+const MANIFEST_FILE: &str = "bundle.json";
+
+/// Largest file a bundle may carry, manifest included
+const MAX_ENTRY_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Largest total size of all files in a bundle
+const MAX_BUNDLE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Kind of file stored in a bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BundleFileKind {
+    /// The protocol rule (exactly one per bundle)
+    Rule,
+    /// Catalog table (CSV) referenced by the rule
+    Catalog,
+    /// Protobuf schema referenced by the rule
+    Schema,
+    /// Sample capture
+    Sample,
+}
+
+impl BundleFileKind {
+    /// Archive directory for this kind of file
+    fn directory(&self) -> &'static str {
+        match self {
+            BundleFileKind::Rule => "rule",
+            BundleFileKind::Catalog => "catalogs",
+            BundleFileKind::Schema => "schemas",
+            BundleFileKind::Sample => "samples",
+        }
+    }
+}
+
+/// File entry in the bundle manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleFile {
+    /// Path inside the archive
+    pub path: String,
+
+    /// File kind
+    pub kind: BundleFileKind,
+
+    /// SHA-256 of the content (hex)
+    pub sha256: String,
+
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl BundleFile {
+    /// File name without the archive directory (a relative path for catalogs)
+    pub fn name(&self) -> &str {
+        self.path.split_once('/').map(|(_, name)| name).unwrap_or(&self.path)
+    }
+}
+
+/// Bundle manifest (`bundle.json`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    /// Bundle format version
+    pub format_version: u32,
+
+    /// When the bundle was created
+    pub created_at: DateTime<Utc>,
+
+    /// Protocol metadata, including enabled state and tags
+    pub metadata: ProtocolMetadata,
+
+    /// Files in the bundle
+    pub files: Vec<BundleFile>,
+}
+
+/// Protocol bundle held in memory
+#[derive(Debug, Clone)]
+pub struct ProtocolBundle {
+    manifest: BundleManifest,
+    contents: HashMap<String, Vec<u8>>,
+}
+
+impl ProtocolBundle {
+    /// Create an empty bundle for a protocol
+    pub fn new(metadata: ProtocolMetadata) -> Self {
+        Self {
+            manifest: BundleManifest {
+                format_version: BUNDLE_FORMAT_VERSION,
+                created_at: Utc::now(),
+                metadata,
+                files: Vec::new(),
+            },
+            contents: HashMap::new(),
+        }
+    }
+
+    /// Add a file to the bundle
+    pub fn add_file(&mut self, kind: BundleFileKind, name: &str, content: Vec<u8>) -> NetworkResult<()> {
+        let name = match kind {
+            BundleFileKind::Catalog => catalog_path(name),
+            _ => Some(name.to_string()).filter(|name| is_safe_name(name)),
+        }
+        .ok_or_else(|| NetworkError::ParseError(format!("Invalid bundle file name: {}", name)))?;
+        if kind == BundleFileKind::Rule && !self.files_of(BundleFileKind::Rule).is_empty() {
+            return Err(NetworkError::ParseError("A bundle can only contain one rule".to_string()));
+        }
+
+        let path = format!("{}/{}", kind.directory(), name);
+        if self.contents.contains_key(&path) {
+            return Ok(());
+        }
+
+        self.manifest.files.push(BundleFile {
+            path: path.clone(),
+            kind,
+            sha256: content_hash_bytes(&content),
+            size: content.len() as u64,
+        });
+        self.contents.insert(path, content);
+        Ok(())
+    }
+
+    /// Get the manifest
+    pub fn manifest(&self) -> &BundleManifest {
+        &self.manifest
+    }
+
+    /// Get the protocol metadata
+    pub fn metadata(&self) -> &ProtocolMetadata {
+        &self.manifest.metadata
+    }
+
+    /// Get files of one kind
+    pub fn files_of(&self, kind: BundleFileKind) -> Vec<&BundleFile> {
+        self.manifest.files.iter().filter(|f| f.kind == kind).collect()
+    }
+
+    /// Get a file's content
+    pub fn content(&self, file: &BundleFile) -> Option<&[u8]> {
+        self.contents.get(&file.path).map(|c| c.as_slice())
+    }
+
+    /// Get the rule file and its content
+    pub fn rule(&self) -> NetworkResult<(&BundleFile, &str)> {
+        let file = self.files_of(BundleFileKind::Rule).into_iter().next()
+            .ok_or_else(|| NetworkError::ParseError("Bundle contains no rule".to_string()))?;
+        let content = self.content(file).unwrap_or_default();
+        let content = std::str::from_utf8(content)
+            .map_err(|e| NetworkError::ParseError(format!("Bundle rule is not valid UTF-8: {}", e)))?;
+        Ok((file, content))
+    }
+
+    /// Write the bundle as a zip archive
+    pub fn write_to<W: Write + Seek>(&self, writer: W) -> NetworkResult<()> {
+        let zip_error = |e: zip::result::ZipError| NetworkError::ParseError(format!("Failed to write bundle: {}", e));
+        let io_error = |e: std::io::Error| NetworkError::ParseError(format!("Failed to write bundle: {}", e));
+
+        let mut zip = zip::ZipWriter::new(writer);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        let manifest = serde_json::to_vec_pretty(&self.manifest)?;
+        zip.start_file(MANIFEST_FILE, options).map_err(zip_error)?;
+        zip.write_all(&manifest).map_err(io_error)?;
+
+        for file in &self.manifest.files {
+            zip.start_file(file.path.as_str(), options).map_err(zip_error)?;
+            zip.write_all(&self.contents[&file.path]).map_err(io_error)?;
+        }
+
+        zip.finish().map_err(zip_error)?;
+        Ok(())
+    }
+
+    /// Serialize the bundle to bytes
+    pub fn to_bytes(&self) -> NetworkResult<Vec<u8>> {
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        self.write_to(&mut buffer)?;
+        Ok(buffer.into_inner())
+    }
+
+    /// Read and validate a bundle from a zip archive
+    pub fn read_from<R: Read + Seek>(reader: R) -> NetworkResult<Self> {
+        Self::read_with_limits(reader, MAX_ENTRY_SIZE, MAX_BUNDLE_SIZE)
+    }
+
+    /// Read a bundle, refusing files over `entry_limit` bytes or more than
+    /// `total_limit` bytes in all, whatever sizes the archive declares
+    fn read_with_limits<R: Read + Seek>(reader: R, entry_limit: u64, total_limit: u64) -> NetworkResult<Self> {
+        let zip_error = |e: zip::result::ZipError| NetworkError::ParseError(format!("Invalid bundle archive: {}", e));
+
+        let mut archive = zip::ZipArchive::new(reader).map_err(zip_error)?;
+
+        let manifest: BundleManifest = {
+            let entry = archive.by_name(MANIFEST_FILE)
+                .map_err(|_| NetworkError::ParseError(format!("Bundle has no {}", MANIFEST_FILE)))?;
+            let content = read_limited(entry, MANIFEST_FILE, entry_limit)?;
+            serde_json::from_slice(&content)
+                .map_err(|e| NetworkError::ParseError(format!("Invalid bundle manifest: {}", e)))?
+        };
+
+        if manifest.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(NetworkError::ParseError(format!(
+                "Bundle format version {} is newer than supported version {}",
+                manifest.format_version, BUNDLE_FORMAT_VERSION
+            )));
+        }
+
+        let rules = manifest.files.iter().filter(|f| f.kind == BundleFileKind::Rule).count();
+        if rules != 1 {
+            return Err(NetworkError::ParseError(format!("Bundle must contain exactly one rule, found {}", rules)));
+        }
+
+        let mut contents = HashMap::new();
+        let mut total: u64 = 0;
+        for file in &manifest.files {
+            let valid_name = match file.kind {
+                BundleFileKind::Catalog => catalog_path(file.name()).as_deref() == Some(file.name()),
+                _ => is_safe_name(file.name()),
+            };
+            if !file.path.starts_with(&format!("{}/", file.kind.directory())) || !valid_name {
+                return Err(NetworkError::ParseError(format!("Invalid bundle file path: {}", file.path)));
+            }
+
+            let entry = archive.by_name(&file.path)
+                .map_err(|_| NetworkError::ParseError(format!("Bundle is missing file: {}", file.path)))?;
+            let content = read_limited(entry, &file.path, entry_limit.min(total_limit - total))?;
+            total += content.len() as u64;
+
+            if content.len() as u64 != file.size || content_hash_bytes(&content) != file.sha256 {
+                return Err(NetworkError::ParseError(format!("Checksum mismatch for bundle file: {}", file.path)));
+            }
+            contents.insert(file.path.clone(), content);
+        }
+
+        Ok(Self { manifest, contents })
+    }
+}
+
+/// Read an archive entry, failing once it grows past `limit` bytes
+fn read_limited<R: Read>(entry: R, path: &str, limit: u64) -> NetworkResult<Vec<u8>> {
+    let mut content = Vec::new();
+    entry.take(limit.saturating_add(1)).read_to_end(&mut content)
+        .map_err(|e| NetworkError::ParseError(format!("Failed to read {}: {}", path, e)))?;
+
+    if content.len() as u64 > limit {
+        return Err(NetworkError::ParseError(format!(
+            "Bundle file {} exceeds the size limit of {} bytes",
+            path, limit
+        )));
+    }
+    Ok(content)
+}
+
+/// How to resolve a clash with an existing protocol on import
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Import under a new name
+    Rename,
+    /// Replace the existing protocol (recorded as a new revision)
+    Replace,
+    /// Keep the existing protocol and import nothing
+    Skip,
+}
+
+/// What happened when a bundle was imported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BundleImportAction {
+    Imported,
+    Renamed,
+    Replaced,
+    Skipped,
+}
+
+/// Result of a bundle import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleImportResult {
+    /// ID of the imported (or existing) protocol
+    pub protocol_id: String,
+
+    /// Protocol name after import
+    pub name: String,
+
+    /// Action taken
+    pub action: BundleImportAction,
+
+    /// Whether the protocol is enabled
+    pub enabled: bool,
+
+    /// Non-fatal problems (e.g. kept an existing catalog)
+    pub warnings: Vec<String>,
+}
+
+/// Find `.proto` schema files referenced by rule content
+pub fn referenced_schemas(content: &str) -> Vec<String> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern = PATTERN.get_or_init(|| Regex::new(r#"[\w.-]+\.proto\b"#).unwrap());
+
+    let mut schemas: Vec<String> = pattern.find_iter(content)
+        .map(|m| m.as_str().to_string())
+        .collect();
+    schemas.sort();
+    schemas.dedup();
+    schemas
+}
+
+/// Normalize a catalog path relative to the rule directory
+///
+/// Paths are accepted like the rule loader does: only plain components and
+/// `.`, which are dropped. Returns `None` for anything that could leave the
+/// rule directory.
+pub fn catalog_path(file: &str) -> Option<String> {
+    let mut components = Vec::new();
+    for component in Path::new(file).components() {
+        match component {
+            Component::CurDir => {}
+            Component::Normal(name) => {
+                let name = name.to_str().filter(|name| is_safe_name(name))?;
+                components.push(name);
+            }
+            _ => return None,
+        }
+    }
+    if components.is_empty() {
+        return None;
+    }
+    Some(components.join("/"))
+}
+
+/// Check that a bundle file name has no directory components
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\', ':'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::repository::ValidationStatus;
+
+    fn metadata() -> ProtocolMetadata {
+        ProtocolMetadata {
+            id: "p1".to_string(),
+            name: "Meter".to_string(),
+            version: "1.0".to_string(),
+            author: "Test".to_string(),
+            description: String::new(),
+            category: "test".to_string(),
+            tags: vec!["energy".to_string()],
+            filename: "Meter.kkp.yaml".to_string(),
+            file_size: 0,
+            imported_at: Utc::now(),
+            modified_at: Utc::now(),
+            enabled: true,
+            validation_status: ValidationStatus::Valid,
+            revision: 1,
+            extra: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let mut bundle = ProtocolBundle::new(metadata());
+        bundle.add_file(BundleFileKind::Rule, "Meter.kkp.yaml", b"meta: {}".to_vec()).unwrap();
+        bundle.add_file(BundleFileKind::Catalog, "points.csv", b"id,name\n1,Flow\n".to_vec()).unwrap();
+        bundle.add_file(BundleFileKind::Sample, "capture.bin", vec![0x01, 0x02]).unwrap();
+        assert!(bundle.add_file(BundleFileKind::Rule, "Other.kkp.yaml", vec![]).is_err());
+        assert!(bundle.add_file(BundleFileKind::Sample, "../escape.bin", vec![]).is_err());
+
+        let bytes = bundle.to_bytes().unwrap();
+        let read = ProtocolBundle::read_from(std::io::Cursor::new(bytes)).unwrap();
+
+        assert!(read.metadata().enabled);
+        assert_eq!(read.rule().unwrap().1, "meta: {}");
+        let catalog = read.files_of(BundleFileKind::Catalog)[0];
+        assert_eq!(catalog.name(), "points.csv");
+        assert_eq!(read.content(catalog).unwrap(), b"id,name\n1,Flow\n");
+    }
+
+    #[test]
+    fn test_catalog_paths() {
+        assert_eq!(catalog_path("./tables/points.csv").as_deref(), Some("tables/points.csv"));
+        assert_eq!(catalog_path("points.csv").as_deref(), Some("points.csv"));
+        assert!(catalog_path("../points.csv").is_none());
+        assert!(catalog_path("tables/../../points.csv").is_none());
+        assert!(catalog_path("/etc/points.csv").is_none());
+        assert!(catalog_path(".").is_none());
+
+        let mut bundle = ProtocolBundle::new(metadata());
+        bundle.add_file(BundleFileKind::Rule, "Meter.kkp.yaml", b"meta: {}".to_vec()).unwrap();
+        bundle.add_file(BundleFileKind::Catalog, "./tables/points.csv", b"id\n1\n".to_vec()).unwrap();
+        assert!(bundle.add_file(BundleFileKind::Catalog, "../points.csv", vec![]).is_err());
+        assert!(bundle.add_file(BundleFileKind::Sample, "captures/frame.bin", vec![]).is_err());
+
+        let bytes = bundle.to_bytes().unwrap();
+        let read = ProtocolBundle::read_from(std::io::Cursor::new(bytes)).unwrap();
+        let catalog = read.files_of(BundleFileKind::Catalog)[0];
+        assert_eq!(catalog.path, "catalogs/tables/points.csv");
+        assert_eq!(catalog.name(), "tables/points.csv");
+
+        let mut tampered = read.clone();
+        let content = tampered.contents.remove("catalogs/tables/points.csv").unwrap();
+        tampered.contents.insert("catalogs/../points.csv".to_string(), content);
+        tampered.manifest.files[1].path = "catalogs/../points.csv".to_string();
+        let error = ProtocolBundle::read_from(std::io::Cursor::new(tampered.to_bytes().unwrap())).unwrap_err();
+        assert!(error.to_string().contains("Invalid bundle file path"));
+    }
+
+    #[test]
+    fn test_bundle_rejects_tampered_file() {
+        let mut bundle = ProtocolBundle::new(metadata());
+        bundle.add_file(BundleFileKind::Rule, "Meter.kkp.yaml", b"meta: {}".to_vec()).unwrap();
+        bundle.manifest.files[0].sha256 = "00".to_string();
+
+        let bytes = bundle.to_bytes().unwrap();
+        let error = ProtocolBundle::read_from(std::io::Cursor::new(bytes)).unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch"));
+    }
+
+    #[test]
+    fn test_bundle_size_limits() {
+        let mut bundle = ProtocolBundle::new(metadata());
+        bundle.add_file(BundleFileKind::Rule, "Meter.kkp.yaml", b"meta: {}".to_vec()).unwrap();
+        bundle.add_file(BundleFileKind::Sample, "big.bin", vec![0; 4096]).unwrap();
+        // A lying manifest size must not matter
+        bundle.manifest.files[1].size = 10;
+        let bytes = bundle.to_bytes().unwrap();
+
+        let error = ProtocolBundle::read_with_limits(std::io::Cursor::new(bytes.clone()), 1024, 1 << 20).unwrap_err();
+        assert!(error.to_string().contains("exceeds the size limit"));
+
+        let error = ProtocolBundle::read_with_limits(std::io::Cursor::new(bytes.clone()), 1 << 20, 2048).unwrap_err();
+        assert!(error.to_string().contains("exceeds the size limit"));
+
+        let error = ProtocolBundle::read_from(std::io::Cursor::new(bytes)).unwrap_err();
+        assert!(error.to_string().contains("Checksum mismatch"));
+    }
+
+    #[test]
+    fn test_referenced_schemas() {
+        let content = "codec:\n  schema: meter.proto\n  other: \"types.proto\"\n# meter.proto again\n";
+        assert_eq!(referenced_schemas(content), vec!["meter.proto", "types.proto"]);
+    }
+}
@@ -271,12 +271,17 @@ mod tests {
                 fixed_size: None,
                 escape_rules: vec![],
                 frame_validation: FrameValidation::default(),
+                block_crc: None,
             },
             fields: vec![],
             validation: ValidationRules::default(),
             conditions: vec![],
             functions: HashMap::new(),
             factor_codes: None,
+            catalogs: HashMap::new(),
+            reassembly: None,
+            envelope: None,
+            exports: ExportRules::default(),
         };
         
         CompiledRule {
@@ -0,0 +1,407 @@
+//! Lookup catalogs referenced from protocol rules
+//!
+//! A rule can declare inline tables or CSV files stored next to it in the
+//! repository, each keyed by one column. Fields reference them with
+//! `lookup(catalog, key).column` to add names, units, scales or locations
+//! to the parsed output.
+
+use crate::parser::schema::CatalogDefinition;
+use crate::types::{NetworkResult, NetworkError};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Component, Path};
+use std::sync::OnceLock;
+
+/// A single catalog row (column name -> value)
+pub type CatalogRow = HashMap<String, serde_json::Value>;
+
+/// Catalog loaded into memory and indexed by its key column
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    /// Catalog name as declared in the rule
+    name: String,
+
+    /// Key column name
+    key_column: String,
+
+    /// Rows indexed by key
+    rows: HashMap<String, CatalogRow>,
+}
+
+impl Catalog {
+    /// Build a catalog from its rule definition
+    pub fn from_definition(name: &str, definition: &CatalogDefinition) -> NetworkResult<Self> {
+        let mut rows = HashMap::new();
+
+        for (index, row) in definition.rows.iter().enumerate() {
+            let key = row.get(&definition.key)
+                .and_then(value_to_key)
+                .ok_or_else(|| NetworkError::ParseError(format!(
+                    "Catalog '{}' row {} has no value for key column '{}'",
+                    name, index + 1, definition.key
+                )))?;
+
+            if rows.insert(key.clone(), row.clone()).is_some() {
+                log::warn!("Duplicate key '{}' in catalog '{}', last row wins", key, name);
+            }
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            key_column: definition.key.clone(),
+            rows,
+        })
+    }
+
+    /// Get catalog name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get key column name
+    pub fn key_column(&self) -> &str {
+        &self.key_column
+    }
+
+    /// Get a row by key
+    pub fn get(&self, key: &str) -> Option<&CatalogRow> {
+        self.rows.get(key.trim())
+    }
+
+    /// Get row count
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Check if the catalog is empty
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+/// Set of catalogs available to a parser
+#[derive(Debug, Clone, Default)]
+pub struct CatalogSet {
+    catalogs: HashMap<String, Catalog>,
+}
+
+impl CatalogSet {
+    /// Create an empty catalog set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build catalogs from rule definitions
+    pub fn from_definitions(definitions: &HashMap<String, CatalogDefinition>) -> NetworkResult<Self> {
+        let mut set = Self::new();
+
+        for (name, definition) in definitions {
+            if definition.file.is_some() && definition.rows.is_empty() {
+                log::warn!("Catalog '{}' references a file that has not been loaded", name);
+            }
+            set.insert(Catalog::from_definition(name, definition)?);
+        }
+
+        Ok(set)
+    }
+
+    /// Add or replace a catalog
+    pub fn insert(&mut self, catalog: Catalog) {
+        self.catalogs.insert(catalog.name.clone(), catalog);
+    }
+
+    /// Get a catalog by name
+    pub fn get(&self, name: &str) -> Option<&Catalog> {
+        self.catalogs.get(name)
+    }
+
+    /// Look up a row in a catalog
+    pub fn lookup(&self, catalog: &str, key: &str) -> Option<&CatalogRow> {
+        self.catalogs.get(catalog).and_then(|c| c.get(key))
+    }
+
+    /// Evaluate a lookup expression
+    ///
+    /// `resolve_field` maps a field name to its parsed value as a string.
+    /// Returns `Ok(None)` when the key or column is not present in the catalog.
+    pub fn evaluate<F>(&self, expression: &LookupExpression, resolve_field: F) -> NetworkResult<Option<serde_json::Value>>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let catalog = self.catalogs.get(&expression.catalog)
+            .ok_or_else(|| NetworkError::ParseError(format!(
+                "Unknown catalog: {}",
+                expression.catalog
+            )))?;
+
+        let key = match &expression.key {
+            LookupKey::Literal(value) => value.clone(),
+            LookupKey::Field(field) => match resolve_field(field) {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+        };
+
+        let row = match catalog.get(&key) {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+
+        match &expression.column {
+            Some(column) => Ok(row.get(column).cloned()),
+            None => Ok(Some(serde_json::to_value(row)?)),
+        }
+    }
+
+    /// Get all catalog names
+    pub fn names(&self) -> Vec<String> {
+        self.catalogs.keys().cloned().collect()
+    }
+}
+
+/// Lookup key source
+#[derive(Debug, Clone, PartialEq)]
+pub enum LookupKey {
+    /// Value of a parsed field
+    Field(String),
+
+    /// Quoted literal key
+    Literal(String),
+}
+
+/// Parsed `lookup(catalog, key).column` expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookupExpression {
+    /// Catalog name
+    pub catalog: String,
+
+    /// Key source
+    pub key: LookupKey,
+
+    /// Column to return (the whole row when omitted)
+    pub column: Option<String>,
+}
+
+impl LookupExpression {
+    /// Parse a lookup expression
+    pub fn parse(expression: &str) -> NetworkResult<Self> {
+        static PATTERN: OnceLock<Regex> = OnceLock::new();
+        let pattern = PATTERN.get_or_init(|| {
+            Regex::new(
+                r#"^lookup\(\s*([A-Za-z_][\w-]*)\s*,\s*(?:'([^']*)'|"([^"]*)"|([A-Za-z_][\w.]*))\s*\)(?:\.([A-Za-z_]\w*))?$"#
+            ).unwrap()
+        });
+
+        let captures = pattern.captures(expression.trim())
+            .ok_or_else(|| NetworkError::ParseError(format!(
+                "Invalid lookup expression '{}', expected lookup(catalog, key).column",
+                expression
+            )))?;
+
+        let key = if let Some(literal) = captures.get(2).or_else(|| captures.get(3)) {
+            LookupKey::Literal(literal.as_str().to_string())
+        } else {
+            LookupKey::Field(captures[4].to_string())
+        };
+
+        Ok(Self {
+            catalog: captures[1].to_string(),
+            key,
+            column: captures.get(5).map(|m| m.as_str().to_string()),
+        })
+    }
+}
+
+/// Load CSV files referenced by catalog definitions into their rows
+///
+/// File paths are resolved relative to `base_dir` (the rule's directory) and
+/// may not leave it. Inline rows are kept and CSV rows are appended after them.
+pub fn load_catalog_files(catalogs: &mut HashMap<String, CatalogDefinition>, base_dir: &Path) -> NetworkResult<()> {
+    for (name, definition) in catalogs.iter_mut() {
+        if let Some(file) = &definition.file {
+            let relative = Path::new(file);
+            if !relative.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+                return Err(NetworkError::ParseError(format!(
+                    "Catalog '{}' file '{}' must be a relative path inside the rule directory",
+                    name, file
+                )));
+            }
+
+            let path = base_dir.join(relative);
+            let rows = read_csv_rows(&path, definition.delimiter, Some(&definition.key))
+                .map_err(|e| NetworkError::ParseError(format!(
+                    "Failed to load catalog '{}': {}",
+                    name, e
+                )))?;
+            definition.rows.extend(rows);
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a CSV file with a header row into catalog rows
+pub fn read_csv_rows(path: &Path, delimiter: char, key_column: Option<&str>) -> NetworkResult<Vec<CatalogRow>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| NetworkError::ParseError(format!("Failed to open {}: {}", path.display(), e)))?;
+    read_csv(file, delimiter, key_column)
+}
+
+/// Read CSV content with a header row into catalog rows
+///
+/// Cells of `key_column` stay strings, so zero-padded codes such as `007`
+/// keep their exact spelling.
+pub fn read_csv<R: std::io::Read>(reader: R, delimiter: char, key_column: Option<&str>) -> NetworkResult<Vec<CatalogRow>> {
+    if !delimiter.is_ascii() {
+        return Err(NetworkError::ParseError(format!(
+            "CSV delimiter must be an ASCII character, got '{}'",
+            delimiter
+        )));
+    }
+
+    let mut csv_reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter as u8)
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(reader);
+
+    let headers = csv_reader.headers()
+        .map_err(|e| NetworkError::ParseError(format!("Failed to read CSV header: {}", e)))?
+        .clone();
+
+    let mut rows = Vec::new();
+    for record in csv_reader.records() {
+        let record = record
+            .map_err(|e| NetworkError::ParseError(format!("Failed to read CSV record: {}", e)))?;
+
+        let row: CatalogRow = headers.iter()
+            .zip(record.iter())
+            .map(|(column, cell)| {
+                let value = if Some(column) == key_column {
+                    serde_json::Value::String(cell.to_string())
+                } else {
+                    csv_cell_value(cell)
+                };
+                (column.to_string(), value)
+            })
+            .collect();
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Convert a CSV cell to a JSON value, keeping numbers numeric
+fn csv_cell_value(cell: &str) -> serde_json::Value {
+    if cell.is_empty() {
+        return serde_json::Value::Null;
+    }
+
+    if let Ok(value) = cell.parse::<i64>() {
+        return serde_json::Value::from(value);
+    }
+
+    if let Ok(value) = cell.parse::<f64>() {
+        if value.is_finite() {
+            return serde_json::Value::from(value);
+        }
+    }
+
+    serde_json::Value::String(cell.to_string())
+}
+
+/// Convert a key column value to its lookup string
+fn value_to_key(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) => Some(s.trim().to_string()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn inline_definition() -> CatalogDefinition {
+        let rows = vec![
+            [("id", serde_json::json!(1)), ("name", serde_json::json!("Voltage")), ("unit", serde_json::json!("V"))],
+            [("id", serde_json::json!(2)), ("name", serde_json::json!("Current")), ("unit", serde_json::json!("A"))],
+        ];
+
+        CatalogDefinition {
+            key: "id".to_string(),
+            rows: rows.into_iter()
+                .map(|row| row.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+                .collect(),
+            file: None,
+            delimiter: ',',
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_lookup_expression() {
+        let expr = LookupExpression::parse("lookup(points, point_id).name").unwrap();
+        assert_eq!(expr.catalog, "points");
+        assert_eq!(expr.key, LookupKey::Field("point_id".to_string()));
+        assert_eq!(expr.column, Some("name".to_string()));
+
+        let expr = LookupExpression::parse("lookup(points, 'a01')").unwrap();
+        assert_eq!(expr.key, LookupKey::Literal("a01".to_string()));
+        assert_eq!(expr.column, None);
+
+        assert!(LookupExpression::parse("points.name").is_err());
+    }
+
+    #[test]
+    fn test_inline_catalog_lookup() {
+        let mut definitions = HashMap::new();
+        definitions.insert("points".to_string(), inline_definition());
+        let set = CatalogSet::from_definitions(&definitions).unwrap();
+
+        let expr = LookupExpression::parse("lookup(points, point_id).unit").unwrap();
+        let value = set.evaluate(&expr, |_| Some("2".to_string())).unwrap();
+        assert_eq!(value, Some(serde_json::json!("A")));
+
+        let missing = set.evaluate(&expr, |_| Some("9".to_string())).unwrap();
+        assert_eq!(missing, None);
+
+        let unknown = LookupExpression::parse("lookup(other, point_id).unit").unwrap();
+        assert!(set.evaluate(&unknown, |_| Some("1".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_load_csv_catalog_file() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("points.csv"),
+            "address,name,scale\n40001,Temperature,0.1\n40002, Pressure ,0.01\n007,Flow,1\n",
+        ).unwrap();
+
+        let mut definitions = HashMap::new();
+        definitions.insert("points".to_string(), CatalogDefinition {
+            key: "address".to_string(),
+            rows: Vec::new(),
+            file: Some("points.csv".to_string()),
+            delimiter: ',',
+            description: String::new(),
+        });
+
+        load_catalog_files(&mut definitions, temp_dir.path()).unwrap();
+        let set = CatalogSet::from_definitions(&definitions).unwrap();
+
+        let row = set.lookup("points", "40002").unwrap();
+        assert_eq!(row.get("name"), Some(&serde_json::json!("Pressure")));
+        assert_eq!(row.get("scale"), Some(&serde_json::json!(0.01)));
+        assert_eq!(set.lookup("points", "007").unwrap().get("name"), Some(&serde_json::json!("Flow")));
+        assert!(set.lookup("points", "7").is_none());
+
+        for file in ["../points.csv", "/etc/points.csv"] {
+            definitions.get_mut("points").unwrap().file = Some(file.to_string());
+            assert!(load_catalog_files(&mut definitions, temp_dir.path()).is_err());
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Byte checksums used by frame protocols
+//!
+//! The counterpart of the CRC validator for the simple checksums carried
+//! by protocols such as JT/T 808 (XOR) and DL/T 645 (modulo-256 sum).
+
+/// Modulo-256 sum
+pub fn sum8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// XOR of every byte
+pub fn xor8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |check, byte| check ^ byte)
+}
+
+/// Two's complement of the modulo-256 sum (Intel HEX, Modbus ASCII LRC)
+pub fn lrc8(data: &[u8]) -> u8 {
+    sum8(data).wrapping_neg()
+}
+
+/// Calculate a checksum by the algorithm name used in rule files
+pub fn calculate(algorithm: &str, data: &[u8]) -> Option<u64> {
+    match algorithm.to_ascii_lowercase().as_str() {
+        "sum" | "sum8" => Some(sum8(data) as u64),
+        "xor" | "xor8" => Some(xor8(data) as u64),
+        "lrc" | "lrc8" => Some(lrc8(data) as u64),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(sum8(&[0xFF, 0x02]), 0x01);
+        assert_eq!(xor8(&[0x0F, 0xF0, 0x01]), 0xFE);
+        assert_eq!(lrc8(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A]), 0xF2);
+        assert_eq!(calculate("SUM", &[1, 2]), Some(3));
+        assert_eq!(calculate("crc16", &[1, 2]), None);
+    }
+}
@@ -1,21 +1,242 @@
 //! CRC validation implementation
+//!
+//! Named CRC algorithms used by rule files, plus the block layout used by
+//! DNP3 where every data block is followed by its own CRC.
 
 use crate::parser::result::CrcValidationResult;
-use crate::types::NetworkResult;
+use crate::parser::schema::{BlockCrc, Endianness};
+use crate::types::{NetworkError, NetworkResult};
+use crc::{Algorithm, Crc, CRC_16_DNP, CRC_16_IBM_3740, CRC_16_KERMIT, CRC_16_MODBUS, CRC_16_XMODEM, CRC_32_ISO_HDLC, CRC_8_SMBUS};
+
+/// CRC algorithm resolved from its rule-file name
+#[derive(Debug, Clone, Copy)]
+pub enum CrcAlgorithm {
+    Crc8(&'static Algorithm<u8>),
+    Crc16(&'static Algorithm<u16>),
+    Crc32(&'static Algorithm<u32>),
+}
+
+impl CrcAlgorithm {
+    /// CRC-16/MODBUS, also used by Modbus RTU framing
+    pub const MODBUS: Self = Self::Crc16(&CRC_16_MODBUS);
+
+    /// Look up an algorithm by name (case-insensitive, separators ignored)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name: String = name.chars()
+            .filter(|c| *c != '-' && *c != '_' && *c != '/')
+            .collect::<String>()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "crc8" | "crc8smbus" => Some(Self::Crc8(&CRC_8_SMBUS)),
+            "crc16" | "crc16modbus" | "modbus" => Some(Self::MODBUS),
+            "crc16dnp" | "dnp" | "dnp3" => Some(Self::Crc16(&CRC_16_DNP)),
+            "crc16ccitt" | "crc16ccittfalse" | "crc16ibm3740" => Some(Self::Crc16(&CRC_16_IBM_3740)),
+            "crc16xmodem" | "xmodem" => Some(Self::Crc16(&CRC_16_XMODEM)),
+            "crc16kermit" | "kermit" => Some(Self::Crc16(&CRC_16_KERMIT)),
+            "crc32" => Some(Self::Crc32(&CRC_32_ISO_HDLC)),
+            _ => None,
+        }
+    }
+
+    /// CRC width in bytes
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Crc8(_) => 1,
+            Self::Crc16(_) => 2,
+            Self::Crc32(_) => 4,
+        }
+    }
+
+    /// Calculate the CRC of data
+    pub fn checksum(&self, data: &[u8]) -> u64 {
+        match self {
+            Self::Crc8(algorithm) => Crc::<u8>::new(algorithm).checksum(data) as u64,
+            Self::Crc16(algorithm) => Crc::<u16>::new(algorithm).checksum(data) as u64,
+            Self::Crc32(algorithm) => Crc::<u32>::new(algorithm).checksum(data) as u64,
+        }
+    }
+
+    /// Encode a CRC value in the given byte order
+    pub fn encode(&self, value: u64, endian: &Endianness) -> Vec<u8> {
+        let bytes = &value.to_le_bytes()[..self.width()];
+        match endian {
+            Endianness::Little => bytes.to_vec(),
+            Endianness::Big => bytes.iter().rev().copied().collect(),
+            Endianness::Native if cfg!(target_endian = "little") => bytes.to_vec(),
+            Endianness::Native => bytes.iter().rev().copied().collect(),
+        }
+    }
+
+    /// Decode a CRC value in the given byte order
+    pub fn decode(&self, bytes: &[u8], endian: &Endianness) -> u64 {
+        let little = match endian {
+            Endianness::Little => true,
+            Endianness::Big => false,
+            Endianness::Native => cfg!(target_endian = "little"),
+        };
+        let fold = |value: u64, byte: &u8| (value << 8) | *byte as u64;
+        if little {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        }
+    }
+}
+
+/// Calculate a CRC by the algorithm name used in rule files
+pub fn calculate(algorithm: &str, data: &[u8]) -> Option<u64> {
+    CrcAlgorithm::from_name(algorithm).map(|algorithm| algorithm.checksum(data))
+}
+
+fn resolve(algorithm: &str) -> NetworkResult<CrcAlgorithm> {
+    CrcAlgorithm::from_name(algorithm)
+        .ok_or_else(|| NetworkError::ParseError(format!("Unknown CRC algorithm: {}", algorithm)))
+}
+
+/// Frame with its block CRCs checked and removed
+#[derive(Debug, Clone)]
+pub struct BlockCrcResult {
+    /// Frame data without the CRCs
+    pub data: Vec<u8>,
+
+    /// One result per block
+    pub blocks: Vec<CrcValidationResult>,
+}
+
+impl BlockCrcResult {
+    /// Whether every block CRC matched
+    pub fn valid(&self) -> bool {
+        self.blocks.iter().all(|block| block.valid)
+    }
+}
+
+impl BlockCrc {
+    /// Block sizes (without CRCs) covering `data_length` bytes
+    fn block_sizes(&self, data_length: usize) -> Vec<usize> {
+        let mut sizes = Vec::new();
+        let mut remaining = data_length;
+        let mut size = self.first_block.unwrap_or(self.block_size);
+        while remaining > 0 && size > 0 {
+            sizes.push(size.min(remaining));
+            remaining -= size.min(remaining);
+            size = self.block_size;
+        }
+        sizes
+    }
+
+    /// Wire length of `data_length` bytes once block CRCs are inserted
+    pub fn wire_length(&self, data_length: usize) -> NetworkResult<usize> {
+        let width = resolve(&self.algorithm)?.width();
+        Ok(data_length + self.block_sizes(data_length).len() * width)
+    }
+
+    /// Insert a CRC after every block
+    pub fn append(&self, data: &[u8]) -> NetworkResult<Vec<u8>> {
+        let algorithm = resolve(&self.algorithm)?;
+        let mut output = Vec::with_capacity(self.wire_length(data.len())?);
+        let mut offset = 0;
+        for size in self.block_sizes(data.len()) {
+            let block = &data[offset..offset + size];
+            output.extend_from_slice(block);
+            output.extend(algorithm.encode(algorithm.checksum(block), &self.endian));
+            offset += size;
+        }
+        Ok(output)
+    }
+
+    /// Check and remove the CRC following every block
+    pub fn strip(&self, frame: &[u8]) -> NetworkResult<BlockCrcResult> {
+        let algorithm = resolve(&self.algorithm)?;
+        let width = algorithm.width();
+        let mut result = BlockCrcResult { data: Vec::with_capacity(frame.len()), blocks: Vec::new() };
+        let mut offset = 0;
+        let mut size = self.first_block.unwrap_or(self.block_size);
+        while offset < frame.len() {
+            let remaining = frame.len() - offset;
+            if remaining <= width || size == 0 {
+                return Err(NetworkError::ParseError(format!(
+                    "Truncated CRC block at offset {}", offset
+                )));
+            }
+            let block_length = size.min(remaining - width);
+            let block = &frame[offset..offset + block_length];
+            let expected = algorithm.decode(&frame[offset + block_length..offset + block_length + width], &self.endian);
+            let calculated = algorithm.checksum(block);
+            result.data.extend_from_slice(block);
+            result.blocks.push(CrcValidationResult {
+                algorithm: self.algorithm.clone(),
+                expected,
+                calculated,
+                valid: expected == calculated,
+                data_range: format!("{}-{}", offset, offset + block_length),
+            });
+            offset += block_length + width;
+            size = self.block_size;
+        }
+        Ok(result)
+    }
+}
 
 /// CRC validator
 pub struct CrcValidator;
 
 impl CrcValidator {
-    /// Validate CRC
-    pub fn validate_crc(_data: &[u8], _algorithm: &str) -> NetworkResult<CrcValidationResult> {
-        // TODO: Implement CRC validation
+    /// Validate data against an expected CRC
+    pub fn validate_crc(data: &[u8], algorithm: &str, expected: u64) -> NetworkResult<CrcValidationResult> {
+        let calculated = resolve(algorithm)?.checksum(data);
         Ok(CrcValidationResult {
-            algorithm: "CRC16".to_string(),
-            expected: 0,
-            calculated: 0,
-            valid: true,
-            data_range: "0-10".to_string(),
+            algorithm: algorithm.to_string(),
+            expected,
+            calculated,
+            valid: expected == calculated,
+            data_range: format!("0-{}", data.len()),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_named_algorithms() {
+        assert_eq!(calculate("crc16_modbus", &[0x01, 0x03, 0x00, 0x00, 0x00, 0x0A]), Some(0xCDC5));
+        assert_eq!(calculate("CRC16-DNP", b"123456789"), Some(0xEA82));
+        assert_eq!(calculate("crc32", b"123456789"), Some(0xCBF43926));
+        assert_eq!(calculate("md5", b"x"), None);
+        assert!(CrcValidator::validate_crc(b"123456789", "crc16_ccitt", 0x29B1).unwrap().valid);
+    }
+
+    #[test]
+    fn test_dnp3_block_crcs() {
+        let layout = BlockCrc {
+            algorithm: "crc16_dnp".to_string(),
+            block_size: 16,
+            first_block: Some(8),
+            endian: Endianness::Little,
+        };
+        // Link status request header from master 1 to outstation 10
+        let header = [0x05, 0x64, 0x05, 0xC9, 0x0A, 0x00, 0x01, 0x00];
+        let wire = layout.append(&header).unwrap();
+        assert_eq!(wire.len(), 10);
+
+        let mut data = header.to_vec();
+        data.extend(0..20u8);
+        let wire = layout.append(&data).unwrap();
+        assert_eq!(wire.len(), layout.wire_length(data.len()).unwrap());
+        assert_eq!(wire.len(), 8 + 2 + 16 + 2 + 4 + 2);
+
+        let stripped = layout.strip(&wire).unwrap();
+        assert!(stripped.valid());
+        assert_eq!(stripped.blocks.len(), 3);
+        assert_eq!(stripped.data, data);
+
+        let mut corrupted = wire.clone();
+        corrupted[12] ^= 0xFF;
+        let stripped = layout.strip(&corrupted).unwrap();
+        assert!(!stripped.valid());
+        assert!(!stripped.blocks[1].valid);
+        assert!(layout.strip(&wire[..wire.len() - 6]).is_ok());
+        assert!(layout.strip(&wire[..wire.len() - 5]).is_err());
+    }
+}
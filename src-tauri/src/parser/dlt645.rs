@@ -0,0 +1,696 @@
+//! DL/T 645-2007 electricity meter protocol engine
+//!
+//! Frames are `0x68` + address (6 BCD bytes, low byte first) + `0x68` +
+//! control + length + data + sum check + `0x16`, optionally preceded by
+//! `0xFE` wake-up bytes. Every data byte is sent with `0x33` added. Read
+//! responses are decoded through a table of common data identifiers (DI)
+//! into scaled values. `Dlt645Parser` exposes the engine as the built-in
+//! `dlt645` parser.
+
+use crate::parser::result::{
+    ChecksumValidationResult, ErrorSeverity, FieldValue, ParseError, ParseResult, ParseWarning,
+    ParsedField, ParsedFields, ProtocolInfo,
+};
+use crate::parser::validation_report::ValidationReport;
+use crate::parser::{bcd, checksum};
+use crate::parser::{Parser, ProtocolInfo as ParserProtocolInfo};
+use crate::types::{NetworkError, NetworkResult};
+use serde::{Deserialize, Serialize};
+
+/// ID of the built-in DL/T 645 parser
+pub const DLT645_PARSER_ID: &str = "dlt645";
+
+/// Frame start marker
+const START: u8 = 0x68;
+
+/// Frame end marker
+const END: u8 = 0x16;
+
+/// Wake-up byte sent before a frame
+const WAKE_UP: u8 = 0xFE;
+
+/// Offset added to every data byte on the wire
+const DATA_OFFSET: u8 = 0x33;
+
+/// Wake-up bytes sent before requests
+const PREAMBLE_LENGTH: usize = 4;
+
+/// Bytes around the data field: two starts, address, control, length, check, end
+const FRAME_OVERHEAD: usize = 12;
+
+/// Address every meter answers to
+pub const BROADCAST_ADDRESS: &str = "999999999999";
+
+/// Wildcard address, used to read the address of the only meter on a line
+pub const WILDCARD_ADDRESS: &str = "AAAAAAAAAAAA";
+
+/// Broadcast time synchronisation
+pub const FN_BROADCAST_TIME: u8 = 0x08;
+/// Read data
+pub const FN_READ_DATA: u8 = 0x11;
+/// Read follow-up data
+pub const FN_READ_FOLLOW_UP: u8 = 0x12;
+/// Read communication address
+pub const FN_READ_ADDRESS: u8 = 0x13;
+/// Write data
+pub const FN_WRITE_DATA: u8 = 0x14;
+
+/// Find the first complete frame, skipping wake-up bytes and noise
+///
+/// Returns the range of the frame from its first `0x68` to its `0x16`.
+pub fn find_frame(data: &[u8]) -> Option<(usize, usize)> {
+    (0..data.len()).find_map(|start| {
+        if data[start] != START || data.get(start + 7) != Some(&START) {
+            return None;
+        }
+        let end = start + FRAME_OVERHEAD + *data.get(start + 9)? as usize;
+        (data.get(end - 1) == Some(&END)).then_some((start, end))
+    })
+}
+
+/// Address bytes in wire order (low byte first)
+fn encode_address(address: &str) -> NetworkResult<Vec<u8>> {
+    let padded = format!("{:0>12}", address.to_ascii_uppercase());
+    if padded.len() != 12 || !padded.bytes().all(|digit| digit.is_ascii_digit() || digit == b'A') {
+        return Err(NetworkError::InvalidConfig(format!("Invalid DL/T 645 address '{}'", address)));
+    }
+
+    // The wildcard nibble A is not BCD, so decode the digits as hex
+    let mut bytes = hex::decode(&padded)
+        .map_err(|e| NetworkError::InvalidConfig(format!("Invalid DL/T 645 address '{}': {}", address, e)))?;
+    bytes.reverse();
+    Ok(bytes)
+}
+
+/// Reverse wire order bytes and show them as BCD digits
+fn decode_reversed(bytes: &[u8]) -> String {
+    let mut bytes = bytes.to_vec();
+    bytes.reverse();
+    bcd::to_digits(&bytes)
+}
+
+/// Build a frame with wake-up preamble, data offset and check
+pub fn encode_frame(address: &str, control: u8, data: &[u8]) -> NetworkResult<Vec<u8>> {
+    let length = u8::try_from(data.len())
+        .map_err(|_| NetworkError::ParseError(format!("DL/T 645 data field of {} bytes is too long", data.len())))?;
+
+    let mut frame = vec![START];
+    frame.extend(encode_address(address)?);
+    frame.push(START);
+    frame.push(control);
+    frame.push(length);
+    frame.extend(data.iter().map(|byte| byte.wrapping_add(DATA_OFFSET)));
+    frame.push(checksum::sum8(&frame));
+    frame.push(END);
+
+    let mut wire = vec![WAKE_UP; PREAMBLE_LENGTH];
+    wire.extend(frame);
+    Ok(wire)
+}
+
+/// Read data request for one DI
+pub fn read_request(address: &str, di: u32) -> NetworkResult<Vec<u8>> {
+    encode_frame(address, FN_READ_DATA, &di.to_le_bytes())
+}
+
+/// Read address request, answered by the only meter on the line
+pub fn read_address_request() -> NetworkResult<Vec<u8>> {
+    encode_frame(WILDCARD_ADDRESS, FN_READ_ADDRESS, &[])
+}
+
+/// DI as shown in the standard (`DI3 DI2 DI1 DI0`)
+pub fn format_di(di: u32) -> String {
+    format!("{:08X}", di)
+}
+
+/// Parse a DI written as eight hex digits
+pub fn parse_di(text: &str) -> NetworkResult<u32> {
+    let text = text.trim().trim_start_matches("0x").replace(['-', ' ', '_'], "");
+    if text.len() != 8 {
+        return Err(NetworkError::ParseError(format!("DI '{}' must be eight hex digits", text)));
+    }
+    u32::from_str_radix(&text, 16).map_err(|e| NetworkError::ParseError(format!("Invalid DI '{}': {}", text, e)))
+}
+
+/// Control code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dlt645Control {
+    pub raw: u8,
+    /// Sent by the meter
+    pub response: bool,
+    /// Meter reports an error
+    pub abnormal: bool,
+    /// More frames follow
+    pub follow_up: bool,
+    /// Function code (low five bits)
+    pub function: u8,
+}
+
+impl Dlt645Control {
+    pub fn from_raw(raw: u8) -> Self {
+        Self {
+            raw,
+            response: raw & 0x80 != 0,
+            abnormal: raw & 0x40 != 0,
+            follow_up: raw & 0x20 != 0,
+            function: raw & 0x1F,
+        }
+    }
+
+    /// Name of the function code
+    pub fn function_name(&self) -> Option<&'static str> {
+        function_name(self.function)
+    }
+}
+
+/// How the bytes of a DI are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiFormat {
+    /// Scaled BCD number
+    Number,
+    /// BCD digits kept as text (dates, addresses)
+    Digits,
+}
+
+/// Data identifier definition
+#[derive(Debug, Clone, Copy)]
+pub struct DiDefinition {
+    pub di: u32,
+    pub name: &'static str,
+    pub unit: Option<&'static str>,
+    /// Bytes per value
+    pub length: usize,
+    pub decimals: i32,
+    /// Highest bit of the most significant byte is the sign
+    pub signed: bool,
+    pub format: DiFormat,
+    /// Names of the values of a data block; empty for a single value
+    pub items: &'static [&'static str],
+}
+
+const fn number(di: u32, name: &'static str, unit: &'static str, length: usize, decimals: i32) -> DiDefinition {
+    DiDefinition { di, name, unit: Some(unit), length, decimals, signed: false, format: DiFormat::Number, items: &[] }
+}
+
+const fn signed(di: u32, name: &'static str, unit: &'static str, length: usize, decimals: i32) -> DiDefinition {
+    DiDefinition { signed: true, ..number(di, name, unit, length, decimals) }
+}
+
+const fn digits(di: u32, name: &'static str, length: usize) -> DiDefinition {
+    DiDefinition { di, name, unit: None, length, decimals: 0, signed: false, format: DiFormat::Digits, items: &[] }
+}
+
+const fn block(definition: DiDefinition, items: &'static [&'static str]) -> DiDefinition {
+    DiDefinition { items, ..definition }
+}
+
+const RATES: &[&str] = &["总", "费率1", "费率2", "费率3", "费率4"];
+const PHASES: &[&str] = &["A相", "B相", "C相"];
+const TOTAL_PHASES: &[&str] = &["总", "A相", "B相", "C相"];
+
+/// Common DL/T 645-2007 data identifiers
+const DI_TABLE: &[DiDefinition] = &[
+    // Energy
+    number(0x0000_0000, "组合有功总电能", "kWh", 4, 2),
+    number(0x0001_0000, "正向有功总电能", "kWh", 4, 2),
+    number(0x0002_0000, "反向有功总电能", "kWh", 4, 2),
+    number(0x0003_0000, "组合无功1总电能", "kvarh", 4, 2),
+    number(0x0004_0000, "组合无功2总电能", "kvarh", 4, 2),
+    block(number(0x0000_FF00, "组合有功电能数据块", "kWh", 4, 2), RATES),
+    block(number(0x0001_FF00, "正向有功电能数据块", "kWh", 4, 2), RATES),
+    block(number(0x0002_FF00, "反向有功电能数据块", "kWh", 4, 2), RATES),
+    // Instantaneous values
+    number(0x0201_0100, "A相电压", "V", 2, 1),
+    number(0x0201_0200, "B相电压", "V", 2, 1),
+    number(0x0201_0300, "C相电压", "V", 2, 1),
+    block(number(0x0201_FF00, "电压数据块", "V", 2, 1), PHASES),
+    signed(0x0202_0100, "A相电流", "A", 3, 3),
+    signed(0x0202_0200, "B相电流", "A", 3, 3),
+    signed(0x0202_0300, "C相电流", "A", 3, 3),
+    block(signed(0x0202_FF00, "电流数据块", "A", 3, 3), PHASES),
+    signed(0x0203_0000, "瞬时总有功功率", "kW", 3, 4),
+    signed(0x0203_0100, "瞬时A相有功功率", "kW", 3, 4),
+    signed(0x0203_0200, "瞬时B相有功功率", "kW", 3, 4),
+    signed(0x0203_0300, "瞬时C相有功功率", "kW", 3, 4),
+    block(signed(0x0203_FF00, "瞬时有功功率数据块", "kW", 3, 4), TOTAL_PHASES),
+    signed(0x0204_0000, "瞬时总无功功率", "kvar", 3, 4),
+    block(signed(0x0204_FF00, "瞬时无功功率数据块", "kvar", 3, 4), TOTAL_PHASES),
+    signed(0x0205_0000, "瞬时总视在功率", "kVA", 3, 4),
+    block(signed(0x0205_FF00, "瞬时视在功率数据块", "kVA", 3, 4), TOTAL_PHASES),
+    DiDefinition { unit: None, ..signed(0x0206_0000, "总功率因数", "", 2, 3) },
+    block(DiDefinition { unit: None, ..signed(0x0206_FF00, "功率因数数据块", "", 2, 3) }, TOTAL_PHASES),
+    signed(0x0280_0001, "零线电流", "A", 3, 3),
+    number(0x0280_0002, "电网频率", "Hz", 2, 2),
+    signed(0x0280_0004, "当前有功需量", "kW", 3, 4),
+    signed(0x0280_0007, "表内温度", "℃", 2, 1),
+    // Parameters
+    digits(0x0400_0101, "日期及星期", 4),
+    digits(0x0400_0102, "时间", 3),
+    digits(0x0400_0401, "通信地址", 6),
+    digits(0x0400_0402, "表号", 6),
+];
+
+/// Definition of a DI
+pub fn di_definition(di: u32) -> Option<&'static DiDefinition> {
+    DI_TABLE.iter().find(|definition| definition.di == di)
+}
+
+/// All known DIs
+pub fn di_definitions() -> &'static [DiDefinition] {
+    DI_TABLE
+}
+
+/// One decoded value of a read response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dlt645Value {
+    pub name: String,
+    /// Scaled number
+    pub value: Option<f64>,
+    /// Digits of non-numeric or undecodable values
+    pub text: Option<String>,
+    pub unit: Option<String>,
+    /// Position in the received data
+    pub offset: usize,
+    pub length: usize,
+}
+
+/// Decode the values of a read response after the DI
+fn decode_values(definition: Option<&DiDefinition>, data: &[u8], offset: usize) -> Vec<Dlt645Value> {
+    let Some(definition) = definition.filter(|definition| definition.length > 0) else {
+        return vec![Dlt645Value {
+            name: "data".to_string(),
+            value: None,
+            text: Some(decode_reversed(data)),
+            unit: None,
+            offset,
+            length: data.len(),
+        }];
+    };
+
+    data.chunks(definition.length)
+        .enumerate()
+        .map(|(index, bytes)| {
+            let name = match (definition.items.get(index), definition.items.is_empty()) {
+                (Some(item), _) => format!("{}.{}", definition.name, item),
+                (None, true) if index == 0 => definition.name.to_string(),
+                (None, _) => format!("{}[{}]", definition.name, index),
+            };
+            let (value, text) = decode_item(definition, bytes);
+            Dlt645Value {
+                name,
+                value,
+                text,
+                unit: definition.unit.map(str::to_string),
+                offset: offset + index * definition.length,
+                length: bytes.len(),
+            }
+        })
+        .collect()
+}
+
+/// Decode one value from its wire bytes
+fn decode_item(definition: &DiDefinition, bytes: &[u8]) -> (Option<f64>, Option<String>) {
+    let mut digits: Vec<u8> = bytes.iter().rev().copied().collect();
+    if definition.format == DiFormat::Digits || bytes.len() != definition.length {
+        return (None, Some(bcd::to_digits(&digits)));
+    }
+
+    let negative = definition.signed && digits[0] & 0x80 != 0;
+    if definition.signed {
+        digits[0] &= 0x7F;
+    }
+    match bcd::decode(&digits) {
+        Ok(magnitude) => {
+            let value = magnitude as f64 / 10f64.powi(definition.decimals);
+            (Some(if negative { -value } else { value }), None)
+        }
+        Err(_) => (None, Some(decode_reversed(bytes))),
+    }
+}
+
+/// Meaning of the bits of an abnormal response error byte
+fn error_names(error: u8) -> Vec<String> {
+    const ERRORS: [&str; 7] = [
+        "其他错误",
+        "无请求数据",
+        "密码错/未授权",
+        "通信速率不能更改",
+        "年时区数超",
+        "日时段数超",
+        "费率数超",
+    ];
+    ERRORS.iter()
+        .enumerate()
+        .filter(|(bit, _)| error & (1 << bit) != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+/// Decoded data field
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Dlt645Body {
+    /// Read data request
+    ReadRequest { di: String },
+    /// Read data response
+    ReadResponse { di: String, name: Option<String>, values: Vec<Dlt645Value> },
+    /// Read address request
+    ReadAddress,
+    /// Read address response
+    Address { address: String },
+    /// Abnormal response with its error bits
+    Abnormal { error: u8, errors: Vec<String> },
+    /// Anything else, data field without offset as hex
+    Raw { data: String },
+}
+
+/// Decoded DL/T 645 frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dlt645Frame {
+    /// Bytes before the first `0x68`
+    pub preamble: usize,
+    /// Meter address, twelve digits
+    pub address: String,
+    pub control: Dlt645Control,
+    pub data_length: u8,
+    pub body: Dlt645Body,
+    pub checksum: u8,
+    pub calculated_checksum: u8,
+    /// Problems found while decoding
+    #[serde(default)]
+    pub issues: Vec<String>,
+}
+
+impl Dlt645Frame {
+    /// Decode the first frame in the data
+    pub fn decode(data: &[u8]) -> NetworkResult<Self> {
+        let (start, end) = find_frame(data)
+            .ok_or_else(|| NetworkError::ParseError("No complete DL/T 645 frame found".to_string()))?;
+        let frame = &data[start..end];
+
+        let mut issues = Vec::new();
+        if data[..start].iter().any(|&byte| byte != WAKE_UP) {
+            issues.push(format!("{} bytes before the frame are not wake-up bytes", start));
+        }
+        if end < data.len() {
+            issues.push(format!("{} bytes after the frame were ignored", data.len() - end));
+        }
+
+        let control = Dlt645Control::from_raw(frame[8]);
+        let data_length = frame[9];
+        let payload: Vec<u8> = frame[10..end - start - 2].iter().map(|byte| byte.wrapping_sub(DATA_OFFSET)).collect();
+        let payload_offset = start + 10;
+
+        let body = match (control.response, control.abnormal, control.function) {
+            (true, true, _) => {
+                let error = payload.first().copied().unwrap_or_default();
+                Dlt645Body::Abnormal { error, errors: error_names(error) }
+            }
+            (false, _, FN_READ_DATA) if payload.len() >= 4 => Dlt645Body::ReadRequest {
+                di: format_di(u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]])),
+            },
+            (true, false, FN_READ_DATA | FN_READ_FOLLOW_UP) if payload.len() >= 4 => {
+                let di = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+                let definition = di_definition(di);
+                // Follow-up frames end with a sequence byte
+                let values_end = if control.function == FN_READ_FOLLOW_UP { payload.len() - 1 } else { payload.len() };
+                let values = payload.get(4..values_end).unwrap_or_default();
+                if let Some(definition) = definition {
+                    if definition.length > 0 && values.len() % definition.length != 0 {
+                        issues.push(format!(
+                            "{} bytes of data for {} are not a multiple of {}",
+                            values.len(), definition.name, definition.length
+                        ));
+                    }
+                }
+                Dlt645Body::ReadResponse {
+                    di: format_di(di),
+                    name: definition.map(|definition| definition.name.to_string()),
+                    values: decode_values(definition, values, payload_offset + 4),
+                }
+            }
+            (false, _, FN_READ_ADDRESS) => Dlt645Body::ReadAddress,
+            (true, false, FN_READ_ADDRESS) if payload.len() == 6 => Dlt645Body::Address {
+                address: decode_reversed(&payload),
+            },
+            _ => Dlt645Body::Raw { data: hex::encode_upper(&payload) },
+        };
+
+        Ok(Self {
+            preamble: start,
+            address: decode_reversed(&frame[1..7]),
+            control,
+            data_length,
+            body,
+            checksum: frame[end - start - 2],
+            calculated_checksum: checksum::sum8(&frame[..end - start - 2]),
+            issues,
+        })
+    }
+
+    /// Sum check matches
+    pub fn checksum_valid(&self) -> bool {
+        self.checksum == self.calculated_checksum
+    }
+
+    /// Response from the meter at an address, to a request with the function
+    ///
+    /// Requests sent to the wildcard or broadcast address accept any meter.
+    pub fn answers(&self, address: &str, function: u8) -> bool {
+        let address = format!("{:0>12}", address.to_ascii_uppercase());
+        self.control.response
+            && self.control.function == function
+            && (address == WILDCARD_ADDRESS || address == BROADCAST_ADDRESS || address == self.address)
+    }
+
+    /// Convert to a generic parse result
+    pub fn to_parse_result(&self, raw: &[u8]) -> ParseResult {
+        let start = self.preamble;
+        let mut fields = ParsedFields::new();
+        let mut add = |name: &str, value: FieldValue, field_type: &str, range: Option<(usize, usize)>, description: &str| {
+            let (offset, length) = range.unwrap_or((0, 0));
+            let raw_bytes = raw.get(offset..offset + length).unwrap_or(&[]);
+            fields.add_field(
+                name.to_string(),
+                ParsedField::new(name, value, field_type, offset, raw_bytes).with_description(description),
+            );
+        };
+
+        add("preamble", FieldValue::UInt(self.preamble as u64), "uint", Some((0, start)), "唤醒前导字节数");
+        add("address", FieldValue::String(self.address.clone()), "bcd", Some((start + 1, 6)), "通信地址");
+        add("control", FieldValue::UInt(self.control.raw as u64), "uint", Some((start + 8, 1)), "控制码");
+        add("function", FieldValue::UInt(self.control.function as u64), "uint", None, self.control.function_name().unwrap_or_default());
+        add("response", FieldValue::Bool(self.control.response), "bool", None, "从站应答");
+        add("abnormal", FieldValue::Bool(self.control.abnormal), "bool", None, "异常应答");
+        add("follow_up", FieldValue::Bool(self.control.follow_up), "bool", None, "有后续帧");
+        add("data_length", FieldValue::UInt(self.data_length as u64), "uint", Some((start + 9, 1)), "数据域长度");
+
+        let di_range = Some((start + 10, 4));
+        match &self.body {
+            Dlt645Body::ReadRequest { di } => {
+                add("di", FieldValue::String(di.clone()), "hex", di_range, "数据标识");
+            }
+            Dlt645Body::ReadResponse { di, name, values } => {
+                add("di", FieldValue::String(di.clone()), "hex", di_range, name.as_deref().unwrap_or("数据标识"));
+                for (index, value) in values.iter().enumerate() {
+                    let field_name = if values.len() == 1 { "value".to_string() } else { format!("value[{}]", index) };
+                    let field_value = match (value.value, &value.text) {
+                        (Some(number), _) => FieldValue::Float(number),
+                        (None, Some(text)) => FieldValue::String(text.clone()),
+                        (None, None) => continue,
+                    };
+                    let description = match &value.unit {
+                        Some(unit) => format!("{} ({})", value.name, unit),
+                        None => value.name.clone(),
+                    };
+                    add(&field_name, field_value, "bcd", Some((value.offset, value.length)), &description);
+                }
+            }
+            Dlt645Body::ReadAddress => {}
+            Dlt645Body::Address { address } => {
+                add("meter_address", FieldValue::String(address.clone()), "bcd", Some((start + 10, 6)), "通信地址");
+            }
+            Dlt645Body::Abnormal { error, errors } => {
+                add("error", FieldValue::UInt(*error as u64), "uint", Some((start + 10, 1)), &errors.join(", "));
+            }
+            Dlt645Body::Raw { data } => {
+                add("data", FieldValue::String(data.clone()), "hex", Some((start + 10, self.data_length as usize)), "数据域");
+            }
+        }
+
+        let mut result = ParseResult::success(dlt645_protocol_info(1.0), fields, raw.to_vec(), raw.len());
+
+        result.validation.checksum_results.push(ChecksumValidationResult {
+            algorithm: "SUM8".to_string(),
+            expected: self.checksum as u64,
+            calculated: self.calculated_checksum as u64,
+            valid: self.checksum_valid(),
+            data_range: "first 0x68 to end of data".to_string(),
+        });
+        if !self.checksum_valid() {
+            result.add_error(ParseError::new(
+                "CHECKSUM_MISMATCH",
+                format!("Check sum {:02X} does not match calculated {:02X}", self.checksum, self.calculated_checksum),
+                ErrorSeverity::Error,
+            ));
+        }
+        if let Dlt645Body::Abnormal { errors, .. } = &self.body {
+            result.add_warning(ParseWarning::new("DLT645_ABNORMAL", format!("Meter reported: {}", errors.join(", "))));
+        }
+        for issue in &self.issues {
+            result.add_warning(ParseWarning::new("DLT645_DECODE", issue.clone()));
+        }
+
+        if let Ok(frame) = serde_json::to_value(self) {
+            result.metadata.extra.insert("dlt645".to_string(), frame);
+        }
+
+        result
+    }
+}
+
+/// Name of a function code
+pub fn function_name(function: u8) -> Option<&'static str> {
+    Some(match function {
+        FN_BROADCAST_TIME => "广播校时",
+        FN_READ_DATA => "读数据",
+        FN_READ_FOLLOW_UP => "读后续数据",
+        FN_READ_ADDRESS => "读通信地址",
+        FN_WRITE_DATA => "写数据",
+        0x15 => "写通信地址",
+        0x16 => "冻结命令",
+        0x17 => "更改通信速率",
+        0x18 => "修改密码",
+        0x19 => "最大需量清零",
+        0x1A => "电表清零",
+        0x1B => "事件清零",
+        _ => return None,
+    })
+}
+
+/// Protocol information for DL/T 645 parse results
+fn dlt645_protocol_info(confidence: f64) -> ProtocolInfo {
+    ProtocolInfo {
+        name: "DL/T 645".to_string(),
+        version: "2007".to_string(),
+        parser_id: DLT645_PARSER_ID.to_string(),
+        confidence,
+    }
+}
+
+/// Built-in DL/T 645 parser
+#[derive(Debug, Clone, Default)]
+pub struct Dlt645Parser;
+
+impl Dlt645Parser {
+    /// Create the parser
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Parser for Dlt645Parser {
+    fn parse(&self, data: &[u8]) -> NetworkResult<ParseResult> {
+        match Dlt645Frame::decode(data) {
+            Ok(frame) => Ok(frame.to_parse_result(data)),
+            Err(e) => Ok(ParseResult::failure(
+                dlt645_protocol_info(0.0),
+                data.to_vec(),
+                ParseError::new("DLT645_DECODE", e.to_string(), ErrorSeverity::Critical),
+            )),
+        }
+    }
+
+    fn validate(&self, result: &ParseResult) -> ValidationReport {
+        ValidationReport::from_parse_result(result)
+    }
+
+    fn get_protocol_info(&self) -> ParserProtocolInfo {
+        ParserProtocolInfo {
+            name: "DL/T 645".to_string(),
+            version: "2007".to_string(),
+            author: "DL/T 645-2007".to_string(),
+            description: "Multi-function watt-hour meter communication protocol".to_string(),
+            supported_formats: vec!["binary".to_string()],
+            magic_bytes: Some(vec![START]),
+            min_frame_size: Some(FRAME_OVERHEAD),
+            max_frame_size: Some(PREAMBLE_LENGTH + FRAME_OVERHEAD + u8::MAX as usize),
+        }
+    }
+
+    fn get_id(&self) -> &str {
+        DLT645_PARSER_ID
+    }
+
+    fn can_parse(&self, data: &[u8]) -> bool {
+        let start = data.iter().take_while(|&&byte| byte == WAKE_UP).count();
+        find_frame(data).is_some_and(|(frame_start, _)| frame_start == start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Meter answer with `0x33` added and check appended
+    fn response(address: &str, control: u8, data: &[u8]) -> Vec<u8> {
+        encode_frame(address, control, data).unwrap()
+    }
+
+    #[test]
+    fn test_read_request_frame() {
+        let frame = read_request("123456789012", 0x0201_0100).unwrap();
+        assert_eq!(hex::encode_upper(&frame), "FEFEFEFE68129078563412681104333434356B16");
+
+        let decoded = Dlt645Frame::decode(&frame).unwrap();
+        assert_eq!(decoded.preamble, 4);
+        assert_eq!(decoded.address, "123456789012");
+        assert_eq!(decoded.body, Dlt645Body::ReadRequest { di: "02010100".to_string() });
+        assert!(decoded.checksum_valid());
+    }
+
+    #[test]
+    fn test_decode_read_responses() {
+        // Voltage block: 220.1 V, 221.0 V, 219.9 V
+        let mut data = 0x0201_FF00u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&[0x01, 0x22, 0x10, 0x22, 0x99, 0x21]);
+        let frame = Dlt645Frame::decode(&response("123456789012", 0x91, &data)).unwrap();
+        assert!(frame.checksum_valid() && frame.answers("123456789012", FN_READ_DATA));
+        let Dlt645Body::ReadResponse { values, .. } = &frame.body else {
+            panic!("expected a read response, got {:?}", frame.body);
+        };
+        assert_eq!(values.iter().map(|value| value.value.unwrap()).collect::<Vec<_>>(), vec![220.1, 221.0, 219.9]);
+        assert_eq!(values[1].name, "电压数据块.B相");
+
+        // Negative current: -1.234 A
+        let mut data = 0x0202_0100u32.to_le_bytes().to_vec();
+        data.extend_from_slice(&[0x34, 0x12, 0x80]);
+        let raw = response("123456789012", 0x91, &data);
+        let result = Dlt645Parser::new().parse(&raw).unwrap();
+        assert!(result.success);
+        let field = result.fields.get_field("value").unwrap();
+        assert_eq!(field.value, FieldValue::Float(-1.234));
+        assert_eq!(field.raw_bytes, vec![0x34 + 0x33, 0x12 + 0x33, 0x80 + 0x33]);
+
+        // Abnormal answer: no requested data
+        let frame = Dlt645Frame::decode(&response("123456789012", 0xD1, &[0x02])).unwrap();
+        assert_eq!(frame.body, Dlt645Body::Abnormal { error: 2, errors: vec!["无请求数据".to_string()] });
+    }
+
+    #[test]
+    fn test_framing_noise_and_bad_check() {
+        let mut raw = vec![0x00, 0xFE];
+        raw.extend(response("000000000001", 0x93, &[0x01, 0x00, 0x00, 0x00, 0x00, 0x00]));
+        assert_eq!(find_frame(&raw).map(|(start, _)| start), Some(6));
+        let frame = Dlt645Frame::decode(&raw).unwrap();
+        assert_eq!(frame.body, Dlt645Body::Address { address: "000000000001".to_string() });
+        assert!(frame.answers(WILDCARD_ADDRESS, FN_READ_ADDRESS));
+        assert_eq!(frame.issues.len(), 1);
+
+        let check = raw.len() - 2;
+        raw[check] = raw[check].wrapping_add(1);
+        let result = Dlt645Parser::new().parse(&raw).unwrap();
+        assert!(result.errors.iter().any(|e| e.code == "CHECKSUM_MISMATCH"));
+        assert!(find_frame(&raw[..raw.len() - 1]).is_none());
+    }
+}
@@ -0,0 +1,1231 @@
+//! DNP3 (IEEE 1815) protocol engine
+//!
+//! A link frame is `05 64` + length + control + destination + source (all
+//! little-endian) followed by a CRC, then the user data in blocks of up to
+//! 16 octets, each with its own CRC-16/DNP. The first user data octet is the
+//! transport header (FIN, FIR and a six-bit sequence) that splits application
+//! fragments into segments of up to 249 octets. Application fragments carry
+//! a control octet, a function code, the internal indications (responses
+//! only) and object headers. Binary inputs (groups 1 and 2), counters
+//! (groups 20 and 22), analog inputs (groups 30 and 32) and class data
+//! (group 60) are decoded into typed values. `Dnp3Parser` exposes the engine
+//! as the built-in `dnp3` parser.
+
+use crate::parser::framing::FrameDetector;
+use crate::parser::result::{
+    ErrorSeverity, FieldValue, ParseError, ParseResult, ParseWarning, ParsedField, ParsedFields,
+    ProtocolInfo,
+};
+use crate::parser::schema::{BlockCrc, Endianness, FrameValidation, FramingRule, LengthEncoding, LengthField};
+use crate::parser::validation_report::ValidationReport;
+use crate::parser::{Parser, ProtocolInfo as ParserProtocolInfo};
+use crate::types::{NetworkError, NetworkResult};
+use serde::{Deserialize, Serialize};
+
+/// ID of the built-in DNP3 parser
+pub const DNP3_PARSER_ID: &str = "dnp3";
+
+/// Link frame start octets
+pub const START: [u8; 2] = [0x05, 0x64];
+
+/// Start, length, control, destination and source
+pub const LINK_HEADER_LENGTH: usize = 8;
+
+/// Smallest link frame: the header and its CRC
+pub const MIN_FRAME_LENGTH: usize = LINK_HEADER_LENGTH + 2;
+
+/// Most user data octets in one link frame
+pub const MAX_USER_DATA: usize = 250;
+
+/// Largest link frame with all block CRCs
+pub const MAX_FRAME_LENGTH: usize = 292;
+
+/// Most application octets in one transport segment
+pub const MAX_SEGMENT_DATA: usize = MAX_USER_DATA - 1;
+
+/// Transport sequence numbers count modulo 64
+pub const TRANSPORT_SEQUENCE_MODULO: u8 = 64;
+
+/// Application sequence numbers count modulo 16
+pub const APP_SEQUENCE_MODULO: u8 = 16;
+
+/// Control octet: direction (set on frames from the master)
+pub const LINK_DIR: u8 = 0x80;
+/// Control octet: primary (initiating) station
+pub const LINK_PRM: u8 = 0x40;
+/// Control octet: frame count bit
+pub const LINK_FCB: u8 = 0x20;
+/// Control octet: frame count valid (primary) or data flow control (secondary)
+pub const LINK_FCV: u8 = 0x10;
+
+/// Primary function: reset link states
+pub const LINK_RESET_LINK_STATES: u8 = 0;
+/// Primary function: test link states
+pub const LINK_TEST_LINK_STATES: u8 = 2;
+/// Primary function: confirmed user data
+pub const LINK_CONFIRMED_USER_DATA: u8 = 3;
+/// Primary function: unconfirmed user data
+pub const LINK_UNCONFIRMED_USER_DATA: u8 = 4;
+/// Primary function: request link status
+pub const LINK_REQUEST_LINK_STATUS: u8 = 9;
+/// Secondary function: positive acknowledgement
+pub const LINK_ACK: u8 = 0;
+/// Secondary function: negative acknowledgement
+pub const LINK_NACK: u8 = 1;
+/// Secondary function: link status
+pub const LINK_STATUS: u8 = 11;
+/// Secondary function: link service not supported
+pub const LINK_NOT_SUPPORTED: u8 = 15;
+
+/// Application function: confirm
+pub const FC_CONFIRM: u8 = 0;
+/// Application function: read
+pub const FC_READ: u8 = 1;
+/// Application function: write
+pub const FC_WRITE: u8 = 2;
+/// Application function: select
+pub const FC_SELECT: u8 = 3;
+/// Application function: operate
+pub const FC_OPERATE: u8 = 4;
+/// Application function: direct operate
+pub const FC_DIRECT_OPERATE: u8 = 5;
+/// Application function: cold restart
+pub const FC_COLD_RESTART: u8 = 13;
+/// Application function: enable unsolicited responses
+pub const FC_ENABLE_UNSOLICITED: u8 = 20;
+/// Application function: disable unsolicited responses
+pub const FC_DISABLE_UNSOLICITED: u8 = 21;
+/// Application function: solicited response
+pub const FC_RESPONSE: u8 = 129;
+/// Application function: unsolicited response
+pub const FC_UNSOLICITED_RESPONSE: u8 = 130;
+
+/// IIN1.0 all stations (broadcast received)
+pub const IIN_BROADCAST: u16 = 0x0100;
+/// IIN1.1 class 1 events available
+pub const IIN_CLASS1_EVENTS: u16 = 0x0200;
+/// IIN1.2 class 2 events available
+pub const IIN_CLASS2_EVENTS: u16 = 0x0400;
+/// IIN1.3 class 3 events available
+pub const IIN_CLASS3_EVENTS: u16 = 0x0800;
+/// IIN1.4 time synchronisation required
+pub const IIN_NEED_TIME: u16 = 0x1000;
+/// IIN1.5 some outputs in local mode
+pub const IIN_LOCAL_CONTROL: u16 = 0x2000;
+/// IIN1.6 device trouble
+pub const IIN_DEVICE_TROUBLE: u16 = 0x4000;
+/// IIN1.7 device restart
+pub const IIN_DEVICE_RESTART: u16 = 0x8000;
+/// IIN2.0 function code not supported
+pub const IIN_NO_FUNC_CODE_SUPPORT: u16 = 0x0001;
+/// IIN2.1 requested object unknown
+pub const IIN_OBJECT_UNKNOWN: u16 = 0x0002;
+/// IIN2.2 parameter error
+pub const IIN_PARAMETER_ERROR: u16 = 0x0004;
+/// IIN2.3 event buffer overflow
+pub const IIN_EVENT_BUFFER_OVERFLOW: u16 = 0x0008;
+
+/// Qualifier: one-octet start and stop index
+pub const QUALIFIER_RANGE_8: u8 = 0x00;
+/// Qualifier: two-octet start and stop index
+pub const QUALIFIER_RANGE_16: u8 = 0x01;
+/// Qualifier: all objects, no range
+pub const QUALIFIER_ALL: u8 = 0x06;
+/// Qualifier: one-octet count
+pub const QUALIFIER_COUNT_8: u8 = 0x07;
+/// Qualifier: two-octet count
+pub const QUALIFIER_COUNT_16: u8 = 0x08;
+/// Qualifier: one-octet count, one-octet index before every object
+pub const QUALIFIER_INDEX_8: u8 = 0x17;
+/// Qualifier: two-octet count, two-octet index before every object
+pub const QUALIFIER_INDEX_16: u8 = 0x28;
+
+/// Object flag: online
+pub const FLAG_ONLINE: u8 = 0x01;
+/// Object flag: restart
+pub const FLAG_RESTART: u8 = 0x02;
+/// Object flag: communication lost
+pub const FLAG_COMM_LOST: u8 = 0x04;
+/// Object flag: remote forced
+pub const FLAG_REMOTE_FORCED: u8 = 0x08;
+/// Object flag: local forced
+pub const FLAG_LOCAL_FORCED: u8 = 0x10;
+/// Binary flag: current state
+pub const FLAG_STATE: u8 = 0x80;
+
+/// Data octets per link user data block
+const BLOCK_SIZE: usize = 16;
+
+/// DNP3 absolute time: milliseconds since 1970 in six octets
+const TIME_LENGTH: usize = 6;
+
+/// Per-block CRC layout of link frames
+pub fn block_layout() -> BlockCrc {
+    BlockCrc {
+        algorithm: "crc16_dnp".to_string(),
+        block_size: BLOCK_SIZE,
+        first_block: Some(LINK_HEADER_LENGTH),
+        endian: Endianness::Little,
+    }
+}
+
+/// Link layer header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkHeader {
+    /// Set on frames sent by the master
+    pub dir: bool,
+    /// Set on frames from the initiating station
+    pub prm: bool,
+    pub fcb: bool,
+    /// Frame count valid (primary) or data flow control (secondary)
+    pub fcv: bool,
+    pub function: u8,
+    pub destination: u16,
+    pub source: u16,
+}
+
+impl LinkHeader {
+    /// Header of a frame initiating a link transaction
+    pub fn primary(function: u8, from_master: bool, destination: u16, source: u16) -> Self {
+        Self { dir: from_master, prm: true, fcb: false, fcv: false, function, destination, source }
+    }
+
+    /// Header of a frame answering a primary frame
+    pub fn secondary(function: u8, from_master: bool, destination: u16, source: u16) -> Self {
+        Self { dir: from_master, prm: false, fcb: false, fcv: false, function, destination, source }
+    }
+
+    /// Control octet
+    pub fn control(&self) -> u8 {
+        let mut control = self.function & 0x0F;
+        for (set, bit) in [(self.dir, LINK_DIR), (self.prm, LINK_PRM), (self.fcb, LINK_FCB), (self.fcv, LINK_FCV)] {
+            if set {
+                control |= bit;
+            }
+        }
+        control
+    }
+
+    fn from_control(control: u8, destination: u16, source: u16) -> Self {
+        Self {
+            dir: control & LINK_DIR != 0,
+            prm: control & LINK_PRM != 0,
+            fcb: control & LINK_FCB != 0,
+            fcv: control & LINK_FCV != 0,
+            function: control & 0x0F,
+            destination,
+            source,
+        }
+    }
+
+    /// Name of the link function
+    pub fn function_name(&self) -> &'static str {
+        match (self.prm, self.function) {
+            (true, LINK_RESET_LINK_STATES) => "RESET_LINK_STATES",
+            (true, LINK_TEST_LINK_STATES) => "TEST_LINK_STATES",
+            (true, LINK_CONFIRMED_USER_DATA) => "CONFIRMED_USER_DATA",
+            (true, LINK_UNCONFIRMED_USER_DATA) => "UNCONFIRMED_USER_DATA",
+            (true, LINK_REQUEST_LINK_STATUS) => "REQUEST_LINK_STATUS",
+            (false, LINK_ACK) => "ACK",
+            (false, LINK_NACK) => "NACK",
+            (false, LINK_STATUS) => "LINK_STATUS",
+            (false, LINK_NOT_SUPPORTED) => "NOT_SUPPORTED",
+            _ => "UNKNOWN",
+        }
+    }
+}
+
+/// Link layer frame with its CRCs removed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinkFrame {
+    pub header: LinkHeader,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", with = "hex_bytes")]
+    pub user_data: Vec<u8>,
+}
+
+impl LinkFrame {
+    pub fn new(header: LinkHeader, user_data: Vec<u8>) -> Self {
+        Self { header, user_data }
+    }
+
+    /// Decode one complete link frame, checking every CRC
+    pub fn decode(frame: &[u8]) -> NetworkResult<Self> {
+        if frame.len() < MIN_FRAME_LENGTH {
+            return Err(NetworkError::ParseError(format!("Link frame too short: {} octets", frame.len())));
+        }
+        if frame[..2] != START {
+            return Err(NetworkError::ParseError(format!(
+                "Link frame must start with 0564, got {:02X}{:02X}",
+                frame[0], frame[1]
+            )));
+        }
+        let length = frame[2] as usize;
+        if length < 5 {
+            return Err(NetworkError::ParseError(format!("Link length {} is below the minimum of 5", length)));
+        }
+        let layout = block_layout();
+        let expected = layout.wire_length(length + 3)?;
+        if frame.len() != expected {
+            return Err(NetworkError::ParseError(format!(
+                "Link length {} needs {} octets but {} were given",
+                length,
+                expected,
+                frame.len()
+            )));
+        }
+
+        let stripped = layout.strip(frame)?;
+        if let Some(block) = stripped.blocks.iter().find(|block| !block.valid) {
+            return Err(NetworkError::ParseError(format!(
+                "CRC {:04X} of octets {} does not match calculated {:04X}",
+                block.expected, block.data_range, block.calculated
+            )));
+        }
+
+        let data = stripped.data;
+        let header = LinkHeader::from_control(
+            data[3],
+            u16::from_le_bytes([data[4], data[5]]),
+            u16::from_le_bytes([data[6], data[7]]),
+        );
+        Ok(Self { header, user_data: data[LINK_HEADER_LENGTH..].to_vec() })
+    }
+
+    /// Encode the frame with its block CRCs
+    pub fn encode(&self) -> NetworkResult<Vec<u8>> {
+        if self.user_data.len() > MAX_USER_DATA {
+            return Err(NetworkError::InvalidConfig(format!(
+                "Link user data of {} octets exceeds {}",
+                self.user_data.len(),
+                MAX_USER_DATA
+            )));
+        }
+
+        let mut data = Vec::with_capacity(LINK_HEADER_LENGTH + self.user_data.len());
+        data.extend_from_slice(&START);
+        data.push((5 + self.user_data.len()) as u8);
+        data.push(self.header.control());
+        data.extend_from_slice(&self.header.destination.to_le_bytes());
+        data.extend_from_slice(&self.header.source.to_le_bytes());
+        data.extend_from_slice(&self.user_data);
+        block_layout().append(&data)
+    }
+
+    /// Describe the frame as a parse result with octet ranges into `raw`
+    pub fn to_parse_result(&self, raw: &[u8]) -> ParseResult {
+        let mut fields = ParsedFields::new();
+        let mut add = |name: &str, value: FieldValue, field_type: &str, range: Option<(usize, usize)>, description: &str| {
+            let (offset, length) = range
+                .map(|(offset, length)| (wire_offset(offset), length))
+                .unwrap_or((0, 0));
+            let raw_bytes = raw.get(offset..offset + length).unwrap_or(&[]);
+            fields.add_field(
+                name.to_string(),
+                ParsedField::new(name, value, field_type, offset, raw_bytes).with_description(description),
+            );
+        };
+
+        let header = &self.header;
+        add("start", FieldValue::UInt(0x0564), "uint", Some((0, 2)), "起始字");
+        add("length", FieldValue::UInt(raw.get(2).copied().unwrap_or(0) as u64), "uint", Some((2, 1)), "长度");
+        add("control", FieldValue::UInt(header.control() as u64), "uint", Some((3, 1)), "链路控制字");
+        add("dir", FieldValue::Bool(header.dir), "bool", Some((3, 1)), "方向 DIR (主站发出)");
+        add("prm", FieldValue::Bool(header.prm), "bool", Some((3, 1)), "启动站 PRM");
+        add("link_function", FieldValue::String(header.function_name().to_string()), "string", Some((3, 1)), "链路功能码");
+        add("destination", FieldValue::UInt(header.destination as u64), "uint", Some((4, 2)), "目的地址");
+        add("source", FieldValue::UInt(header.source as u64), "uint", Some((6, 2)), "源地址");
+
+        let mut warnings = Vec::new();
+        let mut application = None;
+        if let Some((&octet, segment)) = self.user_data.split_first() {
+            let transport = TransportHeader::decode(octet);
+            let base = LINK_HEADER_LENGTH;
+            add("transport_fir", FieldValue::Bool(transport.fir), "bool", Some((base, 1)), "传输层首段 FIR");
+            add("transport_fin", FieldValue::Bool(transport.fin), "bool", Some((base, 1)), "传输层末段 FIN");
+            add("transport_seq", FieldValue::UInt(transport.seq as u64), "uint", Some((base, 1)), "传输层序号");
+
+            if transport.fir && transport.fin {
+                match AppFragment::decode(segment) {
+                    Ok(fragment) => {
+                        let base = base + 1;
+                        let control = &fragment.control;
+                        add("app_seq", FieldValue::UInt(control.seq as u64), "uint", Some((base, 1)), "应用层序号");
+                        add("app_con", FieldValue::Bool(control.con), "bool", Some((base, 1)), "要求确认 CON");
+                        add("app_uns", FieldValue::Bool(control.uns), "bool", Some((base, 1)), "非请求响应 UNS");
+                        add(
+                            "function",
+                            FieldValue::String(function_name(fragment.function).unwrap_or("UNKNOWN").to_string()),
+                            "string",
+                            Some((base + 1, 1)),
+                            "应用层功能码",
+                        );
+                        if let Some(iin) = fragment.iin {
+                            add("iin", FieldValue::UInt(iin as u64), "uint", Some((base + 2, 2)), &iin_text(iin));
+                        }
+                        for (index, header) in fragment.headers.iter().enumerate() {
+                            add(&format!("object[{}]", index), FieldValue::String(header.name()), "string", None, &header.range_text());
+                            for object in &header.objects {
+                                let name = format!("{}[{}]", object.value.kind(), object.index);
+                                let value = match object.value {
+                                    DataValue::Binary { state, .. } => FieldValue::Bool(state),
+                                    DataValue::Counter { value, .. } => FieldValue::UInt(value as u64),
+                                    DataValue::Analog { value, .. } => FieldValue::Float(value),
+                                };
+                                let description = format!("g{}v{} {}", header.group, header.variation, flags_text(object.value.flags()));
+                                add(&name, value, object.value.kind(), None, &description);
+                                if let Some(time) = object.time {
+                                    add(&format!("{}_time[{}]", object.value.kind(), object.index), FieldValue::String(time_text(time)), "timestamp", None, "事件时标");
+                                }
+                            }
+                        }
+                        if fragment.iin.is_some_and(|iin| iin & (IIN_NO_FUNC_CODE_SUPPORT | IIN_OBJECT_UNKNOWN | IIN_PARAMETER_ERROR) != 0) {
+                            warnings.push(iin_text(fragment.iin.unwrap_or_default()));
+                        }
+                        application = Some(fragment);
+                    }
+                    Err(e) => warnings.push(format!("Application fragment not decoded: {}", e)),
+                }
+            } else {
+                warnings.push("Multi-segment fragment: reassemble the transport segments to decode it".to_string());
+            }
+        }
+
+        let mut result = ParseResult::success(dnp3_protocol_info(1.0), fields, raw.to_vec(), raw.len());
+        result.validation.crc_results = block_layout().strip(raw).map(|stripped| stripped.blocks).unwrap_or_default();
+        for warning in warnings {
+            result.add_warning(ParseWarning::new("DNP3_DECODE", warning));
+        }
+        result.metadata.extra.insert(
+            "dnp3".to_string(),
+            serde_json::json!({ "link": self, "application": application }),
+        );
+        result
+    }
+}
+
+/// Offset in the wire frame of an offset into the CRC-stripped frame
+fn wire_offset(offset: usize) -> usize {
+    if offset < LINK_HEADER_LENGTH {
+        offset
+    } else {
+        let data = offset - LINK_HEADER_LENGTH;
+        MIN_FRAME_LENGTH + data + 2 * (data / BLOCK_SIZE)
+    }
+}
+
+/// Transport header octet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransportHeader {
+    pub fir: bool,
+    pub fin: bool,
+    pub seq: u8,
+}
+
+impl TransportHeader {
+    pub fn encode(&self) -> u8 {
+        (self.seq % TRANSPORT_SEQUENCE_MODULO) | if self.fir { 0x40 } else { 0 } | if self.fin { 0x80 } else { 0 }
+    }
+
+    pub fn decode(octet: u8) -> Self {
+        Self { fir: octet & 0x40 != 0, fin: octet & 0x80 != 0, seq: octet & 0x3F }
+    }
+}
+
+/// Split an application fragment into transport segments, returning them
+/// and the next transport sequence number
+pub fn segment(fragment: &[u8], seq: u8) -> (Vec<Vec<u8>>, u8) {
+    let chunks: Vec<&[u8]> = if fragment.is_empty() {
+        vec![&[]]
+    } else {
+        fragment.chunks(MAX_SEGMENT_DATA).collect()
+    };
+    let count = chunks.len();
+    let mut seq = seq % TRANSPORT_SEQUENCE_MODULO;
+    let segments = chunks
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let header = TransportHeader { fir: index == 0, fin: index + 1 == count, seq };
+            seq = (seq + 1) % TRANSPORT_SEQUENCE_MODULO;
+            let mut segment = Vec::with_capacity(chunk.len() + 1);
+            segment.push(header.encode());
+            segment.extend_from_slice(chunk);
+            segment
+        })
+        .collect();
+    (segments, seq)
+}
+
+/// Joins transport segments back into application fragments
+#[derive(Debug, Default, Clone)]
+pub struct TransportReassembler {
+    buffer: Vec<u8>,
+    next_seq: Option<u8>,
+}
+
+impl TransportReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a segment, returning the fragment it completes
+    ///
+    /// A segment out of sequence discards the fragment in progress.
+    pub fn push(&mut self, segment: &[u8]) -> Option<Vec<u8>> {
+        let (&octet, data) = segment.split_first()?;
+        let header = TransportHeader::decode(octet);
+        if header.fir {
+            self.buffer = data.to_vec();
+        } else if self.next_seq == Some(header.seq) {
+            self.buffer.extend_from_slice(data);
+        } else {
+            self.buffer.clear();
+            self.next_seq = None;
+            return None;
+        }
+
+        if header.fin {
+            self.next_seq = None;
+            Some(std::mem::take(&mut self.buffer))
+        } else {
+            self.next_seq = Some((header.seq + 1) % TRANSPORT_SEQUENCE_MODULO);
+            None
+        }
+    }
+}
+
+/// Application control octet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppControl {
+    pub fir: bool,
+    pub fin: bool,
+    /// Confirmation requested
+    pub con: bool,
+    /// Unsolicited
+    pub uns: bool,
+    pub seq: u8,
+}
+
+impl AppControl {
+    /// Control of a single-fragment message
+    pub fn single(seq: u8) -> Self {
+        Self { fir: true, fin: true, con: false, uns: false, seq: seq % APP_SEQUENCE_MODULO }
+    }
+
+    fn encode(&self) -> u8 {
+        let mut octet = self.seq % APP_SEQUENCE_MODULO;
+        for (set, bit) in [(self.fir, 0x80), (self.fin, 0x40), (self.con, 0x20), (self.uns, 0x10)] {
+            if set {
+                octet |= bit;
+            }
+        }
+        octet
+    }
+
+    fn decode(octet: u8) -> Self {
+        Self {
+            fir: octet & 0x80 != 0,
+            fin: octet & 0x40 != 0,
+            con: octet & 0x20 != 0,
+            uns: octet & 0x10 != 0,
+            seq: octet & 0x0F,
+        }
+    }
+}
+
+/// Value of a measurement object
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DataValue {
+    /// Binary input (groups 1 and 2)
+    Binary { state: bool, flags: u8 },
+    /// Counter (groups 20 and 22)
+    Counter { value: u32, flags: u8 },
+    /// Analog input (groups 30 and 32)
+    Analog { value: f64, flags: u8 },
+}
+
+impl DataValue {
+    /// Point type name
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Binary { .. } => "binary",
+            Self::Counter { .. } => "counter",
+            Self::Analog { .. } => "analog",
+        }
+    }
+
+    /// Object flags; the binary state is reported in bit 7
+    pub fn flags(&self) -> u8 {
+        match *self {
+            Self::Binary { state, flags } => if state { flags | FLAG_STATE } else { flags & !FLAG_STATE },
+            Self::Counter { flags, .. } | Self::Analog { flags, .. } => flags,
+        }
+    }
+}
+
+/// Measurement object with its point index
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DataObject {
+    pub index: u16,
+    #[serde(flatten)]
+    pub value: DataValue,
+    /// Event time in milliseconds since 1970
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<u64>,
+}
+
+impl DataObject {
+    pub fn new(index: u16, value: DataValue) -> Self {
+        Self { index, value, time: None }
+    }
+
+    pub fn with_time(mut self, time: u64) -> Self {
+        self.time = Some(time);
+        self
+    }
+}
+
+/// Range part of an object header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ObjectRange {
+    /// Start and stop index (qualifiers 00 and 01)
+    Range { start: u16, stop: u16 },
+    /// All objects (qualifier 06)
+    All,
+    /// Object count, with index prefixes for qualifiers 17 and 28
+    Count { count: u16 },
+}
+
+/// Object header with the objects that follow it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectHeader {
+    pub group: u8,
+    pub variation: u8,
+    pub qualifier: u8,
+    pub range: ObjectRange,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub objects: Vec<DataObject>,
+}
+
+impl ObjectHeader {
+    /// Request for all objects of a group and variation
+    pub fn all(group: u8, variation: u8) -> Self {
+        Self { group, variation, qualifier: QUALIFIER_ALL, range: ObjectRange::All, objects: Vec::new() }
+    }
+
+    /// Class data request; class 0 is the static data of an integrity poll
+    pub fn class(class: u8) -> Self {
+        Self::all(60, class.min(3) + 1)
+    }
+
+    /// Static objects with contiguous indexes, starting at the first object's index
+    pub fn range(group: u8, variation: u8, objects: Vec<DataObject>) -> Self {
+        let start = objects.first().map_or(0, |object| object.index);
+        let stop = start.saturating_add(objects.len().saturating_sub(1) as u16);
+        let qualifier = if stop > 0xFF { QUALIFIER_RANGE_16 } else { QUALIFIER_RANGE_8 };
+        Self { group, variation, qualifier, range: ObjectRange::Range { start, stop }, objects }
+    }
+
+    /// Objects each prefixed with its index, as used for events
+    pub fn indexed(group: u8, variation: u8, objects: Vec<DataObject>) -> Self {
+        let wide = objects.len() > 0xFF || objects.iter().any(|object| object.index > 0xFF);
+        let qualifier = if wide { QUALIFIER_INDEX_16 } else { QUALIFIER_INDEX_8 };
+        let range = ObjectRange::Count { count: objects.len() as u16 };
+        Self { group, variation, qualifier, range, objects }
+    }
+
+    /// Group and variation as text, e.g. `g30v5`
+    pub fn name(&self) -> String {
+        format!("g{}v{}", self.group, self.variation)
+    }
+
+    /// Class polled by a group 60 header
+    pub fn polled_class(&self) -> Option<u8> {
+        (self.group == 60 && (1..=4).contains(&self.variation)).then(|| self.variation - 1)
+    }
+
+    fn range_text(&self) -> String {
+        let range = match self.range {
+            ObjectRange::Range { start, stop } => format!("索引 {}..={}", start, stop),
+            ObjectRange::All => "全部对象".to_string(),
+            ObjectRange::Count { count } => format!("{} 个对象", count),
+        };
+        format!("{} 限定词 {:02X} {}", group_name(self.group), self.qualifier, range)
+    }
+
+    fn encode(&self, out: &mut Vec<u8>, with_data: bool) -> NetworkResult<()> {
+        out.extend_from_slice(&[self.group, self.variation, self.qualifier]);
+        let wide = matches!(self.qualifier, QUALIFIER_RANGE_16 | QUALIFIER_COUNT_16 | QUALIFIER_INDEX_16);
+        let push = |out: &mut Vec<u8>, value: u16| {
+            if wide {
+                out.extend_from_slice(&value.to_le_bytes());
+            } else {
+                out.push(value as u8);
+            }
+        };
+        match (self.qualifier, self.range) {
+            (QUALIFIER_ALL, ObjectRange::All) => {}
+            (QUALIFIER_RANGE_8 | QUALIFIER_RANGE_16, ObjectRange::Range { start, stop }) => {
+                push(out, start);
+                push(out, stop);
+            }
+            (QUALIFIER_COUNT_8 | QUALIFIER_COUNT_16 | QUALIFIER_INDEX_8 | QUALIFIER_INDEX_16, ObjectRange::Count { count }) => {
+                push(out, count);
+            }
+            (qualifier, range) => {
+                return Err(NetworkError::InvalidConfig(format!("Qualifier {:02X} does not match range {:?}", qualifier, range)));
+            }
+        }
+        if !with_data || self.objects.is_empty() {
+            return Ok(());
+        }
+
+        if (self.group, self.variation) == (1, 1) {
+            let mut packed = vec![0u8; self.objects.len().div_ceil(8)];
+            for (position, object) in self.objects.iter().enumerate() {
+                if matches!(object.value, DataValue::Binary { state: true, .. }) {
+                    packed[position / 8] |= 1 << (position % 8);
+                }
+            }
+            out.extend(packed);
+            return Ok(());
+        }
+
+        let prefixed = matches!(self.qualifier, QUALIFIER_INDEX_8 | QUALIFIER_INDEX_16);
+        for object in &self.objects {
+            if prefixed {
+                push(out, object.index);
+            }
+            encode_object(self.group, self.variation, object, out)?;
+        }
+        Ok(())
+    }
+
+    fn decode(data: &[u8], position: &mut usize, with_data: bool) -> NetworkResult<Self> {
+        let take = |position: &mut usize, length: usize| -> NetworkResult<&[u8]> {
+            let bytes = data.get(*position..*position + length).ok_or_else(|| {
+                NetworkError::ParseError(format!("Object data truncated at octet {}", *position))
+            })?;
+            *position += length;
+            Ok(bytes)
+        };
+        let header = take(position, 3)?;
+        let (group, variation, qualifier) = (header[0], header[1], header[2]);
+        let wide = matches!(qualifier, QUALIFIER_RANGE_16 | QUALIFIER_COUNT_16 | QUALIFIER_INDEX_16);
+        let read = |position: &mut usize| -> NetworkResult<u16> {
+            let bytes = take(position, if wide { 2 } else { 1 })?;
+            Ok(if wide { u16::from_le_bytes([bytes[0], bytes[1]]) } else { bytes[0] as u16 })
+        };
+
+        let range = match qualifier {
+            QUALIFIER_ALL => ObjectRange::All,
+            QUALIFIER_RANGE_8 | QUALIFIER_RANGE_16 => {
+                let start = read(position)?;
+                let stop = read(position)?;
+                if stop < start {
+                    return Err(NetworkError::ParseError(format!("Range stop {} is below start {}", stop, start)));
+                }
+                ObjectRange::Range { start, stop }
+            }
+            QUALIFIER_COUNT_8 | QUALIFIER_COUNT_16 | QUALIFIER_INDEX_8 | QUALIFIER_INDEX_16 => {
+                ObjectRange::Count { count: read(position)? }
+            }
+            _ => return Err(NetworkError::ParseError(format!("Unsupported qualifier {:02X}", qualifier))),
+        };
+        let mut header = Self { group, variation, qualifier, range, objects: Vec::new() };
+        if !with_data || group == 60 || range == ObjectRange::All {
+            return Ok(header);
+        }
+
+        let (first, count) = match range {
+            ObjectRange::Range { start, stop } => (start, stop - start + 1),
+            ObjectRange::Count { count } => (0, count),
+            ObjectRange::All => (0, 0),
+        };
+        if (group, variation) == (1, 1) {
+            let packed = take(position, (count as usize).div_ceil(8))?;
+            header.objects = (0..count)
+                .map(|offset| {
+                    let state = packed[offset as usize / 8] & (1 << (offset % 8)) != 0;
+                    DataObject::new(first + offset, DataValue::Binary { state, flags: FLAG_ONLINE })
+                })
+                .collect();
+            return Ok(header);
+        }
+
+        let size = object_size(group, variation).ok_or_else(|| {
+            NetworkError::ParseError(format!("Object g{}v{} is not supported", group, variation))
+        })?;
+        let prefixed = matches!(qualifier, QUALIFIER_INDEX_8 | QUALIFIER_INDEX_16);
+        for offset in 0..count {
+            let index = if prefixed { read(position)? } else { first + offset };
+            header.objects.push(decode_object(group, variation, index, take(position, size)?));
+        }
+        Ok(header)
+    }
+}
+
+/// Application layer fragment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppFragment {
+    pub control: AppControl,
+    pub function: u8,
+    /// Internal indications, IIN1 in the high octet (responses only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iin: Option<u16>,
+    #[serde(default)]
+    pub headers: Vec<ObjectHeader>,
+}
+
+impl AppFragment {
+    /// Request from the master
+    pub fn request(function: u8, seq: u8, headers: Vec<ObjectHeader>) -> Self {
+        Self { control: AppControl::single(seq), function, iin: None, headers }
+    }
+
+    /// Integrity poll: class 1, 2 and 3 events followed by the class 0 static data
+    pub fn integrity_poll(seq: u8) -> Self {
+        Self::request(FC_READ, seq, [1, 2, 3, 0].into_iter().map(ObjectHeader::class).collect())
+    }
+
+    /// Event poll of the given classes
+    pub fn class_poll(seq: u8, classes: &[u8]) -> Self {
+        Self::request(FC_READ, seq, classes.iter().map(|class| ObjectHeader::class(*class)).collect())
+    }
+
+    /// Solicited response from the outstation
+    pub fn response(seq: u8, iin: u16, headers: Vec<ObjectHeader>) -> Self {
+        Self { control: AppControl::single(seq), function: FC_RESPONSE, iin: Some(iin), headers }
+    }
+
+    /// Application confirm of a response
+    pub fn confirm(seq: u8, unsolicited: bool) -> Self {
+        let mut fragment = Self::request(FC_CONFIRM, seq, Vec::new());
+        fragment.control.uns = unsolicited;
+        fragment
+    }
+
+    /// Whether the fragment is a solicited or unsolicited response
+    pub fn is_response(&self) -> bool {
+        self.function == FC_RESPONSE || self.function == FC_UNSOLICITED_RESPONSE
+    }
+
+    /// Measurement objects with the group that carried them
+    pub fn objects(&self) -> impl Iterator<Item = (&ObjectHeader, &DataObject)> {
+        self.headers.iter().flat_map(|header| header.objects.iter().map(move |object| (header, object)))
+    }
+
+    /// Decode a complete fragment
+    pub fn decode(data: &[u8]) -> NetworkResult<Self> {
+        if data.len() < 2 {
+            return Err(NetworkError::ParseError(format!("Application fragment too short: {} octets", data.len())));
+        }
+        let control = AppControl::decode(data[0]);
+        let function = data[1];
+        let mut position = 2;
+        let response = function == FC_RESPONSE || function == FC_UNSOLICITED_RESPONSE;
+        let iin = if response {
+            let bytes = data.get(2..4).ok_or_else(|| {
+                NetworkError::ParseError("Response is missing its internal indications".to_string())
+            })?;
+            position = 4;
+            Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+        } else {
+            None
+        };
+
+        // Read requests name objects without carrying their values
+        let with_data = function != FC_READ;
+        let mut headers = Vec::new();
+        while position < data.len() {
+            headers.push(ObjectHeader::decode(data, &mut position, with_data)?);
+        }
+        Ok(Self { control, function, iin, headers })
+    }
+
+    /// Encode the fragment
+    pub fn encode(&self) -> NetworkResult<Vec<u8>> {
+        let mut data = vec![self.control.encode(), self.function];
+        if let Some(iin) = self.iin {
+            data.extend_from_slice(&iin.to_be_bytes());
+        }
+        let with_data = self.function != FC_READ;
+        for header in &self.headers {
+            header.encode(&mut data, with_data)?;
+        }
+        Ok(data)
+    }
+}
+
+/// Octets of one object without index prefix; `None` for unsupported and packed variations
+pub fn object_size(group: u8, variation: u8) -> Option<usize> {
+    Some(match (group, variation) {
+        (1, 2) | (2, 1) => 1,
+        (2, 2) => 1 + TIME_LENGTH,
+        (20, 1) | (22, 1) | (30, 1) | (30, 5) | (32, 1) | (32, 5) => 5,
+        (20, 5) | (30, 3) => 4,
+        (22, 5) | (32, 3) | (32, 7) => 5 + TIME_LENGTH,
+        _ => return None,
+    })
+}
+
+fn read_time(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0, |time, byte| (time << 8) | *byte as u64)
+}
+
+fn decode_object(group: u8, variation: u8, index: u16, bytes: &[u8]) -> DataObject {
+    let flags = bytes[0];
+    let u32_at = |offset: usize| u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]]);
+    let (value, time) = match (group, variation) {
+        (1, _) | (2, _) => (
+            DataValue::Binary { state: flags & FLAG_STATE != 0, flags: flags & !FLAG_STATE },
+            (variation == 2 && group == 2).then(|| read_time(&bytes[1..])),
+        ),
+        (20, 5) => (DataValue::Counter { value: u32_at(0), flags: FLAG_ONLINE }, None),
+        (20, _) | (22, _) => (
+            DataValue::Counter { value: u32_at(1), flags },
+            (variation == 5).then(|| read_time(&bytes[5..])),
+        ),
+        (30, 3) => (DataValue::Analog { value: u32_at(0) as i32 as f64, flags: FLAG_ONLINE }, None),
+        (_, 5) | (_, 7) => (
+            DataValue::Analog { value: f32::from_bits(u32_at(1)) as f64, flags },
+            (variation == 7).then(|| read_time(&bytes[5..])),
+        ),
+        _ => (
+            DataValue::Analog { value: u32_at(1) as i32 as f64, flags },
+            (variation == 3).then(|| read_time(&bytes[5..])),
+        ),
+    };
+    DataObject { index, value, time }
+}
+
+fn encode_object(group: u8, variation: u8, object: &DataObject, out: &mut Vec<u8>) -> NetworkResult<()> {
+    let time = object.time.unwrap_or_default().to_le_bytes();
+    let time = &time[..TIME_LENGTH];
+    let mismatch = || NetworkError::InvalidConfig(format!(
+        "A {} value cannot be encoded as g{}v{}",
+        object.value.kind(),
+        group,
+        variation
+    ));
+    match (group, variation, object.value) {
+        (1, 2, value) | (2, 1, value) if value.kind() == "binary" => out.push(value.flags()),
+        (2, 2, value) if value.kind() == "binary" => {
+            out.push(value.flags());
+            out.extend_from_slice(time);
+        }
+        (20, 5, DataValue::Counter { value, .. }) => out.extend_from_slice(&value.to_le_bytes()),
+        (20, 1, DataValue::Counter { value, flags }) | (22, 1, DataValue::Counter { value, flags }) => {
+            out.push(flags);
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        (22, 5, DataValue::Counter { value, flags }) => {
+            out.push(flags);
+            out.extend_from_slice(&value.to_le_bytes());
+            out.extend_from_slice(time);
+        }
+        (30, 3, DataValue::Analog { value, .. }) => out.extend_from_slice(&(value.round() as i32).to_le_bytes()),
+        (30, 1, DataValue::Analog { value, flags }) | (32, 1, DataValue::Analog { value, flags }) | (32, 3, DataValue::Analog { value, flags }) => {
+            out.push(flags);
+            out.extend_from_slice(&(value.round() as i32).to_le_bytes());
+            if variation == 3 {
+                out.extend_from_slice(time);
+            }
+        }
+        (30, 5, DataValue::Analog { value, flags }) | (32, 5, DataValue::Analog { value, flags }) | (32, 7, DataValue::Analog { value, flags }) => {
+            out.push(flags);
+            out.extend_from_slice(&(value as f32).to_le_bytes());
+            if variation == 7 {
+                out.extend_from_slice(time);
+            }
+        }
+        _ if object_size(group, variation).is_none() => {
+            return Err(NetworkError::InvalidConfig(format!("Object g{}v{} is not supported", group, variation)));
+        }
+        _ => return Err(mismatch()),
+    }
+    Ok(())
+}
+
+/// Name of an application function code
+pub fn function_name(function: u8) -> Option<&'static str> {
+    Some(match function {
+        FC_CONFIRM => "CONFIRM",
+        FC_READ => "READ",
+        FC_WRITE => "WRITE",
+        FC_SELECT => "SELECT",
+        FC_OPERATE => "OPERATE",
+        FC_DIRECT_OPERATE => "DIRECT_OPERATE",
+        FC_COLD_RESTART => "COLD_RESTART",
+        FC_ENABLE_UNSOLICITED => "ENABLE_UNSOLICITED",
+        FC_DISABLE_UNSOLICITED => "DISABLE_UNSOLICITED",
+        FC_RESPONSE => "RESPONSE",
+        FC_UNSOLICITED_RESPONSE => "UNSOLICITED_RESPONSE",
+        _ => return None,
+    })
+}
+
+/// Name of an object group
+pub fn group_name(group: u8) -> &'static str {
+    match group {
+        1 => "二进制输入",
+        2 => "二进制输入事件",
+        20 => "计数器",
+        22 => "计数器事件",
+        30 => "模拟量输入",
+        32 => "模拟量输入事件",
+        60 => "类别数据",
+        _ => "未知对象组",
+    }
+}
+
+/// Internal indications as text
+fn iin_text(iin: u16) -> String {
+    let flags: Vec<&str> = [
+        (IIN_BROADCAST, "BROADCAST"),
+        (IIN_CLASS1_EVENTS, "CLASS_1_EVENTS"),
+        (IIN_CLASS2_EVENTS, "CLASS_2_EVENTS"),
+        (IIN_CLASS3_EVENTS, "CLASS_3_EVENTS"),
+        (IIN_NEED_TIME, "NEED_TIME"),
+        (IIN_LOCAL_CONTROL, "LOCAL_CONTROL"),
+        (IIN_DEVICE_TROUBLE, "DEVICE_TROUBLE"),
+        (IIN_DEVICE_RESTART, "DEVICE_RESTART"),
+        (IIN_NO_FUNC_CODE_SUPPORT, "NO_FUNC_CODE_SUPPORT"),
+        (IIN_OBJECT_UNKNOWN, "OBJECT_UNKNOWN"),
+        (IIN_PARAMETER_ERROR, "PARAMETER_ERROR"),
+        (IIN_EVENT_BUFFER_OVERFLOW, "EVENT_BUFFER_OVERFLOW"),
+    ]
+    .iter()
+    .filter(|(bit, _)| iin & bit != 0)
+    .map(|(_, name)| *name)
+    .collect();
+
+    if flags.is_empty() {
+        "内部指示: 无".to_string()
+    } else {
+        format!("内部指示: {}", flags.join(" "))
+    }
+}
+
+/// Object flags as text
+fn flags_text(flags: u8) -> String {
+    let names: Vec<&str> = [
+        (FLAG_ONLINE, "ONLINE"),
+        (FLAG_RESTART, "RESTART"),
+        (FLAG_COMM_LOST, "COMM_LOST"),
+        (FLAG_REMOTE_FORCED, "REMOTE_FORCED"),
+        (FLAG_LOCAL_FORCED, "LOCAL_FORCED"),
+    ]
+    .iter()
+    .filter(|(bit, _)| flags & bit != 0)
+    .map(|(_, name)| *name)
+    .collect();
+    format!("品质: {}", if names.is_empty() { "OFFLINE".to_string() } else { names.join(" ") })
+}
+
+fn time_text(time: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(time as i64)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S%.3f").to_string())
+        .unwrap_or_else(|| time.to_string())
+}
+
+/// User data octets as a hex string in JSON
+mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode_upper(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        hex::decode(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Stream framer splitting TCP data into link frames on the length octet
+pub fn frame_detector() -> FrameDetector {
+    FrameDetector::new(FramingRule {
+        start_delimiter: Some(String::from_utf8_lossy(&START).into_owned()),
+        end_delimiter: None,
+        length_field: Some(LengthField {
+            offset: 2,
+            length: 1,
+            encoding: LengthEncoding::Binary,
+            includes_header: false,
+            includes_length_field: false,
+            endian: Endianness::Little,
+        }),
+        fixed_size: None,
+        escape_rules: Vec::new(),
+        frame_validation: FrameValidation::default(),
+        block_crc: Some(block_layout()),
+    })
+}
+
+/// Protocol information for DNP3 parse results
+fn dnp3_protocol_info(confidence: f64) -> ProtocolInfo {
+    ProtocolInfo {
+        name: "DNP3".to_string(),
+        version: "IEEE 1815-2012".to_string(),
+        parser_id: DNP3_PARSER_ID.to_string(),
+        confidence,
+    }
+}
+
+/// Built-in DNP3 parser
+#[derive(Debug, Clone, Default)]
+pub struct Dnp3Parser;
+
+impl Dnp3Parser {
+    /// Create the parser
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Parser for Dnp3Parser {
+    fn parse(&self, data: &[u8]) -> NetworkResult<ParseResult> {
+        match LinkFrame::decode(data) {
+            Ok(frame) => Ok(frame.to_parse_result(data)),
+            Err(e) => Ok(ParseResult::failure(
+                dnp3_protocol_info(0.0),
+                data.to_vec(),
+                ParseError::new("DNP3_DECODE", e.to_string(), ErrorSeverity::Critical),
+            )),
+        }
+    }
+
+    fn validate(&self, result: &ParseResult) -> ValidationReport {
+        ValidationReport::from_parse_result(result)
+    }
+
+    fn get_protocol_info(&self) -> ParserProtocolInfo {
+        ParserProtocolInfo {
+            name: "DNP3".to_string(),
+            version: "IEEE 1815-2012".to_string(),
+            author: "IEEE 1815".to_string(),
+            description: "Distributed Network Protocol for SCADA masters and outstations".to_string(),
+            supported_formats: vec!["binary".to_string()],
+            magic_bytes: Some(START.to_vec()),
+            min_frame_size: Some(MIN_FRAME_LENGTH),
+            max_frame_size: Some(MAX_FRAME_LENGTH),
+        }
+    }
+
+    fn get_id(&self) -> &str {
+        DNP3_PARSER_ID
+    }
+
+    fn can_parse(&self, data: &[u8]) -> bool {
+        data.len() >= MIN_FRAME_LENGTH
+            && data[..2] == START
+            && block_layout().wire_length(data[2] as usize + 3).ok() == Some(data.len())
+    }
+
+    fn stream_framer(&self) -> Option<FrameDetector> {
+        Some(frame_detector())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_frame_crcs() {
+        // Request link status from outstation 10, master 1
+        let frame = LinkFrame::new(LinkHeader::primary(LINK_REQUEST_LINK_STATUS, true, 10, 1), Vec::new());
+        let encoded = frame.encode().unwrap();
+        assert_eq!(&encoded[..8], &[0x05, 0x64, 0x05, 0xC9, 0x0A, 0x00, 0x01, 0x00]);
+        assert_eq!(encoded.len(), MIN_FRAME_LENGTH);
+        assert_eq!(LinkFrame::decode(&encoded).unwrap(), frame);
+
+        let mut corrupted = encoded.clone();
+        corrupted[9] ^= 0x01;
+        assert!(LinkFrame::decode(&corrupted).is_err());
+
+        // 40 octets of user data take three blocks after the header
+        let frame = LinkFrame::new(LinkHeader::primary(LINK_UNCONFIRMED_USER_DATA, true, 10, 1), (0..40).collect());
+        let encoded = frame.encode().unwrap();
+        assert_eq!(encoded.len(), 10 + 40 + 3 * 2);
+        assert_eq!(LinkFrame::decode(&encoded).unwrap(), frame);
+
+        let mut framer = frame_detector();
+        let mut stream = encoded.clone();
+        stream.extend(LinkFrame::new(LinkHeader::secondary(LINK_ACK, false, 1, 10), Vec::new()).encode().unwrap());
+        let frames = framer.detect_frames(&stream).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].data, encoded);
+    }
+
+    #[test]
+    fn test_application_objects() {
+        let request = AppFragment::integrity_poll(3);
+        let encoded = request.encode().unwrap();
+        assert_eq!(hex::encode_upper(&encoded), "C3013C02063C03063C04063C0106");
+        assert_eq!(AppFragment::decode(&encoded).unwrap(), request);
+
+        let response = AppFragment::response(
+            3,
+            IIN_DEVICE_RESTART | IIN_CLASS1_EVENTS,
+            vec![
+                ObjectHeader::range(1, 2, vec![
+                    DataObject::new(0, DataValue::Binary { state: true, flags: FLAG_ONLINE }),
+                    DataObject::new(1, DataValue::Binary { state: false, flags: FLAG_ONLINE }),
+                ]),
+                ObjectHeader::range(30, 5, vec![DataObject::new(4, DataValue::Analog { value: 12.5, flags: FLAG_ONLINE })]),
+                ObjectHeader::range(20, 1, vec![DataObject::new(0, DataValue::Counter { value: 70000, flags: FLAG_ONLINE })]),
+                ObjectHeader::indexed(32, 7, vec![
+                    DataObject::new(300, DataValue::Analog { value: -1.25, flags: FLAG_ONLINE }).with_time(1_700_000_000_123),
+                ]),
+            ],
+        );
+        let encoded = response.encode().unwrap();
+        assert_eq!(hex::encode_upper(&encoded[..11]), "C381820001020000018101");
+        let decoded = AppFragment::decode(&encoded).unwrap();
+        assert_eq!(decoded, response);
+        assert_eq!(decoded.headers[3].qualifier, QUALIFIER_INDEX_16);
+        assert_eq!(decoded.objects().count(), 5);
+
+        // Packed binary inputs
+        let packed = AppFragment::decode(&hex::decode("C08100000101000209A5").unwrap()).unwrap();
+        let states: Vec<bool> = packed.headers[0].objects.iter()
+            .map(|object| matches!(object.value, DataValue::Binary { state: true, .. }))
+            .collect();
+        assert_eq!(states, vec![true, false, true, false, false, true, false, true]);
+        assert_eq!(packed.headers[0].objects[0].index, 2);
+    }
+
+    #[test]
+    fn test_transport_and_parser() {
+        let fragment: Vec<u8> = (0..=255u8).chain(0..=99).collect();
+        let (segments, next) = segment(&fragment, 62);
+        assert_eq!((segments.len(), next), (2, 0));
+        assert_eq!(segments[0][0], 0x40 | 62);
+        assert_eq!(segments[1][0], 0x80 | 63);
+
+        let mut reassembler = TransportReassembler::new();
+        assert_eq!(reassembler.push(&segments[0]), None);
+        assert_eq!(reassembler.push(&segments[1]), Some(fragment));
+        // A lost first segment drops the rest
+        assert_eq!(reassembler.push(&segments[1]), None);
+
+        let response = AppFragment::response(0, 0, vec![
+            ObjectHeader::range(30, 1, vec![DataObject::new(0, DataValue::Analog { value: 42.0, flags: FLAG_ONLINE })]),
+        ]);
+        let (segments, _) = segment(&response.encode().unwrap(), 0);
+        let frame = LinkFrame::new(LinkHeader::primary(LINK_UNCONFIRMED_USER_DATA, false, 1, 10), segments[0].clone());
+        let encoded = frame.encode().unwrap();
+
+        let parser = Dnp3Parser::new();
+        assert!(parser.can_parse(&encoded));
+        let result = parser.parse(&encoded).unwrap();
+        assert!(result.success);
+        assert_eq!(result.fields.get_field("function").unwrap().value, FieldValue::String("RESPONSE".to_string()));
+        assert_eq!(result.fields.get_field("analog[0]").unwrap().value, FieldValue::Float(42.0));
+        assert_eq!(result.validation.crc_results.len(), 2);
+        assert_eq!(result.fields.get_field("source").unwrap().offset, 6);
+    }
+}
@@ -0,0 +1,252 @@
+//! Transport envelope context
+//!
+//! The bytes of a frame arrive with transport context that the payload does
+//! not carry: MQTT topic, peer address, session and receive time. The envelope exposes that context to parsing, so topic
+//! templates such as `plant/{site}/mn/{mn}/up/{type}` can select a rule and
+//! feed device identity into the parsed output.
+
+use crate::parser::result::ParseResult;
+use crate::types::{NetworkResult, NetworkError};
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+/// Transport context of a received frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvelopeContext {
+    /// Transport protocol (tcp, udp, websocket, mqtt, ...)
+    #[serde(default)]
+    pub transport: Option<String>,
+
+    /// Session that received the data
+    #[serde(default)]
+    pub session_id: Option<String>,
+
+    /// Client ID (server sessions)
+    #[serde(default)]
+    pub client_id: Option<String>,
+
+    /// Peer IP address
+    #[serde(default)]
+    pub peer_address: Option<String>,
+
+    /// Peer port
+    #[serde(default)]
+    pub peer_port: Option<u16>,
+
+    /// MQTT topic the message was published on
+    #[serde(default)]
+    pub mqtt_topic: Option<String>,
+
+    /// When the data was received
+    #[serde(default = "Utc::now")]
+    pub received_at: DateTime<Utc>,
+
+    /// Variables extracted from the envelope (e.g. topic template matches)
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+impl EnvelopeContext {
+    /// Create an empty envelope received now
+    pub fn new() -> Self {
+        Self {
+            transport: None,
+            session_id: None,
+            client_id: None,
+            peer_address: None,
+            peer_port: None,
+            mqtt_topic: None,
+            received_at: Utc::now(),
+            variables: HashMap::new(),
+        }
+    }
+
+    /// Build an envelope for data received on a session
+    pub fn for_session(transport: &str, session_id: &str, client_id: Option<&str>, peer: Option<SocketAddr>) -> Self {
+        let mut envelope = Self::new();
+        envelope.transport = Some(transport.to_lowercase());
+        envelope.session_id = Some(session_id.to_string());
+        envelope.client_id = client_id.map(str::to_string);
+
+        match peer {
+            Some(addr) => envelope.with_peer(addr),
+            None => envelope,
+        }
+    }
+
+    /// Set the peer address
+    pub fn with_peer(mut self, addr: SocketAddr) -> Self {
+        self.peer_address = Some(addr.ip().to_string());
+        self.peer_port = Some(addr.port());
+        self
+    }
+
+    /// Get an envelope value by name
+    ///
+    /// Built-in names are checked first, then extracted variables.
+    pub fn get(&self, name: &str) -> Option<String> {
+        match name {
+            "transport" => self.transport.clone(),
+            "session_id" => self.session_id.clone(),
+            "client_id" => self.client_id.clone(),
+            "peer_address" | "peer_ip" => self.peer_address.clone(),
+            "peer_port" => self.peer_port.map(|port| port.to_string()),
+            "topic" | "mqtt_topic" => self.mqtt_topic.clone(),
+            "received_at" => Some(self.received_at.to_rfc3339()),
+            _ => self.variables.get(name).cloned(),
+        }
+    }
+
+    /// Copy the envelope into a parse result's metadata
+    pub fn apply_to(&self, result: &mut ParseResult) {
+        if let Ok(value) = serde_json::to_value(self) {
+            result.metadata.extra.insert("envelope".to_string(), value);
+        }
+    }
+}
+
+impl Default for EnvelopeContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compiled topic template
+///
+/// `{name}` captures one topic level, `+` matches one level without
+/// capturing and a trailing `#` matches all remaining levels.
+#[derive(Debug, Clone)]
+pub struct TopicTemplate {
+    /// Original template
+    template: String,
+
+    /// Compiled matcher
+    regex: Regex,
+
+    /// Variable names in template order
+    variables: Vec<String>,
+}
+
+impl TopicTemplate {
+    /// Compile a topic template
+    pub fn parse(template: &str) -> NetworkResult<Self> {
+        if template.is_empty() {
+            return Err(NetworkError::ParseError("Topic template cannot be empty".to_string()));
+        }
+
+        let levels: Vec<&str> = template.split('/').collect();
+        let mut pattern = String::from("^");
+        let mut variables = Vec::new();
+
+        for (index, level) in levels.iter().enumerate() {
+            if index > 0 {
+                pattern.push('/');
+            }
+
+            if *level == "#" {
+                if index != levels.len() - 1 {
+                    return Err(NetworkError::ParseError(format!(
+                        "'#' must be the last level of topic template '{}'",
+                        template
+                    )));
+                }
+                pattern.push_str(".*");
+            } else if *level == "+" {
+                pattern.push_str("[^/]+");
+            } else if level.starts_with('{') && level.ends_with('}') && level.len() > 2 {
+                let name = &level[1..level.len() - 1];
+                if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    return Err(NetworkError::ParseError(format!(
+                        "Invalid variable '{}' in topic template '{}'",
+                        name, template
+                    )));
+                }
+                if variables.iter().any(|v| v == name) {
+                    return Err(NetworkError::ParseError(format!(
+                        "Duplicate variable '{}' in topic template '{}'",
+                        name, template
+                    )));
+                }
+                pattern.push_str(&format!("(?P<{}>[^/]+)", name));
+                variables.push(name.to_string());
+            } else {
+                pattern.push_str(&regex::escape(level));
+            }
+        }
+
+        pattern.push('$');
+
+        let regex = Regex::new(&pattern)
+            .map_err(|e| NetworkError::ParseError(format!("Invalid topic template '{}': {}", template, e)))?;
+
+        Ok(Self {
+            template: template.to_string(),
+            regex,
+            variables,
+        })
+    }
+
+    /// Match a topic, returning the captured variables
+    pub fn match_topic(&self, topic: &str) -> Option<HashMap<String, String>> {
+        let captures = self.regex.captures(topic)?;
+        Some(self.variables.iter()
+            .filter_map(|name| captures.name(name).map(|m| (name.clone(), m.as_str().to_string())))
+            .collect())
+    }
+
+    /// Get the template string
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// Get variable names in template order
+    pub fn variables(&self) -> &[String] {
+        &self.variables
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_template_match() {
+        let template = TopicTemplate::parse("plant/{site}/mn/{mn}/up/{type}").unwrap();
+        assert_eq!(template.variables(), ["site", "mn", "type"]);
+
+        let vars = template.match_topic("plant/A1/mn/DEV001/up/rtd").unwrap();
+        assert_eq!(vars["site"], "A1");
+        assert_eq!(vars["mn"], "DEV001");
+        assert_eq!(vars["type"], "rtd");
+
+        assert!(template.match_topic("plant/A1/mn/DEV001/down/rtd").is_none());
+        assert!(template.match_topic("plant/A1/mn/DEV001/up").is_none());
+    }
+
+    #[test]
+    fn test_topic_template_wildcards() {
+        let template = TopicTemplate::parse("devices/+/{id}/#").unwrap();
+        let vars = template.match_topic("devices/meter/42/status/online").unwrap();
+        assert_eq!(vars["id"], "42");
+
+        assert!(TopicTemplate::parse("devices/#/tail").is_err());
+        assert!(TopicTemplate::parse("a/{x}/{x}").is_err());
+    }
+
+    #[test]
+    fn test_envelope_for_session() {
+        let peer: SocketAddr = "[2001:db8::20]:5021".parse().unwrap();
+        let envelope = EnvelopeContext::for_session("TCP", "s1", Some(&peer.to_string()), Some(peer));
+        assert_eq!(envelope.transport.as_deref(), Some("tcp"));
+        assert_eq!(envelope.get("client_id").as_deref(), Some("[2001:db8::20]:5021"));
+        assert_eq!(envelope.get("peer_ip").as_deref(), Some("2001:db8::20"));
+        assert_eq!(envelope.get("peer_port").as_deref(), Some("5021"));
+        assert_eq!(envelope.get("session_id").as_deref(), Some("s1"));
+
+        let envelope = EnvelopeContext::for_session("tcp", "s1", None, None);
+        assert!(envelope.get("peer_ip").is_none());
+    }
+}
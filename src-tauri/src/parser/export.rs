@@ -0,0 +1,525 @@
+//! Export of parse results following rule export mappings
+//!
+//! A parse result is first flattened into an ordered record of named values:
+//! parsed fields, lookup outputs (`field.output`) and envelope context
+//! (`envelope.<name>`, with exposed topic variables also available bare).
+//! The record is then written as Influx line protocol, flat JSON or CSV
+//! according to the rule's `exports` block. Storage connectors reuse
+//! `ResultExporter` directly.
+
+use crate::parser::result::{FieldValue, ParseResult};
+use crate::parser::schema::{CsvExport, ExportRules, InfluxExport, JsonExport};
+use crate::types::{NetworkResult, NetworkError};
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Influx line protocol, one line per result
+    Influx,
+    /// Flat JSON, one object per line
+    Json,
+    /// CSV with an optional header row
+    Csv,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = NetworkError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "influx" | "line" => Ok(ExportFormat::Influx),
+            "json" => Ok(ExportFormat::Json),
+            "csv" => Ok(ExportFormat::Csv),
+            _ => Err(NetworkError::ParseError(format!("Unsupported export format: {}", s))),
+        }
+    }
+}
+
+/// Ordered record of named values flattened from a parse result
+#[derive(Debug, Clone, Default)]
+pub struct FlatRecord {
+    entries: Vec<(String, Value)>,
+}
+
+impl FlatRecord {
+    /// Flatten a parse result
+    pub fn from_result(result: &ParseResult) -> Self {
+        let mut record = Self::default();
+
+        for name in result.fields.get_field_names() {
+            let Some(field) = result.fields.get_field(name) else {
+                continue;
+            };
+            record.insert(name.clone(), field_value_to_json(&field.value));
+
+            let mut outputs: Vec<_> = field.metadata.extra.iter().collect();
+            outputs.sort_by(|a, b| a.0.cmp(b.0));
+            for (output, value) in outputs {
+                record.insert(format!("{}.{}", name, output), value.clone());
+            }
+        }
+
+        if let Some(Value::Object(envelope)) = result.metadata.extra.get("envelope") {
+            let mut keys: Vec<_> = envelope.keys().filter(|k| *k != "variables").collect();
+            keys.sort();
+            for key in keys {
+                if !envelope[key].is_null() {
+                    record.insert(format!("envelope.{}", key), envelope[key].clone());
+                }
+            }
+
+            if let Some(Value::Object(variables)) = envelope.get("variables") {
+                let mut names: Vec<_> = variables.keys().collect();
+                names.sort();
+                for name in names {
+                    record.insert(format!("envelope.{}", name), variables[name].clone());
+                    if record.get(name).is_none() {
+                        record.insert(name.clone(), variables[name].clone());
+                    }
+                }
+            }
+        }
+
+        record
+    }
+
+    /// Add or replace a value
+    pub fn insert(&mut self, name: String, value: Value) {
+        match self.entries.iter_mut().find(|(key, _)| *key == name) {
+            Some(entry) => entry.1 = value,
+            None => self.entries.push((name, value)),
+        }
+    }
+
+    /// Get a value by name (a leading `$.` is ignored)
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        let name = strip_path_prefix(name);
+        self.entries.iter().find(|(key, _)| key == name).map(|(_, value)| value)
+    }
+
+    /// Get value names in order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(|(key, _)| key.as_str())
+    }
+
+    /// Get all entries in order
+    pub fn entries(&self) -> &[(String, Value)] {
+        &self.entries
+    }
+}
+
+/// Exporter applying a rule's export mappings
+#[derive(Debug, Clone, Default)]
+pub struct ResultExporter {
+    rules: ExportRules,
+}
+
+impl ResultExporter {
+    /// Create an exporter from rule export mappings
+    pub fn new(rules: ExportRules) -> Self {
+        Self { rules }
+    }
+
+    /// Export parse results in the given format
+    pub fn export(&self, results: &[ParseResult], format: ExportFormat) -> NetworkResult<String> {
+        match format {
+            ExportFormat::Influx => {
+                let mapping = self.rules.influx.clone()
+                    .unwrap_or_else(|| default_influx_export(results));
+                let lines = results.iter()
+                    .map(|result| to_influx_line(result, &mapping))
+                    .collect::<NetworkResult<Vec<_>>>()?;
+                Ok(lines.join("\n"))
+            }
+            ExportFormat::Json => {
+                let mapping = self.rules.json.clone().unwrap_or_default();
+                let lines = results.iter()
+                    .map(|result| serde_json::to_string(&to_flat_json(result, &mapping)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(lines.join("\n"))
+            }
+            ExportFormat::Csv => {
+                let mapping = self.rules.csv.clone().unwrap_or_default();
+                to_csv(results, &mapping)
+            }
+        }
+    }
+}
+
+/// Convert a parse result to one line of Influx line protocol
+pub fn to_influx_line(result: &ParseResult, mapping: &InfluxExport) -> NetworkResult<String> {
+    if mapping.measurement.is_empty() {
+        return Err(NetworkError::ParseError("Influx export requires a measurement".to_string()));
+    }
+
+    let record = FlatRecord::from_result(result);
+    let mut line = escape_influx(&mapping.measurement, &[',', ' ']);
+
+    for tag in &mapping.tags {
+        let Some(value) = record.get(tag) else {
+            continue;
+        };
+        let text = value_to_text(value);
+        if text.is_empty() {
+            continue;
+        }
+        line.push(',');
+        line.push_str(&escape_influx(strip_path_prefix(tag), &[',', '=', ' ']));
+        line.push('=');
+        line.push_str(&escape_influx(&text, &[',', '=', ' ']));
+    }
+
+    let field_names: Vec<&str> = if mapping.fields.is_empty() {
+        record.names()
+            .filter(|name| !name.starts_with("envelope."))
+            .filter(|name| !mapping.tags.iter().any(|tag| strip_path_prefix(tag) == *name))
+            .filter(|name| Some(*name) != mapping.timestamp.as_deref().map(strip_path_prefix))
+            .collect()
+    } else {
+        mapping.fields.iter().map(|name| strip_path_prefix(name)).collect()
+    };
+
+    let fields: Vec<String> = field_names.iter()
+        .filter_map(|name| {
+            let value = influx_field_value(record.get(name)?)?;
+            Some(format!("{}={}", escape_influx(name, &[',', '=', ' ']), value))
+        })
+        .collect();
+
+    if fields.is_empty() {
+        return Err(NetworkError::ParseError(format!(
+            "Influx export of '{}' has no field values",
+            mapping.measurement
+        )));
+    }
+
+    line.push(' ');
+    line.push_str(&fields.join(","));
+    line.push(' ');
+    line.push_str(&influx_timestamp(result, &record, mapping)?.to_string());
+
+    Ok(line)
+}
+
+/// Convert a parse result to a flat JSON object
+pub fn to_flat_json(result: &ParseResult, mapping: &JsonExport) -> Value {
+    let record = FlatRecord::from_result(result);
+    let mut output = serde_json::Map::new();
+
+    for (name, value) in record.entries() {
+        if !mapping.include.is_empty()
+            && !mapping.include.iter().any(|include| strip_path_prefix(include) == name)
+        {
+            continue;
+        }
+
+        let key = mapping.rename.iter()
+            .find(|(from, _)| strip_path_prefix(from) == name)
+            .map(|(_, to)| strip_path_prefix(to).to_string())
+            .unwrap_or_else(|| name.clone());
+        output.insert(key, value.clone());
+    }
+
+    let mut moves: Vec<_> = mapping.move_to.iter().collect();
+    moves.sort_by(|a, b| a.0.cmp(b.0));
+    for (from, to) in moves {
+        if let Some(value) = output.remove(strip_path_prefix(from)) {
+            insert_path(&mut output, strip_path_prefix(to), value);
+        }
+    }
+
+    Value::Object(output)
+}
+
+/// Convert parse results to CSV rows
+pub fn to_csv(results: &[ParseResult], mapping: &CsvExport) -> NetworkResult<String> {
+    if !mapping.delimiter.is_ascii() {
+        return Err(NetworkError::ParseError(format!(
+            "CSV delimiter must be an ASCII character, got '{}'",
+            mapping.delimiter
+        )));
+    }
+
+    let records: Vec<FlatRecord> = results.iter().map(FlatRecord::from_result).collect();
+
+    let columns: Vec<String> = if mapping.columns.is_empty() {
+        let mut columns: Vec<String> = Vec::new();
+        for record in &records {
+            for name in record.names() {
+                if !columns.iter().any(|c| c == name) {
+                    columns.push(name.to_string());
+                }
+            }
+        }
+        columns
+    } else {
+        mapping.columns.iter().map(|c| strip_path_prefix(c).to_string()).collect()
+    };
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(mapping.delimiter as u8)
+        .from_writer(Vec::new());
+
+    let csv_error = |e: csv::Error| NetworkError::ParseError(format!("Failed to write CSV: {}", e));
+
+    if mapping.header {
+        writer.write_record(&columns).map_err(csv_error)?;
+    }
+
+    for record in &records {
+        let row: Vec<String> = columns.iter()
+            .map(|column| record.get(column).map(value_to_text).unwrap_or_default())
+            .collect();
+        writer.write_record(&row).map_err(csv_error)?;
+    }
+
+    let bytes = writer.into_inner()
+        .map_err(|e| NetworkError::ParseError(format!("Failed to write CSV: {}", e)))?;
+    String::from_utf8(bytes)
+        .map_err(|e| NetworkError::ParseError(format!("Invalid CSV output: {}", e)))
+}
+
+/// Default Influx mapping: measurement named after the protocol, all values as fields
+fn default_influx_export(results: &[ParseResult]) -> InfluxExport {
+    let measurement = results.first()
+        .map(|result| result.protocol.name.clone())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "parsed".to_string());
+
+    InfluxExport {
+        measurement,
+        tags: Vec::new(),
+        fields: Vec::new(),
+        timestamp: None,
+        timestamp_format: None,
+        timestamp_unit: "s".to_string(),
+    }
+}
+
+/// Resolve the Influx timestamp in nanoseconds
+fn influx_timestamp(result: &ParseResult, record: &FlatRecord, mapping: &InfluxExport) -> NetworkResult<i64> {
+    let time = match &mapping.timestamp {
+        Some(name) => {
+            let value = record.get(name).ok_or_else(|| NetworkError::ParseError(format!(
+                "Timestamp value '{}' not found",
+                name
+            )))?;
+            parse_timestamp(value, mapping)?
+        }
+        None => record.get("envelope.received_at")
+            .and_then(Value::as_str)
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or(result.metadata.timestamp),
+    };
+
+    time.timestamp_nanos_opt()
+        .ok_or_else(|| NetworkError::ParseError(format!("Timestamp out of range: {}", time)))
+}
+
+/// Parse a timestamp value using the mapping's format or unit
+fn parse_timestamp(value: &Value, mapping: &InfluxExport) -> NetworkResult<DateTime<Utc>> {
+    let invalid = || NetworkError::ParseError(format!("Invalid timestamp value: {}", value));
+
+    match value {
+        Value::Number(n) => {
+            let n = n.as_i64().ok_or_else(invalid)?;
+            let time = match mapping.timestamp_unit.as_str() {
+                "s" => Utc.timestamp_opt(n, 0).single(),
+                "ms" => Utc.timestamp_millis_opt(n).single(),
+                "us" => Utc.timestamp_micros(n).single(),
+                "ns" => Some(Utc.timestamp_nanos(n)),
+                unit => {
+                    return Err(NetworkError::ParseError(format!("Unsupported timestamp unit: {}", unit)));
+                }
+            };
+            time.ok_or_else(invalid)
+        }
+        Value::String(s) => match &mapping.timestamp_format {
+            Some(format) => NaiveDateTime::parse_from_str(s, format)
+                .map(|t| t.and_utc())
+                .map_err(|_| invalid()),
+            None => DateTime::parse_from_rfc3339(s)
+                .map(|t| t.with_timezone(&Utc))
+                .map_err(|_| invalid()),
+        },
+        _ => Err(invalid()),
+    }
+}
+
+/// Format a value as an Influx field value
+fn influx_field_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) if n.is_f64() => Some(n.to_string()),
+        Value::Number(n) => Some(format!("{}i", n)),
+        Value::String(s) => Some(format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))),
+        other => Some(format!("\"{}\"", other.to_string().replace('\\', "\\\\").replace('"', "\\\""))),
+    }
+}
+
+/// Escape special characters in Influx measurement, tag and field keys
+fn escape_influx(text: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if special.contains(&c) || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Convert a field value to JSON
+fn field_value_to_json(value: &FieldValue) -> Value {
+    match value {
+        FieldValue::UInt(v) => Value::from(*v),
+        FieldValue::Int(v) => Value::from(*v),
+        FieldValue::Float(v) => serde_json::Number::from_f64(*v).map(Value::Number).unwrap_or(Value::Null),
+        FieldValue::String(v) => Value::String(v.clone()),
+        FieldValue::Bytes(v) => Value::String(hex::encode(v)),
+        FieldValue::Bool(v) => Value::Bool(*v),
+        FieldValue::Array(values) => Value::Array(values.iter().map(field_value_to_json).collect()),
+        FieldValue::Object(values) => Value::Object(
+            values.iter().map(|(k, v)| (k.clone(), field_value_to_json(v))).collect()
+        ),
+        FieldValue::Null => Value::Null,
+    }
+}
+
+/// Convert a value to plain text (strings without quotes)
+fn value_to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Insert a value at a dotted path, creating objects as needed
+fn insert_path(object: &mut serde_json::Map<String, Value>, path: &str, value: Value) {
+    match path.split_once('.') {
+        Some((head, rest)) => {
+            let child = object.entry(head.to_string())
+                .or_insert_with(|| Value::Object(serde_json::Map::new()));
+            if !child.is_object() {
+                *child = Value::Object(serde_json::Map::new());
+            }
+            if let Value::Object(child) = child {
+                insert_path(child, rest, value);
+            }
+        }
+        None => {
+            object.insert(path.to_string(), value);
+        }
+    }
+}
+
+/// Strip the `$.` prefix used by KPT paths
+fn strip_path_prefix(name: &str) -> &str {
+    name.strip_prefix("$.").unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::result::{ParsedField, ParsedFields, ProtocolInfo, FieldMetadata, FieldValidationResult};
+
+    fn field(name: &str, value: FieldValue) -> ParsedField {
+        ParsedField {
+            name: name.to_string(),
+            value,
+            raw_bytes: vec![],
+            offset: 0,
+            length: 0,
+            field_type: "test".to_string(),
+            description: String::new(),
+            valid: true,
+            validation: FieldValidationResult::default(),
+            nested_fields: None,
+            metadata: FieldMetadata::default(),
+        }
+    }
+
+    fn sample_result() -> ParseResult {
+        let mut fields = ParsedFields::new();
+        fields.add_field("val".to_string(), field("val", FieldValue::Float(12.5)));
+        fields.add_field("count".to_string(), field("count", FieldValue::UInt(3)));
+        fields.add_field("device_id".to_string(), field("device_id", FieldValue::String("dev 1".to_string())));
+        fields.add_field("parsed_time".to_string(), field("parsed_time", FieldValue::String("20240101120000".to_string())));
+
+        let mut code = field("code", FieldValue::String("a01".to_string()));
+        code.metadata.extra.insert("name".to_string(), serde_json::json!("PM2.5"));
+        fields.add_field("code".to_string(), code);
+
+        let mut result = ParseResult::success(
+            ProtocolInfo {
+                name: "Test".to_string(),
+                version: "1.0".to_string(),
+                parser_id: "test".to_string(),
+                confidence: 1.0,
+            },
+            fields,
+            vec![],
+            0,
+        );
+        result.metadata.extra.insert("envelope".to_string(), serde_json::json!({
+            "session_id": "s1",
+            "variables": { "site": "A1" }
+        }));
+        result
+    }
+
+    #[test]
+    fn test_influx_line() {
+        let mapping = InfluxExport {
+            measurement: "env reading".to_string(),
+            tags: vec!["site".to_string(), "device_id".to_string(), "code.name".to_string()],
+            fields: vec!["$.val".to_string(), "count".to_string(), "missing".to_string()],
+            timestamp: Some("parsed_time".to_string()),
+            timestamp_format: Some("%Y%m%d%H%M%S".to_string()),
+            timestamp_unit: "s".to_string(),
+        };
+
+        let line = to_influx_line(&sample_result(), &mapping).unwrap();
+        assert_eq!(
+            line,
+            "env\\ reading,site=A1,device_id=dev\\ 1,code.name=PM2.5 val=12.5,count=3i 1704110400000000000"
+        );
+    }
+
+    #[test]
+    fn test_flat_json_rename_and_move() {
+        let mut mapping = JsonExport::default();
+        mapping.rename.insert("$.val".to_string(), "$.value".to_string());
+        mapping.move_to.insert("code.name".to_string(), "labels.name".to_string());
+
+        let json = to_flat_json(&sample_result(), &mapping);
+        assert_eq!(json["value"], serde_json::json!(12.5));
+        assert!(json.get("val").is_none());
+        assert_eq!(json["labels"]["name"], "PM2.5");
+        assert_eq!(json["site"], "A1");
+        assert_eq!(json["envelope.session_id"], "s1");
+    }
+
+    #[test]
+    fn test_csv_rows() {
+        let mapping = CsvExport {
+            columns: vec!["device_id".to_string(), "val".to_string(), "site".to_string()],
+            delimiter: ';',
+            header: true,
+        };
+
+        let csv = to_csv(&[sample_result(), sample_result()], &mapping).unwrap();
+        assert_eq!(csv, "device_id;val;site\ndev 1;12.5;A1\ndev 1;12.5;A1\n");
+
+        let exporter = ResultExporter::default();
+        let json = exporter.export(&[sample_result()], ExportFormat::Json).unwrap();
+        assert!(json.contains("\"count\":3"));
+    }
+}
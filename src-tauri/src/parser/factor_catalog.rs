@@ -0,0 +1,104 @@
+//! Built-in HJ212 factor code catalog
+//!
+//! Standard HJ 212-2017 water and air pollutant codes with their metadata,
+//! and the mapping from the HJ/T 212-2005 codes they replaced. The catalog
+//! is the base every `FactorTranslator` starts from; rule `factor_codes`
+//! override it entry by entry.
+
+use crate::parser::factor_translator::FactorDefinition;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Catalog source, in the shape of a rule's `factor_codes`
+const HJ212_FACTORS: &str = include_str!("builtin/hj212_factors.yaml");
+
+#[derive(Deserialize)]
+struct CatalogFile {
+    factors: HashMap<String, CatalogEntry>,
+}
+
+#[derive(Deserialize)]
+struct CatalogEntry {
+    #[serde(flatten)]
+    definition: FactorDefinition,
+    #[serde(default)]
+    code_2005: Option<String>,
+}
+
+/// Factor definitions with their legacy code mapping
+#[derive(Debug, Clone, Default)]
+pub struct FactorCatalog {
+    definitions: HashMap<String, FactorDefinition>,
+    legacy_codes: HashMap<String, String>,
+}
+
+impl FactorCatalog {
+    /// Parse a catalog file
+    fn parse(source: &str) -> Result<Self, serde_yaml::Error> {
+        let file: CatalogFile = serde_yaml::from_str(source)?;
+        let mut catalog = Self::default();
+
+        for (code, entry) in file.factors {
+            if let Some(code_2005) = entry.code_2005 {
+                catalog.legacy_codes.insert(code_2005, code.clone());
+            }
+            catalog.definitions.insert(code, entry.definition);
+        }
+
+        Ok(catalog)
+    }
+
+    /// Factor definitions by HJ 212-2017 code
+    pub fn definitions(&self) -> &HashMap<String, FactorDefinition> {
+        &self.definitions
+    }
+
+    /// HJ/T 212-2005 codes mapped to the HJ 212-2017 codes replacing them
+    pub fn legacy_codes(&self) -> &HashMap<String, String> {
+        &self.legacy_codes
+    }
+
+    /// HJ 212-2017 code replacing an HJ/T 212-2005 code
+    pub fn standard_code(&self, code_2005: &str) -> Option<&str> {
+        self.legacy_codes.get(code_2005).map(String::as_str)
+    }
+
+    /// HJ/T 212-2005 code of an HJ 212-2017 code
+    pub fn legacy_code(&self, code: &str) -> Option<&str> {
+        self.legacy_codes
+            .iter()
+            .find(|(_, standard)| standard.as_str() == code)
+            .map(|(legacy, _)| legacy.as_str())
+    }
+}
+
+/// Global built-in catalog
+static BUILTIN_CATALOG: OnceLock<FactorCatalog> = OnceLock::new();
+
+/// Get the built-in HJ212 catalog
+pub fn builtin_catalog() -> &'static FactorCatalog {
+    BUILTIN_CATALOG.get_or_init(|| {
+        FactorCatalog::parse(HJ212_FACTORS).expect("built-in HJ212 factor catalog is valid")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_catalog_loads() {
+        let catalog = builtin_catalog();
+
+        let cod = catalog.definitions().get("w01018").unwrap();
+        assert_eq!(cod.unit, "mg/L");
+        assert_eq!(cod.category, "water");
+        assert_eq!(catalog.definitions().get("a34004").unwrap().category, "air");
+
+        assert_eq!(catalog.standard_code("011"), Some("w01018"));
+        assert_eq!(catalog.standard_code("B02"), Some("a00000"));
+        assert_eq!(catalog.legacy_code("w21003"), Some("060"));
+        assert_eq!(catalog.standard_code("w01018"), None);
+    }
+}
@@ -5,6 +5,9 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::parser::factor_catalog::builtin_catalog;
+use crate::parser::schema::ProtocolRule;
+use crate::parser::units;
 use crate::types::{NetworkResult, NetworkError};
 
 /// Factor code definition with metadata
@@ -91,6 +94,34 @@ pub struct ParsedFactor {
 
     /// Quality grade
     pub quality_grade: Option<String>,
+
+    /// HJ 212-2017 code the factor was mapped to from an HJ/T 212-2005 code
+    #[serde(default)]
+    pub standard_code: Option<String>,
+
+    /// Value converted to the canonical unit of its dimension
+    #[serde(default)]
+    pub normalized_value: Option<f64>,
+
+    /// Canonical unit of `normalized_value`
+    #[serde(default)]
+    pub normalized_unit: Option<String>,
+}
+
+impl ParsedFactor {
+    /// Numeric value, if any
+    pub fn numeric_value(&self) -> Option<f64> {
+        match self.value {
+            Some(FactorValue::Float(value)) => Some(value),
+            Some(FactorValue::Integer(value)) => Some(value as f64),
+            _ => None,
+        }
+    }
+
+    /// Value converted to another unit of the same dimension
+    pub fn value_in(&self, unit: &str) -> Option<f64> {
+        units::convert(self.numeric_value()?, &self.unit, unit)
+    }
 }
 
 /// Alarm status for factor values
@@ -146,15 +177,26 @@ pub struct FactorTranslator {
     /// Factor definitions
     factor_definitions: HashMap<String, FactorDefinition>,
     
+    /// HJ/T 212-2005 codes mapped to HJ 212-2017 codes
+    legacy_codes: HashMap<String, String>,
+    
     /// Quality flag descriptions
     quality_flags: QualityFlags,
 }
 
 impl FactorTranslator {
-    /// Create a new factor translator
+    /// Create a translator with the built-in HJ212 catalog
     pub fn new() -> Self {
+        let mut translator = Self::empty();
+        translator.merge_builtin_catalog();
+        translator
+    }
+    
+    /// Create a translator without any factor definitions
+    pub fn empty() -> Self {
         Self {
             factor_definitions: HashMap::new(),
+            legacy_codes: HashMap::new(),
             quality_flags: QualityFlags::default(),
         }
     }
@@ -164,11 +206,42 @@ impl FactorTranslator {
         self.factor_definitions = definitions;
     }
     
+    /// Add the built-in catalog's definitions and HJ/T 212-2005 code mapping,
+    /// keeping codes that are already defined
+    pub fn merge_builtin_catalog(&mut self) {
+        let catalog = builtin_catalog();
+        for (code, definition) in catalog.definitions() {
+            self.factor_definitions.entry(code.clone()).or_insert_with(|| definition.clone());
+        }
+        for (legacy, code) in catalog.legacy_codes() {
+            self.legacy_codes.entry(legacy.clone()).or_insert_with(|| code.clone());
+        }
+    }
+    
     /// Add a single factor definition
     pub fn add_factor_definition(&mut self, code: String, definition: FactorDefinition) {
         self.factor_definitions.insert(code, definition);
     }
     
+    /// Create a translator from the built-in catalog overridden by the
+    /// factor codes of a protocol rule
+    pub fn from_rule(rule: &ProtocolRule) -> NetworkResult<Self> {
+        let mut translator = Self::new();
+        
+        for (code, definition_value) in rule.factor_codes.iter().flatten() {
+            let definition: FactorDefinition = serde_yaml::from_value(definition_value.clone())
+                .map_err(|e| NetworkError::ParseError(format!("Failed to parse factor definition for {}: {}", code, e)))?;
+            translator.add_factor_definition(code.clone(), definition);
+        }
+        
+        Ok(translator)
+    }
+    
+    /// Get all factor definitions by code
+    pub fn definitions(&self) -> &HashMap<String, FactorDefinition> {
+        &self.factor_definitions
+    }
+    
     /// Get factor definition by code
     pub fn get_factor_definition(&self, code: &str) -> Option<&FactorDefinition> {
         self.factor_definitions.get(code)
@@ -197,109 +270,131 @@ impl FactorTranslator {
                 continue;
             }
             
-            let factor_code = parts[0].trim();
-            let mut parsed_factor = ParsedFactor {
-                code: factor_code.to_string(),
-                name: "未知因子".to_string(),
-                name_en: None,
-                unit: "".to_string(),
-                category: "unknown".to_string(),
-                value: None,
-                quality_flag: None,
-                quality_description: None,
-                validation_errors: Vec::new(),
-                is_unknown: true,
-                alarm_status: None,
-                standard_value: None,
-                quality_grade: None,
-            };
+            let value = parts.get(1).map(|value| value.trim());
+            let flag = parts.get(2).map(|flag| flag.trim());
+            let parsed_factor = self.translate_factor(parts[0].trim(), value, flag);
             
-            // Try to get factor definition
-            if let Some(definition) = self.factor_definitions.get(factor_code) {
-                parsed_factor.name = definition.name.clone();
-                parsed_factor.name_en = definition.name_en.clone();
-                parsed_factor.unit = definition.unit.clone();
-                parsed_factor.category = definition.category.clone();
-                parsed_factor.is_unknown = false;
-                parsed_factor.standard_value = definition.standard_value;
-                parsed_factor.quality_grade = definition.quality_grade.clone();
-                
-                // Parse value if present
-                if parts.len() >= 2 {
-                    let value_str = parts[1].trim();
-                    if !value_str.is_empty() && value_str != "-" {
-                        match self.parse_factor_value(value_str, definition) {
-                            Ok(value) => {
-                                parsed_factor.value = Some(value);
-                                
-                                // Validate range and check alarms
-                                if let Some(FactorValue::Float(val)) = &parsed_factor.value {
-                                    // Check range
-                                    if let Some(range) = &definition.range {
-                                        if *val < range[0] || *val > range[1] {
-                                            parsed_factor.validation_errors.push(
-                                                format!("数值{}超出有效范围[{}, {}]", val, range[0], range[1])
-                                            );
-                                            parsed_factor.alarm_status = Some(AlarmStatus::OutOfRange);
-                                        }
+            parsed_factors.push(parsed_factor);
+        }
+        
+        Ok(parsed_factors)
+    }
+    
+    /// Translate one factor code with its value and quality flag
+    pub fn translate_factor(&self, factor_code: &str, value: Option<&str>, flag: Option<&str>) -> ParsedFactor {
+        let mut parsed_factor = ParsedFactor {
+            code: factor_code.to_string(),
+            name: "未知因子".to_string(),
+            name_en: None,
+            unit: "".to_string(),
+            category: "unknown".to_string(),
+            value: None,
+            quality_flag: None,
+            quality_description: None,
+            validation_errors: Vec::new(),
+            is_unknown: true,
+            alarm_status: None,
+            standard_value: None,
+            quality_grade: None,
+            standard_code: None,
+            normalized_value: None,
+            normalized_unit: None,
+        };
+
+        // Try to get factor definition, mapping HJ/T 212-2005 codes when needed
+        let definition = self.factor_definitions.get(factor_code).or_else(|| {
+            let standard_code = self.legacy_codes.get(factor_code)?;
+            parsed_factor.standard_code = Some(standard_code.clone());
+            self.factor_definitions.get(standard_code)
+        });
+        if let Some(definition) = definition {
+            parsed_factor.name = definition.name.clone();
+            parsed_factor.name_en = definition.name_en.clone();
+            parsed_factor.unit = definition.unit.clone();
+            parsed_factor.category = definition.category.clone();
+            parsed_factor.is_unknown = false;
+            parsed_factor.standard_value = definition.standard_value;
+            parsed_factor.quality_grade = definition.quality_grade.clone();
+
+            // Parse value if present
+            if let Some(value_str) = value {
+                if !value_str.is_empty() && value_str != "-" {
+                    match self.parse_factor_value(value_str, definition) {
+                        Ok(value) => {
+                            parsed_factor.value = Some(value);
+
+                            // Validate range and check alarms
+                            if let Some(FactorValue::Float(val)) = &parsed_factor.value {
+                                // Check range
+                                if let Some(range) = &definition.range {
+                                    if *val < range[0] || *val > range[1] {
+                                        parsed_factor.validation_errors.push(
+                                            format!("数值{}超出有效范围[{}, {}]", val, range[0], range[1])
+                                        );
+                                        parsed_factor.alarm_status = Some(AlarmStatus::OutOfRange);
                                     }
+                                }
 
-                                    // Check alarm thresholds
-                                    if parsed_factor.alarm_status.is_none() {
-                                        if let Some(high_threshold) = definition.alarm_high {
-                                            if *val > high_threshold {
-                                                parsed_factor.alarm_status = Some(AlarmStatus::HighAlarm);
-                                            }
+                                // Check alarm thresholds
+                                if parsed_factor.alarm_status.is_none() {
+                                    if let Some(high_threshold) = definition.alarm_high {
+                                        if *val > high_threshold {
+                                            parsed_factor.alarm_status = Some(AlarmStatus::HighAlarm);
                                         }
+                                    }
 
-                                        if let Some(low_threshold) = definition.alarm_low {
-                                            if *val < low_threshold {
-                                                parsed_factor.alarm_status = Some(AlarmStatus::LowAlarm);
-                                            }
+                                    if let Some(low_threshold) = definition.alarm_low {
+                                        if *val < low_threshold {
+                                            parsed_factor.alarm_status = Some(AlarmStatus::LowAlarm);
                                         }
+                                    }
 
-                                        // If no alarms, set as normal
-                                        if parsed_factor.alarm_status.is_none() {
-                                            parsed_factor.alarm_status = Some(AlarmStatus::Normal);
-                                        }
+                                    // If no alarms, set as normal
+                                    if parsed_factor.alarm_status.is_none() {
+                                        parsed_factor.alarm_status = Some(AlarmStatus::Normal);
                                     }
                                 }
                             }
-                            Err(e) => {
-                                parsed_factor.validation_errors.push(
-                                    format!("数值解析错误: {}", e)
-                                );
-                            }
                         }
-                    }
-                }
-                
-                // Parse quality flag if present
-                if parts.len() >= 3 {
-                    let flag = parts[2].trim();
-                    if !flag.is_empty() {
-                        parsed_factor.quality_flag = Some(flag.to_string());
-                        parsed_factor.quality_description = self.quality_flags
-                            .get_description(flag)
-                            .map(|s| s.clone());
-                        
-                        if parsed_factor.quality_description.is_none() {
+                        Err(e) => {
                             parsed_factor.validation_errors.push(
-                                format!("未知的质量标识: {}", flag)
+                                format!("数值解析错误: {}", e)
                             );
                         }
                     }
                 }
-            } else {
-                parsed_factor.validation_errors.push(
-                    format!("未知的因子代码: {}", factor_code)
-                );
             }
-            
-            parsed_factors.push(parsed_factor);
+
+            // Parse quality flag if present
+            if let Some(flag) = flag {
+                if !flag.is_empty() {
+                    parsed_factor.quality_flag = Some(flag.to_string());
+                    parsed_factor.quality_description = self.quality_flags
+                        .get_description(flag)
+                        .cloned();
+
+                    if parsed_factor.quality_description.is_none() {
+                        parsed_factor.validation_errors.push(
+                            format!("未知的质量标识: {}", flag)
+                        );
+                    }
+                }
+            }
+        } else {
+            parsed_factor.validation_errors.push(
+                format!("未知的因子代码: {}", factor_code)
+            );
         }
-        
-        Ok(parsed_factors)
+
+        // Express the value in the canonical unit of its dimension
+        if let Some((value, unit)) = parsed_factor.numeric_value()
+            .and_then(|value| units::normalize(value, &parsed_factor.unit))
+        {
+            parsed_factor.normalized_value = Some(value);
+            parsed_factor.normalized_unit = Some(unit.to_string());
+        }
+
+        parsed_factor
     }
     
     /// Parse factor value according to its data type
@@ -371,3 +466,42 @@ impl Default for FactorTranslator {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_catalog_with_legacy_codes_and_units() {
+        let translator = FactorTranslator::new();
+
+        let pm25 = translator.translate_factor("a34004", Some("35"), Some("N"));
+        assert!(!pm25.is_unknown);
+        assert_eq!(pm25.unit, "μg/m³");
+        assert_eq!(pm25.normalized_unit.as_deref(), Some("mg/m³"));
+        assert!((pm25.normalized_value.unwrap() - 0.035).abs() < 1e-9);
+        assert!((pm25.value_in("ug/m3").unwrap() - 35.0).abs() < 1e-9);
+
+        // HJ/T 212-2005 COD code
+        let cod = translator.translate_factor("011", Some("-1.5"), None);
+        assert_eq!(cod.standard_code.as_deref(), Some("w01018"));
+        assert_eq!(cod.name, "化学需氧量");
+
+        assert!(FactorTranslator::empty().translate_factor("a34004", None, None).is_unknown);
+    }
+
+    #[test]
+    fn test_load_replaces_and_merge_keeps_definitions() {
+        let mut custom = FactorTranslator::new().definitions()["a34004"].clone();
+        custom.name = "Custom".to_string();
+
+        let mut translator = FactorTranslator::new();
+        translator.load_factor_definitions(HashMap::from([("a34004".to_string(), custom)]));
+        assert_eq!(translator.definitions().len(), 1);
+        assert_eq!(translator.translate_factor("a34004", None, None).name, "Custom");
+
+        translator.merge_builtin_catalog();
+        assert_eq!(translator.translate_factor("a34004", None, None).name, "Custom");
+        assert!(!translator.translate_factor("w01018", None, None).is_unknown);
+    }
+}
@@ -3,6 +3,7 @@
 //! This module handles frame synchronization, boundary detection,
 //! and frame extraction from data streams.
 
+use crate::parser::bcd;
 use crate::parser::schema::{FramingRule, LengthField, LengthEncoding, Endianness};
 use crate::types::{NetworkResult, NetworkError};
 
@@ -186,6 +187,12 @@ impl FrameDetector {
             frame_length + length_field.offset + length_field.length
         };
         
+        // The length field counts data bytes only; block CRCs come on top
+        let total_length = match &self.rules.block_crc {
+            Some(block_crc) => block_crc.wire_length(total_length)?,
+            None => total_length,
+        };
+        
         self.state.current_state = FrameState::ReadingData { expected_length: total_length };
         self.process_buffer()
     }
@@ -196,8 +203,13 @@ impl FrameDetector {
             // Use end delimiter
             if let Some(ref end_delimiter) = self.rules.end_delimiter {
                 let delimiter_bytes = end_delimiter.as_bytes();
+                // Search past the start delimiter, which may be the same bytes
+                let skip = self.rules.start_delimiter.as_ref()
+                    .map(|start| start.len())
+                    .unwrap_or(0)
+                    .min(self.state.buffer.len());
                 
-                if let Some(pos) = self.find_pattern(&self.state.buffer, delimiter_bytes) {
+                if let Some(pos) = self.find_pattern(&self.state.buffer[skip..], delimiter_bytes).map(|pos| pos + skip) {
                     // Extract frame data including delimiter
                     let frame_data = self.state.buffer.drain(..pos + delimiter_bytes.len()).collect();
                     
@@ -297,8 +309,11 @@ impl FrameDetector {
                     )))
             }
             LengthEncoding::Bcd => {
-                // TODO: Implement BCD parsing
-                Err(NetworkError::ParseError("BCD length encoding not yet implemented".to_string()))
+                let value = match length_field.endian {
+                    Endianness::Little => bcd::decode(&data.iter().rev().copied().collect::<Vec<u8>>())?,
+                    _ => bcd::decode(data)?,
+                };
+                Ok(value as usize)
             }
         }
     }
@@ -347,6 +362,7 @@ mod tests {
             fixed_size: Some(4),
             escape_rules: vec![],
             frame_validation: FrameValidation::default(),
+            block_crc: None,
         };
         
         let mut detector = FrameDetector::new(rules);
@@ -367,6 +383,7 @@ mod tests {
             fixed_size: None,
             escape_rules: vec![],
             frame_validation: FrameValidation::default(),
+            block_crc: None,
         };
         
         let mut detector = FrameDetector::new(rules);
@@ -377,4 +394,24 @@ mod tests {
         assert_eq!(frames[0].data, b"##Hello\r\n");
         assert_eq!(frames[1].data, b"##World\r\n");
     }
+
+    #[test]
+    fn test_same_start_and_end_delimiter() {
+        let rules = FramingRule {
+            start_delimiter: Some("~".to_string()),
+            end_delimiter: Some("~".to_string()),
+            length_field: None,
+            fixed_size: None,
+            escape_rules: vec![],
+            frame_validation: FrameValidation::default(),
+            block_crc: None,
+        };
+
+        let mut detector = FrameDetector::new(rules);
+        let mut frames = detector.detect_frames(b"xx~AB~~C").unwrap();
+        frames.extend(detector.detect_frames(b"D~").unwrap());
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].data, b"~AB~");
+        assert_eq!(frames[1].data, b"~CD~");
+    }
 }
@@ -0,0 +1,962 @@
+//! HJ212 environmental monitoring protocol engine
+//!
+//! Decodes HJ/T 212-2005 and HJ 212-2017 packets:
+//! `##` + four-digit data length + data segment + CRC16 (hex) + CRLF.
+//! The data segment header (`QN/ST/CN/PW/MN/Flag/PNUM/PNO`) is decoded into
+//! typed fields and the `CP=&&...&&` area into one record per factor with
+//! its `Rtd/Min/Avg/Max/Cou/Flag` statistics. `Hj212Parser` exposes the
+//! engine through the `Parser` trait as the built-in `hj212` parser.
+
+use crate::parser::framing::FrameDetector;
+use crate::parser::result::{
+    CrcValidationResult, ErrorSeverity, FieldValue, ParseError, ParseResult, ParseWarning,
+    ParsedField, ParsedFields, ProtocolInfo,
+};
+use crate::parser::schema::{FrameValidation, FramingRule};
+use crate::parser::validation_report::ValidationReport;
+use crate::parser::{Parser, ProtocolInfo as ParserProtocolInfo};
+use crate::types::{NetworkResult, NetworkError};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// ID of the built-in HJ212 parser
+pub const HJ212_PARSER_ID: &str = "hj212";
+
+/// Packet header marker
+const HEADER: &[u8] = b"##";
+
+/// Packet trailer
+const TRAILER: &[u8] = b"\r\n";
+
+/// Largest data segment the four-digit length field can describe
+pub const MAX_DATA_LENGTH: usize = 9999;
+
+/// HJ212 CRC16 over the data segment (poly 0xA001, init 0xFFFF, high byte folded first)
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for &byte in data {
+        crc = (crc >> 8) ^ byte as u16;
+        for _ in 0..8 {
+            let carry = crc & 0x0001;
+            crc >>= 1;
+            if carry != 0 {
+                crc ^= 0xA001;
+            }
+        }
+    }
+    crc
+}
+
+/// Wrap a data segment into a complete packet
+pub fn encode_frame(data_segment: &str) -> NetworkResult<String> {
+    if data_segment.len() > MAX_DATA_LENGTH {
+        return Err(NetworkError::ParseError(format!(
+            "HJ212 data segment of {} bytes exceeds {} bytes",
+            data_segment.len(),
+            MAX_DATA_LENGTH
+        )));
+    }
+
+    Ok(format!(
+        "##{:04}{}{:04X}\r\n",
+        data_segment.len(),
+        data_segment,
+        crc16(data_segment.as_bytes())
+    ))
+}
+
+/// Protocol revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hj212Version {
+    /// HJ/T 212-2005
+    #[serde(rename = "2005")]
+    V2005,
+    /// HJ 212-2017
+    #[serde(rename = "2017")]
+    V2017,
+}
+
+impl Hj212Version {
+    /// Version bits carried in the 2017 `Flag`
+    fn flag_bits(self) -> u8 {
+        match self {
+            Hj212Version::V2005 => 0,
+            Hj212Version::V2017 => 1,
+        }
+    }
+
+    /// Year of the standard
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Hj212Version::V2005 => "2005",
+            Hj212Version::V2017 => "2017",
+        }
+    }
+
+    /// Work out the revision a decoded packet follows
+    ///
+    /// A 2017 `Flag` carries version bits. Without them the factor codes
+    /// decide: 2017 codes are a letter and five digits (`w01018-Rtd`), 2005
+    /// codes three characters (`B01-Rtd`, `011-Avg`). The `EFlag` and
+    /// `SampleTime` statistics only exist in 2017.
+    pub fn detect(header: &Hj212Header, cp: &Hj212Cp) -> Self {
+        if header.flag.map(|flag| flag.version_bits != 0).unwrap_or(false) {
+            return Hj212Version::V2017;
+        }
+        if cp.factors.iter().any(|factor| factor.eflag.is_some() || factor.sample_time.is_some()) {
+            return Hj212Version::V2017;
+        }
+
+        let is_2017_code = |code: &str| {
+            code.len() == 6
+                && code.as_bytes()[0].is_ascii_lowercase()
+                && code.bytes().skip(1).all(|b| b.is_ascii_digit())
+        };
+        let is_2005_code = |code: &str| code.len() == 3 && code.bytes().all(|b| b.is_ascii_alphanumeric());
+
+        let codes_2017 = cp.factors.iter().filter(|factor| is_2017_code(&factor.code)).count();
+        let codes_2005 = cp.factors.iter().filter(|factor| is_2005_code(&factor.code)).count();
+        if codes_2017 > codes_2005 {
+            Hj212Version::V2017
+        } else {
+            Hj212Version::V2005
+        }
+    }
+}
+
+/// Decoded `Flag` field
+///
+/// Bit 0 requests an answer, bit 1 marks a split packet with `PNUM/PNO`,
+/// bits 2..7 carry the standard version (0 = 2005, 1 = 2017).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hj212Flag {
+    /// Raw flag value
+    pub raw: u8,
+    /// Receiver must answer the command
+    pub answer_required: bool,
+    /// Packet is one part of a split message
+    pub split: bool,
+    /// Version bits
+    pub version_bits: u8,
+}
+
+impl Hj212Flag {
+    /// Decode a raw flag value
+    pub fn from_raw(raw: u8) -> Self {
+        Self {
+            raw,
+            answer_required: raw & 0x01 != 0,
+            split: raw & 0x02 != 0,
+            version_bits: raw >> 2,
+        }
+    }
+
+    /// Build a flag for the given version
+    pub fn new(version: Hj212Version, answer_required: bool, split: bool) -> Self {
+        Self::from_raw((version.flag_bits() << 2) | ((split as u8) << 1) | answer_required as u8)
+    }
+
+    /// Protocol version announced by the flag
+    pub fn version(&self) -> Hj212Version {
+        if self.version_bits == 0 {
+            Hj212Version::V2005
+        } else {
+            Hj212Version::V2017
+        }
+    }
+}
+
+/// Data segment header
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hj212Header {
+    /// Request number (`yyyyMMddHHmmssSSS`)
+    pub qn: Option<String>,
+    /// System code
+    pub st: Option<String>,
+    /// Command code
+    pub cn: Option<String>,
+    /// Access password
+    pub pw: Option<String>,
+    /// Device unique identifier
+    pub mn: Option<String>,
+    /// Flag
+    pub flag: Option<Hj212Flag>,
+    /// Total packet count of a split message
+    pub pnum: Option<u32>,
+    /// Packet number of a split message
+    pub pno: Option<u32>,
+    /// Header keys not defined by the standard
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Hj212Header {
+    /// Time encoded in the request number
+    pub fn qn_time(&self) -> Option<NaiveDateTime> {
+        let qn = self.qn.as_deref()?;
+        NaiveDateTime::parse_from_str(qn.get(..14)?, "%Y%m%d%H%M%S").ok()
+    }
+
+    /// Description of the system code
+    pub fn st_name(&self) -> Option<&'static str> {
+        system_name(self.st.as_deref()?)
+    }
+
+    /// Description of the command code
+    pub fn cn_name(&self) -> Option<&'static str> {
+        command_name(self.cn.as_deref()?)
+    }
+
+    /// Encode the header followed by the given CP content
+    pub fn encode(&self, cp: &str) -> String {
+        let mut parts = Vec::new();
+        let mut push = |key: &str, value: Option<String>| {
+            if let Some(value) = value {
+                parts.push(format!("{}={}", key, value));
+            }
+        };
+
+        push("QN", self.qn.clone());
+        push("ST", self.st.clone());
+        push("CN", self.cn.clone());
+        push("PW", self.pw.clone());
+        push("MN", self.mn.clone());
+        push("Flag", self.flag.map(|flag| flag.raw.to_string()));
+        push("PNUM", self.pnum.map(|pnum| pnum.to_string()));
+        push("PNO", self.pno.map(|pno| pno.to_string()));
+        for (key, value) in &self.extra {
+            push(key, Some(value.clone()));
+        }
+
+        parts.push(format!("CP=&&{}&&", cp));
+        parts.join(";")
+    }
+}
+
+/// Values reported for one monitoring factor
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hj212Factor {
+    /// Factor code (e.g. `w01018`, `a34004`, `B01`)
+    pub code: String,
+    /// Real-time value
+    pub rtd: Option<f64>,
+    /// Minimum over the interval
+    pub min: Option<f64>,
+    /// Average over the interval
+    pub avg: Option<f64>,
+    /// Maximum over the interval
+    pub max: Option<f64>,
+    /// Cumulative value
+    pub cou: Option<f64>,
+    /// Converted (折算) real-time value
+    pub zs_rtd: Option<f64>,
+    /// Converted minimum
+    pub zs_min: Option<f64>,
+    /// Converted average
+    pub zs_avg: Option<f64>,
+    /// Converted maximum
+    pub zs_max: Option<f64>,
+    /// Data flag (N, F, M, S, D, C, T, B...)
+    pub flag: Option<String>,
+    /// Extended device flag
+    pub eflag: Option<String>,
+    /// Sample time
+    pub sample_time: Option<String>,
+    /// Other statistics and values, as sent
+    pub extra: BTreeMap<String, String>,
+}
+
+impl Hj212Factor {
+    /// Create an empty record for a factor
+    pub fn new(code: &str) -> Self {
+        Self {
+            code: code.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Numeric statistics in wire order, by their wire name
+    pub fn statistics(&self) -> Vec<(&'static str, f64)> {
+        [
+            ("Rtd", self.rtd),
+            ("Min", self.min),
+            ("Avg", self.avg),
+            ("Max", self.max),
+            ("Cou", self.cou),
+            ("ZsRtd", self.zs_rtd),
+            ("ZsMin", self.zs_min),
+            ("ZsAvg", self.zs_avg),
+            ("ZsMax", self.zs_max),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect()
+    }
+
+    /// Most representative value: real-time, else average, else cumulative
+    pub fn primary_value(&self) -> Option<f64> {
+        self.rtd.or(self.avg).or(self.cou).or(self.max).or(self.min)
+    }
+
+    /// Store one `code-Statistic=value` item
+    fn set(&mut self, statistic: &str, value: &str) -> Result<(), String> {
+        let numeric = match statistic {
+            "Rtd" => &mut self.rtd,
+            "Min" => &mut self.min,
+            "Avg" => &mut self.avg,
+            "Max" => &mut self.max,
+            "Cou" => &mut self.cou,
+            "ZsRtd" => &mut self.zs_rtd,
+            "ZsMin" => &mut self.zs_min,
+            "ZsAvg" => &mut self.zs_avg,
+            "ZsMax" => &mut self.zs_max,
+            "Flag" => {
+                self.flag = Some(value.to_string());
+                return Ok(());
+            }
+            "EFlag" => {
+                self.eflag = Some(value.to_string());
+                return Ok(());
+            }
+            "SampleTime" => {
+                self.sample_time = Some(value.to_string());
+                return Ok(());
+            }
+            _ => {
+                self.extra.insert(statistic.to_string(), value.to_string());
+                return Ok(());
+            }
+        };
+
+        match value.trim().parse::<f64>() {
+            Ok(number) => {
+                *numeric = Some(number);
+                Ok(())
+            }
+            Err(_) => {
+                self.extra.insert(statistic.to_string(), value.to_string());
+                Err(format!("{}-{} value '{}' is not a number", self.code, statistic, value))
+            }
+        }
+    }
+
+    /// Encode as a `;`-separated CP group
+    fn encode(&self) -> String {
+        let mut items: Vec<String> = self.statistics()
+            .into_iter()
+            .map(|(name, value)| format!("{}-{}={}", self.code, name, value))
+            .collect();
+
+        for (name, value) in [("SampleTime", &self.sample_time), ("Flag", &self.flag), ("EFlag", &self.eflag)] {
+            if let Some(value) = value {
+                items.push(format!("{}-{}={}", self.code, name, value));
+            }
+        }
+        for (name, value) in &self.extra {
+            items.push(format!("{}-{}={}", self.code, name, value));
+        }
+
+        items.join(",")
+    }
+}
+
+/// Decoded `CP=&&...&&` command parameter area
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hj212Cp {
+    /// Data time (`DataTime=yyyyMMddHHmmss`)
+    pub data_time: Option<NaiveDateTime>,
+    /// Parameters that are not factor values (e.g. `RtdInterval`, `PolId`, `QnRtn`)
+    pub params: BTreeMap<String, String>,
+    /// Factor records in order of first appearance
+    pub factors: Vec<Hj212Factor>,
+}
+
+impl Hj212Cp {
+    /// Decode CP content (without the surrounding `&&`)
+    pub fn decode(content: &str, issues: &mut Vec<String>) -> Self {
+        let mut cp = Self::default();
+
+        for group in content.split(';').filter(|group| !group.trim().is_empty()) {
+            for item in group.split(',').filter(|item| !item.trim().is_empty()) {
+                let Some((key, value)) = item.split_once('=') else {
+                    issues.push(format!("CP item '{}' has no value", item));
+                    continue;
+                };
+                let key = key.trim();
+
+                match key.split_once('-') {
+                    Some((code, statistic)) if !code.is_empty() && !statistic.is_empty() => {
+                        if let Err(e) = cp.factor_mut(code).set(statistic, value) {
+                            issues.push(e);
+                        }
+                    }
+                    _ if key == "DataTime" => {
+                        match NaiveDateTime::parse_from_str(value.trim(), "%Y%m%d%H%M%S") {
+                            Ok(time) => cp.data_time = Some(time),
+                            Err(_) => {
+                                issues.push(format!("DataTime '{}' is not yyyyMMddHHmmss", value));
+                                cp.params.insert(key.to_string(), value.to_string());
+                            }
+                        }
+                    }
+                    _ => {
+                        cp.params.insert(key.to_string(), value.to_string());
+                    }
+                }
+            }
+        }
+
+        cp
+    }
+
+    /// Get a factor record by code
+    pub fn factor(&self, code: &str) -> Option<&Hj212Factor> {
+        self.factors.iter().find(|factor| factor.code == code)
+    }
+
+    /// Get or create a factor record
+    pub fn factor_mut(&mut self, code: &str) -> &mut Hj212Factor {
+        let index = match self.factors.iter().position(|factor| factor.code == code) {
+            Some(index) => index,
+            None => {
+                self.factors.push(Hj212Factor::new(code));
+                self.factors.len() - 1
+            }
+        };
+        &mut self.factors[index]
+    }
+
+    /// Encode the CP content (without the surrounding `&&`)
+    pub fn encode(&self) -> String {
+        let mut groups = Vec::new();
+        if let Some(time) = self.data_time {
+            groups.push(format!("DataTime={}", time.format("%Y%m%d%H%M%S")));
+        }
+        for (key, value) in &self.params {
+            groups.push(format!("{}={}", key, value));
+        }
+        for factor in &self.factors {
+            groups.push(factor.encode());
+        }
+        groups.join(";")
+    }
+}
+
+/// A decoded HJ212 packet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Hj212Packet {
+    /// Protocol revision, from the flag or the factor codes
+    pub version: Hj212Version,
+    /// Length declared in the packet header
+    pub declared_length: usize,
+    /// Actual data segment length
+    pub data_length: usize,
+    /// Data segment header
+    pub header: Hj212Header,
+    /// Command parameter area
+    pub cp: Hj212Cp,
+    /// CRC carried by the packet
+    pub crc: Option<u16>,
+    /// CRC calculated over the data segment
+    pub calculated_crc: u16,
+    /// Non-fatal decoding problems
+    pub issues: Vec<String>,
+}
+
+impl Hj212Packet {
+    /// Build a packet to encode
+    pub fn new(header: Hj212Header, cp: Hj212Cp) -> Self {
+        let version = header.flag.map(|flag| flag.version()).unwrap_or(Hj212Version::V2005);
+        Self {
+            version,
+            declared_length: 0,
+            data_length: 0,
+            header,
+            cp,
+            crc: None,
+            calculated_crc: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    /// Decode a complete packet
+    ///
+    /// Fails only when the frame structure is unusable; length and CRC
+    /// mismatches are reported through `length_valid` and `crc_valid`.
+    pub fn decode(frame: &[u8]) -> NetworkResult<Self> {
+        let frame = frame.strip_suffix(TRAILER).unwrap_or(frame);
+
+        if !frame.starts_with(HEADER) {
+            return Err(NetworkError::ParseError("HJ212 packet must start with '##'".to_string()));
+        }
+        if frame.len() < HEADER.len() + 8 {
+            return Err(NetworkError::ParseError(format!("HJ212 packet too short ({} bytes)", frame.len())));
+        }
+
+        let length_digits = &frame[2..6];
+        let declared_length = std::str::from_utf8(length_digits)
+            .ok()
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse::<usize>().ok())
+            .ok_or_else(|| NetworkError::ParseError(format!(
+                "HJ212 length field '{}' is not four decimal digits",
+                String::from_utf8_lossy(length_digits)
+            )))?;
+
+        // The CRC is always the last four characters, whatever the declared length says
+        let body = &frame[6..];
+        let (data, crc_text) = body.split_at(body.len() - 4);
+        let mut issues = Vec::new();
+
+        let crc = std::str::from_utf8(crc_text)
+            .ok()
+            .and_then(|text| u16::from_str_radix(text, 16).ok());
+        if crc.is_none() {
+            issues.push(format!("CRC '{}' is not four hex digits", String::from_utf8_lossy(crc_text)));
+        }
+
+        let segment = String::from_utf8_lossy(data);
+        let (header_text, cp_content) = match segment.find("CP=&&") {
+            Some(pos) => {
+                let cp = &segment[pos + 5..];
+                let cp = match cp.strip_suffix("&&") {
+                    Some(cp) => cp,
+                    None => {
+                        issues.push("CP area is not terminated by '&&'".to_string());
+                        cp
+                    }
+                };
+                (&segment[..pos], cp)
+            }
+            None => {
+                issues.push("Data segment has no CP area".to_string());
+                (&segment[..], "")
+            }
+        };
+
+        let header = decode_header(header_text, &mut issues);
+        let cp = Hj212Cp::decode(cp_content, &mut issues);
+        let version = Hj212Version::detect(&header, &cp);
+
+        if header.flag.map(|flag| flag.split).unwrap_or(false) && (header.pnum.is_none() || header.pno.is_none()) {
+            issues.push("Flag marks a split packet but PNUM/PNO are missing".to_string());
+        }
+        for (key, value) in [("ST", &header.st), ("CN", &header.cn), ("MN", &header.mn)] {
+            if value.is_none() {
+                issues.push(format!("Header field {} is missing", key));
+            }
+        }
+
+        Ok(Self {
+            version,
+            declared_length,
+            data_length: data.len(),
+            header,
+            cp,
+            crc,
+            calculated_crc: crc16(data),
+            issues,
+        })
+    }
+
+    /// Check the declared length against the data segment
+    pub fn length_valid(&self) -> bool {
+        self.declared_length == self.data_length
+    }
+
+    /// Check the carried CRC against the data segment
+    pub fn crc_valid(&self) -> bool {
+        self.crc == Some(self.calculated_crc)
+    }
+
+    /// Check if the packet decoded without any problem
+    pub fn is_valid(&self) -> bool {
+        self.length_valid() && self.crc_valid() && self.issues.is_empty()
+    }
+
+    /// Check if the sender expects an answer
+    pub fn answer_required(&self) -> bool {
+        self.header.flag.map(|flag| flag.answer_required).unwrap_or(false)
+    }
+
+    /// Encode the packet, recalculating length and CRC
+    pub fn encode(&self) -> NetworkResult<String> {
+        encode_frame(&self.header.encode(&self.cp.encode()))
+    }
+
+    /// Convert to a generic parse result
+    pub fn to_parse_result(&self, raw: &[u8]) -> ParseResult {
+        let text = String::from_utf8_lossy(raw);
+        let mut fields = ParsedFields::new();
+        let mut add = |name: String, value: FieldValue, field_type: &str, key: Option<&str>| {
+            let (offset, length) = key.and_then(|key| locate_value(&text, key)).unwrap_or((0, 0));
+            let raw_bytes = raw.get(offset..offset + length).unwrap_or(&[]);
+            fields.add_field(name.clone(), ParsedField::new(&name, value, field_type, offset, raw_bytes));
+        };
+
+        add("version".to_string(), FieldValue::String(self.version.as_str().to_string()), "string", None);
+        add("length".to_string(), FieldValue::UInt(self.declared_length as u64), "uint", None);
+
+        let header = &self.header;
+        for (name, key, value) in [
+            ("qn", "QN", &header.qn),
+            ("st", "ST", &header.st),
+            ("cn", "CN", &header.cn),
+            ("pw", "PW", &header.pw),
+            ("mn", "MN", &header.mn),
+        ] {
+            if let Some(value) = value {
+                add(name.to_string(), FieldValue::String(value.clone()), "string", Some(key));
+            }
+        }
+        if let Some(name) = header.st_name() {
+            add("st_name".to_string(), FieldValue::String(name.to_string()), "string", None);
+        }
+        if let Some(name) = header.cn_name() {
+            add("cn_name".to_string(), FieldValue::String(name.to_string()), "string", None);
+        }
+        if let Some(flag) = header.flag {
+            add("flag".to_string(), FieldValue::UInt(flag.raw as u64), "uint", Some("Flag"));
+            add("answer_required".to_string(), FieldValue::Bool(flag.answer_required), "bool", None);
+            add("split".to_string(), FieldValue::Bool(flag.split), "bool", None);
+        }
+        if let Some(pnum) = header.pnum {
+            add("pnum".to_string(), FieldValue::UInt(pnum as u64), "uint", Some("PNUM"));
+        }
+        if let Some(pno) = header.pno {
+            add("pno".to_string(), FieldValue::UInt(pno as u64), "uint", Some("PNO"));
+        }
+
+        if let Some(time) = self.cp.data_time {
+            add("data_time".to_string(), FieldValue::String(time.format("%Y-%m-%d %H:%M:%S").to_string()), "datetime", Some("DataTime"));
+        }
+        for (key, value) in &self.cp.params {
+            add(format!("cp.{}", key), FieldValue::String(value.clone()), "string", Some(key));
+        }
+        for factor in &self.cp.factors {
+            for (statistic, value) in factor.statistics() {
+                let key = format!("{}-{}", factor.code, statistic);
+                add(format!("{}.{}", factor.code, statistic), FieldValue::Float(value), "float", Some(&key));
+            }
+            for (statistic, value) in [("Flag", &factor.flag), ("EFlag", &factor.eflag), ("SampleTime", &factor.sample_time)] {
+                if let Some(value) = value {
+                    let key = format!("{}-{}", factor.code, statistic);
+                    add(format!("{}.{}", factor.code, statistic), FieldValue::String(value.clone()), "string", Some(&key));
+                }
+            }
+            for (statistic, value) in &factor.extra {
+                let key = format!("{}-{}", factor.code, statistic);
+                add(format!("{}.{}", factor.code, statistic), FieldValue::String(value.clone()), "string", Some(&key));
+            }
+        }
+
+        let mut result = ParseResult::success(hj212_protocol_info(self.version.as_str(), 1.0), fields, raw.to_vec(), raw.len());
+
+        result.validation.crc_results.push(CrcValidationResult {
+            algorithm: "CRC16/HJ212".to_string(),
+            expected: self.crc.unwrap_or_default() as u64,
+            calculated: self.calculated_crc as u64,
+            valid: self.crc_valid(),
+            data_range: "data segment".to_string(),
+        });
+        if !self.crc_valid() {
+            result.add_error(ParseError::new(
+                "CRC_MISMATCH",
+                format!("CRC {:04X} does not match calculated {:04X}", self.crc.unwrap_or_default(), self.calculated_crc),
+                ErrorSeverity::Error,
+            ));
+        }
+        if !self.length_valid() {
+            result.add_error(ParseError::new(
+                "LENGTH_MISMATCH",
+                format!("Declared length {} does not match data segment length {}", self.declared_length, self.data_length),
+                ErrorSeverity::Error,
+            ));
+        }
+        for issue in &self.issues {
+            result.add_warning(ParseWarning::new("HJ212_DECODE", issue.clone()));
+        }
+
+        if let Ok(packet) = serde_json::to_value(self) {
+            result.metadata.extra.insert("hj212".to_string(), packet);
+        }
+
+        result
+    }
+}
+
+/// Decode `key=value;` header pairs
+fn decode_header(text: &str, issues: &mut Vec<String>) -> Hj212Header {
+    let mut header = Hj212Header::default();
+
+    for pair in text.split(';').filter(|pair| !pair.trim().is_empty()) {
+        let Some((key, value)) = pair.split_once('=') else {
+            issues.push(format!("Header item '{}' has no value", pair));
+            continue;
+        };
+        let value = value.to_string();
+
+        match key.trim() {
+            "QN" => header.qn = Some(value),
+            "ST" => header.st = Some(value),
+            "CN" => header.cn = Some(value),
+            "PW" => header.pw = Some(value),
+            "MN" => header.mn = Some(value),
+            "Flag" => match value.parse::<u8>() {
+                Ok(raw) => header.flag = Some(Hj212Flag::from_raw(raw)),
+                Err(_) => issues.push(format!("Flag '{}' is not a number", value)),
+            },
+            "PNUM" => match value.parse::<u32>() {
+                Ok(pnum) => header.pnum = Some(pnum),
+                Err(_) => issues.push(format!("PNUM '{}' is not a number", value)),
+            },
+            "PNO" => match value.parse::<u32>() {
+                Ok(pno) => header.pno = Some(pno),
+                Err(_) => issues.push(format!("PNO '{}' is not a number", value)),
+            },
+            other => {
+                header.extra.insert(other.to_string(), value);
+            }
+        }
+    }
+
+    header
+}
+
+/// Find the byte range of the value of `key=` within a packet
+fn locate_value(text: &str, key: &str) -> Option<(usize, usize)> {
+    let pattern = format!("{}=", key);
+    let mut search_from = 0;
+
+    while let Some(pos) = text[search_from..].find(&pattern) {
+        let start = search_from + pos;
+        let at_boundary = start == 0 || matches!(text.as_bytes()[start - 1], b';' | b',' | b'&' | b'#');
+        if at_boundary {
+            let value_start = start + pattern.len();
+            let value_end = text[value_start..]
+                .find([';', ',', '&'])
+                .map(|end| value_start + end)
+                .unwrap_or(text.len());
+            return Some((value_start, value_end - value_start));
+        }
+        search_from = start + 1;
+    }
+
+    None
+}
+
+/// Description of a system code (ST)
+pub fn system_name(st: &str) -> Option<&'static str> {
+    Some(match st {
+        "21" => "地表水质量监测",
+        "22" => "空气质量监测",
+        "23" => "声环境质量监测",
+        "24" => "地下水质量监测",
+        "25" => "土壤质量监测",
+        "26" => "海水质量监测",
+        "27" => "挥发性有机物监测",
+        "31" => "大气环境污染源",
+        "32" => "地表水体环境污染源",
+        "33" => "地下水体环境污染源",
+        "34" => "海洋环境污染源",
+        "35" => "土壤环境污染源",
+        "36" => "声环境污染源",
+        "37" => "振动环境污染源",
+        "38" => "放射性环境污染源",
+        "39" => "工地扬尘污染源",
+        "41" => "电磁环境污染源",
+        "51" => "烟气排放过程监控",
+        "52" => "污水排放过程监控",
+        "91" => "系统交互",
+        _ => return None,
+    })
+}
+
+/// Description of a command code (CN)
+pub fn command_name(cn: &str) -> Option<&'static str> {
+    Some(match cn {
+        "1000" => "设置超时时间及重发次数",
+        "1011" => "提取现场机时间",
+        "1012" => "设置现场机时间",
+        "1013" => "现场机时间校准请求",
+        "1061" => "提取实时数据间隔",
+        "1062" => "设置实时数据间隔",
+        "1063" => "提取分钟数据间隔",
+        "1064" => "设置分钟数据间隔",
+        "1072" => "设置现场机访问密码",
+        "2011" => "实时数据",
+        "2012" => "停止察看实时数据",
+        "2021" => "设备运行状态",
+        "2022" => "停止察看设备运行状态",
+        "2031" => "日数据",
+        "2041" => "设备运行时间日历史数据",
+        "2051" => "分钟数据",
+        "2061" => "小时数据",
+        "2081" => "数采仪开机时间",
+        "3011" => "零点校准量程校准",
+        "3012" => "即时采样",
+        "3013" => "启动清洗/反吹",
+        "3014" => "比对采样",
+        "3015" => "超标留样",
+        "3016" => "设置采样时间周期",
+        "3017" => "提取采样时间周期",
+        "3018" => "提取出样时间",
+        "3019" => "提取设备唯一标识",
+        "3020" => "提取现场机信息",
+        "3021" => "设置现场机参数",
+        "9011" => "请求应答",
+        "9012" => "执行结果",
+        "9013" => "通知应答",
+        "9014" => "数据应答",
+        _ => return None,
+    })
+}
+
+/// Revisions the built-in parser decodes
+const SUPPORTED_VERSIONS: &str = "2005/2017";
+
+/// Protocol information for HJ212 parse results
+fn hj212_protocol_info(version: &str, confidence: f64) -> ProtocolInfo {
+    ProtocolInfo {
+        name: "HJ212".to_string(),
+        version: version.to_string(),
+        parser_id: HJ212_PARSER_ID.to_string(),
+        confidence,
+    }
+}
+
+/// Built-in HJ212 parser
+#[derive(Debug, Clone, Default)]
+pub struct Hj212Parser;
+
+impl Hj212Parser {
+    /// Create the parser
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Parser for Hj212Parser {
+    fn parse(&self, data: &[u8]) -> NetworkResult<ParseResult> {
+        match Hj212Packet::decode(data) {
+            Ok(packet) => Ok(packet.to_parse_result(data)),
+            Err(e) => Ok(ParseResult::failure(
+                hj212_protocol_info(SUPPORTED_VERSIONS, 0.0),
+                data.to_vec(),
+                ParseError::new("HJ212_DECODE", e.to_string(), ErrorSeverity::Critical),
+            )),
+        }
+    }
+
+    fn validate(&self, result: &ParseResult) -> ValidationReport {
+        ValidationReport::from_parse_result(result)
+    }
+
+    fn get_protocol_info(&self) -> ParserProtocolInfo {
+        ParserProtocolInfo {
+            name: "HJ212".to_string(),
+            version: SUPPORTED_VERSIONS.to_string(),
+            author: "HJ 212-2017 / HJ/T 212-2005".to_string(),
+            description: "Environmental monitoring data transmission protocol".to_string(),
+            supported_formats: vec!["text".to_string()],
+            magic_bytes: Some(HEADER.to_vec()),
+            min_frame_size: Some(12),
+            max_frame_size: Some(MAX_DATA_LENGTH + 12),
+        }
+    }
+
+    fn get_id(&self) -> &str {
+        HJ212_PARSER_ID
+    }
+
+    fn can_parse(&self, data: &[u8]) -> bool {
+        data.len() >= 12 && data.starts_with(HEADER) && data[2..6].iter().all(u8::is_ascii_digit)
+    }
+
+    fn stream_framer(&self) -> Option<FrameDetector> {
+        Some(FrameDetector::new(FramingRule {
+            start_delimiter: Some("##".to_string()),
+            end_delimiter: Some("\r\n".to_string()),
+            length_field: None,
+            fixed_size: None,
+            escape_rules: Vec::new(),
+            frame_validation: FrameValidation::default(),
+            block_crc: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REALTIME: &str = "QN=20240102030405123;ST=22;CN=2011;PW=123456;MN=AQ0001;Flag=5;CP=&&DataTime=20240102030400;a34004-Rtd=35.2,a34004-Flag=N;a34002-Rtd=60,a34002-Flag=N&&";
+
+    #[test]
+    fn test_crc16_matches_standard_example() {
+        let data = "QN=20160801085857223;ST=32;CN=1062;PW=100000;MN=010000A8900016F000169DC0;Flag=5;CP=&&RtdInterval=30&&";
+        assert_eq!(crc16(data.as_bytes()), 0x1C80);
+        assert_eq!(encode_frame(data).unwrap(), format!("##0101{}1C80\r\n", data));
+    }
+
+    #[test]
+    fn test_decode_realtime_packet() {
+        let frame = encode_frame(REALTIME).unwrap();
+        let packet = Hj212Packet::decode(frame.as_bytes()).unwrap();
+
+        assert!(packet.is_valid(), "{:?}", packet.issues);
+        assert_eq!(packet.version, Hj212Version::V2017);
+        assert!(packet.answer_required());
+        assert_eq!(packet.header.cn_name(), Some("实时数据"));
+        assert_eq!(packet.header.qn_time().unwrap().to_string(), "2024-01-02 03:04:05");
+        assert_eq!(packet.cp.data_time.unwrap().to_string(), "2024-01-02 03:04:00");
+        assert_eq!(packet.cp.factors.len(), 2);
+
+        let pm25 = packet.cp.factor("a34004").unwrap();
+        assert_eq!(pm25.rtd, Some(35.2));
+        assert_eq!(pm25.flag.as_deref(), Some("N"));
+
+        let result = packet.to_parse_result(frame.as_bytes());
+        assert!(result.success);
+        let field = result.fields.get_field("a34004.Rtd").unwrap();
+        assert_eq!(field.value, FieldValue::Float(35.2));
+        assert_eq!(field.raw_bytes, b"35.2".to_vec());
+
+        // Re-encoding gives the same packet back
+        assert_eq!(packet.encode().unwrap(), frame);
+    }
+
+    #[test]
+    fn test_decode_2005_statistics_and_bad_crc() {
+        let data = "QN=20240102030405123;ST=32;CN=2061;PW=123456;MN=WS0001;Flag=2;PNUM=2;PNO=1;CP=&&DataTime=20240102030000;011-Min=1.5,011-Avg=2.5,011-Max=3.5,011-Cou=120;B01-Avg=abc&&";
+        let frame = format!("##{:04}{}0000\r\n", data.len() + 1, data);
+        let packet = Hj212Packet::decode(frame.as_bytes()).unwrap();
+
+        assert_eq!(packet.version, Hj212Version::V2005);
+        assert!(packet.header.flag.unwrap().split);
+        assert_eq!((packet.header.pnum, packet.header.pno), (Some(2), Some(1)));
+        assert!(!packet.crc_valid());
+        assert!(!packet.length_valid());
+
+        let cod = packet.cp.factor("011").unwrap();
+        assert_eq!((cod.min, cod.avg, cod.max, cod.cou), (Some(1.5), Some(2.5), Some(3.5), Some(120.0)));
+        assert_eq!(packet.cp.factor("B01").unwrap().extra.get("Avg").map(String::as_str), Some("abc"));
+        assert_eq!(packet.issues.len(), 1);
+
+        let result = Hj212Parser::new().parse(frame.as_bytes()).unwrap();
+        assert!(!result.success);
+        assert_eq!(result.protocol.version, "2005");
+        assert!(result.errors.iter().any(|e| e.code == "CRC_MISMATCH"));
+        assert!(result.errors.iter().any(|e| e.code == "LENGTH_MISMATCH"));
+    }
+
+    #[test]
+    fn test_version_detected_without_flag() {
+        let decode = |data: &str| Hj212Packet::decode(encode_frame(data).unwrap().as_bytes()).unwrap().version;
+
+        assert_eq!(decode("QN=1;ST=32;CN=2011;PW=1;MN=M1;CP=&&B01-Rtd=1.5,011-Rtd=20&&"), Hj212Version::V2005);
+        assert_eq!(decode("QN=1;ST=32;CN=2011;PW=1;MN=M1;CP=&&w01018-Rtd=20,w00000-Rtd=1.5&&"), Hj212Version::V2017);
+        assert_eq!(decode("QN=1;ST=32;CN=2011;PW=1;MN=M1;Flag=1;CP=&&w01018-Rtd=20&&"), Hj212Version::V2017);
+        assert_eq!(decode("QN=1;ST=32;CN=2011;PW=1;MN=M1;CP=&&B01-Rtd=1,B01-SampleTime=20240102030400&&"), Hj212Version::V2017);
+    }
+}
@@ -0,0 +1,1045 @@
+//! IEC 60870-5-104 telecontrol protocol engine
+//!
+//! An APDU is `0x68` + length + four control octets (APCI) + an optional
+//! ASDU. The control octets select the I (numbered information transfer),
+//! S (numbered supervisory acknowledgement) or U (unnumbered control:
+//! STARTDT, STOPDT, TESTFR) format. ASDUs use the standard 104 field sizes:
+//! two-octet cause of transmission, two-octet common address and three-octet
+//! information object addresses. Single points, short floats, integrated
+//! totals, single commands and interrogation commands are decoded into typed
+//! values, including their CP56Time2a time-tagged variants. `Iec104Parser`
+//! exposes the engine as the built-in `iec104` parser.
+
+use crate::parser::framing::FrameDetector;
+use crate::parser::result::{
+    ErrorSeverity, FieldValue, ParseError, ParseResult, ParseWarning, ParsedField, ParsedFields,
+    ProtocolInfo,
+};
+use crate::parser::schema::{Endianness, FrameValidation, FramingRule, LengthEncoding, LengthField};
+use crate::parser::validation_report::ValidationReport;
+use crate::parser::{Parser, ProtocolInfo as ParserProtocolInfo};
+use crate::types::{NetworkError, NetworkResult};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+/// ID of the built-in IEC 104 parser
+pub const IEC104_PARSER_ID: &str = "iec104";
+
+/// APDU start octet
+pub const START: u8 = 0x68;
+
+/// Start octet, length octet and control field
+pub const APCI_LENGTH: usize = 6;
+
+/// Largest value of the APDU length octet
+pub const MAX_APDU_LENGTH: usize = 253;
+
+/// Send and receive sequence numbers count modulo 2^15
+pub const SEQUENCE_MODULO: u16 = 0x8000;
+
+/// Type identification, data unit identifier and common address
+const ASDU_HEADER_LENGTH: usize = 6;
+
+/// Information object address size
+const IOA_LENGTH: usize = 3;
+
+/// CP56Time2a size
+const TIME_LENGTH: usize = 7;
+
+/// Most information objects one ASDU can carry
+const MAX_OBJECTS: usize = 0x7F;
+
+/// Single-point information
+pub const M_SP_NA_1: u8 = 1;
+/// Measured value, short floating point
+pub const M_ME_NC_1: u8 = 13;
+/// Integrated totals
+pub const M_IT_NA_1: u8 = 15;
+/// Single-point information with CP56Time2a
+pub const M_SP_TB_1: u8 = 30;
+/// Measured value, short floating point with CP56Time2a
+pub const M_ME_TF_1: u8 = 36;
+/// Integrated totals with CP56Time2a
+pub const M_IT_TB_1: u8 = 37;
+/// Single command
+pub const C_SC_NA_1: u8 = 45;
+/// Single command with CP56Time2a
+pub const C_SC_TA_1: u8 = 58;
+/// Interrogation command
+pub const C_IC_NA_1: u8 = 100;
+
+/// Cause of transmission: periodic, cyclic
+pub const COT_PERIODIC: u8 = 1;
+/// Cause of transmission: background scan
+pub const COT_BACKGROUND: u8 = 2;
+/// Cause of transmission: spontaneous
+pub const COT_SPONTANEOUS: u8 = 3;
+/// Cause of transmission: initialised
+pub const COT_INITIALISED: u8 = 4;
+/// Cause of transmission: request or requested
+pub const COT_REQUEST: u8 = 5;
+/// Cause of transmission: activation
+pub const COT_ACTIVATION: u8 = 6;
+/// Cause of transmission: activation confirmation
+pub const COT_ACTIVATION_CON: u8 = 7;
+/// Cause of transmission: deactivation
+pub const COT_DEACTIVATION: u8 = 8;
+/// Cause of transmission: deactivation confirmation
+pub const COT_DEACTIVATION_CON: u8 = 9;
+/// Cause of transmission: activation termination
+pub const COT_ACTIVATION_TERM: u8 = 10;
+/// Cause of transmission: return information caused by a remote command
+pub const COT_REMOTE_COMMAND: u8 = 11;
+/// Cause of transmission: interrogated by station interrogation
+pub const COT_INTERROGATED: u8 = 20;
+/// Cause of transmission: unknown type identification
+pub const COT_UNKNOWN_TYPE: u8 = 44;
+/// Cause of transmission: unknown cause of transmission
+pub const COT_UNKNOWN_CAUSE: u8 = 45;
+/// Cause of transmission: unknown common address of ASDU
+pub const COT_UNKNOWN_COMMON_ADDRESS: u8 = 46;
+/// Cause of transmission: unknown information object address
+pub const COT_UNKNOWN_OBJECT_ADDRESS: u8 = 47;
+
+/// Qualifier of interrogation: station interrogation
+pub const QOI_STATION: u8 = 20;
+
+/// Common address addressing every station
+pub const BROADCAST_ADDRESS: u16 = 0xFFFF;
+
+/// Quality descriptor: overflow (measured values only)
+pub const QUALITY_OVERFLOW: u8 = 0x01;
+/// Quality descriptor: blocked
+pub const QUALITY_BLOCKED: u8 = 0x10;
+/// Quality descriptor: substituted
+pub const QUALITY_SUBSTITUTED: u8 = 0x20;
+/// Quality descriptor: not topical
+pub const QUALITY_NOT_TOPICAL: u8 = 0x40;
+/// Quality descriptor: invalid
+pub const QUALITY_INVALID: u8 = 0x80;
+
+/// Unnumbered control function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UFunction {
+    StartDtAct,
+    StartDtCon,
+    StopDtAct,
+    StopDtCon,
+    TestFrAct,
+    TestFrCon,
+}
+
+impl UFunction {
+    /// First control octet
+    pub fn code(self) -> u8 {
+        match self {
+            Self::StartDtAct => 0x07,
+            Self::StartDtCon => 0x0B,
+            Self::StopDtAct => 0x13,
+            Self::StopDtCon => 0x23,
+            Self::TestFrAct => 0x43,
+            Self::TestFrCon => 0x83,
+        }
+    }
+
+    /// Function for a first control octet
+    pub fn from_code(code: u8) -> Option<Self> {
+        Some(match code {
+            0x07 => Self::StartDtAct,
+            0x0B => Self::StartDtCon,
+            0x13 => Self::StopDtAct,
+            0x23 => Self::StopDtCon,
+            0x43 => Self::TestFrAct,
+            0x83 => Self::TestFrCon,
+            _ => return None,
+        })
+    }
+
+    /// Display name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::StartDtAct => "STARTDT act",
+            Self::StartDtCon => "STARTDT con",
+            Self::StopDtAct => "STOPDT act",
+            Self::StopDtCon => "STOPDT con",
+            Self::TestFrAct => "TESTFR act",
+            Self::TestFrCon => "TESTFR con",
+        }
+    }
+
+    /// Confirmation answering an activation
+    pub fn confirmation(self) -> Option<Self> {
+        match self {
+            Self::StartDtAct => Some(Self::StartDtCon),
+            Self::StopDtAct => Some(Self::StopDtCon),
+            Self::TestFrAct => Some(Self::TestFrCon),
+            _ => None,
+        }
+    }
+}
+
+/// Application protocol control information
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "format")]
+pub enum Apci {
+    /// Numbered information transfer
+    I { send_seq: u16, recv_seq: u16 },
+    /// Numbered supervisory acknowledgement
+    S { recv_seq: u16 },
+    /// Unnumbered control function
+    U { function: UFunction },
+}
+
+impl Apci {
+    /// Format letter
+    pub fn format(&self) -> &'static str {
+        match self {
+            Self::I { .. } => "I",
+            Self::S { .. } => "S",
+            Self::U { .. } => "U",
+        }
+    }
+
+    fn encode(&self) -> [u8; 4] {
+        match *self {
+            Self::I { send_seq, recv_seq } => {
+                let [s0, s1] = sequence_octets(send_seq);
+                let [r0, r1] = sequence_octets(recv_seq);
+                [s0, s1, r0, r1]
+            }
+            Self::S { recv_seq } => {
+                let [r0, r1] = sequence_octets(recv_seq);
+                [0x01, 0x00, r0, r1]
+            }
+            Self::U { function } => [function.code(), 0x00, 0x00, 0x00],
+        }
+    }
+
+    fn decode(control: &[u8]) -> NetworkResult<Self> {
+        let sequence = |low: u8, high: u8| (low as u16 >> 1) | ((high as u16) << 7);
+        if control[0] & 0x01 == 0 {
+            Ok(Self::I {
+                send_seq: sequence(control[0], control[1]),
+                recv_seq: sequence(control[2], control[3]),
+            })
+        } else if control[0] & 0x03 == 0x01 {
+            Ok(Self::S { recv_seq: sequence(control[2], control[3]) })
+        } else {
+            UFunction::from_code(control[0])
+                .map(|function| Self::U { function })
+                .ok_or_else(|| NetworkError::ParseError(format!("Unknown U-format control octet {:02X}", control[0])))
+        }
+    }
+}
+
+/// Sequence number as two control octets, shifted past the format bit
+fn sequence_octets(sequence: u16) -> [u8; 2] {
+    let sequence = sequence % SEQUENCE_MODULO;
+    [((sequence << 1) & 0xFE) as u8, (sequence >> 7) as u8]
+}
+
+/// Seven-octet binary time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cp56Time2a {
+    /// Seconds and milliseconds, 0..59999
+    pub milliseconds: u16,
+    pub minute: u8,
+    pub hour: u8,
+    pub day: u8,
+    /// Day of week, 1 = Monday, 0 = not used
+    pub weekday: u8,
+    pub month: u8,
+    /// Years since 2000
+    pub year: u8,
+    pub invalid: bool,
+    pub summer_time: bool,
+}
+
+impl Cp56Time2a {
+    /// Time tag for a wall-clock time
+    pub fn from_datetime(time: &NaiveDateTime) -> Self {
+        Self {
+            milliseconds: (time.second() * 1000 + (time.nanosecond() / 1_000_000).min(999)) as u16,
+            minute: time.minute() as u8,
+            hour: time.hour() as u8,
+            day: time.day() as u8,
+            weekday: time.weekday().number_from_monday() as u8,
+            month: time.month() as u8,
+            year: (time.year() - 2000).clamp(0, 99) as u8,
+            invalid: false,
+            summer_time: false,
+        }
+    }
+
+    /// Wall-clock time, if the fields form a valid date
+    pub fn to_datetime(&self) -> Option<NaiveDateTime> {
+        NaiveDate::from_ymd_opt(2000 + self.year as i32, self.month as u32, self.day as u32)?.and_hms_milli_opt(
+            self.hour as u32,
+            self.minute as u32,
+            (self.milliseconds / 1000) as u32,
+            (self.milliseconds % 1000) as u32,
+        )
+    }
+
+    /// Encode the seven octets
+    pub fn encode(&self) -> [u8; TIME_LENGTH] {
+        let [ms0, ms1] = self.milliseconds.to_le_bytes();
+        [
+            ms0,
+            ms1,
+            (self.minute & 0x3F) | if self.invalid { 0x80 } else { 0 },
+            (self.hour & 0x1F) | if self.summer_time { 0x80 } else { 0 },
+            (self.day & 0x1F) | ((self.weekday & 0x07) << 5),
+            self.month & 0x0F,
+            self.year & 0x7F,
+        ]
+    }
+
+    /// Decode the seven octets
+    pub fn decode(data: &[u8]) -> NetworkResult<Self> {
+        if data.len() < TIME_LENGTH {
+            return Err(NetworkError::ParseError("CP56Time2a needs 7 octets".to_string()));
+        }
+        Ok(Self {
+            milliseconds: u16::from_le_bytes([data[0], data[1]]),
+            minute: data[2] & 0x3F,
+            invalid: data[2] & 0x80 != 0,
+            hour: data[3] & 0x1F,
+            summer_time: data[3] & 0x80 != 0,
+            day: data[4] & 0x1F,
+            weekday: data[4] >> 5,
+            month: data[5] & 0x0F,
+            year: data[6] & 0x7F,
+        })
+    }
+
+    /// Display text, `yyyy-mm-dd hh:mm:ss.mmm`
+    pub fn to_text(&self) -> String {
+        match self.to_datetime() {
+            Some(time) => time.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            None => format!(
+                "20{:02}-{:02}-{:02} {:02}:{:02}:{:06.3}",
+                self.year, self.month, self.day, self.hour, self.minute, self.milliseconds as f64 / 1000.0
+            ),
+        }
+    }
+}
+
+/// Information element of an object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ObjectValue {
+    /// Single-point information with its quality bits
+    SinglePoint { value: bool, quality: u8 },
+    /// Short floating point measured value with its quality descriptor
+    ShortFloat { value: f32, quality: u8 },
+    /// Binary counter reading
+    IntegratedTotal { value: i32, sequence: u8, carry: bool, adjusted: bool, invalid: bool },
+    /// Single command
+    SingleCommand { state: bool, select: bool, qualifier: u8 },
+    /// Interrogation command
+    Interrogation { qualifier: u8 },
+    /// Element of a type this engine does not decode, hex encoded
+    Raw { data: String },
+}
+
+impl ObjectValue {
+    fn encode(&self, out: &mut Vec<u8>) -> NetworkResult<()> {
+        match self {
+            Self::SinglePoint { value, quality } => out.push((quality & 0xF0) | *value as u8),
+            Self::ShortFloat { value, quality } => {
+                out.extend_from_slice(&value.to_le_bytes());
+                out.push(*quality);
+            }
+            Self::IntegratedTotal { value, sequence, carry, adjusted, invalid } => {
+                out.extend_from_slice(&value.to_le_bytes());
+                out.push(
+                    (sequence & 0x1F)
+                        | if *carry { 0x20 } else { 0 }
+                        | if *adjusted { 0x40 } else { 0 }
+                        | if *invalid { 0x80 } else { 0 },
+                );
+            }
+            Self::SingleCommand { state, select, qualifier } => {
+                out.push(*state as u8 | ((qualifier & 0x1F) << 2) | if *select { 0x80 } else { 0 });
+            }
+            Self::Interrogation { qualifier } => out.push(*qualifier),
+            Self::Raw { data } => out.extend(
+                hex::decode(data).map_err(|e| NetworkError::InvalidConfig(format!("Invalid raw element: {}", e)))?,
+            ),
+        }
+        Ok(())
+    }
+
+    fn decode(type_id: u8, data: &[u8]) -> Self {
+        let u32_le = |data: &[u8]| [data[0], data[1], data[2], data[3]];
+        match type_id {
+            M_SP_NA_1 | M_SP_TB_1 => Self::SinglePoint { value: data[0] & 0x01 != 0, quality: data[0] & 0xF0 },
+            M_ME_NC_1 | M_ME_TF_1 => Self::ShortFloat { value: f32::from_le_bytes(u32_le(data)), quality: data[4] },
+            M_IT_NA_1 | M_IT_TB_1 => Self::IntegratedTotal {
+                value: i32::from_le_bytes(u32_le(data)),
+                sequence: data[4] & 0x1F,
+                carry: data[4] & 0x20 != 0,
+                adjusted: data[4] & 0x40 != 0,
+                invalid: data[4] & 0x80 != 0,
+            },
+            C_SC_NA_1 | C_SC_TA_1 => Self::SingleCommand {
+                state: data[0] & 0x01 != 0,
+                select: data[0] & 0x80 != 0,
+                qualifier: (data[0] >> 2) & 0x1F,
+            },
+            C_IC_NA_1 => Self::Interrogation { qualifier: data[0] },
+            _ => Self::Raw { data: hex::encode_upper(data) },
+        }
+    }
+
+    /// Whether this element belongs in an ASDU of the given type
+    fn matches(&self, type_id: u8) -> bool {
+        match self {
+            Self::SinglePoint { .. } => matches!(type_id, M_SP_NA_1 | M_SP_TB_1),
+            Self::ShortFloat { .. } => matches!(type_id, M_ME_NC_1 | M_ME_TF_1),
+            Self::IntegratedTotal { .. } => matches!(type_id, M_IT_NA_1 | M_IT_TB_1),
+            Self::SingleCommand { .. } => matches!(type_id, C_SC_NA_1 | C_SC_TA_1),
+            Self::Interrogation { .. } => type_id == C_IC_NA_1,
+            Self::Raw { .. } => element_layout(type_id).is_none(),
+        }
+    }
+}
+
+/// Information object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InformationObject {
+    pub address: u32,
+    pub value: ObjectValue,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<Cp56Time2a>,
+}
+
+impl InformationObject {
+    /// Object without time tag
+    pub fn new(address: u32, value: ObjectValue) -> Self {
+        Self { address, value, time: None }
+    }
+
+    /// Attach a time tag
+    pub fn with_time(mut self, time: Cp56Time2a) -> Self {
+        self.time = Some(time);
+        self
+    }
+}
+
+/// Application service data unit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Asdu {
+    pub type_id: u8,
+    /// SQ bit: objects follow one address in sequence
+    pub sequence: bool,
+    pub cause: u8,
+    /// P/N bit: negative confirmation
+    pub negative: bool,
+    pub test: bool,
+    pub originator: u8,
+    pub common_address: u16,
+    pub objects: Vec<InformationObject>,
+}
+
+impl Asdu {
+    /// ASDU with individually addressed objects
+    pub fn new(type_id: u8, cause: u8, common_address: u16, objects: Vec<InformationObject>) -> Self {
+        Self {
+            type_id,
+            sequence: false,
+            cause,
+            negative: false,
+            test: false,
+            originator: 0,
+            common_address,
+            objects,
+        }
+    }
+
+    /// Station interrogation activation
+    pub fn interrogation(common_address: u16) -> Self {
+        Self::new(
+            C_IC_NA_1,
+            COT_ACTIVATION,
+            common_address,
+            vec![InformationObject::new(0, ObjectValue::Interrogation { qualifier: QOI_STATION })],
+        )
+    }
+
+    /// Single command activation, select-before-operate when `select` is set
+    pub fn single_command(common_address: u16, address: u32, state: bool, select: bool) -> Self {
+        Self::new(
+            C_SC_NA_1,
+            COT_ACTIVATION,
+            common_address,
+            vec![InformationObject::new(address, ObjectValue::SingleCommand { state, select, qualifier: 0 })],
+        )
+    }
+
+    /// Mirror of this ASDU with a new cause, as used for confirmations and terminations
+    pub fn mirror(&self, cause: u8, negative: bool) -> Self {
+        Self { cause, negative, ..self.clone() }
+    }
+
+    /// Type name such as `M_SP_NA_1`
+    pub fn type_name(&self) -> Option<&'static str> {
+        type_name(self.type_id)
+    }
+
+    /// Decode an ASDU
+    pub fn decode(data: &[u8]) -> NetworkResult<Self> {
+        if data.len() < ASDU_HEADER_LENGTH {
+            return Err(NetworkError::ParseError(format!("ASDU too short: {} octets", data.len())));
+        }
+
+        let type_id = data[0];
+        let sequence = data[1] & 0x80 != 0;
+        let count = (data[1] & 0x7F) as usize;
+        let mut asdu = Self {
+            type_id,
+            sequence,
+            cause: data[2] & 0x3F,
+            negative: data[2] & 0x40 != 0,
+            test: data[2] & 0x80 != 0,
+            originator: data[3],
+            common_address: u16::from_le_bytes([data[4], data[5]]),
+            objects: Vec::with_capacity(count),
+        };
+        let body = &data[ASDU_HEADER_LENGTH..];
+
+        let Some(size) = element_size(type_id) else {
+            if body.len() >= IOA_LENGTH {
+                asdu.objects.push(InformationObject::new(
+                    read_ioa(body),
+                    ObjectValue::Raw { data: hex::encode_upper(&body[IOA_LENGTH..]) },
+                ));
+            }
+            return Ok(asdu);
+        };
+
+        let expected = if sequence {
+            if count == 0 { 0 } else { IOA_LENGTH + count * size }
+        } else {
+            count * (IOA_LENGTH + size)
+        };
+        if body.len() != expected {
+            return Err(NetworkError::ParseError(format!(
+                "{} ASDU with {} objects needs {} octets of objects, got {}",
+                type_name(type_id).unwrap_or("ASDU"),
+                count,
+                expected,
+                body.len()
+            )));
+        }
+
+        let mut position = 0;
+        let mut base = 0;
+        for index in 0..count {
+            let address = if sequence && index > 0 {
+                base + index as u32
+            } else {
+                base = read_ioa(&body[position..]);
+                position += IOA_LENGTH;
+                base
+            };
+            let element = &body[position..position + size];
+            position += size;
+
+            let mut object = InformationObject::new(address, ObjectValue::decode(type_id, element));
+            if is_time_tagged(type_id) {
+                object.time = Some(Cp56Time2a::decode(&element[size - TIME_LENGTH..])?);
+            }
+            asdu.objects.push(object);
+        }
+
+        Ok(asdu)
+    }
+
+    /// Encode the ASDU
+    pub fn encode(&self) -> NetworkResult<Vec<u8>> {
+        if self.objects.len() > MAX_OBJECTS {
+            return Err(NetworkError::InvalidConfig(format!(
+                "An ASDU carries at most {} objects, got {}",
+                MAX_OBJECTS,
+                self.objects.len()
+            )));
+        }
+
+        let mut data = vec![
+            self.type_id,
+            self.objects.len() as u8 | if self.sequence { 0x80 } else { 0 },
+            (self.cause & 0x3F) | if self.negative { 0x40 } else { 0 } | if self.test { 0x80 } else { 0 },
+            self.originator,
+        ];
+        data.extend_from_slice(&self.common_address.to_le_bytes());
+
+        for (index, object) in self.objects.iter().enumerate() {
+            if !object.value.matches(self.type_id) {
+                return Err(NetworkError::InvalidConfig(format!(
+                    "Object {} does not fit type {}",
+                    object.address,
+                    type_name(self.type_id).map(str::to_string).unwrap_or_else(|| self.type_id.to_string())
+                )));
+            }
+            if !self.sequence || index == 0 {
+                data.extend_from_slice(&object.address.to_le_bytes()[..IOA_LENGTH]);
+            }
+            object.value.encode(&mut data)?;
+            if is_time_tagged(self.type_id) {
+                let time = object.time.unwrap_or_else(|| Cp56Time2a::from_datetime(&chrono::Local::now().naive_local()));
+                data.extend_from_slice(&time.encode());
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+/// Application protocol data unit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Apdu {
+    pub apci: Apci,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asdu: Option<Asdu>,
+}
+
+impl Apdu {
+    /// I-format APDU carrying an ASDU
+    pub fn i_frame(send_seq: u16, recv_seq: u16, asdu: Asdu) -> Self {
+        Self { apci: Apci::I { send_seq, recv_seq }, asdu: Some(asdu) }
+    }
+
+    /// S-format acknowledgement
+    pub fn s_frame(recv_seq: u16) -> Self {
+        Self { apci: Apci::S { recv_seq }, asdu: None }
+    }
+
+    /// U-format control function
+    pub fn u_frame(function: UFunction) -> Self {
+        Self { apci: Apci::U { function }, asdu: None }
+    }
+
+    /// Decode one complete APDU
+    pub fn decode(frame: &[u8]) -> NetworkResult<Self> {
+        if frame.len() < APCI_LENGTH {
+            return Err(NetworkError::ParseError(format!("APDU too short: {} octets", frame.len())));
+        }
+        if frame[0] != START {
+            return Err(NetworkError::ParseError(format!("APDU must start with 68, got {:02X}", frame[0])));
+        }
+        let length = frame[1] as usize;
+        if length + 2 != frame.len() {
+            return Err(NetworkError::ParseError(format!(
+                "APDU length octet says {} but {} octets follow",
+                length,
+                frame.len() - 2
+            )));
+        }
+
+        let apci = Apci::decode(&frame[2..APCI_LENGTH])?;
+        let asdu = match apci {
+            Apci::I { .. } => Some(Asdu::decode(&frame[APCI_LENGTH..])?),
+            _ if frame.len() > APCI_LENGTH => {
+                return Err(NetworkError::ParseError(format!("{}-format APDU must not carry an ASDU", apci.format())));
+            }
+            _ => None,
+        };
+
+        Ok(Self { apci, asdu })
+    }
+
+    /// Encode the APDU
+    pub fn encode(&self) -> NetworkResult<Vec<u8>> {
+        let asdu = match (&self.apci, &self.asdu) {
+            (Apci::I { .. }, Some(asdu)) => asdu.encode()?,
+            (Apci::I { .. }, None) => {
+                return Err(NetworkError::InvalidConfig("I-format APDU needs an ASDU".to_string()));
+            }
+            _ => Vec::new(),
+        };
+        let length = 4 + asdu.len();
+        if length > MAX_APDU_LENGTH {
+            return Err(NetworkError::InvalidConfig(format!(
+                "APDU length {} exceeds {}",
+                length, MAX_APDU_LENGTH
+            )));
+        }
+
+        let mut frame = Vec::with_capacity(length + 2);
+        frame.push(START);
+        frame.push(length as u8);
+        frame.extend_from_slice(&self.apci.encode());
+        frame.extend(asdu);
+        Ok(frame)
+    }
+
+    /// Describe the APDU as a parse result with octet ranges into `raw`
+    pub fn to_parse_result(&self, raw: &[u8]) -> ParseResult {
+        let mut fields = ParsedFields::new();
+        let mut add = |name: &str, value: FieldValue, field_type: &str, range: Option<(usize, usize)>, description: &str| {
+            let (offset, length) = range.unwrap_or((0, 0));
+            let raw_bytes = raw.get(offset..offset + length).unwrap_or(&[]);
+            fields.add_field(
+                name.to_string(),
+                ParsedField::new(name, value, field_type, offset, raw_bytes).with_description(description),
+            );
+        };
+
+        add("start", FieldValue::UInt(START as u64), "uint", Some((0, 1)), "启动字符");
+        add("length", FieldValue::UInt(raw.get(1).copied().unwrap_or(0) as u64), "uint", Some((1, 1)), "APDU长度");
+        add("format", FieldValue::String(self.apci.format().to_string()), "string", Some((2, 4)), "帧格式");
+        match self.apci {
+            Apci::I { send_seq, recv_seq } => {
+                add("send_seq", FieldValue::UInt(send_seq as u64), "uint", Some((2, 2)), "发送序号 N(S)");
+                add("recv_seq", FieldValue::UInt(recv_seq as u64), "uint", Some((4, 2)), "接收序号 N(R)");
+            }
+            Apci::S { recv_seq } => {
+                add("recv_seq", FieldValue::UInt(recv_seq as u64), "uint", Some((4, 2)), "接收序号 N(R)");
+            }
+            Apci::U { function } => {
+                add("u_function", FieldValue::String(function.name().to_string()), "string", Some((2, 1)), "控制功能");
+            }
+        }
+
+        let mut warnings = Vec::new();
+        if let Some(asdu) = &self.asdu {
+            let base = APCI_LENGTH;
+            add("type_id", FieldValue::UInt(asdu.type_id as u64), "uint", Some((base, 1)), asdu.type_name().unwrap_or("未知类型"));
+            add("sequence", FieldValue::Bool(asdu.sequence), "bool", Some((base + 1, 1)), "顺序寻址 SQ");
+            add("object_count", FieldValue::UInt(asdu.objects.len() as u64), "uint", Some((base + 1, 1)), "信息对象数目");
+            add("cause", FieldValue::UInt(asdu.cause as u64), "uint", Some((base + 2, 1)), cause_name(asdu.cause).unwrap_or("传送原因"));
+            add("negative", FieldValue::Bool(asdu.negative), "bool", Some((base + 2, 1)), "否定确认 P/N");
+            add("test", FieldValue::Bool(asdu.test), "bool", Some((base + 2, 1)), "试验 T");
+            add("originator", FieldValue::UInt(asdu.originator as u64), "uint", Some((base + 3, 1)), "源发站地址");
+            add("common_address", FieldValue::UInt(asdu.common_address as u64), "uint", Some((base + 4, 2)), "公共地址");
+
+            let size = element_size(asdu.type_id);
+            let mut position = base + ASDU_HEADER_LENGTH;
+            for (index, object) in asdu.objects.iter().enumerate() {
+                let ioa_range = if !asdu.sequence || index == 0 {
+                    let range = (position, IOA_LENGTH);
+                    position += IOA_LENGTH;
+                    Some(range)
+                } else {
+                    None
+                };
+                let element_range = size.map(|size| {
+                    let range = (position, size);
+                    position += size;
+                    range
+                });
+                let value_range = element_range.map(|(offset, size)| {
+                    (offset, if is_time_tagged(asdu.type_id) { size - TIME_LENGTH } else { size })
+                });
+
+                add(&format!("ioa[{}]", index), FieldValue::UInt(object.address as u64), "uint", ioa_range, "信息对象地址");
+                let value_name = format!("value[{}]", index);
+                match &object.value {
+                    ObjectValue::SinglePoint { value, quality } => {
+                        add(&value_name, FieldValue::Bool(*value), "bool", value_range, "单点信息");
+                        add(&format!("quality[{}]", index), FieldValue::UInt(*quality as u64), "uint", value_range, &quality_text(*quality));
+                    }
+                    ObjectValue::ShortFloat { value, quality } => {
+                        add(&value_name, FieldValue::Float(*value as f64), "float", value_range.map(|(offset, _)| (offset, 4)), "短浮点测量值");
+                        add(&format!("quality[{}]", index), FieldValue::UInt(*quality as u64), "uint", value_range.map(|(offset, _)| (offset + 4, 1)), &quality_text(*quality));
+                    }
+                    ObjectValue::IntegratedTotal { value, sequence, invalid, .. } => {
+                        add(&value_name, FieldValue::Int(*value as i64), "int", value_range.map(|(offset, _)| (offset, 4)), "累计量");
+                        add(&format!("counter_sequence[{}]", index), FieldValue::UInt(*sequence as u64), "uint", value_range.map(|(offset, _)| (offset + 4, 1)), "顺序号");
+                        if *invalid {
+                            warnings.push(format!("Integrated total {} is invalid", object.address));
+                        }
+                    }
+                    ObjectValue::SingleCommand { state, select, qualifier } => {
+                        add(&value_name, FieldValue::Bool(*state), "bool", value_range, if *select { "单点命令 (选择)" } else { "单点命令 (执行)" });
+                        add(&format!("qualifier[{}]", index), FieldValue::UInt(*qualifier as u64), "uint", value_range, "命令限定词 QU");
+                    }
+                    ObjectValue::Interrogation { qualifier } => {
+                        add(&value_name, FieldValue::UInt(*qualifier as u64), "uint", value_range, "召唤限定词 QOI");
+                    }
+                    ObjectValue::Raw { data } => {
+                        let length = raw.len().saturating_sub(position);
+                        add(&value_name, FieldValue::String(data.clone()), "hex", Some((position, length)), "信息元素");
+                    }
+                }
+                if let Some(time) = &object.time {
+                    let time_range = element_range.map(|(offset, size)| (offset + size - TIME_LENGTH, TIME_LENGTH));
+                    add(&format!("time[{}]", index), FieldValue::String(time.to_text()), "timestamp", time_range, "CP56Time2a 时标");
+                    if time.invalid {
+                        warnings.push(format!("Time tag of object {} is marked invalid", object.address));
+                    }
+                }
+            }
+
+            if size.is_none() {
+                warnings.push(format!("Type identification {} is not decoded", asdu.type_id));
+            }
+            if asdu.negative {
+                warnings.push(format!(
+                    "Negative confirmation: {}",
+                    cause_name(asdu.cause).map(str::to_string).unwrap_or_else(|| asdu.cause.to_string())
+                ));
+            }
+        }
+
+        let mut result = ParseResult::success(iec104_protocol_info(1.0), fields, raw.to_vec(), raw.len());
+        for warning in warnings {
+            result.add_warning(ParseWarning::new("IEC104_DECODE", warning));
+        }
+        if let Ok(apdu) = serde_json::to_value(self) {
+            result.metadata.extra.insert("iec104".to_string(), apdu);
+        }
+        result
+    }
+}
+
+/// Size of an information element without its address, time tag included
+pub fn element_size(type_id: u8) -> Option<usize> {
+    element_layout(type_id).map(|(size, time)| if time { size + TIME_LENGTH } else { size })
+}
+
+/// Most individually addressed objects of a type that fit into one APDU
+pub fn max_objects(type_id: u8) -> usize {
+    let space = MAX_APDU_LENGTH - (APCI_LENGTH - 2) - ASDU_HEADER_LENGTH;
+    element_size(type_id).map_or(1, |size| (space / (IOA_LENGTH + size)).min(MAX_OBJECTS))
+}
+
+/// Element size without time tag, and whether a time tag follows
+fn element_layout(type_id: u8) -> Option<(usize, bool)> {
+    Some(match type_id {
+        M_SP_NA_1 => (1, false),
+        M_SP_TB_1 => (1, true),
+        M_ME_NC_1 => (5, false),
+        M_ME_TF_1 => (5, true),
+        M_IT_NA_1 => (5, false),
+        M_IT_TB_1 => (5, true),
+        C_SC_NA_1 => (1, false),
+        C_SC_TA_1 => (1, true),
+        C_IC_NA_1 => (1, false),
+        _ => return None,
+    })
+}
+
+fn is_time_tagged(type_id: u8) -> bool {
+    element_layout(type_id).is_some_and(|(_, time)| time)
+}
+
+fn read_ioa(data: &[u8]) -> u32 {
+    u32::from_le_bytes([data[0], data[1], data[2], 0])
+}
+
+/// Type name of a type identification
+pub fn type_name(type_id: u8) -> Option<&'static str> {
+    Some(match type_id {
+        M_SP_NA_1 => "M_SP_NA_1",
+        M_ME_NC_1 => "M_ME_NC_1",
+        M_IT_NA_1 => "M_IT_NA_1",
+        M_SP_TB_1 => "M_SP_TB_1",
+        M_ME_TF_1 => "M_ME_TF_1",
+        M_IT_TB_1 => "M_IT_TB_1",
+        C_SC_NA_1 => "C_SC_NA_1",
+        C_SC_TA_1 => "C_SC_TA_1",
+        C_IC_NA_1 => "C_IC_NA_1",
+        _ => return None,
+    })
+}
+
+/// Name of a cause of transmission
+pub fn cause_name(cause: u8) -> Option<&'static str> {
+    Some(match cause {
+        COT_PERIODIC => "周期/循环",
+        COT_BACKGROUND => "背景扫描",
+        COT_SPONTANEOUS => "突发",
+        COT_INITIALISED => "初始化",
+        COT_REQUEST => "请求或被请求",
+        COT_ACTIVATION => "激活",
+        COT_ACTIVATION_CON => "激活确认",
+        COT_DEACTIVATION => "停止激活",
+        COT_DEACTIVATION_CON => "停止激活确认",
+        COT_ACTIVATION_TERM => "激活终止",
+        COT_REMOTE_COMMAND => "远方命令引起的返送信息",
+        COT_INTERROGATED => "响应站召唤",
+        COT_UNKNOWN_TYPE => "未知的类型标识",
+        COT_UNKNOWN_CAUSE => "未知的传送原因",
+        COT_UNKNOWN_COMMON_ADDRESS => "未知的公共地址",
+        COT_UNKNOWN_OBJECT_ADDRESS => "未知的信息对象地址",
+        _ => return None,
+    })
+}
+
+/// Quality bits as text
+fn quality_text(quality: u8) -> String {
+    let flags: Vec<&str> = [
+        (QUALITY_OVERFLOW, "OV"),
+        (QUALITY_BLOCKED, "BL"),
+        (QUALITY_SUBSTITUTED, "SB"),
+        (QUALITY_NOT_TOPICAL, "NT"),
+        (QUALITY_INVALID, "IV"),
+    ]
+    .iter()
+    .filter(|(bit, _)| quality & bit != 0)
+    .map(|(_, name)| *name)
+    .collect();
+
+    if flags.is_empty() {
+        "品质描述: 良好".to_string()
+    } else {
+        format!("品质描述: {}", flags.join(" "))
+    }
+}
+
+/// Stream framer splitting TCP data into APDUs on the length octet
+pub fn frame_detector() -> FrameDetector {
+    FrameDetector::new(FramingRule {
+        start_delimiter: Some(char::from(START).to_string()),
+        end_delimiter: None,
+        length_field: Some(LengthField {
+            offset: 1,
+            length: 1,
+            encoding: LengthEncoding::Binary,
+            includes_header: false,
+            includes_length_field: false,
+            endian: Endianness::Big,
+        }),
+        fixed_size: None,
+        escape_rules: Vec::new(),
+        frame_validation: FrameValidation::default(),
+        block_crc: None,
+    })
+}
+
+/// Protocol information for IEC 104 parse results
+fn iec104_protocol_info(confidence: f64) -> ProtocolInfo {
+    ProtocolInfo {
+        name: "IEC 60870-5-104".to_string(),
+        version: "2006".to_string(),
+        parser_id: IEC104_PARSER_ID.to_string(),
+        confidence,
+    }
+}
+
+/// Built-in IEC 60870-5-104 parser
+#[derive(Debug, Clone, Default)]
+pub struct Iec104Parser;
+
+impl Iec104Parser {
+    /// Create the parser
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Parser for Iec104Parser {
+    fn parse(&self, data: &[u8]) -> NetworkResult<ParseResult> {
+        match Apdu::decode(data) {
+            Ok(apdu) => Ok(apdu.to_parse_result(data)),
+            Err(e) => Ok(ParseResult::failure(
+                iec104_protocol_info(0.0),
+                data.to_vec(),
+                ParseError::new("IEC104_DECODE", e.to_string(), ErrorSeverity::Critical),
+            )),
+        }
+    }
+
+    fn validate(&self, result: &ParseResult) -> ValidationReport {
+        ValidationReport::from_parse_result(result)
+    }
+
+    fn get_protocol_info(&self) -> ParserProtocolInfo {
+        ParserProtocolInfo {
+            name: "IEC 60870-5-104".to_string(),
+            version: "2006".to_string(),
+            author: "IEC 60870-5-104".to_string(),
+            description: "Telecontrol companion standard for TCP/IP networks".to_string(),
+            supported_formats: vec!["binary".to_string()],
+            magic_bytes: Some(vec![START]),
+            min_frame_size: Some(APCI_LENGTH),
+            max_frame_size: Some(MAX_APDU_LENGTH + 2),
+        }
+    }
+
+    fn get_id(&self) -> &str {
+        IEC104_PARSER_ID
+    }
+
+    fn can_parse(&self, data: &[u8]) -> bool {
+        data.len() >= APCI_LENGTH && data[0] == START && data[1] as usize + 2 == data.len()
+    }
+
+    fn stream_framer(&self) -> Option<FrameDetector> {
+        Some(frame_detector())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apci_formats() {
+        let start = Apdu::u_frame(UFunction::StartDtAct).encode().unwrap();
+        assert_eq!(start, vec![0x68, 0x04, 0x07, 0x00, 0x00, 0x00]);
+        assert_eq!(Apdu::decode(&start).unwrap().apci, Apci::U { function: UFunction::StartDtAct });
+
+        let ack = Apdu::s_frame(300).encode().unwrap();
+        assert_eq!(ack, vec![0x68, 0x04, 0x01, 0x00, 0x58, 0x02]);
+        assert_eq!(Apdu::decode(&ack).unwrap().apci, Apci::S { recv_seq: 300 });
+
+        let frame = Apdu::i_frame(32767, 5, Asdu::interrogation(1)).encode().unwrap();
+        assert_eq!(hex::encode_upper(&frame), "680EFEFF0A0064010600010000000014");
+        let decoded = Apdu::decode(&frame).unwrap();
+        assert_eq!(decoded.apci, Apci::I { send_seq: 32767, recv_seq: 5 });
+        assert_eq!(decoded.asdu, Some(Asdu::interrogation(1)));
+    }
+
+    #[test]
+    fn test_time_tagged_measurements() {
+        let time = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap().and_hms_milli_opt(10, 20, 30, 456).unwrap();
+        let tag = Cp56Time2a::from_datetime(&time);
+        assert_eq!(tag.encode(), [0xF8, 0x76, 20, 10, 15 | (5 << 5), 3, 24]);
+        assert_eq!(tag.to_datetime(), Some(time));
+
+        let asdu = Asdu::new(
+            M_ME_TF_1,
+            COT_SPONTANEOUS,
+            1,
+            vec![InformationObject::new(16385, ObjectValue::ShortFloat { value: 220.5, quality: 0 }).with_time(tag)],
+        );
+        let frame = Apdu::i_frame(0, 0, asdu.clone()).encode().unwrap();
+        let decoded = Apdu::decode(&frame).unwrap();
+        assert_eq!(decoded.asdu, Some(asdu));
+
+        let result = Iec104Parser::new().parse(&frame).unwrap();
+        assert!(result.success);
+        assert_eq!(result.fields.get_field("value[0]").unwrap().value, FieldValue::Float(220.5));
+        assert_eq!(
+            result.fields.get_field("time[0]").unwrap().value,
+            FieldValue::String("2024-03-15 10:20:30.456".to_string())
+        );
+        assert_eq!(result.fields.get_field("ioa[0]").unwrap().offset, 12);
+    }
+
+    #[test]
+    fn test_sequence_of_single_points() {
+        // SQ=1, three points starting at IOA 1, interrogated
+        let data = hex::decode("681000000000018314000100010000018001").unwrap();
+        let apdu = Apdu::decode(&data).unwrap();
+        let asdu = apdu.asdu.unwrap();
+        assert!(asdu.sequence);
+        assert_eq!(asdu.cause, COT_INTERROGATED);
+        let addresses: Vec<u32> = asdu.objects.iter().map(|object| object.address).collect();
+        assert_eq!(addresses, vec![1, 2, 3]);
+        assert_eq!(asdu.objects[1].value, ObjectValue::SinglePoint { value: false, quality: QUALITY_INVALID });
+        assert_eq!(asdu.encode().unwrap(), data[APCI_LENGTH..].to_vec());
+
+        let mut framer = frame_detector();
+        let mut stream = data.clone();
+        stream.extend(Apdu::u_frame(UFunction::TestFrAct).encode().unwrap());
+        let frames = framer.detect_frames(&stream).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].data, data);
+    }
+}
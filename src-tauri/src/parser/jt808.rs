@@ -0,0 +1,1341 @@
+//! JT/T 808 vehicle terminal protocol engine
+//!
+//! Decodes JT/T 808-2013 and JT/T 808-2019 messages:
+//! `0x7E` + escaped(header + body + XOR check) + `0x7E`, where `0x7E` and
+//! `0x7D` inside the frame are sent as `0x7D 0x02` and `0x7D 0x01`. The
+//! header is decoded into typed fields (BCD phone number, sub-package
+//! counters) and the common bodies into typed records: general responses,
+//! heartbeat, registration, authentication and location reports with their
+//! additional-info items. Field offsets in parse results refer to the
+//! unescaped frame. `Jt808Parser` exposes the engine as the built-in `jt808`
+//! parser and joins sub-packaged messages: packages are buffered per stream
+//! by phone, message ID, first serial number and package count, and the
+//! joined body is decoded once every package has arrived.
+
+use crate::parser::framing::FrameDetector;
+use crate::parser::{bcd, checksum};
+use crate::parser::result::{
+    CrcValidationResult, ErrorSeverity, FieldValue, ParseError, ParseResult, ParseWarning,
+    ParsedField, ParsedFields, ProtocolInfo,
+};
+use crate::parser::envelope::EnvelopeContext;
+use crate::parser::reassembly::{ExpiredFragments, ReassemblyScope};
+use crate::parser::schema::{FrameValidation, FramingRule};
+use crate::parser::validation_report::ValidationReport;
+use crate::parser::{Parser, ProtocolInfo as ParserProtocolInfo};
+use crate::types::{NetworkError, NetworkResult};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// ID of the built-in JT/T 808 parser
+pub const JT808_PARSER_ID: &str = "jt808";
+
+/// Frame delimiter
+const FLAG: u8 = 0x7E;
+
+/// Escape marker
+const ESCAPE: u8 = 0x7D;
+
+/// Largest body the 10-bit length field can describe
+pub const MAX_BODY_LENGTH: usize = 0x03FF;
+
+/// How long an incomplete sub-packaged message waits for its next package
+pub const PACKAGE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Incomplete sub-packaged messages kept per parser
+const MAX_PENDING_MESSAGES: usize = 256;
+
+/// Terminal general response
+pub const MSG_TERMINAL_RESPONSE: u16 = 0x0001;
+/// Terminal heartbeat
+pub const MSG_HEARTBEAT: u16 = 0x0002;
+/// Terminal registration
+pub const MSG_REGISTRATION: u16 = 0x0100;
+/// Terminal authentication
+pub const MSG_AUTHENTICATION: u16 = 0x0102;
+/// Location report
+pub const MSG_LOCATION: u16 = 0x0200;
+/// Platform general response
+pub const MSG_PLATFORM_RESPONSE: u16 = 0x8001;
+/// Terminal registration response
+pub const MSG_REGISTRATION_RESPONSE: u16 = 0x8100;
+
+/// General response result: success / confirmed
+pub const RESULT_SUCCESS: u8 = 0;
+/// General response result: failure
+pub const RESULT_FAILURE: u8 = 1;
+/// General response result: message error
+pub const RESULT_MESSAGE_ERROR: u8 = 2;
+/// General response result: not supported
+pub const RESULT_UNSUPPORTED: u8 = 3;
+/// General response result: alarm handling confirmed
+pub const RESULT_ALARM_CONFIRMED: u8 = 4;
+
+/// Escape `0x7E` and `0x7D` inside a frame
+pub fn escape(data: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(data.len() + 4);
+    for &byte in data {
+        match byte {
+            FLAG => escaped.extend_from_slice(&[ESCAPE, 0x02]),
+            ESCAPE => escaped.extend_from_slice(&[ESCAPE, 0x01]),
+            _ => escaped.push(byte),
+        }
+    }
+    escaped
+}
+
+/// Reverse `escape`
+pub fn unescape(data: &[u8]) -> NetworkResult<Vec<u8>> {
+    let mut unescaped = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte != ESCAPE {
+            unescaped.push(byte);
+            continue;
+        }
+        match bytes.next() {
+            Some(0x01) => unescaped.push(ESCAPE),
+            Some(0x02) => unescaped.push(FLAG),
+            Some(other) => {
+                return Err(NetworkError::ParseError(format!("Invalid JT/T 808 escape sequence 7D {:02X}", other)));
+            }
+            None => return Err(NetworkError::ParseError("JT/T 808 frame ends inside an escape sequence".to_string())),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Wrap header and body into a complete frame, appending the check code
+pub fn encode_frame(content: &[u8]) -> Vec<u8> {
+    let mut checked = content.to_vec();
+    checked.push(checksum::xor8(content));
+
+    let mut frame = vec![FLAG];
+    frame.extend(escape(&checked));
+    frame.push(FLAG);
+    frame
+}
+
+/// Protocol revision, told apart by bit 14 of the body properties
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Jt808Version {
+    #[serde(rename = "2013")]
+    V2013,
+    #[serde(rename = "2019")]
+    V2019,
+}
+
+impl Jt808Version {
+    /// Length of the BCD phone number field
+    fn phone_length(self) -> usize {
+        match self {
+            Self::V2013 => 6,
+            Self::V2019 => 10,
+        }
+    }
+}
+
+/// Message header
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Jt808Header {
+    pub msg_id: u16,
+    /// Body length declared in the body properties
+    pub body_length: u16,
+    /// Encryption bits (bit 0 set: RSA)
+    pub encryption: u8,
+    pub subpackage: bool,
+    pub version: Jt808Version,
+    /// Protocol version byte (2019 only)
+    pub protocol_version: Option<u8>,
+    /// Terminal phone number, BCD digits as sent
+    pub phone: String,
+    pub serial: u16,
+    /// Package count of a sub-packaged message
+    pub total_packages: Option<u16>,
+    /// One-based package index of a sub-packaged message
+    pub package_index: Option<u16>,
+}
+
+impl Jt808Header {
+    /// Header for a new message
+    pub fn new(msg_id: u16, version: Jt808Version, phone: &str, serial: u16) -> Self {
+        Self {
+            msg_id,
+            body_length: 0,
+            encryption: 0,
+            subpackage: false,
+            version,
+            protocol_version: (version == Jt808Version::V2019).then_some(1),
+            phone: phone.to_string(),
+            serial,
+            total_packages: None,
+            package_index: None,
+        }
+    }
+
+    /// Name of the message
+    pub fn msg_name(&self) -> Option<&'static str> {
+        message_name(self.msg_id)
+    }
+
+    /// Encode the header for a body of the given length
+    fn encode(&self, body_length: usize) -> NetworkResult<Vec<u8>> {
+        if body_length > MAX_BODY_LENGTH {
+            return Err(NetworkError::ParseError(format!(
+                "JT/T 808 body of {} bytes exceeds {} bytes",
+                body_length, MAX_BODY_LENGTH
+            )));
+        }
+
+        let mut properties = body_length as u16 | ((self.encryption as u16 & 0x07) << 10);
+        if self.subpackage {
+            properties |= 1 << 13;
+        }
+        if self.version == Jt808Version::V2019 {
+            properties |= 1 << 14;
+        }
+
+        let mut header = Vec::with_capacity(21);
+        header.extend_from_slice(&self.msg_id.to_be_bytes());
+        header.extend_from_slice(&properties.to_be_bytes());
+        if self.version == Jt808Version::V2019 {
+            header.push(self.protocol_version.unwrap_or(1));
+        }
+        header.extend(bcd::encode(&self.phone, self.version.phone_length())?);
+        header.extend_from_slice(&self.serial.to_be_bytes());
+        if self.subpackage {
+            header.extend_from_slice(&self.total_packages.unwrap_or(1).to_be_bytes());
+            header.extend_from_slice(&self.package_index.unwrap_or(1).to_be_bytes());
+        }
+        Ok(header)
+    }
+}
+
+/// One additional-info item of a location report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Jt808AdditionalInfo {
+    pub id: u8,
+    pub name: Option<String>,
+    /// Scaled value of numeric items
+    pub value: Option<f64>,
+    pub unit: Option<String>,
+    /// Item content, upper-case hex
+    pub raw: String,
+}
+
+impl Jt808AdditionalInfo {
+    /// Decode an item from its ID and content
+    pub fn new(id: u8, content: &[u8]) -> Self {
+        let (name, unit, scale) = match additional_info_definition(id) {
+            Some((name, unit, scale)) => (Some(name.to_string()), unit.map(str::to_string), scale),
+            None => (None, None, None),
+        };
+        let value = scale.and_then(|scale| {
+            let number = match content.len() {
+                1 => content[0] as f64,
+                2 => u16::from_be_bytes([content[0], content[1]]) as f64,
+                4 => u32::from_be_bytes([content[0], content[1], content[2], content[3]]) as f64,
+                _ => return None,
+            };
+            Some(number * scale)
+        });
+
+        Self { id, name, value, unit, raw: hex::encode_upper(content) }
+    }
+}
+
+/// Location report body (0x0200)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Jt808Location {
+    pub alarm: u32,
+    pub status: u32,
+    /// Degrees, negative for south
+    pub latitude: f64,
+    /// Degrees, negative for west
+    pub longitude: f64,
+    /// Metres
+    pub altitude: u16,
+    /// km/h
+    pub speed: f64,
+    /// Degrees from north
+    pub direction: u16,
+    /// Terminal time (GMT+8)
+    pub time: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub additional: Vec<Jt808AdditionalInfo>,
+}
+
+impl Jt808Location {
+    /// ACC on
+    pub fn acc_on(&self) -> bool {
+        self.status & 0x01 != 0
+    }
+
+    /// Position fixed
+    pub fn positioned(&self) -> bool {
+        self.status & 0x02 != 0
+    }
+}
+
+/// Message body
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Jt808Body {
+    /// 0x0001
+    TerminalResponse { ack_serial: u16, ack_msg_id: u16, result: u8 },
+    /// 0x8001
+    PlatformResponse { ack_serial: u16, ack_msg_id: u16, result: u8 },
+    /// 0x0002
+    Heartbeat,
+    /// 0x0100
+    Registration {
+        province: u16,
+        city: u16,
+        manufacturer: String,
+        model: String,
+        terminal_id: String,
+        plate_color: u8,
+        plate: String,
+    },
+    /// 0x8100
+    RegistrationResponse { ack_serial: u16, result: u8, auth_code: Option<String> },
+    /// 0x0102; `imei` and `software_version` are 2019 only
+    Authentication { auth_code: String, imei: Option<String>, software_version: Option<String> },
+    /// 0x0200
+    Location(Jt808Location),
+    /// Any other message, or one package of a sub-packaged message before
+    /// reassembly
+    Raw { data: String },
+}
+
+impl Jt808Body {
+    /// Decode the body of a message
+    fn decode(header: &Jt808Header, reader: &mut Reader) -> NetworkResult<Self> {
+        if header.subpackage {
+            return Ok(Self::Raw { data: hex::encode_upper(reader.rest("body.data")) });
+        }
+
+        let body = match header.msg_id {
+            MSG_TERMINAL_RESPONSE | MSG_PLATFORM_RESPONSE => {
+                let ack_serial = reader.u16("body.ack_serial")?;
+                let ack_msg_id = reader.u16("body.ack_msg_id")?;
+                let result = reader.u8("body.result")?;
+                if header.msg_id == MSG_TERMINAL_RESPONSE {
+                    Self::TerminalResponse { ack_serial, ack_msg_id, result }
+                } else {
+                    Self::PlatformResponse { ack_serial, ack_msg_id, result }
+                }
+            }
+            MSG_HEARTBEAT => Self::Heartbeat,
+            MSG_REGISTRATION => {
+                let (manufacturer, model, terminal_id) = match header.version {
+                    Jt808Version::V2013 => (5, 20, 7),
+                    Jt808Version::V2019 => (11, 30, 30),
+                };
+                Self::Registration {
+                    province: reader.u16("body.province")?,
+                    city: reader.u16("body.city")?,
+                    manufacturer: reader.text("body.manufacturer", manufacturer)?,
+                    model: reader.text("body.model", model)?,
+                    terminal_id: reader.text("body.terminal_id", terminal_id)?,
+                    plate_color: reader.u8("body.plate_color")?,
+                    plate: decode_text(reader.rest("body.plate")),
+                }
+            }
+            MSG_REGISTRATION_RESPONSE => {
+                let ack_serial = reader.u16("body.ack_serial")?;
+                let result = reader.u8("body.result")?;
+                let auth_code = Some(decode_text(reader.rest("body.auth_code"))).filter(|code| !code.is_empty());
+                Self::RegistrationResponse { ack_serial, result, auth_code }
+            }
+            MSG_AUTHENTICATION => match header.version {
+                Jt808Version::V2013 => Self::Authentication {
+                    auth_code: decode_text(reader.rest("body.auth_code")),
+                    imei: None,
+                    software_version: None,
+                },
+                Jt808Version::V2019 => {
+                    let length = reader.u8("body.auth_code_length")? as usize;
+                    Self::Authentication {
+                        auth_code: reader.text("body.auth_code", length)?,
+                        imei: Some(reader.text("body.imei", 15)?),
+                        software_version: Some(reader.text("body.software_version", 20)?),
+                    }
+                }
+            },
+            MSG_LOCATION => Self::Location(decode_location(reader)?),
+            _ => Self::Raw { data: hex::encode_upper(reader.rest("body.data")) },
+        };
+
+        Ok(body)
+    }
+
+    /// Encode the body for a protocol revision
+    pub fn encode(&self, version: Jt808Version) -> NetworkResult<Vec<u8>> {
+        let mut body = Vec::new();
+        match self {
+            Self::TerminalResponse { ack_serial, ack_msg_id, result }
+            | Self::PlatformResponse { ack_serial, ack_msg_id, result } => {
+                body.extend_from_slice(&ack_serial.to_be_bytes());
+                body.extend_from_slice(&ack_msg_id.to_be_bytes());
+                body.push(*result);
+            }
+            Self::Heartbeat => {}
+            Self::Registration { province, city, manufacturer, model, terminal_id, plate_color, plate } => {
+                let (manufacturer_length, model_length, terminal_id_length) = match version {
+                    Jt808Version::V2013 => (5, 20, 7),
+                    Jt808Version::V2019 => (11, 30, 30),
+                };
+                body.extend_from_slice(&province.to_be_bytes());
+                body.extend_from_slice(&city.to_be_bytes());
+                body.extend(encode_text(manufacturer, Some(manufacturer_length))?);
+                body.extend(encode_text(model, Some(model_length))?);
+                body.extend(encode_text(terminal_id, Some(terminal_id_length))?);
+                body.push(*plate_color);
+                body.extend(encode_text(plate, None)?);
+            }
+            Self::RegistrationResponse { ack_serial, result, auth_code } => {
+                body.extend_from_slice(&ack_serial.to_be_bytes());
+                body.push(*result);
+                if let Some(auth_code) = auth_code {
+                    body.extend(encode_text(auth_code, None)?);
+                }
+            }
+            Self::Authentication { auth_code, imei, software_version } => {
+                let auth_code = encode_text(auth_code, None)?;
+                match version {
+                    Jt808Version::V2013 => body.extend(auth_code),
+                    Jt808Version::V2019 => {
+                        body.push(u8::try_from(auth_code.len()).map_err(|_| {
+                            NetworkError::ParseError("JT/T 808 authentication code is too long".to_string())
+                        })?);
+                        body.extend(auth_code);
+                        body.extend(encode_text(imei.as_deref().unwrap_or_default(), Some(15))?);
+                        body.extend(encode_text(software_version.as_deref().unwrap_or_default(), Some(20))?);
+                    }
+                }
+            }
+            Self::Location(location) => encode_location(location, &mut body)?,
+            Self::Raw { data } => {
+                body = hex::decode(data)
+                    .map_err(|e| NetworkError::ParseError(format!("Invalid raw JT/T 808 body: {}", e)))?;
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// Decoded JT/T 808 message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Jt808Packet {
+    pub header: Jt808Header,
+    pub body: Jt808Body,
+    /// Body length actually received
+    pub received_body_length: usize,
+    /// Check code in the frame
+    pub checksum: u8,
+    /// Check code calculated over header and body
+    pub calculated_checksum: u8,
+    /// Problems found while decoding the body
+    #[serde(default)]
+    pub issues: Vec<String>,
+    /// Byte ranges of decoded fields in the unescaped frame
+    #[serde(skip)]
+    spans: BTreeMap<String, (usize, usize)>,
+}
+
+impl Jt808Packet {
+    /// Build a message, filling in the body length
+    pub fn new(header: Jt808Header, body: Jt808Body) -> NetworkResult<Self> {
+        let encoded = body.encode(header.version)?;
+        let mut packet = Self {
+            header,
+            body,
+            received_body_length: encoded.len(),
+            checksum: 0,
+            calculated_checksum: 0,
+            issues: Vec::new(),
+            spans: BTreeMap::new(),
+        };
+        packet.header.body_length = encoded.len() as u16;
+
+        let check = checksum::xor8(&packet.content()?);
+        packet.checksum = check;
+        packet.calculated_checksum = check;
+        Ok(packet)
+    }
+
+    /// Platform general response (0x8001) acknowledging a terminal message
+    pub fn platform_response(request: &Jt808Header, serial: u16, result: u8) -> NetworkResult<Self> {
+        let mut header = Jt808Header::new(MSG_PLATFORM_RESPONSE, request.version, &request.phone, serial);
+        header.protocol_version = request.protocol_version;
+        Self::new(header, Jt808Body::PlatformResponse {
+            ack_serial: request.serial,
+            ack_msg_id: request.msg_id,
+            result,
+        })
+    }
+
+    /// Decode a frame delimited by `0x7E`
+    pub fn decode(frame: &[u8]) -> NetworkResult<Self> {
+        let frame = frame.strip_suffix(b"\r\n").unwrap_or(frame);
+        if frame.len() < 2 || frame[0] != FLAG || frame[frame.len() - 1] != FLAG {
+            return Err(NetworkError::ParseError("JT/T 808 frame must start and end with 0x7E".to_string()));
+        }
+
+        let content = unescape(&frame[1..frame.len() - 1])?;
+        let Some((&checksum, content)) = content.split_last() else {
+            return Err(NetworkError::ParseError("JT/T 808 frame is empty".to_string()));
+        };
+
+        let mut spans = BTreeMap::new();
+        let mut reader = Reader::new(content, &mut spans);
+        let header = decode_header(&mut reader)?;
+        let received_body_length = content.len() - reader.pos;
+        let (body, issues) = decode_body(&header, &mut reader);
+
+        Ok(Self {
+            header,
+            body,
+            received_body_length,
+            checksum,
+            calculated_checksum: checksum::xor8(content),
+            issues,
+            spans,
+        })
+    }
+
+    /// Check code matches
+    pub fn checksum_valid(&self) -> bool {
+        self.checksum == self.calculated_checksum
+    }
+
+    /// Declared body length matches the received body
+    pub fn length_valid(&self) -> bool {
+        self.header.body_length as usize == self.received_body_length
+    }
+
+    /// Check code and length match
+    pub fn is_valid(&self) -> bool {
+        self.checksum_valid() && self.length_valid()
+    }
+
+    /// Header and body, unescaped and without check code
+    fn content(&self) -> NetworkResult<Vec<u8>> {
+        let body = self.body.encode(self.header.version)?;
+        let mut content = self.header.encode(body.len())?;
+        content.extend(body);
+        Ok(content)
+    }
+
+    /// Encode the message, recalculating body length and check code
+    pub fn encode(&self) -> NetworkResult<Vec<u8>> {
+        Ok(encode_frame(&self.content()?))
+    }
+
+    /// Convert to a generic parse result
+    pub fn to_parse_result(&self, raw: &[u8]) -> ParseResult {
+        let content = raw.get(1..raw.len().saturating_sub(1))
+            .and_then(|escaped| unescape(escaped).ok())
+            .unwrap_or_default();
+        let mut fields = ParsedFields::new();
+        let mut add = |name: &str, value: FieldValue, field_type: &str| {
+            let (offset, length) = self.span(name).unwrap_or((0, 0));
+            let raw_bytes = content.get(offset..offset + length).unwrap_or(&[]);
+            // Offsets count the leading 0x7E
+            let offset = if length > 0 { offset + 1 } else { 0 };
+            fields.add_field(name.to_string(), ParsedField::new(name, value, field_type, offset, raw_bytes));
+        };
+
+        let header = &self.header;
+        let version = match header.version {
+            Jt808Version::V2013 => "2013",
+            Jt808Version::V2019 => "2019",
+        };
+        add("version", FieldValue::String(version.to_string()), "string");
+        add("msg_id", FieldValue::UInt(header.msg_id as u64), "uint");
+        if let Some(name) = header.msg_name() {
+            add("msg_name", FieldValue::String(name.to_string()), "string");
+        }
+        add("body_length", FieldValue::UInt(header.body_length as u64), "uint");
+        add("encryption", FieldValue::UInt(header.encryption as u64), "uint");
+        add("subpackage", FieldValue::Bool(header.subpackage), "bool");
+        if let Some(protocol_version) = header.protocol_version {
+            add("protocol_version", FieldValue::UInt(protocol_version as u64), "uint");
+        }
+        add("phone", FieldValue::String(header.phone.clone()), "bcd");
+        add("serial", FieldValue::UInt(header.serial as u64), "uint");
+        if let (Some(total), Some(index)) = (header.total_packages, header.package_index) {
+            add("total_packages", FieldValue::UInt(total as u64), "uint");
+            add("package_index", FieldValue::UInt(index as u64), "uint");
+        }
+
+        if let Ok(body) = serde_json::to_value(&self.body) {
+            let mut body_fields = Vec::new();
+            flatten_json("body", &body, &mut body_fields);
+            for (name, value, field_type) in body_fields {
+                add(&name, value, field_type);
+            }
+        }
+
+        let mut result = ParseResult::success(jt808_protocol_info(1.0), fields, raw.to_vec(), raw.len());
+
+        result.validation.crc_results.push(CrcValidationResult {
+            algorithm: "XOR/JT808".to_string(),
+            expected: self.checksum as u64,
+            calculated: self.calculated_checksum as u64,
+            valid: self.checksum_valid(),
+            data_range: "header and body".to_string(),
+        });
+        if !self.checksum_valid() {
+            result.add_error(ParseError::new(
+                "CHECKSUM_MISMATCH",
+                format!("Check code {:02X} does not match calculated {:02X}", self.checksum, self.calculated_checksum),
+                ErrorSeverity::Error,
+            ));
+        }
+        if !self.length_valid() {
+            result.add_error(ParseError::new(
+                "LENGTH_MISMATCH",
+                format!("Declared body length {} does not match received {}", header.body_length, self.received_body_length),
+                ErrorSeverity::Error,
+            ));
+        }
+        for issue in &self.issues {
+            result.add_warning(ParseWarning::new("JT808_DECODE", issue.clone()));
+        }
+
+        if let Ok(packet) = serde_json::to_value(self) {
+            result.metadata.extra.insert("jt808".to_string(), packet);
+        }
+
+        result
+    }
+
+    /// Byte range of a field, or of the nearest enclosing field
+    fn span(&self, name: &str) -> Option<(usize, usize)> {
+        let mut name = name;
+        loop {
+            if let Some(span) = self.spans.get(name) {
+                return Some(*span);
+            }
+            name = &name[..name.rfind(['.', '['])?];
+        }
+    }
+}
+
+/// Decode the body at the reader position, keeping it raw if it is malformed
+fn decode_body(header: &Jt808Header, reader: &mut Reader) -> (Jt808Body, Vec<String>) {
+    let start = reader.pos;
+    let mut issues = Vec::new();
+    let body = match Jt808Body::decode(header, reader) {
+        Ok(body) => {
+            if reader.pos < reader.data.len() {
+                issues.push(format!("{} trailing body bytes were not decoded", reader.data.len() - reader.pos));
+            }
+            body
+        }
+        Err(e) => {
+            issues.push(e.to_string());
+            Jt808Body::Raw { data: hex::encode_upper(&reader.data[start..]) }
+        }
+    };
+    (body, issues)
+}
+
+/// Cursor over the unescaped frame that records where each field came from
+struct Reader<'a, 'b> {
+    data: &'a [u8],
+    pos: usize,
+    spans: &'b mut BTreeMap<String, (usize, usize)>,
+}
+
+impl<'a, 'b> Reader<'a, 'b> {
+    fn new(data: &'a [u8], spans: &'b mut BTreeMap<String, (usize, usize)>) -> Self {
+        Self { data, pos: 0, spans }
+    }
+
+    fn bytes(&mut self, name: &str, length: usize) -> NetworkResult<&'a [u8]> {
+        let data = self.data;
+        let bytes = data.get(self.pos..self.pos + length).ok_or_else(|| {
+            NetworkError::ParseError(format!(
+                "JT/T 808 field {} needs {} bytes at offset {}, {} left",
+                name, length, self.pos, data.len().saturating_sub(self.pos)
+            ))
+        })?;
+        self.spans.insert(name.to_string(), (self.pos, length));
+        self.pos += length;
+        Ok(bytes)
+    }
+
+    fn rest(&mut self, name: &str) -> &'a [u8] {
+        let data = self.data;
+        let rest = &data[self.pos.min(data.len())..];
+        self.spans.insert(name.to_string(), (self.pos, rest.len()));
+        self.pos = data.len();
+        rest
+    }
+
+    fn u8(&mut self, name: &str) -> NetworkResult<u8> {
+        Ok(self.bytes(name, 1)?[0])
+    }
+
+    fn u16(&mut self, name: &str) -> NetworkResult<u16> {
+        let bytes = self.bytes(name, 2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self, name: &str) -> NetworkResult<u32> {
+        let bytes = self.bytes(name, 4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn bcd(&mut self, name: &str, length: usize) -> NetworkResult<String> {
+        Ok(bcd::to_digits(self.bytes(name, length)?))
+    }
+
+    fn text(&mut self, name: &str, length: usize) -> NetworkResult<String> {
+        Ok(decode_text(self.bytes(name, length)?))
+    }
+}
+
+/// Decode the message header
+fn decode_header(reader: &mut Reader) -> NetworkResult<Jt808Header> {
+    let msg_id = reader.u16("msg_id")?;
+    let properties = reader.u16("body_length")?;
+    let version = if properties & (1 << 14) != 0 { Jt808Version::V2019 } else { Jt808Version::V2013 };
+    let subpackage = properties & (1 << 13) != 0;
+
+    let protocol_version = match version {
+        Jt808Version::V2013 => None,
+        Jt808Version::V2019 => Some(reader.u8("protocol_version")?),
+    };
+    let phone = reader.bcd("phone", version.phone_length())?;
+    let serial = reader.u16("serial")?;
+    let (total_packages, package_index) = if subpackage {
+        (Some(reader.u16("total_packages")?), Some(reader.u16("package_index")?))
+    } else {
+        (None, None)
+    };
+
+    Ok(Jt808Header {
+        msg_id,
+        body_length: properties & MAX_BODY_LENGTH as u16,
+        encryption: ((properties >> 10) & 0x07) as u8,
+        subpackage,
+        version,
+        protocol_version,
+        phone,
+        serial,
+        total_packages,
+        package_index,
+    })
+}
+
+/// Decode a location report body
+fn decode_location(reader: &mut Reader) -> NetworkResult<Jt808Location> {
+    let alarm = reader.u32("body.alarm")?;
+    let status = reader.u32("body.status")?;
+    let mut latitude = reader.u32("body.latitude")? as f64 / 1_000_000.0;
+    let mut longitude = reader.u32("body.longitude")? as f64 / 1_000_000.0;
+    if status & (1 << 2) != 0 {
+        latitude = -latitude;
+    }
+    if status & (1 << 3) != 0 {
+        longitude = -longitude;
+    }
+    let altitude = reader.u16("body.altitude")?;
+    let speed = reader.u16("body.speed")? as f64 / 10.0;
+    let direction = reader.u16("body.direction")?;
+    let time = NaiveDateTime::parse_from_str(&format!("20{}", reader.bcd("body.time", 6)?), "%Y%m%d%H%M%S").ok();
+
+    let mut additional = Vec::new();
+    while reader.pos < reader.data.len() {
+        let name = format!("body.additional[{}]", additional.len());
+        let id = reader.u8(&format!("{}.id", name))?;
+        let length = reader.u8(&format!("{}.length", name))? as usize;
+        let content = reader.bytes(&format!("{}.raw", name), length)?;
+        reader.spans.insert(format!("{}.value", name), (reader.pos - length, length));
+        additional.push(Jt808AdditionalInfo::new(id, content));
+    }
+
+    Ok(Jt808Location { alarm, status, latitude, longitude, altitude, speed, direction, time, additional })
+}
+
+/// Encode a location report body
+fn encode_location(location: &Jt808Location, body: &mut Vec<u8>) -> NetworkResult<()> {
+    let mut status = location.status & !0b1100;
+    if location.latitude < 0.0 {
+        status |= 1 << 2;
+    }
+    if location.longitude < 0.0 {
+        status |= 1 << 3;
+    }
+
+    body.extend_from_slice(&location.alarm.to_be_bytes());
+    body.extend_from_slice(&status.to_be_bytes());
+    body.extend_from_slice(&((location.latitude.abs() * 1_000_000.0).round() as u32).to_be_bytes());
+    body.extend_from_slice(&((location.longitude.abs() * 1_000_000.0).round() as u32).to_be_bytes());
+    body.extend_from_slice(&location.altitude.to_be_bytes());
+    body.extend_from_slice(&((location.speed * 10.0).round() as u16).to_be_bytes());
+    body.extend_from_slice(&location.direction.to_be_bytes());
+    let time = location.time.map(|time| time.format("%y%m%d%H%M%S").to_string()).unwrap_or_else(|| "0".repeat(12));
+    body.extend(bcd::encode(&time, 6)?);
+
+    for item in &location.additional {
+        let content = hex::decode(&item.raw)
+            .map_err(|e| NetworkError::ParseError(format!("Invalid additional info {:02X}: {}", item.id, e)))?;
+        let length = u8::try_from(content.len())
+            .map_err(|_| NetworkError::ParseError(format!("Additional info {:02X} is too long", item.id)))?;
+        body.push(item.id);
+        body.push(length);
+        body.extend(content);
+    }
+    Ok(())
+}
+
+/// GBK text without NUL padding
+fn decode_text(bytes: &[u8]) -> String {
+    let (text, _, _) = encoding_rs::GBK.decode(bytes);
+    text.trim_end_matches('\0').trim().to_string()
+}
+
+/// GBK text, NUL-padded to a fixed width when given
+fn encode_text(text: &str, width: Option<usize>) -> NetworkResult<Vec<u8>> {
+    let (bytes, _, _) = encoding_rs::GBK.encode(text);
+    let mut bytes = bytes.into_owned();
+    if let Some(width) = width {
+        if bytes.len() > width {
+            return Err(NetworkError::ParseError(format!("'{}' is longer than {} bytes", text, width)));
+        }
+        bytes.resize(width, 0);
+    }
+    Ok(bytes)
+}
+
+/// Flatten a JSON value into named parse result fields
+fn flatten_json(name: &str, value: &serde_json::Value, fields: &mut Vec<(String, FieldValue, &'static str)>) {
+    use serde_json::Value;
+
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                flatten_json(&format!("{}.{}", name, key), value, fields);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                flatten_json(&format!("{}[{}]", name, index), value, fields);
+            }
+        }
+        Value::Bool(flag) => fields.push((name.to_string(), FieldValue::Bool(*flag), "bool")),
+        Value::Number(number) => {
+            let field = if let Some(value) = number.as_u64() {
+                (FieldValue::UInt(value), "uint")
+            } else if let Some(value) = number.as_i64() {
+                (FieldValue::Int(value), "int")
+            } else {
+                (FieldValue::Float(number.as_f64().unwrap_or_default()), "float")
+            };
+            fields.push((name.to_string(), field.0, field.1));
+        }
+        Value::String(text) => fields.push((name.to_string(), FieldValue::String(text.clone()), "string")),
+        Value::Null => {}
+    }
+}
+
+/// Name, unit and scale of the common location additional-info items
+fn additional_info_definition(id: u8) -> Option<(&'static str, Option<&'static str>, Option<f64>)> {
+    Some(match id {
+        0x01 => ("里程", Some("km"), Some(0.1)),
+        0x02 => ("油量", Some("L"), Some(0.1)),
+        0x03 => ("行驶记录速度", Some("km/h"), Some(0.1)),
+        0x04 => ("需人工确认报警事件ID", None, Some(1.0)),
+        0x05 => ("胎压", None, None),
+        0x06 => ("车厢温度", Some("℃"), None),
+        0x11 => ("超速报警附加信息", None, None),
+        0x12 => ("进出区域/路线报警附加信息", None, None),
+        0x13 => ("路段行驶时间不足/过长报警附加信息", None, None),
+        0x25 => ("扩展车辆信号状态位", None, Some(1.0)),
+        0x2A => ("IO状态位", None, Some(1.0)),
+        0x2B => ("模拟量", None, Some(1.0)),
+        0x30 => ("无线通信网络信号强度", None, Some(1.0)),
+        0x31 => ("GNSS定位卫星数", None, Some(1.0)),
+        _ => return None,
+    })
+}
+
+/// Name of a message ID
+pub fn message_name(msg_id: u16) -> Option<&'static str> {
+    Some(match msg_id {
+        0x0001 => "终端通用应答",
+        0x0002 => "终端心跳",
+        0x0003 => "终端注销",
+        0x0004 => "查询服务器时间请求",
+        0x0100 => "终端注册",
+        0x0102 => "终端鉴权",
+        0x0104 => "查询终端参数应答",
+        0x0107 => "查询终端属性应答",
+        0x0200 => "位置信息汇报",
+        0x0201 => "位置信息查询应答",
+        0x0704 => "定位数据批量上传",
+        0x0800 => "多媒体事件信息上传",
+        0x0801 => "多媒体数据上传",
+        0x0900 => "数据上行透传",
+        0x8001 => "平台通用应答",
+        0x8003 => "补传分包请求",
+        0x8004 => "查询服务器时间应答",
+        0x8100 => "终端注册应答",
+        0x8103 => "设置终端参数",
+        0x8104 => "查询终端参数",
+        0x8201 => "位置信息查询",
+        0x8202 => "临时位置跟踪控制",
+        0x8300 => "文本信息下发",
+        0x8900 => "数据下行透传",
+        _ => return None,
+    })
+}
+
+/// Protocol information for JT/T 808 parse results
+fn jt808_protocol_info(confidence: f64) -> ProtocolInfo {
+    ProtocolInfo {
+        name: "JT/T 808".to_string(),
+        version: "2019".to_string(),
+        parser_id: JT808_PARSER_ID.to_string(),
+        confidence,
+    }
+}
+
+/// Stream, phone number, message ID, first serial number and package count
+/// of a sub-packaged message
+type MessageKey = (ReassemblyScope, String, u16, u16, u16);
+
+/// Packages received so far for one sub-packaged message
+#[derive(Debug)]
+struct PendingMessage {
+    /// Packages and their frames by one-based index
+    packages: BTreeMap<u16, (Jt808Packet, Vec<u8>)>,
+
+    /// When the first package arrived
+    started_at: Instant,
+
+    /// When the last package arrived
+    updated_at: Instant,
+}
+
+/// Joins the packages of sub-packaged messages
+#[derive(Debug)]
+pub struct Jt808Reassembler {
+    /// How long a message waits for its next package
+    timeout: Duration,
+
+    /// Incomplete messages
+    pending: HashMap<MessageKey, PendingMessage>,
+}
+
+impl Default for Jt808Reassembler {
+    fn default() -> Self {
+        Self::new(PACKAGE_TIMEOUT)
+    }
+}
+
+impl Jt808Reassembler {
+    /// Create a reassembler with the given package timeout
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Feed a decoded frame received on the given stream at the given instant
+    ///
+    /// Returns the frame's own parse result, or the joined message once its
+    /// last package arrives.
+    pub fn push_at(&mut self, scope: &ReassemblyScope, packet: Jt808Packet, frame: &[u8], now: Instant) -> ParseResult {
+        let mut result = packet.to_parse_result(frame);
+        let header = &packet.header;
+        let (Some(total), Some(index)) = (header.total_packages, header.package_index) else {
+            return result;
+        };
+        if index == 0 || index > total {
+            result.add_warning(ParseWarning::new(
+                "FRAGMENT_INDEX_OUT_OF_RANGE",
+                format!("Package index {} outside the {} package(s) of this message", index, total),
+            ));
+            return result;
+        }
+        // Packages with a bad check code or length are reported, not joined
+        if !packet.is_valid() {
+            return result;
+        }
+
+        let key = (
+            scope.clone(),
+            header.phone.clone(),
+            header.msg_id,
+            header.serial.wrapping_sub(index - 1),
+            total,
+        );
+        if !self.pending.contains_key(&key) {
+            self.evict_if_full(&mut result);
+            self.pending.insert(key.clone(), PendingMessage {
+                packages: BTreeMap::new(),
+                started_at: now,
+                updated_at: now,
+            });
+        }
+
+        let message = self.pending.get_mut(&key).unwrap();
+        if message.packages.insert(index, (packet, frame.to_vec())).is_some() {
+            result.add_warning(ParseWarning::new(
+                "FRAGMENT_DUPLICATE",
+                format!("Duplicate package {} replaces the earlier copy", index),
+            ));
+        }
+        message.updated_at = now;
+
+        if message.packages.len() == total as usize {
+            let message = self.pending.remove(&key).unwrap();
+            return join_packages(&key, message);
+        }
+
+        result.metadata.extra.insert("reassembly".to_string(), serde_json::json!({
+            "key": message_label(&key),
+            "index": index,
+            "total": total,
+            "received": message.packages.len(),
+            "complete": false,
+        }));
+        result
+    }
+
+    /// Drop messages whose next package timed out, returning the stream and a
+    /// warning for each
+    pub fn expire(&mut self, now: Instant) -> Vec<(ReassemblyScope, ParseWarning)> {
+        let expired: Vec<MessageKey> = self.pending.iter()
+            .filter(|(_, message)| now.saturating_duration_since(message.updated_at) >= self.timeout)
+            .map(|(key, _)| key.clone())
+            .collect();
+
+        expired.into_iter()
+            .filter_map(|key| {
+                let message = self.pending.remove(&key)?;
+                let warning = incomplete_warning(&key, &message);
+                Some((key.0, warning))
+            })
+            .collect()
+    }
+
+    /// Number of incomplete messages
+    pub fn pending_messages(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Make room for a new message by dropping the oldest one
+    fn evict_if_full(&mut self, result: &mut ParseResult) {
+        if self.pending.len() < MAX_PENDING_MESSAGES {
+            return;
+        }
+
+        let oldest = self.pending.iter()
+            .min_by_key(|(_, message)| message.started_at)
+            .map(|(key, _)| key.clone());
+
+        if let Some(key) = oldest {
+            if let Some(message) = self.pending.remove(&key) {
+                result.add_warning(incomplete_warning(&key, &message));
+            }
+        }
+    }
+}
+
+/// Readable key of a sub-packaged message: phone, message ID and first serial
+fn message_label(key: &MessageKey) -> String {
+    format!("{}/0x{:04X}/{}", key.1, key.2, key.3)
+}
+
+/// Warning for a sub-packaged message that never completed
+fn incomplete_warning(key: &MessageKey, message: &PendingMessage) -> ParseWarning {
+    let received: Vec<u16> = message.packages.keys().copied().collect();
+    let mut warning = ParseWarning::new(
+        "FRAGMENT_INCOMPLETE",
+        format!(
+            "Incomplete JT/T 808 message '{}': received {} of {} package(s)",
+            message_label(key), received.len(), key.4
+        ),
+    );
+    warning.context.insert("key".to_string(), serde_json::json!(message_label(key)));
+    warning.context.insert("received".to_string(), serde_json::json!(received));
+    warning.context.insert("total".to_string(), serde_json::json!(key.4));
+    warning
+}
+
+/// Decode the joined body of a complete message into one parse result
+///
+/// Header fields and their offsets come from the first package; the raw data
+/// holds every package's frame.
+fn join_packages(key: &MessageKey, message: PendingMessage) -> ParseResult {
+    let total = message.packages.len();
+    let mut packages = message.packages.into_values();
+    // A complete message has at least one package
+    let (mut packet, first_frame) = packages.next().unwrap();
+    let mut frames = first_frame.clone();
+    let mut body = packet.body.encode(packet.header.version).unwrap_or_default();
+    for (package, frame) in packages {
+        body.extend(package.body.encode(package.header.version).unwrap_or_default());
+        frames.extend(frame);
+        packet.issues.extend(package.issues);
+    }
+
+    let mut unpacked = packet.header.clone();
+    unpacked.subpackage = false;
+    let mut spans = BTreeMap::new();
+    let (decoded, issues) = decode_body(&unpacked, &mut Reader::new(&body, &mut spans));
+    packet.body = decoded;
+    packet.issues.extend(issues);
+    packet.header.body_length = u16::try_from(body.len()).unwrap_or(u16::MAX);
+    packet.received_body_length = body.len();
+    // Body offsets of the first package do not apply to the joined body
+    packet.spans.retain(|name, _| !name.starts_with("body."));
+
+    let mut result = packet.to_parse_result(&first_frame);
+    result.raw_data = frames;
+    result.parsed_size = result.raw_data.len();
+    result.metadata.extra.insert("reassembly".to_string(), serde_json::json!({
+        "key": message_label(key),
+        "total": key.4,
+        "received": total,
+        "complete": true,
+        "duration_ms": message.updated_at.saturating_duration_since(message.started_at).as_millis() as u64,
+    }));
+    result
+}
+
+/// Built-in JT/T 808 parser
+#[derive(Debug, Default)]
+pub struct Jt808Parser {
+    /// Sub-packaged messages waiting for their remaining packages
+    reassembler: RwLock<Jt808Reassembler>,
+}
+
+impl Jt808Parser {
+    /// Create the parser
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode a frame received on the given stream, joining sub-packages
+    fn parse_scoped(&self, data: &[u8], scope: &ReassemblyScope) -> NetworkResult<ParseResult> {
+        match Jt808Packet::decode(data) {
+            Ok(packet) => Ok(self.reassembler.write().unwrap().push_at(scope, packet, data, Instant::now())),
+            Err(e) => Ok(ParseResult::failure(
+                jt808_protocol_info(0.0),
+                data.to_vec(),
+                ParseError::new("JT808_DECODE", e.to_string(), ErrorSeverity::Critical),
+            )),
+        }
+    }
+}
+
+impl Parser for Jt808Parser {
+    fn parse(&self, data: &[u8]) -> NetworkResult<ParseResult> {
+        self.parse_scoped(data, &ReassemblyScope::default())
+    }
+
+    fn parse_with_envelope(&self, data: &[u8], envelope: &EnvelopeContext) -> NetworkResult<ParseResult> {
+        let mut result = self.parse_scoped(data, &ReassemblyScope::from_envelope(Some(envelope)))?;
+        envelope.apply_to(&mut result);
+        Ok(result)
+    }
+
+    fn expire_fragments(&self, now: Instant) -> Vec<ExpiredFragments> {
+        self.reassembler.write().unwrap().expire(now)
+            .into_iter()
+            .map(|(scope, warning)| ExpiredFragments {
+                session_id: scope.session_id,
+                client_id: scope.client_id,
+                parser_id: JT808_PARSER_ID.to_string(),
+                warning,
+            })
+            .collect()
+    }
+
+    fn validate(&self, result: &ParseResult) -> ValidationReport {
+        ValidationReport::from_parse_result(result)
+    }
+
+    fn get_protocol_info(&self) -> ParserProtocolInfo {
+        ParserProtocolInfo {
+            name: "JT/T 808".to_string(),
+            version: "2019".to_string(),
+            author: "JT/T 808-2013 / JT/T 808-2019".to_string(),
+            description: "Road transport vehicle satellite positioning terminal protocol".to_string(),
+            supported_formats: vec!["binary".to_string()],
+            magic_bytes: Some(vec![FLAG]),
+            min_frame_size: Some(15),
+            max_frame_size: Some((MAX_BODY_LENGTH + 22) * 2 + 2),
+        }
+    }
+
+    fn get_id(&self) -> &str {
+        JT808_PARSER_ID
+    }
+
+    fn can_parse(&self, data: &[u8]) -> bool {
+        data.len() >= 15
+            && data[0] == FLAG
+            && data[data.len() - 1] == FLAG
+            && !data[1..data.len() - 1].contains(&FLAG)
+    }
+
+    fn stream_framer(&self) -> Option<FrameDetector> {
+        let flag = char::from(FLAG).to_string();
+        Some(FrameDetector::new(FramingRule {
+            start_delimiter: Some(flag.clone()),
+            end_delimiter: Some(flag),
+            length_field: None,
+            fixed_size: None,
+            escape_rules: Vec::new(),
+            frame_validation: FrameValidation::default(),
+            block_crc: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location_2013() -> Vec<u8> {
+        let mut content = vec![0x02, 0x00, 0x00, 0x00];
+        content.extend_from_slice(&[0x01, 0x38, 0x00, 0x13, 0x80, 0x00]);
+        content.extend_from_slice(&[0x00, 0x7E]);
+        let mut body = Vec::new();
+        body.extend_from_slice(&1u32.to_be_bytes());
+        body.extend_from_slice(&0x0000_0003u32.to_be_bytes());
+        body.extend_from_slice(&22_543_096u32.to_be_bytes());
+        body.extend_from_slice(&114_057_865u32.to_be_bytes());
+        body.extend_from_slice(&32u16.to_be_bytes());
+        body.extend_from_slice(&605u16.to_be_bytes());
+        body.extend_from_slice(&90u16.to_be_bytes());
+        body.extend_from_slice(&[0x24, 0x01, 0x02, 0x03, 0x04, 0x05]);
+        body.extend_from_slice(&[0x01, 0x04, 0x00, 0x00, 0x30, 0x39]);
+        body.extend_from_slice(&[0x30, 0x01, 0x1F]);
+        content[3] = body.len() as u8;
+        content.extend(body);
+        encode_frame(&content)
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        let data = [0x30, 0x7E, 0x08, 0x7D, 0x55];
+        let escaped = escape(&data);
+        assert_eq!(escaped, vec![0x30, 0x7D, 0x02, 0x08, 0x7D, 0x01, 0x55]);
+        assert_eq!(unescape(&escaped).unwrap(), data.to_vec());
+        assert!(unescape(&[0x7D, 0x03]).is_err());
+    }
+
+    #[test]
+    fn test_decode_location_report() {
+        let frame = location_2013();
+        // The serial number 0x007E must be escaped on the wire
+        assert!(frame.windows(2).any(|pair| pair == [0x7D, 0x02]));
+
+        let packet = Jt808Packet::decode(&frame).unwrap();
+        assert!(packet.is_valid(), "{:?}", packet.issues);
+        assert_eq!(packet.header.version, Jt808Version::V2013);
+        assert_eq!(packet.header.phone, "013800138000");
+        assert_eq!(packet.header.serial, 0x7E);
+
+        let Jt808Body::Location(location) = &packet.body else {
+            panic!("expected a location report, got {:?}", packet.body);
+        };
+        assert!(location.acc_on() && location.positioned());
+        assert_eq!(location.latitude, 22.543096);
+        assert_eq!(location.speed, 60.5);
+        assert_eq!(location.time.unwrap().to_string(), "2024-01-02 03:04:05");
+        assert_eq!(location.additional.len(), 2);
+        assert_eq!(location.additional[0].value, Some(1234.5));
+        assert_eq!(location.additional[1].name.as_deref(), Some("无线通信网络信号强度"));
+
+        let result = Jt808Parser::new().parse(&frame).unwrap();
+        assert!(result.success);
+        let field = result.fields.get_field("body.latitude").unwrap();
+        assert_eq!(field.value, FieldValue::Float(22.543096));
+        assert_eq!(field.offset, 1 + 12 + 8);
+        assert_eq!(field.raw_bytes, 22_543_096u32.to_be_bytes().to_vec());
+
+        assert_eq!(packet.encode().unwrap(), frame);
+    }
+
+    #[test]
+    fn test_platform_response_2019() {
+        let mut header = Jt808Header::new(MSG_AUTHENTICATION, Jt808Version::V2019, "13800138000", 7);
+        header.protocol_version = Some(1);
+        let request = Jt808Packet::new(header, Jt808Body::Authentication {
+            auth_code: "AUTH123".to_string(),
+            imei: Some("860000000000001".to_string()),
+            software_version: Some("V1.0".to_string()),
+        }).unwrap();
+        let decoded = Jt808Packet::decode(&request.encode().unwrap()).unwrap();
+        assert!(decoded.is_valid());
+        assert_eq!(decoded.header.phone, "00000000013800138000");
+        assert_eq!(decoded.body, request.body);
+
+        let response = Jt808Packet::platform_response(&decoded.header, 1, RESULT_SUCCESS).unwrap();
+        let frame = response.encode().unwrap();
+        let response = Jt808Packet::decode(&frame).unwrap();
+        assert!(response.is_valid());
+        assert_eq!(response.header.msg_id, MSG_PLATFORM_RESPONSE);
+        assert_eq!(response.header.version, Jt808Version::V2019);
+        assert_eq!(response.body, Jt808Body::PlatformResponse { ack_serial: 7, ack_msg_id: MSG_AUTHENTICATION, result: RESULT_SUCCESS });
+
+        let mut content = unescape(&frame[1..frame.len() - 1]).unwrap();
+        *content.last_mut().unwrap() ^= 0x01;
+        let mut corrupted = vec![FLAG];
+        corrupted.extend(escape(&content));
+        corrupted.push(FLAG);
+        let result = Jt808Parser::new().parse(&corrupted).unwrap();
+        assert!(result.errors.iter().any(|e| e.code == "CHECKSUM_MISMATCH"));
+    }
+
+    #[test]
+    fn test_sub_packages_are_joined() {
+        let location = Jt808Packet::decode(&location_2013()).unwrap();
+        let body = location.body.encode(Jt808Version::V2013).unwrap();
+        let package = |index: u16, part: &[u8], serial: u16| {
+            let mut header = Jt808Header::new(MSG_LOCATION, Jt808Version::V2013, "13800138000", serial);
+            header.subpackage = true;
+            header.total_packages = Some(3);
+            header.package_index = Some(index);
+            Jt808Packet::new(header, Jt808Body::Raw { data: hex::encode_upper(part) }).unwrap().encode().unwrap()
+        };
+        let frames = [package(1, &body[..10], 20), package(2, &body[10..20], 21), package(3, &body[20..], 22)];
+
+        let parser = Jt808Parser::new();
+        let envelope = EnvelopeContext::for_session("tcp", "s1", Some("c1"), None);
+        let second = parser.parse_with_envelope(&frames[1], &envelope).unwrap();
+        assert_eq!(second.metadata.extra["reassembly"]["complete"], false);
+        assert!(second.fields.get_field("body.data").is_some());
+        parser.parse_with_envelope(&frames[0], &envelope).unwrap();
+        // The same package on another stream belongs to a different message
+        parser.parse(&frames[2]).unwrap();
+
+        let joined = parser.parse_with_envelope(&frames[2], &envelope).unwrap();
+        assert!(joined.success && joined.errors.is_empty(), "{:?}", joined.errors);
+        assert_eq!(joined.metadata.extra["reassembly"]["complete"], true);
+        assert_eq!(joined.fields.get_field("body.latitude").unwrap().value, FieldValue::Float(22.543096));
+        assert_eq!(joined.raw_data, frames.concat());
+
+        // The unscoped package times out incomplete
+        assert!(parser.expire_fragments(Instant::now()).is_empty());
+        let expired = parser.expire_fragments(Instant::now() + PACKAGE_TIMEOUT);
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].warning.code, "FRAGMENT_INCOMPLETE");
+        assert_eq!(expired[0].session_id, None);
+    }
+}
@@ -0,0 +1,716 @@
+//! Static linting of protocol rules
+//!
+//! `RuleValidator` rejects rules that cannot be loaded. The linter looks for
+//! rules that load but are probably wrong: overlapping or gapped fields,
+//! fields past the frame size, CRC ranges covering the CRC itself, unknown
+//! field references and byte order on single-byte fields. Findings are
+//! returned as a `ValidationReport` with fix suggestions for the editor.
+
+use crate::parser::schema::*;
+use crate::parser::types::TypeParser;
+use crate::parser::validation_report::*;
+use crate::types::{NetworkResult, NetworkError};
+use chrono::Utc;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Words that may appear in condition expressions without being field names
+const EXPRESSION_KEYWORDS: &[&str] = &["and", "or", "not", "in", "true", "false", "null", "envelope"];
+
+/// Byte span of a field with an absolute offset
+#[derive(Debug, Clone)]
+struct FieldSpan<'a> {
+    field: &'a FieldDefinition,
+    start: usize,
+    /// End offset (exclusive), `None` for fields running to the frame end
+    end: Option<usize>,
+}
+
+/// Protocol rule linter
+#[derive(Debug, Default)]
+pub struct RuleLinter {
+    /// Fields with an explicit `endian` key in the rule source
+    explicit_endian: HashSet<String>,
+}
+
+impl RuleLinter {
+    /// Create a new rule linter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lint rule source (YAML)
+    ///
+    /// Unlike `lint`, this can tell an explicit `endian: big` from the default.
+    pub fn lint_str(content: &str) -> NetworkResult<ValidationReport> {
+        let rule: ProtocolRule = serde_yaml::from_str(content)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to parse YAML: {}", e)))?;
+        let raw: serde_yaml::Value = serde_yaml::from_str(content)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to parse YAML: {}", e)))?;
+
+        let explicit_endian = raw.get("fields")
+            .and_then(|fields| fields.as_sequence())
+            .map(|fields| fields.iter()
+                .filter(|field| field.get("endian").is_some())
+                .filter_map(|field| field.get("name").and_then(|n| n.as_str()).map(str::to_string))
+                .collect())
+            .unwrap_or_default();
+
+        Ok(Self { explicit_endian }.lint(&rule))
+    }
+
+    /// Lint a protocol rule
+    pub fn lint(&self, rule: &ProtocolRule) -> ValidationReport {
+        let start_time = std::time::Instant::now();
+        let mut report = ValidationReport::new();
+
+        let spans = fixed_spans(&rule.fields);
+
+        self.lint_layout(&spans, &mut report);
+        self.lint_frame_size(rule, &spans, &mut report);
+        self.lint_integrity(rule, &spans, &mut report);
+        self.lint_references(rule, &mut report);
+        self.lint_field_types(rule, &mut report);
+
+        report.metrics.validation_time_ms = start_time.elapsed().as_secs_f64() * 1000.0;
+        report.metrics.rules_evaluated = rule.fields.len();
+        report.metadata.validator_version = "lint-1.0".to_string();
+        report
+    }
+
+    /// Overlapping and gapped fixed fields
+    fn lint_layout(&self, spans: &[FieldSpan], report: &mut ValidationReport) {
+        let mut previous: Option<&FieldSpan> = None;
+
+        for span in spans {
+            if let Some(prev) = previous {
+                let prev_end = prev.end.unwrap_or(usize::MAX);
+
+                if span.start < prev_end {
+                    let id = format!("LINT_FIELD_OVERLAP_{}", span.field.name);
+                    report.add_issue(issue(
+                        &id,
+                        IssueSeverity::Error,
+                        IssueCategory::FieldParsing,
+                        format!("Field '{}' overlaps '{}'", span.field.name, prev.field.name),
+                        format!(
+                            "Field '{}' starts at offset {} but '{}' ends at {}",
+                            span.field.name,
+                            span.start,
+                            prev.field.name,
+                            prev.end.map(|e| e.to_string()).unwrap_or_else(|| "the end of the frame".to_string())
+                        ),
+                        Some(&span.field.name),
+                        Some(span.start),
+                    ));
+                    if let Some(prev_end) = prev.end {
+                        report.add_suggestion(suggestion(
+                            &id,
+                            format!("Move '{}' to offset {}", span.field.name, prev_end),
+                            "Fields with absolute offsets should not share bytes. Move the field after the previous one or shorten the previous field.".to_string(),
+                            SuggestionType::FieldUpdate,
+                            0.7,
+                            vec![field_example(&span.field.name, &format!("offset: {}", prev_end))],
+                        ));
+                    }
+                } else if span.start > prev_end {
+                    let id = format!("LINT_FIELD_GAP_{}", span.field.name);
+                    let gap = span.start - prev_end;
+                    report.add_issue(issue(
+                        &id,
+                        IssueSeverity::Warning,
+                        IssueCategory::FieldParsing,
+                        format!("{} unparsed byte(s) before '{}'", gap, span.field.name),
+                        format!(
+                            "Bytes {}..{} between '{}' and '{}' are not covered by any field",
+                            prev_end, span.start, prev.field.name, span.field.name
+                        ),
+                        Some(&span.field.name),
+                        Some(prev_end),
+                    ));
+                    report.add_suggestion(suggestion(
+                        &id,
+                        format!("Cover bytes {}..{}", prev_end, span.start),
+                        "Add a reserved field for the gap, or fix the offset if the gap is unintended.".to_string(),
+                        SuggestionType::FieldUpdate,
+                        0.5,
+                        vec![CodeExample {
+                            title: "Reserved field".to_string(),
+                            language: "yaml".to_string(),
+                            code: format!("- name: reserved_{}\n  type: bytes\n  offset: {}\n  length: {}", prev_end, prev_end, gap),
+                            description: "Explicitly mark the bytes as reserved".to_string(),
+                        }],
+                    ));
+                }
+            }
+
+            // Keep the span reaching furthest so nested overlaps are compared against it
+            let reaches_further = match previous.map(|prev| (prev.end, span.end)) {
+                None => true,
+                Some((None, _)) => false,
+                Some((Some(_), None)) => true,
+                Some((Some(prev_end), Some(end))) => end > prev_end,
+            };
+            if reaches_further {
+                previous = Some(span);
+            }
+        }
+    }
+
+    /// Fields past the frame size and length fields that disagree with it
+    fn lint_frame_size(&self, rule: &ProtocolRule, spans: &[FieldSpan], report: &mut ValidationReport) {
+        let framing = &rule.framing;
+        let limits = [
+            (framing.fixed_size, "fixed_size"),
+            (framing.frame_validation.max_size, "max_size"),
+        ];
+
+        for (limit, name) in limits {
+            let Some(limit) = limit else { continue };
+            for span in spans {
+                let end = span.end.unwrap_or(span.start);
+                if end > limit || span.start >= limit {
+                    let id = format!("LINT_FIELD_PAST_{}_{}", name.to_uppercase(), span.field.name);
+                    report.add_issue(issue(
+                        &id,
+                        IssueSeverity::Error,
+                        IssueCategory::Framing,
+                        format!("Field '{}' extends past {}", span.field.name, name),
+                        format!("Field '{}' ends at byte {} but {} is {}", span.field.name, end, name, limit),
+                        Some(&span.field.name),
+                        Some(span.start),
+                    ));
+                    report.add_suggestion(suggestion(
+                        &id,
+                        format!("Increase {} or move '{}'", name, span.field.name),
+                        format!("The field can never be parsed from a frame of at most {} bytes.", limit),
+                        SuggestionType::FramingFix,
+                        0.6,
+                        vec![framing_example(&format!("{}: {}", name, end))],
+                    ));
+                }
+            }
+        }
+
+        if let Some(fixed_size) = framing.fixed_size {
+            if let Some(length_field) = &framing.length_field {
+                let id = "LINT_LENGTH_FIELD_WITH_FIXED_SIZE".to_string();
+                let length_end = length_field.offset + length_field.length;
+                let (severity, description) = if length_end > fixed_size {
+                    (IssueSeverity::Error, format!(
+                        "The length field ends at byte {} but frames are fixed at {} bytes",
+                        length_end, fixed_size
+                    ))
+                } else {
+                    (IssueSeverity::Warning, format!(
+                        "Frames are fixed at {} bytes, so the length field at offset {} is not used for framing",
+                        fixed_size, length_field.offset
+                    ))
+                };
+                report.add_issue(issue(
+                    &id,
+                    severity,
+                    IssueCategory::Framing,
+                    "Length field disagrees with fixed_size".to_string(),
+                    description,
+                    None,
+                    Some(length_field.offset),
+                ));
+                report.add_suggestion(suggestion(
+                    &id,
+                    "Use either length_field or fixed_size".to_string(),
+                    "Remove fixed_size for variable-length frames, or remove length_field if every frame has the same size.".to_string(),
+                    SuggestionType::FramingFix,
+                    0.8,
+                    Vec::new(),
+                ));
+            }
+
+            let bounds = [
+                (framing.frame_validation.min_size.filter(|min| *min > fixed_size), "min_size"),
+                (framing.frame_validation.max_size.filter(|max| *max < fixed_size), "max_size"),
+            ];
+            for (value, name) in bounds {
+                if let Some(value) = value {
+                    let id = format!("LINT_{}_CONFLICTS_FIXED_SIZE", name.to_uppercase());
+                    report.add_issue(issue(
+                        &id,
+                        IssueSeverity::Error,
+                        IssueCategory::Framing,
+                        format!("{} conflicts with fixed_size", name),
+                        format!("{} is {} but fixed_size is {}", name, value, fixed_size),
+                        None,
+                        None,
+                    ));
+                    report.add_suggestion(suggestion(
+                        &id,
+                        format!("Set {} to {}", name, fixed_size),
+                        "Every frame would be rejected by frame validation.".to_string(),
+                        SuggestionType::FramingFix,
+                        0.8,
+                        Vec::new(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// CRC and checksum ranges that include their own field
+    fn lint_integrity(&self, rule: &ProtocolRule, spans: &[FieldSpan], report: &mut ValidationReport) {
+        let checks = rule.validation.crc.iter()
+            .map(|crc| ("CRC", &crc.crc_field, &crc.data_range))
+            .chain(rule.validation.checksum.iter()
+                .map(|checksum| ("Checksum", &checksum.checksum_field, &checksum.data_range)));
+
+        for (kind, field_name, range) in checks {
+            let Some(span) = spans.iter().find(|span| &span.field.name == field_name) else {
+                continue;
+            };
+
+            let range_end = match &range.end {
+                DataRangeEnd::Absolute(end) => *end,
+                DataRangeEnd::Length(length) => range.start + length,
+                DataRangeEnd::EndOfFrame => usize::MAX,
+                DataRangeEnd::Expression(_) => continue,
+            };
+            let field_end = span.end.unwrap_or(usize::MAX);
+
+            if span.start < range_end && field_end > range.start {
+                let id = format!("LINT_{}_RANGE_INCLUDES_FIELD_{}", kind.to_uppercase(), field_name);
+                report.add_issue(issue(
+                    &id,
+                    IssueSeverity::Error,
+                    IssueCategory::Integrity,
+                    format!("{} range includes the {} field '{}'", kind, kind.to_lowercase(), field_name),
+                    format!(
+                        "The {} is calculated over bytes {}..{} which include '{}' at offset {}",
+                        kind.to_lowercase(),
+                        range.start,
+                        if range_end == usize::MAX { "end".to_string() } else { range_end.to_string() },
+                        field_name,
+                        span.start
+                    ),
+                    Some(field_name),
+                    Some(span.start),
+                ));
+                report.add_suggestion(suggestion(
+                    &id,
+                    format!("End the {} range at offset {}", kind.to_lowercase(), span.start),
+                    format!("A {} cannot cover its own bytes; validation would always fail.", kind.to_lowercase()),
+                    SuggestionType::ValidationFix,
+                    0.9,
+                    vec![CodeExample {
+                        title: "Data range".to_string(),
+                        language: "yaml".to_string(),
+                        code: format!("data_range:\n  start: {}\n  end: {}", range.start, span.start),
+                        description: format!("Stop before the {} field", kind.to_lowercase()),
+                    }],
+                ));
+            }
+        }
+    }
+
+    /// References to fields that do not exist
+    fn lint_references(&self, rule: &ProtocolRule, report: &mut ValidationReport) {
+        let mut known: HashSet<&str> = HashSet::new();
+        for field in all_fields(rule) {
+            known.insert(field.name.as_str());
+        }
+        for name in rule.functions.keys() {
+            known.insert(name.as_str());
+        }
+
+        let mut references: Vec<(String, String, Option<String>)> = Vec::new();
+
+        for field in all_fields(rule) {
+            if let FieldLength::Variable(length_field) = &field.length {
+                references.push((length_field.clone(), format!("length of field '{}'", field.name), Some(field.name.clone())));
+            }
+            if let Some(condition) = &field.condition {
+                for name in expression_identifiers(condition) {
+                    references.push((name, format!("condition of field '{}'", field.name), Some(field.name.clone())));
+                }
+            }
+        }
+
+        for condition in &rule.conditions {
+            for name in expression_identifiers(&condition.condition) {
+                references.push((name, format!("conditional rule '{}'", condition.condition), None));
+            }
+        }
+
+        for crc in &rule.validation.crc {
+            references.push((crc.crc_field.clone(), "CRC validation".to_string(), None));
+        }
+        for checksum in &rule.validation.checksum {
+            references.push((checksum.checksum_field.clone(), "checksum validation".to_string(), None));
+        }
+
+        let mut reported = HashSet::new();
+        for (name, source, field) in references {
+            if known.contains(name.as_str()) || !reported.insert((name.clone(), source.clone())) {
+                continue;
+            }
+
+            let id = format!("LINT_UNKNOWN_FIELD_{}", name);
+            report.add_issue(issue(
+                &id,
+                IssueSeverity::Error,
+                IssueCategory::Configuration,
+                format!("Unknown field '{}'", name),
+                format!("The {} references '{}', which is not defined", source, name),
+                field.as_deref(),
+                None,
+            ));
+
+            let closest = closest_name(&name, &known);
+            report.add_suggestion(suggestion(
+                &id,
+                match &closest {
+                    Some(candidate) => format!("Did you mean '{}'?", candidate),
+                    None => format!("Define field '{}'", name),
+                },
+                format!("Fix the reference in the {} or add the missing field.", source),
+                SuggestionType::FieldUpdate,
+                if closest.is_some() { 0.7 } else { 0.4 },
+                Vec::new(),
+            ));
+        }
+    }
+
+    /// Byte order on single-byte fields and lengths that disagree with the type
+    fn lint_field_types(&self, rule: &ProtocolRule, report: &mut ValidationReport) {
+        for field in all_fields(rule) {
+            let type_size = TypeParser::get_type_size(&field.field_type);
+            let length = match &field.length {
+                FieldLength::Fixed(length) => Some(*length),
+                _ => None,
+            };
+            let single_byte = type_size.or(length) == Some(1);
+
+            let endian_set = self.explicit_endian.contains(&field.name)
+                || !matches!(field.endian, Endianness::Big);
+            if single_byte && endian_set {
+                let id = format!("LINT_ENDIAN_SINGLE_BYTE_{}", field.name);
+                report.add_issue(issue(
+                    &id,
+                    IssueSeverity::Warning,
+                    IssueCategory::DataType,
+                    format!("Byte order set on single-byte field '{}'", field.name),
+                    format!("Field '{}' is one byte long, so '{:?}' endianness has no effect", field.name, field.endian),
+                    Some(&field.name),
+                    None,
+                ));
+                report.add_suggestion(suggestion(
+                    &id,
+                    format!("Remove endian from '{}'", field.name),
+                    "Byte order only applies to multi-byte values. If the field should be wider, fix its type or length instead.".to_string(),
+                    SuggestionType::FieldUpdate,
+                    0.6,
+                    Vec::new(),
+                ));
+            }
+
+            if let (Some(type_size), Some(length)) = (type_size, length) {
+                if type_size != length {
+                    let id = format!("LINT_LENGTH_TYPE_MISMATCH_{}", field.name);
+                    report.add_issue(issue(
+                        &id,
+                        IssueSeverity::Warning,
+                        IssueCategory::DataType,
+                        format!("Length of '{}' does not match its type", field.name),
+                        format!("{:?} values are {} byte(s) but the field length is {}", field.field_type, type_size, length),
+                        Some(&field.name),
+                        None,
+                    ));
+                    report.add_suggestion(suggestion(
+                        &id,
+                        format!("Set the length of '{}' to {}", field.name, type_size),
+                        "The length defaults to 1 when omitted, which truncates multi-byte types.".to_string(),
+                        SuggestionType::FieldUpdate,
+                        0.8,
+                        vec![field_example(&field.name, &format!("length: {}", type_size))],
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// Collect spans of unconditional fields with absolute offsets, sorted by start
+fn fixed_spans(fields: &[FieldDefinition]) -> Vec<FieldSpan<'_>> {
+    let mut spans: Vec<FieldSpan> = fields.iter()
+        .filter(|field| !field.optional && field.condition.is_none())
+        .filter_map(|field| {
+            let FieldOffset::Absolute(start) = field.offset else {
+                return None;
+            };
+            let end = match &field.length {
+                FieldLength::Fixed(length) => Some(start + length),
+                FieldLength::Remaining => None,
+                _ => return None,
+            };
+            Some(FieldSpan { field, start, end })
+        })
+        .collect();
+
+    spans.sort_by_key(|span| (span.start, span.end.unwrap_or(usize::MAX)));
+    spans
+}
+
+/// Iterate over top-level and conditional field definitions
+fn all_fields(rule: &ProtocolRule) -> impl Iterator<Item = &FieldDefinition> {
+    rule.fields.iter().chain(rule.conditions.iter()
+        .flat_map(|condition| condition.then_fields.iter().chain(condition.else_fields.iter())))
+}
+
+/// Extract referenced names from an expression, ignoring literals, keywords and calls
+fn expression_identifiers(expression: &str) -> Vec<String> {
+    static STRINGS: OnceLock<Regex> = OnceLock::new();
+    static IDENTIFIERS: OnceLock<Regex> = OnceLock::new();
+    let strings = STRINGS.get_or_init(|| Regex::new(r#"'[^']*'|"[^"]*""#).unwrap());
+    let identifiers = IDENTIFIERS.get_or_init(|| Regex::new(r"\b([A-Za-z_][A-Za-z0-9_]*)(?:\.[A-Za-z0-9_.]*)?(\s*\()?").unwrap());
+
+    let stripped = strings.replace_all(expression, "''");
+    let mut names = Vec::new();
+
+    for captures in identifiers.captures_iter(&stripped) {
+        let name = &captures[1];
+        let is_call = captures.get(2).is_some();
+        let preceded_by_digit = captures.get(0)
+            .map(|m| stripped[..m.start()].ends_with(|c: char| c.is_ascii_digit()))
+            .unwrap_or(false);
+
+        if is_call || preceded_by_digit || EXPRESSION_KEYWORDS.contains(&name.to_lowercase().as_str()) {
+            continue;
+        }
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+
+    names
+}
+
+/// Find the closest known name (edit distance at most 2)
+fn closest_name(name: &str, known: &HashSet<&str>) -> Option<String> {
+    known.iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Levenshtein distance
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(row[j + 1])
+            };
+            previous = current;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Create a lint issue
+fn issue(
+    id: &str,
+    severity: IssueSeverity,
+    category: IssueCategory,
+    title: String,
+    description: String,
+    field: Option<&str>,
+    offset: Option<usize>,
+) -> ValidationIssue {
+    ValidationIssue {
+        id: id.to_string(),
+        severity,
+        category,
+        title,
+        description,
+        location: IssueLocation {
+            offset,
+            length: None,
+            field: field.map(str::to_string),
+            field_path: field.map(str::to_string),
+            line: None,
+            column: None,
+        },
+        expected: None,
+        actual: None,
+        rule: Some("lint".to_string()),
+        context: HashMap::new(),
+        timestamp: Utc::now(),
+    }
+}
+
+/// Create a fix suggestion for a lint issue
+fn suggestion(
+    issue_id: &str,
+    title: String,
+    description: String,
+    action_type: SuggestionType,
+    confidence: f64,
+    examples: Vec<CodeExample>,
+) -> FixSuggestion {
+    let instructions = examples.iter().map(|example| example.description.clone()).collect();
+    FixSuggestion {
+        id: format!("FIX_{}", issue_id),
+        related_issues: vec![issue_id.to_string()],
+        title,
+        description,
+        action_type,
+        confidence,
+        effort: EffortLevel::Low,
+        instructions,
+        examples,
+    }
+}
+
+/// YAML example for a field change
+fn field_example(field_name: &str, change: &str) -> CodeExample {
+    CodeExample {
+        title: format!("Update '{}'", field_name),
+        language: "yaml".to_string(),
+        code: format!("- name: {}\n  {}", field_name, change),
+        description: format!("Set {} on field '{}'", change, field_name),
+    }
+}
+
+/// YAML example for a framing change
+fn framing_example(change: &str) -> CodeExample {
+    CodeExample {
+        title: "Update framing".to_string(),
+        language: "yaml".to_string(),
+        code: format!("framing:\n  {}", change),
+        description: format!("Set {} in framing", change),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULE: &str = r#"
+meta:
+  name: Lint Test
+  version: "1.0"
+  author: Test
+framing:
+  fixed_size: 8
+  length_field:
+    offset: 1
+    length: 1
+    encoding: binary
+fields:
+  - name: header
+    type: uint8
+    offset: 0
+    endian: little
+  - name: value
+    type: uint16
+    offset: 2
+  - name: status
+    type: uint8
+    offset: 2
+    condition: "valeu > 3"
+  - name: crc
+    type: uint16
+    offset: 6
+    length: 2
+validation:
+  crc:
+    - algorithm: crc16_modbus
+      crc_field: crc
+      data_range:
+        start: 0
+        end: 8
+"#;
+
+    fn ids(report: &ValidationReport) -> Vec<&str> {
+        report.issues.iter().map(|issue| issue.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_lint_detects_issues() {
+        let report = RuleLinter::lint_str(RULE).unwrap();
+        let ids = ids(&report);
+
+        assert!(ids.contains(&"LINT_FIELD_GAP_value"));
+        assert!(ids.contains(&"LINT_FIELD_GAP_crc"));
+        assert!(ids.contains(&"LINT_LENGTH_TYPE_MISMATCH_value"));
+        assert!(ids.contains(&"LINT_LENGTH_FIELD_WITH_FIXED_SIZE"));
+        assert!(ids.contains(&"LINT_CRC_RANGE_INCLUDES_FIELD_crc"));
+        assert!(ids.contains(&"LINT_UNKNOWN_FIELD_valeu"));
+        assert!(ids.contains(&"LINT_ENDIAN_SINGLE_BYTE_header"));
+        assert!(!report.valid);
+
+        let fix = report.suggestions.iter()
+            .find(|s| s.related_issues.contains(&"LINT_UNKNOWN_FIELD_valeu".to_string()))
+            .unwrap();
+        assert_eq!(fix.title, "Did you mean 'value'?");
+    }
+
+    #[test]
+    fn test_lint_overlap_and_max_size() {
+        let rule = RULE
+            .replace("    offset: 2\n    condition: \"valeu > 3\"", "    offset: 2\n    length: 1")
+            .replace("  fixed_size: 8\n", "  frame_validation:\n    max_size: 7\n");
+        let report = RuleLinter::lint_str(&rule).unwrap();
+        let ids = ids(&report);
+
+        assert!(ids.contains(&"LINT_FIELD_OVERLAP_status"));
+        assert!(ids.contains(&"LINT_FIELD_PAST_MAX_SIZE_crc"));
+        assert!(!ids.contains(&"LINT_LENGTH_FIELD_WITH_FIXED_SIZE"));
+    }
+
+    #[test]
+    fn test_lint_clean_rule() {
+        let rule = r#"
+meta:
+  name: Clean
+  version: "1.0"
+  author: Test
+framing:
+  fixed_size: 4
+fields:
+  - name: id
+    type: uint8
+    offset: 0
+  - name: value
+    type: uint8
+    offset: 1
+  - name: flag
+    type: uint8
+    offset: 1
+    condition: "id == 1 and crc_ok('x')"
+  - name: crc
+    type: uint16
+    offset: 2
+    length: 2
+    endian: little
+validation:
+  crc:
+    - algorithm: crc16_modbus
+      crc_field: crc
+      data_range:
+        start: 0
+        end: 2
+"#;
+        let report = RuleLinter::lint_str(rule).unwrap();
+        assert!(report.issues.is_empty(), "{:?}", ids(&report));
+        assert!(report.valid);
+    }
+}
@@ -34,6 +34,25 @@ pub mod protocol_matcher;
 pub mod protocol_parser;
 pub mod repository;
 pub mod factor_translator;
+pub mod factor_catalog;
+pub mod alarm_engine;
+pub mod units;
+pub mod catalog;
+pub mod reassembly;
+pub mod envelope;
+pub mod export;
+pub mod lint;
+pub mod revisions;
+pub mod bundle;
+pub mod watcher;
+pub mod binding;
+pub mod hj212;
+pub mod jt808;
+pub mod dlt645;
+pub mod iec104;
+pub mod dnp3;
+pub mod bcd;
+pub mod checksum;
 
 // Re-export key types for convenience
 pub use result::*;
@@ -41,6 +60,22 @@ pub use validation_report::*;
 pub use protocol_parser::ProtocolParser;
 pub use repository::{ProtocolRepository, ProtocolMetadata, ProtocolImportRequest, ProtocolExportOptions, ValidationStatus};
 pub use factor_translator::{FactorTranslator, FactorDefinition, ParsedFactor, FactorValue, FactorSummary};
+pub use factor_catalog::{builtin_catalog, FactorCatalog};
+pub use alarm_engine::{get_alarm_engine, load_factor_alarm_rules, ActiveAlarm, AlarmEngine, AlarmKind, AlarmRule, AlarmTransition, FactorAlarmEvent, FACTOR_ALARM_EVENT};
+pub use catalog::{Catalog, CatalogSet, LookupExpression};
+pub use reassembly::{ExpiredFragments, Reassembler, ReassemblyOutcome, ReassemblyScope, FRAGMENTS_EXPIRED_EVENT};
+pub use envelope::{EnvelopeContext, TopicTemplate};
+pub use export::{ExportFormat, FlatRecord, ResultExporter};
+pub use lint::RuleLinter;
+pub use revisions::{ProtocolRevision, RuleDiff};
+pub use bundle::{BundleImportAction, BundleImportResult, ConflictStrategy, ProtocolBundle};
+pub use binding::{get_session_parsers, ParsedMessage, SessionParserBinding, SessionParsers, MESSAGE_PARSED_EVENT};
+pub use hj212::{Hj212Cp, Hj212Factor, Hj212Flag, Hj212Header, Hj212Packet, Hj212Parser, Hj212Version, HJ212_PARSER_ID};
+pub use dlt645::{Dlt645Body, Dlt645Control, Dlt645Frame, Dlt645Parser, Dlt645Value, DLT645_PARSER_ID};
+pub use dnp3::{AppFragment, DataObject, DataValue, Dnp3Parser, LinkFrame, LinkHeader, ObjectHeader, DNP3_PARSER_ID};
+pub use iec104::{Apci, Apdu, Asdu, Cp56Time2a, Iec104Parser, InformationObject, ObjectValue, UFunction, IEC104_PARSER_ID};
+pub use jt808::{Jt808AdditionalInfo, Jt808Body, Jt808Header, Jt808Location, Jt808Packet, Jt808Parser, Jt808Version, JT808_PARSER_ID};
+pub use watcher::{ProtocolChangeEvent, ProtocolChangeKind, ProtocolWatcher, PROTOCOLS_CHANGED_EVENT};
 
 /// Main parser interface that all protocol parsers must implement
 pub trait Parser: Send + Sync {
@@ -58,6 +93,40 @@ pub trait Parser: Send + Sync {
     
     /// Check if this parser can handle the given data
     fn can_parse(&self, data: &[u8]) -> bool;
+
+    /// Parse raw data together with its transport envelope
+    fn parse_with_envelope(&self, data: &[u8], envelope: &EnvelopeContext) -> NetworkResult<ParseResult> {
+        let mut result = self.parse(data)?;
+        envelope.apply_to(&mut result);
+        Ok(result)
+    }
+
+    /// Check if this parser applies to the given transport envelope
+    fn accepts_envelope(&self, _envelope: &EnvelopeContext) -> bool {
+        true
+    }
+
+    /// Get the export mappings declared for this protocol
+    fn export_rules(&self) -> schema::ExportRules {
+        schema::ExportRules::default()
+    }
+
+    /// Create a frame detector for splitting a byte stream into frames
+    ///
+    /// `None` means every received chunk is parsed as one message.
+    fn stream_framer(&self) -> Option<framing::FrameDetector> {
+        None
+    }
+
+    /// Parse one complete frame cut from a stream, with its transport envelope
+    fn parse_frame_with_envelope(&self, frame: &[u8], envelope: &EnvelopeContext) -> NetworkResult<ParseResult> {
+        self.parse_with_envelope(frame, envelope)
+    }
+
+    /// Drop buffered message fragments that timed out, reporting each group
+    fn expire_fragments(&self, _now: std::time::Instant) -> Vec<ExpiredFragments> {
+        Vec::new()
+    }
 }
 
 /// Protocol information structure
@@ -126,6 +195,11 @@ impl ParserRegistry {
         self.parsers.get(id).map(|p| p.as_ref())
     }
     
+    /// Drop timed-out message fragments of every parser
+    pub fn expire_fragments(&self, now: std::time::Instant) -> Vec<ExpiredFragments> {
+        self.parsers.values().flat_map(|parser| parser.expire_fragments(now)).collect()
+    }
+    
     /// Get all registered parser IDs
     pub fn get_parser_ids(&self) -> Vec<String> {
         self.parsers.keys().cloned().collect()
@@ -163,6 +237,71 @@ impl ParserRegistry {
         }
     }
 
+    /// Auto-detect the best parser for the given data and envelope
+    pub fn auto_detect_with_envelope(&self, data: &[u8], envelope: &EnvelopeContext) -> Option<&dyn Parser> {
+        for parser_id in &self.auto_detect_order {
+            if let Some(parser) = self.parsers.get(parser_id) {
+                if parser.accepts_envelope(envelope) && parser.can_parse(data) {
+                    return Some(parser.as_ref());
+                }
+            }
+        }
+        None
+    }
+
+    /// Parse data with its transport envelope
+    ///
+    /// Uses the given parser, or auto-detects one that accepts the envelope.
+    pub fn parse_with_envelope(&self, parser_id: Option<&str>, data: &[u8], envelope: &EnvelopeContext) -> NetworkResult<ParseResult> {
+        let parser = match parser_id {
+            Some(id) => self.get_parser(id).ok_or_else(|| crate::types::NetworkError::ParseError(
+                format!("Parser '{}' not found", id)
+            ))?,
+            None => self.auto_detect_with_envelope(data, envelope).ok_or_else(|| crate::types::NetworkError::ParseError(
+                "No suitable parser found for the data".to_string()
+            ))?,
+        };
+
+        parser.parse_with_envelope(data, envelope)
+    }
+
+    /// Export parse results using the export mappings of their parser
+    ///
+    /// Each result uses the mappings of the parser that produced it; results
+    /// from unknown parsers use the default mappings. CSV output has one
+    /// table per parser, separated by an empty line.
+    pub fn export_results(&self, results: &[ParseResult], format: ExportFormat) -> NetworkResult<String> {
+        let exporter = |parser_id: &str| ResultExporter::new(
+            self.get_parser(parser_id)
+                .map(|parser| parser.export_rules())
+                .unwrap_or_default()
+        );
+
+        match format {
+            ExportFormat::Csv => {
+                let mut groups: Vec<(&str, Vec<ParseResult>)> = Vec::new();
+                for result in results {
+                    let parser_id = result.protocol.parser_id.as_str();
+                    match groups.iter_mut().find(|(id, _)| *id == parser_id) {
+                        Some((_, group)) => group.push(result.clone()),
+                        None => groups.push((parser_id, vec![result.clone()])),
+                    }
+                }
+
+                let tables = groups.iter()
+                    .map(|(parser_id, group)| exporter(parser_id).export(group, format))
+                    .collect::<NetworkResult<Vec<_>>>()?;
+                Ok(tables.join("\n"))
+            }
+            ExportFormat::Influx | ExportFormat::Json => {
+                let lines = results.iter()
+                    .map(|result| exporter(&result.protocol.parser_id).export(std::slice::from_ref(result), format))
+                    .collect::<NetworkResult<Vec<_>>>()?;
+                Ok(lines.join("\n"))
+            }
+        }
+    }
+
     /// Load protocols from repository
     pub fn load_protocols_from_repository(&mut self) -> NetworkResult<usize> {
         // Collect protocol IDs first to avoid borrowing issues
@@ -198,20 +337,59 @@ impl ParserRegistry {
 
     /// Reload a specific protocol from repository
     pub fn reload_protocol(&mut self, protocol_id: &str) -> NetworkResult<()> {
-        // Remove existing parser if it exists
+        // Build the new parser first so a broken rule keeps the existing one
+        let parser = if let Some(repository) = &mut self.repository {
+            repository.create_protocol_parser(protocol_id)?
+        } else {
+            return Err(crate::types::NetworkError::ParseError(
+                "No protocol repository available".to_string()
+            ));
+        };
+
         self.remove_parser(protocol_id);
+        self.register_parser(Box::new(parser));
 
-        // Load new parser
-        if let Some(repository) = &mut self.repository {
-            let parser = repository.create_protocol_parser(protocol_id)?;
-            self.register_parser(Box::new(parser));
+        log::info!("Reloaded protocol parser: {}", protocol_id);
+        Ok(())
+    }
 
-            log::info!("Reloaded protocol parser: {}", protocol_id);
-            Ok(())
-        } else {
-            Err(crate::types::NetworkError::ParseError(
-                "No protocol repository available".to_string()
-            ))
+    /// Apply an external change to a rule file and reload its parser
+    ///
+    /// If the changed rule cannot be loaded, the last good parser stays registered.
+    pub fn sync_protocol_file(&mut self, filename: &str) -> NetworkResult<Option<ProtocolChangeEvent>> {
+        let repository = self.repository.as_mut().ok_or_else(|| {
+            crate::types::NetworkError::ParseError("No protocol repository available".to_string())
+        })?;
+
+        let Some(change) = repository.sync_protocol_file(filename)? else {
+            return Ok(None);
+        };
+        let Some(protocol_id) = change.protocol_id.clone() else {
+            return Ok(Some(change));
+        };
+
+        match change.kind {
+            ProtocolChangeKind::Removed => {
+                self.remove_parser(&protocol_id);
+                Ok(Some(change))
+            }
+            ProtocolChangeKind::Added | ProtocolChangeKind::Updated if change.enabled => {
+                match self.reload_protocol(&protocol_id) {
+                    Ok(()) => Ok(Some(change)),
+                    Err(e) => {
+                        let error = e.to_string();
+                        log::warn!("Failed to reload protocol {}, keeping last good parser: {}", protocol_id, error);
+                        if let Some(repository) = &mut self.repository {
+                            repository.mark_protocol_invalid(&protocol_id, &error)?;
+                        }
+                        Ok(Some(ProtocolChangeEvent {
+                            kind: ProtocolChangeKind::Invalid,
+                            ..change
+                        }.with_error(error)))
+                    }
+                }
+            }
+            _ => Ok(Some(change)),
         }
     }
 
@@ -260,10 +438,12 @@ pub fn initialize_parser_system_with_repository(repository_path: Option<std::pat
         log::info!("Loaded {} protocols from repository", loaded_count);
     }
 
-    // TODO: Register built-in parsers here
-    // registry.write().unwrap().register_parser(Box::new(ModbusParser::new()));
-    // registry.write().unwrap().register_parser(Box::new(TcpParser::new()));
-    // etc.
+    // Built-in parsers
+    registry.write().unwrap().register_parser(Box::new(Hj212Parser::new()));
+    registry.write().unwrap().register_parser(Box::new(Jt808Parser::new()));
+    registry.write().unwrap().register_parser(Box::new(Dlt645Parser::new()));
+    registry.write().unwrap().register_parser(Box::new(Iec104Parser::new()));
+    registry.write().unwrap().register_parser(Box::new(Dnp3Parser::new()));
 
     log::info!("Parser system initialized with {} parsers", registry.read().unwrap().get_parser_ids().len());
     Ok(())
@@ -285,4 +465,22 @@ mod tests {
         let data = b"test data";
         assert!(registry.auto_detect(data).is_none());
     }
+
+    #[test]
+    fn test_export_uses_each_parsers_mapping() {
+        let rule = |name: &str, column: &str| format!(
+            "meta:\n  name: {name}\n  version: \"1.0\"\n  author: A\nframing:\n  fixed_size: 2\nfields:\n  - name: a\n    type: uint8\n    offset: 0\n  - name: b\n    type: uint8\n    offset: 1\nexports:\n  csv:\n    columns: [{column}]\n"
+        );
+        let mut registry = ParserRegistry::new();
+        registry.register_parser(Box::new(ProtocolParser::from_rule_string("first".to_string(), &rule("First", "a")).unwrap()));
+        registry.register_parser(Box::new(ProtocolParser::from_rule_string("second".to_string(), &rule("Second", "b")).unwrap()));
+
+        let results = vec![
+            registry.parse_with_parser("first", &[1, 2]).unwrap(),
+            registry.parse_with_parser("second", &[3, 4]).unwrap(),
+            registry.parse_with_parser("first", &[5, 6]).unwrap(),
+        ];
+        let csv = registry.export_results(&results, ExportFormat::Csv).unwrap();
+        assert_eq!(csv, "a\n1\n5\n\nb\n4\n");
+    }
 }
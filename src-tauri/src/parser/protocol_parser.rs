@@ -9,6 +9,9 @@ use crate::parser::rules::RulesLoader;
 use crate::parser::compiler::{RuleCompiler, CompiledRule};
 use crate::parser::cache::RuleCache;
 use crate::parser::framing::FrameDetector;
+use crate::parser::catalog::{CatalogSet, LookupExpression};
+use crate::parser::reassembly::{ExpiredFragments, Reassembler, ReassemblyScope};
+use crate::parser::envelope::{EnvelopeContext, TopicTemplate};
 use crate::parser::types::TypeParser;
 use crate::parser::result::{ParseResult, ParseError, ErrorSeverity, FieldValue, ParsedField, ParsedFields, ProtocolInfo, FieldMetadata, FieldValidationResult};
 use crate::parser::validation_report::{ValidationReport, ValidationIssue, IssueSeverity, IssueCategory, IssueLocation};
@@ -30,6 +33,18 @@ pub struct ProtocolParser {
     
     /// Rule cache
     cache: Arc<RuleCache>,
+    
+    /// Lookup catalogs declared by the rule
+    catalogs: Arc<CatalogSet>,
+    
+    /// Field lookups (field name, output name, expression)
+    lookups: Vec<(String, String, LookupExpression)>,
+    
+    /// Multi-packet reassembler (when the rule declares reassembly)
+    reassembler: Option<Arc<RwLock<Reassembler>>>,
+    
+    /// Compiled envelope topic template
+    topic_template: Option<TopicTemplate>,
 }
 
 impl ProtocolParser {
@@ -59,16 +74,41 @@ impl ProtocolParser {
         // Create cache
         let cache = Arc::new(RuleCache::new(100));
         
+        // Load catalogs and prepare field lookups
+        let catalogs = CatalogSet::from_definitions(&compiled_rule.rule.catalogs)?;
+        let mut lookups = Vec::new();
+        for field_def in &compiled_rule.rule.fields {
+            let mut outputs: Vec<_> = field_def.lookups.iter().collect();
+            outputs.sort_by(|a, b| a.0.cmp(b.0));
+            for (output, expression) in outputs {
+                lookups.push((field_def.name.clone(), output.clone(), LookupExpression::parse(expression)?));
+            }
+        }
+        
+        // Create reassembler
+        let reassembler = compiled_rule.rule.reassembly.clone()
+            .map(|rule| Arc::new(RwLock::new(Reassembler::new(rule))));
+        
+        // Compile envelope topic template
+        let topic_template = compiled_rule.rule.envelope.as_ref()
+            .and_then(|envelope| envelope.topic_match.as_deref())
+            .map(TopicTemplate::parse)
+            .transpose()?;
+        
         Ok(Self {
             id: parser_id,
             compiled_rule: Arc::new(compiled_rule),
             frame_detector: Arc::new(RwLock::new(frame_detector)),
             cache,
+            catalogs: Arc::new(catalogs),
+            lookups,
+            reassembler,
+            topic_template,
         })
     }
     
     /// Parse a single frame
-    fn parse_frame(&self, frame_data: &[u8]) -> NetworkResult<ParsedFields> {
+    fn parse_frame(&self, frame_data: &[u8], envelope: Option<&EnvelopeContext>) -> NetworkResult<ParsedFields> {
         let mut fields = ParsedFields::new();
         let rule = &self.compiled_rule.rule;
         
@@ -104,9 +144,67 @@ impl ProtocolParser {
             }
         }
         
+        // Enrich fields from lookup catalogs
+        self.apply_lookups(&mut fields, envelope);
+        
         Ok(fields)
     }
     
+    /// Apply catalog lookups declared on fields
+    ///
+    /// Keys of the form `envelope.<name>` resolve against the transport envelope.
+    fn apply_lookups(&self, fields: &mut ParsedFields, envelope: Option<&EnvelopeContext>) {
+        for (field_name, output, expression) in &self.lookups {
+            let result = self.catalogs.evaluate(expression, |name| {
+                if let Some(key) = name.strip_prefix("envelope.") {
+                    return envelope.and_then(|envelope| envelope.get(key));
+                }
+                fields.get_field(name)
+                    .filter(|field| field.valid)
+                    .map(|field| field.value.as_string())
+            });
+            
+            if let Some(field) = fields.fields.get_mut(field_name) {
+                match result {
+                    Ok(Some(value)) => {
+                        field.metadata.extra.insert(output.clone(), value);
+                    }
+                    Ok(None) => {
+                        field.validation.warnings.push(format!(
+                            "No '{}' entry in catalog '{}'",
+                            output, expression.catalog
+                        ));
+                    }
+                    Err(e) => {
+                        field.validation.warnings.push(e.to_string());
+                    }
+                }
+            }
+        }
+    }
+    
+    /// Add topic template variables to the envelope
+    fn resolve_envelope(&self, envelope: &EnvelopeContext) -> EnvelopeContext {
+        let mut resolved = envelope.clone();
+        
+        let (Some(template), Some(topic)) = (&self.topic_template, envelope.mqtt_topic.as_deref()) else {
+            return resolved;
+        };
+        
+        if let Some(variables) = template.match_topic(topic) {
+            let expose = self.compiled_rule.rule.envelope.as_ref()
+                .map(|rule| rule.expose.as_slice())
+                .unwrap_or_default();
+            for (name, value) in variables {
+                if expose.is_empty() || expose.contains(&name) {
+                    resolved.variables.insert(name, value);
+                }
+            }
+        }
+        
+        resolved
+    }
+    
     /// Parse a single field
     fn parse_single_field(&self, data: &[u8], field_def: &FieldDefinition) -> NetworkResult<ParsedField> {
         // Calculate field offset
@@ -187,10 +285,9 @@ impl ProtocolParser {
             },
         })
     }
-}
-
-impl Parser for ProtocolParser {
-    fn parse(&self, data: &[u8]) -> NetworkResult<ParseResult> {
+    
+    /// Parse raw data, optionally with its resolved transport envelope
+    fn parse_data(&self, data: &[u8], envelope: Option<&EnvelopeContext>) -> NetworkResult<ParseResult> {
         let start_time = std::time::Instant::now();
         
         // Detect frames in the data
@@ -240,8 +337,20 @@ impl Parser for ProtocolParser {
             ));
         }
         
+        self.parse_detected_frame(data, &frame.data, envelope, start_time)
+    }
+    
+    /// Build the parse result for one detected frame
+    fn parse_detected_frame(&self, data: &[u8], frame_data: &[u8], envelope: Option<&EnvelopeContext>, start_time: std::time::Instant) -> NetworkResult<ParseResult> {
+        // Verify and remove per-block CRCs before the field layout applies
+        let block_crcs = match &self.compiled_rule.rule.framing.block_crc {
+            Some(block_crc) => Some(block_crc.strip(frame_data)?),
+            None => None,
+        };
+        let field_data = block_crcs.as_ref().map(|blocks| blocks.data.as_slice()).unwrap_or(frame_data);
+        
         // Parse fields from the frame
-        let fields = self.parse_frame(&frame.data)?;
+        let fields = self.parse_frame(field_data, envelope)?;
         
         // Create parse result
         let mut result = ParseResult::success(
@@ -253,7 +362,7 @@ impl Parser for ProtocolParser {
             },
             fields,
             data.to_vec(),
-            frame.data.len(),
+            frame_data.len(),
         );
         
         // Update metadata
@@ -261,9 +370,85 @@ impl Parser for ProtocolParser {
         result.metadata.timestamp = Utc::now();
         result.metadata.parser_version = "1.0.0".to_string();
         
+        if let Some(block_crcs) = block_crcs {
+            for block in block_crcs.blocks {
+                if !block.valid {
+                    result.add_error(ParseError::new(
+                        "CRC_MISMATCH",
+                        format!("Block {} CRC {:X} does not match calculated {:X}", block.data_range, block.expected, block.calculated),
+                        ErrorSeverity::Error,
+                    ));
+                }
+                result.validation.crc_results.push(block);
+            }
+        }
+        
+        // Reassemble fragmented messages of this session/client stream;
+        // the registry sweep reports groups that time out
+        if let Some(reassembler) = &self.reassembler {
+            let scope = ReassemblyScope::from_envelope(envelope);
+            result = reassembler.write().unwrap().push(&scope, result).into_result();
+        }
+        
+        Ok(result)
+    }
+}
+
+impl Parser for ProtocolParser {
+    fn parse(&self, data: &[u8]) -> NetworkResult<ParseResult> {
+        self.parse_data(data, None)
+    }
+    
+    fn parse_with_envelope(&self, data: &[u8], envelope: &EnvelopeContext) -> NetworkResult<ParseResult> {
+        let envelope = self.resolve_envelope(envelope);
+        let mut result = self.parse_data(data, Some(&envelope))?;
+        envelope.apply_to(&mut result);
+        Ok(result)
+    }
+    
+    fn stream_framer(&self) -> Option<FrameDetector> {
+        Some(FrameDetector::new(self.compiled_rule.rule.framing.clone()))
+    }
+    
+    fn parse_frame_with_envelope(&self, frame: &[u8], envelope: &EnvelopeContext) -> NetworkResult<ParseResult> {
+        let envelope = self.resolve_envelope(envelope);
+        let mut result = self.parse_detected_frame(frame, frame, Some(&envelope), std::time::Instant::now())?;
+        envelope.apply_to(&mut result);
         Ok(result)
     }
     
+    fn expire_fragments(&self, now: std::time::Instant) -> Vec<ExpiredFragments> {
+        let Some(reassembler) = &self.reassembler else {
+            return Vec::new();
+        };
+        reassembler.write().unwrap().expire(now)
+            .into_iter()
+            .map(|(scope, warning)| ExpiredFragments {
+                session_id: scope.session_id,
+                client_id: scope.client_id,
+                parser_id: self.id.clone(),
+                warning,
+            })
+            .collect()
+    }
+    
+    fn export_rules(&self) -> ExportRules {
+        self.compiled_rule.rule.exports.clone()
+    }
+    
+    fn accepts_envelope(&self, envelope: &EnvelopeContext) -> bool {
+        let require_match = self.compiled_rule.rule.envelope.as_ref()
+            .map(|rule| rule.require_match)
+            .unwrap_or(false);
+        
+        match &self.topic_template {
+            Some(template) if require_match => envelope.mqtt_topic.as_deref()
+                .map(|topic| template.match_topic(topic).is_some())
+                .unwrap_or(false),
+            _ => true,
+        }
+    }
+    
     fn validate(&self, result: &ParseResult) -> ValidationReport {
         let mut report = ValidationReport::new();
         
@@ -382,6 +567,7 @@ mod tests {
                 fixed_size: Some(4),
                 escape_rules: vec![],
                 frame_validation: FrameValidation::default(),
+                block_crc: None,
             },
             fields: vec![
                 FieldDefinition {
@@ -395,6 +581,7 @@ mod tests {
                     default_value: None,
                     validation: FieldValidation::default(),
                     condition: None,
+                    lookups: HashMap::new(),
                 },
                 FieldDefinition {
                     name: "field2".to_string(),
@@ -407,6 +594,7 @@ mod tests {
                     default_value: None,
                     validation: FieldValidation::default(),
                     condition: None,
+                    lookups: HashMap::new(),
                 },
                 FieldDefinition {
                     name: "field3".to_string(),
@@ -419,12 +607,17 @@ mod tests {
                     default_value: None,
                     validation: FieldValidation::default(),
                     condition: None,
+                    lookups: HashMap::new(),
                 },
             ],
             validation: ValidationRules::default(),
             conditions: vec![],
             functions: HashMap::new(),
             factor_codes: None,
+            catalogs: HashMap::new(),
+            reassembly: None,
+            envelope: None,
+            exports: ExportRules::default(),
         }
     }
     
@@ -480,6 +673,97 @@ mod tests {
         assert_eq!(validation_report.issues.len(), 0);
     }
     
+    #[test]
+    fn test_protocol_parser_catalog_lookup() {
+        let mut rule = create_test_rule();
+        rule.catalogs.insert("devices".to_string(), CatalogDefinition {
+            key: "id".to_string(),
+            rows: vec![
+                [("id".to_string(), serde_json::json!(1)), ("name".to_string(), serde_json::json!("Pump"))]
+                    .into_iter().collect(),
+            ],
+            file: None,
+            delimiter: ',',
+            description: String::new(),
+        });
+        rule.fields[0].lookups.insert("name".to_string(), "lookup(devices, field1).name".to_string());
+        rule.fields[2].lookups.insert("name".to_string(), "lookup(devices, field3).name".to_string());
+        
+        let parser = ProtocolParser::from_rule("test".to_string(), rule).unwrap();
+        let result = parser.parse(&[0x01, 0x02, 0x03, 0x04]).unwrap();
+        
+        let field1 = result.fields.get_field("field1").unwrap();
+        assert_eq!(field1.metadata.extra.get("name"), Some(&serde_json::json!("Pump")));
+        
+        let field3 = result.fields.get_field("field3").unwrap();
+        assert!(!field3.metadata.extra.contains_key("name"));
+        assert_eq!(field3.validation.warnings.len(), 1);
+    }
+    
+    #[test]
+    fn test_protocol_parser_envelope() {
+        let mut rule = create_test_rule();
+        rule.envelope = Some(EnvelopeRule {
+            topic_match: Some("plant/{site}/mn/{mn}/up/{type}".to_string()),
+            expose: vec!["site".to_string(), "mn".to_string()],
+            require_match: true,
+        });
+        rule.catalogs.insert("stations".to_string(), CatalogDefinition {
+            key: "mn".to_string(),
+            rows: vec![
+                [("mn".to_string(), serde_json::json!("DEV001")), ("location".to_string(), serde_json::json!("Outfall 1"))]
+                    .into_iter().collect(),
+            ],
+            file: None,
+            delimiter: ',',
+            description: String::new(),
+        });
+        rule.fields[0].lookups.insert("location".to_string(), "lookup(stations, envelope.mn).location".to_string());
+        
+        let parser = ProtocolParser::from_rule("test".to_string(), rule).unwrap();
+        
+        let mut envelope = EnvelopeContext::new();
+        envelope.mqtt_topic = Some("plant/A1/mn/DEV001/up/rtd".to_string());
+        assert!(parser.accepts_envelope(&envelope));
+        
+        let result = parser.parse_with_envelope(&[0x01, 0x02, 0x03, 0x04], &envelope).unwrap();
+        let field1 = result.fields.get_field("field1").unwrap();
+        assert_eq!(field1.metadata.extra.get("location"), Some(&serde_json::json!("Outfall 1")));
+        
+        let exposed = &result.metadata.extra["envelope"]["variables"];
+        assert_eq!(exposed["site"], "A1");
+        assert!(exposed.get("type").is_none());
+        
+        envelope.mqtt_topic = Some("plant/A1/cmd".to_string());
+        assert!(!parser.accepts_envelope(&envelope));
+    }
+    
+    #[test]
+    fn test_protocol_parser_block_crc() {
+        let mut rule = create_test_rule();
+        let block_crc = BlockCrc {
+            algorithm: "crc16_modbus".to_string(),
+            block_size: 2,
+            first_block: None,
+            endian: Endianness::Little,
+        };
+        let wire = block_crc.append(&[0x01, 0x02, 0x03, 0x04]).unwrap();
+        rule.framing.fixed_size = Some(wire.len());
+        rule.framing.block_crc = Some(block_crc);
+        let parser = ProtocolParser::from_rule("test".to_string(), rule).unwrap();
+        
+        let result = parser.parse(&wire).unwrap();
+        assert!(result.success);
+        assert_eq!(result.validation.crc_results.len(), 2);
+        assert_eq!(result.fields.get_field("field3").unwrap().value, FieldValue::UInt(0x04));
+        
+        let mut corrupted = wire.clone();
+        corrupted[4] ^= 0x01;
+        let result = parser.parse(&corrupted).unwrap();
+        assert!(!result.success);
+        assert_eq!(result.errors[0].code, "CRC_MISMATCH");
+    }
+    
     #[test]
     fn test_protocol_parser_can_parse() {
         let rule = create_test_rule();
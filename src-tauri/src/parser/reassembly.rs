@@ -0,0 +1,489 @@
+//! Multi-packet message reassembly
+//!
+//! Protocols such as HJ212 (`PNUM`/`PNO`) and JT/T 808 split large messages
+//! into fragments that carry a total count and an index. The reassembler
+//! groups parsed fragments by configurable key fields and emits one combined
+//! `ParseResult` once every fragment of a group has arrived. Groups are kept
+//! per session and client, and a periodic sweep reports groups whose
+//! remaining fragments never arrived.
+
+use crate::parser::envelope::EnvelopeContext;
+use crate::parser::schema::ReassemblyRule;
+use crate::parser::result::{ParseResult, ParseWarning, ParsedField, FieldValue};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+/// Event name emitted for fragment groups that timed out incomplete
+pub const FRAGMENTS_EXPIRED_EVENT: &str = "message-fragments-expired";
+
+/// Session and client stream a fragment group belongs to
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ReassemblyScope {
+    pub session_id: Option<String>,
+    pub client_id: Option<String>,
+}
+
+impl ReassemblyScope {
+    /// Scope of the stream an envelope describes; unscoped without one
+    pub fn from_envelope(envelope: Option<&EnvelopeContext>) -> Self {
+        Self {
+            session_id: envelope.and_then(|envelope| envelope.session_id.clone()),
+            client_id: envelope.and_then(|envelope| envelope.client_id.clone()),
+        }
+    }
+}
+
+/// Fragment group dropped by the timeout sweep
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiredFragments {
+    pub session_id: Option<String>,
+    pub client_id: Option<String>,
+    pub parser_id: String,
+    pub warning: ParseWarning,
+}
+
+/// Outcome of feeding a parsed frame to the reassembler
+#[derive(Debug, Clone)]
+pub enum ReassemblyOutcome {
+    /// Frame is not fragmented and passes through unchanged
+    Single(ParseResult),
+
+    /// Fragment stored, waiting for the rest of its group
+    Pending(ParseResult),
+
+    /// Last fragment arrived, carries the combined result
+    Complete(ParseResult),
+
+    /// Fragment caused its group to be dropped
+    Dropped(ParseResult),
+}
+
+impl ReassemblyOutcome {
+    /// Get the parse result carried by this outcome
+    pub fn into_result(self) -> ParseResult {
+        match self {
+            ReassemblyOutcome::Single(result)
+            | ReassemblyOutcome::Pending(result)
+            | ReassemblyOutcome::Complete(result)
+            | ReassemblyOutcome::Dropped(result) => result,
+        }
+    }
+}
+
+/// Fragments collected for one group
+#[derive(Debug, Clone)]
+struct FragmentGroup {
+    /// Expected fragment count
+    total: u64,
+
+    /// Fragments by zero-based index
+    fragments: BTreeMap<u64, ParseResult>,
+
+    /// When the first fragment arrived
+    started_at: Instant,
+
+    /// When the last fragment arrived
+    updated_at: Instant,
+}
+
+/// Stateful fragment reassembler
+#[derive(Debug)]
+pub struct Reassembler {
+    /// Reassembly configuration
+    rule: ReassemblyRule,
+
+    /// Incomplete groups by stream and key
+    groups: HashMap<(ReassemblyScope, String), FragmentGroup>,
+}
+
+impl Reassembler {
+    /// Create a new reassembler
+    pub fn new(rule: ReassemblyRule) -> Self {
+        Self {
+            rule,
+            groups: HashMap::new(),
+        }
+    }
+
+    /// Feed a parsed frame received on the given stream
+    pub fn push(&mut self, scope: &ReassemblyScope, result: ParseResult) -> ReassemblyOutcome {
+        self.push_at(scope, result, Instant::now())
+    }
+
+    /// Feed a parsed frame received on the given stream at the given instant
+    pub fn push_at(&mut self, scope: &ReassemblyScope, mut result: ParseResult, now: Instant) -> ReassemblyOutcome {
+        if !result.success {
+            return ReassemblyOutcome::Single(result);
+        }
+
+        let total = field_number(&result, &self.rule.total_field);
+        let index = field_number(&result, &self.rule.index_field);
+
+        let (total, index) = match (total, index) {
+            (Some(total), Some(index)) if total > 1 => (total, index),
+            _ => return ReassemblyOutcome::Single(result),
+        };
+
+        let position = match index.checked_sub(self.rule.index_base) {
+            Some(position) if position < total => position,
+            _ => {
+                result.add_warning(reassembly_warning(
+                    "FRAGMENT_INDEX_OUT_OF_RANGE",
+                    format!("Fragment index {} outside the {} fragment(s) of this message", index, total),
+                    &self.group_key(&result),
+                ));
+                return ReassemblyOutcome::Dropped(result);
+            }
+        };
+
+        let key = self.group_key(&result);
+        let group_id = (scope.clone(), key.clone());
+
+        // A different total means a new message reusing the same key
+        if let Some(group) = self.groups.get(&group_id) {
+            if group.total != total {
+                let stale = self.groups.remove(&group_id).unwrap();
+                result.add_warning(incomplete_warning(&key, &stale));
+            }
+        }
+
+        if !self.groups.contains_key(&group_id) {
+            self.evict_if_full(&mut result);
+            self.groups.insert(group_id.clone(), FragmentGroup {
+                total,
+                fragments: BTreeMap::new(),
+                started_at: now,
+                updated_at: now,
+            });
+        }
+
+        let group = self.groups.get_mut(&group_id).unwrap();
+
+        if self.rule.drop_on_gap {
+            let expected = group.fragments.len() as u64;
+            if position != expected {
+                let dropped = self.groups.remove(&group_id).unwrap();
+                result.add_warning(reassembly_warning(
+                    "FRAGMENT_GAP",
+                    format!(
+                        "Expected fragment {} but received {}, dropped {} buffered fragment(s)",
+                        expected + self.rule.index_base, index, dropped.fragments.len()
+                    ),
+                    &key,
+                ));
+                return ReassemblyOutcome::Dropped(result);
+            }
+        }
+
+        if group.fragments.contains_key(&position) {
+            result.add_warning(reassembly_warning(
+                "FRAGMENT_DUPLICATE",
+                format!("Duplicate fragment {} replaces the earlier copy", index),
+                &key,
+            ));
+        }
+
+        group.fragments.insert(position, result.clone());
+        group.updated_at = now;
+
+        if group.fragments.len() as u64 == group.total {
+            let group = self.groups.remove(&group_id).unwrap();
+            return ReassemblyOutcome::Complete(self.combine(&key, group));
+        }
+
+        result.metadata.extra.insert("reassembly".to_string(), serde_json::json!({
+            "key": key,
+            "index": index,
+            "total": total,
+            "received": group.fragments.len(),
+            "complete": false,
+        }));
+
+        ReassemblyOutcome::Pending(result)
+    }
+
+    /// Drop groups that have timed out, returning the stream and a warning for each
+    pub fn expire(&mut self, now: Instant) -> Vec<(ReassemblyScope, ParseWarning)> {
+        let timeout = Duration::from_millis(self.rule.timeout_ms);
+        let expired: Vec<(ReassemblyScope, String)> = self.groups.iter()
+            .filter(|(_, group)| now.saturating_duration_since(group.updated_at) >= timeout)
+            .map(|(group_id, _)| group_id.clone())
+            .collect();
+
+        expired.into_iter()
+            .filter_map(|group_id| {
+                let group = self.groups.remove(&group_id)?;
+                let warning = incomplete_warning(&group_id.1, &group);
+                Some((group_id.0, warning))
+            })
+            .collect()
+    }
+
+    /// Number of incomplete groups
+    pub fn pending_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Discard all buffered fragments
+    pub fn reset(&mut self) {
+        self.groups.clear();
+    }
+
+    /// Build the group key from the configured key fields
+    fn group_key(&self, result: &ParseResult) -> String {
+        self.rule.key_fields.iter()
+            .map(|name| result.fields.get_field(name)
+                .map(|field| field.value.as_string())
+                .unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    /// Make room for a new group by dropping the oldest one
+    fn evict_if_full(&mut self, result: &mut ParseResult) {
+        if self.groups.len() < self.rule.max_pending_groups.max(1) {
+            return;
+        }
+
+        let oldest = self.groups.iter()
+            .min_by_key(|(_, group)| group.started_at)
+            .map(|(group_id, _)| group_id.clone());
+
+        if let Some(group_id) = oldest {
+            if let Some(group) = self.groups.remove(&group_id) {
+                result.add_warning(incomplete_warning(&group_id.1, &group));
+            }
+        }
+    }
+
+    /// Combine a complete group into a single result
+    fn combine(&self, key: &str, group: FragmentGroup) -> ParseResult {
+        let fragment_count = group.fragments.len();
+        let mut fragments = group.fragments.into_values();
+
+        // All fragments exist here, the group is complete
+        let mut combined = fragments.next().unwrap();
+        let mut payload = payload_field(&combined, &self.rule.payload_field);
+
+        for fragment in fragments {
+            if let (Some(payload), Some(next)) = (payload.as_mut(), payload_field(&fragment, &self.rule.payload_field)) {
+                append_field(payload, next);
+            }
+
+            combined.raw_data.extend_from_slice(&fragment.raw_data);
+            combined.parsed_size += fragment.parsed_size;
+            combined.errors.extend(fragment.errors);
+            combined.warnings.extend(fragment.warnings);
+        }
+
+        if let Some(payload) = payload {
+            combined.fields.add_field(self.rule.payload_field.clone(), payload);
+        } else {
+            combined.add_warning(reassembly_warning(
+                "FRAGMENT_NO_PAYLOAD",
+                format!("Payload field '{}' not found in fragments", self.rule.payload_field),
+                key,
+            ));
+        }
+
+        combined.metadata.extra.insert("reassembly".to_string(), serde_json::json!({
+            "key": key,
+            "total": group.total,
+            "received": fragment_count,
+            "complete": true,
+            "duration_ms": group.updated_at.saturating_duration_since(group.started_at).as_millis() as u64,
+        }));
+
+        combined
+    }
+}
+
+/// Read a numeric field value (binary or ASCII)
+fn field_number(result: &ParseResult, name: &str) -> Option<u64> {
+    let field = result.fields.get_field(name)?;
+    match &field.value {
+        FieldValue::UInt(v) => Some(*v),
+        FieldValue::Int(v) if *v >= 0 => Some(*v as u64),
+        FieldValue::String(s) => s.trim().parse::<u64>().ok(),
+        _ => None,
+    }
+}
+
+/// Clone the payload field of a fragment
+fn payload_field(result: &ParseResult, name: &str) -> Option<ParsedField> {
+    result.fields.get_field(name).cloned()
+}
+
+/// Append a fragment's payload to the combined payload field
+fn append_field(target: &mut ParsedField, next: ParsedField) {
+    target.raw_bytes.extend_from_slice(&next.raw_bytes);
+    target.length += next.length;
+    target.valid &= next.valid;
+
+    target.value = match (std::mem::replace(&mut target.value, FieldValue::Null), next.value) {
+        (FieldValue::String(mut a), FieldValue::String(b)) => {
+            a.push_str(&b);
+            FieldValue::String(a)
+        }
+        _ => FieldValue::Bytes(target.raw_bytes.clone()),
+    };
+}
+
+/// Warning for a group that never completed
+fn incomplete_warning(key: &str, group: &FragmentGroup) -> ParseWarning {
+    let received: Vec<u64> = group.fragments.keys().copied().collect();
+    let mut warning = reassembly_warning(
+        "FRAGMENT_INCOMPLETE",
+        format!(
+            "Incomplete message '{}': received {} of {} fragment(s)",
+            key, group.fragments.len(), group.total
+        ),
+        key,
+    );
+    warning.context.insert("received".to_string(), serde_json::json!(received));
+    warning.context.insert("total".to_string(), serde_json::json!(group.total));
+    warning
+}
+
+/// Build a reassembly warning
+fn reassembly_warning(code: &str, message: String, key: &str) -> ParseWarning {
+    let mut context = HashMap::new();
+    context.insert("key".to_string(), serde_json::Value::String(key.to_string()));
+
+    ParseWarning {
+        message,
+        code: code.to_string(),
+        offset: None,
+        field: None,
+        context,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::result::{ParsedFields, ProtocolInfo, FieldMetadata, FieldValidationResult};
+
+    fn rule(drop_on_gap: bool) -> ReassemblyRule {
+        ReassemblyRule {
+            key_fields: vec!["mn".to_string()],
+            total_field: "pnum".to_string(),
+            index_field: "pno".to_string(),
+            index_base: 1,
+            payload_field: "cp".to_string(),
+            timeout_ms: 1000,
+            drop_on_gap,
+            max_pending_groups: 16,
+        }
+    }
+
+    fn field(name: &str, value: FieldValue) -> ParsedField {
+        let raw_bytes = value.as_string().into_bytes();
+        ParsedField {
+            name: name.to_string(),
+            length: raw_bytes.len(),
+            value,
+            raw_bytes,
+            offset: 0,
+            field_type: "String".to_string(),
+            description: String::new(),
+            valid: true,
+            validation: FieldValidationResult::default(),
+            nested_fields: None,
+            metadata: FieldMetadata::default(),
+        }
+    }
+
+    fn fragment(pno: u64, pnum: u64, cp: &str) -> ParseResult {
+        let mut fields = ParsedFields::new();
+        fields.add_field("mn".to_string(), field("mn", FieldValue::String("MN01".to_string())));
+        fields.add_field("pnum".to_string(), field("pnum", FieldValue::String(pnum.to_string())));
+        fields.add_field("pno".to_string(), field("pno", FieldValue::UInt(pno)));
+        fields.add_field("cp".to_string(), field("cp", FieldValue::String(cp.to_string())));
+
+        let protocol = ProtocolInfo {
+            name: "Test".to_string(),
+            version: "1.0".to_string(),
+            parser_id: "test".to_string(),
+            confidence: 1.0,
+        };
+        ParseResult::success(protocol, fields, cp.as_bytes().to_vec(), cp.len())
+    }
+
+    #[test]
+    fn test_single_frame_passes_through() {
+        let mut reassembler = Reassembler::new(rule(false));
+        let outcome = reassembler.push(&ReassemblyScope::default(), fragment(1, 1, "a"));
+        assert!(matches!(outcome, ReassemblyOutcome::Single(_)));
+        assert_eq!(reassembler.pending_groups(), 0);
+    }
+
+    #[test]
+    fn test_out_of_order_reassembly() {
+        let mut reassembler = Reassembler::new(rule(false));
+        let now = Instant::now();
+
+        assert!(matches!(reassembler.push_at(&ReassemblyScope::default(), fragment(2, 3, "b;"), now), ReassemblyOutcome::Pending(_)));
+        assert!(matches!(reassembler.push_at(&ReassemblyScope::default(), fragment(1, 3, "a;"), now), ReassemblyOutcome::Pending(_)));
+
+        match reassembler.push_at(&ReassemblyScope::default(), fragment(3, 3, "c"), now) {
+            ReassemblyOutcome::Complete(result) => {
+                let cp = result.fields.get_field("cp").unwrap();
+                assert_eq!(cp.value, FieldValue::String("a;b;c".to_string()));
+                assert_eq!(result.raw_data, b"a;b;c");
+                assert_eq!(result.metadata.extra["reassembly"]["received"], 3);
+            }
+            other => panic!("expected complete result, got {:?}", other),
+        }
+        assert_eq!(reassembler.pending_groups(), 0);
+    }
+
+    #[test]
+    fn test_drop_on_gap() {
+        let mut reassembler = Reassembler::new(rule(true));
+        reassembler.push(&ReassemblyScope::default(), fragment(1, 3, "a"));
+
+        match reassembler.push(&ReassemblyScope::default(), fragment(3, 3, "c")) {
+            ReassemblyOutcome::Dropped(result) => {
+                assert_eq!(result.warnings[0].code, "FRAGMENT_GAP");
+            }
+            other => panic!("expected dropped group, got {:?}", other),
+        }
+        assert_eq!(reassembler.pending_groups(), 0);
+    }
+
+    #[test]
+    fn test_expired_group_reports_warning() {
+        let mut reassembler = Reassembler::new(rule(false));
+        let start = Instant::now();
+        reassembler.push_at(&ReassemblyScope::default(), fragment(1, 2, "a"), start);
+
+        assert!(reassembler.expire(start + Duration::from_millis(500)).is_empty());
+
+        let warnings = reassembler.expire(start + Duration::from_millis(1500));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].1.code, "FRAGMENT_INCOMPLETE");
+        assert_eq!(reassembler.pending_groups(), 0);
+    }
+
+    #[test]
+    fn test_groups_are_kept_per_stream() {
+        let mut reassembler = Reassembler::new(rule(false));
+        let now = Instant::now();
+        let first = ReassemblyScope { session_id: Some("s1".to_string()), client_id: Some("c1".to_string()) };
+        let second = ReassemblyScope { session_id: Some("s1".to_string()), client_id: Some("c2".to_string()) };
+
+        // Same MN and PNUM from two clients: neither completes the other's message
+        reassembler.push_at(&first, fragment(1, 2, "a;"), now);
+        assert!(matches!(reassembler.push_at(&second, fragment(2, 2, "x"), now), ReassemblyOutcome::Pending(_)));
+        assert_eq!(reassembler.pending_groups(), 2);
+
+        assert!(matches!(reassembler.push_at(&first, fragment(2, 2, "b"), now), ReassemblyOutcome::Complete(_)));
+
+        let expired = reassembler.expire(now + Duration::from_millis(1000));
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].0, second);
+    }
+}
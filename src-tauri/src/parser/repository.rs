@@ -6,6 +6,11 @@
 
 use crate::parser::schema::ProtocolRule;
 use crate::parser::rules::RulesLoader;
+use crate::parser::lint::RuleLinter;
+use crate::parser::validation_report::ValidationReport;
+use crate::parser::revisions::{self, ProtocolRevision, RevisionStore, RuleDiff};
+use crate::parser::watcher::{ProtocolChangeEvent, ProtocolChangeKind};
+use crate::parser::bundle::{self, BundleFileKind, BundleImportAction, BundleImportResult, ConflictStrategy, ProtocolBundle};
 use crate::parser::ProtocolParser;
 use crate::types::{NetworkResult, NetworkError};
 use serde::{Deserialize, Serialize};
@@ -15,6 +20,10 @@ use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 
+/// `extra` key set on protocols whose rule file was deleted, holding the
+/// enabled state to restore if the file comes back
+const MISSING_FILE_KEY: &str = "missingFile";
+
 /// Protocol repository for managing stored protocol definitions
 pub struct ProtocolRepository {
     /// Repository root directory
@@ -25,6 +34,9 @@ pub struct ProtocolRepository {
     
     /// Rules loader for parsing protocol files
     rules_loader: RulesLoader,
+    
+    /// Revision history of stored protocols
+    revisions: RevisionStore,
 }
 
 /// Metadata for a stored protocol
@@ -69,6 +81,10 @@ pub struct ProtocolMetadata {
     /// Protocol validation status
     pub validation_status: ValidationStatus,
     
+    /// Current revision number (0 if no revision has been recorded)
+    #[serde(default)]
+    pub revision: u32,
+    
     /// Additional metadata
     pub extra: HashMap<String, serde_json::Value>,
 }
@@ -98,6 +114,14 @@ pub struct ProtocolImportRequest {
     
     /// Whether to enable the protocol immediately
     pub enabled: bool,
+    
+    /// Author recorded in the revision history
+    #[serde(default)]
+    pub author: Option<String>,
+    
+    /// Note recorded in the revision history
+    #[serde(default)]
+    pub note: Option<String>,
 }
 
 /// Protocol export options
@@ -132,10 +156,13 @@ impl ProtocolRepository {
         fs::create_dir_all(&metadata_dir)
             .map_err(|e| NetworkError::ParseError(format!("Failed to create metadata directory: {}", e)))?;
         
+        let revisions = RevisionStore::new(repository_path.join("revisions"))?;
+        
         let mut repository = Self {
             repository_path,
             metadata_cache: HashMap::new(),
             rules_loader: RulesLoader::new(),
+            revisions,
         };
         
         // Load existing protocols
@@ -149,11 +176,17 @@ impl ProtocolRepository {
         // Parse the protocol rule to validate it
         let rule = self.rules_loader.load_rule_from_string(&request.content)?;
         
+        // Determine filename
+        let filename = format!("{}.kkp.yaml", sanitize_filename(&rule.meta.name));
+        
+        self.import_protocol_as(request, rule, filename)
+    }
+    
+    /// Import a validated protocol rule under the given filename
+    fn import_protocol_as(&mut self, request: ProtocolImportRequest, rule: ProtocolRule, filename: String) -> NetworkResult<String> {
         // Generate unique ID
         let protocol_id = Uuid::new_v4().to_string();
         
-        // Determine filename
-        let filename = format!("{}.kkp.yaml", sanitize_filename(&rule.meta.name));
         let file_path = self.repository_path.join("protocols").join(&filename);
         
         // Check if file already exists
@@ -168,6 +201,29 @@ impl ProtocolRepository {
         fs::write(&file_path, &request.content)
             .map_err(|e| NetworkError::ParseError(format!("Failed to write protocol file: {}", e)))?;
         
+        self.register_protocol_file(protocol_id, request, rule, filename)
+    }
+    
+    /// Create metadata and the first revision for a rule file already in the protocols directory
+    fn register_protocol_file(&mut self, protocol_id: String, request: ProtocolImportRequest, rule: ProtocolRule, filename: String) -> NetworkResult<String> {
+        // Lint the rule and keep a summary with the metadata
+        let mut extra = HashMap::new();
+        match RuleLinter::lint_str(&request.content) {
+            Ok(report) => {
+                extra.insert("lint".to_string(), lint_summary(&report));
+            }
+            Err(e) => log::warn!("Failed to lint protocol '{}': {}", rule.meta.name, e),
+        }
+        
+        // Record the first revision
+        let revision = self.revisions.record(
+            &protocol_id,
+            &request.content,
+            &rule.meta.version,
+            &request.author.unwrap_or_else(default_author),
+            request.note.as_deref().unwrap_or("Imported"),
+        )?;
+        
         // Create metadata
         let now = Utc::now();
         let metadata = ProtocolMetadata {
@@ -184,7 +240,8 @@ impl ProtocolRepository {
             modified_at: now,
             enabled: request.enabled,
             validation_status: ValidationStatus::Valid,
-            extra: HashMap::new(),
+            revision: revision.revision,
+            extra,
         };
         
         // Save metadata
@@ -239,6 +296,218 @@ impl ProtocolRepository {
         &self.repository_path
     }
     
+    /// Export a protocol with its catalogs, schemas and samples as a bundle
+    pub fn export_bundle(&mut self, protocol_id: &str, export_path: &Path) -> NetworkResult<PathBuf> {
+        let metadata = self.get_protocol_metadata(protocol_id)?.clone();
+        let protocols_dir = self.repository_path.join("protocols");
+        
+        let content = fs::read_to_string(protocols_dir.join(&metadata.filename))
+            .map_err(|e| NetworkError::ParseError(format!("Failed to read protocol file: {}", e)))?;
+        let rule = self.rules_loader.load_rule_from_string(&content)?;
+        
+        let mut bundle = ProtocolBundle::new(metadata.clone());
+        bundle.add_file(BundleFileKind::Rule, &metadata.filename, content.clone().into_bytes())?;
+        
+        for (name, catalog) in &rule.catalogs {
+            if let Some(file) = &catalog.file {
+                let data = fs::read(protocols_dir.join(file))
+                    .map_err(|e| NetworkError::ParseError(format!("Failed to read catalog '{}' ({}): {}", name, file, e)))?;
+                bundle.add_file(BundleFileKind::Catalog, file, data)?;
+            }
+        }
+        
+        for schema in bundle::referenced_schemas(&content) {
+            match fs::read(protocols_dir.join(&schema)) {
+                Ok(data) => bundle.add_file(BundleFileKind::Schema, &schema, data)?,
+                Err(e) => log::warn!("Schema '{}' referenced by '{}' not found: {}", schema, metadata.name, e),
+            }
+        }
+        
+        for sample in self.list_samples(protocol_id)? {
+            let data = fs::read(self.samples_dir(protocol_id).join(&sample))
+                .map_err(|e| NetworkError::ParseError(format!("Failed to read sample '{}': {}", sample, e)))?;
+            bundle.add_file(BundleFileKind::Sample, &sample, data)?;
+        }
+        
+        let file = fs::File::create(export_path)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to create bundle file: {}", e)))?;
+        bundle.write_to(file)?;
+        
+        log::info!("Exported protocol bundle '{}' to: {}", metadata.name, export_path.display());
+        
+        Ok(export_path.to_path_buf())
+    }
+    
+    /// Import a protocol bundle
+    pub fn import_bundle(&mut self, data: &[u8], strategy: ConflictStrategy) -> NetworkResult<BundleImportResult> {
+        let bundle = ProtocolBundle::read_from(std::io::Cursor::new(data))?;
+        let bundle_metadata = bundle.metadata().clone();
+        let (rule_file, content) = bundle.rule()?;
+        let rule = self.rules_loader.load_rule_from_string(content)?;
+        
+        // Every catalog file the rule references must travel with it
+        for (name, catalog) in &rule.catalogs {
+            if let Some(file) = &catalog.file {
+                let path = bundle::catalog_path(file);
+                if !bundle.files_of(BundleFileKind::Catalog).iter().any(|f| Some(f.name()) == path.as_deref()) {
+                    return Err(NetworkError::ParseError(format!(
+                        "Bundle is missing catalog '{}' ({})",
+                        name, file
+                    )));
+                }
+            }
+        }
+        
+        let existing = self.metadata_cache.values()
+            .find(|m| m.name == bundle_metadata.name || m.filename == rule_file.name())
+            .map(|m| m.id.clone());
+        
+        let (action, name, filename) = match (&existing, strategy) {
+            (Some(existing_id), ConflictStrategy::Skip) => {
+                let metadata = self.get_protocol_metadata(existing_id)?;
+                return Ok(BundleImportResult {
+                    protocol_id: existing_id.clone(),
+                    name: metadata.name.clone(),
+                    action: BundleImportAction::Skipped,
+                    enabled: metadata.enabled,
+                    warnings: Vec::new(),
+                });
+            }
+            (Some(existing_id), ConflictStrategy::Replace) => {
+                let metadata = self.get_protocol_metadata(existing_id)?;
+                (BundleImportAction::Replaced, metadata.name.clone(), metadata.filename.clone())
+            }
+            (Some(_), ConflictStrategy::Rename) => {
+                let name = self.unique_protocol_name(&bundle_metadata.name);
+                let filename = format!("{}.kkp.yaml", sanitize_filename(&name));
+                (BundleImportAction::Renamed, name, filename)
+            }
+            (None, _) => (BundleImportAction::Imported, bundle_metadata.name.clone(), rule_file.name().to_string()),
+        };
+        
+        // Supporting files live next to the rules and may be shared with other protocols
+        let mut warnings = Vec::new();
+        let protocols_dir = self.repository_path.join("protocols");
+        for kind in [BundleFileKind::Catalog, BundleFileKind::Schema] {
+            for file in bundle.files_of(kind) {
+                let data = bundle.content(file).unwrap_or_default();
+                // Catalogs go back to the relative path the rule references
+                let path = protocols_dir.join(file.name());
+                
+                if let Ok(current) = fs::read(&path) {
+                    if current == data {
+                        continue;
+                    }
+                    if action != BundleImportAction::Replaced {
+                        warnings.push(format!("Kept existing '{}', which differs from the bundled copy", file.name()));
+                        continue;
+                    }
+                }
+                
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| NetworkError::ParseError(format!("Failed to create directory for '{}': {}", file.name(), e)))?;
+                }
+                fs::write(&path, data)
+                    .map_err(|e| NetworkError::ParseError(format!("Failed to write '{}': {}", file.name(), e)))?;
+            }
+        }
+        self.rules_loader.clear_cache();
+        
+        let note = format!("Imported from bundle ({})", bundle_metadata.name);
+        let protocol_id = match &existing {
+            Some(existing_id) if action == BundleImportAction::Replaced => {
+                self.update_protocol(existing_id, content, None, &note)?;
+                
+                let metadata = self.metadata_cache.get_mut(existing_id).unwrap();
+                metadata.enabled = bundle_metadata.enabled;
+                metadata.tags = bundle_metadata.tags.clone();
+                metadata.category = bundle_metadata.category.clone();
+                let metadata = metadata.clone();
+                self.save_metadata(&metadata)?;
+                existing_id.clone()
+            }
+            _ => {
+                let request = ProtocolImportRequest {
+                    content: content.to_string(),
+                    custom_name: Some(name.clone()),
+                    custom_category: Some(bundle_metadata.category.clone()),
+                    tags: bundle_metadata.tags.clone(),
+                    enabled: bundle_metadata.enabled,
+                    author: None,
+                    note: Some(note),
+                };
+                self.import_protocol_as(request, rule, filename)?
+            }
+        };
+        
+        for file in bundle.files_of(BundleFileKind::Sample) {
+            self.add_sample(&protocol_id, file.name(), bundle.content(file).unwrap_or_default())?;
+        }
+        
+        log::info!("Imported protocol bundle '{}' as {:?} ({})", name, action, protocol_id);
+        
+        Ok(BundleImportResult {
+            protocol_id,
+            name,
+            action,
+            enabled: bundle_metadata.enabled,
+            warnings,
+        })
+    }
+    
+    /// Store a sample capture for a protocol
+    pub fn add_sample(&self, protocol_id: &str, name: &str, data: &[u8]) -> NetworkResult<PathBuf> {
+        self.get_protocol_metadata(protocol_id)?;
+        
+        let dir = self.samples_dir(protocol_id);
+        fs::create_dir_all(&dir)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to create samples directory: {}", e)))?;
+        
+        let path = dir.join(sanitize_filename(name));
+        fs::write(&path, data)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to write sample: {}", e)))?;
+        
+        Ok(path)
+    }
+    
+    /// List sample captures of a protocol
+    pub fn list_samples(&self, protocol_id: &str) -> NetworkResult<Vec<String>> {
+        let dir = self.samples_dir(protocol_id);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        
+        let entries = fs::read_dir(&dir)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to read samples directory: {}", e)))?;
+        
+        let mut samples: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        samples.sort();
+        
+        Ok(samples)
+    }
+    
+    /// Directory holding a protocol's sample captures
+    fn samples_dir(&self, protocol_id: &str) -> PathBuf {
+        self.repository_path.join("samples").join(protocol_id)
+    }
+    
+    /// Find a protocol name not used by any stored protocol
+    fn unique_protocol_name(&self, name: &str) -> String {
+        let protocols_dir = self.repository_path.join("protocols");
+        (2..)
+            .map(|n| format!("{} ({})", name, n))
+            .find(|candidate| {
+                !self.metadata_cache.values().any(|m| &m.name == candidate)
+                    && !protocols_dir.join(format!("{}.kkp.yaml", sanitize_filename(candidate))).exists()
+            })
+            .unwrap()
+    }
+    
     /// Delete a protocol
     pub fn delete_protocol(&mut self, protocol_id: &str) -> NetworkResult<()> {
         let metadata = self.get_protocol_metadata(protocol_id)?.clone();
@@ -257,6 +526,14 @@ impl ProtocolRepository {
                 .map_err(|e| NetworkError::ParseError(format!("Failed to delete metadata file: {}", e)))?;
         }
         
+        // Delete revision history and samples
+        self.revisions.remove(protocol_id)?;
+        let samples_dir = self.samples_dir(protocol_id);
+        if samples_dir.exists() {
+            fs::remove_dir_all(&samples_dir)
+                .map_err(|e| NetworkError::ParseError(format!("Failed to delete samples: {}", e)))?;
+        }
+        
         // Remove from cache
         self.metadata_cache.remove(protocol_id);
         
@@ -285,6 +562,256 @@ impl ProtocolRepository {
         Ok(())
     }
     
+    /// Replace a protocol's rule content, recording a new revision
+    pub fn update_protocol(&mut self, protocol_id: &str, content: &str, author: Option<&str>, note: &str) -> NetworkResult<ProtocolRevision> {
+        let rule = self.rules_loader.load_rule_from_string(content)?;
+        let metadata = self.get_protocol_metadata(protocol_id)?.clone();
+        let protocol_path = self.repository_path.join("protocols").join(&metadata.filename);
+        
+        // Protocols imported before revisions existed get their current file as a baseline
+        if self.revisions.list(protocol_id)?.is_empty() {
+            if let Ok(current) = fs::read_to_string(&protocol_path) {
+                self.revisions.record(protocol_id, &current, &metadata.version, &metadata.author, "Baseline")?;
+            }
+        }
+        
+        let author = author.map(str::to_string).unwrap_or_else(default_author);
+        let revision = self.revisions.record(protocol_id, content, &rule.meta.version, &author, note)?;
+        
+        fs::write(&protocol_path, content)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to write protocol file: {}", e)))?;
+        
+        self.refresh_protocol_metadata(metadata, &rule, content, &revision)?;
+        
+        log::info!("Updated protocol {} to revision {}", protocol_id, revision.revision);
+        
+        Ok(revision)
+    }
+    
+    /// Refresh cached metadata after a protocol's rule file changed
+    fn refresh_protocol_metadata(&mut self, metadata: ProtocolMetadata, rule: &ProtocolRule, content: &str, revision: &ProtocolRevision) -> NetworkResult<()> {
+        // Cached rules must be reloaded from disk
+        self.rules_loader.clear_cache();
+        
+        let mut extra = metadata.extra.clone();
+        if let Ok(report) = RuleLinter::lint_str(content) {
+            extra.insert("lint".to_string(), lint_summary(&report));
+        }
+        
+        let metadata = ProtocolMetadata {
+            version: rule.meta.version.clone(),
+            author: rule.meta.author.clone(),
+            description: rule.meta.description.clone(),
+            file_size: content.len() as u64,
+            modified_at: Utc::now(),
+            validation_status: ValidationStatus::Valid,
+            revision: revision.revision,
+            extra,
+            ..metadata
+        };
+        self.save_metadata(&metadata)?;
+        self.metadata_cache.insert(metadata.id.clone(), metadata);
+        
+        Ok(())
+    }
+    
+    /// Bring the repository in line with a rule file changed outside the app
+    ///
+    /// Returns `None` when the file already matches the recorded state.
+    /// A rule that fails to load marks the protocol invalid but keeps its metadata.
+    /// A deleted rule file only disables its protocol, keeping revisions and
+    /// samples; a file that reappears, or shows up elsewhere with the same
+    /// content, takes the protocol back.
+    pub fn sync_protocol_file(&mut self, filename: &str) -> NetworkResult<Option<ProtocolChangeEvent>> {
+        let path = self.repository_path.join("protocols").join(filename);
+        let existing = self.metadata_cache.values()
+            .find(|m| m.filename == filename)
+            .cloned();
+        
+        if !path.exists() {
+            let Some(mut metadata) = existing else {
+                return Ok(None);
+            };
+            if metadata.extra.contains_key(MISSING_FILE_KEY) {
+                return Ok(None);
+            }
+            
+            metadata.extra.insert(MISSING_FILE_KEY.to_string(), serde_json::Value::Bool(metadata.enabled));
+            metadata.enabled = false;
+            metadata.validation_status = ValidationStatus::Invalid(format!("Rule file '{}' is missing", filename));
+            metadata.modified_at = Utc::now();
+            self.save_metadata(&metadata)?;
+            self.metadata_cache.insert(metadata.id.clone(), metadata.clone());
+            
+            log::info!("Protocol file '{}' removed, disabled protocol {}", filename, metadata.id);
+            return Ok(Some(ProtocolChangeEvent::new(ProtocolChangeKind::Removed, filename, Some(&metadata))));
+        }
+        
+        let content = fs::read_to_string(&path)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to read protocol file: {}", e)))?;
+        
+        let existing = match existing {
+            Some(metadata) => Some(metadata),
+            None => self.find_moved_protocol(&content)?,
+        };
+        let existing = existing.map(|mut metadata| {
+            metadata.filename = filename.to_string();
+            if let Some(enabled) = metadata.extra.remove(MISSING_FILE_KEY) {
+                metadata.enabled = enabled.as_bool().unwrap_or(false);
+            }
+            metadata
+        });
+        
+        let Some(metadata) = existing else {
+            let rule = match self.rules_loader.load_rule_from_string(&content) {
+                Ok(rule) => rule,
+                Err(e) => {
+                    log::warn!("Ignoring invalid protocol file '{}': {}", filename, e);
+                    return Ok(Some(ProtocolChangeEvent::new(ProtocolChangeKind::Invalid, filename, None)
+                        .with_error(e.to_string())));
+                }
+            };
+            
+            let request = ProtocolImportRequest {
+                content,
+                custom_name: None,
+                custom_category: None,
+                tags: Vec::new(),
+                enabled: true,
+                author: None,
+                note: Some("Added to protocols directory".to_string()),
+            };
+            let protocol_id = self.register_protocol_file(Uuid::new_v4().to_string(), request, rule, filename.to_string())?;
+            let metadata = self.get_protocol_metadata(&protocol_id)?;
+            return Ok(Some(ProtocolChangeEvent::new(ProtocolChangeKind::Added, filename, Some(metadata))));
+        };
+        
+        // Writes made through the repository are already recorded
+        let unchanged = self.revisions.list(&metadata.id)?
+            .last()
+            .map(|revision| revision.content_hash == revisions::content_hash(&content))
+            .unwrap_or(false);
+        if unchanged && matches!(metadata.validation_status, ValidationStatus::Valid) {
+            return Ok(None);
+        }
+        
+        let rule = match self.rules_loader.load_rule_from_string(&content) {
+            Ok(rule) => rule,
+            Err(e) => {
+                let error = e.to_string();
+                log::warn!("Protocol file '{}' is invalid, keeping last good rule: {}", filename, error);
+                
+                let mut metadata = metadata;
+                metadata.validation_status = ValidationStatus::Invalid(error.clone());
+                metadata.modified_at = Utc::now();
+                self.save_metadata(&metadata)?;
+                self.metadata_cache.insert(metadata.id.clone(), metadata.clone());
+                return Ok(Some(ProtocolChangeEvent::new(ProtocolChangeKind::Invalid, filename, Some(&metadata))
+                    .with_error(error)));
+            }
+        };
+        
+        let revision = self.revisions.record(&metadata.id, &content, &rule.meta.version, &default_author(), "Edited outside the app")?;
+        self.refresh_protocol_metadata(metadata, &rule, &content, &revision)?;
+        
+        let metadata = self.metadata_cache.values()
+            .find(|m| m.filename == filename)
+            .unwrap();
+        log::info!("Protocol file '{}' changed, now at revision {}", filename, revision.revision);
+        
+        Ok(Some(ProtocolChangeEvent::new(ProtocolChangeKind::Updated, filename, Some(metadata))))
+    }
+    
+    /// Find a protocol whose rule file went missing and whose latest revision
+    /// matches `content`, i.e. a rule file that was moved
+    fn find_moved_protocol(&self, content: &str) -> NetworkResult<Option<ProtocolMetadata>> {
+        let content_hash = revisions::content_hash(content);
+        for metadata in self.metadata_cache.values().filter(|m| m.extra.contains_key(MISSING_FILE_KEY)) {
+            let latest = self.revisions.list(&metadata.id)?;
+            if latest.last().map(|revision| revision.content_hash == content_hash).unwrap_or(false) {
+                return Ok(Some(metadata.clone()));
+            }
+        }
+        Ok(None)
+    }
+    
+    /// Mark a protocol invalid without touching its rule file
+    pub fn mark_protocol_invalid(&mut self, protocol_id: &str, error: &str) -> NetworkResult<()> {
+        let mut metadata = self.get_protocol_metadata(protocol_id)?.clone();
+        metadata.validation_status = ValidationStatus::Invalid(error.to_string());
+        self.save_metadata(&metadata)?;
+        self.metadata_cache.insert(protocol_id.to_string(), metadata);
+        Ok(())
+    }
+    
+    /// List revisions of a protocol, oldest first
+    pub fn list_revisions(&self, protocol_id: &str) -> NetworkResult<Vec<ProtocolRevision>> {
+        self.get_protocol_metadata(protocol_id)?;
+        self.revisions.list(protocol_id)
+    }
+    
+    /// Get the rule content of a revision
+    pub fn get_revision_content(&self, protocol_id: &str, revision: u32) -> NetworkResult<String> {
+        self.get_protocol_metadata(protocol_id)?;
+        self.revisions.content(protocol_id, revision)
+    }
+    
+    /// Compare two revisions field by field
+    pub fn diff_revisions(&mut self, protocol_id: &str, from_revision: u32, to_revision: u32) -> NetworkResult<RuleDiff> {
+        let old = self.rules_loader.load_rule_from_string(&self.get_revision_content(protocol_id, from_revision)?)?;
+        let new = self.rules_loader.load_rule_from_string(&self.get_revision_content(protocol_id, to_revision)?)?;
+        revisions::diff_rules(from_revision, &old, to_revision, &new)
+    }
+    
+    /// Restore an earlier revision as a new revision
+    pub fn rollback_protocol(&mut self, protocol_id: &str, revision: u32, author: Option<&str>, note: Option<&str>) -> NetworkResult<ProtocolRevision> {
+        let content = self.get_revision_content(protocol_id, revision)?;
+        let note = note.map(str::to_string)
+            .unwrap_or_else(|| format!("Rollback to revision {}", revision));
+        self.update_protocol(protocol_id, &content, author, &note)
+    }
+    
+    /// Import a catalog file (CSV) next to the protocol rules
+    pub fn import_catalog(&mut self, filename: &str, content: &str) -> NetworkResult<PathBuf> {
+        let filename = sanitize_filename(filename);
+        if !filename.to_lowercase().ends_with(".csv") {
+            return Err(NetworkError::ParseError(format!(
+                "Catalog file '{}' must have a .csv extension",
+                filename
+            )));
+        }
+        
+        // Make sure the content is a readable table before storing it
+        let rows = crate::parser::catalog::read_csv(content.as_bytes(), ',', None)?;
+        
+        let file_path = self.repository_path.join("protocols").join(&filename);
+        fs::write(&file_path, content)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to write catalog file: {}", e)))?;
+        
+        // Rules referencing the catalog must be reloaded from disk
+        self.rules_loader.clear_cache();
+        
+        log::info!("Imported catalog '{}' with {} rows", filename, rows.len());
+        
+        Ok(file_path)
+    }
+    
+    /// List catalog files stored in the repository
+    pub fn list_catalogs(&self) -> NetworkResult<Vec<String>> {
+        let protocols_dir = self.repository_path.join("protocols");
+        let entries = fs::read_dir(&protocols_dir)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to read protocols directory: {}", e)))?;
+        
+        let mut catalogs: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name.to_lowercase().ends_with(".csv"))
+            .collect();
+        catalogs.sort();
+        
+        Ok(catalogs)
+    }
+    
     /// Load a protocol rule by ID
     pub fn load_protocol_rule(&mut self, protocol_id: &str) -> NetworkResult<ProtocolRule> {
         let metadata = self.get_protocol_metadata(protocol_id)?;
@@ -293,6 +820,25 @@ impl ProtocolRepository {
         self.rules_loader.load_rule(&protocol_path)
     }
     
+    /// Lint a stored protocol rule and refresh its lint summary
+    pub fn lint_protocol(&mut self, protocol_id: &str) -> NetworkResult<ValidationReport> {
+        let metadata = self.get_protocol_metadata(protocol_id)?;
+        let protocol_path = self.repository_path.join("protocols").join(&metadata.filename);
+        
+        let content = fs::read_to_string(&protocol_path)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to read protocol file: {}", e)))?;
+        let mut report = RuleLinter::lint_str(&content)?;
+        report.metadata.rule_file = Some(protocol_path.display().to_string());
+        
+        if let Some(metadata) = self.metadata_cache.get_mut(protocol_id) {
+            metadata.extra.insert("lint".to_string(), lint_summary(&report));
+            let metadata = metadata.clone();
+            self.save_metadata(&metadata)?;
+        }
+        
+        Ok(report)
+    }
+    
     /// Create a protocol parser by ID
     pub fn create_protocol_parser(&mut self, protocol_id: &str) -> NetworkResult<ProtocolParser> {
         let rule = self.load_protocol_rule(protocol_id)?;
@@ -355,6 +901,23 @@ impl ProtocolRepository {
     }
 }
 
+/// Default revision author (the OS user)
+fn default_author() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".to_string())
+}
+
+/// Summarize a lint report for protocol metadata
+fn lint_summary(report: &ValidationReport) -> serde_json::Value {
+    serde_json::json!({
+        "errors": report.summary.failed,
+        "warnings": report.summary.warnings,
+        "score": report.score,
+        "checked_at": report.metadata.generated_at,
+    })
+}
+
 /// Sanitize filename for cross-platform compatibility
 fn sanitize_filename(name: &str) -> String {
     name.chars()
@@ -380,6 +943,160 @@ mod tests {
         assert_eq!(sanitize_filename("Test:Protocol*"), "Test_Protocol_");
     }
     
+    #[test]
+    fn test_import_catalog() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut repo = ProtocolRepository::new(temp_dir.path()).unwrap();
+        
+        let path = repo.import_catalog("points.csv", "id,name\n1,Flow\n").unwrap();
+        assert!(path.exists());
+        assert_eq!(repo.list_catalogs().unwrap(), vec!["points.csv".to_string()]);
+        
+        assert!(repo.import_catalog("points.txt", "id,name\n").is_err());
+    }
+    
+    #[test]
+    fn test_update_and_rollback_protocol() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut repo = ProtocolRepository::new(temp_dir.path()).unwrap();
+        
+        let v1 = "meta:\n  name: Rev\n  version: \"1.0\"\n  author: A\nframing:\n  fixed_size: 1\nfields:\n  - name: id\n    type: uint8\n    offset: 0\n";
+        let id = repo.import_protocol(ProtocolImportRequest {
+            content: v1.to_string(),
+            custom_name: None,
+            custom_category: None,
+            tags: vec![],
+            enabled: true,
+            author: Some("alice".to_string()),
+            note: None,
+        }).unwrap();
+        assert_eq!(repo.get_protocol_metadata(&id).unwrap().revision, 1);
+        
+        let v2 = v1.replace("1.0", "1.1");
+        repo.update_protocol(&id, &v2, Some("bob"), "Bump version").unwrap();
+        assert_eq!(repo.load_protocol_rule(&id).unwrap().meta.version, "1.1");
+        
+        let diff = repo.diff_revisions(&id, 1, 2).unwrap();
+        assert!(diff.fields.is_empty());
+        assert_eq!(diff.sections[0].property, "meta.version");
+        
+        let rollback = repo.rollback_protocol(&id, 1, None, None).unwrap();
+        assert_eq!(rollback.revision, 3);
+        assert_eq!(rollback.note, "Rollback to revision 1");
+        assert_eq!(repo.load_protocol_rule(&id).unwrap().meta.version, "1.0");
+        assert_eq!(repo.list_revisions(&id).unwrap().len(), 3);
+        
+        repo.delete_protocol(&id).unwrap();
+        assert!(!temp_dir.path().join("revisions").join(&id).exists());
+    }
+    
+    #[test]
+    fn test_bundle_export_and_import() {
+        let source_dir = TempDir::new().unwrap();
+        let mut source = ProtocolRepository::new(source_dir.path()).unwrap();
+        source.import_catalog("points.csv", "id,name\n1,Flow\n").unwrap();
+        fs::create_dir_all(source_dir.path().join("protocols/tables")).unwrap();
+        fs::write(source_dir.path().join("protocols/tables/units.csv"), "id,unit\n1,m3/h\n").unwrap();
+        
+        let content = "meta:\n  name: Bundled\n  version: \"1.0\"\n  author: A\nframing:\n  fixed_size: 1\nfields:\n  - name: id\n    type: uint8\n    offset: 0\ncatalogs:\n  points:\n    key: id\n    file: points.csv\n  units:\n    key: id\n    file: ./tables/units.csv\n";
+        let id = source.import_protocol(ProtocolImportRequest {
+            content: content.to_string(),
+            custom_name: None,
+            custom_category: None,
+            tags: vec!["flow".to_string()],
+            enabled: false,
+            author: None,
+            note: None,
+        }).unwrap();
+        source.add_sample(&id, "frame.bin", &[0x01]).unwrap();
+        
+        let bundle_path = source_dir.path().join("Bundled.kkpbundle");
+        source.export_bundle(&id, &bundle_path).unwrap();
+        let data = fs::read(&bundle_path).unwrap();
+        
+        let target_dir = TempDir::new().unwrap();
+        let mut target = ProtocolRepository::new(target_dir.path()).unwrap();
+        
+        let imported = target.import_bundle(&data, ConflictStrategy::Rename).unwrap();
+        assert_eq!(imported.action, BundleImportAction::Imported);
+        assert!(!imported.enabled);
+        assert_eq!(target.get_protocol_metadata(&imported.protocol_id).unwrap().tags, vec!["flow".to_string()]);
+        assert_eq!(target.list_catalogs().unwrap(), vec!["points.csv".to_string()]);
+        assert_eq!(fs::read_to_string(target_dir.path().join("protocols/tables/units.csv")).unwrap(), "id,unit\n1,m3/h\n");
+        assert_eq!(target.list_samples(&imported.protocol_id).unwrap(), vec!["frame.bin".to_string()]);
+        assert!(target.load_protocol_rule(&imported.protocol_id).is_ok());
+        
+        let skipped = target.import_bundle(&data, ConflictStrategy::Skip).unwrap();
+        assert_eq!(skipped.action, BundleImportAction::Skipped);
+        assert_eq!(skipped.protocol_id, imported.protocol_id);
+        
+        let renamed = target.import_bundle(&data, ConflictStrategy::Rename).unwrap();
+        assert_eq!(renamed.action, BundleImportAction::Renamed);
+        assert_eq!(renamed.name, "Bundled (2)");
+        
+        let replaced = target.import_bundle(&data, ConflictStrategy::Replace).unwrap();
+        assert_eq!(replaced.action, BundleImportAction::Replaced);
+        assert_eq!(target.list_protocols().len(), 2);
+    }
+    
+    #[test]
+    fn test_sync_protocol_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut repository = ProtocolRepository::new(temp_dir.path()).unwrap();
+        
+        let content = "meta:\n  name: Synced\n  version: \"1.0\"\n  author: A\nframing:\n  fixed_size: 1\nfields:\n  - name: id\n    type: uint8\n    offset: 0\n";
+        let id = repository.import_protocol(ProtocolImportRequest {
+            content: content.to_string(),
+            custom_name: None,
+            custom_category: None,
+            tags: vec![],
+            enabled: true,
+            author: None,
+            note: None,
+        }).unwrap();
+        let filename = repository.get_protocol_metadata(&id).unwrap().filename.clone();
+        let path = temp_dir.path().join("protocols").join(&filename);
+        
+        // Our own write is already recorded
+        assert!(repository.sync_protocol_file(&filename).unwrap().is_none());
+        
+        fs::write(&path, content.replace("\"1.0\"", "\"1.1\"")).unwrap();
+        let change = repository.sync_protocol_file(&filename).unwrap().unwrap();
+        assert_eq!(change.kind, ProtocolChangeKind::Updated);
+        assert_eq!(change.revision, 2);
+        assert_eq!(repository.get_protocol_metadata(&id).unwrap().version, "1.1");
+        
+        fs::write(&path, "meta: [broken").unwrap();
+        let change = repository.sync_protocol_file(&filename).unwrap().unwrap();
+        assert_eq!(change.kind, ProtocolChangeKind::Invalid);
+        assert!(change.error.is_some());
+        assert!(matches!(repository.get_protocol_metadata(&id).unwrap().validation_status, ValidationStatus::Invalid(_)));
+        assert_eq!(repository.list_revisions(&id).unwrap().len(), 2);
+        
+        // A moved file keeps the protocol and its history
+        fs::write(&path, content.replace("\"1.0\"", "\"1.1\"")).unwrap();
+        repository.sync_protocol_file(&filename).unwrap();
+        repository.add_sample(&id, "capture.bin", &[0x01]).unwrap();
+        let moved = "Moved.kkp.yaml";
+        fs::rename(&path, temp_dir.path().join("protocols").join(moved)).unwrap();
+        
+        let change = repository.sync_protocol_file(&filename).unwrap().unwrap();
+        assert_eq!(change.kind, ProtocolChangeKind::Removed);
+        let metadata = repository.get_protocol_metadata(&id).unwrap();
+        assert!(!metadata.enabled);
+        assert!(matches!(metadata.validation_status, ValidationStatus::Invalid(_)));
+        assert!(repository.sync_protocol_file(&filename).unwrap().is_none());
+        
+        let change = repository.sync_protocol_file(moved).unwrap().unwrap();
+        assert_eq!(change.kind, ProtocolChangeKind::Updated);
+        let metadata = repository.get_protocol_metadata(&id).unwrap();
+        assert!(metadata.enabled);
+        assert_eq!(metadata.filename, moved);
+        assert_eq!(repository.list_revisions(&id).unwrap().len(), 2);
+        assert_eq!(repository.list_samples(&id).unwrap().len(), 1);
+        assert_eq!(repository.list_protocols().len(), 1);
+    }
+    
     #[test]
     fn test_repository_creation() {
         let temp_dir = TempDir::new().unwrap();
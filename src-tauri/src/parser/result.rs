@@ -143,6 +143,31 @@ pub struct ParsedField {
     pub metadata: FieldMetadata,
 }
 
+impl ParsedField {
+    /// Create a valid field decoded from `raw_bytes` at `offset`
+    pub fn new(name: &str, value: FieldValue, field_type: &str, offset: usize, raw_bytes: &[u8]) -> Self {
+        Self {
+            name: name.to_string(),
+            value,
+            raw_bytes: raw_bytes.to_vec(),
+            offset,
+            length: raw_bytes.len(),
+            field_type: field_type.to_string(),
+            description: String::new(),
+            valid: true,
+            validation: FieldValidationResult::default(),
+            nested_fields: None,
+            metadata: FieldMetadata::default(),
+        }
+    }
+    
+    /// Set the field description
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+}
+
 /// Field value types
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -403,6 +428,26 @@ pub struct ParseError {
     pub context: HashMap<String, serde_json::Value>,
 }
 
+impl ParseError {
+    /// Create an error with the given code and message
+    pub fn new(code: &str, message: String, severity: ErrorSeverity) -> Self {
+        Self {
+            message,
+            code: code.to_string(),
+            offset: None,
+            field: None,
+            severity,
+            context: HashMap::new(),
+        }
+    }
+    
+    /// Set the field the error refers to
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+}
+
 /// Parse warning
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParseWarning {
@@ -422,6 +467,19 @@ pub struct ParseWarning {
     pub context: HashMap<String, serde_json::Value>,
 }
 
+impl ParseWarning {
+    /// Create a warning with the given code and message
+    pub fn new(code: &str, message: String) -> Self {
+        Self {
+            message,
+            code: code.to_string(),
+            offset: None,
+            field: None,
+            context: HashMap::new(),
+        }
+    }
+}
+
 /// Error severity levels
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
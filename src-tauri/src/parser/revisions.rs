@@ -0,0 +1,385 @@
+//! Protocol revision history
+//!
+//! Every import, edit and rollback of a stored protocol writes an immutable
+//! revision under `revisions/{protocol_id}/`. Revisions are identified by a
+//! sequence number and carry a SHA-256 content hash, author and note.
+//! `diff_rules` compares two rules field by field.
+
+use crate::parser::schema::{FieldDefinition, ProtocolRule};
+use crate::types::{NetworkResult, NetworkError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::PathBuf;
+
+/// Revision index file name inside a protocol's revision directory
+const INDEX_FILE: &str = "index.json";
+
+/// A stored protocol revision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolRevision {
+    /// Revision number (starting at 1)
+    pub revision: u32,
+
+    /// SHA-256 of the rule content (hex)
+    pub content_hash: String,
+
+    /// Who made the change
+    pub author: String,
+
+    /// Change note
+    #[serde(default)]
+    pub note: String,
+
+    /// Protocol version declared in the rule
+    #[serde(default)]
+    pub version: String,
+
+    /// Content size in bytes
+    pub size: u64,
+
+    /// When the revision was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// Append-only store of protocol revisions
+#[derive(Debug, Clone)]
+pub struct RevisionStore {
+    /// Root directory for all revisions
+    root: PathBuf,
+}
+
+impl RevisionStore {
+    /// Create a revision store rooted at the given directory
+    pub fn new(root: PathBuf) -> NetworkResult<Self> {
+        fs::create_dir_all(&root)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to create revisions directory: {}", e)))?;
+        Ok(Self { root })
+    }
+
+    /// Record a new revision
+    ///
+    /// Content identical to the latest revision is not stored again; the
+    /// latest revision is returned instead.
+    pub fn record(&self, protocol_id: &str, content: &str, version: &str, author: &str, note: &str) -> NetworkResult<ProtocolRevision> {
+        let mut revisions = self.list(protocol_id)?;
+        let content_hash = content_hash(content);
+
+        if let Some(latest) = revisions.last() {
+            if latest.content_hash == content_hash {
+                return Ok(latest.clone());
+            }
+        }
+
+        let revision = ProtocolRevision {
+            revision: revisions.last().map(|r| r.revision + 1).unwrap_or(1),
+            content_hash,
+            author: author.to_string(),
+            note: note.to_string(),
+            version: version.to_string(),
+            size: content.len() as u64,
+            created_at: Utc::now(),
+        };
+
+        let dir = self.protocol_dir(protocol_id);
+        fs::create_dir_all(&dir)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to create revision directory: {}", e)))?;
+
+        let content_path = dir.join(revision_filename(revision.revision));
+        fs::write(&content_path, content)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to write revision: {}", e)))?;
+
+        revisions.push(revision.clone());
+        let index = serde_json::to_string_pretty(&revisions)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to serialize revision index: {}", e)))?;
+        fs::write(dir.join(INDEX_FILE), index)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to write revision index: {}", e)))?;
+
+        Ok(revision)
+    }
+
+    /// List revisions of a protocol, oldest first
+    pub fn list(&self, protocol_id: &str) -> NetworkResult<Vec<ProtocolRevision>> {
+        let index_path = self.protocol_dir(protocol_id).join(INDEX_FILE);
+        if !index_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&index_path)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to read revision index: {}", e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to parse revision index: {}", e)))
+    }
+
+    /// Get a revision's metadata
+    pub fn get(&self, protocol_id: &str, revision: u32) -> NetworkResult<ProtocolRevision> {
+        self.list(protocol_id)?
+            .into_iter()
+            .find(|r| r.revision == revision)
+            .ok_or_else(|| NetworkError::ParseError(format!(
+                "Revision {} not found for protocol {}",
+                revision, protocol_id
+            )))
+    }
+
+    /// Read a revision's content, verifying its hash
+    pub fn content(&self, protocol_id: &str, revision: u32) -> NetworkResult<String> {
+        let metadata = self.get(protocol_id, revision)?;
+        let path = self.protocol_dir(protocol_id).join(revision_filename(revision));
+
+        let content = fs::read_to_string(&path)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to read revision {}: {}", revision, e)))?;
+
+        if content_hash(&content) != metadata.content_hash {
+            return Err(NetworkError::ParseError(format!(
+                "Revision {} of protocol {} is corrupted (hash mismatch)",
+                revision, protocol_id
+            )));
+        }
+
+        Ok(content)
+    }
+
+    /// Remove all revisions of a protocol
+    pub fn remove(&self, protocol_id: &str) -> NetworkResult<()> {
+        let dir = self.protocol_dir(protocol_id);
+        if dir.exists() {
+            fs::remove_dir_all(&dir)
+                .map_err(|e| NetworkError::ParseError(format!("Failed to delete revisions: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    /// Directory holding a protocol's revisions
+    fn protocol_dir(&self, protocol_id: &str) -> PathBuf {
+        self.root.join(protocol_id)
+    }
+}
+
+/// SHA-256 of content as lowercase hex
+pub fn content_hash(content: &str) -> String {
+    content_hash_bytes(content.as_bytes())
+}
+
+/// SHA-256 of raw bytes as lowercase hex
+pub fn content_hash_bytes(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+/// File name of a stored revision
+fn revision_filename(revision: u32) -> String {
+    format!("{:06}.kkp.yaml", revision)
+}
+
+/// Kind of change between two revisions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// Changed property with old and new values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyChange {
+    /// Property path (e.g. `offset`, `framing.fixed_size`)
+    pub property: String,
+
+    /// Value in the older revision
+    pub old_value: Option<serde_json::Value>,
+
+    /// Value in the newer revision
+    pub new_value: Option<serde_json::Value>,
+}
+
+/// Change to a single field definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Field name
+    pub field: String,
+
+    /// Kind of change
+    pub kind: ChangeKind,
+
+    /// Changed properties (for modified fields)
+    pub changes: Vec<PropertyChange>,
+}
+
+/// Field-by-field difference between two rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDiff {
+    /// Older revision number
+    pub from_revision: u32,
+
+    /// Newer revision number
+    pub to_revision: u32,
+
+    /// Field changes in the newer rule's order (removed fields last)
+    pub fields: Vec<FieldChange>,
+
+    /// Changes outside the field list (meta, framing, validation, ...)
+    pub sections: Vec<PropertyChange>,
+}
+
+impl RuleDiff {
+    /// Check if the rules are identical
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.sections.is_empty()
+    }
+}
+
+/// Compare two rules field by field
+pub fn diff_rules(from_revision: u32, old: &ProtocolRule, to_revision: u32, new: &ProtocolRule) -> NetworkResult<RuleDiff> {
+    let mut fields = Vec::new();
+
+    for new_field in &new.fields {
+        match old.fields.iter().find(|f| f.name == new_field.name) {
+            Some(old_field) => {
+                let changes = diff_values("", &field_value(old_field)?, &field_value(new_field)?);
+                if !changes.is_empty() {
+                    fields.push(FieldChange {
+                        field: new_field.name.clone(),
+                        kind: ChangeKind::Modified,
+                        changes,
+                    });
+                }
+            }
+            None => fields.push(FieldChange {
+                field: new_field.name.clone(),
+                kind: ChangeKind::Added,
+                changes: Vec::new(),
+            }),
+        }
+    }
+
+    for old_field in &old.fields {
+        if !new.fields.iter().any(|f| f.name == old_field.name) {
+            fields.push(FieldChange {
+                field: old_field.name.clone(),
+                kind: ChangeKind::Removed,
+                changes: Vec::new(),
+            });
+        }
+    }
+
+    let mut old_rule = serde_json::to_value(old)?;
+    let mut new_rule = serde_json::to_value(new)?;
+    for rule in [&mut old_rule, &mut new_rule] {
+        if let Some(object) = rule.as_object_mut() {
+            object.remove("fields");
+        }
+    }
+
+    Ok(RuleDiff {
+        from_revision,
+        to_revision,
+        fields,
+        sections: diff_values("", &old_rule, &new_rule),
+    })
+}
+
+/// Serialize a field definition for comparison
+fn field_value(field: &FieldDefinition) -> NetworkResult<serde_json::Value> {
+    Ok(serde_json::to_value(field)?)
+}
+
+/// Recursively compare JSON values, reporting leaf changes by path
+fn diff_values(path: &str, old: &serde_json::Value, new: &serde_json::Value) -> Vec<PropertyChange> {
+    use serde_json::Value;
+
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            let mut keys: Vec<&String> = old_map.keys().chain(new_map.keys()).collect();
+            keys.sort();
+            keys.dedup();
+
+            keys.into_iter()
+                .flat_map(|key| {
+                    let child = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    match (old_map.get(key), new_map.get(key)) {
+                        (Some(o), Some(n)) => diff_values(&child, o, n),
+                        (o, n) => vec![PropertyChange {
+                            property: child,
+                            old_value: o.cloned(),
+                            new_value: n.cloned(),
+                        }],
+                    }
+                })
+                .collect()
+        }
+        _ if old == new => Vec::new(),
+        _ => vec![PropertyChange {
+            property: path.to_string(),
+            old_value: Some(old.clone()),
+            new_value: Some(new.clone()),
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const RULE_V1: &str = r#"
+meta:
+  name: Test
+  version: "1.0"
+  author: Test
+framing:
+  fixed_size: 4
+fields:
+  - name: id
+    type: uint8
+    offset: 0
+  - name: value
+    type: uint16
+    offset: 1
+    length: 2
+"#;
+
+    #[test]
+    fn test_record_and_read_revisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = RevisionStore::new(temp_dir.path().join("revisions")).unwrap();
+
+        let first = store.record("p1", "a: 1", "1.0", "alice", "initial").unwrap();
+        assert_eq!(first.revision, 1);
+
+        // Unchanged content does not create a revision
+        let same = store.record("p1", "a: 1", "1.0", "bob", "no-op").unwrap();
+        assert_eq!(same.revision, 1);
+
+        let second = store.record("p1", "a: 2", "1.1", "bob", "bump").unwrap();
+        assert_eq!(second.revision, 2);
+        assert_ne!(first.content_hash, second.content_hash);
+
+        assert_eq!(store.list("p1").unwrap().len(), 2);
+        assert_eq!(store.content("p1", 1).unwrap(), "a: 1");
+        assert!(store.content("p1", 3).is_err());
+    }
+
+    #[test]
+    fn test_diff_rules() {
+        let old: ProtocolRule = serde_yaml::from_str(RULE_V1).unwrap();
+        let new_content = RULE_V1
+            .replace("  fixed_size: 4", "  fixed_size: 5")
+            .replace("    offset: 1\n    length: 2", "    offset: 2\n    length: 2\n  - name: flags\n    type: uint8\n    offset: 4")
+            .replace("  - name: id\n    type: uint8\n    offset: 0\n", "");
+        let new: ProtocolRule = serde_yaml::from_str(&new_content).unwrap();
+
+        let diff = diff_rules(1, &old, 2, &new).unwrap();
+
+        let value = diff.fields.iter().find(|f| f.field == "value").unwrap();
+        assert_eq!(value.kind, ChangeKind::Modified);
+        assert_eq!(value.changes[0].property, "offset");
+        assert_eq!(value.changes[0].new_value, Some(serde_json::json!(2)));
+
+        assert!(diff.fields.iter().any(|f| f.field == "flags" && f.kind == ChangeKind::Added));
+        assert!(diff.fields.iter().any(|f| f.field == "id" && f.kind == ChangeKind::Removed));
+        assert!(diff.sections.iter().any(|c| c.property == "framing.fixed_size"));
+        assert!(diff_rules(1, &old, 1, &old).unwrap().is_empty());
+    }
+}
@@ -5,6 +5,9 @@
 //! comprehensive validation.
 
 use crate::parser::schema::*;
+use crate::parser::catalog::{self, LookupExpression, LookupKey};
+use crate::parser::envelope::TopicTemplate;
+use crate::parser::crc_validator::CrcAlgorithm;
 use crate::types::{NetworkResult, NetworkError};
 use serde_yaml;
 use std::collections::HashMap;
@@ -65,6 +68,9 @@ impl RulesLoader {
         // Resolve inheritance and references
         rule = self.inheritance_resolver.resolve_inheritance(rule, &file_path.parent().unwrap_or(&file_path))?;
         
+        // Load catalog files stored next to the rule
+        catalog::load_catalog_files(&mut rule.catalogs, file_path.parent().unwrap_or(&file_path))?;
+        
         // Validate the rule
         self.validate_rule(&rule)?;
         
@@ -197,6 +203,12 @@ impl RuleValidator {
         // Validate conditional rules
         self.validate_conditions(&rule.conditions, &rule.fields)?;
         
+        // Validate catalogs and lookups
+        self.validate_catalogs(rule)?;
+        
+        // Validate envelope matching
+        self.validate_envelope(rule)?;
+        
         Ok(())
     }
     
@@ -242,6 +254,17 @@ impl RuleValidator {
             }
         }
         
+        // Validate block CRC layout if present
+        if let Some(block_crc) = &framing.block_crc {
+            if CrcAlgorithm::from_name(&block_crc.algorithm).is_none() {
+                return Err(NetworkError::ParseError(format!("Unknown block CRC algorithm: {}", block_crc.algorithm)));
+            }
+            
+            if block_crc.block_size == 0 || block_crc.first_block == Some(0) {
+                return Err(NetworkError::ParseError("Block CRC block size cannot be zero".to_string()));
+            }
+        }
+        
         Ok(())
     }
     
@@ -352,6 +375,27 @@ impl RuleValidator {
             }
         }
         
+        // Check reassembly references
+        if let Some(reassembly) = &rule.reassembly {
+            let referenced = reassembly.key_fields.iter()
+                .chain([&reassembly.total_field, &reassembly.index_field, &reassembly.payload_field]);
+            for field_name in referenced {
+                if !field_names.contains(field_name) {
+                    return Err(NetworkError::ParseError(format!(
+                        "Reassembly references unknown field: {}",
+                        field_name
+                    )));
+                }
+            }
+            
+            if reassembly.index_base > 1 {
+                return Err(NetworkError::ParseError(format!(
+                    "Reassembly index base must be 0 or 1, got {}",
+                    reassembly.index_base
+                )));
+            }
+        }
+        
         // Check checksum validation references
         for checksum_validation in &rule.validation.checksum {
             if !field_names.contains(&checksum_validation.checksum_field) {
@@ -365,6 +409,75 @@ impl RuleValidator {
         Ok(())
     }
     
+    fn validate_catalogs(&self, rule: &ProtocolRule) -> NetworkResult<()> {
+        for (name, definition) in &rule.catalogs {
+            if definition.key.is_empty() {
+                return Err(NetworkError::ParseError(format!(
+                    "Catalog '{}' must specify a key column",
+                    name
+                )));
+            }
+            
+            if definition.file.is_none() && definition.rows.is_empty() {
+                log::warn!("Catalog '{}' has no rows and no file", name);
+            }
+        }
+        
+        let field_names: std::collections::HashSet<_> = rule.fields.iter().map(|f| f.name.as_str()).collect();
+        
+        for field in &rule.fields {
+            for (output, expression) in &field.lookups {
+                let lookup = LookupExpression::parse(expression).map_err(|e| NetworkError::ParseError(format!(
+                    "Field '{}' lookup '{}': {}",
+                    field.name, output, e
+                )))?;
+                
+                if !rule.catalogs.contains_key(&lookup.catalog) {
+                    return Err(NetworkError::ParseError(format!(
+                        "Field '{}' lookup '{}' references unknown catalog: {}",
+                        field.name, output, lookup.catalog
+                    )));
+                }
+                
+                if let LookupKey::Field(key_field) = &lookup.key {
+                    if !key_field.starts_with("envelope.") && !field_names.contains(key_field.as_str()) {
+                        return Err(NetworkError::ParseError(format!(
+                            "Field '{}' lookup '{}' references unknown field: {}",
+                            field.name, output, key_field
+                        )));
+                    }
+                }
+            }
+        }
+        
+        Ok(())
+    }
+    
+    fn validate_envelope(&self, rule: &ProtocolRule) -> NetworkResult<()> {
+        let Some(envelope) = &rule.envelope else {
+            return Ok(());
+        };
+        
+        let Some(topic_match) = &envelope.topic_match else {
+            if !envelope.expose.is_empty() {
+                return Err(NetworkError::ParseError("Envelope expose requires a topic_match template".to_string()));
+            }
+            return Ok(());
+        };
+        
+        let template = TopicTemplate::parse(topic_match)?;
+        for name in &envelope.expose {
+            if !template.variables().contains(name) {
+                return Err(NetworkError::ParseError(format!(
+                    "Envelope exposes '{}' which is not a variable of topic template '{}'",
+                    name, topic_match
+                )));
+            }
+        }
+        
+        Ok(())
+    }
+    
     fn validate_conditions(&self, conditions: &[ConditionalRule], fields: &[FieldDefinition]) -> NetworkResult<()> {
         let _field_names: std::collections::HashSet<_> = fields.iter().map(|f| &f.name).collect();
         
@@ -457,4 +570,37 @@ fields:
         let result = loader.load_rule_from_string(yaml);
         assert!(result.is_err());
     }
+    
+    #[test]
+    fn test_validate_lookup_unknown_catalog() {
+        let yaml = r#"
+meta:
+  name: "Test Protocol"
+  version: "1.0.0"
+  author: "Test Author"
+
+framing:
+  fixed_size: 2
+
+catalogs:
+  points:
+    key: "id"
+    rows:
+      - { id: 1, name: "Temperature" }
+
+fields:
+  - name: "point_id"
+    type: "uint8"
+    offset: 0
+    lookups:
+      name: "lookup(sensors, point_id).name"
+"#;
+        
+        let mut loader = RulesLoader::new();
+        assert!(loader.load_rule_from_string(yaml).is_err());
+        
+        let fixed = yaml.replace("lookup(sensors", "lookup(points");
+        let rule = loader.load_rule_from_string(&fixed).unwrap();
+        assert_eq!(rule.catalogs["points"].rows.len(), 1);
+    }
 }
@@ -34,6 +34,22 @@ pub struct ProtocolRule {
     /// Factor code definitions for environmental protocols
     #[serde(default)]
     pub factor_codes: Option<HashMap<String, serde_yaml::Value>>,
+
+    /// Lookup catalogs (inline tables or CSV files next to the rule)
+    #[serde(default)]
+    pub catalogs: HashMap<String, CatalogDefinition>,
+
+    /// Multi-packet message reassembly
+    #[serde(default)]
+    pub reassembly: Option<ReassemblyRule>,
+
+    /// Transport envelope matching (MQTT topic templates)
+    #[serde(default)]
+    pub envelope: Option<EnvelopeRule>,
+
+    /// Export mappings (Influx line protocol, flat JSON, CSV)
+    #[serde(default)]
+    pub exports: ExportRules,
 }
 
 /// Protocol metadata
@@ -91,6 +107,29 @@ pub struct FramingRule {
     /// Frame validation rules
     #[serde(default)]
     pub frame_validation: FrameValidation,
+    
+    /// Per-block CRCs interleaved with the frame data (a length field
+    /// counts data bytes only, a fixed size counts the whole frame)
+    #[serde(default)]
+    pub block_crc: Option<BlockCrc>,
+}
+
+/// Block CRC layout (DNP3 style: every data block carries its own CRC)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockCrc {
+    /// CRC algorithm name
+    pub algorithm: String,
+    
+    /// Data bytes per block
+    pub block_size: usize,
+    
+    /// Size of the first block when it differs (e.g. a frame header)
+    #[serde(default)]
+    pub first_block: Option<usize>,
+    
+    /// Byte order of each CRC
+    #[serde(default)]
+    pub endian: Endianness,
 }
 
 /// Length field configuration
@@ -232,6 +271,11 @@ pub struct FieldDefinition {
     /// Conditional parsing rules
     #[serde(default)]
     pub condition: Option<String>,
+
+    /// Catalog lookups added to the parsed output, keyed by output name
+    /// (e.g. `name: "lookup(points, point_id).name"`)
+    #[serde(default)]
+    pub lookups: HashMap<String, String>,
 }
 
 /// Field data types
@@ -446,6 +490,188 @@ pub struct ConditionalRule {
     pub else_fields: Vec<FieldDefinition>,
 }
 
+/// Lookup catalog definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogDefinition {
+    /// Column used as the lookup key
+    pub key: String,
+    
+    /// Inline table rows
+    #[serde(default)]
+    pub rows: Vec<HashMap<String, serde_json::Value>>,
+    
+    /// CSV file, relative to the rule file
+    #[serde(default)]
+    pub file: Option<String>,
+    
+    /// CSV column delimiter
+    #[serde(default = "default_csv_delimiter")]
+    pub delimiter: char,
+    
+    /// Catalog description
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Multi-packet message reassembly configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReassemblyRule {
+    /// Fields identifying a fragment group (device id, sequence, ...)
+    #[serde(default)]
+    pub key_fields: Vec<String>,
+    
+    /// Field holding the total fragment count
+    pub total_field: String,
+    
+    /// Field holding the fragment index
+    pub index_field: String,
+    
+    /// Index of the first fragment (0 or 1)
+    #[serde(default = "default_index_base")]
+    pub index_base: u64,
+    
+    /// Field whose bytes are concatenated across fragments
+    pub payload_field: String,
+    
+    /// Time to wait for missing fragments (milliseconds)
+    #[serde(default = "default_reassembly_timeout_ms")]
+    pub timeout_ms: u64,
+    
+    /// Drop the group when a fragment arrives out of sequence
+    #[serde(default)]
+    pub drop_on_gap: bool,
+    
+    /// Maximum number of incomplete groups kept at once
+    #[serde(default = "default_max_pending_groups")]
+    pub max_pending_groups: usize,
+}
+
+/// Transport envelope configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvelopeRule {
+    /// Topic template, e.g. `plant/{site}/mn/{mn}/up/{type}`
+    #[serde(default)]
+    pub topic_match: Option<String>,
+    
+    /// Topic variables copied into the parse result (all when empty)
+    #[serde(default)]
+    pub expose: Vec<String>,
+    
+    /// Only apply this rule to messages whose topic matches
+    #[serde(default = "default_true")]
+    pub require_match: bool,
+}
+
+/// Export mappings applied to parse results
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportRules {
+    /// Influx line protocol mapping
+    #[serde(default)]
+    pub influx: Option<InfluxExport>,
+    
+    /// Flat JSON mapping
+    #[serde(default)]
+    pub json: Option<JsonExport>,
+    
+    /// CSV mapping
+    #[serde(default)]
+    pub csv: Option<CsvExport>,
+}
+
+/// Influx line protocol export mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfluxExport {
+    /// Measurement name
+    pub measurement: String,
+    
+    /// Values written as tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    
+    /// Values written as fields (all non-tag values when empty)
+    #[serde(default)]
+    pub fields: Vec<String>,
+    
+    /// Value holding the timestamp (receive time when omitted)
+    #[serde(default)]
+    pub timestamp: Option<String>,
+    
+    /// chrono format of string timestamps (RFC 3339 when omitted)
+    #[serde(default)]
+    pub timestamp_format: Option<String>,
+    
+    /// Unit of numeric timestamps (s, ms, us, ns)
+    #[serde(default = "default_timestamp_unit")]
+    pub timestamp_unit: String,
+}
+
+/// Flat JSON export mapping
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JsonExport {
+    /// Values to include (all when empty)
+    #[serde(default)]
+    pub include: Vec<String>,
+    
+    /// Key renames (`val: value`)
+    #[serde(default)]
+    pub rename: HashMap<String, String>,
+    
+    /// Moves into nested paths (`pt_name: labels.name`)
+    #[serde(rename = "move", default)]
+    pub move_to: HashMap<String, String>,
+}
+
+/// CSV export mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvExport {
+    /// Columns in output order (all values when empty)
+    #[serde(default)]
+    pub columns: Vec<String>,
+    
+    /// Column delimiter
+    #[serde(default = "default_csv_delimiter")]
+    pub delimiter: char,
+    
+    /// Write a header row
+    #[serde(default = "default_true")]
+    pub header: bool,
+}
+
+impl Default for CsvExport {
+    fn default() -> Self {
+        Self {
+            columns: Vec::new(),
+            delimiter: default_csv_delimiter(),
+            header: true,
+        }
+    }
+}
+
+// Helper function for serde default
+fn default_timestamp_unit() -> String {
+    "s".to_string()
+}
+
+// Helper function for serde default
+fn default_index_base() -> u64 {
+    1
+}
+
+// Helper function for serde default
+fn default_reassembly_timeout_ms() -> u64 {
+    30_000
+}
+
+// Helper function for serde default
+fn default_max_pending_groups() -> usize {
+    256
+}
+
+// Helper function for serde default
+fn default_csv_delimiter() -> char {
+    ','
+}
+
 // Helper function for serde default
 fn default_true() -> bool {
     true
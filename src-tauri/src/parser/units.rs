@@ -0,0 +1,151 @@
+//! Measurement unit normalisation
+//!
+//! Maps the unit spellings found in factor definitions (`ug/m3`, `μg/m³`,
+//! `mg/l`, `℃`...) to one canonical unit per dimension, so values reported
+//! by different stations in different units can be compared. Conversions
+//! only happen within a dimension; `mg/L` and `mg/m³` are kept apart.
+
+/// A unit spelling and its relation to the canonical unit of its dimension
+struct UnitDefinition {
+    /// Lowercase spelling after `normalize_spelling`
+    alias: &'static str,
+    /// Canonical unit of the dimension
+    canonical: &'static str,
+    /// `canonical = value * scale + offset`
+    scale: f64,
+    offset: f64,
+}
+
+const fn unit(alias: &'static str, canonical: &'static str, scale: f64) -> UnitDefinition {
+    UnitDefinition { alias, canonical, scale, offset: 0.0 }
+}
+
+const UNITS: &[UnitDefinition] = &[
+    // Mass concentration in air
+    unit("mg/m3", "mg/m³", 1.0),
+    unit("ug/m3", "mg/m³", 0.001),
+    unit("ng/m3", "mg/m³", 0.000_001),
+    unit("g/m3", "mg/m³", 1000.0),
+    // Mass concentration in water
+    unit("mg/l", "mg/L", 1.0),
+    unit("ug/l", "mg/L", 0.001),
+    unit("ng/l", "mg/L", 0.000_001),
+    unit("g/l", "mg/L", 1000.0),
+    // Volume ratio
+    unit("ppm", "ppm", 1.0),
+    unit("ppb", "ppm", 0.001),
+    // Flow
+    unit("m3/h", "m³/h", 1.0),
+    unit("m3/s", "m³/h", 3600.0),
+    unit("m3/min", "m³/h", 60.0),
+    unit("m3/d", "m³/h", 1.0 / 24.0),
+    unit("l/s", "m³/h", 3.6),
+    unit("l/min", "m³/h", 0.06),
+    unit("l/h", "m³/h", 0.001),
+    // Volume
+    unit("m3", "m³", 1.0),
+    unit("l", "m³", 0.001),
+    // Mass
+    unit("kg", "kg", 1.0),
+    unit("g", "kg", 0.001),
+    unit("mg", "kg", 0.000_001),
+    unit("t", "kg", 1000.0),
+    // Emission rate
+    unit("kg/h", "kg/h", 1.0),
+    unit("g/h", "kg/h", 0.001),
+    unit("g/s", "kg/h", 3.6),
+    unit("t/h", "kg/h", 1000.0),
+    // Temperature
+    unit("℃", "℃", 1.0),
+    unit("°c", "℃", 1.0),
+    unit("degc", "℃", 1.0),
+    UnitDefinition { alias: "k", canonical: "℃", scale: 1.0, offset: -273.15 },
+    UnitDefinition { alias: "°f", canonical: "℃", scale: 5.0 / 9.0, offset: -32.0 * 5.0 / 9.0 },
+    UnitDefinition { alias: "℉", canonical: "℃", scale: 5.0 / 9.0, offset: -32.0 * 5.0 / 9.0 },
+    // Pressure
+    unit("kpa", "kPa", 1.0),
+    unit("pa", "kPa", 0.001),
+    unit("hpa", "kPa", 0.1),
+    unit("mpa", "kPa", 1000.0),
+    unit("bar", "kPa", 100.0),
+    unit("mbar", "kPa", 0.1),
+    // Speed
+    unit("m/s", "m/s", 1.0),
+    unit("km/h", "m/s", 1.0 / 3.6),
+    // Conductivity
+    unit("us/cm", "μS/cm", 1.0),
+    unit("ms/cm", "μS/cm", 1000.0),
+    unit("ms/m", "μS/cm", 10.0),
+    // Ratio
+    unit("%", "%", 1.0),
+];
+
+/// Lowercase a spelling and fold micro signs and superscripts
+fn normalize_spelling(unit: &str) -> String {
+    unit.trim()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            'μ' | 'µ' => 'u',
+            '³' => '3',
+            '²' => '2',
+            _ => c,
+        })
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn lookup(unit: &str) -> Option<&'static UnitDefinition> {
+    let spelling = normalize_spelling(unit);
+    UNITS.iter().find(|definition| definition.alias == spelling)
+}
+
+/// Canonical unit of the dimension a unit belongs to
+pub fn canonical_unit(unit: &str) -> Option<&'static str> {
+    lookup(unit).map(|definition| definition.canonical)
+}
+
+/// Convert a value to the canonical unit of its dimension
+pub fn normalize(value: f64, unit: &str) -> Option<(f64, &'static str)> {
+    let definition = lookup(unit)?;
+    Some((value * definition.scale + definition.offset, definition.canonical))
+}
+
+/// Convert a value between two units of the same dimension
+pub fn convert(value: f64, from: &str, to: &str) -> Option<f64> {
+    let from = lookup(from)?;
+    let to = lookup(to)?;
+    if from.canonical != to.canonical {
+        return None;
+    }
+
+    let canonical = value * from.scale + from.offset;
+    Some((canonical - to.offset) / to.scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_normalize_spellings() {
+        assert_eq!(normalize(35.0, "μg/m³"), Some((0.035, "mg/m³")));
+        assert_eq!(normalize(35.0, "ug/m3"), Some((0.035, "mg/m³")));
+        assert_eq!(normalize(2.0, " mg/l "), Some((2.0, "mg/L")));
+        assert_eq!(canonical_unit("L/s"), Some("m³/h"));
+        assert_eq!(canonical_unit("无量纲"), None);
+    }
+
+    #[test]
+    fn test_convert_within_dimension() {
+        assert!(close(convert(10.0, "L/s", "m³/h").unwrap(), 36.0));
+        assert!(close(convert(1.0, "m3/s", "L/s").unwrap(), 1000.0));
+        assert!(close(convert(300.0, "K", "℃").unwrap(), 26.85));
+        assert!(close(convert(212.0, "°F", "K").unwrap(), 373.15));
+        assert_eq!(convert(1.0, "mg/L", "mg/m³"), None);
+    }
+}
@@ -324,6 +324,51 @@ impl ValidationReport {
         }
     }
     
+    /// Build a report from the errors and warnings of a parse result
+    pub fn from_parse_result(result: &crate::parser::result::ParseResult) -> Self {
+        use crate::parser::result::ErrorSeverity;
+        
+        let mut report = Self::new();
+        let issues = result.errors.iter()
+            .map(|error| {
+                let severity = match error.severity {
+                    ErrorSeverity::Critical => IssueSeverity::Critical,
+                    ErrorSeverity::Error => IssueSeverity::Error,
+                    ErrorSeverity::Warning => IssueSeverity::Warning,
+                    ErrorSeverity::Info => IssueSeverity::Info,
+                };
+                (error.code.as_str(), severity, &error.message, error.offset, error.field.as_ref())
+            })
+            .chain(result.warnings.iter().map(|warning| {
+                (warning.code.as_str(), IssueSeverity::Warning, &warning.message, warning.offset, warning.field.as_ref())
+            }));
+        
+        for (code, severity, message, offset, field) in issues {
+            report.add_issue(ValidationIssue {
+                id: code.to_string(),
+                severity,
+                category: IssueCategory::Protocol,
+                title: code.to_string(),
+                description: message.clone(),
+                location: IssueLocation {
+                    offset,
+                    length: None,
+                    field: field.cloned(),
+                    field_path: field.cloned(),
+                    line: None,
+                    column: None,
+                },
+                expected: None,
+                actual: None,
+                rule: None,
+                context: HashMap::new(),
+                timestamp: Utc::now(),
+            });
+        }
+        
+        report
+    }
+    
     /// Add a validation issue
     pub fn add_issue(&mut self, issue: ValidationIssue) {
         // Update overall validity
@@ -0,0 +1,216 @@
+//! Protocol directory watcher
+//!
+//! Watches the repository's `protocols/` directory for rule files added,
+//! edited or removed outside the app. Bursts of file system events are
+//! debounced, each touched rule file is synced through
+//! `ParserRegistry::sync_protocol_file`, and the resulting change is handed
+//! to a callback (the app forwards it to the UI as a Tauri event).
+
+use crate::parser::repository::ProtocolMetadata;
+use crate::parser::ParserRegistry;
+use crate::types::{NetworkResult, NetworkError};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::Duration;
+
+/// Event name emitted to the frontend when a protocol file changes
+pub const PROTOCOLS_CHANGED_EVENT: &str = "protocols-changed";
+
+/// Quiet period before a burst of file events is processed
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Kind of change detected for a rule file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtocolChangeKind {
+    /// A new rule file was registered
+    Added,
+    /// An existing rule was reloaded
+    Updated,
+    /// The rule file was deleted and the protocol disabled
+    Removed,
+    /// The rule file failed to load; the last good parser stays active
+    Invalid,
+}
+
+/// A rule file change, as reported to the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolChangeEvent {
+    /// What happened
+    pub kind: ProtocolChangeKind,
+
+    /// Rule file name inside the protocols directory
+    pub filename: String,
+
+    /// Affected protocol, if the file belongs to one
+    pub protocol_id: Option<String>,
+
+    /// Protocol name
+    pub name: Option<String>,
+
+    /// Whether the protocol is enabled
+    pub enabled: bool,
+
+    /// Current revision of the protocol
+    pub revision: u32,
+
+    /// Load or validation error
+    pub error: Option<String>,
+}
+
+impl ProtocolChangeEvent {
+    /// Create an event for a rule file and its protocol metadata
+    pub fn new(kind: ProtocolChangeKind, filename: &str, metadata: Option<&ProtocolMetadata>) -> Self {
+        Self {
+            kind,
+            filename: filename.to_string(),
+            protocol_id: metadata.map(|m| m.id.clone()),
+            name: metadata.map(|m| m.name.clone()),
+            enabled: metadata.map(|m| m.enabled).unwrap_or(false),
+            revision: metadata.map(|m| m.revision).unwrap_or(0),
+            error: None,
+        }
+    }
+
+    /// Attach an error message
+    pub fn with_error(mut self, error: String) -> Self {
+        self.error = Some(error);
+        self
+    }
+}
+
+/// Watches a repository's protocols directory while it is alive
+pub struct ProtocolWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl ProtocolWatcher {
+    /// Start watching the protocols directory of the registry's repository
+    pub fn start<F>(registry: Arc<RwLock<ParserRegistry>>, on_change: F) -> NetworkResult<Self>
+    where
+        F: Fn(ProtocolChangeEvent) + Send + 'static,
+    {
+        let protocols_dir = registry.read().unwrap()
+            .repository()
+            .map(|repository| repository.get_repository_path().join("protocols"))
+            .ok_or_else(|| NetworkError::ParseError("No protocol repository available".to_string()))?;
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            match result {
+                Ok(event) => {
+                    let _ = tx.send(event);
+                }
+                Err(e) => log::warn!("Protocol watcher error: {}", e),
+            }
+        })
+        .map_err(|e| NetworkError::ParseError(format!("Failed to create protocol watcher: {}", e)))?;
+
+        watcher.watch(&protocols_dir, RecursiveMode::NonRecursive)
+            .map_err(|e| NetworkError::ParseError(format!("Failed to watch {}: {}", protocols_dir.display(), e)))?;
+
+        thread::Builder::new()
+            .name("protocol-watcher".to_string())
+            .spawn(move || Self::run(rx, registry, on_change))
+            .map_err(|e| NetworkError::ParseError(format!("Failed to start protocol watcher: {}", e)))?;
+
+        log::info!("Watching protocol files in {}", protocols_dir.display());
+
+        Ok(Self { _watcher: watcher })
+    }
+
+    /// Debounce file events and sync touched rule files until the watcher is dropped
+    fn run<F>(rx: Receiver<Event>, registry: Arc<RwLock<ParserRegistry>>, on_change: F)
+    where
+        F: Fn(ProtocolChangeEvent),
+    {
+        while let Ok(event) = rx.recv() {
+            let mut pending = BTreeSet::new();
+            collect_rule_files(&event, &mut pending);
+
+            // Editors often save in several steps (truncate, write, rename)
+            loop {
+                match rx.recv_timeout(DEBOUNCE) {
+                    Ok(event) => collect_rule_files(&event, &mut pending),
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+
+            for filename in pending {
+                let result = registry.write().unwrap().sync_protocol_file(&filename);
+                match result {
+                    Ok(Some(change)) => on_change(change),
+                    Ok(None) => {}
+                    Err(e) => log::warn!("Failed to sync protocol file '{}': {}", filename, e),
+                }
+            }
+        }
+    }
+}
+
+/// Add rule files touched by an event to the pending set
+fn collect_rule_files(event: &Event, pending: &mut BTreeSet<String>) {
+    if matches!(event.kind, EventKind::Access(_)) {
+        return;
+    }
+
+    pending.extend(event.paths.iter().filter_map(|path| rule_filename(path)));
+}
+
+/// File name of a rule file, ignoring catalogs, schemas and editor temp files
+fn rule_filename(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let is_rule = name.ends_with(".yaml") || name.ends_with(".yml");
+
+    if is_rule && !name.starts_with('.') {
+        Some(name.to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::ProtocolRepository;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rule_filename() {
+        assert_eq!(rule_filename(&PathBuf::from("/p/modbus.kkp.yaml")), Some("modbus.kkp.yaml".to_string()));
+        assert_eq!(rule_filename(&PathBuf::from("/p/custom.yml")), Some("custom.yml".to_string()));
+        assert_eq!(rule_filename(&PathBuf::from("/p/points.csv")), None);
+        assert_eq!(rule_filename(&PathBuf::from("/p/.modbus.kkp.yaml.swp")), None);
+        assert_eq!(rule_filename(&PathBuf::from("/p/.#modbus.kkp.yaml")), None);
+    }
+
+    #[test]
+    fn test_watcher_reports_added_rule() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut registry = ParserRegistry::new();
+        registry.set_repository(ProtocolRepository::new(temp_dir.path()).unwrap());
+        let registry = Arc::new(RwLock::new(registry));
+
+        let (tx, rx) = mpsc::channel();
+        let _watcher = ProtocolWatcher::start(registry.clone(), move |change| {
+            let _ = tx.send(change);
+        })
+        .unwrap();
+
+        let content = "meta:\n  name: Watched\n  version: \"1.0\"\n  author: A\nframing:\n  fixed_size: 1\nfields:\n  - name: id\n    type: uint8\n    offset: 0\n";
+        fs::write(temp_dir.path().join("protocols").join("watched.kkp.yaml"), content).unwrap();
+
+        let change = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(change.kind, ProtocolChangeKind::Added);
+        assert_eq!(change.name.as_deref(), Some("Watched"));
+        assert!(registry.read().unwrap().get_parser(change.protocol_id.as_deref().unwrap()).is_some());
+    }
+}
@@ -1,5 +1,6 @@
 use super::{Session, SessionStatistics};
 use crate::types::{SessionConfig, NetworkResult, NetworkError};
+use crate::parser::SessionParserBinding;
 use dashmap::DashMap;
 use std::sync::Arc;
 use tauri::AppHandle;
@@ -50,6 +51,10 @@ impl SessionManager {
             ));
         }
 
+        crate::network::idle_gap::configure_session(&session_id, &config)?;
+        crate::parser::get_session_parsers().lock().unwrap()
+            .set_binding(&session_id, config.parser_binding.clone().unwrap_or_default());
+
         let mut session = Session::new(session_id.clone(), config);
 
         // Set app handle if available
@@ -69,6 +74,9 @@ impl SessionManager {
         match self.sessions.get_mut(session_id) {
             Some(mut session) => {
                 eprintln!("SessionManager: Found session {}, updating config", session_id);
+                crate::network::idle_gap::configure_session(session_id, &new_config)?;
+                crate::parser::get_session_parsers().lock().unwrap()
+                    .set_binding(session_id, new_config.parser_binding.clone().unwrap_or_default());
                 session.config = new_config;
                 eprintln!("SessionManager: Session {} configuration updated successfully", session_id);
                 Ok(())
@@ -80,6 +88,35 @@ impl SessionManager {
         }
     }
 
+    /// Bind parsers to a session's received data
+    pub fn set_parser_binding(&self, session_id: &str, binding: SessionParserBinding) -> NetworkResult<()> {
+        match self.sessions.get_mut(session_id) {
+            Some(mut session) => {
+                crate::parser::get_session_parsers().lock().unwrap()
+                    .set_binding(session_id, binding.clone());
+                session.config.parser_binding = Some(binding).filter(SessionParserBinding::is_active);
+                Ok(())
+            }
+            None => Err(NetworkError::SessionNotFound(session_id.to_string())),
+        }
+    }
+
+    /// Get the parsers bound to a session
+    pub fn get_parser_binding(&self, session_id: &str) -> NetworkResult<SessionParserBinding> {
+        match self.sessions.get(session_id) {
+            Some(session) => Ok(session.config.parser_binding.clone().unwrap_or_default()),
+            None => Err(NetworkError::SessionNotFound(session_id.to_string())),
+        }
+    }
+
+    /// Get a session's configuration
+    pub fn get_session_config(&self, session_id: &str) -> NetworkResult<SessionConfig> {
+        match self.sessions.get(session_id) {
+            Some(session) => Ok(session.config.clone()),
+            None => Err(NetworkError::SessionNotFound(session_id.to_string())),
+        }
+    }
+
     /// Connect a session
     pub async fn connect_session(&self, session_id: &str) -> NetworkResult<bool> {
         eprintln!("SessionManager: Attempting to connect session {}", session_id);
@@ -315,7 +352,14 @@ impl SessionManager {
     #[allow(dead_code)]
     pub fn remove_session(&self, session_id: &str) -> NetworkResult<()> {
         match self.sessions.remove(session_id) {
-            Some(_) => Ok(()),
+            Some(_) => {
+                crate::parser::get_session_parsers().lock().unwrap().remove_session(session_id);
+                crate::network::idle_gap::remove_session(session_id);
+                crate::network::modbus_poll::stop_polling(session_id);
+                crate::simulator::remove_session(session_id);
+                crate::parser::get_alarm_engine().lock().unwrap().remove_session(session_id);
+                Ok(())
+            }
             None => Err(NetworkError::SessionNotFound(session_id.to_string())),
         }
     }
@@ -176,6 +176,9 @@ impl Session {
         // Update state
         self.state.set_status(ConnectionStatus::Disconnected);
 
+        // Drop partial frames buffered for live parsing
+        crate::parser::get_session_parsers().lock().unwrap().reset_session(&self.id);
+
         // Cleanup channels
         self.event_sender = None;
 
@@ -0,0 +1,506 @@
+//! HJ212 monitoring station simulator
+//!
+//! Generates the uplink traffic of a data acquisition unit: 2011 real-time,
+//! 2051 minute, 2061 hourly and 2031 daily data. Each factor follows a
+//! random walk inside the normal band of its `FactorDefinition` (`range`,
+//! narrowed by `alarm_low`/`alarm_high`), with optional alarm excursions and
+//! non-normal quality flags. Packets larger than the length field allows are
+//! split with `PNUM/PNO`.
+
+use crate::parser::hj212::{Hj212Cp, Hj212Factor, Hj212Flag, Hj212Header, Hj212Packet, Hj212Version, MAX_DATA_LENGTH};
+use crate::parser::{FactorDefinition, FactorTranslator};
+use crate::types::{NetworkError, NetworkResult};
+use chrono::{NaiveDateTime, Timelike};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Event name emitted to the frontend for every simulated upload
+pub const HJ212_DEVICE_EVENT: &str = "hj212-device-event";
+
+/// Samples aggregated into one minute/hourly/daily statistic
+const AGGREGATE_SAMPLES: usize = 12;
+
+/// Room left in a packet for the `PNUM/PNO` header fields
+const SPLIT_HEADER_RESERVE: usize = 24;
+
+fn default_password() -> String {
+    "123456".to_string()
+}
+
+fn default_system_code() -> String {
+    "32".to_string()
+}
+
+fn default_version() -> Hj212Version {
+    Hj212Version::V2017
+}
+
+fn default_uploads() -> Vec<Hj212UploadSchedule> {
+    vec![Hj212UploadSchedule {
+        kind: Hj212UploadKind::Realtime,
+        interval_secs: 30,
+    }]
+}
+
+fn default_noise() -> f64 {
+    0.05
+}
+
+fn default_fault_flags() -> Vec<String> {
+    vec!["M".to_string(), "D".to_string(), "C".to_string()]
+}
+
+/// Kind of data upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hj212UploadKind {
+    /// 2011 real-time data
+    Realtime,
+    /// 2051 minute data
+    Minute,
+    /// 2061 hourly data
+    Hourly,
+    /// 2031 daily data
+    Daily,
+}
+
+impl Hj212UploadKind {
+    /// Command code of the upload
+    pub fn cn(self) -> &'static str {
+        match self {
+            Hj212UploadKind::Realtime => "2011",
+            Hj212UploadKind::Minute => "2051",
+            Hj212UploadKind::Hourly => "2061",
+            Hj212UploadKind::Daily => "2031",
+        }
+    }
+
+    /// Data time reported for an upload made at `now`
+    fn data_time(self, now: NaiveDateTime) -> NaiveDateTime {
+        let truncated = match self {
+            Hj212UploadKind::Realtime => now.with_nanosecond(0),
+            Hj212UploadKind::Minute => now.with_nanosecond(0).and_then(|t| t.with_second(0)),
+            Hj212UploadKind::Hourly => now.with_nanosecond(0).and_then(|t| t.with_second(0)).and_then(|t| t.with_minute(0)),
+            Hj212UploadKind::Daily => now.date().and_hms_opt(0, 0, 0),
+        };
+        truncated.unwrap_or(now)
+    }
+}
+
+/// An upload sent on a fixed interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hj212UploadSchedule {
+    /// Data kind
+    pub kind: Hj212UploadKind,
+    /// Seconds between two uploads
+    pub interval_secs: u64,
+}
+
+/// One upload sent by the station simulator
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hj212DeviceEvent {
+    /// Client session the station sends through
+    pub session_id: String,
+    /// Data kind
+    pub kind: Hj212UploadKind,
+    /// Packet text
+    pub frame: String,
+    /// Sent packet
+    pub packet: Option<Hj212Packet>,
+    /// Encoding or send error
+    pub error: Option<String>,
+}
+
+/// Station simulator configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hj212DeviceConfig {
+    /// Protocol whose factor definitions drive the generated values
+    #[serde(default)]
+    pub protocol_id: Option<String>,
+
+    /// Device unique identifier
+    pub mn: String,
+
+    /// Access password
+    #[serde(default = "default_password")]
+    pub pw: String,
+
+    /// System code
+    #[serde(default = "default_system_code")]
+    pub st: String,
+
+    /// Protocol revision to speak
+    #[serde(default = "default_version")]
+    pub version: Hj212Version,
+
+    /// Ask the platform to acknowledge uploads
+    #[serde(default)]
+    pub answer_required: bool,
+
+    /// Factor codes to report; the defined factors of the system's medium when empty
+    #[serde(default)]
+    pub factors: Vec<String>,
+
+    /// Upload schedule
+    #[serde(default = "default_uploads")]
+    pub uploads: Vec<Hj212UploadSchedule>,
+
+    /// Random walk step as a fraction of the normal band
+    #[serde(default = "default_noise")]
+    pub noise: f64,
+
+    /// Chance that a sample jumps past `alarm_high` or `alarm_low`
+    #[serde(default)]
+    pub alarm_probability: f64,
+
+    /// Chance that a sample carries one of `fault_flags` instead of `N`
+    #[serde(default)]
+    pub fault_probability: f64,
+
+    /// Quality flags used for faulty samples
+    #[serde(default = "default_fault_flags")]
+    pub fault_flags: Vec<String>,
+
+    /// Random seed for reproducible traffic
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// One generated sample
+struct Sample {
+    value: f64,
+    flag: String,
+}
+
+/// Value model of one factor
+struct FactorModel {
+    code: String,
+    range: (f64, f64),
+    normal: (f64, f64),
+    alarm_low: Option<f64>,
+    alarm_high: Option<f64>,
+    precision: i32,
+    current: f64,
+}
+
+impl FactorModel {
+    fn new(code: &str, definition: Option<&FactorDefinition>) -> Self {
+        let alarm_low = definition.and_then(|d| d.alarm_low);
+        let alarm_high = definition.and_then(|d| d.alarm_high);
+        let range = match definition.and_then(|d| d.range) {
+            Some([low, high]) if high > low => (low, high),
+            _ => (0.0, alarm_high.map(|high| high * 2.0).filter(|high| *high > 0.0).unwrap_or(100.0)),
+        };
+
+        let normal_low = alarm_low.map(|low| low.max(range.0)).unwrap_or(range.0);
+        let normal_high = alarm_high.map(|high| high.min(range.1)).unwrap_or(range.1);
+        let normal = if normal_high > normal_low {
+            (normal_low, normal_high)
+        } else {
+            range
+        };
+
+        Self {
+            code: code.to_string(),
+            range,
+            normal,
+            alarm_low,
+            alarm_high,
+            precision: definition.and_then(|d| d.precision).unwrap_or(2) as i32,
+            current: (normal.0 + normal.1) / 2.0,
+        }
+    }
+
+    /// Draw the next sample
+    fn sample(&mut self, rng: &mut StdRng, config: &Hj212DeviceConfig) -> Sample {
+        let span = self.normal.1 - self.normal.0;
+        let baseline = (self.normal.0 + self.normal.1) / 2.0;
+
+        // Mean-reverting random walk inside the normal band
+        let step = rng.gen_range(-1.0..=1.0) * config.noise * span;
+        self.current = (self.current + step + (baseline - self.current) * 0.1).clamp(self.normal.0, self.normal.1);
+
+        let mut value = self.current;
+        if rng.gen_bool(config.alarm_probability.clamp(0.0, 1.0)) {
+            let excess = span * rng.gen_range(0.05..0.3);
+            let high = match (self.alarm_low, self.alarm_high) {
+                (Some(_), Some(_)) => rng.gen_bool(0.5),
+                (None, Some(_)) => true,
+                (Some(_), None) => false,
+                (None, None) => rng.gen_bool(0.5),
+            };
+            value = match (high, self.alarm_high, self.alarm_low) {
+                (true, Some(alarm_high), _) => alarm_high + excess,
+                (false, _, Some(alarm_low)) => alarm_low - excess,
+                (true, None, _) => self.normal.1 + excess,
+                (false, _, None) => self.normal.0 - excess,
+            };
+        }
+        value = value.clamp(self.range.0, self.range.1);
+
+        let factor = 10f64.powi(self.precision);
+        value = (value * factor).round() / factor;
+
+        let flag = if !config.fault_flags.is_empty() && rng.gen_bool(config.fault_probability.clamp(0.0, 1.0)) {
+            config.fault_flags[rng.gen_range(0..config.fault_flags.len())].clone()
+        } else {
+            "N".to_string()
+        };
+
+        Sample { value, flag }
+    }
+}
+
+/// Simulated HJ212 monitoring station
+pub struct Hj212DeviceSimulator {
+    config: Hj212DeviceConfig,
+    models: Vec<FactorModel>,
+    rng: StdRng,
+}
+
+impl Hj212DeviceSimulator {
+    /// Create a simulator, taking factor definitions from the translator
+    pub fn new(config: Hj212DeviceConfig, translator: &FactorTranslator) -> NetworkResult<Self> {
+        validate_config(&config)?;
+
+        let mut codes = config.factors.clone();
+        if codes.is_empty() {
+            codes = default_factor_codes(translator, &config.st);
+        }
+        if codes.is_empty() {
+            return Err(NetworkError::InvalidConfig(
+                "No factors to simulate: list them or pick a protocol with factor definitions".to_string(),
+            ));
+        }
+
+        let models = codes
+            .iter()
+            .map(|code| FactorModel::new(code, translator.get_factor_definition(code)))
+            .collect();
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        Ok(Self { config, models, rng })
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &Hj212DeviceConfig {
+        &self.config
+    }
+
+    /// Generate the packets of one upload
+    pub fn upload(&mut self, kind: Hj212UploadKind, now: NaiveDateTime) -> NetworkResult<Vec<Hj212Packet>> {
+        let factors: Vec<Hj212Factor> = self.models
+            .iter_mut()
+            .map(|model| sample_factor(model, kind, &mut self.rng, &self.config))
+            .collect();
+
+        let mut header = Hj212Header {
+            qn: Some(now.format("%Y%m%d%H%M%S%3f").to_string()),
+            st: Some(self.config.st.clone()),
+            cn: Some(kind.cn().to_string()),
+            pw: Some(self.config.pw.clone()),
+            mn: Some(self.config.mn.clone()),
+            flag: Some(Hj212Flag::new(self.config.version, self.config.answer_required, false)),
+            ..Default::default()
+        };
+        let data_time = Some(kind.data_time(now));
+
+        // Fill packets up to the length limit
+        let mut chunks: Vec<Hj212Cp> = Vec::new();
+        let mut current = Hj212Cp { data_time, ..Default::default() };
+        for factor in factors {
+            current.factors.push(factor);
+            let length = header.encode(&current.encode()).len();
+            if length > MAX_DATA_LENGTH - SPLIT_HEADER_RESERVE && current.factors.len() > 1 {
+                let factor = current.factors.pop().expect("factor just pushed");
+                chunks.push(std::mem::replace(&mut current, Hj212Cp { data_time, ..Default::default() }));
+                current.factors.push(factor);
+            }
+        }
+        chunks.push(current);
+
+        let count = chunks.len() as u32;
+        if count > 1 {
+            header.flag = Some(Hj212Flag::new(self.config.version, self.config.answer_required, true));
+            header.pnum = Some(count);
+        }
+
+        let mut packets = Vec::new();
+        for (index, cp) in chunks.into_iter().enumerate() {
+            let mut header = header.clone();
+            if count > 1 {
+                header.pno = Some(index as u32 + 1);
+            }
+            let packet = Hj212Packet::new(header, cp);
+            // Encode once to catch a single factor group that is too long
+            packet.encode()?;
+            packets.push(packet);
+        }
+
+        Ok(packets)
+    }
+}
+
+/// Defined factors matching the medium of a system code, or all of them
+fn default_factor_codes(translator: &FactorTranslator, st: &str) -> Vec<String> {
+    let medium = match st {
+        "21" | "24" | "26" | "32" | "33" | "34" | "52" => Some("water"),
+        "22" | "27" | "31" | "39" | "51" => Some("air"),
+        _ => None,
+    };
+
+    let definitions = translator.definitions();
+    let mut codes: Vec<String> = definitions
+        .iter()
+        .filter(|(_, definition)| medium.map(|medium| definition.category == medium).unwrap_or(true))
+        .map(|(code, _)| code.clone())
+        .collect();
+    if codes.is_empty() {
+        codes = definitions.keys().cloned().collect();
+    }
+    codes.sort();
+    codes
+}
+
+/// Sample a factor for one upload kind
+fn sample_factor(model: &mut FactorModel, kind: Hj212UploadKind, rng: &mut StdRng, config: &Hj212DeviceConfig) -> Hj212Factor {
+    let mut factor = Hj212Factor::new(&model.code);
+
+    if kind == Hj212UploadKind::Realtime {
+        let sample = model.sample(rng, config);
+        factor.rtd = Some(sample.value);
+        factor.flag = Some(sample.flag);
+        return factor;
+    }
+
+    let samples: Vec<Sample> = (0..AGGREGATE_SAMPLES).map(|_| model.sample(rng, config)).collect();
+    let values = samples.iter().map(|sample| sample.value);
+    let precision = 10f64.powi(model.precision);
+
+    factor.min = values.clone().reduce(f64::min);
+    factor.max = values.clone().reduce(f64::max);
+    factor.avg = Some((values.sum::<f64>() / samples.len() as f64 * precision).round() / precision);
+    factor.flag = samples
+        .iter()
+        .map(|sample| sample.flag.clone())
+        .find(|flag| flag != "N")
+        .or_else(|| Some("N".to_string()));
+    factor
+}
+
+/// Check a configuration before starting a simulator
+pub fn validate_config(config: &Hj212DeviceConfig) -> NetworkResult<()> {
+    if config.mn.is_empty() {
+        return Err(NetworkError::InvalidConfig("HJ212 device needs an MN".to_string()));
+    }
+    if let Some(upload) = config.uploads.iter().find(|upload| upload.interval_secs == 0) {
+        return Err(NetworkError::InvalidConfig(format!(
+            "Upload {} needs an interval of at least one second",
+            upload.kind.cn()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translator() -> FactorTranslator {
+        let mut translator = FactorTranslator::empty();
+        let definition: FactorDefinition = serde_json::from_value(serde_json::json!({
+            "name": "PM2.5",
+            "name_en": null,
+            "unit": "μg/m³",
+            "unit_en": null,
+            "category": "air",
+            "data_type": "float",
+            "precision": 1,
+            "range": [0.0, 500.0],
+            "description": "",
+            "alarm_high": 75.0,
+            "alarm_low": null,
+            "standard_value": null,
+            "quality_grade": null
+        })).unwrap();
+        translator.add_factor_definition("a34004".to_string(), definition);
+        translator
+    }
+
+    fn base_config() -> Hj212DeviceConfig {
+        serde_json::from_value(serde_json::json!({ "mn": "AQ0001", "seed": 7 })).unwrap()
+    }
+
+    fn now() -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2024-01-02 03:04:05", "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    #[test]
+    fn test_realtime_values_stay_in_normal_band() {
+        let mut simulator = Hj212DeviceSimulator::new(base_config(), &translator()).unwrap();
+
+        for _ in 0..50 {
+            let packets = simulator.upload(Hj212UploadKind::Realtime, now()).unwrap();
+            assert_eq!(packets.len(), 1);
+
+            let frame = packets[0].encode().unwrap();
+            let packet = Hj212Packet::decode(frame.as_bytes()).unwrap();
+            assert!(packet.is_valid(), "{:?}", packet.issues);
+            assert_eq!(packet.header.cn.as_deref(), Some("2011"));
+
+            let factor = packet.cp.factor("a34004").unwrap();
+            let value = factor.rtd.unwrap();
+            assert!((0.0..=75.0).contains(&value), "{}", value);
+            assert_eq!(factor.flag.as_deref(), Some("N"));
+        }
+    }
+
+    #[test]
+    fn test_statistics_excursions_and_flags() {
+        let mut config = base_config();
+        config.alarm_probability = 1.0;
+        config.fault_probability = 1.0;
+        config.fault_flags = vec!["D".to_string()];
+        let mut simulator = Hj212DeviceSimulator::new(config, &translator()).unwrap();
+
+        let packet = simulator.upload(Hj212UploadKind::Hourly, now()).unwrap().remove(0);
+        assert_eq!(packet.header.cn.as_deref(), Some("2061"));
+        assert_eq!(packet.cp.data_time.unwrap().to_string(), "2024-01-02 03:00:00");
+
+        let factor = packet.cp.factor("a34004").unwrap();
+        let (min, avg, max) = (factor.min.unwrap(), factor.avg.unwrap(), factor.max.unwrap());
+        assert!(min > 75.0 && min <= avg && avg <= max && max <= 500.0);
+        assert_eq!(factor.flag.as_deref(), Some("D"));
+    }
+
+    #[test]
+    fn test_large_uploads_are_split() {
+        let mut config = base_config();
+        config.factors = (0..800).map(|i| format!("w{:05}", i)).collect();
+        let mut simulator = Hj212DeviceSimulator::new(config, &FactorTranslator::empty()).unwrap();
+
+        let packets = simulator.upload(Hj212UploadKind::Daily, now()).unwrap();
+        assert!(packets.len() > 1);
+
+        let total: usize = packets.iter().map(|packet| packet.cp.factors.len()).sum();
+        assert_eq!(total, 800);
+        for (index, packet) in packets.iter().enumerate() {
+            let decoded = Hj212Packet::decode(packet.encode().unwrap().as_bytes()).unwrap();
+            assert!(decoded.is_valid(), "{:?}", decoded.issues);
+            assert!(decoded.header.flag.unwrap().split);
+            assert_eq!(decoded.header.pnum, Some(packets.len() as u32));
+            assert_eq!(decoded.header.pno, Some(index as u32 + 1));
+        }
+
+        // Without an MN or any factor there is nothing to simulate
+        assert!(Hj212DeviceSimulator::new(Hj212DeviceConfig { mn: String::new(), ..base_config() }, &translator()).is_err());
+        assert!(Hj212DeviceSimulator::new(base_config(), &FactorTranslator::empty()).is_err());
+    }
+}
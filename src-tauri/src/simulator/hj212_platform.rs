@@ -0,0 +1,526 @@
+//! HJ212 upper platform simulator
+//!
+//! Acts as the data platform devices report to. Every uplink received on a
+//! TCP server session is framed per client, decoded and translated, and the
+//! platform answers the way the standard requires: 9014 data acknowledge for
+//! uploads whose Flag asks for an answer, 9011 request acknowledge and 9012
+//! execution result for device requests. Scheduled downlink commands (time
+//! sync, parameter queries) are addressed to every device seen so far.
+
+use crate::parser::framing::FrameDetector;
+use crate::parser::hj212::{Hj212Cp, Hj212Flag, Hj212Header, Hj212Packet, Hj212Parser, Hj212Version};
+use crate::parser::{FactorTranslator, ParsedFactor, Parser};
+use crate::types::NetworkResult;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Event name emitted to the frontend for platform traffic
+pub const HJ212_PLATFORM_EVENT: &str = "hj212-platform-event";
+
+/// System code used for interaction replies
+const ST_SYSTEM: &str = "91";
+
+/// Commands carrying monitoring data uploads
+const UPLOAD_COMMANDS: &[&str] = &["2011", "2021", "2031", "2041", "2051", "2061", "2081", "3020"];
+
+/// Replies devices send back for platform commands
+const ANSWER_COMMANDS: &[&str] = &["9011", "9012", "9013", "9014"];
+
+/// `QnRtn` values of a 9011 request acknowledge
+pub mod qn_rtn {
+    /// Ready to execute the request
+    pub const READY: &str = "1";
+    /// Password error
+    pub const PASSWORD_ERROR: &str = "3";
+    /// CRC error
+    pub const CRC_ERROR: &str = "9";
+}
+
+/// `ExeRtn` value of a successful 9012 execution result
+pub const EXE_RTN_SUCCESS: &str = "1";
+
+fn default_true() -> bool {
+    true
+}
+
+/// Platform simulator configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hj212PlatformConfig {
+    /// Protocol whose factor codes translate uplink values
+    #[serde(default)]
+    pub protocol_id: Option<String>,
+
+    /// Expected access password; any password is accepted when unset
+    #[serde(default)]
+    pub password: Option<String>,
+
+    /// Answer uplinks automatically
+    #[serde(default = "default_true")]
+    pub auto_reply: bool,
+
+    /// Downlink commands sent periodically to every known device
+    #[serde(default)]
+    pub scheduled_commands: Vec<Hj212ScheduledCommand>,
+}
+
+impl Default for Hj212PlatformConfig {
+    fn default() -> Self {
+        Self {
+            protocol_id: None,
+            password: None,
+            auto_reply: true,
+            scheduled_commands: Vec::new(),
+        }
+    }
+}
+
+/// A downlink command sent on a fixed interval
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hj212ScheduledCommand {
+    /// Command code
+    pub cn: String,
+
+    /// Seconds between two sends
+    pub interval_secs: u64,
+
+    /// CP parameters
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+
+    /// Add `SystemTime` with the platform's current time
+    #[serde(default)]
+    pub system_time: bool,
+}
+
+impl Hj212ScheduledCommand {
+    /// Set the device clock (CN 1012)
+    pub fn time_sync(interval_secs: u64) -> Self {
+        Self {
+            cn: "1012".to_string(),
+            interval_secs,
+            params: BTreeMap::new(),
+            system_time: true,
+        }
+    }
+
+    /// Query a device parameter, e.g. CN 1011 (time) or 1061 (real-time interval)
+    pub fn query(cn: &str, interval_secs: u64) -> Self {
+        Self {
+            cn: cn.to_string(),
+            interval_secs,
+            params: BTreeMap::new(),
+            system_time: false,
+        }
+    }
+}
+
+/// A device connected to the platform
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hj212Device {
+    /// Server session client the device connects through
+    pub client_id: String,
+    /// Device unique identifier
+    pub mn: Option<String>,
+    /// System code the device reports
+    pub st: Option<String>,
+    /// Access password the device uses
+    pub pw: Option<String>,
+    /// Protocol revision the device speaks
+    pub version: Hj212Version,
+    /// Last command code received
+    pub last_cn: Option<String>,
+    /// Time of the last uplink
+    pub last_seen: DateTime<Local>,
+    /// Uplink packets received
+    pub uplinks: u64,
+    /// Uplink packets that failed CRC or length checks
+    pub invalid_uplinks: u64,
+    /// Packets sent to the device
+    pub downlinks: u64,
+}
+
+/// Direction of a packet relative to the platform
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Hj212Direction {
+    Uplink,
+    Downlink,
+}
+
+/// One packet seen or sent by the platform
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hj212PlatformEvent {
+    /// Server session of the platform
+    pub session_id: String,
+    /// Device client
+    pub client_id: String,
+    /// Packet direction
+    pub direction: Hj212Direction,
+    /// Packet text
+    pub frame: String,
+    /// Decoded packet
+    pub packet: Option<Hj212Packet>,
+    /// Translated factor values (uplinks)
+    pub factors: Vec<ParsedFactor>,
+    /// Decoding error
+    pub error: Option<String>,
+}
+
+/// Simulated HJ212 data platform bound to one server session
+pub struct Hj212Platform {
+    session_id: String,
+    config: Hj212PlatformConfig,
+    translator: FactorTranslator,
+    framers: HashMap<String, FrameDetector>,
+    devices: HashMap<String, Hj212Device>,
+}
+
+impl Hj212Platform {
+    /// Create a platform for a server session
+    pub fn new(session_id: &str, config: Hj212PlatformConfig, translator: FactorTranslator) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            config,
+            translator,
+            framers: HashMap::new(),
+            devices: HashMap::new(),
+        }
+    }
+
+    /// Get the configuration
+    pub fn config(&self) -> &Hj212PlatformConfig {
+        &self.config
+    }
+
+    /// Devices seen so far
+    pub fn devices(&self) -> Vec<Hj212Device> {
+        let mut devices: Vec<_> = self.devices.values().cloned().collect();
+        devices.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+        devices
+    }
+
+    /// Forget a disconnected client
+    pub fn remove_client(&mut self, client_id: &str) {
+        self.framers.remove(client_id);
+        self.devices.remove(client_id);
+    }
+
+    /// Process data received from a client
+    ///
+    /// Returns the events to report, replies included; the frames of
+    /// downlink events are to be sent back to the client in order.
+    pub fn receive(&mut self, client_id: &str, data: &[u8]) -> Vec<Hj212PlatformEvent> {
+        let framer = self.framers
+            .entry(client_id.to_string())
+            .or_insert_with(|| Hj212Parser::new().stream_framer().expect("HJ212 parser frames streams"));
+
+        let frames = match framer.detect_frames(data) {
+            Ok(frames) => frames,
+            Err(e) => {
+                framer.reset();
+                return vec![self.event(client_id, Hj212Direction::Uplink, data, None, Some(e.to_string()))];
+            }
+        };
+
+        let mut events = Vec::new();
+        for frame in frames.into_iter().filter(|frame| frame.complete) {
+            events.extend(self.handle_frame(client_id, &frame.data));
+        }
+        events
+    }
+
+    /// Process one complete uplink packet
+    fn handle_frame(&mut self, client_id: &str, frame: &[u8]) -> Vec<Hj212PlatformEvent> {
+        let packet = match Hj212Packet::decode(frame) {
+            Ok(packet) => packet,
+            Err(e) => return vec![self.event(client_id, Hj212Direction::Uplink, frame, None, Some(e.to_string()))],
+        };
+
+        let device = self.devices.entry(client_id.to_string()).or_insert_with(|| Hj212Device {
+            client_id: client_id.to_string(),
+            mn: None,
+            st: None,
+            pw: None,
+            version: packet.version,
+            last_cn: None,
+            last_seen: Local::now(),
+            uplinks: 0,
+            invalid_uplinks: 0,
+            downlinks: 0,
+        });
+        device.version = packet.version;
+        device.last_seen = Local::now();
+        device.uplinks += 1;
+        if !(packet.crc_valid() && packet.length_valid()) {
+            device.invalid_uplinks += 1;
+        }
+        for (target, value) in [
+            (&mut device.mn, &packet.header.mn),
+            (&mut device.st, &packet.header.st),
+            (&mut device.pw, &packet.header.pw),
+            (&mut device.last_cn, &packet.header.cn),
+        ] {
+            if value.is_some() {
+                target.clone_from(value);
+            }
+        }
+
+        let replies = if self.config.auto_reply {
+            reply_packets(&packet, self.config.password.as_deref())
+        } else {
+            Vec::new()
+        };
+
+        let factors = self.translate(&packet);
+        let mut uplink = self.event(client_id, Hj212Direction::Uplink, frame, Some(packet), None);
+        uplink.factors = factors;
+
+        let mut events = vec![uplink];
+        for reply in replies {
+            events.extend(self.downlink_event(client_id, reply));
+        }
+        events
+    }
+
+    /// Build the scheduled command for every known device
+    pub fn scheduled(&mut self, command: &Hj212ScheduledCommand) -> Vec<Hj212PlatformEvent> {
+        let client_ids: Vec<String> = self.devices.keys().cloned().collect();
+        let mut events = Vec::new();
+
+        for client_id in client_ids {
+            let device = &self.devices[&client_id];
+            let mut cp = Hj212Cp::default();
+            cp.params.clone_from(&command.params);
+            if command.system_time {
+                cp.params.insert("SystemTime".to_string(), Local::now().format("%Y%m%d%H%M%S").to_string());
+            }
+
+            let header = Hj212Header {
+                qn: Some(new_qn()),
+                st: device.st.clone(),
+                cn: Some(command.cn.clone()),
+                pw: device.pw.clone(),
+                mn: device.mn.clone(),
+                flag: Some(Hj212Flag::new(device.version, true, false)),
+                ..Default::default()
+            };
+            events.extend(self.downlink_event(&client_id, Hj212Packet::new(header, cp)));
+        }
+
+        events
+    }
+
+    /// Translate the factor values of an uplink
+    fn translate(&self, packet: &Hj212Packet) -> Vec<ParsedFactor> {
+        packet.cp.factors
+            .iter()
+            .map(|factor| {
+                let value = factor.primary_value().map(|value| value.to_string());
+                self.translator.translate_factor(&factor.code, value.as_deref(), factor.flag.as_deref())
+            })
+            .collect()
+    }
+
+    /// Encode a downlink and count it against the device
+    fn downlink_event(&mut self, client_id: &str, packet: Hj212Packet) -> Option<Hj212PlatformEvent> {
+        let frame = match packet.encode() {
+            Ok(frame) => frame,
+            Err(e) => {
+                log::warn!("Failed to encode HJ212 downlink for {}: {}", client_id, e);
+                return None;
+            }
+        };
+
+        if let Some(device) = self.devices.get_mut(client_id) {
+            device.downlinks += 1;
+        }
+        let packet = Hj212Packet::decode(frame.as_bytes()).ok();
+        Some(self.event(client_id, Hj212Direction::Downlink, frame.as_bytes(), packet, None))
+    }
+
+    fn event(
+        &self,
+        client_id: &str,
+        direction: Hj212Direction,
+        frame: &[u8],
+        packet: Option<Hj212Packet>,
+        error: Option<String>,
+    ) -> Hj212PlatformEvent {
+        Hj212PlatformEvent {
+            session_id: self.session_id.clone(),
+            client_id: client_id.to_string(),
+            direction,
+            frame: String::from_utf8_lossy(frame).to_string(),
+            packet,
+            factors: Vec::new(),
+            error,
+        }
+    }
+}
+
+/// Replies the platform owes for an uplink
+pub fn reply_packets(packet: &Hj212Packet, password: Option<&str>) -> Vec<Hj212Packet> {
+    let Some(cn) = packet.header.cn.as_deref() else {
+        return Vec::new();
+    };
+    if ANSWER_COMMANDS.contains(&cn) {
+        return Vec::new();
+    }
+
+    let reply = |reply_cn: &str, params: &[(&str, &str)]| {
+        let header = Hj212Header {
+            qn: packet.header.qn.clone(),
+            st: Some(ST_SYSTEM.to_string()),
+            cn: Some(reply_cn.to_string()),
+            pw: packet.header.pw.clone(),
+            mn: packet.header.mn.clone(),
+            flag: Some(Hj212Flag::new(packet.version, false, false)),
+            ..Default::default()
+        };
+        let mut cp = Hj212Cp::default();
+        for (key, value) in params {
+            cp.params.insert(key.to_string(), value.to_string());
+        }
+        Hj212Packet::new(header, cp)
+    };
+
+    if UPLOAD_COMMANDS.contains(&cn) {
+        // Corrupted uploads are left unanswered so the device resends them
+        if packet.answer_required() && packet.crc_valid() {
+            return vec![reply("9014", &[])];
+        }
+        return Vec::new();
+    }
+
+    if !packet.answer_required() {
+        return Vec::new();
+    }
+
+    let rejected = if !packet.crc_valid() {
+        Some(qn_rtn::CRC_ERROR)
+    } else if password.is_some() && packet.header.pw.as_deref() != password {
+        Some(qn_rtn::PASSWORD_ERROR)
+    } else {
+        None
+    };
+    if let Some(code) = rejected {
+        return vec![reply("9011", &[("QnRtn", code)])];
+    }
+
+    let mut replies = vec![reply("9011", &[("QnRtn", qn_rtn::READY)])];
+    if cn == "1013" {
+        // Time calibration request: answer with the platform clock
+        let mut set_time = reply("1012", &[("SystemTime", &Local::now().format("%Y%m%d%H%M%S").to_string())]);
+        set_time.header.st.clone_from(&packet.header.st);
+        replies.push(set_time);
+    }
+    replies.push(reply("9012", &[("ExeRtn", EXE_RTN_SUCCESS)]));
+    replies
+}
+
+/// Request number for a new command
+pub fn new_qn() -> String {
+    Local::now().format("%Y%m%d%H%M%S%3f").to_string()
+}
+
+/// Check a scheduled command before starting a platform
+pub fn validate_config(config: &Hj212PlatformConfig) -> NetworkResult<()> {
+    for command in &config.scheduled_commands {
+        if command.interval_secs == 0 {
+            return Err(crate::types::NetworkError::InvalidConfig(format!(
+                "Scheduled command {} needs an interval of at least one second",
+                command.cn
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::hj212::encode_frame;
+
+    fn uplink(cn: &str, flag: u8, cp: &str) -> String {
+        encode_frame(&format!("QN=20240102030405123;ST=22;CN={};PW=123456;MN=AQ0001;Flag={};CP=&&{}&&", cn, flag, cp)).unwrap()
+    }
+
+    fn reply_cns(events: &[Hj212PlatformEvent]) -> Vec<String> {
+        events.iter()
+            .filter(|event| event.direction == Hj212Direction::Downlink)
+            .map(|event| event.packet.as_ref().unwrap().header.cn.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_data_acknowledge_follows_flag() {
+        let mut platform = Hj212Platform::new("s1", Hj212PlatformConfig::default(), FactorTranslator::new());
+        let data = uplink("2011", 5, "DataTime=20240102030400;a34004-Rtd=35.2,a34004-Flag=N");
+
+        // Split across two reads
+        let (first, second) = data.as_bytes().split_at(20);
+        assert!(platform.receive("c1", first).is_empty());
+        let events = platform.receive("c1", second);
+
+        assert_eq!(reply_cns(&events), vec!["9014"]);
+        assert_eq!(events[0].factors.len(), 1);
+        let ack = events[1].packet.as_ref().unwrap();
+        assert_eq!(ack.header.qn.as_deref(), Some("20240102030405123"));
+        assert_eq!(ack.header.st.as_deref(), Some("91"));
+        assert!(!ack.answer_required());
+
+        // No answer requested
+        let events = platform.receive("c1", uplink("2011", 4, "a34004-Rtd=1").as_bytes());
+        assert!(reply_cns(&events).is_empty());
+
+        let devices = platform.devices();
+        assert_eq!(devices[0].mn.as_deref(), Some("AQ0001"));
+        assert_eq!((devices[0].uplinks, devices[0].downlinks), (2, 1));
+    }
+
+    #[test]
+    fn test_request_acknowledge_and_execution_result() {
+        let config = Hj212PlatformConfig {
+            password: Some("123456".to_string()),
+            ..Default::default()
+        };
+        let mut platform = Hj212Platform::new("s1", config, FactorTranslator::new());
+
+        let events = platform.receive("c1", uplink("1013", 5, "").as_bytes());
+        assert_eq!(reply_cns(&events), vec!["9011", "1012", "9012"]);
+        assert_eq!(events[1].packet.as_ref().unwrap().cp.params.get("QnRtn").map(String::as_str), Some("1"));
+
+        // Replies to our own commands are not answered
+        assert!(reply_cns(&platform.receive("c1", uplink("9011", 4, "QnRtn=1").as_bytes())).is_empty());
+
+        let bad_password = encode_frame("QN=1;ST=22;CN=1013;PW=000000;MN=AQ0001;Flag=5;CP=&&&&").unwrap();
+        let events = platform.receive("c1", bad_password.as_bytes());
+        assert_eq!(reply_cns(&events), vec!["9011"]);
+        assert_eq!(events[1].packet.as_ref().unwrap().cp.params.get("QnRtn").map(String::as_str), Some("3"));
+    }
+
+    #[test]
+    fn test_scheduled_commands_reach_known_devices() {
+        let mut platform = Hj212Platform::new("s1", Hj212PlatformConfig::default(), FactorTranslator::new());
+        assert!(platform.scheduled(&Hj212ScheduledCommand::time_sync(60)).is_empty());
+
+        platform.receive("c1", uplink("2011", 4, "a34004-Rtd=1").as_bytes());
+        let events = platform.scheduled(&Hj212ScheduledCommand::time_sync(60));
+
+        assert_eq!(events.len(), 1);
+        let packet = events[0].packet.as_ref().unwrap();
+        assert!(packet.crc_valid());
+        assert!(packet.answer_required());
+        assert_eq!(packet.header.mn.as_deref(), Some("AQ0001"));
+        assert_eq!(packet.header.cn.as_deref(), Some("1012"));
+        assert!(packet.cp.params.contains_key("SystemTime"));
+
+        platform.remove_client("c1");
+        assert!(platform.devices().is_empty());
+    }
+}
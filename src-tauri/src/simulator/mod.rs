@@ -0,0 +1,293 @@
+//! Protocol simulators attached to live sessions
+//!
+//! Simulators sit on top of ordinary sessions: received data is handed to
+//! them by the network layer through `handle_inbound`, and whatever they
+//! answer is sent back through the `SessionManager` like any user message.
+
+pub mod hj212_device;
+pub mod hj212_platform;
+
+pub use hj212_device::{
+    Hj212DeviceConfig, Hj212DeviceEvent, Hj212DeviceSimulator, Hj212UploadKind, Hj212UploadSchedule,
+    HJ212_DEVICE_EVENT,
+};
+pub use hj212_platform::{
+    Hj212Device, Hj212Direction, Hj212Platform, Hj212PlatformConfig, Hj212PlatformEvent,
+    Hj212ScheduledCommand, HJ212_PLATFORM_EVENT,
+};
+
+use crate::parser::{get_alarm_engine, get_parser_registry, FactorTranslator};
+use crate::session::SessionManager;
+use crate::types::{NetworkError, NetworkResult};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::task::JoinHandle;
+
+/// A running platform simulator and its scheduler task
+struct RunningPlatform {
+    platform: Arc<Mutex<Hj212Platform>>,
+    scheduler: Option<JoinHandle<()>>,
+}
+
+/// Running platform simulators by session
+static HJ212_PLATFORMS: OnceLock<Mutex<HashMap<String, RunningPlatform>>> = OnceLock::new();
+
+fn hj212_platforms() -> &'static Mutex<HashMap<String, RunningPlatform>> {
+    HJ212_PLATFORMS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Running station simulators by session
+static HJ212_DEVICES: OnceLock<Mutex<HashMap<String, JoinHandle<()>>>> = OnceLock::new();
+
+fn hj212_devices() -> &'static Mutex<HashMap<String, JoinHandle<()>>> {
+    HJ212_DEVICES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Load the factor translator of a protocol, or an empty one
+fn load_translator(protocol_id: Option<&str>) -> NetworkResult<FactorTranslator> {
+    let Some(protocol_id) = protocol_id else {
+        return Ok(FactorTranslator::new());
+    };
+
+    let registry = get_parser_registry();
+    let mut registry = registry.write().unwrap();
+    let repository = registry.repository_mut()
+        .ok_or_else(|| NetworkError::InvalidConfig("Protocol repository not initialized".to_string()))?;
+    FactorTranslator::from_rule(&repository.load_protocol_rule(protocol_id)?)
+}
+
+/// Start an HJ212 platform simulator on a TCP server session
+pub fn start_hj212_platform(
+    app_handle: AppHandle,
+    session_manager: &SessionManager,
+    session_id: &str,
+    config: Hj212PlatformConfig,
+) -> NetworkResult<()> {
+    let session_config = session_manager.get_session_config(session_id)?;
+    if !session_config.protocol.eq_ignore_ascii_case("tcp") || session_config.connection_type != "server" {
+        return Err(NetworkError::InvalidConfig(format!(
+            "HJ212 platform needs a TCP server session, {} is {} {}",
+            session_id, session_config.protocol, session_config.connection_type
+        )));
+    }
+    hj212_platform::validate_config(&config)?;
+
+    let translator = load_translator(config.protocol_id.as_deref())?;
+
+    let scheduled_commands = config.scheduled_commands.clone();
+    let platform = Arc::new(Mutex::new(Hj212Platform::new(session_id, config, translator)));
+
+    let scheduler = (!scheduled_commands.is_empty()).then(|| {
+        let platform = platform.clone();
+        let session_manager = session_manager.clone();
+        let session_id = session_id.to_string();
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(1));
+            let mut elapsed_secs: u64 = 0;
+            loop {
+                ticker.tick().await;
+                elapsed_secs += 1;
+
+                for command in &scheduled_commands {
+                    if elapsed_secs % command.interval_secs != 0 {
+                        continue;
+                    }
+                    let events = platform.lock().unwrap().scheduled(command);
+                    deliver_platform_events(&app_handle, &session_manager, events).await;
+                }
+            }
+        })
+    });
+
+    let previous = hj212_platforms().lock().unwrap().insert(session_id.to_string(), RunningPlatform {
+        platform,
+        scheduler,
+    });
+    if let Some(scheduler) = previous.and_then(|previous| previous.scheduler) {
+        scheduler.abort();
+    }
+
+    Ok(())
+}
+
+/// Stop the HJ212 platform simulator of a session
+pub fn stop_hj212_platform(session_id: &str) -> bool {
+    match hj212_platforms().lock().unwrap().remove(session_id) {
+        Some(running) => {
+            if let Some(scheduler) = running.scheduler {
+                scheduler.abort();
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+/// Devices connected to a session's HJ212 platform simulator
+pub fn hj212_platform_devices(session_id: &str) -> NetworkResult<Vec<Hj212Device>> {
+    let platforms = hj212_platforms().lock().unwrap();
+    let running = platforms.get(session_id)
+        .ok_or_else(|| NetworkError::InvalidConfig(format!("No HJ212 platform running on session {}", session_id)))?;
+    let devices = running.platform.lock().unwrap().devices();
+    Ok(devices)
+}
+
+/// Start an HJ212 station simulator on a TCP client session
+pub fn start_hj212_device(
+    app_handle: AppHandle,
+    session_manager: &SessionManager,
+    session_id: &str,
+    config: Hj212DeviceConfig,
+) -> NetworkResult<()> {
+    let session_config = session_manager.get_session_config(session_id)?;
+    if !session_config.protocol.eq_ignore_ascii_case("tcp") || session_config.connection_type != "client" {
+        return Err(NetworkError::InvalidConfig(format!(
+            "HJ212 device needs a TCP client session, {} is {} {}",
+            session_id, session_config.protocol, session_config.connection_type
+        )));
+    }
+
+    let translator = load_translator(config.protocol_id.as_deref())?;
+    let uploads = config.uploads.clone();
+    let mut simulator = Hj212DeviceSimulator::new(config, &translator)?;
+
+    let session_manager = session_manager.clone();
+    let task_session_id = session_id.to_string();
+    let task = tokio::spawn(async move {
+        let session_id = task_session_id;
+        let mut ticker = tokio::time::interval(Duration::from_secs(1));
+        let mut elapsed_secs: u64 = 0;
+        loop {
+            ticker.tick().await;
+            elapsed_secs += 1;
+
+            for upload in &uploads {
+                if elapsed_secs % upload.interval_secs != 0 {
+                    continue;
+                }
+
+                let packets = match simulator.upload(upload.kind, chrono::Local::now().naive_local()) {
+                    Ok(packets) => packets,
+                    Err(e) => {
+                        emit_device_event(&app_handle, Hj212DeviceEvent {
+                            session_id: session_id.clone(),
+                            kind: upload.kind,
+                            frame: String::new(),
+                            packet: None,
+                            error: Some(e.to_string()),
+                        });
+                        continue;
+                    }
+                };
+
+                for packet in packets {
+                    let mut event = Hj212DeviceEvent {
+                        session_id: session_id.clone(),
+                        kind: upload.kind,
+                        frame: String::new(),
+                        packet: None,
+                        error: None,
+                    };
+                    match packet.encode() {
+                        Ok(frame) => {
+                            if let Err(e) = session_manager.send_message(&session_id, frame.as_bytes()).await {
+                                event.error = Some(e.to_string());
+                            }
+                            event.frame = frame;
+                            event.packet = Some(packet);
+                        }
+                        Err(e) => event.error = Some(e.to_string()),
+                    }
+                    emit_device_event(&app_handle, event);
+                }
+            }
+        }
+    });
+
+    if let Some(previous) = hj212_devices().lock().unwrap().insert(session_id.to_string(), task) {
+        previous.abort();
+    }
+
+    Ok(())
+}
+
+/// Stop the HJ212 station simulator of a session
+pub fn stop_hj212_device(session_id: &str) -> bool {
+    match hj212_devices().lock().unwrap().remove(session_id) {
+        Some(task) => {
+            task.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+fn emit_device_event(app_handle: &AppHandle, event: Hj212DeviceEvent) {
+    if let Err(e) = app_handle.emit(HJ212_DEVICE_EVENT, &event) {
+        eprintln!("Failed to emit HJ212 device event for session {}: {}", event.session_id, e);
+    }
+}
+
+/// Hand received data to the simulators attached to a session
+pub fn handle_inbound(app_handle: &AppHandle, session_id: &str, client_id: Option<&str>, data: &[u8]) {
+    let Some(client_id) = client_id else {
+        return;
+    };
+    let Some(platform) = hj212_platforms().lock().unwrap().get(session_id).map(|running| running.platform.clone()) else {
+        return;
+    };
+
+    let events = platform.lock().unwrap().receive(client_id, data);
+    if events.is_empty() {
+        return;
+    }
+
+    let alarms = {
+        let engine = get_alarm_engine();
+        let mut engine = engine.lock().unwrap();
+        let now = chrono::Utc::now();
+        events.iter()
+            .filter(|event| event.direction == Hj212Direction::Uplink)
+            .filter_map(|event| event.packet.as_ref())
+            .flat_map(|packet| engine.observe_packet(session_id, packet, now))
+            .collect::<Vec<_>>()
+    };
+    crate::network::notify_factor_alarms(app_handle, alarms);
+
+    let app_handle = app_handle.clone();
+    let session_manager = app_handle.state::<SessionManager>().inner().clone();
+    tokio::spawn(async move {
+        deliver_platform_events(&app_handle, &session_manager, events).await;
+    });
+}
+
+/// Tell the simulators of a session that a client went away
+pub fn client_disconnected(session_id: &str, client_id: &str) {
+    if let Some(running) = hj212_platforms().lock().unwrap().get(session_id) {
+        running.platform.lock().unwrap().remove_client(client_id);
+    }
+}
+
+/// Forget every simulator of a removed session
+pub fn remove_session(session_id: &str) {
+    stop_hj212_platform(session_id);
+    stop_hj212_device(session_id);
+}
+
+/// Send downlink frames in order and report every event to the frontend
+async fn deliver_platform_events(app_handle: &AppHandle, session_manager: &SessionManager, events: Vec<Hj212PlatformEvent>) {
+    for mut event in events {
+        if event.direction == Hj212Direction::Downlink {
+            if let Err(e) = session_manager.send_to_client(&event.session_id, &event.client_id, event.frame.as_bytes()).await {
+                event.error = Some(e.to_string());
+            }
+        }
+
+        if let Err(e) = app_handle.emit(HJ212_PLATFORM_EVENT, &event) {
+            eprintln!("Failed to emit HJ212 platform event for session {}: {}", event.session_id, e);
+        }
+    }
+}
@@ -31,6 +31,32 @@ pub struct SessionConfig {
     // SSE specific
     pub sse_event_types: Option<Vec<String>>,
     pub sse_retry_interval: Option<u64>,
+
+    // Modbus specific
+    #[serde(default)]
+    pub modbus_unit_id: Option<u8>,
+    #[serde(default)]
+    pub modbus_rtu_slave: Option<crate::network::modbus_rtu_slave::ModbusRtuSlaveSettings>,
+
+    // IEC 60870-5-104 specific
+    #[serde(default)]
+    pub iec104: Option<crate::network::iec104::Iec104Settings>,
+
+    // DNP3 specific
+    #[serde(default)]
+    pub dnp3: Option<crate::network::dnp3::Dnp3Settings>,
+
+    // Raw serial port specific
+    #[serde(default)]
+    pub serial: Option<crate::network::serial::SerialSettings>,
+
+    // Idle-gap framing of received data
+    #[serde(default)]
+    pub idle_gap: Option<crate::network::idle_gap::IdleGapSettings>,
+
+    // Parsers applied to received data
+    #[serde(default)]
+    pub parser_binding: Option<crate::parser::SessionParserBinding>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +66,9 @@ export interface ParsedFactor {
   alarm_status?: AlarmStatus;
   standard_value?: number;
   quality_grade?: string;
+  standard_code?: string; // HJ 212-2017 code a 2005 code was mapped to
+  normalized_value?: number;
+  normalized_unit?: string;
 }
 
 export type AlarmStatus = 'Normal' | 'HighAlarm' | 'LowAlarm' | 'OutOfRange';
@@ -87,6 +90,43 @@ export interface HJ212ParseResult {
   parse_result: any; // ParseResult from backend
   parsed_factors: ParsedFactor[];
   factor_summary?: FactorSummary;
+  packet?: HJ212Packet | null; // Decoded by the built-in HJ212 engine
+}
+
+export interface HJ212Factor {
+  code: string;
+  rtd?: number | null;
+  min?: number | null;
+  avg?: number | null;
+  max?: number | null;
+  cou?: number | null;
+  flag?: string | null;
+  extra: Record<string, string>;
+}
+
+export interface HJ212Packet {
+  version: '2005' | '2017';
+  declared_length: number;
+  data_length: number;
+  header: {
+    qn?: string | null;
+    st?: string | null;
+    cn?: string | null;
+    pw?: string | null;
+    mn?: string | null;
+    flag?: { raw: number; answer_required: boolean; split: boolean; version_bits: number } | null;
+    pnum?: number | null;
+    pno?: number | null;
+    extra: Record<string, string>;
+  };
+  cp: {
+    data_time?: string | null;
+    params: Record<string, string>;
+    factors: HJ212Factor[];
+  };
+  crc?: number | null;
+  calculated_crc: number;
+  issues: string[];
 }
 
 export class ProtocolRepositoryService {
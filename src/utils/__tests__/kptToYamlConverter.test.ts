@@ -0,0 +1,47 @@
+/**
+ * Tests for the KPT to YAML converter
+ */
+
+import { describe, it, expect } from 'vitest';
+import { readFileSync } from 'fs';
+import { resolve } from 'path';
+import { parseKptContent, convertKptToYaml } from '../kptToYamlConverter';
+
+const mqttJson = readFileSync(resolve(__dirname, 'fixtures/mqtt-json.kpt'), 'utf-8');
+
+describe('KPT to YAML converter', () => {
+  it('should parse export blocks nested in the protocol block', () => {
+    const parsed = parseKptContent(mqttJson);
+    expect(parsed.exports).toEqual([
+      {
+        format: 'influx',
+        measurement: 'iot_measurements',
+        tags: ['site', 'device_id', 'device_type', 'sensor_type'],
+        fields: ['value', 'converted_value', 'quality'],
+        timestamp: 'timestamp',
+      },
+      {
+        format: 'json',
+        rename: { device_id: 'deviceId', sensor_id: 'sensorId' },
+        move: { device_name: 'metadata.deviceName', location: 'metadata.location' },
+      },
+    ]);
+  });
+
+  it('should convert export blocks to the exports section', () => {
+    const yaml = convertKptToYaml(mqttJson);
+    expect(yaml).toContain(`exports:
+  influx:
+    measurement: "iot_measurements"
+    tags: ["site","device_id","device_type","sensor_type"]
+    fields: ["value","converted_value","quality"]
+    timestamp: "timestamp"
+  json:
+    rename:
+      "device_id": "deviceId"
+      "sensor_id": "sensorId"
+    move:
+      "device_name": "metadata.deviceName"
+      "location": "metadata.location"`);
+  });
+});
@@ -80,6 +80,17 @@ export interface KptCodec {
   params?: Record<string, any>;
 }
 
+export interface KptExport {
+  format: string;
+  measurement?: string;
+  tags?: string[];
+  fields?: string[];
+  timestamp?: string;
+  include?: string[];
+  rename?: Record<string, string>;
+  move?: Record<string, string>;
+}
+
 export interface KptTests {
   samples: Array<{
     name: string;
@@ -99,6 +110,7 @@ export function parseKptContent(content: string): {
   catalogs?: KptCatalog[];
   enums?: KptEnum[];
   codecs?: KptCodec[];
+  exports?: KptExport[];
   tests?: KptTests;
 } {
   const result: any = {};
@@ -146,9 +158,113 @@ export function parseKptContent(content: string): {
     }
   }
   
+  // Export blocks usually sit inside the protocol block
+  const exports = parseExportBlocks(lines);
+  if (exports.length > 0) {
+    result.exports = exports;
+  }
+  
   return result;
 }
 
+/**
+ * Find `export <format> { ... }` blocks at any nesting level
+ */
+function parseExportBlocks(lines: string[]): KptExport[] {
+  const exports: KptExport[] = [];
+  let current: { format: string; content: string[] } | null = null;
+  
+  for (const rawLine of lines) {
+    const line = rawLine.trim();
+    if (!line || line.startsWith('#')) continue;
+    
+    if (current === null) {
+      const match = line.match(/^export\s+(\w+)\s*\{/);
+      if (match) {
+        current = { format: match[1], content: [] };
+      }
+    } else if (line.startsWith('}')) {
+      exports.push(parseExportBlock(current.format, current.content));
+      current = null;
+    } else {
+      current.content.push(line);
+    }
+  }
+  
+  return exports;
+}
+
+/**
+ * Parse export block
+ */
+function parseExportBlock(format: string, content: string[]): KptExport {
+  const exportDef: KptExport = { format };
+  
+  for (const line of content) {
+    const key = line.split(/\s+/)[0];
+    const rest = line.slice(key.length).trim();
+    
+    switch (key) {
+      case 'measurement':
+        exportDef.measurement = unquote(rest);
+        break;
+      case 'tags':
+        exportDef.tags = parseKptList(rest).map(stripValuePath);
+        break;
+      case 'fields':
+        exportDef.fields = parseKptList(rest).map(stripValuePath);
+        break;
+      case 'include':
+        exportDef.include = parseKptList(rest).map(stripValuePath);
+        break;
+      case 'timestamp': {
+        // Parse: timestamp from "timestamp"
+        const match = rest.match(/^from\s+(.+)$/);
+        if (match) exportDef.timestamp = stripValuePath(unquote(match[1]));
+        break;
+      }
+      case 'rename':
+      case 'move': {
+        // Parse: rename $.device_id -> $.deviceId
+        const match = rest.match(/^(\S+)\s*->\s*(\S+)$/);
+        if (match) {
+          const mapping = (key === 'rename' ? exportDef.rename : exportDef.move) || {};
+          mapping[stripValuePath(unquote(match[1]))] = stripValuePath(unquote(match[2]));
+          if (key === 'rename') exportDef.rename = mapping;
+          else exportDef.move = mapping;
+        }
+        break;
+      }
+    }
+  }
+  
+  return exportDef;
+}
+
+/**
+ * Parse a list such as `["site", "device_id"]` or `site device_id`
+ */
+function parseKptList(value: string): string[] {
+  try {
+    const parsed = JSON.parse(value);
+    if (Array.isArray(parsed)) return parsed.map(String);
+  } catch {
+    // Not JSON, fall back to separated words
+  }
+  return value.replace(/^\[|\]$/g, '').split(/[\s,]+/).filter(Boolean).map(unquote);
+}
+
+function unquote(value: string): string {
+  return value.trim().replace(/^"(.*)"$/, '$1');
+}
+
+/**
+ * Strip the `$.` prefix of a value path
+ */
+function stripValuePath(path: string): string {
+  return path.replace(/^\$\./, '');
+}
+
 /**
  * Process a KPT block and add it to the result
  */
@@ -478,6 +594,17 @@ export function convertKptToYaml(kptContent: string): string {
     }
   }
   
+  // Convert export blocks to the rule's export mappings
+  if (parsed.exports) {
+    yamlObj.exports = {};
+    for (const exportDef of parsed.exports) {
+      const { format, ...mapping } = exportDef;
+      if (format === 'influx' || format === 'json') {
+        yamlObj.exports[format] = mapping;
+      }
+    }
+  }
+  
   // Convert to YAML string
   return `# Auto-generated YAML from KPT 1.1 format
 meta:
@@ -508,5 +635,30 @@ conditions: []
 functions: {}
 ${yamlObj.factor_codes ? `
 factor_codes: ${JSON.stringify(yamlObj.factor_codes, null, 2).split('\n').map((line: string, index: number) => index === 0 ? line : `  ${line}`).join('\n')}` : ''}
+${yamlObj.exports && Object.keys(yamlObj.exports).length > 0 ? `
+exports:
+${formatExportsYaml(yamlObj.exports)}` : ''}
 `;
 }
+
+/**
+ * Format export mappings as an indented YAML block
+ */
+function formatExportsYaml(exports: Record<string, Omit<KptExport, 'format'>>): string {
+  const lines: string[] = [];
+  for (const [format, mapping] of Object.entries(exports)) {
+    lines.push(`  ${format}:`);
+    for (const [key, value] of Object.entries(mapping)) {
+      if (value === undefined) continue;
+      if (typeof value === 'string' || Array.isArray(value)) {
+        lines.push(`    ${key}: ${JSON.stringify(value)}`);
+      } else {
+        lines.push(`    ${key}:`);
+        for (const [from, to] of Object.entries(value)) {
+          lines.push(`      ${JSON.stringify(from)}: ${JSON.stringify(to)}`);
+        }
+      }
+    }
+  }
+  return lines.join('\n');
+}